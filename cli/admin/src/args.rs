@@ -34,6 +34,32 @@ pub enum SubCommand {
         repos_path: String,
     },
 
+    /// usage: 1. svnadmin freeze REPOS_PATH -- PROGRAM [ARG...]
+    ///        2. svnadmin freeze -F FILE -- PROGRAM [ARG...]
+    ///
+    /// 1. Run PROGRAM passing ARGS while holding a write-lock on REPOS_PATH.
+    ///    Allows read-only operations during the freeze.
+    ///
+    /// 2. Like 1 except all repositories listed in FILE are locked. The file
+    ///    format is repository paths separated by newlines.  Repositories are
+    ///    locked in the same order as they are listed in the file.
+    Freeze {
+        #[arg(value_name = "REPOS_PATH", required_unless_present = "file")]
+        repos_path: Option<String>,
+
+        /// read repository paths from file ARG
+        #[arg(
+            short = 'F',
+            long = "file",
+            value_name = "ARG",
+            conflicts_with = "repos_path"
+        )]
+        file: Option<String>,
+
+        #[arg(value_name = "PROGRAM", last = true, required = true)]
+        command: Vec<String>,
+    },
+
     /// Verify the data stored in the repository.
     Verify {
         /// REPOS_PATH
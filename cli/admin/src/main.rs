@@ -16,6 +16,18 @@ fn main() {
             // Here you would call the function to verify the repository
             // For example: verify_repository(&repos_path);
         }
+        SubCommand::Freeze {
+            repos_path,
+            file,
+            command,
+        } => match sub::freeze(repos_path.as_deref(), file.as_deref(), &command) {
+            // Make any non-zero status visible to the user.
+            Ok(code) => std::process::exit(code),
+            Err(err) => {
+                eprintln!("svnadmin: {err:#}");
+                std::process::exit(1);
+            }
+        },
         _ => todo!("Other commands are not implemented yet"),
     }
 }
@@ -0,0 +1,41 @@
+//! Run a program while the repositories are frozen.
+//!
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Context;
+use svn_repos::Repos;
+
+/// Freeze the repository at @a repos_path, or all the repositories listed
+/// in @a file, and run @a command while holding the locks.
+///
+/// Return the exit status of @a command.
+///
+/// `subcommand_freeze`
+pub fn freeze(
+    repos_path: Option<&str>,
+    file: Option<&str>,
+    command: &[String],
+) -> anyhow::Result<i32> {
+    let paths: Vec<PathBuf> = match file {
+        Some(file) => fs_err::read_to_string(file)?
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+        None => repos_path.into_iter().map(PathBuf::from).collect(),
+    };
+    let (program, args) = command.split_first().context("no program to run")?;
+
+    // `freeze_body`
+    let mut status = None;
+    Repos::freeze(&paths, &mut || {
+        status = Some(Command::new(program).args(args).status()?);
+        Ok(())
+    })?;
+    let status = status.expect("the freeze function has been called");
+
+    // A program killed by a signal has no exit code.
+    Ok(status.code().unwrap_or(1))
+}
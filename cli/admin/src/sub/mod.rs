@@ -1,4 +1,6 @@
 //! subcommands for the `admin` CLI tool
 
 mod create;
+mod freeze;
+pub use freeze::freeze;
 mod verify;
@@ -0,0 +1,28 @@
+use clap::Parser;
+use clap::Subcommand;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about, max_term_width = 80)]
+pub struct AppArgs {
+    #[command(subcommand)]
+    command: SubCommand,
+}
+
+impl AppArgs {
+    pub fn run(&self) -> anyhow::Result<()> {
+        match &self.command {
+            SubCommand::Stats { repos_path } => crate::sub::stats::run(repos_path),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SubCommand {
+    /// usage: svnfsfs stats REPOS_PATH
+    ///
+    /// Write object size statistics to console.
+    Stats {
+        #[arg(value_name = "REPOS_PATH")]
+        repos_path: String,
+    },
+}
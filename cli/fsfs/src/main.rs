@@ -0,0 +1,19 @@
+//! Subversion FSFS repository manipulation tool main file.
+
+mod args;
+use args::AppArgs;
+
+mod sub;
+
+use clap::Parser;
+
+fn main() -> anyhow::Result<()> {
+    let args = AppArgs::parse();
+
+    if let Err(e) = args.run() {
+        eprintln!("svnfsfs: {e:#}");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
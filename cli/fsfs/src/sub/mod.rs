@@ -0,0 +1,3 @@
+//! sub commands for the `fsfs` CLI tool
+
+pub mod stats;
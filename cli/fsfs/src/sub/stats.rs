@@ -0,0 +1,275 @@
+//! Write object size statistics to console.
+//!
+//! `subversion/svnfsfs/stats-cmd.c`
+
+use std::io::Write;
+use std::path::Path;
+
+use svn_fs::FsStats;
+use svn_fs::Histogram;
+use svn_fs::RepStats;
+use svn_fs::SvnFs;
+use svn_types::RevisionNumber;
+
+/// Return @a value with thousands separators.
+///
+/// `svn__ui64toa_sep`
+fn sep(value: u64) -> String {
+    let digits = value.to_string();
+    let mut result = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(digit);
+    }
+    result
+}
+
+/// Return @a part as a percentage of @a total.
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// Show a dot every 1000 revisions and the revision number every 10000.
+///
+/// `print_progress`
+fn print_progress(revision: RevisionNumber) {
+    if revision % 1000 == 0 {
+        if revision % 10000 == 0 {
+            print!("\n{revision:8} ");
+        } else {
+            print!(".");
+        }
+        let _ = std::io::stdout().flush();
+    }
+}
+
+/// Print the lines of @a histogram, in units of @a unit.
+///
+/// `print_histogram`
+fn print_histogram(histogram: &Histogram, unit: &str) {
+    for (i, line) in histogram.lines.iter().enumerate() {
+        if line.count == 0 {
+            continue;
+        }
+        let (lower, upper) = if i == 0 {
+            (0, 0)
+        } else {
+            (1u64 << (i - 1), (1u64 << (i - 1)) * 2 - 1)
+        };
+        println!(
+            "   [{}, {}] {unit}: {:>12} ({:5.1}%) {:>20} bytes ({:5.1}%)",
+            sep(lower),
+            sep(upper),
+            sep(line.count),
+            percent(line.count, histogram.total.count),
+            sep(line.sum),
+            percent(line.sum, histogram.total.sum),
+        );
+    }
+}
+
+/// Print the statistics of the representations in @a reps, called
+/// @a name.
+///
+/// `print_rep_stats`
+fn print_rep_stats(name: &str, reps: &RepStats) {
+    let total = reps.total();
+    let chain_length: u64 = reps
+        .chain_lengths
+        .iter()
+        .map(|(&length, &count)| length as u64 * count)
+        .sum();
+
+    println!("\n{name} representation statistics:");
+    println!(
+        "   {:>20} bytes in {:>12} reps",
+        sep(total.packed_size),
+        sep(total.count)
+    );
+    println!(
+        "   {:>20} bytes in {:>12} PLAIN reps",
+        sep(reps.plain.packed_size),
+        sep(reps.plain.count)
+    );
+    println!(
+        "   {:>20} bytes in {:>12} DELTA reps",
+        sep(reps.delta.packed_size),
+        sep(reps.delta.count)
+    );
+    println!(
+        "   {:>20} bytes in {:>12} shared reps",
+        sep(reps.shared.packed_size),
+        sep(reps.shared.count)
+    );
+    println!("   {:>20} bytes expanded size", sep(total.expanded_size));
+    println!(
+        "   {:>20} bytes expanded shared size",
+        sep(reps.shared.expanded_size)
+    );
+    println!(
+        "   {:>20} bytes with rep-sharing off",
+        sep(total.packed_size + reps.saved_size)
+    );
+    println!(
+        "   {:>20} shared references",
+        sep(reps.references - total.count)
+    );
+    println!(
+        "   {:>20.3} average delta chain length",
+        chain_length as f64 / total.count.max(1) as f64
+    );
+
+    if !reps.chain_lengths.is_empty() {
+        println!("\n   Delta chain lengths:");
+        for (length, &count) in &reps.chain_lengths {
+            println!(
+                "   {length:>6} reps long: {:>12} ({:5.1}%)",
+                sep(count),
+                percent(count, total.count)
+            );
+        }
+
+        println!("\n   Representation sizes:");
+        print_histogram(&reps.size_histogram, "bytes");
+    }
+}
+
+/// Print the statistics gathered for the repository.
+///
+/// `print_stats`
+fn print_stats(stats: &FsStats) {
+    let reps = stats.total_reps();
+    let nodes = stats.total_nodes();
+    let saved_size = [
+        &stats.file_reps,
+        &stats.dir_reps,
+        &stats.file_prop_reps,
+        &stats.dir_prop_reps,
+    ]
+    .iter()
+    .map(|reps| reps.saved_size)
+    .sum::<u64>();
+
+    println!("\nGlobal statistics:");
+    println!(
+        "   {:>20} bytes in {:>12} revisions",
+        sep(stats.total_size),
+        sep(stats.revision_count)
+    );
+    println!(
+        "   {:>20} bytes in {:>12} changes",
+        sep(stats.change_len),
+        sep(stats.change_count)
+    );
+    println!(
+        "   {:>20} bytes in {:>12} node revision records",
+        sep(nodes.size),
+        sep(nodes.count)
+    );
+    println!(
+        "   {:>20} bytes in {:>12} representations",
+        sep(reps.packed_size),
+        sep(reps.count)
+    );
+    println!(
+        "   {:>20} bytes expanded representation size",
+        sep(reps.expanded_size)
+    );
+    println!(
+        "   {:>20} bytes with rep-sharing off",
+        sep(reps.packed_size + saved_size)
+    );
+
+    println!("\nOverhead:");
+    let dir_size = stats.dir_nodes.size
+        + stats.dir_reps.total().packed_size
+        + stats.dir_reps.total().overhead_size;
+    let prop_size = [&stats.file_prop_reps, &stats.dir_prop_reps]
+        .iter()
+        .map(|reps| reps.total().packed_size + reps.total().overhead_size)
+        .sum::<u64>();
+    let file_overhead = stats.shards.iter().map(|shard| shard.overhead()).sum();
+    for (size, what) in [
+        (dir_size, "directory noderevs and representations"),
+        (prop_size, "property representations"),
+        (reps.overhead_size, "representation headers and trailers"),
+        (file_overhead, "indexes, trailers and manifests"),
+    ] {
+        println!(
+            "   {:>20} bytes ({:5.1}%) in {what}",
+            sep(size),
+            percent(size, stats.total_size)
+        );
+    }
+
+    println!("\nNoderev statistics:");
+    println!(
+        "   {:>20} bytes in {:>12} nodes total",
+        sep(nodes.size),
+        sep(nodes.count)
+    );
+    println!(
+        "   {:>20} bytes in {:>12} directory noderevs",
+        sep(stats.dir_nodes.size),
+        sep(stats.dir_nodes.count)
+    );
+    println!(
+        "   {:>20} bytes in {:>12} file noderevs",
+        sep(stats.file_nodes.size),
+        sep(stats.file_nodes.count)
+    );
+
+    print_rep_stats("Directory", &stats.dir_reps);
+    print_rep_stats("File", &stats.file_reps);
+    print_rep_stats("Directory property", &stats.dir_prop_reps);
+    print_rep_stats("File property", &stats.file_prop_reps);
+
+    println!("\nLargest files:");
+    for file in &stats.largest_files {
+        println!(
+            "   {:>20} bytes in r{:<8} {}",
+            sep(file.size),
+            file.revision,
+            file.path
+        );
+    }
+
+    println!("\nShard statistics:");
+    println!(
+        "   {:>10} {:>8} {:>6} {:>6} {:>20} {:>20} {:>10}",
+        "revision", "revs", "packed", "files", "bytes", "item bytes", "efficiency"
+    );
+    for shard in &stats.shards {
+        println!(
+            "   {:>10} {:>8} {:>6} {:>6} {:>20} {:>20} {:>9.1}%",
+            shard.start_revision,
+            sep(shard.revisions),
+            if shard.packed { "yes" } else { "no" },
+            sep(shard.files),
+            sep(shard.file_size),
+            sep(shard.item_size),
+            percent(shard.item_size, shard.file_size)
+        );
+    }
+}
+
+/// This implements `svnfsfs stats`.
+///
+/// `subcommand__stats`
+pub fn run(repos_path: &str) -> anyhow::Result<()> {
+    let fs_path = Path::new(repos_path).join("db");
+
+    print!("Reading revisions");
+    let stats = SvnFs::stats(&fs_path, Some(&mut print_progress), None)?;
+    println!();
+
+    print_stats(&stats);
+
+    Ok(())
+}
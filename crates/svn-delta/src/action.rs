@@ -1,6 +1,7 @@
 /// Action codes for text delta instructions.
 ///
 /// `svn_delta_action`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Action {
     /// Append the @a length bytes at @a offset in the source view to the
     /// target.
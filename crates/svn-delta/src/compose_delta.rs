@@ -4,6 +4,8 @@
 use indextree::{Arena, NodeId};
 
 use crate::TxdeltaWindow;
+use crate::action::Action;
+use crate::text_delta::OpsBaton;
 
 /// A node in the range index tree.
 ///
@@ -94,3 +96,148 @@ impl OffsetIndex {
         lo - 1
     }
 }
+
+/// Copy the data at [@a offset, @a limit) of the target of @a window as
+/// ops to @a baton.  Source and new data ops of @a window map directly;
+/// target ops get resolved recursively to the data they copy.  @a hint
+/// is passed on to the offset index search.
+///
+/// `copy_source_ops`
+fn copy_source_ops(
+    offset: usize,
+    limit: usize,
+    baton: &mut OpsBaton,
+    window: &TxdeltaWindow,
+    ndx: &OffsetIndex,
+    hint: &mut usize,
+) {
+    let first_op = ndx.search_offset(offset, *hint);
+    let last_op = ndx.search_offset(limit - 1, first_op);
+    *hint = first_op;
+
+    for op_ndx in first_op..=last_op {
+        let op = &window.ops[op_ndx];
+        let off = (ndx.offs[op_ndx], ndx.offs[op_ndx + 1]);
+        let fix_offset = offset.max(off.0) - off.0;
+        let fix_limit = off.1 - limit.min(off.1);
+        let length = op.length - fix_offset - fix_limit;
+
+        match op.action_code {
+            Action::Source => {
+                baton.insert_op(Action::Source, op.offset + fix_offset, length, &[]);
+            }
+            Action::New => {
+                let start = op.offset + fix_offset;
+                baton.insert_op(
+                    Action::New,
+                    0,
+                    length,
+                    &window.new_data[start..start + length],
+                );
+            }
+            Action::Target => {
+                // The op copies earlier data of the target, possibly
+                // overlapping with itself to repeat a pattern of
+                // PTN_LENGTH bytes.  Resolve every repetition of the
+                // pattern separately.
+                let ptn_length = off.0 - op.offset;
+                let mut pos = fix_offset;
+                while pos < fix_offset + length {
+                    let ptn_offset = pos % ptn_length;
+                    let chunk = (ptn_length - ptn_offset).min(fix_offset + length - pos);
+                    let start = op.offset + ptn_offset;
+                    let mut ptn_hint = first_op;
+                    copy_source_ops(start, start + chunk, baton, window, ndx, &mut ptn_hint);
+                    pos += chunk;
+                }
+            }
+        }
+    }
+}
+
+/// Compose two delta windows, yielding a third window that transforms
+/// the source of @a window_a directly into the target of @a window_b.
+/// The source view of @a window_b must be the target view of
+/// @a window_a.
+///
+/// `svn_txdelta_compose_windows`
+pub fn compose_windows(window_a: &TxdeltaWindow, window_b: &TxdeltaWindow) -> TxdeltaWindow {
+    let offset_index = OffsetIndex::create(window_a);
+    let mut baton = OpsBaton::default();
+    let mut hint = 0;
+
+    for op in &window_b.ops {
+        match op.action_code {
+            Action::Source => {
+                // NOTE: Remember that `offset' and `limit' refer to
+                // positions in window_B's _source_ stream, which is the
+                // same as window_A's _target_ stream!
+                copy_source_ops(
+                    op.offset,
+                    op.offset + op.length,
+                    &mut baton,
+                    window_a,
+                    &offset_index,
+                    &mut hint,
+                );
+            }
+            Action::Target => baton.insert_op(Action::Target, op.offset, op.length, &[]),
+            Action::New => baton.insert_op(
+                Action::New,
+                0,
+                op.length,
+                &window_b.new_data[op.offset..op.offset + op.length],
+            ),
+        }
+    }
+
+    baton.make_window(
+        window_a.sview_offset,
+        window_a.sview_length,
+        window_b.tview_length,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_delta::txdelta;
+
+    /// Apply all @a windows to @a source.
+    fn apply(windows: &[TxdeltaWindow], source: &[u8]) -> Vec<u8> {
+        let mut target = Vec::new();
+        for window in windows {
+            let sbuf = &source[window.sview_offset..window.sview_offset + window.sview_length];
+            window.apply_instructions(sbuf, &mut target);
+        }
+        target
+    }
+
+    #[test]
+    fn test_compose_windows() {
+        let base: Vec<u8> = (0..300_000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let mut middle = base.clone();
+        middle.splice(1000..1000, b"inserted text".iter().copied());
+        middle.truncate(250_000);
+        let mut top = b"xyzxyzxyzxyzxyzxyz".to_vec();
+        top.extend_from_slice(&middle[5000..]);
+        top.extend_from_slice(&middle[..5000]);
+
+        let lower = txdelta(&base, &middle);
+        let upper = txdelta(&middle, &top);
+        assert_eq!(apply(&lower, &base), middle);
+        assert_eq!(apply(&upper, &middle), top);
+        assert!(lower.iter().all(|window| window.src_ops > 0));
+
+        let mut target = Vec::new();
+        for (i, window) in upper.iter().enumerate() {
+            let window = match lower.get(i) {
+                Some(lower) if window.src_ops > 0 => compose_windows(lower, window),
+                _ => window.clone(),
+            };
+            let sbuf = &base[window.sview_offset..window.sview_offset + window.sview_length];
+            window.apply_instructions(sbuf, &mut target);
+        }
+        assert_eq!(target, top);
+    }
+}
@@ -1,16 +1,23 @@
 mod action;
+pub use action::Action;
 mod compress;
 mod range;
 
 mod text_delta;
+pub use text_delta::SVN_DELTA_WINDOW_SIZE;
+pub use text_delta::txdelta;
+
+mod xdelta;
 
 mod cancel;
 
-mod svndiff;
+pub mod svndiff;
+pub use svndiff::SvndiffError;
 
 pub mod editor;
 
 pub mod compose_delta;
 
 pub mod window;
+pub use window::TxdeltaOp;
 pub use window::TxdeltaWindow;
@@ -1 +1,380 @@
 //! svndiff.c -- Encoding and decoding svndiff-format deltas.
+
+use svn_subr::compress;
+use svn_subr::compress::encode_uint;
+
+use crate::TxdeltaWindow;
+use crate::action::Action;
+use crate::window::TxdeltaOp;
+
+/// The magic bytes every svndiff stream starts with, followed by the
+/// version byte.
+const SVNDIFF_MAGIC: &[u8; 3] = b"SVN";
+
+/// The highest svndiff version we understand.
+const SVNDIFF_MAX_VERSION: u8 = 2;
+
+/// Upper bound for the decompressed size of a single svndiff section, to
+/// protect against bogus length fields.
+const MAX_INSTRUCTION_SECTION_LEN: usize = 100 * 1024 * 1024;
+
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+pub enum SvndiffError {
+    #[error("Svndiff has invalid header")]
+    InvalidHeader,
+
+    #[error("Unexpected end of svndiff input")]
+    UnexpectedEnd,
+
+    #[error("Svndiff data contains backward-sliding source view")]
+    BackwardSlidingView,
+
+    #[error("Svndiff data contains corrupt window")]
+    CorruptWindow,
+
+    #[error("Invalid diff stream: insn {0} cannot be decoded")]
+    InvalidInstruction(usize),
+
+    #[error("Decompression of svndiff data failed")]
+    Decompression,
+}
+
+/// Decode the variable-length number at @a pos in @a data and advance
+/// @a pos.
+///
+/// `svn__decode_uint`
+fn decode_uint(data: &[u8], pos: &mut usize) -> Result<usize, SvndiffError> {
+    compress::decode_uint(data, pos).ok_or(if *pos >= data.len() {
+        SvndiffError::UnexpectedEnd
+    } else {
+        SvndiffError::CorruptWindow
+    })
+}
+
+/// Parse the svndiff header at the start of @a data and return the svndiff
+/// version it announces.
+pub fn parse_header(data: &[u8]) -> Result<u8, SvndiffError> {
+    match data {
+        [b'S', b'V', b'N', version, ..] if *version <= SVNDIFF_MAX_VERSION => Ok(*version),
+        _ => Err(SvndiffError::InvalidHeader),
+    }
+}
+
+/// Return the svndiff header for svndiff @a version.
+pub fn header(version: u8) -> [u8; 4] {
+    [
+        SVNDIFF_MAGIC[0],
+        SVNDIFF_MAGIC[1],
+        SVNDIFF_MAGIC[2],
+        version,
+    ]
+}
+
+/// Undo the compression of a svndiff1 (zlib) / svndiff2 (lz4) section
+/// in @a data.
+///
+/// `svn__decompress_zlib` / `svn__decompress_lz4`
+fn decompress(data: &[u8], version: u8) -> Result<Vec<u8>, SvndiffError> {
+    if version == 1 {
+        compress::decompress_zlib(data, MAX_INSTRUCTION_SECTION_LEN)
+    } else {
+        compress::decompress_lz4(data, MAX_INSTRUCTION_SECTION_LEN)
+    }
+    .map_err(|_| SvndiffError::Decompression)
+}
+
+/// Decode the instructions in @a data, checking them against the sizes
+/// of the views in @a window.
+///
+/// `decode_window`, `count_and_verify_instructions`
+fn decode_instructions(data: &[u8], window: &mut TxdeltaWindow) -> Result<(), SvndiffError> {
+    let mut pos = 0;
+    let mut tpos = 0;
+    let mut npos = 0;
+
+    while pos < data.len() {
+        let n = window.ops.len();
+        let byte = data[pos];
+        pos += 1;
+
+        let action_code = match byte >> 6 {
+            0 => Action::Source,
+            1 => Action::Target,
+            2 => Action::New,
+            _ => return Err(SvndiffError::InvalidInstruction(n)),
+        };
+        let length = match (byte & 0x3f) as usize {
+            0 => decode_uint(data, &mut pos)?,
+            length => length,
+        };
+        let offset = match action_code {
+            Action::New => npos,
+            _ => decode_uint(data, &mut pos)?,
+        };
+
+        // Check that the instruction stays within its view.
+        let valid = match action_code {
+            Action::Source => offset
+                .checked_add(length)
+                .is_some_and(|end| end <= window.sview_length),
+            Action::Target => offset < tpos,
+            Action::New => {
+                npos += length;
+                npos <= window.new_data.len()
+            }
+        };
+        if length == 0 || !valid {
+            return Err(SvndiffError::InvalidInstruction(n));
+        }
+
+        tpos += length;
+        if tpos > window.tview_length {
+            return Err(SvndiffError::InvalidInstruction(n));
+        }
+
+        if action_code == Action::Source {
+            window.src_ops += 1;
+        }
+        window.ops.push(TxdeltaOp {
+            action_code,
+            offset,
+            length,
+        });
+    }
+
+    if tpos != window.tview_length {
+        return Err(SvndiffError::CorruptWindow);
+    }
+    if npos != window.new_data.len() {
+        return Err(SvndiffError::CorruptWindow);
+    }
+
+    window.num_ops = window.ops.len() as i32;
+    Ok(())
+}
+
+/// Read the svndiff @a version window at @a pos in @a data and advance
+/// @a pos behind it.
+///
+/// `svn_txdelta_read_svndiff_window`
+pub fn read_window(
+    data: &[u8],
+    pos: &mut usize,
+    version: u8,
+) -> Result<TxdeltaWindow, SvndiffError> {
+    let sview_offset = decode_uint(data, pos)?;
+    let sview_length = decode_uint(data, pos)?;
+    let tview_length = decode_uint(data, pos)?;
+    let inslen = decode_uint(data, pos)?;
+    let newlen = decode_uint(data, pos)?;
+
+    if sview_offset.checked_add(sview_length).is_none() {
+        return Err(SvndiffError::CorruptWindow);
+    }
+
+    let end = (*pos)
+        .checked_add(inslen)
+        .and_then(|end| end.checked_add(newlen))
+        .ok_or(SvndiffError::CorruptWindow)?;
+    if end > data.len() {
+        return Err(SvndiffError::UnexpectedEnd);
+    }
+
+    let ins = &data[*pos..*pos + inslen];
+    let new = &data[*pos + inslen..end];
+    *pos = end;
+
+    let (ins, new_data) = if version == 0 {
+        (ins.to_vec(), new.to_vec())
+    } else {
+        (decompress(ins, version)?, decompress(new, version)?)
+    };
+
+    let mut window = TxdeltaWindow {
+        sview_offset,
+        sview_length,
+        tview_length,
+        num_ops: 0,
+        src_ops: 0,
+        ops: Vec::new(),
+        new_data,
+    };
+    decode_instructions(&ins, &mut window)?;
+
+    Ok(window)
+}
+
+/// Parse the complete svndiff stream in @a data, header included.
+///
+/// `svn_txdelta_parse_svndiff`
+pub fn parse_svndiff(data: &[u8]) -> Result<Vec<TxdeltaWindow>, SvndiffError> {
+    let version = parse_header(data)?;
+    let mut pos = SVNDIFF_MAGIC.len() + 1;
+
+    let mut windows = Vec::new();
+    let mut last_sview_offset = 0;
+    let mut last_sview_len = 0;
+    while pos < data.len() {
+        let window = read_window(data, &mut pos, version)?;
+
+        // The source view must never slide backwards.
+        if window.sview_offset < last_sview_offset
+            || window.sview_offset + window.sview_length < last_sview_offset + last_sview_len
+        {
+            return Err(SvndiffError::BackwardSlidingView);
+        }
+        last_sview_offset = window.sview_offset;
+        last_sview_len = window.sview_length;
+
+        windows.push(window);
+    }
+
+    Ok(windows)
+}
+
+/// Append the svndiff version 0 encoding of @a window to @a out.
+///
+/// `write_window`
+pub fn write_window(out: &mut Vec<u8>, window: &TxdeltaWindow) {
+    encode_window(out, window, 0, 0);
+}
+
+/// Append the svndiff @a version encoding of @a window to @a out.  For
+/// svndiff1, @a compression_level is the zlib compression level to use;
+/// it is ignored otherwise.
+///
+/// `svn_txdelta_to_svndiff3` / `window_handler`
+pub fn encode_window(
+    out: &mut Vec<u8>,
+    window: &TxdeltaWindow,
+    version: u8,
+    compression_level: i32,
+) {
+    let mut ins = Vec::new();
+    for op in &window.ops {
+        let action = match op.action_code {
+            Action::Source => 0u8,
+            Action::Target => 1,
+            Action::New => 2,
+        };
+        if op.length < 0x40 {
+            ins.push((action << 6) | op.length as u8);
+        } else {
+            ins.push(action << 6);
+            encode_uint(&mut ins, op.length);
+        }
+        if op.action_code != Action::New {
+            encode_uint(&mut ins, op.offset);
+        }
+    }
+
+    let (ins, new_data) = match version {
+        0 => (ins, window.new_data.clone()),
+        1 => (
+            compress::compress_zlib(&ins, compression_level),
+            compress::compress_zlib(&window.new_data, compression_level),
+        ),
+        _ => (
+            compress::compress_lz4(&ins),
+            compress::compress_lz4(&window.new_data),
+        ),
+    };
+
+    encode_uint(out, window.sview_offset);
+    encode_uint(out, window.sview_length);
+    encode_uint(out, window.tview_length);
+    encode_uint(out, ins.len());
+    encode_uint(out, new_data.len());
+    out.extend_from_slice(&ins);
+    out.extend_from_slice(&new_data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uint_roundtrip() {
+        for value in [0, 1, 0x7f, 0x80, 0x3fff, 0x4000, usize::MAX >> 1] {
+            let mut out = Vec::new();
+            encode_uint(&mut out, value);
+            let mut pos = 0;
+            assert_eq!(decode_uint(&out, &mut pos).unwrap(), value);
+            assert_eq!(pos, out.len());
+        }
+    }
+
+    #[test]
+    fn test_window_roundtrip() {
+        let window = TxdeltaWindow {
+            sview_offset: 0,
+            sview_length: 4,
+            tview_length: 10,
+            num_ops: 3,
+            src_ops: 1,
+            ops: vec![
+                TxdeltaOp {
+                    action_code: Action::Source,
+                    offset: 0,
+                    length: 4,
+                },
+                TxdeltaOp {
+                    action_code: Action::New,
+                    offset: 0,
+                    length: 2,
+                },
+                TxdeltaOp {
+                    action_code: Action::Target,
+                    offset: 4,
+                    length: 4,
+                },
+            ],
+            new_data: b"xy".to_vec(),
+        };
+
+        let mut data = header(0).to_vec();
+        write_window(&mut data, &window);
+        let windows = parse_svndiff(&data).unwrap();
+        assert_eq!(windows, [window.clone()]);
+
+        let mut target = Vec::new();
+        windows[0].apply_instructions(b"abcd", &mut target);
+        assert_eq!(target, b"abcdxyxyxy");
+    }
+
+    #[test]
+    fn test_compressed_roundtrip() {
+        let text: Vec<u8> = (0..4096u32).map(|i| (i % 7) as u8).collect();
+        let window = TxdeltaWindow {
+            sview_offset: 0,
+            sview_length: 0,
+            tview_length: text.len(),
+            num_ops: 1,
+            src_ops: 0,
+            ops: vec![TxdeltaOp {
+                action_code: Action::New,
+                offset: 0,
+                length: text.len(),
+            }],
+            new_data: text,
+        };
+
+        for (version, level) in [(1, 5), (1, 0), (2, 0)] {
+            let mut data = header(version).to_vec();
+            encode_window(&mut data, &window, version, level);
+            if level != 0 {
+                assert!(data.len() < window.new_data.len());
+            }
+            assert_eq!(parse_svndiff(&data).unwrap(), std::slice::from_ref(&window));
+        }
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        assert!(matches!(
+            parse_svndiff(b"SVN\x07"),
+            Err(SvndiffError::InvalidHeader)
+        ));
+    }
+}
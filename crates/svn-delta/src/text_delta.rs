@@ -1 +1,175 @@
 //! text-delta.c -- Internal text delta representation
+
+use crate::TxdeltaOp;
+use crate::TxdeltaWindow;
+use crate::action::Action;
+use crate::xdelta;
+
+/// The size of the windows a fulltext gets split into when computing
+/// deltas.  Source and target are read in chunks of this size, so that
+/// window N of a delta always refers to chunk N of its source.
+///
+/// `SVN_DELTA_WINDOW_SIZE`
+pub const SVN_DELTA_WINDOW_SIZE: usize = 102400;
+
+/// Collects the instructions of a delta window while it is being built.
+///
+/// `svn_txdelta__ops_baton_t`
+#[derive(Debug, Default)]
+pub(crate) struct OpsBaton {
+    /// the instructions collected so far
+    ops: Vec<TxdeltaOp>,
+    /// the number of svn_txdelta_source instructions in @a ops
+    src_ops: i32,
+    /// the new data referred to by the svn_txdelta_new instructions
+    new_data: Vec<u8>,
+}
+
+impl OpsBaton {
+    /// Insert a delta op into the window being built.  Adjacent ops get
+    /// merged where possible.  @a new_data is only used by
+    /// [`Action::New`] ops.
+    ///
+    /// `svn_txdelta__insert_op`
+    pub(crate) fn insert_op(
+        &mut self,
+        action_code: Action,
+        offset: usize,
+        length: usize,
+        new_data: &[u8],
+    ) {
+        if length == 0 {
+            return;
+        }
+
+        // Check if this op can be merged with the previous op.  The
+        // delta combiner sometimes generates such ops, and this is the
+        // obvious place to make the check.
+        if let Some(op) = self.ops.last_mut()
+            && op.action_code == action_code
+        {
+            match action_code {
+                Action::Source | Action::Target if op.offset + op.length == offset => {
+                    op.length += length;
+                    return;
+                }
+                Action::New => {
+                    op.length += length;
+                    self.new_data.extend_from_slice(&new_data[..length]);
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        let offset = match action_code {
+            Action::Source => {
+                self.src_ops += 1;
+                offset
+            }
+            Action::Target => offset,
+            Action::New => {
+                let offset = self.new_data.len();
+                self.new_data.extend_from_slice(&new_data[..length]);
+                offset
+            }
+        };
+        self.ops.push(TxdeltaOp {
+            action_code,
+            offset,
+            length,
+        });
+    }
+
+    /// Turn the collected instructions into a window with the given
+    /// source and target views.
+    ///
+    /// `svn_txdelta__make_window`
+    pub(crate) fn make_window(
+        self,
+        sview_offset: usize,
+        sview_length: usize,
+        tview_length: usize,
+    ) -> TxdeltaWindow {
+        TxdeltaWindow {
+            sview_offset,
+            sview_length,
+            tview_length,
+            num_ops: self.ops.len() as i32,
+            src_ops: self.src_ops,
+            ops: self.ops,
+            new_data: self.new_data,
+        }
+    }
+}
+
+/// Compute the delta window turning the source view @a source, which
+/// starts at @a source_offset in the source, into @a target.
+///
+/// `compute_window`
+fn compute_window(source: &[u8], target: &[u8], source_offset: usize) -> TxdeltaWindow {
+    let mut baton = OpsBaton::default();
+
+    if source.is_empty() {
+        baton.insert_op(Action::New, 0, target.len(), target);
+    } else {
+        xdelta::xdelta(&mut baton, source, target);
+    }
+
+    baton.make_window(source_offset, source.len(), target.len())
+}
+
+/// Return the delta windows transforming @a source into @a target.
+///
+/// Both texts are processed in chunks of [`SVN_DELTA_WINDOW_SIZE`]:
+/// window N turns chunk N of @a source (if any) into chunk N of
+/// @a target.  An empty @a target produces no windows at all.
+///
+/// `svn_txdelta2` / `txdelta_next_window`
+pub fn txdelta(source: &[u8], target: &[u8]) -> Vec<TxdeltaWindow> {
+    target
+        .chunks(SVN_DELTA_WINDOW_SIZE)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let offset = (i * SVN_DELTA_WINDOW_SIZE).min(source.len());
+            let end = (offset + SVN_DELTA_WINDOW_SIZE).min(source.len());
+            compute_window(&source[offset..end], chunk, offset)
+        })
+        .collect()
+}
+
+impl TxdeltaWindow {
+    /// Apply the instructions of this window to the source view @a sbuf
+    /// and append the resulting target view to @a tbuf.
+    ///
+    /// @a sbuf must hold the source data of this window, i.e. the
+    /// `sview_length` bytes starting at `sview_offset` in the source.
+    ///
+    /// `svn_txdelta_apply_instructions`
+    pub fn apply_instructions(&self, sbuf: &[u8], tbuf: &mut Vec<u8>) {
+        let start = tbuf.len();
+        tbuf.reserve(self.tview_length);
+
+        for op in &self.ops {
+            match op.action_code {
+                Action::Source => {
+                    // Copy from source area.
+                    tbuf.extend_from_slice(&sbuf[op.offset..op.offset + op.length]);
+                }
+                Action::Target => {
+                    // Copy from target area.  Don't use a slice copy here:
+                    // the target range may overlap the data being written,
+                    // which is how runs of repeated data get encoded.
+                    for i in 0..op.length {
+                        let byte = tbuf[start + op.offset + i];
+                        tbuf.push(byte);
+                    }
+                }
+                Action::New => {
+                    // Copy from window new area.
+                    tbuf.extend_from_slice(&self.new_data[op.offset..op.offset + op.length]);
+                }
+            }
+        }
+    }
+}
@@ -21,34 +21,36 @@ use crate::action::Action;
 /// without making a full copy of the source stream.
 ///
 /// `svn_txdelta_window_t`
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TxdeltaWindow {
     /// The offset of the source view for this window.
-    sview_offset: usize,
+    pub sview_offset: usize,
 
     /// The length of the source view for this window.
-    sview_length: usize,
+    pub sview_length: usize,
     /// The length of the target view for this window, i.e. the number of
     /// bytes which will be reconstructed by the instruction stream.
-    tview_length: usize,
+    pub tview_length: usize,
     /// The number of instructions in this window.
     pub num_ops: i32,
     /// The number of svn_txdelta_source instructions in this window. If
     /// this number is 0, we don't need to read the source in order to
     /// reconstruct the target view.
-    src_ops: i32,
+    pub src_ops: i32,
     /// The instructions for this window.
     pub ops: Vec<TxdeltaOp>,
     /// New data, for use by any `svn_txdelta_new' instructions.
-    new_data: String,
+    pub new_data: Vec<u8>,
 }
 /// A single text delta instruction.
 ///
 /// `svn_txdelta_op_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TxdeltaOp {
     /// Action code of delta instruction
-    action_code: Action,
+    pub action_code: Action,
     ///  Offset of delta, see #svn_delta_action for more details.
-    offset: usize,
+    pub offset: usize,
     /// Number of bytes of delta, see #svn_delta_action for more details.
     pub length: usize,
 }
@@ -0,0 +1,222 @@
+//! xdelta.c -- an implementation of the xdelta algorithm
+//!
+//! Find the parts of a target that also occur in its source, using a
+//! rolling checksum over blocks of the source.
+
+use std::collections::HashMap;
+
+use crate::action::Action;
+use crate::text_delta::OpsBaton;
+
+/// The "blocksize" used in the xdelta algorithm.  Matches are searched
+/// for at this granularity, and shorter matches are never found.
+///
+/// `MATCH_BLOCKSIZE`
+const MATCH_BLOCKSIZE: usize = 64;
+
+/// A rolling checksum over a window of [`MATCH_BLOCKSIZE`] bytes.  This
+/// is a simplified Adler-32 that uses plain 16 bit arithmetics.
+///
+/// `struct adler32`
+#[derive(Clone, Copy)]
+struct Adler32 {
+    s1: u32,
+    s2: u32,
+}
+
+impl Adler32 {
+    /// Return the checksum of @a data.
+    ///
+    /// `init_adler32`
+    fn new(data: &[u8]) -> Self {
+        let mut s1: u32 = 0;
+        let mut s2: u32 = 0;
+        for &byte in data {
+            s1 = s1.wrapping_add(byte as u32);
+            s2 = s2.wrapping_add(s1);
+        }
+        Self { s1, s2 }
+    }
+
+    /// Slide the checksummed window one byte to the right: drop @a c_out
+    /// at its start and append @a c_in.
+    ///
+    /// `adler32_replace`
+    fn replace(&mut self, c_out: u8, c_in: u8) {
+        self.s1 = self.s1.wrapping_sub(c_out as u32).wrapping_add(c_in as u32);
+        self.s2 = self
+            .s2
+            .wrapping_sub((MATCH_BLOCKSIZE as u32).wrapping_mul(c_out as u32))
+            .wrapping_add(self.s1);
+    }
+
+    /// `adler32_out`
+    fn out(&self) -> u32 {
+        ((self.s2 & 0xffff) << 16) | (self.s1 & 0xffff)
+    }
+}
+
+/// Return the number of bytes at the start of @a a and @a b that are
+/// equal.
+///
+/// `svn_cstring__match_length`
+fn match_length(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// Return the number of bytes at the end of @a a and @a b that are equal.
+///
+/// `svn_cstring__reverse_match_length`
+fn reverse_match_length(a: &[u8], b: &[u8]) -> usize {
+    a.iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count()
+}
+
+/// The blocks of the source, indexed by their checksum.
+///
+/// `struct blocks`
+struct Blocks<'a> {
+    /// the source data
+    data: &'a [u8],
+    /// maps checksums to the start of the first block having it
+    slots: HashMap<u32, usize>,
+}
+
+impl<'a> Blocks<'a> {
+    /// Index all complete, aligned blocks of @a data.
+    ///
+    /// `init_blocks_table`
+    fn new(data: &'a [u8]) -> Self {
+        let mut slots = HashMap::with_capacity(data.len() / MATCH_BLOCKSIZE);
+        for (i, block) in data.chunks_exact(MATCH_BLOCKSIZE).enumerate() {
+            slots
+                .entry(Adler32::new(block).out())
+                .or_insert(i * MATCH_BLOCKSIZE);
+        }
+
+        Self { data, slots }
+    }
+
+    /// Return the start of a source block with checksum @a checksum that
+    /// equals the block at the start of @a block.
+    ///
+    /// `find_block`
+    fn find(&self, checksum: u32, block: &[u8]) -> Option<usize> {
+        let pos = *self.slots.get(&checksum)?;
+        (self.data[pos..pos + MATCH_BLOCKSIZE] == block[..MATCH_BLOCKSIZE]).then_some(pos)
+    }
+}
+
+/// Try to find a match for the target data at @a bpos in @a b in the
+/// source @a blocks.  On success, return the source position, the target
+/// position (the match may have been extended backwards, but never beyond
+/// @a pending_insert_start) and the match length.
+///
+/// `find_match`
+fn find_match(
+    blocks: &Blocks<'_>,
+    rolling: &Adler32,
+    b: &[u8],
+    bpos: usize,
+    pending_insert_start: usize,
+) -> Option<(usize, usize, usize)> {
+    let a = blocks.data;
+    let mut apos = blocks.find(rolling.out(), &b[bpos..])?;
+    let mut bpos = bpos;
+
+    // Extend the match forward as far as possible.
+    let mut len =
+        MATCH_BLOCKSIZE + match_length(&a[apos + MATCH_BLOCKSIZE..], &b[bpos + MATCH_BLOCKSIZE..]);
+
+    // Also extend it backwards into the data not covered by any op yet.
+    while apos > 0 && bpos > pending_insert_start && a[apos - 1] == b[bpos - 1] {
+        apos -= 1;
+        bpos -= 1;
+        len += 1;
+    }
+
+    Some((apos, bpos, len))
+}
+
+/// Compute a delta from @a a to @a b and store its instructions in
+/// @a baton.
+///
+/// `compute_delta` / `svn_txdelta__xdelta`
+pub(crate) fn xdelta(baton: &mut OpsBaton, a: &[u8], b: &[u8]) {
+    // If the size of the target is smaller than the match blocksize,
+    // just insert the entire target.
+    if b.len() < MATCH_BLOCKSIZE || a.len() < MATCH_BLOCKSIZE {
+        baton.insert_op(Action::New, 0, b.len(), b);
+        return;
+    }
+
+    // Optimization: skip all initial and final matching bytes.
+    let prefix = match_length(a, b);
+    let suffix = reverse_match_length(&a[prefix..], &b[prefix..]);
+    let upper = b.len() - suffix;
+
+    let mut lo = 0;
+    if prefix > 0 {
+        baton.insert_op(Action::Source, 0, prefix, &[]);
+        lo = prefix;
+    }
+
+    let blocks = Blocks::new(a);
+    let mut pending_insert_start = lo;
+    let mut rolling =
+        (lo + MATCH_BLOCKSIZE <= upper).then(|| Adler32::new(&b[lo..lo + MATCH_BLOCKSIZE]));
+
+    while lo < upper {
+        // Match the target against the source.
+        let found = match &rolling {
+            Some(rolling) if lo + MATCH_BLOCKSIZE <= upper => {
+                find_match(&blocks, rolling, &b[..upper], lo, pending_insert_start)
+            }
+            _ => None,
+        };
+
+        match found {
+            None => {
+                // If we didn't find a real match, treat the next target
+                // byte as new data.
+                if lo + MATCH_BLOCKSIZE < upper
+                    && let Some(rolling) = &mut rolling
+                {
+                    rolling.replace(b[lo], b[lo + MATCH_BLOCKSIZE]);
+                }
+                lo += 1;
+            }
+            Some((apos, bpos, len)) => {
+                // Store the sequence of B that is between the matches.
+                baton.insert_op(
+                    Action::New,
+                    0,
+                    bpos - pending_insert_start,
+                    &b[pending_insert_start..bpos],
+                );
+                baton.insert_op(Action::Source, apos, len, &[]);
+
+                // Adjust and restart the rolling checksum.
+                lo = bpos + len;
+                pending_insert_start = lo;
+                rolling = (lo + MATCH_BLOCKSIZE <= upper)
+                    .then(|| Adler32::new(&b[lo..lo + MATCH_BLOCKSIZE]));
+            }
+        }
+    }
+
+    // If we still have an insert pending at the end, throw it in.
+    baton.insert_op(
+        Action::New,
+        0,
+        upper - pending_insert_start,
+        &b[pending_insert_start..upper],
+    );
+
+    if suffix > 0 {
+        baton.insert_op(Action::Source, a.len() - suffix, suffix, &[]);
+    }
+}
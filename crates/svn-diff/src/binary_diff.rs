@@ -1,10 +1,168 @@
 //! binary_diff.c:  handling of git like binary diffs
 
-use std::fs::File;
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+
+/// The message printed instead of a content diff for binary files.
+pub const CANNOT_DISPLAY: &str = "Cannot display: file marked as a binary type.";
+
+/// The number of leading bytes inspected when sniffing content for
+/// binary data, matching what `svn_io_detect_mimetype2` reads.
+const SNIFF_LEN: usize = 1024;
+
+/// The maximum number of (compressed) bytes encoded on a single line of a
+/// git binary patch.
+const LITERAL_LINE_LEN: usize = 52;
+
+/// Return TRUE if a file with @a mime_type (the value of its
+/// `svn:mime-type` property, if any) and @a content should be treated as
+/// binary.
+///
+/// An explicit mime-type always wins; invalid mime-types are treated as
+/// binary just like Subversion does. Without a mime-type the leading bytes
+/// of the content are sniffed for NUL and other non-text bytes.
+pub fn is_binary_content(mime_type: Option<&str>, content: &[u8]) -> bool {
+    if let Some(mime_type) = mime_type {
+        return svn_types::mime::is_binary(mime_type).unwrap_or(true);
+    }
+
+    let len = content.len().min(SNIFF_LEN);
+    svn_subr::io::is_binary_data(&content[..len])
+}
 
 /// Copies the data from ORIGINAL_STREAM to a temporary file, returning both
 /// the original and compressed size.
+///
+/// We keep everything in memory, so the compressed data is returned
+/// instead of a temporary file.
+///
 /// `create_compressed`
-pub fn create_compressed(original_stream: &impl std::io::Read) -> File {
-    todo!()
+pub fn create_compressed(original: &[u8]) -> Result<(Vec<u8>, usize), std::io::Error> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(original)?;
+    let compressed = encoder.finish()?;
+
+    Ok((compressed, original.len()))
+}
+
+/// Git Base-85 table for write_literal
+const B85STR: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+/// Write the base85 encoded lines of @a data to @a output, one line per
+/// 52 bytes of input.
+///
+/// `write_literal`
+fn write_literal<W: Write>(output: &mut W, data: &[u8]) -> Result<(), std::io::Error> {
+    for chunk in data.chunks(LITERAL_LINE_LEN) {
+        let len = chunk.len();
+        // Lines of up to 26 bytes are prefixed by 'A'..'Z', longer lines
+        // by 'a'..'z'.
+        let len_char = if len <= 26 {
+            b'A' + len as u8 - 1
+        } else {
+            b'a' + len as u8 - 27
+        };
+
+        let mut line = Vec::with_capacity(1 + len.div_ceil(4) * 5 + 1);
+        line.push(len_char);
+
+        for group in chunk.chunks(4) {
+            let mut padded = [0u8; 4];
+            padded[..group.len()].copy_from_slice(group);
+            let mut value = u32::from_be_bytes(padded);
+
+            let mut encoded = [0u8; 5];
+            for c in encoded.iter_mut().rev() {
+                *c = B85STR[(value % 85) as usize];
+                value /= 85;
+            }
+            line.extend_from_slice(&encoded);
+        }
+        line.push(b'\n');
+
+        output.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a git-like binary diff from @a original to @a latest to
+/// @a output.
+///
+/// The patch contains the compressed literal of the new content, followed
+/// by the compressed literal of the old content, so it can be applied in
+/// both directions.
+///
+/// `svn_diff_output_binary`
+pub fn output_binary<W: Write>(
+    output: &mut W,
+    original: &[u8],
+    latest: &[u8],
+) -> Result<(), std::io::Error> {
+    let (latest_compressed, latest_size) = create_compressed(latest)?;
+    let (original_compressed, original_size) = create_compressed(original)?;
+
+    writeln!(output, "GIT binary patch")?;
+
+    writeln!(output, "literal {latest_size}")?;
+    write_literal(output, &latest_compressed)?;
+    writeln!(output)?;
+
+    writeln!(output, "literal {original_size}")?;
+    write_literal(output, &original_compressed)?;
+    writeln!(output)?;
+
+    Ok(())
+}
+
+/// Writes the note shown instead of a diff for binary content, followed by
+/// the mime-types of both sides as far as they are known.
+///
+/// `diff_content_changed`
+pub fn output_cannot_display<W: Write>(
+    output: &mut W,
+    mimetype1: Option<&str>,
+    mimetype2: Option<&str>,
+) -> Result<(), std::io::Error> {
+    writeln!(output, "{CANNOT_DISPLAY}")?;
+
+    match (mimetype1, mimetype2) {
+        (Some(m1), None) => writeln!(output, "svn:mime-type = {m1}")?,
+        (None, Some(m2)) => writeln!(output, "svn:mime-type = {m2}")?,
+        (Some(m1), Some(m2)) if m1 == m2 => writeln!(output, "svn:mime-type = {m1}")?,
+        (Some(m1), Some(m2)) => writeln!(output, "svn:mime-type = ({m1}, {m2})")?,
+        (None, None) => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_binary_content() {
+        assert!(!is_binary_content(None, b"hello world\n"));
+        assert!(is_binary_content(None, b"hello\0world"));
+        assert!(is_binary_content(Some("application/octet-stream"), b"text"));
+        assert!(!is_binary_content(Some("text/plain"), b"\0\0\0"));
+        assert!(!is_binary_content(Some("image/x-xbitmap"), b"\0"));
+    }
+
+    #[test]
+    fn test_write_literal() {
+        let mut out = Vec::new();
+        write_literal(&mut out, b"\0\0\0\0").unwrap();
+        assert_eq!(out, b"D00000\n");
+
+        let mut out = Vec::new();
+        write_literal(&mut out, &[0xff; 27]).unwrap();
+        let line = String::from_utf8(out).unwrap();
+        assert!(line.starts_with('a'));
+        assert_eq!(line.len(), 1 + 7 * 5 + 1);
+    }
 }
@@ -0,0 +1,235 @@
+//! three-way merges of binary files
+//!
+//! Binary content can't be merged line by line, so instead of writing
+//! conflict markers into the target we either pick one side wholesale or
+//! leave the three versions next to the target for the user to resolve.
+
+use std::path::{Path, PathBuf};
+
+use crate::binary_diff::is_binary_content;
+use crate::merge::{MergeConflict, MergeLabels, MergeOutcome};
+
+/// Merge the changes between @a left and @a right into the binary file at
+/// @a target.
+///
+/// If @a left and @a right are the same, there is nothing to merge.  If
+/// @a target is unmodified with respect to @a left, it is replaced by
+/// @a right. Otherwise the three versions are copied next to @a target,
+/// using @a labels as file name suffixes, and a conflict is returned.
+///
+/// `merge_binary_file`
+pub fn merge_binary_file(
+    target: &Path,
+    left: &Path,
+    right: &Path,
+    labels: &MergeLabels<'_>,
+) -> Result<MergeOutcome, std::io::Error> {
+    merge_binary(
+        target,
+        &fs_err::read(target)?,
+        &fs_err::read(left)?,
+        &fs_err::read(right)?,
+        labels,
+    )
+}
+
+/// Merge the changes between @a left and @a right into @a target as in
+/// [`merge_binary_file`], if the content is binary.
+///
+/// The content is binary if @a mime_type, the `svn:mime-type` of the
+/// target, says so or, without one, if any of the three versions looks
+/// binary.  Otherwise return `None` and leave @a target alone, for the
+/// caller to merge it line by line.
+///
+/// `svn_wc__internal_merge`
+pub fn merge_file_if_binary(
+    target: &Path,
+    left: &Path,
+    right: &Path,
+    mime_type: Option<&str>,
+    labels: &MergeLabels<'_>,
+) -> Result<Option<MergeOutcome>, std::io::Error> {
+    merge_if_binary(
+        target,
+        &fs_err::read(target)?,
+        &fs_err::read(left)?,
+        &fs_err::read(right)?,
+        mime_type,
+        labels,
+    )
+}
+
+/// Like [`merge_file_if_binary`], with the contents of the three versions
+/// already read.
+pub(crate) fn merge_if_binary(
+    target: &Path,
+    target_content: &[u8],
+    left_content: &[u8],
+    right_content: &[u8],
+    mime_type: Option<&str>,
+    labels: &MergeLabels<'_>,
+) -> Result<Option<MergeOutcome>, std::io::Error> {
+    let is_binary = [target_content, left_content, right_content]
+        .iter()
+        .any(|content| is_binary_content(mime_type, content));
+    if !is_binary {
+        return Ok(None);
+    }
+
+    merge_binary(target, target_content, left_content, right_content, labels).map(Some)
+}
+
+/// Merge @a left_content and @a right_content into @a target, holding
+/// @a target_content, as in [`merge_binary_file`].
+fn merge_binary(
+    target: &Path,
+    target_content: &[u8],
+    left_content: &[u8],
+    right_content: &[u8],
+    labels: &MergeLabels<'_>,
+) -> Result<MergeOutcome, std::io::Error> {
+    if left_content == right_content || target_content == right_content {
+        return Ok(MergeOutcome::Unchanged);
+    }
+
+    if target_content == left_content {
+        fs_err::write(target, right_content)?;
+        return Ok(MergeOutcome::Merged);
+    }
+
+    write_conflict_files(target, target_content, left_content, right_content, labels)
+        .map(MergeOutcome::Conflict)
+}
+
+/// Copy the three versions of a conflicted @a target next to it, using
+/// @a labels as file name suffixes.
+pub(crate) fn write_conflict_files(
+    target: &Path,
+    target_content: &[u8],
+    left_content: &[u8],
+    right_content: &[u8],
+    labels: &MergeLabels<'_>,
+) -> Result<MergeConflict, std::io::Error> {
+    let base = unique_conflict_path(target, labels.left)?;
+    fs_err::write(&base, left_content)?;
+    let theirs = unique_conflict_path(target, labels.right)?;
+    fs_err::write(&theirs, right_content)?;
+    let mine = unique_conflict_path(target, labels.target)?;
+    fs_err::write(&mine, target_content)?;
+
+    Ok(MergeConflict { base, mine, theirs })
+}
+
+/// Return a path next to @a target ending in @a suffix that doesn't exist
+/// yet, adding a counter if necessary.
+///
+/// `svn_io_open_uniquely_named`
+fn unique_conflict_path(target: &Path, suffix: &str) -> Result<PathBuf, std::io::Error> {
+    let file_name = target
+        .file_name()
+        .ok_or_else(|| std::io::Error::other(format!("'{}' is not a file", target.display())))?
+        .to_string_lossy()
+        .into_owned();
+
+    let mut candidate = target.with_file_name(format!("{file_name}{suffix}"));
+    let mut i = 2;
+    while candidate.exists() {
+        candidate = target.with_file_name(format!("{file_name}.{i}{suffix}"));
+        i += 1;
+    }
+
+    Ok(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_binary_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &[u8]| {
+            let path = dir.path().join(name);
+            fs_err::write(&path, content).unwrap();
+            path
+        };
+        let left = write("left", b"\0left");
+        let right = write("right", b"\0right");
+        let labels = MergeLabels::default();
+
+        // The target already has the incoming change.
+        let target = write("merged", b"\0right");
+        let outcome = merge_binary_file(&target, &left, &right, &labels).unwrap();
+        assert_eq!(outcome, MergeOutcome::Unchanged);
+
+        // There is no incoming change, local modifications are kept.
+        let target = write("modified", b"\0mine");
+        let outcome = merge_binary_file(&target, &left, &left, &labels).unwrap();
+        assert_eq!(outcome, MergeOutcome::Unchanged);
+        assert_eq!(fs_err::read(&target).unwrap(), b"\0mine");
+        assert!(!dir.path().join("modified.mine").exists());
+
+        // An unmodified target gets replaced.
+        let target = write("unmodified", b"\0left");
+        let outcome = merge_binary_file(&target, &left, &right, &labels).unwrap();
+        assert_eq!(outcome, MergeOutcome::Merged);
+        assert_eq!(fs_err::read(&target).unwrap(), b"\0right");
+
+        // Both sides changed: the target stays as it is, the three
+        // versions are written next to it.
+        let outcome = merge_binary_file(&target, &left, &write("other", b"\0other"), &labels);
+        let MergeOutcome::Conflict(conflict) = outcome.unwrap() else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(fs_err::read(&target).unwrap(), b"\0right");
+        assert_eq!(conflict.base, dir.path().join("unmodified.older"));
+        assert_eq!(fs_err::read(&conflict.base).unwrap(), b"\0left");
+        assert_eq!(fs_err::read(&conflict.mine).unwrap(), b"\0right");
+        assert_eq!(fs_err::read(&conflict.theirs).unwrap(), b"\0other");
+
+        // Existing conflict files are not overwritten.
+        let outcome = merge_binary_file(&target, &left, &write("other", b"\0again"), &labels);
+        let MergeOutcome::Conflict(conflict) = outcome.unwrap() else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(conflict.theirs, dir.path().join("unmodified.2.newer"));
+    }
+
+    #[test]
+    fn test_merge_file_if_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &[u8]| {
+            let path = dir.path().join(name);
+            fs_err::write(&path, content).unwrap();
+            path
+        };
+        let left = write("left", b"a\nb\nc\n");
+        let right = write("right", b"a\nb\nC\n");
+        let labels = MergeLabels::default();
+
+        // Text is left to the caller.
+        let target = write("text", b"A\nb\nc\n");
+        let outcome = merge_file_if_binary(&target, &left, &right, None, &labels).unwrap();
+        assert_eq!(outcome, None);
+        assert_eq!(fs_err::read(&target).unwrap(), b"A\nb\nc\n");
+
+        // The mime-type makes text binary, so it can't be merged.
+        let outcome = merge_file_if_binary(
+            &target,
+            &left,
+            &right,
+            Some("application/octet-stream"),
+            &labels,
+        )
+        .unwrap();
+        assert!(matches!(outcome, Some(MergeOutcome::Conflict(_))));
+        assert_eq!(fs_err::read(&target).unwrap(), b"A\nb\nc\n");
+
+        // Without a mime-type, binary content on any side is sniffed.
+        let target = write("sniffed", b"a\nb\nc\n");
+        let binary = write("binary", b"a\0b\nC\n");
+        let outcome = merge_file_if_binary(&target, &left, &binary, None, &labels).unwrap();
+        assert_eq!(outcome, Some(MergeOutcome::Merged));
+        assert_eq!(fs_err::read(&target).unwrap(), b"a\0b\nC\n");
+    }
+}
@@ -1,3 +1,13 @@
+use std::fmt;
+use std::io::Write;
+
+use imara_diff::{
+    Algorithm, Diff, InternedInput, Interner, Token, UnifiedDiffConfig, UnifiedDiffPrinter,
+};
+
+use crate::EQUAL_STR;
+use crate::binary_diff::{is_binary_content, output_binary, output_cannot_display};
+
 /** Options to control the behaviour of the file diff routines.
  *
  * @since New in 1.4.
@@ -16,3 +26,179 @@ pub struct DiffFileOptions {
     show_c_function: bool,
     context_size: i32,
 }
+
+/// One side of a file diff.
+#[derive(Debug, Clone, Copy)]
+pub struct DiffFileVersion<'a> {
+    /// the file's contents
+    pub content: &'a [u8],
+    /// its `svn:mime-type`, if set
+    pub mime_type: Option<&'a str>,
+    /// how to name this side in the diff header, e.g. `revision 3`
+    pub label: &'a str,
+}
+
+/// Write the diff of the file at @a path from @a original to @a latest to
+/// @a output, preceded by its `Index:` header.  Nothing gets written if
+/// the contents are the same.
+///
+/// If either side is binary, as told by its mime-type or by sniffing its
+/// content, a git binary patch is written when @a use_git_diff_format is
+/// set, and a note that the file cannot be displayed otherwise.  Text
+/// gets a unified diff.
+///
+/// `diff_content_changed`
+pub fn output_file_diff<W: Write>(
+    output: &mut W,
+    path: &str,
+    original: &DiffFileVersion<'_>,
+    latest: &DiffFileVersion<'_>,
+    use_git_diff_format: bool,
+) -> Result<(), std::io::Error> {
+    if original.content == latest.content {
+        return Ok(());
+    }
+
+    write!(output, "Index: {path}\n{EQUAL_STR}\n")?;
+    if use_git_diff_format {
+        writeln!(output, "diff --git a/{path} b/{path}")?;
+    }
+
+    let is_binary = is_binary_content(original.mime_type, original.content)
+        || is_binary_content(latest.mime_type, latest.content);
+    if is_binary {
+        if use_git_diff_format {
+            return output_binary(output, original.content, latest.content);
+        }
+        return output_cannot_display(output, original.mime_type, latest.mime_type);
+    }
+
+    let (original_path, latest_path) = if use_git_diff_format {
+        (format!("a/{path}"), format!("b/{path}"))
+    } else {
+        (path.to_string(), path.to_string())
+    };
+    writeln!(output, "--- {original_path}\t({})", original.label)?;
+    writeln!(output, "+++ {latest_path}\t({})", latest.label)?;
+
+    let input = InternedInput::new(original.content, latest.content);
+    let mut diff = Diff::compute(Algorithm::Myers, &input);
+    diff.postprocess_lines(&input);
+    let printer = UnifiedPrinter(&input.interner);
+    let unified = diff.unified_diff(&printer, UnifiedDiffConfig::default(), &input);
+    write!(output, "{unified}")
+}
+
+/// Prints the hunks of a unified diff of lines, marking a last line
+/// without a newline.
+///
+/// `svn_diff_file_output_unified4`
+struct UnifiedPrinter<'a>(&'a Interner<&'a [u8]>);
+
+impl UnifiedPrinter<'_> {
+    /// Write @a token, prefixed by @a prefix.
+    fn write_line(&self, mut f: impl fmt::Write, prefix: char, token: Token) -> fmt::Result {
+        let line = self.0[token];
+        write!(f, "{prefix}{}", String::from_utf8_lossy(line))?;
+        if !line.ends_with(b"\n") {
+            write!(f, "\n\\ No newline at end of file\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl UnifiedDiffPrinter for UnifiedPrinter<'_> {
+    fn display_header(
+        &self,
+        mut f: impl fmt::Write,
+        start_before: u32,
+        start_after: u32,
+        len_before: u32,
+        len_after: u32,
+    ) -> fmt::Result {
+        // Empty ranges start at the line before them.
+        let start = |start: u32, len: u32| if len == 0 { start } else { start + 1 };
+        writeln!(
+            f,
+            "@@ -{},{len_before} +{},{len_after} @@",
+            start(start_before, len_before),
+            start(start_after, len_after),
+        )
+    }
+
+    fn display_context_token(&self, f: impl fmt::Write, token: Token) -> fmt::Result {
+        self.write_line(f, ' ', token)
+    }
+
+    fn display_hunk(
+        &self,
+        mut f: impl fmt::Write,
+        before: &[Token],
+        after: &[Token],
+    ) -> fmt::Result {
+        for &token in before {
+            self.write_line(&mut f, '-', token)?;
+        }
+        for &token in after {
+            self.write_line(&mut f, '+', token)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(original: &[u8], latest: &[u8], mime_type: Option<&str>, git: bool) -> String {
+        let original = DiffFileVersion {
+            content: original,
+            mime_type,
+            label: "revision 1",
+        };
+        let latest = DiffFileVersion {
+            content: latest,
+            mime_type,
+            label: "working copy",
+        };
+        let mut output = Vec::new();
+        output_file_diff(&mut output, "dir/file", &original, &latest, git).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_output_file_diff() {
+        assert_eq!(diff(b"same\n", b"same\n", None, false), "");
+
+        assert_eq!(
+            diff(b"a\nb\nc\n", b"a\nB\nc", None, false),
+            format!(
+                "Index: dir/file\n{EQUAL_STR}\n\
+                 --- dir/file\t(revision 1)\n\
+                 +++ dir/file\t(working copy)\n\
+                 @@ -1,3 +1,3 @@\n \
+                 a\n\
+                 -b\n\
+                 -c\n\
+                 +B\n\
+                 +c\n\
+                 \\ No newline at end of file\n"
+            )
+        );
+
+        // Binary content is detected by sniffing or by its mime-type.
+        let expected = format!(
+            "Index: dir/file\n{EQUAL_STR}\nCannot display: file marked as a binary type.\n"
+        );
+        assert_eq!(diff(b"a\0", b"b\0", None, false), expected);
+        assert_eq!(
+            diff(b"a\n", b"b\n", Some("image/png"), false),
+            format!("{expected}svn:mime-type = image/png\n")
+        );
+
+        let git = diff(b"a\0", b"b\0", None, true);
+        assert!(git.starts_with(&format!(
+            "Index: dir/file\n{EQUAL_STR}\ndiff --git a/dir/file b/dir/file\nGIT binary patch\nliteral 2\n"
+        )));
+    }
+}
@@ -5,11 +5,16 @@ pub mod parse_diff;
 mod file;
 
 pub mod binary_diff;
+pub mod binary_merge;
+pub mod merge;
 
 pub mod diff;
 pub mod diff_file;
 mod parse;
 pub use diff_file::DiffFileOptions;
+pub use diff_file::DiffFileVersion;
+pub use diff_file::output_file_diff;
+pub use merge::merge_file;
 pub use parse::DiffPatchParser;
 
 pub mod tree;
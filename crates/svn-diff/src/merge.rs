@@ -0,0 +1,347 @@
+//! three-way merges of files
+//!
+//! Text is merged line by line, leaving conflict markers in the target
+//! where both sides changed the same lines differently.  Binary content,
+//! as told by the `svn:mime-type` or by sniffing, is handed to
+//! [`crate::binary_merge`] instead.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use imara_diff::{Algorithm, Diff, InternedInput};
+
+use crate::binary_merge::{merge_if_binary, write_conflict_files};
+
+/// The outcome of a merge.
+///
+/// `svn_wc_merge_outcome_t`
+#[derive(Debug, PartialEq, Eq)]
+pub enum MergeOutcome {
+    /// Nothing had to be done: there is no incoming change, or the target
+    /// already contains it.
+    Unchanged,
+    /// The incoming change has been applied to the target.
+    Merged,
+    /// Both sides changed the content differently.
+    Conflict(MergeConflict),
+}
+
+/// The files written next to a conflicted target.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// Copy of the older (left/base) version.
+    pub base: PathBuf,
+    /// Copy of the local (target) version.
+    pub mine: PathBuf,
+    /// Copy of the incoming (right) version.
+    pub theirs: PathBuf,
+}
+
+/// Labels used to name the conflict files created next to the target,
+/// e.g. `.merge-left.r3`, `.merge-right.r5` and `.working`, and to mark
+/// the sides of text conflicts.
+#[derive(Debug, Clone, Copy)]
+pub struct MergeLabels<'a> {
+    pub left: &'a str,
+    pub right: &'a str,
+    pub target: &'a str,
+}
+
+impl Default for MergeLabels<'_> {
+    fn default() -> Self {
+        Self {
+            left: ".older",
+            right: ".newer",
+            target: ".mine",
+        }
+    }
+}
+
+/// Merge the changes between @a left and @a right into the file at
+/// @a target.
+///
+/// The content is binary if @a mime_type, the `svn:mime-type` of the
+/// target, says so or, without one, if any of the three versions looks
+/// binary.  Binary files are merged as in
+/// [`crate::binary_merge::merge_file_if_binary`], text files line by line.
+/// Conflicting text changes are marked in @a target using @a labels, and
+/// the three versions copied next to it.
+///
+/// `svn_wc__internal_merge`
+pub fn merge_file(
+    target: &Path,
+    left: &Path,
+    right: &Path,
+    mime_type: Option<&str>,
+    labels: &MergeLabels<'_>,
+) -> Result<MergeOutcome, std::io::Error> {
+    let target_content = fs_err::read(target)?;
+    let left_content = fs_err::read(left)?;
+    let right_content = fs_err::read(right)?;
+
+    if let Some(outcome) = merge_if_binary(
+        target,
+        &target_content,
+        &left_content,
+        &right_content,
+        mime_type,
+        labels,
+    )? {
+        return Ok(outcome);
+    }
+
+    if left_content == right_content || target_content == right_content {
+        return Ok(MergeOutcome::Unchanged);
+    }
+    if target_content == left_content {
+        fs_err::write(target, &right_content)?;
+        return Ok(MergeOutcome::Merged);
+    }
+
+    let (merged, conflicted) = merge_text(&left_content, &target_content, &right_content, labels);
+    fs_err::write(target, &merged)?;
+    if !conflicted {
+        return Ok(MergeOutcome::Merged);
+    }
+
+    write_conflict_files(
+        target,
+        &target_content,
+        &left_content,
+        &right_content,
+        labels,
+    )
+    .map(MergeOutcome::Conflict)
+}
+
+/// Return the lines of @a content, each with its line ending.
+fn lines(content: &[u8]) -> Vec<&[u8]> {
+    content.split_inclusive(|&b| b == b'\n').collect()
+}
+
+/// Return the changes from @a base to @a modified, as pairs of line
+/// ranges in both.
+fn changes(base: &[u8], modified: &[u8]) -> Vec<(Range<usize>, Range<usize>)> {
+    let input = InternedInput::new(base, modified);
+    let mut diff = Diff::compute(Algorithm::Histogram, &input);
+    diff.postprocess_lines(&input);
+    diff.hunks()
+        .map(|hunk| {
+            (
+                hunk.before.start as usize..hunk.before.end as usize,
+                hunk.after.start as usize..hunk.after.end as usize,
+            )
+        })
+        .collect()
+}
+
+/// Return the lines of @a side covering @a region of the base, given the
+/// @a changes of @a side within that region.
+fn side_lines<'a>(
+    base: &[&'a [u8]],
+    side: &[&'a [u8]],
+    changes: &[(Range<usize>, Range<usize>)],
+    region: &Range<usize>,
+) -> Vec<&'a [u8]> {
+    match (changes.first(), changes.last()) {
+        (Some((first_base, first_side)), Some((last_base, last_side))) => {
+            let start = first_side.start - (first_base.start - region.start);
+            let end = last_side.end + (region.end - last_base.end);
+            side[start..end].to_vec()
+        }
+        _ => base[region.clone()].to_vec(),
+    }
+}
+
+/// Append @a lines to @a output, making sure a conflict marker written
+/// after them starts on a line of its own.
+fn push_lines(output: &mut Vec<u8>, lines: &[&[u8]]) {
+    for line in lines {
+        output.extend_from_slice(line);
+    }
+    if !output.is_empty() && !output.ends_with(b"\n") {
+        output.push(b'\n');
+    }
+}
+
+/// Merge the changes from @a base to @a latest into @a modified.  Return
+/// the merged content and whether it has conflicts, marked up with
+/// @a labels.
+///
+/// `svn_diff_file_output_merge3`
+fn merge_text(
+    base: &[u8],
+    modified: &[u8],
+    latest: &[u8],
+    labels: &MergeLabels<'_>,
+) -> (Vec<u8>, bool) {
+    let base_lines = lines(base);
+    let modified_lines = lines(modified);
+    let latest_lines = lines(latest);
+    let modified_changes = changes(base, modified);
+    let latest_changes = changes(base, latest);
+
+    let mut output = Vec::new();
+    let mut conflicted = false;
+    let (mut i, mut j) = (0, 0);
+    let mut pos = 0;
+    loop {
+        // Start a region at the next change of either side and grow it
+        // while changes of the other side overlap or touch it.
+        let start = match (modified_changes.get(i), latest_changes.get(j)) {
+            (Some(m), Some(l)) => m.0.start.min(l.0.start),
+            (Some(m), None) => m.0.start,
+            (None, Some(l)) => l.0.start,
+            (None, None) => break,
+        };
+        let mut region = start..start;
+        let (first_i, first_j) = (i, j);
+        loop {
+            if let Some(m) = modified_changes.get(i)
+                && m.0.start <= region.end
+            {
+                region.end = region.end.max(m.0.end);
+                i += 1;
+            } else if let Some(l) = latest_changes.get(j)
+                && l.0.start <= region.end
+            {
+                region.end = region.end.max(l.0.end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        output.extend(base_lines[pos..region.start].concat());
+        pos = region.end;
+
+        let modified_region = side_lines(
+            &base_lines,
+            &modified_lines,
+            &modified_changes[first_i..i],
+            &region,
+        );
+        let latest_region = side_lines(
+            &base_lines,
+            &latest_lines,
+            &latest_changes[first_j..j],
+            &region,
+        );
+        if first_j == j || modified_region == latest_region {
+            output.extend(modified_region.concat());
+        } else if first_i == i {
+            output.extend(latest_region.concat());
+        } else {
+            conflicted = true;
+            if !output.is_empty() && !output.ends_with(b"\n") {
+                output.push(b'\n');
+            }
+            output.extend_from_slice(format!("<<<<<<< {}\n", labels.target).as_bytes());
+            push_lines(&mut output, &modified_region);
+            output.extend_from_slice(format!("||||||| {}\n", labels.left).as_bytes());
+            push_lines(&mut output, &base_lines[region.clone()]);
+            output.extend_from_slice(b"=======\n");
+            push_lines(&mut output, &latest_region);
+            output.extend_from_slice(format!(">>>>>>> {}\n", labels.right).as_bytes());
+        }
+    }
+    output.extend(base_lines[pos..].concat());
+
+    (output, conflicted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(base: &str, modified: &str, latest: &str) -> (String, bool) {
+        let labels = MergeLabels::default();
+        let (merged, conflicted) = merge_text(
+            base.as_bytes(),
+            modified.as_bytes(),
+            latest.as_bytes(),
+            &labels,
+        );
+        (String::from_utf8(merged).unwrap(), conflicted)
+    }
+
+    #[test]
+    fn test_merge_text() {
+        // Changes to different lines are combined.
+        assert_eq!(
+            merge("a\nb\nc\nd\ne\n", "A\nb\nc\nd\ne\n", "a\nb\nc\nd\nE\n"),
+            ("A\nb\nc\nd\nE\n".to_string(), false)
+        );
+
+        // Identical changes on both sides are not a conflict.
+        assert_eq!(
+            merge("a\nb\nc\n", "a\nB\nc\nx\n", "a\nB\nc\n"),
+            ("a\nB\nc\nx\n".to_string(), false)
+        );
+
+        // Markers start on lines of their own, even after a last line
+        // without a newline.
+        assert_eq!(
+            merge("a\nb", "a\nmine", "a\ntheirs"),
+            (
+                "a\n<<<<<<< .mine\nmine\n||||||| .older\nb\n=======\ntheirs\n>>>>>>> .newer\n"
+                    .to_string(),
+                true
+            )
+        );
+
+        // Different changes to the same lines are.
+        assert_eq!(
+            merge("a\nb\nc\n", "a\nmine\nc\n", "a\ntheirs\nc\n"),
+            (
+                "a\n<<<<<<< .mine\nmine\n||||||| .older\nb\n=======\ntheirs\n>>>>>>> .newer\nc\n"
+                    .to_string(),
+                true
+            )
+        );
+    }
+
+    #[test]
+    fn test_merge_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &[u8]| {
+            let path = dir.path().join(name);
+            fs_err::write(&path, content).unwrap();
+            path
+        };
+        let left = write("left", b"a\nb\nc\n");
+        let right = write("right", b"a\nb\nC\n");
+        let labels = MergeLabels::default();
+
+        let target = write("text", b"A\nb\nc\n");
+        let outcome = merge_file(&target, &left, &right, None, &labels).unwrap();
+        assert_eq!(outcome, MergeOutcome::Merged);
+        assert_eq!(fs_err::read(&target).unwrap(), b"A\nb\nC\n");
+
+        // The mime-type makes text binary, so it can't be merged.
+        let target = write("binary", b"A\nb\nc\n");
+        let outcome = merge_file(
+            &target,
+            &left,
+            &right,
+            Some("application/octet-stream"),
+            &labels,
+        )
+        .unwrap();
+        assert!(matches!(outcome, MergeOutcome::Conflict(_)));
+        assert_eq!(fs_err::read(&target).unwrap(), b"A\nb\nc\n");
+
+        // Text conflicts get marked in the target.
+        let target = write("conflict", b"a\nb\nX\n");
+        let outcome = merge_file(&target, &left, &right, None, &labels).unwrap();
+        let MergeOutcome::Conflict(conflict) = outcome else {
+            panic!("expected a conflict, got {outcome:?}");
+        };
+        assert_eq!(fs_err::read(&conflict.mine).unwrap(), b"a\nb\nX\n");
+        assert!(
+            String::from_utf8(fs_err::read(&target).unwrap())
+                .unwrap()
+                .contains("<<<<<<< .mine\nX\n")
+        );
+    }
+}
@@ -19,6 +19,135 @@ pub enum BackendError {
     #[error(transparent)]
     Config(#[from] svn_subr::config::ConfigError),
 
+    #[error(transparent)]
+    Uuid(#[from] uuid::Error),
+
+    #[error(transparent)]
+    Hash(#[from] svn_subr::hash::HashError),
+
+    #[error(transparent)]
+    Cache(#[from] svn_subr::CacheError),
+
+    #[error("Corrupt format file '{path}': {message}")]
+    CorruptFormat { path: String, message: String },
+
+    #[error("Corrupt '{path}' file: {message}")]
+    CorruptFile { path: String, message: String },
+
+    #[error("Expected FS format between '1' and '{expected}'; found format '{found}'")]
+    UnsupportedFormat { expected: u32, found: u32 },
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("{0}")]
+    Corrupt(String),
+
+    #[error("No such revision {0}")]
+    NoSuchRevision(svn_types::RevisionNumber),
+
+    #[error("Invalid revision number '{0}'")]
+    InvalidRevision(svn_types::RevisionNumber),
+
+    #[error(transparent)]
+    Id(#[from] crate::id::IdError),
+
+    #[error(transparent)]
+    Svndiff(#[from] svn_delta::SvndiffError),
+
+    #[error(transparent)]
+    Checksum(#[from] svn_subr::ChecksumError),
+
+    #[error("'{0}' is not a directory")]
+    NotDirectory(String),
+
+    #[error("'{0}' is not a file")]
+    NotFile(String),
+
+    #[error("No such transaction '{0}'")]
+    NoSuchTransaction(String),
+
+    #[error("Transaction '{txn}' out-of-date with respect to revision '{rev}'")]
+    TxnOutOfDate {
+        txn: String,
+        rev: svn_types::RevisionNumber,
+    },
+
+    #[error("Conflict at '{0}'")]
+    Conflict(String),
+
+    #[error("Root object must be a transaction root")]
+    NotTxnRoot,
+
+    #[error("Root object must be a revision root")]
+    NotRevisionRoot,
+
+    #[error("The root directory cannot be deleted")]
+    RootDir,
+
+    #[error(
+        "Cannot write to the prototype revision file of transaction '{0}' because a previous representation is currently being written by this process"
+    )]
+    RepBeingWritten(String),
+
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("Rep-sharing is not allowed")]
+    RepSharingNotAllowed,
+
+    #[error("{0}")]
+    UnsupportedFeature(String),
+
+    #[error("Caught signal")]
+    Cancelled,
+
+    #[error(
+        "Failed to get exclusive repository access; perhaps another process such as httpd, svnserve or svn has it open?"
+    )]
+    ExclusiveAccess,
+
+    #[error("Cannot {action} path '{path}', no authenticated username available")]
+    NoUser { action: &'static str, path: String },
+
+    #[error("Path '{0}' doesn't exist in HEAD revision")]
+    PathNotInHead(String),
+
+    #[error("Lock failed: newer version of '{0}' exists")]
+    LockOutOfDate(String),
+
+    #[error("Path '{path}' is already locked by user '{owner}' in filesystem '{fs}'")]
+    PathAlreadyLocked {
+        path: String,
+        owner: String,
+        fs: String,
+    },
+
+    #[error("No lock on path '{0}'")]
+    NoSuchLock(String),
+
+    #[error("Lock token '{0}' is not a valid 'opaquelocktoken:' URI")]
+    MalformedLockToken(String),
+
+    #[error("Cannot verify lock on path '{0}'; no matching lock-token available")]
+    BadLockToken(String),
+
+    #[error("User '{user}' does not own lock on path '{path}' (currently locked by '{owner}')")]
+    LockOwnerMismatch {
+        user: String,
+        path: String,
+        owner: String,
+    },
+
+    #[error("revprop '{0}' has unexpected value in filesystem")]
+    PropBaseValueMismatch(String),
+
+    #[error(transparent)]
+    Time(#[from] svn_subr::time::TimeError),
+
+    #[error(transparent)]
+    Compress(#[from] svn_subr::compress::CompressError),
+
     #[error(transparent)]
     Caching(#[from] crate::backend::fsfs::CachingError),
 }
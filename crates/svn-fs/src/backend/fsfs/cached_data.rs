@@ -0,0 +1,962 @@
+//! `subversion/libsvn_fs_fs/cached_data.c`
+//!
+//! Read node-revisions, representation headers and changed-paths lists
+//! from revision and pack files.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io::Read;
+
+use svn_delta::TxdeltaWindow;
+use svn_delta::compose_delta;
+use svn_delta::svndiff;
+use svn_subr::Checksum;
+use svn_subr::ChecksumContext;
+use svn_subr::ChecksumKind;
+use svn_subr::SvnCache;
+use svn_subr::hash::HASH_TERMINATOR;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+
+use super::super::BackendError;
+use super::super::fsx;
+use super::FsFsBackend;
+use super::data::CachedWindow;
+use super::data::PairCacheKey;
+use super::data::WindowCacheKey;
+use super::index::SVN_FS_FS__ITEM_INDEX_CHANGES;
+use super::index::SVN_FS_FS__ITEM_INDEX_ROOT_NODE;
+use super::low_level;
+use super::low_level::RepHeader;
+use super::low_level::RepType;
+use super::rev_file::RevisionFile;
+use crate::DirectoryEntry;
+use crate::NodeRevision;
+use crate::PathChange;
+use crate::Representation;
+use crate::backend::PATH_MANIFEST;
+use crate::id::FsId;
+use crate::id::IdPart;
+
+/// How many bytes from the end of a revision we read when looking for the
+/// trailer of a physically addressed revision.
+const TRAILER_SIZE: u64 = 64;
+
+/// How many bytes of a PLAIN representation we read at once.
+const PLAIN_CHUNK_SIZE: usize = 0x4000;
+
+/// Return the length of the header block at the start of @a data, i.e.
+/// up to and including the terminating empty line, if @a data covers it.
+pub(super) fn header_block_len(data: &[u8]) -> Option<usize> {
+    data.windows(2)
+        .position(|w| w == b"\n\n")
+        .map(|pos| pos + 2)
+}
+
+/// Return the length of the single line at the start of @a data,
+/// including its newline, if @a data covers it.
+pub(super) fn line_len(data: &[u8]) -> Option<usize> {
+    data.iter().position(|&b| b == b'\n').map(|pos| pos + 1)
+}
+
+/// Return the length of the changed-paths list at the start of @a data,
+/// including the terminating empty line, if @a data covers it.  Every change
+/// takes two lines, the second of which (the copy source) may be empty.
+fn changes_len(data: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let first = line_len(&data[pos..])?;
+        if first == 1 {
+            return Some(pos + 1);
+        }
+        pos += first;
+        pos += line_len(&data[pos..])?;
+    }
+}
+
+/// One link in the chain of representations that has to be combined to
+/// reconstruct a fulltext.
+///
+/// `rep_state_t`
+struct RepState {
+    /// the rev / pack file the rep lives in
+    file: RevisionFile,
+    /// the header of the rep
+    header: RepHeader,
+    /// offset of the rep data, i.e. behind its header
+    start: u64,
+    /// size of the rep data, i.e. excluding header and trailing "ENDREP"
+    size: u64,
+    /// the cache key of the rep, unless it is part of a transaction
+    key: Option<PairCacheKey>,
+}
+
+impl RepState {
+    /// Read the complete data of this rep.
+    fn read_data(&mut self) -> Result<Vec<u8>, BackendError> {
+        let data = self.file.read_at(self.start, self.size as usize)?;
+        if data.len() as u64 != self.size {
+            return Err(BackendError::Corrupt(format!(
+                "Unexpected end of representation data at offset {}",
+                self.start
+            )));
+        }
+        Ok(data)
+    }
+}
+
+/// The svndiff data of a DELTA representation in a delta chain and how
+/// far it has been read.  The data is only read once a window is not
+/// found in the cache.
+struct DeltaState {
+    /// the DELTA rep
+    state: RepState,
+    /// the svndiff data of the rep, header included, once read
+    data: Option<Vec<u8>>,
+    /// the svndiff version, once @a data has been read
+    version: u8,
+    /// the position of the next window in the svndiff data
+    pos: usize,
+    /// the index of the next window in the svndiff data
+    chunk_index: usize,
+    /// the cache of delta windows, if the rep may be cached
+    window_cache: Option<SvnCache<WindowCacheKey, CachedWindow>>,
+}
+
+impl DeltaState {
+    /// Prepare reading the windows of the DELTA rep @a state, looking
+    /// them up in @a window_cache first.
+    fn new(state: RepState, window_cache: Option<SvnCache<WindowCacheKey, CachedWindow>>) -> Self {
+        let window_cache = window_cache.filter(|_| state.key.is_some());
+        Self {
+            state,
+            data: None,
+            version: 0,
+            // All svndiff versions share the header length.
+            pos: svndiff::header(0).len(),
+            chunk_index: 0,
+            window_cache,
+        }
+    }
+
+    /// Return window number @a chunk_index of this delta, or `None` if
+    /// there are fewer windows.  Windows before it that have not been read
+    /// yet get skipped.
+    ///
+    /// `read_delta_window`
+    fn read_window(&mut self, chunk_index: usize) -> Result<Option<TxdeltaWindow>, BackendError> {
+        let key = self
+            .state
+            .key
+            .map(|rep| WindowCacheKey { rep, chunk_index });
+        if let (Some(cache), Some(key)) = (&self.window_cache, &key)
+            && let Some(cached) = cache.get(key)?
+        {
+            // Continue behind the cached window.
+            self.pos = cached.end_offset;
+            self.chunk_index = chunk_index + 1;
+            return Ok(Some(cached.window));
+        }
+
+        if self.pos as u64 >= self.state.size {
+            return Ok(None);
+        }
+        if self.data.is_none() {
+            let data = self.state.read_data()?;
+            self.version = svndiff::parse_header(&data)?;
+            self.data = Some(data);
+        }
+        let data = self.data.as_deref().unwrap_or_default();
+
+        // Skip windows to reach the one we want.
+        while self.chunk_index < chunk_index && self.pos < data.len() {
+            svndiff::read_window(data, &mut self.pos, self.version)?;
+            self.chunk_index += 1;
+        }
+
+        if self.chunk_index != chunk_index || self.pos >= data.len() {
+            return Ok(None);
+        }
+
+        let window = svndiff::read_window(data, &mut self.pos, self.version)?;
+        self.chunk_index += 1;
+
+        if let (Some(cache), Some(key)) = (&self.window_cache, key) {
+            cache.set(
+                key,
+                CachedWindow {
+                    window: window.clone(),
+                    end_offset: self.pos,
+                },
+            )?;
+        }
+
+        Ok(Some(window))
+    }
+}
+
+/// Return window number @a chunk_index of the first delta in @a deltas,
+/// combined with the matching windows of the deltas it is based on for
+/// as long as it still refers to its source.  Return `None` once all
+/// windows have been read.
+///
+/// `get_combined_window`
+fn get_combined_window(
+    deltas: &mut [DeltaState],
+    chunk_index: usize,
+) -> Result<Option<TxdeltaWindow>, BackendError> {
+    let Some((first, rest)) = deltas.split_first_mut() else {
+        return Ok(None);
+    };
+    let Some(mut window) = first.read_window(chunk_index)? else {
+        return Ok(None);
+    };
+
+    // Combine in the windows from the other delta reps, if needed.
+    for state in rest {
+        if window.src_ops == 0 {
+            break;
+        }
+
+        let lower = state.read_window(chunk_index)?.ok_or_else(|| {
+            BackendError::Corrupt("Delta window refers to data beyond its base".to_string())
+        })?;
+        window = compose_delta::compose_windows(&lower, &window);
+    }
+
+    Ok(Some(window))
+}
+
+/// Where the data of a [`RepReader`] comes from.
+enum RepSource {
+    /// a PLAIN rep: copy the data from the rev / pack file
+    Plain {
+        /// the file containing the rep
+        file: RevisionFile,
+        /// the offset of the next data to read
+        offset: u64,
+        /// the number of bytes left to read
+        remaining: u64,
+    },
+    /// a DELTA rep: combine the windows of the delta chain one by one
+    /// and apply them to the base
+    Delta {
+        /// the DELTA reps of the chain, starting with the rep being read
+        deltas: Vec<DeltaState>,
+        /// the PLAIN rep at the end of the chain, if there is one
+        base: Option<RepState>,
+        /// the index of the next window to produce
+        chunk_index: usize,
+    },
+}
+
+/// A stream over the fulltext of a representation.  The MD5 checksum of
+/// the contents is verified once all data has been read.
+///
+/// `rep_read_baton`
+pub(crate) struct RepReader {
+    source: RepSource,
+    /// decoded data not yet handed out
+    buf: Vec<u8>,
+    /// the number of bytes in @a buf already handed out
+    buf_pos: usize,
+    /// the checksum of all data handed out so far
+    md5_ctx: Option<ChecksumContext>,
+    /// the expected MD5 checksum of the fulltext, if known
+    md5_digest: Option<Checksum>,
+    /// where to put the fulltext once it has been read completely
+    fulltext_cache: Option<(SvnCache<PairCacheKey, Vec<u8>>, PairCacheKey)>,
+    /// the data read so far, if it is to be cached
+    current_fulltext: Vec<u8>,
+}
+
+impl RepReader {
+    /// A reader producing the empty fulltext.
+    pub(crate) fn empty() -> Self {
+        Self::from_fulltext(Vec::new())
+    }
+
+    /// A reader producing the already known @a fulltext.
+    fn from_fulltext(fulltext: Vec<u8>) -> Self {
+        Self {
+            source: RepSource::Delta {
+                deltas: Vec::new(),
+                base: None,
+                chunk_index: 0,
+            },
+            buf: fulltext,
+            buf_pos: 0,
+            md5_ctx: None,
+            md5_digest: None,
+            fulltext_cache: None,
+            current_fulltext: Vec::new(),
+        }
+    }
+
+    /// Refill @a buf from the source.  Return `false` at the end of the
+    /// data.
+    fn fill(&mut self) -> Result<bool, BackendError> {
+        self.buf.clear();
+        self.buf_pos = 0;
+
+        match &mut self.source {
+            RepSource::Plain {
+                file,
+                offset,
+                remaining,
+            } => {
+                if *remaining == 0 {
+                    return Ok(false);
+                }
+                let len = (*remaining).min(PLAIN_CHUNK_SIZE as u64) as usize;
+                self.buf = file.read_at(*offset, len)?;
+                if self.buf.len() != len {
+                    return Err(BackendError::Corrupt(format!(
+                        "Unexpected end of representation data at offset {offset}"
+                    )));
+                }
+                *offset += len as u64;
+                *remaining -= len as u64;
+            }
+            RepSource::Delta {
+                deltas,
+                base,
+                chunk_index,
+            } => {
+                let Some(window) = get_combined_window(deltas, *chunk_index)? else {
+                    return Ok(false);
+                };
+                *chunk_index += 1;
+
+                // Read the source view from the PLAIN base, if the
+                // combined window still needs it.
+                let sbuf = match base {
+                    Some(base) if window.src_ops > 0 => {
+                        if (window.sview_offset + window.sview_length) as u64 > base.size {
+                            return Err(BackendError::Corrupt(
+                                "Delta window refers to data beyond its base".to_string(),
+                            ));
+                        }
+                        let sbuf = base.file.read_at(
+                            base.start + window.sview_offset as u64,
+                            window.sview_length,
+                        )?;
+                        if sbuf.len() != window.sview_length {
+                            return Err(BackendError::Corrupt(format!(
+                                "Unexpected end of representation data at offset {}",
+                                base.start
+                            )));
+                        }
+                        sbuf
+                    }
+                    None if window.src_ops > 0 => {
+                        return Err(BackendError::Corrupt(
+                            "Delta window refers to a missing base".to_string(),
+                        ));
+                    }
+                    _ => Vec::new(),
+                };
+                window.apply_instructions(&sbuf, &mut self.buf);
+            }
+        }
+
+        if let Some(ctx) = &mut self.md5_ctx {
+            ctx.update(&self.buf);
+        }
+        if self.fulltext_cache.is_some() {
+            self.current_fulltext.extend_from_slice(&self.buf);
+        }
+        Ok(true)
+    }
+
+    /// Check the MD5 checksum of the data read against the expected one,
+    /// then cache the fulltext if so requested.
+    fn verify(&mut self) -> Result<(), BackendError> {
+        if let (Some(ctx), Some(expected)) = (self.md5_ctx.take(), &self.md5_digest) {
+            expected.verify(&ctx.finish())?;
+        }
+        if let Some((cache, key)) = self.fulltext_cache.take() {
+            cache.set(key, std::mem::take(&mut self.current_fulltext))?;
+        }
+        Ok(())
+    }
+}
+
+impl Read for RepReader {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        while self.buf_pos == self.buf.len() {
+            let more = self.fill().map_err(std::io::Error::other)?;
+            if !more {
+                self.verify()
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                return Ok(0);
+            }
+        }
+
+        let len = out.len().min(self.buf.len() - self.buf_pos);
+        out[..len].copy_from_slice(&self.buf[self.buf_pos..self.buf_pos + len]);
+        self.buf_pos += len;
+        Ok(len)
+    }
+}
+
+/// Set the expanded size of @a rep to the actual fulltext size: a 0 in
+/// the rev file means "same as the rep size" unless the rep is empty.
+///
+/// `svn_fs_fs__fixup_expanded_size`
+pub(crate) fn fixup_expanded_size(rep: &mut Representation) {
+    // If we already have a non-zero expanded size, we are done.
+    if rep.expanded_size != 0 {
+        return;
+    }
+
+    // If the MD5 checksum matches the empty string, the expanded size
+    // is 0 as well.
+    let empty_md5 = ChecksumKind::Md5.empty_string_digest_array();
+    if rep
+        .md5_digest
+        .as_ref()
+        .is_some_and(|md5| md5.digest() == empty_md5)
+    {
+        return;
+    }
+
+    // Data in the rep-cache.db does not have MD5 checksums (all zero).
+    // Those reps are PLAIN reps with their expanded size equal to size.
+    rep.expanded_size = rep.size;
+}
+
+/// Parse the directory listing @a hash of the directory node-revision
+/// @a noderev_id, which only serves to name it in errors.
+///
+/// `read_dir_entries`
+pub(crate) fn parse_dir_entries(
+    hash: HashMap<String, String>,
+    noderev_id: &dyn std::fmt::Display,
+) -> Result<BTreeMap<String, DirectoryEntry>, BackendError> {
+    let corrupt = || BackendError::Corrupt(format!("Directory entry corrupt in '{noderev_id}'"));
+
+    let mut entries = BTreeMap::new();
+    for (name, value) in hash {
+        let (kind, id) = value.split_once(' ').ok_or_else(corrupt)?;
+        let kind = match kind {
+            "file" => NodeKind::File,
+            "dir" => NodeKind::Directory,
+            _ => return Err(corrupt()),
+        };
+        let id = id.parse()?;
+
+        entries.insert(name.clone(), DirectoryEntry { name, id, kind });
+    }
+
+    Ok(entries)
+}
+
+/// Parse the property list @a contents, which may be empty.
+fn parse_proplist(contents: &[u8]) -> Result<HashMap<String, String>, BackendError> {
+    if contents.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let (proplist, _) = svn_subr::hash::read_hash(contents, HASH_TERMINATOR)?;
+    Ok(proplist)
+}
+
+impl FsFsBackend {
+    /// Get the offset of revision @a rev within its pack file.
+    ///
+    /// `get_packed_offset`
+    pub(crate) fn get_packed_offset(&self, rev: RevisionNumber) -> Result<u64, BackendError> {
+        let shard_size = self._data().max_files_per_dir as RevisionNumber;
+        let path = self.path_rev_pack_shard(rev).join(PATH_MANIFEST);
+        let manifest = fs_err::read_to_string(&path)?;
+
+        let corrupt = || BackendError::CorruptFile {
+            path: path.to_string_lossy().to_string(),
+            message: format!("no valid offset for r{rev}"),
+        };
+        let line = manifest
+            .lines()
+            .nth((rev % shard_size) as usize)
+            .ok_or_else(corrupt)?;
+        line.trim().parse().map_err(|_| corrupt())
+    }
+
+    /// Return the offsets of the root node-revision and the changed-paths
+    /// list of revision @a rev, relative to the start of the revision, as
+    /// given by the trailer of a physically addressed revision in
+    /// @a rev_file.
+    ///
+    /// `get_root_changes_offset`
+    fn get_root_changes_offset(
+        &self,
+        rev_file: &mut RevisionFile,
+        rev: RevisionNumber,
+    ) -> Result<(u64, u64), BackendError> {
+        // Determine where the revision ends: either at the start of the
+        // next revision in the pack file or at the end of the file.
+        let end = if rev_file.is_packed
+            && (rev + 1) % self._data().max_files_per_dir as RevisionNumber != 0
+        {
+            self.get_packed_offset(rev + 1)?
+        } else {
+            rev_file.file_size()
+        };
+
+        let start = end.saturating_sub(TRAILER_SIZE);
+        let trailer = rev_file.read_at(start, (end - start) as usize)?;
+
+        low_level::parse_revision_trailer(&trailer, rev)
+    }
+
+    /// Return the node-revision ID of the root directory of revision
+    /// @a rev.
+    ///
+    /// `svn_fs_fs__rev_get_root`
+    pub(crate) fn rev_get_root(&self, rev: RevisionNumber) -> Result<FsId, BackendError> {
+        self.ensure_revision_exists(rev)?;
+
+        if self._data().use_log_addressing {
+            return Ok(FsId::root(rev, SVN_FS_FS__ITEM_INDEX_ROOT_NODE));
+        }
+
+        let cache = &self._data().cache.rev_root_id_cache;
+        if let Some(cache) = cache
+            && let Some(id) = cache.get(&rev)?
+        {
+            return Ok(id);
+        }
+
+        let mut rev_file = self.open_pack_or_rev_file(rev)?;
+        let (root_offset, _) = self.get_root_changes_offset(&mut rev_file, rev)?;
+
+        let id = FsId::root(rev, root_offset);
+        if let Some(cache) = cache {
+            cache.set(rev, id.clone())?;
+        }
+
+        Ok(id)
+    }
+
+    /// Return the node-revision for the node ID.
+    ///
+    /// `svn_fs_fs__get_node_revision`
+    pub(crate) fn get_node_revision(&self, id: &FsId) -> Result<NodeRevision, BackendError> {
+        if id.is_txn() {
+            // This is a transaction node-rev.  Its storage logic is very
+            // different from that of rev / pack files.
+            let path = self.path_txn_node_rev(id);
+            let data = match fs_err::read_to_string(&path) {
+                Ok(data) => data,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(BackendError::Corrupt(format!(
+                        "Reference to non-existent node '{id}' in filesystem '{}'",
+                        self.path.display()
+                    )));
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            return low_level::parse_noderev(&data);
+        }
+
+        let rev = id.rev();
+        let key = PairCacheKey {
+            revision: rev,
+            second: id.item(),
+        };
+        let cache = &self._data().cache.node_revision_cache;
+        if let Some(cache) = cache
+            && let Some(noderev) = cache.get(&key)?
+        {
+            return Ok(noderev);
+        }
+
+        let mut rev_file = self.open_pack_or_rev_file(rev)?;
+        let offset = self.item_offset(&mut rev_file, rev, id.item())?;
+        let binary = self._data().binary_items;
+        let data = rev_file.read_item(
+            offset,
+            if binary {
+                fsx::item_len
+            } else {
+                header_block_len
+            },
+        )?;
+        let noderev = low_level::parse_noderev_item(&data, binary)?;
+
+        // Make sure we read what we asked for.
+        if noderev.id != *id {
+            return Err(BackendError::Corrupt(format!(
+                "ID '{}' of node-revision read at '{id}' does not match",
+                noderev.id
+            )));
+        }
+
+        // Workaround issue #4031: is-fresh-txn-root in revision files.
+        let noderev = NodeRevision {
+            is_fresh_txn_root: false,
+            ..noderev
+        };
+
+        if let Some(cache) = cache {
+            cache.set(key, noderev.clone())?;
+        }
+
+        Ok(noderev)
+    }
+
+    /// Read the representation header of the representation stored at
+    /// item @a item_index of revision @a rev.
+    ///
+    /// `read_rep_header`
+    pub(crate) fn read_rep_header(
+        &self,
+        rev: RevisionNumber,
+        item_index: u64,
+    ) -> Result<RepHeader, BackendError> {
+        let mut rev_file = self.open_pack_or_rev_file(rev)?;
+        let offset = self.item_offset(&mut rev_file, rev, item_index)?;
+        let data = rev_file.read_item(offset, line_len)?;
+
+        low_level::parse_rep_header(&data)
+    }
+
+    /// Return the length of the delta chain starting at the committed
+    /// representation @a rep and the number of shards it touches.
+    ///
+    /// `svn_fs_fs__rep_chain_length`
+    pub(crate) fn rep_chain_length(
+        &self,
+        rep: &Representation,
+    ) -> Result<(usize, usize), BackendError> {
+        let max_files_per_dir = self._data().max_files_per_dir;
+        let shard_size = if max_files_per_dir > 0 {
+            max_files_per_dir as RevisionNumber
+        } else {
+            1
+        };
+
+        let mut last_shard = rep.revision / shard_size;
+        let mut count = 0;
+        let mut shards = 1;
+        let (mut rev, mut item_index) = (rep.revision, rep.item_index);
+        loop {
+            if rev / shard_size != last_shard {
+                last_shard = rev / shard_size;
+                shards += 1;
+            }
+            count += 1;
+
+            match self.read_rep_header(rev, item_index)?.rep_type {
+                RepType::Delta {
+                    base_revision,
+                    base_item_index,
+                    ..
+                } => (rev, item_index) = (base_revision, base_item_index),
+                RepType::Plain | RepType::SelfDelta => return Ok((count, shards)),
+            }
+        }
+    }
+
+    /// Return the list of changes made in revision @a rev.
+    ///
+    /// `svn_fs_fs__get_changes`
+    pub(crate) fn get_changes(&self, rev: RevisionNumber) -> Result<Vec<PathChange>, BackendError> {
+        let data = self.read_changes_item(rev)?;
+
+        low_level::parse_changes_item(&data, self._data().binary_items)
+    }
+
+    /// Return the unparsed changed-paths list item of revision @a rev.
+    pub(crate) fn read_changes_item(&self, rev: RevisionNumber) -> Result<Vec<u8>, BackendError> {
+        self.ensure_revision_exists(rev)?;
+
+        let mut rev_file = self.open_pack_or_rev_file(rev)?;
+        let offset = if self._data().use_log_addressing {
+            self.item_offset(&mut rev_file, rev, SVN_FS_FS__ITEM_INDEX_CHANGES)?
+        } else {
+            let (_, changes_offset) = self.get_root_changes_offset(&mut rev_file, rev)?;
+            self.item_offset(&mut rev_file, rev, changes_offset)?
+        };
+
+        let binary = self._data().binary_items;
+        rev_file.read_item(offset, if binary { fsx::item_len } else { changes_len })
+    }
+
+    /// Open the representation stored at item @a item_index of revision
+    /// @a rev with a data size of @a size and read its header.
+    ///
+    /// `create_rep_state_body`
+    fn create_rep_state(
+        &self,
+        txn_id: &IdPart,
+        rev: RevisionNumber,
+        item_index: u64,
+        size: u64,
+    ) -> Result<RepState, BackendError> {
+        let (mut file, offset) = if txn_id.is_used_txn() {
+            // The rep is still being written in a transaction.
+            let offset = self.txn_item_offset(txn_id, item_index)?;
+            (self.open_proto_rev_file(txn_id)?, offset)
+        } else {
+            let mut file = self.open_pack_or_rev_file(rev)?;
+            let offset = self.item_offset(&mut file, rev, item_index)?;
+            (file, offset)
+        };
+        let header = file.read_item(offset, line_len)?;
+        let header = low_level::parse_rep_header(&header)?;
+
+        Ok(RepState {
+            file,
+            header,
+            start: offset + header.header_size as u64,
+            size,
+            key: (!txn_id.is_used_txn()).then_some(PairCacheKey {
+                revision: rev,
+                second: item_index,
+            }),
+        })
+    }
+
+    /// Return the list of representations that have to be combined to
+    /// reconstruct the fulltext of @a rep, starting with @a rep itself and
+    /// ending with a PLAIN or self-delta representation.
+    ///
+    /// `build_rep_list`
+    fn build_rep_list(&self, rep: &Representation) -> Result<Vec<RepState>, BackendError> {
+        let mut list = Vec::new();
+        let (mut rev, mut item_index, mut size) = (rep.revision, rep.item_index, rep.size);
+
+        // Only the first rep may be part of a transaction; delta bases
+        // are always committed.
+        let mut txn_id = rep.txn_id;
+        loop {
+            let state = self.create_rep_state(&txn_id, rev, item_index, size)?;
+            txn_id = IdPart::UNUSED_TXN;
+            let next = match state.header.rep_type {
+                RepType::Delta {
+                    base_revision,
+                    base_item_index,
+                    base_length,
+                } => Some((base_revision, base_item_index, base_length)),
+                RepType::Plain | RepType::SelfDelta => None,
+            };
+            list.push(state);
+
+            match next {
+                Some(base) => (rev, item_index, size) = base,
+                None => return Ok(list),
+            }
+        }
+    }
+
+    /// Return a stream over the fulltext of @a rep.  A `None` rep
+    /// represents the empty fulltext.
+    ///
+    /// `svn_fs_fs__get_contents`
+    pub(crate) fn read_representation(
+        &self,
+        rep: Option<&Representation>,
+    ) -> Result<RepReader, BackendError> {
+        let Some(rep) = rep else {
+            return Ok(RepReader::empty());
+        };
+
+        // Committed fulltexts are immutable and may be cached.
+        let mut fulltext_cache = None;
+        if let Some(cache) = &self._data().cache.fulltext_cache
+            && !rep.txn_id.is_used_txn()
+        {
+            let key = PairCacheKey {
+                revision: rep.revision,
+                second: rep.item_index,
+            };
+            if let Some(fulltext) = cache.get(&key)? {
+                return Ok(RepReader::from_fulltext(fulltext));
+            }
+            if cache.is_cachable(rep.expanded_size.max(rep.size) as usize) {
+                fulltext_cache = Some((cache.clone(), key));
+            }
+        }
+
+        let mut list = self.build_rep_list(rep)?;
+        let source = if list[0].header.rep_type == RepType::Plain {
+            let first = list.remove(0);
+            RepSource::Plain {
+                offset: first.start,
+                remaining: first.size,
+                file: first.file,
+            }
+        } else {
+            // The chain ends with either a PLAIN rep or a self-delta.
+            let base = list.pop_if(|state| state.header.rep_type == RepType::Plain);
+            let window_cache = &self._data().cache.txdelta_window_cache;
+            let deltas = list
+                .into_iter()
+                .map(|state| DeltaState::new(state, window_cache.clone()))
+                .collect();
+            RepSource::Delta {
+                deltas,
+                base,
+                chunk_index: 0,
+            }
+        };
+
+        Ok(RepReader {
+            source,
+            buf: Vec::new(),
+            buf_pos: 0,
+            md5_ctx: Some(ChecksumContext::new(ChecksumKind::Md5)),
+            md5_digest: rep.md5_digest.clone(),
+            fulltext_cache,
+            current_fulltext: Vec::new(),
+        })
+    }
+
+    /// Return the complete fulltext of @a rep.
+    ///
+    /// `svn_fs_fs__get_contents` / `svn_stringbuf_from_stream`
+    pub(crate) fn rep_contents(
+        &self,
+        rep: Option<&Representation>,
+    ) -> Result<Vec<u8>, BackendError> {
+        let mut contents = Vec::new();
+        self.read_representation(rep)?.read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+
+    /// Return the entries of the directory @a noderev, keyed by name.
+    ///
+    /// `svn_fs_fs__rep_contents_dir`
+    pub(crate) fn rep_contents_dir(
+        &self,
+        noderev: &NodeRevision,
+    ) -> Result<BTreeMap<String, DirectoryEntry>, BackendError> {
+        if let Some(rep) = &noderev.data_rep
+            && rep.txn_id.is_used_txn()
+        {
+            // The directory has been modified in its transaction: read
+            // the full listing followed by the incremental changes.
+            let contents = fs_err::read(self.path_txn_node_children(&noderev.id))?;
+            let (mut hash, pos) = svn_subr::hash::read_hash(&contents, HASH_TERMINATOR)?;
+            svn_subr::hash::read_hash_incremental(&mut hash, &contents[pos..], None)?;
+            return parse_dir_entries(hash, &noderev.id);
+        }
+
+        let Some(rep) = &noderev.data_rep else {
+            return Ok(BTreeMap::new());
+        };
+
+        // Committed directories are immutable and may be cached.
+        let key = PairCacheKey {
+            revision: rep.revision,
+            second: rep.item_index,
+        };
+        let cache = &self._data().cache.dir_cache;
+        if let Some(cache) = cache
+            && let Some(entries) = cache.get(&key)?
+        {
+            return Ok(entries);
+        }
+
+        let contents = self.rep_contents(Some(rep))?;
+        let entries = if contents.is_empty() {
+            BTreeMap::new()
+        } else {
+            let (hash, _) = svn_subr::hash::read_hash(&contents, HASH_TERMINATOR)?;
+            parse_dir_entries(hash, &noderev.id)?
+        };
+
+        if let Some(cache) = cache {
+            cache.set(key, entries.clone())?;
+        }
+
+        Ok(entries)
+    }
+
+    /// Return the properties of @a noderev.
+    ///
+    /// `svn_fs_fs__get_proplist`
+    pub(crate) fn get_proplist(
+        &self,
+        noderev: &NodeRevision,
+    ) -> Result<HashMap<String, String>, BackendError> {
+        let rep = match &noderev.prop_rep {
+            Some(rep) if rep.txn_id.is_used_txn() => {
+                let contents = fs_err::read(self.path_txn_node_props(&noderev.id))?;
+                return parse_proplist(&contents);
+            }
+            Some(rep) => rep,
+            None => return Ok(HashMap::new()),
+        };
+
+        // Committed property lists are immutable and may be cached.
+        let key = PairCacheKey {
+            revision: rep.revision,
+            second: rep.item_index,
+        };
+        let cache = &self._data().cache.properties_cache;
+        if let Some(cache) = cache
+            && let Some(proplist) = cache.get(&key)?
+        {
+            return Ok(proplist);
+        }
+
+        let proplist = parse_proplist(&self.rep_contents(Some(rep))?)?;
+
+        if let Some(cache) = cache {
+            cache.set(key, proplist.clone())?;
+        }
+
+        Ok(proplist)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use svn_types::NodeKind;
+
+    use super::*;
+    use crate::FsConfig;
+    use crate::FsFsConfig;
+    use crate::SvnFs;
+
+    #[rstest]
+    #[case::logical(true)]
+    #[case::physical(false)]
+    fn test_read_revision_zero(#[case] log_addressing: bool) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo");
+        let config = FsConfig::Fsfs(FsFsConfig {
+            log_addressing,
+            ..Default::default()
+        });
+        SvnFs::create(&path, &config).unwrap();
+
+        let mut backend = FsFsBackend::new(path.clone());
+        backend.set_data(FsFsBackend::initialize_fs_struct());
+        backend._open(&path).unwrap();
+
+        let root_id = backend.rev_get_root(0).unwrap();
+        let noderev = backend.get_node_revision(&root_id).unwrap();
+        assert_eq!(noderev.id, root_id);
+        assert_eq!(noderev.kind, NodeKind::Directory);
+        assert_eq!(noderev.created_path, "/");
+        assert_eq!(noderev.predecessor_id, None);
+        assert_eq!(noderev.predecessor_count, 0);
+
+        let data_rep = noderev.data_rep.unwrap();
+        let header = backend
+            .read_rep_header(data_rep.revision, data_rep.item_index)
+            .unwrap();
+        assert_eq!(header.rep_type, low_level::RepType::Plain);
+
+        assert!(backend.get_changes(0).unwrap().is_empty());
+        assert!(matches!(
+            backend.rev_get_root(1),
+            Err(BackendError::NoSuchRevision(1))
+        ));
+    }
+}
@@ -1,8 +1,29 @@
 //! `subversion\libsvn_fs_fs\caching.c`
 
-use svn_subr::SvnConfig;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::Weak;
 
-use crate::SvnFs;
+use svn_subr::CacheErrorHandler;
+use svn_subr::CacheKey;
+use svn_subr::CachePriority;
+use svn_subr::DeserializeFn;
+use svn_subr::Membuffer;
+use svn_subr::MembufferCache;
+use svn_subr::MemcacheCache;
+use svn_subr::SerializeFn;
+use svn_subr::SvnCache;
+use uuid::Uuid;
+
+use super::FsFsBackend;
+use super::data::FsFsDataCache;
+use super::temp_serializer;
+use crate::backend::BackendError;
+use crate::config::SVN_FS_CONFIG_FSFS_BLOCK_READ;
+use crate::config::SVN_FS_CONFIG_FSFS_CACHE_REVPROPS;
 
 /// Select the cache namespace.  If you potentially share the cache with
 /// another FS object for the same repository, objects read through one FS
@@ -17,19 +38,19 @@ use crate::SvnFs;
 ///
 /// @since New in 1.8.
 ///
-const SVN_FS_CONFIG_FSFS_CACHE_NS: &str = "fsfs-cache-namespace";
+pub(crate) const SVN_FS_CONFIG_FSFS_CACHE_NS: &str = "fsfs-cache-namespace";
 
 /// Enable / disable text delta caching for a FSFS repository.
 ///
 /// @since New in 1.7.
 ///
-const SVN_FS_CONFIG_FSFS_CACHE_DELTAS: &str = "fsfs-cache-deltas";
+pub(crate) const SVN_FS_CONFIG_FSFS_CACHE_DELTAS: &str = "fsfs-cache-deltas";
 
 /// Enable / disable full-text caching for a FSFS repository.
 ///
 /// @since New in 1.7.
 ///
-const SVN_FS_CONFIG_FSFS_CACHE_FULLTEXTS: &str = "fsfs-cache-fulltexts";
+pub(crate) const SVN_FS_CONFIG_FSFS_CACHE_FULLTEXTS: &str = "fsfs-cache-fulltexts";
 
 /// Enable / disable caching of node properties for a FSFS repository.
 ///
@@ -43,33 +64,41 @@ pub enum CachingError {
     ParseBool(#[from] std::str::ParseBoolError),
 }
 
-/// *CACHE_TXDELTAS, *CACHE_FULLTEXTS, *CACHE_NODEPROPS flags will be set
-/// according to FS->CONFIG. *CACHE_NAMESPACE receives the cache prefix to
-/// use.
+/// The caches settings read from the filesystem configuration.
 ///
-/// Use FS->pool for allocating the memcache and CACHE_NAMESPACE, and POOL
-/// for temporary allocations.
+/// `read_config`
+struct CachingConfig {
+    /// the namespace to prefix the cache keys with
+    cache_namespace: String,
+    /// whether to cache txdelta windows
+    cache_txdeltas: bool,
+    /// whether to cache fulltexts
+    cache_fulltexts: bool,
+    /// whether to cache node properties
+    cache_nodeprops: bool,
+    /// whether to cache revision properties
+    cache_revprops: bool,
+    /// whether to read and keep whole blocks of rev / pack files
+    block_read: bool,
+}
+
+/// Read the caches settings from @a fs_config, see [`CachingConfig`].
 ///
 /// `read_config`
-pub fn read_config(fs: &SvnFs) -> Result<(String, bool, bool, bool), CachingError> {
+fn read_config(fs_config: &HashMap<String, String>) -> Result<CachingConfig, CachingError> {
     //  No cache namespace by default.  I.e. all FS instances share the
     // cached data.  If you specify different namespaces, the data will
     // share / compete for the same cache memory but keys will not match
     // across namespaces and, thus, cached data will not be shared between
     // namespaces.
-    //
-    // Since the namespace will be concatenated with other elements to form
-    // the complete key prefix, we must make sure that the resulting string
-    let cache_namespace = fs
-        .config()
+    let cache_namespace = fs_config
         .get(SVN_FS_CONFIG_FSFS_CACHE_NS)
         .cloned()
         .unwrap_or_default();
 
     // Cache text deltas by default.
     // They tend to be smaller and have finer granularity than fulltexts.
-    let cache_txdeltas =
-        svn_subr::hash::get_bool(fs.config(), SVN_FS_CONFIG_FSFS_CACHE_DELTAS, true);
+    let cache_txdeltas = svn_subr::hash::get_bool(fs_config, SVN_FS_CONFIG_FSFS_CACHE_DELTAS, true);
 
     // by default, cache fulltexts.
     // Most SVN tools care about reconstructed file content.
@@ -78,18 +107,389 @@ pub fn read_config(fs: &SvnFs) -> Result<(String, bool, bool, bool), CachingErro
     // won't be re-used rendering the cache less effective
     // by squeezing wanted data out.
     let cache_fulltexts =
-        svn_subr::hash::get_bool(fs.config(), SVN_FS_CONFIG_FSFS_CACHE_FULLTEXTS, true);
+        svn_subr::hash::get_bool(fs_config, SVN_FS_CONFIG_FSFS_CACHE_FULLTEXTS, true);
 
     //  by default, cache nodeprops.
     // Pre-1.10, this was controlled by the SVN_FS_CONFIG_FSFS_CACHE_FULLTEXTS
     // configuration option which defaulted to TRUE.
     let cache_nodeprops =
-        svn_subr::hash::get_bool(fs.config(), SVN_FS_CONFIG_FSFS_CACHE_NODEPROPS, true);
+        svn_subr::hash::get_bool(fs_config, SVN_FS_CONFIG_FSFS_CACHE_NODEPROPS, true);
 
-    Ok((
+    // don't cache revprops by default.
+    // Revprop changes made by other processes would not be noticed.
+    let cache_revprops =
+        svn_subr::hash::get_bool(fs_config, SVN_FS_CONFIG_FSFS_CACHE_REVPROPS, false);
+
+    // Block read is a format 7 feature and disabled by default.
+    let block_read = svn_subr::hash::get_bool(fs_config, SVN_FS_CONFIG_FSFS_BLOCK_READ, false);
+
+    Ok(CachingConfig {
         cache_namespace,
         cache_txdeltas,
         cache_fulltexts,
         cache_nodeprops,
-    ))
+        cache_revprops,
+        block_read,
+    })
+}
+
+/// The sets of caches in use, keyed by their prefix.  Filesystem
+/// instances with the same prefix share the caches and their statistics.
+/// The instances own the sets, which go away with the last of them; the
+/// data itself lives in the global membuffer.
+static CACHES: LazyLock<Mutex<HashMap<String, Weak<FsFsDataCache>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Return the set of caches for the key @a prefix within @a membuffer,
+/// creating it unless an instance still uses it.
+fn shared_caches(membuffer: &Arc<Membuffer>, prefix: &str) -> Arc<FsFsDataCache> {
+    let mut caches = CACHES.lock().unwrap();
+    if let Some(cache) = caches.get(prefix).and_then(Weak::upgrade) {
+        return cache;
+    }
+
+    // Forget about the sets nobody uses anymore.
+    caches.retain(|_, cache| cache.strong_count() > 0);
+
+    let cache = Arc::new(create_caches(membuffer, prefix));
+    caches.insert(prefix.to_string(), Arc::downgrade(&cache));
+    cache
+}
+
+/// Return a cache within @a membuffer, named @a prefix followed by
+/// @a name.
+///
+/// `create_cache`
+fn create_cache<K, V>(
+    membuffer: &Arc<Membuffer>,
+    prefix: &str,
+    name: &str,
+    priority: CachePriority,
+    serialize: SerializeFn<V>,
+    deserialize: DeserializeFn<V>,
+) -> Option<SvnCache<K, V>>
+where
+    K: CacheKey + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    Some(SvnCache::new(MembufferCache::new(
+        membuffer.clone(),
+        &format!("{prefix}{name}"),
+        priority,
+        serialize,
+        deserialize,
+    )))
+}
+
+/// Create the set of caches for the key @a prefix within @a membuffer.
+fn create_caches(membuffer: &Arc<Membuffer>, prefix: &str) -> FsFsDataCache {
+    use temp_serializer::*;
+
+    FsFsDataCache {
+        rev_root_id_cache: create_cache(
+            membuffer,
+            prefix,
+            "RRI",
+            CachePriority::High,
+            serialize_id,
+            deserialize_id,
+        ),
+        dir_cache: create_cache(
+            membuffer,
+            prefix,
+            "DIR",
+            CachePriority::High,
+            serialize_dir_entries,
+            deserialize_dir_entries,
+        ),
+        fulltext_cache: create_cache(
+            membuffer,
+            prefix,
+            "TEXT",
+            CachePriority::Default,
+            serialize_raw,
+            deserialize_raw,
+        ),
+        properties_cache: create_cache(
+            membuffer,
+            prefix,
+            "PROP",
+            CachePriority::Default,
+            serialize_properties,
+            deserialize_properties,
+        ),
+        txdelta_window_cache: create_cache(
+            membuffer,
+            prefix,
+            "TXDELTA_WINDOW",
+            CachePriority::Low,
+            serialize_txdelta_window,
+            deserialize_txdelta_window,
+        ),
+        node_revision_cache: create_cache(
+            membuffer,
+            prefix,
+            "NODEREVS",
+            CachePriority::High,
+            serialize_node_revision,
+            deserialize_node_revision,
+        ),
+        revprop_cache: create_cache(
+            membuffer,
+            prefix,
+            "REVPROP",
+            CachePriority::Default,
+            serialize_properties,
+            deserialize_properties,
+        ),
+    }
+}
+
+/// Install @a handler in @a cache, if there is a cache.
+///
+/// `init_callbacks`
+fn init_callbacks<K, V>(cache: &mut Option<SvnCache<K, V>>, handler: &Option<CacheErrorHandler>)
+where
+    K: Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    if let (Some(cache), Some(handler)) = (cache, handler) {
+        cache.set_error_handler(handler.clone());
+    }
+}
+
+impl FsFsBackend {
+    /// Initialize all session-local caches in FS according to the global
+    /// cache settings and @a fs_config.  The caches are shared with all
+    /// other instances of the repository @a uuid at @a path that use the
+    /// same cache namespace.
+    ///
+    /// Please note that it is permissible for this function to set some
+    /// or all of these caches to NULL, regardless of any setting.
+    ///
+    /// `svn_fs_fs__initialize_caches`
+    pub(crate) fn initialize_caches(
+        &mut self,
+        fs_config: &HashMap<String, String>,
+        uuid: &Uuid,
+        path: &Path,
+    ) -> Result<(), BackendError> {
+        let path = std::path::absolute(path)?;
+        let prefix = format!("fsfs:{uuid}/{}:", path.display());
+
+        let config = read_config(fs_config)?;
+        let ffd = self._data_mut();
+        ffd.use_block_read = config.block_read && ffd.use_log_addressing;
+        let prefix = format!("ns:{}:{prefix}", config.cache_namespace);
+
+        let shared_cache = svn_subr::get_global_membuffer_cache()
+            .map(|membuffer| shared_caches(&membuffer, &prefix));
+        // Without a shared set, caching has been disabled.
+        let mut cache = shared_cache.as_deref().cloned().unwrap_or_default();
+
+        // Fulltexts go to memcached instead, if configured, to share them
+        // between hosts.
+        if let Some(memcache) = &self._data().memcache {
+            cache.fulltext_cache = Some(SvnCache::new(MemcacheCache::new(
+                memcache.clone(),
+                &format!("{prefix}TEXT"),
+                temp_serializer::serialize_raw,
+                temp_serializer::deserialize_raw,
+            )));
+        }
+
+        if !config.cache_txdeltas {
+            cache.txdelta_window_cache = None;
+        }
+        if !config.cache_fulltexts {
+            cache.fulltext_cache = None;
+        }
+        if !config.cache_nodeprops {
+            cache.properties_cache = None;
+        }
+        if !config.cache_revprops {
+            cache.revprop_cache = None;
+        }
+
+        // Caching is an optional feature: unless told otherwise, treat
+        // cache errors as cache misses.
+        let handler: Option<CacheErrorHandler> = if self._data().fail_stop {
+            None
+        } else {
+            Some(Arc::new(|_| Ok(())))
+        };
+        init_callbacks(&mut cache.rev_root_id_cache, &handler);
+        init_callbacks(&mut cache.dir_cache, &handler);
+        init_callbacks(&mut cache.fulltext_cache, &handler);
+        init_callbacks(&mut cache.properties_cache, &handler);
+        init_callbacks(&mut cache.txdelta_window_cache, &handler);
+        init_callbacks(&mut cache.node_revision_cache, &handler);
+        init_callbacks(&mut cache.revprop_cache, &handler);
+
+        let ffd = self._data_mut();
+        ffd.cache = cache;
+        ffd.shared_cache = shared_cache;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use svn_subr::Checksum;
+    use svn_subr::ChecksumKind;
+
+    use super::*;
+    use crate::FsConfig;
+    use crate::FsFsConfig;
+    use crate::SvnFs;
+    use crate::backend::fsfs::FsFsData;
+    use crate::backend::fsfs::data::PairCacheKey;
+
+    /// Return the caches of @a fs.
+    fn caches(fs: &mut SvnFs) -> FsFsDataCache {
+        let ffd = fs.inner_mut().data_mut().downcast_mut::<FsFsData>();
+        ffd.unwrap().cache.clone()
+    }
+
+    /// Return the fulltext cache of @a fs.
+    fn fulltext_cache(fs: &mut SvnFs) -> SvnCache<PairCacheKey, Vec<u8>> {
+        caches(fs).fulltext_cache.unwrap()
+    }
+
+    /// Return the contents of `/iota` in r1 of @a fs.
+    fn read_iota(fs: &SvnFs) -> Vec<u8> {
+        let mut contents = Vec::new();
+        let root = fs.revision_root(1).unwrap();
+        root.file_contents("/iota")
+            .unwrap()
+            .read_to_end(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    /// Create a repository at @a path with `/iota` in r1.
+    fn create_repo(path: &Path, config: &FsConfig) {
+        let fs = SvnFs::create(path, config).unwrap();
+        let txn = fs.begin_txn(0).unwrap();
+        {
+            let root = txn.root().unwrap();
+            root.make_file("/iota").unwrap();
+            root.apply_text(
+                "/iota",
+                b"This is iota.\n",
+                Some(&Checksum::compute(ChecksumKind::Md5, b"This is iota.\n")),
+            )
+            .unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    #[test]
+    fn test_caches_shared_within_namespace() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo");
+        let config = FsConfig::Fsfs(FsFsConfig::default());
+        create_repo(&path, &config);
+
+        // The second instance finds what the first one has read.
+        let mut fs1 = SvnFs::open(&path, &config).unwrap();
+        let mut fs2 = SvnFs::open(&path, &config).unwrap();
+        assert_eq!(read_iota(&fs1), b"This is iota.\n");
+        let (_, _, hits, failures) = fulltext_cache(&mut fs1).stats();
+        assert_eq!(read_iota(&fs2), b"This is iota.\n");
+        assert_eq!(fulltext_cache(&mut fs2).stats().2, hits + 1);
+        assert_eq!(failures, 0);
+
+        // Everything read back from the caches deserializes fine.
+        let caches = caches(&mut fs2);
+        let (_, _, hits, failures) = caches.dir_cache.unwrap().stats();
+        assert!(hits > 0 && failures == 0);
+        let (_, _, hits, failures) = caches.node_revision_cache.unwrap().stats();
+        assert!(hits > 0 && failures == 0);
+
+        // A separate namespace has to read everything itself.
+        let config = FsConfig::Fsfs(FsFsConfig {
+            cache_namespace: Uuid::new_v4(),
+            ..Default::default()
+        });
+        let mut fs3 = SvnFs::open(&path, &config).unwrap();
+        assert_eq!(read_iota(&fs3), b"This is iota.\n");
+        assert_eq!(fulltext_cache(&mut fs3).stats().2, 0);
+    }
+
+    #[test]
+    fn test_caches_released_with_last_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo");
+        create_repo(&path, &FsConfig::Fsfs(FsFsConfig::default()));
+
+        let namespace = Uuid::new_v4();
+        let config = FsConfig::Fsfs(FsFsConfig {
+            cache_namespace: namespace,
+            ..Default::default()
+        });
+        let registered = || {
+            let prefix = format!("ns:{namespace}:");
+            let caches = CACHES.lock().unwrap();
+            caches
+                .iter()
+                .filter(|(key, _)| key.starts_with(&prefix))
+                .map(|(_, cache)| cache.strong_count())
+                .collect::<Vec<_>>()
+        };
+
+        let fs1 = SvnFs::open(&path, &config).unwrap();
+        let fs2 = SvnFs::open(&path, &config).unwrap();
+        assert_eq!(registered(), [2]);
+        drop(fs1);
+        assert_eq!(registered(), [1]);
+        drop(fs2);
+        assert_eq!(registered(), [0]);
+
+        // Registering another set forgets the unused one.
+        let config = FsConfig::Fsfs(FsFsConfig {
+            cache_namespace: Uuid::new_v4(),
+            ..Default::default()
+        });
+        let _fs = SvnFs::open(&path, &config).unwrap();
+        assert!(registered().is_empty());
+    }
+
+    #[test]
+    fn test_memcached_errors_ignored_unless_fail_stop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repo");
+        let config = FsConfig::Fsfs(FsFsConfig::default());
+        create_repo(&path, &config);
+
+        // Point the fulltext cache to a memcached server that isn't there.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let conf_path = path.join(FsFsConfig::PATH_CONFIG);
+        let conf = fs_err::read_to_string(&conf_path).unwrap();
+        let conf = conf.replace(
+            "# remote-memcached = mymemcached.corp.example.com:11212",
+            &format!("remote-memcached = 127.0.0.1:{port}"),
+        );
+        fs_err::write(&conf_path, &conf).unwrap();
+
+        let mut fs = SvnFs::open(&path, &config).unwrap();
+        assert_eq!(read_iota(&fs), b"This is iota.\n");
+        assert!(fulltext_cache(&mut fs).stats().3 > 0);
+
+        fs_err::write(
+            &conf_path,
+            conf.replace("# fail-stop = true", "fail-stop = true"),
+        )
+        .unwrap();
+        let fs = SvnFs::open(&path, &config).unwrap();
+        let root = fs.revision_root(1).unwrap();
+        assert!(root.file_contents("/iota").is_err());
+    }
 }
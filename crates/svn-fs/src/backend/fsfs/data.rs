@@ -1,4 +1,12 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use crate::CompressionType;
+use crate::DirectoryEntry;
+use crate::NodeRevision;
+use crate::id::FsId;
+use svn_delta::TxdeltaWindow;
 use svn_subr::MemCache;
 use svn_subr::SvnCache;
 use svn_types::RevisionNumber;
@@ -45,16 +53,11 @@ pub struct FsFsData {
     /// e.g. memcached may be ignored as caching is an optional feature.
     pub fail_stop: bool,
 
-    // TODO: move to `FsFsDataCache`
-    /// A cache of revision root IDs, mapping from (svn_revnum_t *) to
-    /// (svn_fs_id_t *).  (Not threadsafe.)
-    pub rev_root_id_cache: Option<SvnCache<(), ()>>,
-
     /// Pack after every commit.
-    pack_after_commit: bool,
+    pub(crate) pack_after_commit: bool,
 
     /// Verify each new revision before commit.
-    verify_before_commit: bool,
+    pub(crate) verify_before_commit: bool,
 
     /// Per-instance filesystem ID, which provides an additional level of
     /// uniqueness for filesystems that share the same UUID, but should
@@ -95,10 +98,82 @@ pub struct FsFsData {
     /// Compression type to use with txdelta storage format in new revs.
     pub delta_compression_type: CompressionType,
 
-    /// cache
-    cache: FsFsDataCache,
+    /// Whether this is an FSX filesystem: node-revisions and changed-path
+    /// lists in rev files use the binary FSX encodings then, and the
+    /// 'format' file is the FSX one.
+    pub(crate) binary_items: bool,
+
+    /// The caches of immutable data, see [`FsFsDataCache`].
+    pub cache: FsFsDataCache,
+
+    /// The set of caches shared with the other instances, keeping it
+    /// registered for them while this instance lives.  @a cache starts
+    /// as a copy of it.
+    pub(crate) shared_cache: Option<Arc<FsFsDataCache>>,
 }
 
-/// `fs_fs_data_t`'s inner cache
-#[derive(Debug, Default)]
-pub struct FsFsDataCache {}
+/// Key of cached items identified by a revision and a second number,
+/// usually the item index of a representation or node-revision.
+///
+/// `pair_cache_key_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PairCacheKey {
+    /// the revision containing the item
+    pub revision: RevisionNumber,
+    /// the item index within @a revision, or the revprop generation
+    pub second: u64,
+}
+
+/// Key of a cached delta window: the representation and the window's
+/// index within it.
+///
+/// `window_cache_key_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowCacheKey {
+    /// the representation the window belongs to
+    pub rep: PairCacheKey,
+    /// the index of the window within the representation
+    pub chunk_index: usize,
+}
+
+/// A cached delta window together with the position of the next window
+/// in the svndiff data of its representation.
+///
+/// `svn_fs_fs__txdelta_cached_window_t`
+#[derive(Debug, Clone)]
+pub struct CachedWindow {
+    /// the window itself
+    pub window: TxdeltaWindow,
+    /// the offset just behind the window within the svndiff data
+    pub end_offset: usize,
+}
+
+/// `fs_fs_data_t`'s inner cache.  The caches are shared between all
+/// [`crate::SvnFs`] instances opened for the same repository within the
+/// same cache namespace.  Each of them may be `None`.
+#[derive(Debug, Default, Clone)]
+pub struct FsFsDataCache {
+    /// A cache of revision root IDs, mapping from revision to root
+    /// node-revision ID.  Only used with physical addressing.
+    pub rev_root_id_cache: Option<SvnCache<RevisionNumber, FsId>>,
+
+    /// Caches the entries of directories, keyed by their data
+    /// representation.
+    pub dir_cache: Option<SvnCache<PairCacheKey, BTreeMap<String, DirectoryEntry>>>,
+
+    /// A cache of fulltexts, keyed by their representation.
+    pub fulltext_cache: Option<SvnCache<PairCacheKey, Vec<u8>>>,
+
+    /// A cache of node properties, keyed by their representation.
+    pub properties_cache: Option<SvnCache<PairCacheKey, HashMap<String, String>>>,
+
+    /// A cache of the delta windows of representations.
+    pub txdelta_window_cache: Option<SvnCache<WindowCacheKey, CachedWindow>>,
+
+    /// A cache of node-revisions, keyed by revision and item index.
+    pub node_revision_cache: Option<SvnCache<PairCacheKey, NodeRevision>>,
+
+    /// A cache of revision property lists, keyed by revision and revprop
+    /// generation.
+    pub revprop_cache: Option<SvnCache<PairCacheKey, HashMap<String, String>>>,
+}
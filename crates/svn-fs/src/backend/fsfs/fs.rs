@@ -1,25 +1,73 @@
 // copy from `subversion/libsvn_fs_fs/fs.c`
 
+use std::collections::HashMap;
 use std::fmt::Write;
 use std::path::Path;
-use std::path::PathBuf;
+use std::time::SystemTime;
 
 use svn_subr::SvnConfig;
+use svn_subr::hash::HASH_TERMINATOR;
+use svn_subr::io::FileLock;
+use svn_subr::properties::SVN_PROP_REVISION_DATE;
 use svn_types::RevisionNumber;
+use uuid::Uuid;
 
 use super::super::BackendError;
 use super::super::FsLibrary;
+use super::super::fsx;
 use super::FORMAT_NUMBER;
 use super::FsFsData;
 use super::SVN_FS_CONFIG_FSFS_SHARD_SIZE;
+use super::SVN_FS_FS__MIN_CONFIG_FILE;
+use super::SVN_FS_FS__MIN_INSTANCE_ID_FORMAT;
+use super::SVN_FS_FS__MIN_LAYOUT_FORMAT_OPTION_FORMAT;
+use super::SVN_FS_FS__MIN_LOG_ADDRESSING_FORMAT;
+use super::SVN_FS_FS__MIN_NO_GLOBAL_IDS_FORMAT;
+use super::SVN_FS_FS__MIN_PACK_LOCK_FORMAT;
+use super::SVN_FS_FS__MIN_PACKED_FORMAT;
+use super::SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT;
+use super::SVN_FS_FS__MIN_PROTOREVS_DIR_FORMAT;
+use super::SVN_FS_FS__MIN_SVNDIFF1_FORMAT;
+use super::SVN_FS_FS__MIN_SVNDIFF2_FORMAT;
+use super::SVN_FS_FS__MIN_TXN_CURRENT_FORMAT;
+use super::SVN_FS_FS_DEFAULT_MAX_FILES_PER_DIR;
+use super::index;
+use super::index::ItemType;
+use super::index::P2lEntry;
+use super::index::SVN_FS_FS__ITEM_INDEX_CHANGES;
+use super::index::SVN_FS_FS__ITEM_INDEX_FIRST_USER;
+use super::index::SVN_FS_FS__ITEM_INDEX_ROOT_NODE;
+use super::low_level;
+use crate::CompressionType;
 use crate::FsFsConfig;
+use crate::PackNotifyAction;
+use crate::RecoverReport;
 use crate::SvnFs;
+use crate::UpgradeNotifyAction;
+use crate::VerifyError;
+use crate::backend::CONFIG_OPTION_BLOCK_SIZE;
+use crate::backend::CONFIG_OPTION_COMPRESSION;
+use crate::backend::CONFIG_OPTION_COMPRESSION_LEVEL;
+use crate::backend::CONFIG_OPTION_FAIL_STOP;
+use crate::backend::CONFIG_OPTION_L2P_PAGE_SIZE;
+use crate::backend::CONFIG_OPTION_P2L_PAGE_SIZE;
+use crate::backend::CONFIG_OPTION_PACK_AFTER_COMMIT;
+use crate::backend::CONFIG_OPTION_VERIFY_BEFORE_COMMIT;
+use crate::backend::CONFIG_SECTION_CACHES;
+use crate::backend::CONFIG_SECTION_DEBUG;
+use crate::backend::CONFIG_SECTION_DELTIFICATION;
+use crate::backend::CONFIG_SECTION_IO;
 use crate::backend::PATH_FORMAT;
-use crate::backend::PATH_MIN_UNPACKED_REV;
+use crate::backend::PATH_NODE_ORIGINS_DIR;
+use crate::backend::PATH_REVPROPS_DIR;
 use crate::backend::PATH_REVS_DIR;
 use crate::backend::PATH_UUID;
 use crate::backend::fsfs::FsFsBackend;
 use crate::backend::fsfs::SVN_FS_CONFIG_FSFS_LOG_ADDRESSING;
+use crate::config::SVN_FS_CONFIG_NO_FLUSH_TO_DISK;
+use crate::id::FsId;
+use crate::id::IdPart;
+use crate::id::u64_to_base36;
 
 impl FsLibrary for FsFsBackend {
     fn get_version(&self, path: &Path) -> Result<(), BackendError> {
@@ -33,7 +81,12 @@ impl FsLibrary for FsFsBackend {
     //
     // `fs_create`
     fn create(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
-        let data = Self::initialize_fs_struct();
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(Self::initialize_fs_struct());
+
+        fs.uuid = backend._create(fs.config(), path)?;
+        backend.initialize_caches(fs.config(), &fs.uuid, path)?;
+        fs.set_inner(Box::new(backend));
 
         Ok(())
     }
@@ -45,71 +98,197 @@ impl FsLibrary for FsFsBackend {
     /// The latter must be serialized using COMMON_POOL_LOCK.
     ///
     /// `fs_open`
-    fn open_fs(&self, path: &Path) -> Result<(), BackendError> {
-        // TODO:
-        // self.check_fs(path)?;
-
-        Self::initialize_fs_struct();
+    fn open_fs(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(Self::initialize_fs_struct());
 
-        // self._open(path)?;
-        // self._initialize_cache()?;
+        fs.uuid = backend._open(path)?;
+        backend.initialize_caches(fs.config(), &fs.uuid, path)?;
+        fs.set_inner(Box::new(backend));
 
-        todo!()
+        Ok(())
     }
 
-    fn open_fs_for_recovery(&self, path: &str) -> Result<(), BackendError> {
-        todo!()
+    /// Open the filesystem at PATH even if its `current` file is missing
+    /// or garbled, so that it can be recovered.
+    ///
+    /// `fs_open_for_recovery`
+    fn open_fs_for_recovery(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(Self::initialize_fs_struct());
+
+        fs.uuid = backend._open_for_recovery(path)?;
+        backend.initialize_caches(fs.config(), &fs.uuid, path)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
     }
-    fn upgrade_fs(&self, path: &str) -> Result<(), BackendError> {
-        todo!()
+    /// Upgrade the filesystem at PATH to the latest format, see
+    /// [`FsFsBackend::upgrade`].
+    ///
+    /// `fs_upgrade`
+    fn upgrade_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        notify: Option<&mut dyn FnMut(i64, UpgradeNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(Self::initialize_fs_struct());
+        backend.upgrade(path, notify, cancel)?;
+
+        // Re-open to pick up the settings of the new format.
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(Self::initialize_fs_struct());
+        fs.uuid = backend._open(path)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
     }
-    fn verify_fs(&self, path: &str) -> Result<(), BackendError> {
-        todo!()
+    /// Verify the filesystem at PATH, see [`FsFsBackend::verify`].
+    ///
+    /// `fs_verify`
+    fn verify_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        start: RevisionNumber,
+        end: RevisionNumber,
+        keep_going: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<Vec<VerifyError>, BackendError> {
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(Self::initialize_fs_struct());
+
+        fs.uuid = backend._open(path)?;
+        let errors = backend.verify(start, end, keep_going, notify, cancel)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(errors)
     }
+    /// Delete the filesystem at PATH, with everything in it.
     fn delete_fs(&self, path: &str) -> Result<(), BackendError> {
-        todo!()
+        fs_err::remove_dir_all(path)?;
+        Ok(())
     }
-    fn hotcopy(&self, src_path: &str, dst_path: &str) -> Result<(), BackendError> {
-        todo!()
+    /// Copy the filesystem at SRC_PATH to DST_PATH, see
+    /// [`FsFsBackend::hotcopy`].
+    ///
+    /// `fs_hotcopy`
+    fn hotcopy(
+        src_fs: &mut SvnFs,
+        dst_fs: &mut SvnFs,
+        src_path: &Path,
+        dst_path: &Path,
+        incremental: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber, RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let mut src = FsFsBackend::new(src_path.to_path_buf());
+        src.set_data(Self::initialize_fs_struct());
+        src_fs.uuid = src._open(src_path)?;
+
+        let mut dst = FsFsBackend::new(dst_path.to_path_buf());
+        dst.set_data(Self::initialize_fs_struct());
+        src.hotcopy(src_fs.uuid, &mut dst, incremental, notify, cancel)?;
+
+        // Re-open the destination to pick up everything copied.
+        let mut dst = FsFsBackend::new(dst_path.to_path_buf());
+        dst.set_data(Self::initialize_fs_struct());
+        dst_fs.uuid = dst._open(dst_path)?;
+
+        src_fs.set_inner(Box::new(src));
+        dst_fs.set_inner(Box::new(dst));
+
+        Ok(())
     }
-    fn pack_fs(&self, path: &str) -> Result<(), BackendError> {
-        todo!()
+
+    /// Recover the filesystem at PATH, see [`FsFsBackend::recover`].
+    ///
+    /// `fs_recover`
+    fn recover_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<RecoverReport, BackendError> {
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(Self::initialize_fs_struct());
+        fs.uuid = backend._open_for_recovery(path)?;
+        let report = backend.recover(fs.uuid, cancel)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(report)
+    }
+
+    /// Pack the filesystem at PATH, see [`FsFsBackend::pack`].
+    ///
+    /// `fs_pack`
+    fn pack_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        notify: Option<&mut dyn FnMut(i64, PackNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(Self::initialize_fs_struct());
+
+        fs.uuid = backend._open(path)?;
+        backend.pack(notify, cancel)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
     }
 }
 
 impl FsFsBackend {
     /// Set up vtable and fsap_data fields in FS.
     /// `initialize_fs_struct`
-    fn initialize_fs_struct() {
-        let mut data = FsFsData::default();
-        data.flush_to_disk = true;
+    pub(crate) fn initialize_fs_struct() -> FsFsData {
+        FsFsData {
+            flush_to_disk: true,
+            ..Default::default()
+        }
     }
 
-    // `svn_fs_fs__create`
-    fn _create(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
+    /// Create a new FSFS repository at PATH, using the options in
+    /// FS_CONFIG, and return its UUID.
+    ///
+    /// `svn_fs_fs__create`
+    pub(crate) fn _create(
+        &mut self,
+        fs_config: &HashMap<String, String>,
+        path: &Path,
+    ) -> Result<Uuid, BackendError> {
         // We don't care version, just use 8
         let format = FORMAT_NUMBER;
 
-        let shard_size =
-            if let Some(share_size_str) = fs.config().get(SVN_FS_CONFIG_FSFS_SHARD_SIZE) {
-                share_size_str.parse::<u32>().unwrap_or(0)
-            } else {
-                0
-            };
+        // Use a sharded layout with the default shard size unless told
+        // otherwise.
+        let shard_size = match fs_config.get(SVN_FS_CONFIG_FSFS_SHARD_SIZE) {
+            Some(shard_size) => shard_size.parse::<u32>()?,
+            None => SVN_FS_FS_DEFAULT_MAX_FILES_PER_DIR,
+        };
+
+        // FSX always uses a sharded layout and logical addressing.
+        let binary_items = self._data().binary_items;
+        if binary_items && shard_size == 0 {
+            return Err(BackendError::InvalidConfig(
+                "FSX filesystems cannot have a linear layout".to_string(),
+            ));
+        }
+        let log_addressing = binary_items
+            || svn_subr::hash::get_bool(fs_config, SVN_FS_CONFIG_FSFS_LOG_ADDRESSING, true);
 
-        let log_addressing: bool = fs
-            .config()
-            .get(SVN_FS_CONFIG_FSFS_LOG_ADDRESSING)
-            .map_or(false, |v| v == "true");
+        self._data_mut().flush_to_disk =
+            !svn_subr::hash::get_bool(fs_config, SVN_FS_CONFIG_NO_FLUSH_TO_DISK, false);
 
         // Actual FS creation.
-        let mut f = FsFsBackend::new(path.to_path_buf());
-        f.create_file_tree(path, format, shard_size, log_addressing)?;
+        let uuid = self.create_file_tree(path, format, shard_size, log_addressing)?;
 
         // This filesystem is ready.  Stamp it with a format number.
-        // s.write_format()
-        //
-        todo!()
+        self.write_format(false)?;
+
+        Ok(uuid)
     }
 
     /// Under the repository db PATH, create a FSFS repository with FORMAT,
@@ -121,69 +300,228 @@ impl FsFsBackend {
     /// callers such as hotcopy to modify the contents before turning the
     /// tree into an accessible repository.
     /// `svn_fs_fs__create_file_tree`
-    fn create_file_tree(
+    pub(super) fn create_file_tree(
         &mut self,
         path: &Path,
         format: u32,
         shard_size: u32,
         use_log_addressing: bool,
-    ) -> Result<(), BackendError> {
-        let ffd = self._data_mut();
+    ) -> Result<Uuid, BackendError> {
+        self.path = path.to_path_buf();
 
-        // FIXME:
-        // self.path = path.to_path_buf();
+        let ffd = self._data_mut();
         ffd.format = format;
 
         // Use an appropriate sharding mode if supported by the format.
-        ffd.max_files_per_dir = if format >= 3 { shard_size } else { 0 };
+        ffd.max_files_per_dir = if format >= SVN_FS_FS__MIN_LAYOUT_FORMAT_OPTION_FORMAT {
+            shard_size
+        } else {
+            0
+        };
+
+        // Select the addressing mode depending on the format.
+        ffd.use_log_addressing =
+            format >= SVN_FS_FS__MIN_LOG_ADDRESSING_FORMAT && use_log_addressing;
 
-        //  Create the revision data directories.
+        // Create the revision data directories.
         if ffd.max_files_per_dir > 0 {
-            let p = self.path_rev_shard(0);
-            fs_err::create_dir_all(p)?;
+            fs_err::create_dir_all(self.path_rev_shard(0))?;
         } else {
-            let p = path.join(PATH_REVS_DIR);
-            fs_err::create_dir_all(p)?;
+            fs_err::create_dir_all(path.join(PATH_REVS_DIR))?;
         }
 
-        Ok(())
+        // Create the revprops directory.
+        if self._data().max_files_per_dir > 0 {
+            fs_err::create_dir_all(self.path_revprops_shard(0))?;
+        } else {
+            fs_err::create_dir_all(path.join(PATH_REVPROPS_DIR))?;
+        }
+
+        // Create the transaction directory.
+        fs_err::create_dir_all(self.path_txns_dir())?;
+
+        // Create the protorevs directory.
+        if format >= SVN_FS_FS__MIN_PROTOREVS_DIR_FORMAT {
+            fs_err::create_dir_all(self.path_txn_proto_revs())?;
+        }
+
+        // Create the 'current' file.
+        let current = if format >= SVN_FS_FS__MIN_NO_GLOBAL_IDS_FORMAT {
+            "0\n"
+        } else {
+            "0 1 1\n"
+        };
+        fs_err::write(self.path_current(), current)?;
+
+        fs_err::File::create(self.path_lock())?;
+        let uuid = self.set_uuid(None, None)?;
+
+        // Create the fsfs.conf file if supported.  Older server versions
+        // would simply ignore the file but that might result in a different
+        // behavior than with the later releases.  Also, hotcopy would ignore,
+        // i.e. not copy, a fsfs.conf with old formats.
+        if format >= SVN_FS_FS__MIN_CONFIG_FILE {
+            FsFsConfig::write_config(path).map_err(|e| match e {
+                crate::Error::Io(e) => BackendError::Io(e),
+                e => BackendError::InvalidConfig(e.to_string()),
+            })?;
+        }
+        self._read_config(path)?;
+
+        // Global configuration options.
+        self.write_revision_zero()?;
+        self.reset_revprop_generation_file()?;
+
+        // Create the min unpacked rev file.
+        if format >= SVN_FS_FS__MIN_PACKED_FORMAT {
+            fs_err::write(self.path_min_unpacked_rev(), "0\n")?;
+        }
+
+        // Create the txn-current file if the repository supports
+        // the transaction sequence file.
+        if format >= SVN_FS_FS__MIN_TXN_CURRENT_FORMAT {
+            fs_err::write(self.path_txn_current(), "0\n")?;
+            fs_err::File::create(self.path_txn_current_lock())?;
+        }
+
+        self._data_mut().youngest_rev_cache = 0;
+
+        Ok(uuid)
     }
-}
 
-/// utils
-impl FsFsBackend {
-    /// Return the full path of the rev shard directory that will contain
-    /// revision REV in FS.
+    /// Write out the zeroth revision for filesystem FS.
     ///
-    /// `svn_fs_fs__path_rev_shard`
-    fn path_rev_shard(&self, rev: RevisionNumber) -> PathBuf {
+    /// `write_revision_zero`
+    fn write_revision_zero(&self) -> Result<(), BackendError> {
         let ffd = self._data();
+        let path_revision_zero = self.path_rev(0);
+
+        // Write out a rev file for revision 0.
+        if ffd.use_log_addressing {
+            // Write a skeleton r0 with no indexes: the empty root directory,
+            // its node-revision and an empty changed-paths list.
+            let dir_rep = b"PLAIN\nEND\nENDREP\n";
+            let root_noderev = low_level::parse_noderev(concat!(
+                "id: 0.0.r0/2\n",
+                "type: dir\n",
+                "count: 0\n",
+                "text: 0 3 4 4 2d2977d1c96f487abe4a1e202dd03b4e\n",
+                "cpath: /\n",
+                "\n"
+            ))?;
+            let noderev = low_level::unparse_noderev_item(&root_noderev, ffd.binary_items);
+            let changes = low_level::unparse_changes_item(&[], ffd.binary_items)?;
+            let mut rev_file = [dir_rep.as_slice(), &noderev, &changes].concat();
+            let noderev_offset = dir_rep.len() as u64;
+            let changes_offset = noderev_offset + noderev.len() as u64;
+
+            // Construct the index P2L contents: describe the 3 items we
+            // have.  Be sure to create them in on-disk order.
+            let index_entries = [
+                P2lEntry::new(
+                    &rev_file,
+                    0,
+                    noderev_offset,
+                    ItemType::DirRep,
+                    0,
+                    SVN_FS_FS__ITEM_INDEX_FIRST_USER,
+                ),
+                P2lEntry::new(
+                    &rev_file,
+                    noderev_offset,
+                    noderev.len() as u64,
+                    ItemType::NodeRev,
+                    0,
+                    SVN_FS_FS__ITEM_INDEX_ROOT_NODE,
+                ),
+                P2lEntry::new(
+                    &rev_file,
+                    changes_offset,
+                    changes.len() as u64,
+                    ItemType::Changes,
+                    0,
+                    SVN_FS_FS__ITEM_INDEX_CHANGES,
+                ),
+            ];
+
+            let data_size = rev_file.len() as u64;
+            index::add_index_data(
+                &mut rev_file,
+                data_size,
+                &index_entries,
+                0,
+                ffd.l2p_page_size as u64,
+                ffd.p2l_page_size as u64,
+            )?;
+            fs_err::write(&path_revision_zero, rev_file)?;
+        } else {
+            fs_err::write(
+                &path_revision_zero,
+                concat!(
+                    "PLAIN\nEND\nENDREP\n",
+                    "id: 0.0.r0/17\n",
+                    "type: dir\n",
+                    "count: 0\n",
+                    "text: 0 0 4 4 2d2977d1c96f487abe4a1e202dd03b4e\n",
+                    "cpath: /\n",
+                    "\n\n17 107\n"
+                ),
+            )?;
+        }
 
-        assert!(ffd.max_files_per_dir > 0, "max_files_per_dir must be set");
-        self.path
-            .join(PATH_REVS_DIR)
-            .join(format!("{:0>8}", rev / ffd.max_files_per_dir as i64))
-    }
+        svn_subr::io::set_file_read_only(&path_revision_zero, false)?;
 
-    /// Return the full path of the non-packed rev file containing revision REV in FS.
-    fn path_rev(&self, rev: u32) -> PathBuf {
-        todo!()
+        // Set a date on revision 0.
+        let date = svn_subr::time::time_to_cstring(SystemTime::now());
+        let proplist = HashMap::from([(SVN_PROP_REVISION_DATE.to_string(), date)]);
+        self.set_revision_proplist(0, &proplist)
     }
 
-    /// Return TRUE is REV is packed in FS, FALSE otherwise.
+    /// Set the uuid of repository FS to UUID and the instance ID to
+    /// INSTANCE_ID.  If any of them is NULL, use a newly generated UUID /
+    /// ID instead.  Return the UUID in use.
     ///
-    /// `svn_fs_fs__is_packed_rev`
-    fn is_packed_rev(&self, rev: RevisionNumber) -> bool {
+    /// `svn_fs_fs__set_uuid`
+    pub(crate) fn set_uuid(
+        &mut self,
+        uuid: Option<Uuid>,
+        instance_id: Option<&str>,
+    ) -> Result<Uuid, BackendError> {
+        let uuid = uuid.unwrap_or_else(Uuid::new_v4);
+        let instance_id = instance_id
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
         let ffd = self._data();
-        rev < ffd.min_unpacked_rev
+        let mut contents = format!("{uuid}\n");
+        if ffd.format >= SVN_FS_FS__MIN_INSTANCE_ID_FORMAT {
+            contents.push_str(&instance_id);
+            contents.push('\n');
+        }
+
+        // We use the permissions of the 'current' file, because the 'uuid'
+        // file does not exist during repository creation.
+        svn_subr::io::write_atomic(
+            &self.path_uuid(),
+            contents.as_bytes(),
+            Some(&self.path_current()),
+            ffd.flush_to_disk,
+        )?;
+
+        let ffd = self._data_mut();
+        if ffd.format >= SVN_FS_FS__MIN_INSTANCE_ID_FORMAT {
+            ffd.instance_id = Some(instance_id);
+        }
+
+        Ok(uuid)
     }
 
     ///  Write the format number, maximum number of files per directory and
     ///    the addressing scheme to a new format file in PATH, possibly expecting
     ///    to overwrite a previously existing file.
-    fn write_format(&mut self, overwrite: bool) -> Result<(), BackendError> {
+    pub(super) fn write_format(&mut self, overwrite: bool) -> Result<(), BackendError> {
         let ffd = self._data();
-        let format_path = self.path.join(PATH_FORMAT);
+        let format_path = self.path_format();
 
         assert!(
             1 <= ffd.format && ffd.format <= FORMAT_NUMBER,
@@ -192,20 +530,24 @@ impl FsFsBackend {
         );
 
         let mut sb = String::new();
-        sb.write_str(&format!("{}\n", ffd.format))?;
+        if ffd.binary_items {
+            sb.write_str(&fsx::unparse_format(ffd.max_files_per_dir))?;
+        } else {
+            sb.write_str(&format!("{}\n", ffd.format))?;
 
-        if ffd.format > 3 {
-            if ffd.max_files_per_dir > 0 {
-                sb.write_str(&format!("layout sharded {}\n", ffd.max_files_per_dir))?;
-            } else {
-                sb.write_str("layout linear\n")?;
+            if ffd.format >= SVN_FS_FS__MIN_LAYOUT_FORMAT_OPTION_FORMAT {
+                if ffd.max_files_per_dir > 0 {
+                    sb.write_str(&format!("layout sharded {}\n", ffd.max_files_per_dir))?;
+                } else {
+                    sb.write_str("layout linear\n")?;
+                }
             }
-        }
-        if ffd.format >= 7 {
-            if ffd.use_log_addressing {
-                sb.write_str("addressing logical\n")?;
-            } else {
-                sb.write_str("addressing physical\n")?;
+            if ffd.format >= SVN_FS_FS__MIN_LOG_ADDRESSING_FORMAT {
+                if ffd.use_log_addressing {
+                    sb.write_str("addressing logical\n")?;
+                } else {
+                    sb.write_str("addressing physical\n")?;
+                }
             }
         }
 
@@ -221,51 +563,173 @@ impl FsFsBackend {
             }
             fs_err::write(&format_path, sb)?;
         } else {
-            svn_subr::io::write_atomic(&self.path, sb.as_bytes(), None, ffd.flush_to_disk)?;
+            svn_subr::io::write_atomic(&format_path, sb.as_bytes(), None, ffd.flush_to_disk)?;
         }
 
         svn_subr::io::set_file_read_only(&format_path, false)?;
         Ok(())
     }
 
+    /// Upgrade the filesystem at PATH from format 4 or later to
+    /// `FORMAT_NUMBER` in place, adding the files the new format expects
+    /// and packing the revprops of packed shards.  The layout and the
+    /// addressing mode stay as they are; changing them takes a dump and
+    /// load.  The 'format' file is rewritten last, so that an interrupted
+    /// upgrade leaves a filesystem of the old format that can be upgraded
+    /// again.  Report the steps to NOTIFY and poll CANCEL.
+    ///
+    /// `svn_fs_fs__upgrade` / `upgrade_body`
+    pub(crate) fn upgrade(
+        &mut self,
+        path: &Path,
+        mut notify: Option<&mut dyn FnMut(i64, UpgradeNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        self.path = path.to_path_buf();
+        self._read_format_file(path)?;
+        let format = self._data().format;
+
+        // If we're already up-to-date, there's nothing to be done here.
+        if format == FORMAT_NUMBER {
+            return Ok(());
+        }
+        if format < SVN_FS_FS__MIN_PACKED_FORMAT {
+            return Err(BackendError::UnsupportedFeature(format!(
+                "Cannot upgrade FSFS format {format} in place; only formats \
+                 {SVN_FS_FS__MIN_PACKED_FORMAT} to {} can be upgraded, dump and load \
+                 the repository instead",
+                FORMAT_NUMBER - 1
+            )));
+        }
+
+        let _write_lock = FileLock::lock(&self.path_lock(), true)?;
+        let _pack_lock = FileLock::lock(&self.path_pack_lock(), true)?;
+        let _txn_current_lock = FileLock::lock(&self.path_txn_current_lock(), true)?;
+
+        // If the config file does not exist, create one.
+        if !path.join(FsFsConfig::PATH_CONFIG).exists() {
+            FsFsConfig::write_config(path).map_err(|e| match e {
+                crate::Error::Io(e) => BackendError::Io(e),
+                e => BackendError::InvalidConfig(e.to_string()),
+            })?;
+        }
+
+        // Add the files that the format promises but that might have
+        // gone missing.
+        if !self.path_txn_current().exists() {
+            fs_err::write(self.path_txn_current(), "0\n")?;
+        }
+        fs_err::create_dir_all(self.path_txn_proto_revs())?;
+        if !self.path_min_unpacked_rev().exists() {
+            fs_err::write(self.path_min_unpacked_rev(), "0\n")?;
+        }
+
+        let uuid = self._open(path)?;
+
+        // The revprops of packed shards must be packed as well.
+        if format < SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+            self.upgrade_pack_revprops(&mut notify, cancel)?;
+        }
+
+        // Always add / bump the instance ID such that no form of caching
+        // accidentally uses outdated data.  Keep the UUID.
+        self._data_mut().format = FORMAT_NUMBER;
+        self.set_uuid(Some(uuid), None)?;
+
+        // Bump the format file.
+        self.write_format(true)?;
+        if let Some(notify) = notify {
+            notify(FORMAT_NUMBER as i64, UpgradeNotifyAction::FormatBumped);
+        }
+
+        Ok(())
+    }
+
+    /// Take the pack lock and the write lock of FS, in that order, and
+    /// call FREEZE_FUNC while holding them.  Meanwhile, no commit, revprop
+    /// change or pack can proceed.  The rep cache gets locked as well,
+    /// if rep-sharing is enabled.
+    ///
+    /// `svn_fs_fs__freeze` / `freeze_body`
+    pub(crate) fn freeze(&self, freeze_func: &mut dyn FnMut()) -> Result<(), BackendError> {
+        let _pack_lock = if self._data().format >= SVN_FS_FS__MIN_PACK_LOCK_FORMAT {
+            Some(FileLock::lock(&self.path_pack_lock(), true)?)
+        } else {
+            None
+        };
+        let _write_lock = FileLock::lock(&self.path_lock(), true)?;
+
+        if self._data().rep_sharing_allowed && self.exists_rep_cache() {
+            self.with_rep_cache_lock(&mut || {
+                freeze_func();
+                Ok(())
+            })
+        } else {
+            freeze_func();
+            Ok(())
+        }
+    }
+
     ///  Open the fsfs filesystem pointed to by PATH and associate it with
     ///    filesystem object FS.  Use POOL for temporary allocations.
     ///
     ///    ### Some parts of *FS must have been initialized beforehand; some parts
     ///       (including FS->path) are initialized by this function.
     /// `svn_fs_fs__open`
-    fn _open(&mut self, path: &Path) -> Result<(), BackendError> {
+    pub(crate) fn _open(&mut self, path: &Path) -> Result<Uuid, BackendError> {
         self.path = path.to_path_buf();
 
         // Read the FS format file.
         self._read_format_file(path)?;
 
         // Read in and cache the repository uuid.
-        self._read_uuid(path)?;
+        let uuid = self._read_uuid(path)?;
 
-        let ffd = self._data_mut();
         // Read the min unpacked revision.
-        if ffd.format >= 4 {
+        if self._data().format >= SVN_FS_FS__MIN_PACKED_FORMAT {
             self._update_min_unpacked_rev()?;
         }
 
         //  Read the configuration file.
         self._read_config(path)?;
 
-        Ok(())
+        let youngest = self.read_current()?;
+        self._data_mut().youngest_rev_cache = youngest;
+
+        Ok(uuid)
+    }
+
+    /// Like [`FsFsBackend::_open`], but recreate a `current` file that
+    /// opening can read if it is missing or garbled; the recovery itself
+    /// will put the right revision into it.
+    ///
+    /// `svn_fs_fs__open_for_recovery`
+    pub(crate) fn _open_for_recovery(&mut self, path: &Path) -> Result<Uuid, BackendError> {
+        self.path = path.to_path_buf();
+        self._read_format_file(path)?;
+        self.prepare_current_for_recovery()?;
+
+        self._open(path)
     }
 
     /// Read the 'format' file of fsfs filesystem FS and store its info in FS.
     ///
     /// `svn_fs_fs__read_format_file`
     fn _read_format_file(&mut self, path: &Path) -> Result<(), BackendError> {
-        let ffd = self._data_mut();
         let format_path = path.join(PATH_FORMAT);
 
-        // Read info from format file.
-        let (format, max_files_per_dir, use_log_addressing) = Self::_read_format(&format_path)?;
+        // Read info from format file.  FSX has a format of its own, which
+        // provides all features of the latest FSFS format.
+        let (format, max_files_per_dir, use_log_addressing) = if self._data().binary_items {
+            let content = fs_err::read_to_string(&format_path)?;
+            let max_files_per_dir = fsx::parse_format(&format_path, &content)?;
+            (FORMAT_NUMBER, max_files_per_dir, true)
+        } else {
+            Self::_read_format(&format_path)?
+        };
 
         //  Now that we've got *all* info, store / update values in FFD.
+        let ffd = self._data_mut();
         ffd.format = format;
         ffd.max_files_per_dir = max_files_per_dir;
         ffd.use_log_addressing = use_log_addressing;
@@ -283,30 +747,73 @@ impl FsFsBackend {
     ///   and will be set to FALSE for physical addressing.
     /// `read_format`
     fn _read_format(format_path: &Path) -> Result<(u32, u32, bool), BackendError> {
-        let mut format = 0;
+        let corrupt = |message: String| BackendError::CorruptFormat {
+            path: format_path.to_string_lossy().to_string(),
+            message,
+        };
+
+        let content = match fs_err::read_to_string(format_path) {
+            Ok(content) => content,
+            // Treat an absent format file as format 1.  Do not try to
+            // create the format file on the fly, because the repository
+            // might be read-only for us, or this might be a read-only
+            // operation, and the spirit of FSFS is to make no changes
+            // whatseover in read-only operations.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok((1, 0, false));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut lines = content.lines();
+        let format: u32 = lines
+            .next()
+            .and_then(|line| line.trim().parse().ok())
+            .ok_or_else(|| corrupt("expected a format number".to_string()))?;
+        if !(1..=FORMAT_NUMBER).contains(&format) {
+            return Err(BackendError::UnsupportedFormat {
+                expected: FORMAT_NUMBER,
+                found: format,
+            });
+        }
+
+        // Set the default values for only those options that are
+        // supported by the format.
         let mut max_files_per_dir = 0;
         let mut use_log_addressing = false;
 
-        // Read the format file and parse the values.
-        if format_path.exists() {
-            let content = fs_err::read_to_string(format_path)?;
-            for line in content.lines() {
-                if line.starts_with("layout sharded") {
-                    max_files_per_dir = line
-                        .split_whitespace()
-                        .nth(2)
-                        .and_then(|s| s.parse::<u32>().ok())
-                        .unwrap_or(0);
-                } else if line.starts_with("layout linear") {
+        // Read any options.
+        for line in lines {
+            if format >= SVN_FS_FS__MIN_LAYOUT_FORMAT_OPTION_FORMAT
+                && let Some(layout) = line.strip_prefix("layout ")
+            {
+                if layout == "linear" {
                     max_files_per_dir = 0;
-                } else if line.starts_with("addressing logical") {
-                    use_log_addressing = true;
-                } else if line.starts_with("addressing physical") {
-                    use_log_addressing = false;
-                } else if let Ok(num) = line.parse::<u32>() {
-                    format = num;
+                    continue;
+                }
+                if let Some(shard_size) = layout.strip_prefix("sharded ") {
+                    max_files_per_dir = shard_size
+                        .parse()
+                        .map_err(|_| corrupt(format!("invalid shard size '{shard_size}'")))?;
+                    continue;
+                }
+            }
+
+            if format >= SVN_FS_FS__MIN_LOG_ADDRESSING_FORMAT {
+                match line {
+                    "addressing logical" => {
+                        use_log_addressing = true;
+                        continue;
+                    }
+                    "addressing physical" => {
+                        use_log_addressing = false;
+                        continue;
+                    }
+                    _ => {}
                 }
             }
+
+            return Err(corrupt(format!("unrecognized option '{line}'")));
         }
 
         Ok((format, max_files_per_dir, use_log_addressing))
@@ -314,7 +821,7 @@ impl FsFsBackend {
 
     /// Read FS's UUID file and store the data in the FS struct.
     /// `read_uuid`
-    fn _read_uuid(&mut self, path: &Path) -> Result<(), BackendError> {
+    fn _read_uuid(&mut self, path: &Path) -> Result<Uuid, BackendError> {
         let uuid_path = path.join(PATH_UUID);
         if !uuid_path.exists() {
             return Err(BackendError::FileNotFound(
@@ -322,31 +829,40 @@ impl FsFsBackend {
             ));
         }
 
-        let uuid = fs_err::read_to_string(uuid_path)?;
-        // self._data_mut().uuid = uuid.trim().to_string();
-        // FIXME:
+        let content = fs_err::read_to_string(uuid_path)?;
+        let mut lines = content.lines();
 
-        Ok(())
+        // Read the repository uuid.
+        let uuid = Uuid::parse_str(lines.next().unwrap_or_default().trim())?;
+
+        // Read the instance ID.
+        let ffd = self._data_mut();
+        if ffd.format >= SVN_FS_FS__MIN_INSTANCE_ID_FORMAT {
+            ffd.instance_id = lines.next().map(|line| line.trim().to_string());
+        } else {
+            ffd.instance_id = Some(uuid.to_string());
+        }
+
+        Ok(uuid)
     }
 
     /// Re-read the MIN_UNPACKED_REV member of FS from disk
     /// `svn_fs_fs__update_min_unpacked_rev`
     fn _update_min_unpacked_rev(&mut self) -> Result<(), BackendError> {
         let ffd = self._data_mut();
-        assert!(ffd.format >= 4, "format must be at least 4");
+        assert!(
+            ffd.format >= SVN_FS_FS__MIN_PACKED_FORMAT,
+            "format must be at least 4"
+        );
         self._read_min_unpacked_rev()?;
         Ok(())
     }
 
-    /// Set *MIN_UNPACKED_REV to the integer value read from the file returned
-    /// by #svn_fs_fs__path_min_unpacked_rev() for FS.
-    /// `svn_fs_fs__read_min_unpacked_rev`
-    ///
+    /// Set the MIN_UNPACKED_REV member of FS to the value read from the
+    /// file returned by [`FsFsBackend::path_min_unpacked_rev`].
     fn _read_min_unpacked_rev(&mut self) -> Result<(), BackendError> {
-        let p = self.path.join(PATH_MIN_UNPACKED_REV);
-        let read = fs_err::read_to_string(&p)?;
-        let ffd = self._data_mut();
-        ffd.min_unpacked_rev = read.trim().parse()?;
+        let min_unpacked_rev = self.read_min_unpacked_rev()?;
+        self._data_mut().min_unpacked_rev = min_unpacked_rev;
         Ok(())
     }
 
@@ -356,7 +872,7 @@ impl FsFsBackend {
     /// `read_config`
     fn _read_config(&mut self, path: &Path) -> Result<(), BackendError> {
         let config_path = path.join(FsFsConfig::PATH_CONFIG);
-        let config = SvnConfig::from_path(&config_path);
+        let config = SvnConfig::from_path(&config_path)?;
 
         let ffd = self._data_mut();
         // Initialize ffd->rep_sharing_allowed.
@@ -409,36 +925,223 @@ impl FsFsBackend {
             ffd.compress_packed_revprops = false;
         }
 
-        if ffd.format >= 7 {
-            todo!()
+        // Initialize ffd->block_size and the index page sizes.
+        if ffd.format >= SVN_FS_FS__MIN_LOG_ADDRESSING_FORMAT {
+            ffd.block_size = config.get_i64(CONFIG_SECTION_IO, CONFIG_OPTION_BLOCK_SIZE, 64)?;
+            ffd.l2p_page_size =
+                config.get_i64(CONFIG_SECTION_IO, CONFIG_OPTION_L2P_PAGE_SIZE, 0x2000)?;
+            ffd.p2l_page_size =
+                config.get_i64(CONFIG_SECTION_IO, CONFIG_OPTION_P2L_PAGE_SIZE, 0x400)?;
+
+            // Don't accept unreasonable or illegal values.
+            // Block size and P2L page size are in kbytes;
+            // L2P blocks are arrays of apr_off_t.
+            if ffd.block_size <= 0 || ffd.block_size > 0x400 * 0x400 {
+                return Err(BackendError::InvalidConfig(format!(
+                    "{CONFIG_OPTION_BLOCK_SIZE} must be between 1 and 1048576, not {}",
+                    ffd.block_size
+                )));
+            }
+            if ffd.l2p_page_size <= 0 || ffd.l2p_page_size > 0x100000 {
+                return Err(BackendError::InvalidConfig(format!(
+                    "{CONFIG_OPTION_L2P_PAGE_SIZE} must be between 1 and 1048576, not {}",
+                    ffd.l2p_page_size
+                )));
+            }
+            if ffd.p2l_page_size <= 0 || ffd.p2l_page_size > 0x400 * 0x400 {
+                return Err(BackendError::InvalidConfig(format!(
+                    "{CONFIG_OPTION_P2L_PAGE_SIZE} must be between 1 and 1048576, not {}",
+                    ffd.p2l_page_size
+                )));
+            }
+
+            ffd.block_size *= 0x400;
+            ffd.p2l_page_size *= 0x400;
+        } else {
+            // should be irrelevant but we initialize them anyway
+            ffd.block_size = 0x1000; // Matches default APR file buffer size.
+            ffd.l2p_page_size = 0x2000; // Matches above default.
+            ffd.p2l_page_size = 0x100000; // Matches above default in bytes.
+        }
+
+        // Initialize ffd->memcache.
+        ffd.memcache = svn_subr::make_memcache_from_config(&config)?;
+
+        // Initialize ffd->fail_stop.
+        ffd.fail_stop = config.get_bool(CONFIG_SECTION_CACHES, CONFIG_OPTION_FAIL_STOP, false)?;
+
+        if ffd.format >= SVN_FS_FS__MIN_LOG_ADDRESSING_FORMAT {
+            ffd.pack_after_commit =
+                config.get_bool(CONFIG_SECTION_DEBUG, CONFIG_OPTION_PACK_AFTER_COMMIT, false)?;
+        } else {
+            ffd.pack_after_commit = false;
+        }
+
+        if ffd.format >= SVN_FS_FS__MIN_SVNDIFF1_FORMAT {
+            let compression_val =
+                config.get(CONFIG_SECTION_DELTIFICATION, CONFIG_OPTION_COMPRESSION);
+            let compression_level_val = config.get(
+                CONFIG_SECTION_DELTIFICATION,
+                CONFIG_OPTION_COMPRESSION_LEVEL,
+            );
+
+            ffd.delta_compression_type = match (compression_val, compression_level_val) {
+                (Some(_), Some(_)) => {
+                    return Err(BackendError::InvalidConfig(format!(
+                        "The '{CONFIG_OPTION_COMPRESSION}' and '{CONFIG_OPTION_COMPRESSION_LEVEL}' \
+                         options are mutually exclusive"
+                    )));
+                }
+                (Some(compression), None) => parse_compression_option(compression)?,
+                (None, Some(_)) => {
+                    // Handle the deprecated 'compression-level' option.
+                    let level = config
+                        .get_i64(
+                            CONFIG_SECTION_DELTIFICATION,
+                            CONFIG_OPTION_COMPRESSION_LEVEL,
+                            SVN_DELTA_COMPRESSION_LEVEL_DEFAULT,
+                        )?
+                        .clamp(
+                            SVN_DELTA_COMPRESSION_LEVEL_NONE,
+                            SVN_DELTA_COMPRESSION_LEVEL_MAX,
+                        );
+                    if level == SVN_DELTA_COMPRESSION_LEVEL_NONE {
+                        CompressionType::None
+                    } else {
+                        CompressionType::Zlib(level as i32)
+                    }
+                }
+                (None, None) => {
+                    // Nothing specified explicitly, use the default options:
+                    // LZ4 compression for formats supporting it and zlib
+                    // otherwise.
+                    if ffd.format >= SVN_FS_FS__MIN_SVNDIFF2_FORMAT {
+                        CompressionType::Lz4
+                    } else {
+                        CompressionType::Zlib(SVN_DELTA_COMPRESSION_LEVEL_DEFAULT as i32)
+                    }
+                }
+            };
+
+            // Check that the repository format supports the requested
+            // compression type.
+            if ffd.delta_compression_type == CompressionType::Lz4
+                && ffd.format < SVN_FS_FS__MIN_SVNDIFF2_FORMAT
+            {
+                return Err(BackendError::InvalidConfig(format!(
+                    "Compression type 'lz4' requires FSFS format {SVN_FS_FS__MIN_SVNDIFF2_FORMAT}, \
+                     but the repository has format {}",
+                    ffd.format
+                )));
+            }
         } else {
-            todo!()
+            ffd.delta_compression_type = CompressionType::None;
         }
 
+        ffd.verify_before_commit = config.get_bool(
+            CONFIG_SECTION_DEBUG,
+            CONFIG_OPTION_VERIFY_BEFORE_COMMIT,
+            false,
+        )?;
+
         Ok(())
     }
+}
 
-    /// Initialize all session-local caches in FS according to the global
-    /// cache settings. Use POOL for temporary allocations.
-    ///
-    /// Please note that it is permissible for this function to set some
-    /// or all of these caches to NULL, regardless of any setting.
+/// node origins
+impl FsFsBackend {
+    /// Return the node-origins cache file at @a path as a hash from node
+    /// IDs to node-revision IDs, or `None` if it does not exist.
     ///
-    /// `svn_fs_fs__initialize_caches`
-    fn _initialize_caches(fs: &mut SvnFs) -> Result<(), BackendError> {
-        let ffd = fs.inner_mut().data_mut().downcast_mut::<FsFsData>();
-
-        let prefix = format!("fsfs:{}/{}:", fs.uuid, fs.path().display());
+    /// `get_node_origins_from_file`
+    fn get_node_origins_from_file(
+        path: &Path,
+    ) -> Result<Option<HashMap<String, String>>, BackendError> {
+        let content = match fs_err::read(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let (origins, _) = svn_subr::hash::read_hash(&content, HASH_TERMINATOR).map_err(|_| {
+            BackendError::CorruptFile {
+                path: path.to_string_lossy().to_string(),
+                message: "Failed to parse node-origins file".to_string(),
+            }
+        })?;
+        Ok(Some(origins))
+    }
 
-        // let no_handler = ffd.fail_stop;
+    /// Return the node-revision ID the node @a node_id originates from,
+    /// if the node-origins cache knows it.
+    ///
+    /// `svn_fs_fs__get_node_origin`
+    pub(crate) fn get_node_origin(&self, node_id: &IdPart) -> Result<Option<FsId>, BackendError> {
+        let Some(origins) = Self::get_node_origins_from_file(&self.path_node_origin(node_id))?
+        else {
+            return Ok(None);
+        };
+
+        Ok(match origins.get(&u64_to_base36(node_id.number)) {
+            Some(origin_id) => Some(origin_id.parse()?),
+            None => None,
+        })
+    }
 
-        let (cache_namespace, cache_txdeltas, cache_fulltexts, cache_nodeprops) =
-            super::caching::read_config(&fs)?;
+    /// Record in the node-origins cache that the node @a node_id
+    /// originates from the node-revision @a origin_id.
+    ///
+    /// `svn_fs_fs__set_node_origin`
+    pub(crate) fn set_node_origin(
+        &self,
+        node_id: &IdPart,
+        origin_id: &FsId,
+    ) -> Result<(), BackendError> {
+        match self.set_node_origins_for_file(node_id, origin_id) {
+            // It's just a cache; stop trying if I can't write.
+            Err(BackendError::Io(e)) if e.kind() == std::io::ErrorKind::PermissionDenied => Ok(()),
+            result => result,
+        }
+    }
 
-        let prefix = format!("ns:{cache_namespace}:{prefix}");
-        let has_namespace = !cache_namespace.is_empty();
+    /// `set_node_origins_for_file`
+    fn set_node_origins_for_file(
+        &self,
+        node_id: &IdPart,
+        origin_id: &FsId,
+    ) -> Result<(), BackendError> {
+        fs_err::create_dir_all(self.path.join(PATH_NODE_ORIGINS_DIR))?;
+
+        // Read the previously existing origins (if any), and merge our
+        // update with it.
+        let path = self.path_node_origin(node_id);
+        let mut origins = Self::get_node_origins_from_file(&path)?.unwrap_or_default();
+        let node_id = u64_to_base36(node_id.number);
+        let origin_id = origin_id.to_string();
+        if let Some(old_origin_id) = origins.get(&node_id)
+            && *old_origin_id != origin_id
+        {
+            return Err(BackendError::Corrupt(format!(
+                "Node origin for '{node_id}' exists with a different value \
+                 ({old_origin_id}) than what we were about to store ({origin_id})"
+            )));
+        }
+        origins.insert(node_id, origin_id);
+
+        // Sure, there's a race condition here.  Two processes could be
+        // trying to add different cache elements to the same file at the
+        // same time, and the entries added by the first one to write will
+        // be lost.  But this is just a cache of reconstructible data, so
+        // we'll accept this problem in return for not having to deal with
+        // locking overhead.
+        svn_subr::io::write_atomic(
+            &path,
+            svn_subr::hash::write_hash(&origins, HASH_TERMINATOR).as_bytes(),
+            Some(&self.path_uuid()),
+            self._data().flush_to_disk,
+        )?;
 
-        todo!()
+        Ok(())
     }
 }
 
@@ -455,3 +1158,50 @@ const SVN_FS_FS_MAX_DELTIFICATION_WALK: i64 = 1023;
 /// Should be a power of 2.
 /// Values < 2 will result in standard skip-delta behavior.
 const SVN_FS_FS_MAX_LINEAR_DELTIFICATION: i64 = 16;
+
+/// Default zlib compression level for svndiff.
+///
+/// `SVN_DELTA_COMPRESSION_LEVEL_DEFAULT`
+const SVN_DELTA_COMPRESSION_LEVEL_DEFAULT: i64 = 5;
+
+/// `SVN_DELTA_COMPRESSION_LEVEL_NONE`
+const SVN_DELTA_COMPRESSION_LEVEL_NONE: i64 = 0;
+
+/// `SVN_DELTA_COMPRESSION_LEVEL_MAX`
+const SVN_DELTA_COMPRESSION_LEVEL_MAX: i64 = 9;
+
+/// Parse the value of the `[deltification] compression` option: one of
+/// "none", "lz4", "zlib" or "zlib-N" with N being a zlib level.
+///
+/// `parse_compression_option`
+fn parse_compression_option(value: &str) -> Result<CompressionType, BackendError> {
+    let invalid = || {
+        BackendError::InvalidConfig(format!(
+            "Invalid '{CONFIG_OPTION_COMPRESSION}' value '{value}' in the config"
+        ))
+    };
+
+    match value {
+        "none" => Ok(CompressionType::None),
+        "lz4" => Ok(CompressionType::Lz4),
+        "zlib" => Ok(CompressionType::Zlib(
+            SVN_DELTA_COMPRESSION_LEVEL_DEFAULT as i32,
+        )),
+        _ => {
+            let level: i64 = value
+                .strip_prefix("zlib-")
+                .and_then(|level| level.parse().ok())
+                .ok_or_else(invalid)?;
+            if !(SVN_DELTA_COMPRESSION_LEVEL_NONE..=SVN_DELTA_COMPRESSION_LEVEL_MAX)
+                .contains(&level)
+            {
+                return Err(invalid());
+            }
+            if level == SVN_DELTA_COMPRESSION_LEVEL_NONE {
+                Ok(CompressionType::None)
+            } else {
+                Ok(CompressionType::Zlib(level as i32))
+            }
+        }
+    }
+}
@@ -0,0 +1,585 @@
+//! `subversion/libsvn_fs_fs/hotcopy.c`
+//!
+//! A hotcopy copies a live filesystem while commits to it continue.
+//! Rev files never change once written, so it is enough to copy the
+//! revisions up to the youngest one at the start and to publish them in
+//! the destination's `current` file only once they are all in place.  An
+//! incremental hotcopy skips files that the destination has already.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use svn_subr::io::FileLock;
+use svn_types::RevisionNumber;
+use uuid::Uuid;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::SVN_FS_FS__MIN_CONFIG_FILE;
+use super::SVN_FS_FS__MIN_PACK_LOCK_FORMAT;
+use super::SVN_FS_FS__MIN_PACKED_FORMAT;
+use super::SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT;
+use super::SVN_FS_FS__MIN_REP_SHARING_FORMAT;
+use super::SVN_FS_FS__MIN_TXN_CURRENT_FORMAT;
+use crate::FsFsConfig;
+use crate::backend::PATH_EXT_PACKED_SHARD;
+use crate::backend::PATH_FORMAT;
+use crate::backend::PATH_LOCKS_DIR;
+use crate::backend::PATH_NODE_ORIGINS_DIR;
+use crate::backend::PATH_REVPROPS_DIR;
+use crate::backend::PATH_REVS_DIR;
+use crate::backend::PATH_TXN_CURRENT;
+use crate::backend::check_cancel;
+
+/// Like [`svn_subr::io::copy_file`] for the file @a file in @a src_dir,
+/// but skip the copy if the file in @a dst_dir has the same size and is
+/// at least as new.  Return whether the copy has been skipped.
+///
+/// `hotcopy_io_dir_file_copy`
+fn hotcopy_io_dir_file_copy(
+    src_dir: &Path,
+    dst_dir: &Path,
+    file: &str,
+) -> Result<bool, BackendError> {
+    let src = src_dir.join(file);
+    let dst = dst_dir.join(file);
+
+    // Does the destination already exist?  If not, we must copy it.
+    if let Ok(dst_meta) = fs_err::metadata(&dst) {
+        let src_meta = fs_err::metadata(&src)?;
+        if src_meta.len() == dst_meta.len() && src_meta.modified()? <= dst_meta.modified()? {
+            // skip
+            return Ok(true);
+        }
+    }
+
+    svn_subr::io::copy_file(&src, &dst)?;
+    Ok(false)
+}
+
+/// Copy the directory @a src_dir to @a dst_dir recursively, skipping
+/// files that are up to date as [`hotcopy_io_dir_file_copy`] does.
+/// Return whether all files have been skipped.
+///
+/// `hotcopy_io_copy_dir_recursively`
+fn hotcopy_io_copy_dir_recursively(
+    src_dir: &Path,
+    dst_dir: &Path,
+    cancel: Option<&dyn Fn() -> bool>,
+) -> Result<bool, BackendError> {
+    fs_err::create_dir_all(dst_dir)?;
+
+    let mut skipped = true;
+    for entry in fs_err::read_dir(src_dir)? {
+        check_cancel(cancel)?;
+
+        let entry = entry?;
+        let name = entry.file_name();
+        if entry.file_type()?.is_dir() {
+            skipped &= hotcopy_io_copy_dir_recursively(
+                &src_dir.join(&name),
+                &dst_dir.join(&name),
+                cancel,
+            )?;
+        } else {
+            skipped &= hotcopy_io_dir_file_copy(src_dir, dst_dir, &name.to_string_lossy())?;
+        }
+    }
+
+    Ok(skipped)
+}
+
+/// Return the directory within @a subdir that holds the file of revision
+/// @a rev, given @a max_files_per_dir.
+fn shard_dir(subdir: &Path, rev: RevisionNumber, max_files_per_dir: RevisionNumber) -> PathBuf {
+    if max_files_per_dir > 0 {
+        subdir.join((rev / max_files_per_dir).to_string())
+    } else {
+        subdir.to_path_buf()
+    }
+}
+
+/// Copy the file of revision @a rev from @a src_subdir to @a dst_subdir,
+/// which are either the revs or the revprops directories, creating the
+/// destination shard as needed.  Return whether the copy has been
+/// skipped.
+///
+/// `hotcopy_copy_shard_file`
+fn hotcopy_copy_shard_file(
+    src_subdir: &Path,
+    dst_subdir: &Path,
+    rev: RevisionNumber,
+    max_files_per_dir: RevisionNumber,
+) -> Result<bool, BackendError> {
+    let src_subdir_shard = shard_dir(src_subdir, rev, max_files_per_dir);
+    let dst_subdir_shard = shard_dir(dst_subdir, rev, max_files_per_dir);
+    fs_err::create_dir_all(&dst_subdir_shard)?;
+
+    hotcopy_io_dir_file_copy(&src_subdir_shard, &dst_subdir_shard, &rev.to_string())
+}
+
+/// Remove the files of revisions @a start_rev to @a end_rev (exclusive)
+/// from the shards in @a subdir, ignoring files that do not exist.
+///
+/// `hotcopy_remove_files`
+fn hotcopy_remove_files(
+    subdir: &Path,
+    start_rev: RevisionNumber,
+    end_rev: RevisionNumber,
+    max_files_per_dir: RevisionNumber,
+) -> Result<(), BackendError> {
+    for rev in start_rev..end_rev {
+        let path = shard_dir(subdir, rev, max_files_per_dir).join(rev.to_string());
+        match fs_err::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the directory @a path and its contents, if it exists.
+///
+/// `remove_folder`
+fn remove_folder(path: &Path) -> Result<(), BackendError> {
+    match fs_err::remove_dir_all(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+impl FsFsBackend {
+    /// Copy this filesystem with the UUID @a uuid to @a dst, whose path
+    /// must be set.  If @a incremental is set and @a dst exists already,
+    /// copy only what it is missing.  Otherwise, @a dst must not exist
+    /// yet.  Call @a notify with the range of revisions copied at every
+    /// step and poll @a cancel in between.
+    ///
+    /// `svn_fs_fs__hotcopy`
+    pub(crate) fn hotcopy(
+        &self,
+        uuid: Uuid,
+        dst: &mut FsFsBackend,
+        incremental: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber, RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let dst_path = dst.path().clone();
+        if incremental && dst_path.join(PATH_FORMAT).exists() {
+            // Check the existing repository.
+            let dst_uuid = dst._open(&dst_path)?;
+            self.hotcopy_incremental_check_preconditions(dst, uuid == dst_uuid)?;
+        } else if dst_path.join(PATH_FORMAT).exists() {
+            // Creating the empty destination would clobber this one.
+            return Err(BackendError::FileExists(
+                dst_path.join(PATH_FORMAT).display().to_string(),
+            ));
+        } else {
+            // Destination doesn't exist yet.  Perform a normal hotcopy to
+            // an empty destination using the same format as the source.
+            self.hotcopy_create_empty_dest(dst, &dst_path, uuid)?;
+        }
+
+        // Keep packing out of the source (and commits out of old formats)
+        // and everybody out of the destination while copying.
+        let _src_lock = if self._data().format >= SVN_FS_FS__MIN_PACK_LOCK_FORMAT {
+            FileLock::lock(&self.path_pack_lock(), true)?
+        } else {
+            FileLock::lock(&self.path_lock(), true)?
+        };
+        let _dst_write_lock = FileLock::lock(&dst.path_lock(), true)?;
+        let _dst_pack_lock = if dst._data().format >= SVN_FS_FS__MIN_PACK_LOCK_FORMAT {
+            Some(FileLock::lock(&dst.path_pack_lock(), true)?)
+        } else {
+            None
+        };
+        let _dst_txn_current_lock = if dst._data().format >= SVN_FS_FS__MIN_TXN_CURRENT_FORMAT {
+            Some(FileLock::lock(&dst.path_txn_current_lock(), true)?)
+        } else {
+            None
+        };
+
+        self.hotcopy_body(dst, incremental, notify, cancel)
+    }
+
+    /// Verify that @a dst can receive an incremental hotcopy of this
+    /// filesystem.  @a same_uuid tells whether both have the same UUID.
+    ///
+    /// `hotcopy_incremental_check_preconditions`
+    fn hotcopy_incremental_check_preconditions(
+        &self,
+        dst: &FsFsBackend,
+        same_uuid: bool,
+    ) -> Result<(), BackendError> {
+        let (src_ffd, dst_ffd) = (self._data(), dst._data());
+
+        // We only support incremental hotcopy between the same format.
+        if src_ffd.format != dst_ffd.format {
+            return Err(BackendError::UnsupportedFeature(format!(
+                "The FSFS format ({}) of the hotcopy source does not match the FSFS format \
+                 ({}) of the hotcopy destination; please upgrade both repositories to the \
+                 same format",
+                src_ffd.format, dst_ffd.format
+            )));
+        }
+
+        // Make sure the UUID of source and destination match up.  We don't
+        // want to copy over a different repository.
+        if !same_uuid {
+            return Err(BackendError::UnsupportedFeature(
+                "The UUID of the hotcopy source does not match the UUID of the hotcopy \
+                 destination"
+                    .to_string(),
+            ));
+        }
+
+        // Also require same shard size and addressing.
+        if src_ffd.max_files_per_dir != dst_ffd.max_files_per_dir
+            || src_ffd.use_log_addressing != dst_ffd.use_log_addressing
+        {
+            return Err(BackendError::UnsupportedFeature(
+                "The sharding layout configuration of the hotcopy source does not match the \
+                 sharding layout configuration of the hotcopy destination"
+                    .to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Create an empty filesystem at @a dst_path with the layout of this
+    /// one, its UUID @a uuid and a new instance ID.
+    ///
+    /// `hotcopy_create_empty_dest`
+    fn hotcopy_create_empty_dest(
+        &self,
+        dst: &mut FsFsBackend,
+        dst_path: &Path,
+        uuid: Uuid,
+    ) -> Result<(), BackendError> {
+        let src_ffd = self._data();
+
+        // Create the DST_FS repository with the same layout as SRC_FS.
+        dst.create_file_tree(
+            dst_path,
+            src_ffd.format,
+            src_ffd.max_files_per_dir,
+            src_ffd.use_log_addressing,
+        )?;
+
+        // Copy the UUID.  Hotcopy destination receives a new instance ID,
+        // but has the same filesystem UUID as the source.
+        dst.set_uuid(Some(uuid), None)?;
+
+        // Remove revision 0 contents.  Otherwise, it may not get
+        // overwritten due to having a newer timestamp.
+        fs_err::remove_file(dst.path_rev(0))?;
+        fs_err::remove_file(dst.path_revprops(0))?;
+
+        // This filesystem is ready.  Stamp it with a format number.  Fail
+        // if the 'format' file should already exist.
+        dst.write_format(false)
+    }
+
+    /// Copy the configuration, revisions, locks and rep cache of this
+    /// filesystem to @a dst.  The caller must hold the necessary locks.
+    ///
+    /// `hotcopy_body`
+    fn hotcopy_body(
+        &self,
+        dst: &mut FsFsBackend,
+        incremental: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber, RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let format = self._data().format;
+
+        // Try to copy the config first: without it, the hotcopy would be
+        // unusable anyway.
+        if format >= SVN_FS_FS__MIN_CONFIG_FILE {
+            if !self.path.join(FsFsConfig::PATH_CONFIG).is_file() {
+                return Err(BackendError::FileNotFound(format!(
+                    "Failed to create hotcopy at '{}'. The file '{}' is missing from the \
+                     source repository. Please create this file, for instance by running \
+                     'svnadmin upgrade {}'",
+                    dst.path.display(),
+                    FsFsConfig::PATH_CONFIG,
+                    self.path.display()
+                )));
+            }
+            svn_subr::io::copy_file(
+                &self.path.join(FsFsConfig::PATH_CONFIG),
+                &dst.path.join(FsFsConfig::PATH_CONFIG),
+            )?;
+        }
+
+        check_cancel(cancel)?;
+
+        // Find the youngest revision in the source and destination.  We
+        // only support hotcopies from sources with an equal or greater
+        // amount of revisions than the destination.  This also catches the
+        // case where users accidentally swap the source and destination
+        // arguments.
+        let src_youngest = self.read_current()?;
+        let dst_youngest = if incremental { dst.read_current()? } else { 0 };
+        if src_youngest < dst_youngest {
+            return Err(BackendError::UnsupportedFeature(format!(
+                "The hotcopy destination already contains more revisions ({dst_youngest}) than \
+                 the hotcopy source contains ({src_youngest}); are source and destination \
+                 swapped?"
+            )));
+        }
+
+        check_cancel(cancel)?;
+
+        self.hotcopy_revisions(dst, src_youngest, dst_youngest, incremental, notify, cancel)?;
+
+        // All revisions are in place.  Publish them.
+        dst.write_current(src_youngest)?;
+        dst._data_mut().youngest_rev_cache = src_youngest;
+
+        // Replace the locks tree to get rid of stale locks.  This is racy
+        // in case readers are currently trying to list locks in the
+        // destination, but this small race is acceptable.
+        let dst_locks = dst.path.join(PATH_LOCKS_DIR);
+        remove_folder(&dst_locks)?;
+        let src_locks = self.path.join(PATH_LOCKS_DIR);
+        if src_locks.is_dir() {
+            hotcopy_io_copy_dir_recursively(&src_locks, &dst_locks, cancel)?;
+        }
+
+        // Now copy the node-origins cache tree.
+        let src_origins = self.path.join(PATH_NODE_ORIGINS_DIR);
+        if src_origins.is_dir() {
+            hotcopy_io_copy_dir_recursively(
+                &src_origins,
+                &dst.path.join(PATH_NODE_ORIGINS_DIR),
+                cancel,
+            )?;
+        }
+
+        // Copy the rep cache before copying the txn-current file.  It must
+        // not refer to revisions that the destination does not have, which
+        // may have been committed to the source in the meantime.
+        if format >= SVN_FS_FS__MIN_REP_SHARING_FORMAT && self.exists_rep_cache() {
+            dst.hotcopy_rep_cache(self)?;
+            dst.del_rep_reference(src_youngest)?;
+        }
+
+        // Copy the txn-current file.
+        if format >= SVN_FS_FS__MIN_TXN_CURRENT_FORMAT {
+            svn_subr::io::copy_file(
+                &self.path.join(PATH_TXN_CURRENT),
+                &dst.path.join(PATH_TXN_CURRENT),
+            )?;
+        }
+
+        // Revprops read through caches of the source must not be mistaken
+        // for those of the copy.
+        dst.reset_revprop_generation_file()?;
+
+        Ok(())
+    }
+
+    /// Copy the packed shards and then the non-packed revisions up to
+    /// @a src_youngest, together with their revprops, to @a dst, which has
+    /// revisions up to @a dst_youngest already.
+    ///
+    /// `hotcopy_revisions`
+    fn hotcopy_revisions(
+        &self,
+        dst: &mut FsFsBackend,
+        src_youngest: RevisionNumber,
+        dst_youngest: RevisionNumber,
+        incremental: bool,
+        mut notify: Option<&mut dyn FnMut(RevisionNumber, RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let format = self._data().format;
+        let max_files_per_dir = self._data().max_files_per_dir as RevisionNumber;
+        let src_revs_dir = self.path.join(PATH_REVS_DIR);
+        let dst_revs_dir = dst.path.join(PATH_REVS_DIR);
+        let src_revprops_dir = self.path.join(PATH_REVPROPS_DIR);
+        let dst_revprops_dir = dst.path.join(PATH_REVPROPS_DIR);
+
+        // Read the min unpacked revs.
+        let (src_min_unpacked_rev, mut dst_min_unpacked_rev) =
+            if format >= SVN_FS_FS__MIN_PACKED_FORMAT {
+                (self.read_min_unpacked_rev()?, dst.read_min_unpacked_rev()?)
+            } else {
+                (0, 0)
+            };
+
+        // We only support packs coming from the hotcopy source.  The
+        // destination should not be packed independently from the source.
+        if src_min_unpacked_rev < dst_min_unpacked_rev {
+            return Err(BackendError::UnsupportedFeature(format!(
+                "The hotcopy destination already contains more packed revisions ({}) than \
+                 the hotcopy source contains ({})",
+                dst_min_unpacked_rev - 1,
+                src_min_unpacked_rev - 1
+            )));
+        }
+
+        check_cancel(cancel)?;
+
+        // First, copy packed shards.
+        let mut rev = 0;
+        while rev < src_min_unpacked_rev {
+            check_cancel(cancel)?;
+
+            // Copy the packed shard.
+            let skipped = self.hotcopy_copy_packed_shard(dst, rev, cancel)?;
+
+            // If necessary, update the min-unpacked rev file in the
+            // hotcopy.
+            let pack_end_rev = rev + max_files_per_dir - 1;
+            if dst_min_unpacked_rev < rev + max_files_per_dir {
+                dst_min_unpacked_rev = rev + max_files_per_dir;
+                dst.write_min_unpacked_rev(dst_min_unpacked_rev)?;
+                dst._data_mut().min_unpacked_rev = dst_min_unpacked_rev;
+            }
+
+            // Whenever this pack did not previously exist in the
+            // destination, update 'current' to the most recent packed rev
+            // (so readers can see new revisions which arrived in this
+            // pack).
+            if pack_end_rev > dst_youngest {
+                dst.write_current(pack_end_rev)?;
+            }
+
+            // When notifying about packed shards, report either the full
+            // revision range or nothing.
+            if !skipped && let Some(notify) = &mut notify {
+                notify(rev, pack_end_rev);
+            }
+
+            // Remove revision files which are now packed.
+            if incremental {
+                hotcopy_remove_files(
+                    &dst_revs_dir,
+                    rev,
+                    rev + max_files_per_dir,
+                    max_files_per_dir,
+                )?;
+                if format >= SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+                    // The revprops of r0 never get packed.
+                    hotcopy_remove_files(
+                        &dst_revprops_dir,
+                        rev.max(1),
+                        rev + max_files_per_dir,
+                        max_files_per_dir,
+                    )?;
+                }
+            }
+
+            // Now that all revisions have moved into the pack, the
+            // original rev dir can be removed.
+            remove_folder(&shard_dir(&dst_revs_dir, rev, max_files_per_dir))?;
+            if rev > 0 && format >= SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+                remove_folder(&shard_dir(&dst_revprops_dir, rev, max_files_per_dir))?;
+            }
+
+            rev += max_files_per_dir;
+        }
+
+        check_cancel(cancel)?;
+
+        // Now, copy pairs of non-packed revisions and revprop files.  If
+        // necessary, update 'current' after copying all files from a
+        // shard.
+        while rev <= src_youngest {
+            check_cancel(cancel)?;
+
+            // Copy the rev file.  With formats before the pack lock, the
+            // source may get packed concurrently and the file disappear.
+            let skipped =
+                match hotcopy_copy_shard_file(&src_revs_dir, &dst_revs_dir, rev, max_files_per_dir)
+                {
+                    Err(BackendError::Io(e))
+                        if e.kind() == std::io::ErrorKind::NotFound
+                            && format >= SVN_FS_FS__MIN_PACKED_FORMAT
+                            && self.read_min_unpacked_rev()? > rev =>
+                    {
+                        return Err(BackendError::Corrupt(
+                            "The hotcopy source has been packed while the hotcopy was in \
+                             progress; please restart the hotcopy operation"
+                                .to_string(),
+                        ));
+                    }
+                    result => result?,
+                };
+
+            // Copy the revprop file.
+            let skipped = hotcopy_copy_shard_file(
+                &src_revprops_dir,
+                &dst_revprops_dir,
+                rev,
+                max_files_per_dir,
+            )? && skipped;
+
+            // Whenever this revision did not previously exist in the
+            // destination, checkpoint the progress via 'current' (do that
+            // once per full shard in order not to slow things down).
+            if rev > dst_youngest && max_files_per_dir > 0 && rev % max_files_per_dir == 0 {
+                dst.write_current(rev)?;
+            }
+
+            if !skipped && let Some(notify) = &mut notify {
+                notify(rev, rev);
+            }
+
+            rev += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Copy the packed shard starting with revision @a rev and its
+    /// revprops to @a dst.  Return whether the copy has been skipped
+    /// because the destination has the shard already.
+    ///
+    /// `hotcopy_copy_packed_shard`
+    fn hotcopy_copy_packed_shard(
+        &self,
+        dst: &FsFsBackend,
+        rev: RevisionNumber,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<bool, BackendError> {
+        let format = self._data().format;
+        let max_files_per_dir = self._data().max_files_per_dir as RevisionNumber;
+        let packed_shard = format!("{}{PATH_EXT_PACKED_SHARD}", rev / max_files_per_dir);
+
+        // Copy the packed shard.
+        let skipped = hotcopy_io_copy_dir_recursively(
+            &self.path.join(PATH_REVS_DIR).join(&packed_shard),
+            &dst.path.join(PATH_REVS_DIR).join(&packed_shard),
+            cancel,
+        )?;
+
+        // Copy revprops belonging to revisions in this pack.
+        let src_subdir = self.path.join(PATH_REVPROPS_DIR);
+        let dst_subdir = dst.path.join(PATH_REVPROPS_DIR);
+        if format < SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+            for revprop_rev in rev..rev + max_files_per_dir {
+                check_cancel(cancel)?;
+                hotcopy_copy_shard_file(&src_subdir, &dst_subdir, revprop_rev, max_files_per_dir)?;
+            }
+        } else {
+            // revprop for revision 0 will never be packed
+            if rev == 0 {
+                hotcopy_copy_shard_file(&src_subdir, &dst_subdir, 0, max_files_per_dir)?;
+            }
+
+            // packed revprops folder
+            hotcopy_io_copy_dir_recursively(
+                &src_subdir.join(&packed_shard),
+                &dst_subdir.join(&packed_shard),
+                cancel,
+            )?;
+        }
+
+        Ok(skipped)
+    }
+}
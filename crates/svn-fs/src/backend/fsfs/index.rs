@@ -0,0 +1,903 @@
+//! `subversion/libsvn_fs_fs/index.c`
+//!
+//! Format 7+ revision and pack files end with two indexes: the log-to-phys
+//! (l2p) index maps (revision, item number) pairs to file offsets and the
+//! phys-to-log (p2l) index describes the items found at any given offset.
+
+use std::io::Write;
+
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_types::RevisionNumber;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::rev_file::RevisionFile;
+
+/// Start of the l2p index stream.
+pub(crate) const L2P_STREAM_PREFIX: &str = "L2P-INDEX\n";
+
+/// Start of the p2l index stream.
+pub(crate) const P2L_STREAM_PREFIX: &str = "P2L-INDEX\n";
+
+/// Item index of unused or otherwise uninitialized items.
+pub(crate) const SVN_FS_FS__ITEM_INDEX_UNUSED: u64 = 0;
+
+/// Item index of the changed paths list within a revision.
+pub(crate) const SVN_FS_FS__ITEM_INDEX_CHANGES: u64 = 1;
+
+/// Item index of the root node revision within a revision.
+pub(crate) const SVN_FS_FS__ITEM_INDEX_ROOT_NODE: u64 = 2;
+
+/// First item index usable for arbitrary items.
+pub(crate) const SVN_FS_FS__ITEM_INDEX_FIRST_USER: u64 = 3;
+
+/// Data / item types as stored in the phys-to-log index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ItemType {
+    /// `SVN_FS_FS__ITEM_TYPE_UNUSED`: file section not containing any items
+    Unused = 0,
+    /// `SVN_FS_FS__ITEM_TYPE_FILE_REP`: item is a file representation
+    FileRep = 1,
+    /// `SVN_FS_FS__ITEM_TYPE_DIR_REP`: item is a directory rep.
+    DirRep = 2,
+    /// `SVN_FS_FS__ITEM_TYPE_FILE_PROPS`: item is a file property rep
+    FileProps = 3,
+    /// `SVN_FS_FS__ITEM_TYPE_DIR_PROPS`: item is a directory prop rep
+    DirProps = 4,
+    /// `SVN_FS_FS__ITEM_TYPE_NODEREV`: item is a noderev
+    NodeRev = 5,
+    /// `SVN_FS_FS__ITEM_TYPE_CHANGES`: item is a changed paths list
+    Changes = 6,
+    /// `SVN_FS_FS__ITEM_TYPE_ANY_REP`: item is any representation.
+    AnyRep = 7,
+}
+
+impl ItemType {
+    /// Return the item type with the numeric value @a value.
+    pub(crate) fn from_u64(value: u64) -> Option<Self> {
+        Some(match value {
+            0 => ItemType::Unused,
+            1 => ItemType::FileRep,
+            2 => ItemType::DirRep,
+            3 => ItemType::FileProps,
+            4 => ItemType::DirProps,
+            5 => ItemType::NodeRev,
+            6 => ItemType::Changes,
+            7 => ItemType::AnyRep,
+            _ => return None,
+        })
+    }
+}
+
+/// P2L index entry: describes a section of a rev / pack file.
+///
+/// `svn_fs_fs__p2l_entry_t`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct P2lEntry {
+    /// offset of the first byte that belongs to the item
+    pub offset: u64,
+    /// length of the item in bytes
+    pub size: u64,
+    /// type of the item (see [`ItemType`])
+    pub item_type: ItemType,
+    /// modified FNV-1a checksum.  0 if unknown checksum
+    pub fnv1_checksum: u32,
+    /// revision the item belongs to
+    pub revision: RevisionNumber,
+    /// item number within that revision
+    pub number: u64,
+}
+
+impl P2lEntry {
+    /// Describe the item at @a offset in @a data, checksumming its bytes.
+    pub(crate) fn new(
+        data: &[u8],
+        offset: u64,
+        size: u64,
+        item_type: ItemType,
+        revision: RevisionNumber,
+        number: u64,
+    ) -> Self {
+        let item = &data[offset as usize..(offset + size) as usize];
+        Self {
+            offset,
+            size,
+            item_type,
+            fnv1_checksum: svn_subr::fnv1a_32x4(item),
+            revision,
+            number,
+        }
+    }
+}
+
+/// Append the 7b/8b variable-length encoding of @a value to @a out.
+///
+/// `encode_uint`
+fn encode_uint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value % 0x80) as u8 + 0x80);
+        value /= 0x80;
+    }
+    out.push(value as u8);
+}
+
+/// Append the encoding of the signed @a value to @a out, mapping it to an
+/// unsigned one first (0, -1, 1, -2, ... -> 0, 1, 2, 3, ...).
+///
+/// `encode_int`
+fn encode_int(out: &mut Vec<u8>, value: i64) {
+    let value = if value < 0 {
+        (-1 - 2 * value as i128) as u64
+    } else {
+        (2 * value as i128) as u64
+    };
+    encode_uint(out, value)
+}
+
+/// Decode the unsigned 7b/8b-encoded number at @a pos in @a data and
+/// advance @a pos.
+///
+/// `read_uint64_from_proto_index` / `packed_stream_get`
+fn decode_uint(data: &[u8], pos: &mut usize) -> Result<u64, BackendError> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *data
+            .get(*pos)
+            .ok_or_else(|| BackendError::Corrupt("Unexpected end of index data".to_string()))?;
+        *pos += 1;
+
+        if shift > 63 {
+            return Err(BackendError::Corrupt(
+                "Corrupt index: number too large".to_string(),
+            ));
+        }
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte < 0x80 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+/// Decode a signed number encoded by [`encode_int`].
+///
+/// `decode_int`
+fn decode_int(data: &[u8], pos: &mut usize) -> Result<i64, BackendError> {
+    let value = decode_uint(data, pos)?;
+    Ok(if value % 2 == 1 {
+        -1 - (value / 2) as i64
+    } else {
+        (value / 2) as i64
+    })
+}
+
+/// Encode the offsets (+1, 0 meaning "unused") in @a values as the
+/// difference to the previous one and append them to @a out.
+///
+/// `encode_l2p_page`
+fn encode_l2p_page(out: &mut Vec<u8>, values: &[u64]) {
+    let mut last_value = 0u64;
+    for &value in values {
+        encode_int(out, value.wrapping_sub(last_value) as i64);
+        last_value = value;
+    }
+}
+
+/// Return the l2p index for the items described by @a entries, covering
+/// all revisions from @a first_rev to the youngest one referenced by any
+/// entry. Pages hold up to @a page_size entries.
+///
+/// `svn_fs_fs__l2p_index_from_p2l_entries` / `svn_fs_fs__l2p_index_append`
+pub(crate) fn l2p_index_from_p2l_entries(
+    entries: &[P2lEntry],
+    first_rev: RevisionNumber,
+    page_size: u64,
+) -> Vec<u8> {
+    let last_rev = entries
+        .iter()
+        .map(|e| e.revision)
+        .max()
+        .unwrap_or(first_rev)
+        .max(first_rev);
+
+    // Per revision, the (offset + 1) of every item, indexed by item number.
+    let mut revisions: Vec<Vec<u64>> = vec![Vec::new(); (last_rev - first_rev + 1) as usize];
+    for entry in entries {
+        if entry.item_type == ItemType::Unused {
+            continue;
+        }
+        let offsets = &mut revisions[(entry.revision - first_rev) as usize];
+        let idx = entry.number as usize;
+        if idx >= offsets.len() {
+            offsets.resize(idx + 1, 0);
+        }
+        offsets[idx] = entry.offset + 1;
+    }
+
+    let mut page_counts = Vec::new();
+    let mut page_sizes = Vec::new();
+    let mut entry_counts = Vec::new();
+    let mut pages = Vec::new();
+    for offsets in &revisions {
+        let mut count = 0;
+        for page in offsets.chunks(page_size as usize) {
+            let start = pages.len();
+            encode_l2p_page(&mut pages, page);
+            page_sizes.push((pages.len() - start) as u64);
+            entry_counts.push(page.len() as u64);
+            count += 1;
+        }
+        page_counts.push(count);
+    }
+
+    let mut out = L2P_STREAM_PREFIX.as_bytes().to_vec();
+    encode_uint(&mut out, first_rev as u64);
+    encode_uint(&mut out, page_size);
+    encode_uint(&mut out, page_counts.len() as u64);
+    encode_uint(&mut out, page_sizes.len() as u64);
+    for count in page_counts {
+        encode_uint(&mut out, count);
+    }
+    for (size, count) in page_sizes.into_iter().zip(entry_counts) {
+        encode_uint(&mut out, size);
+        encode_uint(&mut out, count);
+    }
+    out.extend_from_slice(&pages);
+
+    out
+}
+
+/// The header of an l2p index: the page tables of all revisions it covers.
+///
+/// `l2p_header_t`
+#[derive(Debug)]
+struct L2pHeader {
+    /// first revision covered by this index
+    first_revision: RevisionNumber,
+
+    /// number of entries per page
+    page_size: u64,
+
+    /// index of the first page of every revision, plus the total number
+    /// of pages at the end
+    page_table_index: Vec<usize>,
+
+    /// (offset within the page data, entry count) of every page
+    page_table: Vec<(usize, u64)>,
+
+    /// position of the page data within the index
+    data_start: usize,
+}
+
+impl L2pHeader {
+    /// Parse the header at the start of the l2p index @a data.
+    ///
+    /// `get_l2p_header_body`
+    fn read(data: &[u8]) -> Result<Self, BackendError> {
+        if !data.starts_with(L2P_STREAM_PREFIX.as_bytes()) {
+            return Err(BackendError::Corrupt(
+                "L2P index stream prefix mismatch".to_string(),
+            ));
+        }
+        let mut pos = L2P_STREAM_PREFIX.len();
+
+        // read the table sizes
+        let first_revision = decode_uint(data, &mut pos)? as RevisionNumber;
+        let page_size = decode_uint(data, &mut pos)?;
+        let revision_count = decode_uint(data, &mut pos)?;
+        let page_count = decode_uint(data, &mut pos)?;
+
+        if page_size == 0 {
+            return Err(BackendError::Corrupt(
+                "L2P index page size is not positive".to_string(),
+            ));
+        }
+        // Every number takes at least one byte, which limits the table
+        // sizes a valid index can have.
+        if revision_count > data.len() as u64 || page_count > data.len() as u64 {
+            return Err(BackendError::Corrupt(
+                "L2P index page table exceeds the index size".to_string(),
+            ));
+        }
+
+        // read per-revision page table sizes (i.e. number of pages per rev)
+        let mut page_table_index = vec![0];
+        let mut page = 0;
+        for _ in 0..revision_count {
+            page += decode_uint(data, &mut pos)? as usize;
+            page_table_index.push(page);
+        }
+        if page as u64 != page_count {
+            return Err(BackendError::Corrupt(
+                "L2P index page table inconsistent with page count".to_string(),
+            ));
+        }
+
+        // read actual page tables
+        let mut page_table = Vec::with_capacity(page_count as usize);
+        let mut offset = 0;
+        for _ in 0..page_count {
+            let size = decode_uint(data, &mut pos)? as usize;
+            let entry_count = decode_uint(data, &mut pos)?;
+            if entry_count > page_size {
+                return Err(BackendError::Corrupt(
+                    "L2P index page too large".to_string(),
+                ));
+            }
+            page_table.push((offset, entry_count));
+            offset += size;
+        }
+
+        Ok(Self {
+            first_revision,
+            page_size,
+            page_table_index,
+            page_table,
+            data_start: pos,
+        })
+    }
+
+    /// The number of revisions covered by this index.
+    fn revision_count(&self) -> usize {
+        self.page_table_index.len() - 1
+    }
+
+    /// Return the pages of @a revision, or an error if the index does not
+    /// cover it.
+    fn pages(&self, revision: RevisionNumber) -> Result<&[(usize, u64)], BackendError> {
+        let rel_revision = usize::try_from(revision - self.first_revision)
+            .ok()
+            .filter(|&r| r < self.revision_count())
+            .ok_or_else(|| {
+                BackendError::Corrupt(format!("Revision {revision} not covered by item index"))
+            })?;
+
+        Ok(&self.page_table
+            [self.page_table_index[rel_revision]..self.page_table_index[rel_revision + 1]])
+    }
+}
+
+/// Look up the file offset of item @a item_index of @a revision in the l2p
+/// index @a data.
+///
+/// `l2p_index_lookup`
+pub(crate) fn l2p_index_lookup(
+    data: &[u8],
+    revision: RevisionNumber,
+    item_index: u64,
+) -> Result<u64, BackendError> {
+    let header = L2pHeader::read(data)?;
+    let too_large = || {
+        BackendError::Corrupt(format!(
+            "Item index {item_index} too large in revision {revision}"
+        ))
+    };
+
+    // Find the page in question.
+    let pages = header.pages(revision)?;
+    let page_no = usize::try_from(item_index / header.page_size).map_err(|_| too_large())?;
+    let &(page_offset, entry_count) = pages.get(page_no).ok_or_else(too_large)?;
+
+    let page_item = item_index % header.page_size;
+    if page_item >= entry_count {
+        return Err(too_large());
+    }
+
+    // read the page, i.e. the offsets up to our item
+    let mut pos = header.data_start + page_offset;
+    let mut value = 0i64;
+    for _ in 0..=page_item {
+        value += decode_int(data, &mut pos)?;
+    }
+
+    if value <= 0 {
+        return Err(BackendError::Corrupt(format!(
+            "Item index {item_index} unused in revision {revision}"
+        )));
+    }
+
+    Ok(value as u64 - 1)
+}
+
+/// Return the file offsets of all items of @a revision in the l2p index
+/// @a data, indexed by item number.  Unused item numbers map to `None`.
+///
+/// `svn_fs_fs__l2p_get_max_ids` / `l2p_index_lookup`
+pub(crate) fn l2p_revision_offsets(
+    data: &[u8],
+    revision: RevisionNumber,
+) -> Result<Vec<Option<u64>>, BackendError> {
+    let header = L2pHeader::read(data)?;
+
+    let mut offsets = Vec::new();
+    for &(page_offset, entry_count) in header.pages(revision)? {
+        let mut pos = header.data_start + page_offset;
+        let mut value = 0i64;
+        for _ in 0..entry_count {
+            value += decode_int(data, &mut pos)?;
+            offsets.push((value > 0).then(|| value as u64 - 1));
+        }
+    }
+
+    Ok(offsets)
+}
+
+/// Return the p2l index for the items described by @a entries, which must
+/// be sorted by offset and cover the rev / pack file without gaps. The
+/// file starts with @a revision and every page covers @a page_size bytes.
+///
+/// `svn_fs_fs__p2l_index_from_p2l_entries` / `svn_fs_fs__p2l_index_append`
+pub(crate) fn p2l_index_from_p2l_entries(
+    entries: &[P2lEntry],
+    revision: RevisionNumber,
+    page_size: u64,
+) -> Vec<u8> {
+    let mut table_sizes = Vec::new();
+    let mut buffer = Vec::new();
+    let mut last_buffer_size = 0;
+
+    let mut last_entry_end = 0u64;
+    let mut last_page_end = 0u64;
+    let mut last_revision = revision;
+    let mut last_compound = 0i64;
+    let mut new_page = true;
+
+    // "unused" (and usually non-existent) section to cover the offsets
+    // at the end the of the last page.
+    let file_size = entries.last().map_or(0, |e| e.offset + e.size);
+    let padding = P2lEntry {
+        offset: file_size,
+        size: file_size.div_ceil(page_size) * page_size - file_size,
+        item_type: ItemType::Unused,
+        fnv1_checksum: 0,
+        revision: entries.last().map_or(revision, |e| e.revision),
+        number: SVN_FS_FS__ITEM_INDEX_UNUSED,
+    };
+
+    for entry in entries.iter().chain(std::iter::once(&padding)) {
+        // end pages if entry is extending beyond their boundaries
+        let entry_end = entry.offset + entry.size;
+        while entry_end - last_page_end > page_size {
+            table_sizes.push((buffer.len() - last_buffer_size) as u64);
+            last_buffer_size = buffer.len();
+            last_page_end += page_size;
+            new_page = true;
+        }
+
+        // this entry starts a new page -> store its offset
+        if new_page {
+            encode_uint(&mut buffer, entry.offset);
+            last_revision = revision;
+            last_compound = 0;
+        }
+
+        // write simple item entry
+        encode_uint(&mut buffer, entry.size);
+
+        let compound = (entry.number * 8 + entry.item_type as u64) as i64;
+        encode_int(&mut buffer, compound - last_compound);
+        last_compound = compound;
+
+        encode_int(&mut buffer, entry.revision - last_revision);
+        last_revision = entry.revision;
+
+        encode_uint(&mut buffer, entry.fnv1_checksum as u64);
+
+        last_entry_end = entry_end;
+        new_page = false;
+    }
+    debug_assert!(last_entry_end >= file_size);
+
+    // store length of last table
+    table_sizes.push((buffer.len() - last_buffer_size) as u64);
+
+    let mut out = P2L_STREAM_PREFIX.as_bytes().to_vec();
+    encode_uint(&mut out, revision as u64);
+    encode_uint(&mut out, file_size);
+    encode_uint(&mut out, page_size);
+    encode_uint(&mut out, table_sizes.len() as u64);
+    for size in table_sizes {
+        encode_uint(&mut out, size);
+    }
+    out.extend_from_slice(&buffer);
+
+    out
+}
+
+/// The header of a p2l index: the page table.
+///
+/// `p2l_header_t`
+#[derive(Debug)]
+struct P2lHeader {
+    /// first revision covered by the index (and rev / pack file)
+    first_revision: RevisionNumber,
+
+    /// number of bytes in the rev / pack file covered by each p2l page
+    page_size: u64,
+
+    /// size of the rev / pack file in bytes, i.e. of the data covered
+    file_size: u64,
+
+    /// (start, end) positions of every page within the index
+    pages: Vec<(usize, usize)>,
+}
+
+impl P2lHeader {
+    /// Parse the header at the start of the p2l index @a data.
+    ///
+    /// `get_p2l_header`
+    fn read(data: &[u8]) -> Result<Self, BackendError> {
+        if !data.starts_with(P2L_STREAM_PREFIX.as_bytes()) {
+            return Err(BackendError::Corrupt(
+                "P2L index stream prefix mismatch".to_string(),
+            ));
+        }
+        let mut pos = P2L_STREAM_PREFIX.len();
+
+        // read the table sizes
+        let first_revision = decode_uint(data, &mut pos)? as RevisionNumber;
+        let file_size = decode_uint(data, &mut pos)?;
+        let page_size = decode_uint(data, &mut pos)?;
+        let page_count = decode_uint(data, &mut pos)?;
+
+        if page_size == 0 {
+            return Err(BackendError::Corrupt(
+                "P2L index page size is not positive".to_string(),
+            ));
+        }
+        if page_count != file_size.div_ceil(page_size).max(1) {
+            return Err(BackendError::Corrupt(
+                "P2L page count does not match rev / pack file size".to_string(),
+            ));
+        }
+
+        // read the page sizes and calculate page start positions
+        let sizes = (0..page_count)
+            .map(|_| decode_uint(data, &mut pos))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut pages = Vec::with_capacity(sizes.len());
+        for size in sizes {
+            let end = pos
+                .checked_add(size as usize)
+                .filter(|&end| end <= data.len())
+                .ok_or_else(|| {
+                    BackendError::Corrupt("P2L index page exceeds the index size".to_string())
+                })?;
+            pages.push((pos, end));
+            pos = end;
+        }
+
+        Ok(Self {
+            first_revision,
+            page_size,
+            file_size,
+            pages,
+        })
+    }
+
+    /// Decode page @a page_no of the index @a data.  Items that started
+    /// in earlier pages are stored in the page in which they end; pages
+    /// fully covered by such an item are empty.
+    ///
+    /// `get_p2l_page`
+    fn page(&self, data: &[u8], page_no: usize) -> Result<Vec<P2lEntry>, BackendError> {
+        let corrupt = || BackendError::Corrupt("Corrupt P2L index page".to_string());
+        let (start, end) = self.pages[page_no];
+        let page = &data[..end];
+
+        let mut entries = Vec::new();
+        let mut pos = start;
+        if pos < end {
+            let mut offset = decode_uint(page, &mut pos)?;
+            let mut last_revision = self.first_revision;
+            let mut last_compound = 0i64;
+            while pos < end {
+                let size = decode_uint(page, &mut pos)?;
+                let compound = last_compound + decode_int(page, &mut pos)?;
+                let revision = last_revision + decode_int(page, &mut pos)?;
+                let fnv1_checksum = decode_uint(page, &mut pos)?;
+                if compound < 0 || revision < 0 {
+                    return Err(corrupt());
+                }
+
+                entries.push(P2lEntry {
+                    offset,
+                    size,
+                    item_type: ItemType::from_u64(compound as u64 % 8).ok_or_else(corrupt)?,
+                    fnv1_checksum: u32::try_from(fnv1_checksum).map_err(|_| corrupt())?,
+                    revision,
+                    number: compound as u64 / 8,
+                });
+
+                offset += size;
+                last_compound = compound;
+                last_revision = revision;
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Return the entries of the p2l index @a data that overlap the block of
+/// @a block_size bytes starting at @a block_start, ordered by offset.
+///
+/// `svn_fs_fs__p2l_index_lookup`
+pub(crate) fn p2l_index_lookup(
+    data: &[u8],
+    block_start: u64,
+    block_size: u64,
+) -> Result<Vec<P2lEntry>, BackendError> {
+    let header = P2lHeader::read(data)?;
+    let block_end = block_start.saturating_add(block_size);
+
+    // Items are stored in the page in which they end.  Thus, walk the pages
+    // from the one containing BLOCK_START until an item reaches beyond the
+    // block.
+    let mut entries = Vec::new();
+    let first_page = (block_start / header.page_size) as usize;
+    for page_no in first_page..header.pages.len() {
+        for entry in header.page(data, page_no)? {
+            if entry.offset + entry.size <= block_start {
+                continue;
+            }
+            if entry.offset >= block_end {
+                return Ok(entries);
+            }
+            let reaches_end = entry.offset + entry.size >= block_end;
+            entries.push(entry);
+            if reaches_end {
+                return Ok(entries);
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Return the entry of the p2l index @a data describing the item that
+/// starts at @a offset, if there is one.
+///
+/// `svn_fs_fs__p2l_entry_lookup`
+pub(crate) fn p2l_entry_lookup(data: &[u8], offset: u64) -> Result<Option<P2lEntry>, BackendError> {
+    Ok(p2l_index_lookup(data, offset, 1)?
+        .into_iter()
+        .find(|entry| entry.offset == offset))
+}
+
+/// Return the size of the data covered by the p2l index @a data, i.e. the
+/// offset of the first byte behind the last item.
+///
+/// `svn_fs_fs__p2l_get_max_offset`
+pub(crate) fn p2l_get_max_offset(data: &[u8]) -> Result<u64, BackendError> {
+    Ok(P2lHeader::read(data)?.file_size)
+}
+
+/// Return the footer that locates and checksums the two indexes.
+///
+/// `svn_fs_fs__unparse_footer`
+pub(crate) fn unparse_footer(
+    l2p_offset: u64,
+    l2p_checksum: &Checksum,
+    p2l_offset: u64,
+    p2l_checksum: &Checksum,
+) -> String {
+    format!(
+        "{} {} {} {}",
+        l2p_offset,
+        l2p_checksum.to_hex(),
+        p2l_offset,
+        p2l_checksum.to_hex()
+    )
+}
+
+/// Append the l2p and p2l indexes for @a entries plus the footer to
+/// @a out, which already contains @a data_size bytes of revision data
+/// starting with @a first_rev.
+///
+/// `svn_fs_fs__add_index_data`
+pub(crate) fn add_index_data<W: Write>(
+    out: &mut W,
+    data_size: u64,
+    entries: &[P2lEntry],
+    first_rev: RevisionNumber,
+    l2p_page_size: u64,
+    p2l_page_size: u64,
+) -> Result<(), BackendError> {
+    let l2p = l2p_index_from_p2l_entries(entries, first_rev, l2p_page_size);
+    let p2l = p2l_index_from_p2l_entries(entries, first_rev, p2l_page_size);
+
+    let l2p_offset = data_size;
+    let p2l_offset = l2p_offset + l2p.len() as u64;
+    let footer = unparse_footer(
+        l2p_offset,
+        &Checksum::compute(ChecksumKind::Md5, &l2p),
+        p2l_offset,
+        &Checksum::compute(ChecksumKind::Md5, &p2l),
+    );
+    let footer_length = u8::try_from(footer.len()).expect("footer fits into 255 bytes");
+
+    out.write_all(&l2p)?;
+    out.write_all(&p2l)?;
+    out.write_all(footer.as_bytes())?;
+    out.write_all(&[footer_length])?;
+
+    Ok(())
+}
+
+/// The number of bytes per entry in a proto p2l index.
+const P2L_PROTO_ENTRY_SIZE: usize = 5 * 8;
+
+/// Append @a entry to the proto p2l index @a out of a transaction.  The
+/// revision of the entry is not stored; all items of a transaction end up
+/// in the same revision.
+///
+/// `svn_fs_fs__p2l_proto_index_add_entry`
+pub(crate) fn p2l_proto_index_add_entry<W: Write>(
+    out: &mut W,
+    entry: &P2lEntry,
+) -> Result<(), BackendError> {
+    for value in [
+        entry.offset,
+        entry.size,
+        entry.item_type as u64,
+        entry.fnv1_checksum as u64,
+        entry.number,
+    ] {
+        out.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Parse the proto p2l index @a data, assigning all entries to
+/// @a revision.
+///
+/// `svn_fs_fs__p2l_proto_index_next_offset` / `p2l_proto_index_read`
+pub(crate) fn p2l_proto_index_read(
+    data: &[u8],
+    revision: RevisionNumber,
+) -> Result<Vec<P2lEntry>, BackendError> {
+    let corrupt = || BackendError::Corrupt("Corrupt proto p2l index".to_string());
+    if !data.len().is_multiple_of(P2L_PROTO_ENTRY_SIZE) {
+        return Err(corrupt());
+    }
+
+    data.chunks(P2L_PROTO_ENTRY_SIZE)
+        .map(|chunk| {
+            let value = |i: usize| {
+                u64::from_le_bytes(chunk[i * 8..(i + 1) * 8].try_into().expect("8 bytes"))
+            };
+            Ok(P2lEntry {
+                offset: value(0),
+                size: value(1),
+                item_type: ItemType::from_u64(value(2)).ok_or_else(corrupt)?,
+                fnv1_checksum: u32::try_from(value(3)).map_err(|_| corrupt())?,
+                revision,
+                number: value(4),
+            })
+        })
+        .collect()
+}
+
+impl FsFsBackend {
+    /// Use the log-to-phys mapping files in FS to find the packed / non-packed /
+    /// proto-rev file offset of item @a item_index in @a revision, opened as
+    /// @a rev_file.  For physical addressing, the item index is the offset
+    /// relative to the start of the revision.
+    ///
+    /// `svn_fs_fs__item_offset`
+    pub(crate) fn item_offset(
+        &self,
+        rev_file: &mut RevisionFile,
+        revision: RevisionNumber,
+        item_index: u64,
+    ) -> Result<u64, BackendError> {
+        if self._data().use_log_addressing {
+            // the item_index is the logical number
+            l2p_index_lookup(&rev_file.l2p_index()?, revision, item_index)
+        } else if rev_file.is_packed {
+            // pack file with physical addressing
+            Ok(self.get_packed_offset(revision)? + item_index)
+        } else {
+            // for non-packed revs with physical addressing,
+            // item_index *is* the offset
+            Ok(item_index)
+        }
+    }
+
+    /// Return the p2l index entry of the item starting at @a offset in
+    /// @a rev_file, if any.
+    ///
+    /// `svn_fs_fs__p2l_entry_lookup`
+    pub(crate) fn p2l_entry_lookup(
+        &self,
+        rev_file: &mut RevisionFile,
+        offset: u64,
+    ) -> Result<Option<P2lEntry>, BackendError> {
+        p2l_entry_lookup(&rev_file.p2l_index()?, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_int() {
+        let mut out = Vec::new();
+        encode_uint(&mut out, 0x80);
+        assert_eq!(out, [0x80, 0x01]);
+
+        let mut out = Vec::new();
+        for value in [0, -1, 1, -2] {
+            encode_int(&mut out, value);
+        }
+        assert_eq!(out, [0, 1, 2, 3]);
+
+        let mut pos = 0;
+        for value in [0, -1, 1, -2] {
+            assert_eq!(decode_int(&out, &mut pos).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_l2p_index_lookup() {
+        let data = b"0123456789";
+        let entries: Vec<P2lEntry> = [(0, 4, 1, 3), (4, 4, 1, 1), (8, 2, 2, 5)]
+            .into_iter()
+            .map(|(offset, size, revision, number)| {
+                P2lEntry::new(data, offset, size, ItemType::NodeRev, revision, number)
+            })
+            .collect();
+
+        let index = l2p_index_from_p2l_entries(&entries, 1, 2);
+        assert_eq!(l2p_index_lookup(&index, 1, 3).unwrap(), 0);
+        assert_eq!(l2p_index_lookup(&index, 1, 1).unwrap(), 4);
+        assert_eq!(l2p_index_lookup(&index, 2, 5).unwrap(), 8);
+        assert!(l2p_index_lookup(&index, 1, 2).is_err());
+        assert!(l2p_index_lookup(&index, 2, 6).is_err());
+        assert!(l2p_index_lookup(&index, 3, 0).is_err());
+    }
+
+    #[test]
+    fn test_p2l_index_lookup() {
+        let data = vec![b'x'; 300];
+        let entries: Vec<P2lEntry> = [
+            (0, 10, ItemType::Changes, 1, 1),
+            (10, 200, ItemType::FileRep, 1, 3),
+            (210, 90, ItemType::NodeRev, 2, 2),
+        ]
+        .into_iter()
+        .map(|(offset, size, item_type, revision, number)| {
+            P2lEntry::new(&data, offset, size, item_type, revision, number)
+        })
+        .collect();
+
+        let index = p2l_index_from_p2l_entries(&entries, 1, 64);
+        assert_eq!(p2l_get_max_offset(&index).unwrap(), 300);
+        let read = p2l_index_lookup(&index, 0, 1000).unwrap();
+        assert_eq!(read[..3], entries[..]);
+
+        // The rest of the last page is covered by an unused item.
+        assert_eq!(read.len(), 4);
+        assert_eq!(read[3].item_type, ItemType::Unused);
+        assert_eq!((read[3].offset, read[3].size), (300, 20));
+
+        // The item spanning pages 0 - 3 is found from each of them.
+        assert_eq!(
+            p2l_index_lookup(&index, 100, 64).unwrap(),
+            [entries[1].clone()]
+        );
+        assert_eq!(
+            p2l_index_lookup(&index, 5, 10).unwrap(),
+            entries[..2].to_vec()
+        );
+        assert_eq!(
+            p2l_entry_lookup(&index, 210).unwrap(),
+            Some(entries[2].clone())
+        );
+        assert_eq!(p2l_entry_lookup(&index, 211).unwrap(), None);
+    }
+}
@@ -0,0 +1,446 @@
+//! `subversion/libsvn_fs_fs/lock.c`
+//!
+//! Locks are stored in a tree of "digest files" under `locks/`.  Every
+//! path that is locked or has locked descendants gets a file named after
+//! the MD5 digest of the path, in a subdirectory named after the first
+//! three characters of that digest.  The file holds the lock, if any, and
+//! the digests of the children that lead to further locks, so that all
+//! locks below a path can be found without scanning the whole tree.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_subr::hash::HASH_TERMINATOR;
+use svn_subr::io::FileLock;
+use svn_types::Depth;
+use svn_types::NodeKind;
+use svn_types::SvnLock;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::tree::FsFsRoot;
+use crate::FsAccess;
+use crate::PathChangeKind;
+use crate::RootTrait;
+use crate::backend::PATH_LOCKS_DIR;
+use crate::id::IdPart;
+use crate::lock::is_expired;
+use crate::lock::verify_lock;
+use crate::util::canonicalize_abspath;
+use crate::util::fspath_dirname;
+use crate::util::fspath_is_ancestor;
+
+// Names of hash keys used to store a lock for writing to disk.
+const PATH_KEY: &str = "path";
+const TOKEN_KEY: &str = "token";
+const OWNER_KEY: &str = "owner";
+const CREATION_DATE_KEY: &str = "creation_date";
+const EXPIRATION_DATE_KEY: &str = "expiration_date";
+const COMMENT_KEY: &str = "comment";
+const IS_DAV_COMMENT_KEY: &str = "is_dav_comment";
+const CHILDREN_KEY: &str = "children";
+
+/// Number of characters from the head of a digest file name used to
+/// calculate a subdirectory in which to drop that file.
+const DIGEST_SUBDIR_LEN: usize = 3;
+
+fn time_to_cstring(when: i64) -> String {
+    svn_subr::time::time_to_cstring(UNIX_EPOCH + Duration::from_micros(when.max(0) as u64))
+}
+
+fn time_from_cstring(data: &str) -> Result<i64, BackendError> {
+    let when = svn_subr::time::time_from_cstring(data)?;
+    Ok(when
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_micros() as i64))
+}
+
+/// Return the digest of the canonical @a path, which names its digest
+/// file.
+fn path_digest(path: &str) -> String {
+    Checksum::compute(ChecksumKind::Md5, path.as_bytes()).to_hex()
+}
+
+/// The contents of a digest file.
+#[derive(Default)]
+struct DigestFile {
+    /// the lock on the path, if any
+    lock: Option<SvnLock>,
+    /// the digests of the children leading to further locks
+    children: Vec<String>,
+}
+
+impl FsFsBackend {
+    /// Return the path of the digest file named @a digest.
+    ///
+    /// `digest_path_from_digest`
+    fn path_lock_digest(&self, digest: &str) -> PathBuf {
+        self.path
+            .join(PATH_LOCKS_DIR)
+            .join(&digest[..DIGEST_SUBDIR_LEN])
+            .join(digest)
+    }
+
+    /// Read the digest file named @a digest.  A missing file means
+    /// neither a lock nor children.
+    ///
+    /// `read_digest_file`
+    fn read_digest_file(&self, digest: &str) -> Result<DigestFile, BackendError> {
+        let path = self.path_lock_digest(digest);
+        let content = match fs_err::read(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(DigestFile::default());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let (mut hash, _) = svn_subr::hash::read_hash(&content, HASH_TERMINATOR)?;
+        let corrupt = |key: &str| BackendError::CorruptFile {
+            path: path.to_string_lossy().to_string(),
+            message: format!("missing '{key}'"),
+        };
+
+        // If we have a lock path in our hash, we'll assume we have a lock
+        // here.
+        let lock = match hash.remove(PATH_KEY) {
+            Some(lock_path) => {
+                let mut take = |key: &str| hash.remove(key).ok_or_else(|| corrupt(key));
+                let token = take(TOKEN_KEY)?;
+                let owner = take(OWNER_KEY)?;
+                let is_dav_comment = take(IS_DAV_COMMENT_KEY)? == "1";
+                let creation_date = time_from_cstring(&take(CREATION_DATE_KEY)?)?;
+                let expiration_date = match hash.remove(EXPIRATION_DATE_KEY) {
+                    Some(date) => time_from_cstring(&date)?,
+                    None => 0,
+                };
+                Some(SvnLock {
+                    path: lock_path,
+                    token,
+                    owner,
+                    comment: hash.remove(COMMENT_KEY),
+                    is_dav_comment,
+                    creation_date,
+                    expiration_date,
+                })
+            }
+            None => None,
+        };
+
+        // See if we have any children for this path.
+        let children = hash
+            .remove(CHILDREN_KEY)
+            .map(|children| children.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Ok(DigestFile { lock, children })
+    }
+
+    /// Write @a digest_file as the digest file named @a digest, or remove
+    /// that file if it has neither a lock nor children.
+    ///
+    /// `write_digest_file`
+    fn write_digest_file(
+        &self,
+        digest: &str,
+        digest_file: &DigestFile,
+    ) -> Result<(), BackendError> {
+        let path = self.path_lock_digest(digest);
+        if digest_file.lock.is_none() && digest_file.children.is_empty() {
+            return match fs_err::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+
+        let mut hash = HashMap::new();
+        if let Some(lock) = &digest_file.lock {
+            hash.insert(PATH_KEY.to_string(), lock.path.clone());
+            hash.insert(TOKEN_KEY.to_string(), lock.token.clone());
+            hash.insert(OWNER_KEY.to_string(), lock.owner.clone());
+            if let Some(comment) = &lock.comment {
+                hash.insert(COMMENT_KEY.to_string(), comment.clone());
+            }
+            let is_dav_comment = if lock.is_dav_comment { "1" } else { "0" };
+            hash.insert(IS_DAV_COMMENT_KEY.to_string(), is_dav_comment.to_string());
+            hash.insert(
+                CREATION_DATE_KEY.to_string(),
+                time_to_cstring(lock.creation_date),
+            );
+            if lock.expiration_date != 0 {
+                hash.insert(
+                    EXPIRATION_DATE_KEY.to_string(),
+                    time_to_cstring(lock.expiration_date),
+                );
+            }
+        }
+        if !digest_file.children.is_empty() {
+            hash.insert(CHILDREN_KEY.to_string(), digest_file.children.join("\n"));
+        }
+
+        fs_err::create_dir_all(path.parent().expect("digest files live in a subdirectory"))?;
+        svn_subr::io::write_atomic(
+            &path,
+            svn_subr::hash::write_hash(&hash, HASH_TERMINATOR).as_bytes(),
+            Some(&self.path_current()),
+            self._data().flush_to_disk,
+        )?;
+
+        Ok(())
+    }
+
+    /// Store @a lock on its path and link the digest files of all parent
+    /// directories up to the root to it.  The caller must hold the write
+    /// lock.
+    ///
+    /// `set_lock`
+    fn set_lock(&self, lock: &SvnLock) -> Result<(), BackendError> {
+        let mut this_path = lock.path.as_str();
+        let mut child_digest: Option<String> = None;
+        loop {
+            let digest = path_digest(this_path);
+            let mut digest_file = self.read_digest_file(&digest)?;
+            match &child_digest {
+                None => digest_file.lock = Some(lock.clone()),
+                Some(child) => {
+                    // Once a parent knows the child, so do its parents.
+                    if digest_file.children.contains(child) {
+                        break;
+                    }
+                    digest_file.children.push(child.clone());
+                }
+            }
+            self.write_digest_file(&digest, &digest_file)?;
+
+            if this_path == "/" {
+                break;
+            }
+            child_digest = Some(digest);
+            this_path = fspath_dirname(this_path);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the lock on @a path and unlink the digest files that lead
+    /// to no further locks.  The caller must hold the write lock.
+    ///
+    /// `delete_lock`
+    fn delete_lock(&self, path: &str) -> Result<(), BackendError> {
+        let mut this_path = path;
+        let mut child_to_kill: Option<String> = None;
+        loop {
+            let digest = path_digest(this_path);
+            let mut digest_file = self.read_digest_file(&digest)?;
+            match &child_to_kill {
+                None => digest_file.lock = None,
+                Some(child) => digest_file.children.retain(|c| c != child),
+            }
+            self.write_digest_file(&digest, &digest_file)?;
+
+            // Parents only lose this entry if it is gone entirely.
+            if digest_file.lock.is_some() || !digest_file.children.is_empty() || this_path == "/" {
+                break;
+            }
+            child_to_kill = Some(digest);
+            this_path = fspath_dirname(this_path);
+        }
+
+        Ok(())
+    }
+
+    /// Return the unexpired lock on @a path, if any.
+    ///
+    /// `get_lock_helper`
+    fn get_lock_helper(&self, path: &str) -> Result<Option<SvnLock>, BackendError> {
+        let lock = self.read_digest_file(&path_digest(path))?.lock;
+
+        // Don't return an expired lock.
+        Ok(lock.filter(|lock| !is_expired(lock)))
+    }
+
+    /// Call @a callback for every unexpired lock on @a digest's path and
+    /// below.
+    ///
+    /// `walk_locks`
+    fn walk_locks(
+        &self,
+        digest: &str,
+        callback: &mut dyn FnMut(SvnLock) -> Result<(), BackendError>,
+    ) -> Result<(), BackendError> {
+        let digest_file = self.read_digest_file(digest)?;
+        if let Some(lock) = digest_file.lock
+            && !is_expired(&lock)
+        {
+            callback(lock)?;
+        }
+        for child in &digest_file.children {
+            self.walk_locks(child, callback)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lock `lock.path`, which must be a file in the youngest revision,
+    /// with @a lock.  If @a current_rev is valid, fail if the file has
+    /// been changed after that revision.  An existing lock on the path is
+    /// replaced only if @a steal_lock is set.
+    ///
+    /// `svn_fs_fs__lock` / `lock_body`
+    pub(crate) fn lock(
+        &self,
+        lock: &SvnLock,
+        current_rev: svn_types::RevisionNumber,
+        steal_lock: bool,
+    ) -> Result<(), BackendError> {
+        let _write_lock = FileLock::lock(&self.path_lock(), true)?;
+        let path = lock.path.as_str();
+
+        // Until we implement directory locks someday, we only allow locks
+        // on files or non-existent paths.
+        let youngest = self.read_current()?;
+        let root = FsFsRoot::revision_root(self, youngest)?;
+        match root.check_path(path)? {
+            NodeKind::None => return Err(BackendError::PathNotInHead(path.to_string())),
+            NodeKind::Directory => return Err(BackendError::NotFile(path.to_string())),
+            _ => {}
+        }
+
+        // Is the caller attempting to lock an out-of-date working file?
+        if current_rev >= 0 && current_rev < root.node_created_rev(path)? {
+            return Err(BackendError::LockOutOfDate(path.to_string()));
+        }
+
+        // If the caller didn't ask to steal the lock, we need to be sure
+        // that no one else holds it.
+        if !steal_lock && let Some(existing) = self.get_lock_helper(path)? {
+            return Err(BackendError::PathAlreadyLocked {
+                path: path.to_string(),
+                owner: existing.owner,
+                fs: self.path.display().to_string(),
+            });
+        }
+
+        self.set_lock(lock)
+    }
+
+    /// Remove the lock on @a path.  Unless @a break_lock is set, the lock
+    /// must have the token @a token and be owned by @a username.
+    ///
+    /// `svn_fs_fs__unlock` / `unlock_body`
+    pub(crate) fn unlock(
+        &self,
+        path: &str,
+        token: Option<&str>,
+        username: Option<&str>,
+        break_lock: bool,
+    ) -> Result<(), BackendError> {
+        let path = canonicalize_abspath(path);
+        let _write_lock = FileLock::lock(&self.path_lock(), true)?;
+
+        let lock = self
+            .get_lock_helper(&path)?
+            .ok_or_else(|| BackendError::NoSuchLock(path.clone()))?;
+        if !break_lock {
+            // Sanity check:  the incoming token should match lock->token.
+            if token != Some(lock.token.as_str()) {
+                return Err(BackendError::NoSuchLock(path));
+            }
+
+            // There better be a username attached to the fs, and it better
+            // match the lock owner.
+            let user = username.unwrap_or_default();
+            if user != lock.owner {
+                return Err(BackendError::LockOwnerMismatch {
+                    user: user.to_string(),
+                    path,
+                    owner: lock.owner,
+                });
+            }
+        }
+
+        self.delete_lock(&path)
+    }
+
+    /// Return the unexpired lock on @a path, if any.
+    ///
+    /// `svn_fs_fs__get_lock`
+    pub(crate) fn get_lock(&self, path: &str) -> Result<Option<SvnLock>, BackendError> {
+        self.get_lock_helper(&canonicalize_abspath(path))
+    }
+
+    /// Return the unexpired locks on @a path and below, down to
+    /// @a depth, ordered by path.
+    ///
+    /// `svn_fs_fs__get_locks`
+    pub(crate) fn get_locks(&self, path: &str, depth: Depth) -> Result<Vec<SvnLock>, BackendError> {
+        let path = canonicalize_abspath(path);
+        let mut locks = Vec::new();
+        self.walk_locks(&path_digest(&path), &mut |lock| {
+            // Only locks on files exist, so "files" and "immediates" both
+            // mean the path's own lock and those of its direct children.
+            let wanted = match depth {
+                Depth::Empty => lock.path == path,
+                Depth::Files | Depth::Immediates => {
+                    lock.path == path || (lock.path != "/" && fspath_dirname(&lock.path) == path)
+                }
+                _ => true,
+            };
+            if wanted {
+                locks.push(lock);
+            }
+            Ok(())
+        })?;
+        locks.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(locks)
+    }
+
+    /// Fail unless @a access may change every path changed in
+    /// transaction @a txn_id.  Paths that get added, deleted or replaced
+    /// require the right to change everything below them as well.  The
+    /// caller must hold the write lock.
+    ///
+    /// `verify_locks`
+    pub(crate) fn verify_locks(
+        &self,
+        txn_id: &IdPart,
+        access: Option<&FsAccess>,
+    ) -> Result<(), BackendError> {
+        // Without any locks, there is nothing to verify.
+        if !self.path_lock_digest(&path_digest("/")).exists() {
+            return Ok(());
+        }
+
+        // The changes come sorted by path, so a recursively checked path
+        // is seen before its children.
+        let changes = self.txn_changes_fetch(txn_id)?;
+        let mut last_recursed: Option<&str> = None;
+        for (path, change) in &changes {
+            // If this path has already been verified as part of a
+            // recursive check of one of its parents, no need to do it
+            // again.
+            if last_recursed.is_some_and(|parent| fspath_is_ancestor(parent, path)) {
+                continue;
+            }
+
+            // For an existing file or directory getting modified (text,
+            // props), we need to hold the lock on it.  For paths being
+            // added or removed, we need to hold the locks for that path
+            // and any children of that path.
+            if change.change_kind == PathChangeKind::Modify {
+                if let Some(lock) = self.get_lock_helper(path)? {
+                    verify_lock(access, &lock)?;
+                }
+            } else {
+                self.walk_locks(&path_digest(path), &mut |lock| verify_lock(access, &lock))?;
+                last_recursed = Some(path);
+            }
+        }
+
+        Ok(())
+    }
+}
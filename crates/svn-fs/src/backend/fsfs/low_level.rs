@@ -0,0 +1,886 @@
+//! `subversion/libsvn_fs_fs/low_level.c`
+//!
+//! Parsers for the textual items found in FSFS revision files:
+//! node-revisions, representation headers, changed-paths lists and the
+//! revision trailer / footer.
+
+use std::collections::HashMap;
+
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_subr::Tristate;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+use super::super::BackendError;
+use super::super::fsx;
+use crate::CopyFromInfo;
+use crate::NodeRevision;
+use crate::PathChange;
+use crate::PathChangeKind;
+use crate::Representation;
+use crate::id::FsId;
+use crate::id::IdPart;
+use crate::node::RepUniquifier;
+
+// Headers used to describe node-revision in the revision file.
+const HEADER_ID: &str = "id";
+const HEADER_TYPE: &str = "type";
+const HEADER_COUNT: &str = "count";
+const HEADER_PROPS: &str = "props";
+const HEADER_TEXT: &str = "text";
+const HEADER_CPATH: &str = "cpath";
+const HEADER_PRED: &str = "pred";
+const HEADER_COPYFROM: &str = "copyfrom";
+const HEADER_COPYROOT: &str = "copyroot";
+const HEADER_FRESHTXNRT: &str = "is-fresh-txn-root";
+const HEADER_MINFO_HERE: &str = "minfo-here";
+const HEADER_MINFO_CNT: &str = "minfo-cnt";
+
+// Kinds that a change can be.
+const KIND_FILE: &str = "file";
+const KIND_DIR: &str = "dir";
+
+// Kinds that a node-rev can be.
+const REP_PLAIN: &str = "PLAIN";
+const REP_DELTA: &str = "DELTA";
+
+/// Representation header types.
+///
+/// `svn_fs_fs__rep_type_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RepType {
+    /// this is a PLAIN representation
+    Plain,
+    /// this is a DELTA representation with no base representation
+    SelfDelta,
+    /// this is a DELTA representation against some base representation
+    Delta {
+        /// revision of the base rep
+        base_revision: RevisionNumber,
+        /// item index (or offset) of the base rep
+        base_item_index: u64,
+        /// length of the base rep in the rev file
+        base_length: u64,
+    },
+}
+
+/// A representation header as found in the rev file, in front of the
+/// representation data.
+///
+/// `svn_fs_fs__rep_header_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RepHeader {
+    /// type of the representation
+    pub rep_type: RepType,
+    /// length of the header in bytes, including the terminating newline
+    pub header_size: usize,
+}
+
+/// The location of the l2p and p2l indexes, found at the end of format 7+
+/// rev and pack files.
+///
+/// `svn_fs_fs__parse_footer`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Footer {
+    pub l2p_offset: u64,
+    pub l2p_checksum: Checksum,
+    pub p2l_offset: u64,
+    pub p2l_checksum: Checksum,
+}
+
+/// Given the last "few" bytes in @a trailer of revision file @a rev,
+/// return the offsets of the root node-revision and the changed-paths
+/// list.
+///
+/// `svn_fs_fs__parse_revision_trailer`
+pub(crate) fn parse_revision_trailer(
+    trailer: &[u8],
+    rev: RevisionNumber,
+) -> Result<(u64, u64), BackendError> {
+    // The last byte should be a newline.
+    if trailer.last() != Some(&b'\n') {
+        return Err(BackendError::Corrupt(format!(
+            "Revision file (r{rev}) lacks trailing newline"
+        )));
+    }
+
+    // Look for the next previous newline.
+    let body = &trailer[..trailer.len() - 1];
+    let start = match body.iter().rposition(|&b| b == b'\n') {
+        Some(i) => i + 1,
+        None => {
+            return Err(BackendError::Corrupt(format!(
+                "Final line in revision file (r{rev}) longer than 64 characters"
+            )));
+        }
+    };
+
+    let line = std::str::from_utf8(&body[start..]).unwrap_or_default();
+    let (root_offset, changes_offset) = line.split_once(' ').ok_or_else(|| {
+        BackendError::Corrupt(format!("Final line in revision file r{rev} missing space"))
+    })?;
+
+    let parse = |s: &str| {
+        s.parse::<u64>().map_err(|_| {
+            BackendError::Corrupt(format!("Malformed trailer in revision file r{rev}"))
+        })
+    };
+    Ok((parse(root_offset)?, parse(changes_offset)?))
+}
+
+/// Parse the footer @a data of the rev / pack file starting with @a rev.
+///
+/// `svn_fs_fs__parse_footer`
+pub(crate) fn parse_footer(data: &[u8], rev: RevisionNumber) -> Result<Footer, BackendError> {
+    let invalid = || BackendError::Corrupt(format!("Invalid revision footer in r{rev}"));
+
+    let data = std::str::from_utf8(data).map_err(|_| invalid())?;
+    let mut parts = data.split(' ');
+    let mut offset = || -> Result<u64, BackendError> {
+        parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(invalid)
+    };
+    let l2p_offset = offset()?;
+    let l2p_checksum = parts.next().ok_or_else(invalid)?;
+    let p2l_offset = parts
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(invalid)?;
+    let p2l_checksum = parts.next().ok_or_else(invalid)?;
+
+    let checksum = |hex: &str| {
+        Checksum::parse_hex(ChecksumKind::Md5, hex)
+            .ok()
+            .flatten()
+            .ok_or_else(invalid)
+    };
+
+    // Make sure that the index offsets are in order.
+    if l2p_offset >= p2l_offset {
+        return Err(invalid());
+    }
+
+    Ok(Footer {
+        l2p_offset,
+        l2p_checksum: checksum(l2p_checksum)?,
+        p2l_offset,
+        p2l_checksum: checksum(p2l_checksum)?,
+    })
+}
+
+/// Read the "name: value" lines at the start of @a data up to the first
+/// empty line.  Returns the headers and the number of bytes consumed.
+///
+/// `read_header_block`
+fn read_header_block(data: &str) -> Result<(HashMap<&str, &str>, usize), BackendError> {
+    let mut headers = HashMap::new();
+    let mut pos = 0;
+
+    loop {
+        let Some(end) = data[pos..].find('\n') else {
+            return Err(BackendError::Corrupt(
+                "Found malformed header in revision file".to_string(),
+            ));
+        };
+        let line = &data[pos..pos + end];
+        pos += end + 1;
+
+        if line.is_empty() {
+            break;
+        }
+
+        let (name, value) = line.split_once(": ").ok_or_else(|| {
+            BackendError::Corrupt(format!("Found malformed header '{line}' in revision file"))
+        })?;
+        headers.insert(name, value);
+    }
+
+    Ok((headers, pos))
+}
+
+/// Parse the "rev path" pair used by the copyfrom and copyroot headers.
+fn parse_rev_path(value: &str) -> Option<(RevisionNumber, String)> {
+    let (rev, path) = value.split_once(' ')?;
+    if !path.starts_with('/') {
+        return None;
+    }
+    Some((rev.parse().ok()?, path.to_string()))
+}
+
+/// Parse the node-revision in @a data, as found in rev files and
+/// transactions.
+///
+/// `svn_fs_fs__read_noderev`
+pub(crate) fn parse_noderev(data: &str) -> Result<NodeRevision, BackendError> {
+    let (headers, _) = read_header_block(data)?;
+
+    // Read the node-rev id.
+    let id: FsId = headers
+        .get(HEADER_ID)
+        .ok_or_else(|| BackendError::Corrupt("Missing id field in node-rev".to_string()))?
+        .parse()?;
+    let corrupt = |what: &str| BackendError::Corrupt(format!("{what} in node-rev '{id}'"));
+
+    // Read the type.
+    let kind = match headers.get(HEADER_TYPE) {
+        Some(&KIND_FILE) => NodeKind::File,
+        Some(&KIND_DIR) => NodeKind::Directory,
+        _ => return Err(corrupt("Missing kind field")),
+    };
+
+    // Read the 'count' field.
+    let predecessor_count = match headers.get(HEADER_COUNT) {
+        Some(count) => count.parse().map_err(|_| corrupt("Invalid count field"))?,
+        None => 0,
+    };
+
+    // Get the properties location.
+    let prop_rep = headers
+        .get(HEADER_PROPS)
+        .map(|value| parse_representation(value, &id))
+        .transpose()?;
+
+    // Get the data location.
+    let data_rep = headers
+        .get(HEADER_TEXT)
+        .map(|value| parse_representation(value, &id))
+        .transpose()?;
+
+    // Get the created path.
+    let created_path = match headers.get(HEADER_CPATH) {
+        Some(path) if path.starts_with('/') => path.to_string(),
+        Some(_) => return Err(corrupt("Non-canonical cpath field")),
+        None => return Err(corrupt("Missing cpath field")),
+    };
+
+    // Get the predecessor ID.
+    let predecessor_id = headers
+        .get(HEADER_PRED)
+        .map(|value| value.parse::<FsId>())
+        .transpose()?;
+
+    // Get the copyroot.
+    let (copyroot_rev, copyroot_path) = match headers.get(HEADER_COPYROOT) {
+        Some(value) => parse_rev_path(value).ok_or_else(|| corrupt("Malformed copyroot line"))?,
+        None => (id.rev(), created_path.clone()),
+    };
+
+    // Get the copyfrom.
+    let copyfrom = match headers.get(HEADER_COPYFROM) {
+        Some(value) => {
+            let (rev, path) =
+                parse_rev_path(value).ok_or_else(|| corrupt("Malformed copyfrom line"))?;
+            Some(CopyFromInfo { rev, path })
+        }
+        None => None,
+    };
+
+    // Get the mergeinfo count.
+    let mergeinfo_count = match headers.get(HEADER_MINFO_CNT) {
+        Some(count) => count
+            .parse()
+            .map_err(|_| corrupt("Invalid minfo-cnt field"))?,
+        None => 0,
+    };
+
+    Ok(NodeRevision {
+        kind,
+        predecessor_id,
+        copyfrom,
+        copyroot_rev,
+        copyroot_path,
+        predecessor_count,
+        prop_rep,
+        data_rep,
+        created_path,
+        // Get whether this is a fresh txn root.
+        is_fresh_txn_root: headers.contains_key(HEADER_FRESHTXNRT),
+        mergeinfo_count,
+        // Get whether *this* node has mergeinfo.
+        has_mergeinfo: headers.contains_key(HEADER_MINFO_HERE),
+        id,
+    })
+}
+
+/// Parse the representation description @a value of node-revision
+/// @a noderev_id.
+///
+/// `read_rep_offsets`
+pub(crate) fn parse_representation(
+    value: &str,
+    noderev_id: &FsId,
+) -> Result<Representation, BackendError> {
+    let malformed = || {
+        BackendError::Corrupt(format!(
+            "Malformed text representation offset line in node-rev '{noderev_id}'"
+        ))
+    };
+
+    let mut parts = value.split(' ');
+    let revision: RevisionNumber = parts
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(malformed)?;
+
+    // initialize transaction info (never stored)
+    let txn_id = if revision == SVN_INVALID_REVNUM {
+        noderev_id.txn_id
+    } else {
+        IdPart::UNUSED_TXN
+    };
+
+    let mut rep = Representation {
+        md5_digest: None,
+        sha1_digest: None,
+        revision,
+        item_index: 0,
+        size: 0,
+        expanded_size: 0,
+        txn_id,
+        uniquifier: None,
+    };
+
+    // while in transactions, it is legal to simply write "-1"
+    let Some(item_index) = parts.next() else {
+        if revision == SVN_INVALID_REVNUM {
+            return Ok(rep);
+        }
+        return Err(malformed());
+    };
+
+    let number = |s: Option<&str>| -> Result<u64, BackendError> {
+        s.and_then(|s| s.parse().ok()).ok_or_else(malformed)
+    };
+    rep.item_index = number(Some(item_index))?;
+    rep.size = number(parts.next())?;
+    rep.expanded_size = number(parts.next())?;
+
+    // Read in the MD5 hash.
+    let md5 = parts
+        .next()
+        .filter(|s| s.len() == 32)
+        .ok_or_else(malformed)?;
+    rep.md5_digest = Checksum::parse_hex(ChecksumKind::Md5, md5).map_err(|_| malformed())?;
+
+    // The remaining fields are only used for formats >= 4, so check that.
+    let Some(sha1) = parts.next() else {
+        return Ok(rep);
+    };
+
+    // Read the SHA1 hash.
+    if sha1.len() != 40 {
+        return Err(malformed());
+    }
+    rep.sha1_digest = Checksum::parse_hex(ChecksumKind::Sha1, sha1).map_err(|_| malformed())?;
+
+    // Read the uniquifier.
+    let (txn_id, number) = parts
+        .next()
+        .and_then(|s| s.split_once('/'))
+        .ok_or_else(malformed)?;
+    let number = number.strip_prefix('_').ok_or_else(malformed)?;
+    rep.uniquifier = Some(RepUniquifier {
+        noderev_txn_id: IdPart::parse_txn(txn_id)?,
+        number: crate::id::base36_to_u64(number).ok_or_else(malformed)?,
+    });
+
+    Ok(rep)
+}
+
+/// Parse the representation header line at the start of @a data.
+///
+/// `svn_fs_fs__read_rep_header`
+pub(crate) fn parse_rep_header(data: &[u8]) -> Result<RepHeader, BackendError> {
+    let end = data.iter().position(|&b| b == b'\n');
+    let line = end
+        .and_then(|end| std::str::from_utf8(&data[..end]).ok())
+        .ok_or_else(|| BackendError::Corrupt("Malformed representation header".to_string()))?;
+    let header_size = line.len() + 1;
+
+    if line == REP_PLAIN {
+        return Ok(RepHeader {
+            rep_type: RepType::Plain,
+            header_size,
+        });
+    }
+
+    if line == REP_DELTA {
+        // This is a delta against the empty stream.
+        return Ok(RepHeader {
+            rep_type: RepType::SelfDelta,
+            header_size,
+        });
+    }
+
+    // We have hopefully a DELTA vs. a non-empty base revision.
+    let malformed = || BackendError::Corrupt(format!("Malformed representation header '{line}'"));
+    let mut parts = line.split(' ');
+    if parts.next() != Some(REP_DELTA) {
+        return Err(malformed());
+    }
+    let base_revision = parts
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(malformed)?;
+    let base_item_index = parts
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(malformed)?;
+    let base_length = parts
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(malformed)?;
+
+    Ok(RepHeader {
+        rep_type: RepType::Delta {
+            base_revision,
+            base_item_index,
+            base_length,
+        },
+        header_size,
+    })
+}
+
+/// Parse the changed-paths list at the start of @a data, stopping at the
+/// terminating empty line or the end of @a data.
+///
+/// `svn_fs_fs__read_changes`
+pub(crate) fn parse_changes(data: &str) -> Result<Vec<PathChange>, BackendError> {
+    let mut changes = Vec::new();
+    let mut lines = data.split('\n');
+
+    while let Some(line) = lines.next() {
+        if line.is_empty() {
+            break;
+        }
+        let copyfrom_line = lines.next().unwrap_or_default();
+        changes.push(parse_change(line, copyfrom_line)?);
+    }
+
+    Ok(changes)
+}
+
+/// Parse a single change: the description in @a line followed by the
+/// copy source in @a copyfrom_line.
+///
+/// `read_change`
+fn parse_change(line: &str, copyfrom_line: &str) -> Result<PathChange, BackendError> {
+    let invalid = || BackendError::Corrupt("Invalid changes line in rev-file".to_string());
+
+    // Get the node-id of the change.
+    let (id, rest) = line.split_once(' ').ok_or_else(invalid)?;
+    let node_rev_id = Some(id.parse::<FsId>()?);
+
+    // Get the change type.
+    let (change, rest) = rest.split_once(' ').ok_or_else(invalid)?;
+
+    // Don't bother to check the format number before looking for
+    // node-kinds: just read them if you find them.
+    let (change, node_kind) = match change.split_once('-') {
+        Some((change, KIND_FILE)) => (change, NodeKind::File),
+        Some((change, KIND_DIR)) => (change, NodeKind::Directory),
+        Some(_) => return Err(invalid()),
+        None => (change, NodeKind::Unknown),
+    };
+    let change_kind = PathChangeKind::from_word(change)
+        .ok_or_else(|| BackendError::Corrupt("Invalid change kind in rev file".to_string()))?;
+
+    let flag = |word: &str, what: &str| match word {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(BackendError::Corrupt(format!(
+            "Invalid {what} flag in rev-file"
+        ))),
+    };
+
+    // Get the text-mod flag.
+    let (text_mod, rest) = rest.split_once(' ').ok_or_else(invalid)?;
+    let text_mod = flag(text_mod, "text-mod")?;
+
+    // Get the prop-mod flag.
+    let (prop_mod, mut rest) = rest.split_once(' ').ok_or_else(invalid)?;
+    let prop_mod = flag(prop_mod, "prop-mod")?;
+
+    // Get the mergeinfo-mod flag if given.  Otherwise, the server will
+    // be asked to determine it.
+    let mut mergeinfo_mod = Tristate::Unknown;
+    if let Some(path) = rest.strip_prefix("true ") {
+        mergeinfo_mod = Tristate::True;
+        rest = path;
+    } else if let Some(path) = rest.strip_prefix("false ") {
+        mergeinfo_mod = Tristate::False;
+        rest = path;
+    }
+
+    // Get the changed path.
+    if !rest.starts_with('/') {
+        return Err(BackendError::Corrupt(
+            "Invalid path in changes line".to_string(),
+        ));
+    }
+
+    // Read the next line, the copyfrom line.
+    let copyfrom = if copyfrom_line.is_empty() {
+        None
+    } else {
+        let (rev, path) = parse_rev_path(copyfrom_line).ok_or_else(invalid)?;
+        Some(CopyFromInfo { rev, path })
+    };
+
+    Ok(PathChange {
+        path: rest.to_string(),
+        node_rev_id,
+        change_kind,
+        node_kind,
+        text_mod,
+        prop_mod,
+        mergeinfo_mod,
+        copyfrom,
+    })
+}
+
+/// Return the representation header line for @a rep_type, including the
+/// terminating newline.
+///
+/// `svn_fs_fs__write_rep_header`
+pub(crate) fn unparse_rep_header(rep_type: &RepType) -> String {
+    match rep_type {
+        RepType::Plain => format!("{REP_PLAIN}\n"),
+        RepType::SelfDelta => format!("{REP_DELTA}\n"),
+        RepType::Delta {
+            base_revision,
+            base_item_index,
+            base_length,
+        } => format!("{REP_DELTA} {base_revision} {base_item_index} {base_length}\n"),
+    }
+}
+
+/// Return the textual description of @a rep as used in node-revisions.
+/// If @a mutable_rep_truncated is set, representations still in a
+/// transaction are written as a bare "-1".
+///
+/// `svn_fs_fs__unparse_representation`
+pub(crate) fn unparse_representation(rep: &Representation, mutable_rep_truncated: bool) -> String {
+    if rep.txn_id.is_used_txn() && mutable_rep_truncated {
+        return SVN_INVALID_REVNUM.to_string();
+    }
+
+    let md5 = rep
+        .md5_digest
+        .as_ref()
+        .map(Checksum::to_hex)
+        .unwrap_or_else(|| "0".repeat(32));
+    let mut result = format!(
+        "{} {} {} {} {md5}",
+        rep.revision, rep.item_index, rep.size, rep.expanded_size
+    );
+
+    if let (Some(sha1), Some(uniquifier)) = (&rep.sha1_digest, &rep.uniquifier) {
+        result.push_str(&format!(
+            " {} {}/_{}",
+            sha1.to_hex(),
+            uniquifier.noderev_txn_id.unparse_txn(),
+            crate::id::u64_to_base36(uniquifier.number)
+        ));
+    }
+
+    result
+}
+
+/// Return the textual form of @a noderev as stored in rev files and
+/// transactions, terminated by an empty line.
+///
+/// `svn_fs_fs__write_noderev`
+pub(crate) fn unparse_noderev(noderev: &NodeRevision) -> String {
+    let mut out = format!("{HEADER_ID}: {}\n", noderev.id);
+    out.push_str(&format!(
+        "{HEADER_TYPE}: {}\n",
+        if noderev.kind == NodeKind::File {
+            KIND_FILE
+        } else {
+            KIND_DIR
+        }
+    ));
+
+    if let Some(pred) = &noderev.predecessor_id {
+        out.push_str(&format!("{HEADER_PRED}: {pred}\n"));
+    }
+    out.push_str(&format!("{HEADER_COUNT}: {}\n", noderev.predecessor_count));
+
+    if let Some(rep) = &noderev.data_rep {
+        out.push_str(&format!(
+            "{HEADER_TEXT}: {}\n",
+            unparse_representation(rep, noderev.kind == NodeKind::Directory)
+        ));
+    }
+    if let Some(rep) = &noderev.prop_rep {
+        out.push_str(&format!(
+            "{HEADER_PROPS}: {}\n",
+            unparse_representation(rep, true)
+        ));
+    }
+
+    out.push_str(&format!("{HEADER_CPATH}: {}\n", noderev.created_path));
+
+    if let Some(copyfrom) = &noderev.copyfrom {
+        out.push_str(&format!(
+            "{HEADER_COPYFROM}: {} {}\n",
+            copyfrom.rev, copyfrom.path
+        ));
+    }
+
+    if noderev.copyroot_rev != noderev.id.rev() || noderev.copyroot_path != noderev.created_path {
+        out.push_str(&format!(
+            "{HEADER_COPYROOT}: {} {}\n",
+            noderev.copyroot_rev, noderev.copyroot_path
+        ));
+    }
+
+    if noderev.is_fresh_txn_root {
+        out.push_str(&format!("{HEADER_FRESHTXNRT}: y\n"));
+    }
+
+    if noderev.mergeinfo_count > 0 {
+        out.push_str(&format!(
+            "{HEADER_MINFO_CNT}: {}\n",
+            noderev.mergeinfo_count
+        ));
+    }
+
+    if noderev.has_mergeinfo {
+        out.push_str(&format!("{HEADER_MINFO_HERE}: y\n"));
+    }
+
+    out.push('\n');
+    out
+}
+
+/// Return the two lines describing @a change in a changed-paths list.
+///
+/// `write_change_entry`
+pub(crate) fn unparse_change(change: &PathChange) -> String {
+    let id = change
+        .node_rev_id
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_else(|| "reset".to_string());
+
+    let kind = match change.node_kind {
+        NodeKind::File => format!("-{KIND_FILE}"),
+        NodeKind::Directory => format!("-{KIND_DIR}"),
+        _ => String::new(),
+    };
+
+    let mergeinfo = match change.mergeinfo_mod {
+        Tristate::True => " true",
+        Tristate::False => " false",
+        Tristate::Unknown => "",
+    };
+
+    let copyfrom = change
+        .copyfrom
+        .as_ref()
+        .map(|copyfrom| format!("{} {}", copyfrom.rev, copyfrom.path))
+        .unwrap_or_default();
+
+    format!(
+        "{id} {}{kind} {} {}{mergeinfo} {}\n{copyfrom}\n",
+        change.change_kind.as_str(),
+        change.text_mod,
+        change.prop_mod,
+        change.path
+    )
+}
+
+/// Return @a noderev as an item of a rev file, in the binary FSX encoding
+/// if @a binary is set.
+pub(crate) fn unparse_noderev_item(noderev: &NodeRevision, binary: bool) -> Vec<u8> {
+    if binary {
+        fsx::frame_item(&fsx::noderevs::write_noderev(noderev))
+    } else {
+        unparse_noderev(noderev).into_bytes()
+    }
+}
+
+/// Parse the node-revision item @a data of a rev file, which uses the
+/// binary FSX encoding if @a binary is set.
+pub(crate) fn parse_noderev_item(data: &[u8], binary: bool) -> Result<NodeRevision, BackendError> {
+    if binary {
+        return fsx::noderevs::read_noderev(fsx::unframe_item(data)?);
+    }
+
+    let data = std::str::from_utf8(data)
+        .map_err(|_| BackendError::Corrupt("Malformed node-revision".to_string()))?;
+    parse_noderev(data)
+}
+
+/// Return the changed-paths list @a changes as an item of a rev file, in
+/// the binary FSX encoding if @a binary is set.
+///
+/// `svn_fs_fs__write_changes`
+pub(crate) fn unparse_changes_item(
+    changes: &[PathChange],
+    binary: bool,
+) -> Result<Vec<u8>, BackendError> {
+    if binary {
+        return Ok(fsx::frame_item(&fsx::changes::write_changes(changes)?));
+    }
+
+    let mut data: String = changes.iter().map(unparse_change).collect();
+    data.push('\n');
+    Ok(data.into_bytes())
+}
+
+/// Parse the changed-paths list item @a data of a rev file, which uses
+/// the binary FSX encoding if @a binary is set.
+pub(crate) fn parse_changes_item(
+    data: &[u8],
+    binary: bool,
+) -> Result<Vec<PathChange>, BackendError> {
+    if binary {
+        return fsx::changes::read_changes(fsx::unframe_item(data)?);
+    }
+
+    let data = std::str::from_utf8(data)
+        .map_err(|_| BackendError::Corrupt("Malformed changed-paths list".to_string()))?;
+    parse_changes(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_noderev() {
+        let noderev = parse_noderev(
+            "id: 2-1.0-1.r3/1234\n\
+             type: file\n\
+             pred: 2-1.0-1.r2/88\n\
+             count: 2\n\
+             text: 3 1100 20 16 8c3b5f6b4b3a87d0bd4dcd0d2d1bc8a5 \
+             0beec7b5ea3f0fdbc95d0dd47f3c5bc275da8a33 3-2/_5\n\
+             props: 1 500 40 0 d41d8cd98f00b204e9800998ecf8427e\n\
+             cpath: /trunk/iota\n\
+             copyfrom: 1 /iota\n\
+             minfo-cnt: 1\n\
+             minfo-here: y\n\
+             \n",
+        )
+        .unwrap();
+
+        assert_eq!(noderev.id.to_string(), "2-1.0-1.r3/1234");
+        assert_eq!(noderev.kind, NodeKind::File);
+        assert_eq!(noderev.predecessor_count, 2);
+        assert_eq!(noderev.predecessor_id.unwrap().rev(), 2);
+        assert_eq!(noderev.copyroot_rev, 3);
+        assert_eq!(noderev.copyroot_path, "/trunk/iota");
+        assert_eq!(
+            noderev.copyfrom,
+            Some(CopyFromInfo {
+                rev: 1,
+                path: "/iota".to_string()
+            })
+        );
+        assert!(noderev.has_mergeinfo);
+        assert_eq!(noderev.mergeinfo_count, 1);
+
+        let text = noderev.data_rep.unwrap();
+        assert_eq!((text.revision, text.item_index), (3, 1100));
+        assert_eq!((text.size, text.expanded_size), (20, 16));
+        assert!(text.sha1_digest.is_some());
+        assert_eq!(text.uniquifier.unwrap().number, 5);
+
+        // The MD5 of the empty string is stored as such.
+        let props = noderev.prop_rep.unwrap();
+        assert!(props.sha1_digest.is_none());
+    }
+
+    #[test]
+    fn test_unparse_roundtrip() {
+        for data in [
+            "id: 0.0.r0/17\ntype: dir\ncount: 0\ntext: 0 0 4 4 2d2977d1c96f487abe4a1e202dd03b4e\ncpath: /\n\n",
+            "id: _1.0.t0-1\ntype: dir\npred: 0.0.r0/2\ncount: 1\ntext: -1\ncpath: /\nis-fresh-txn-root: y\n\n",
+            "id: 2-1.0-1.r3/1234\n\
+             type: file\n\
+             count: 0\n\
+             text: 3 1100 20 16 8c3b5f6b4b3a87d0bd4dcd0d2d1bc8a5 \
+             0beec7b5ea3f0fdbc95d0dd47f3c5bc275da8a33 3-2/_5\n\
+             cpath: /trunk/iota\n\
+             copyfrom: 1 /iota\n\
+             copyroot: 3 /trunk\n\
+             \n",
+        ] {
+            assert_eq!(unparse_noderev(&parse_noderev(data).unwrap()), data);
+        }
+
+        let changes = "_0.0.t1-1 add-dir false false false /trunk\n\
+                       \n\
+                       1-2.0-2.r2/5 modify-file true false /iota\n\
+                       1 /iota\n";
+        let unparsed: String = parse_changes(changes)
+            .unwrap()
+            .iter()
+            .map(unparse_change)
+            .collect();
+        assert_eq!(unparsed, changes);
+    }
+
+    #[test]
+    fn test_parse_rep_header() {
+        assert_eq!(
+            parse_rep_header(b"PLAIN\nabc").unwrap(),
+            RepHeader {
+                rep_type: RepType::Plain,
+                header_size: 6
+            }
+        );
+        assert_eq!(
+            parse_rep_header(b"DELTA\n").unwrap().rep_type,
+            RepType::SelfDelta
+        );
+        assert_eq!(
+            parse_rep_header(b"DELTA 1 2 3\n").unwrap().rep_type,
+            RepType::Delta {
+                base_revision: 1,
+                base_item_index: 2,
+                base_length: 3
+            }
+        );
+        assert!(parse_rep_header(b"PLAIN").is_err());
+    }
+
+    #[test]
+    fn test_parse_changes() {
+        let changes = parse_changes(
+            "_0.0.t1-1 add-dir false false false /trunk\n\
+             \n\
+             1-2.0-2.r2/5 modify-file true false /iota\n\
+             1 /iota\n\
+             \n",
+        );
+        let changes = changes.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].change_kind, PathChangeKind::Add);
+        assert_eq!(changes[0].node_kind, NodeKind::Directory);
+        assert_eq!(changes[0].mergeinfo_mod, Tristate::False);
+        assert_eq!(changes[1].path, "/iota");
+        assert!(changes[1].text_mod);
+        assert_eq!(changes[1].mergeinfo_mod, Tristate::Unknown);
+        assert_eq!(changes[1].copyfrom.as_ref().unwrap().rev, 1);
+
+        assert!(parse_changes("\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_revision_trailer() {
+        assert_eq!(
+            parse_revision_trailer(b"cpath: /\n\n\n17 107\n", 0).unwrap(),
+            (17, 107)
+        );
+        assert!(parse_revision_trailer(b"17 107", 0).is_err());
+    }
+}
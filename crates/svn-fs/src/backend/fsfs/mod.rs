@@ -1,3 +1,5 @@
+mod cached_data;
+
 mod dag;
 
 mod data;
@@ -5,9 +7,46 @@ pub use data::FsFsData;
 
 mod caching;
 pub use caching::CachingError;
+pub(crate) use caching::{
+    SVN_FS_CONFIG_FSFS_CACHE_DELTAS, SVN_FS_CONFIG_FSFS_CACHE_FULLTEXTS,
+    SVN_FS_CONFIG_FSFS_CACHE_NS,
+};
 
 mod fs;
 
+mod hotcopy;
+
+mod index;
+
+mod lock;
+
+pub(crate) mod low_level;
+
+mod pack;
+
+mod recovery;
+
+mod rep_cache;
+
+mod rev_file;
+
+mod temp_serializer;
+
+mod revprops;
+
+mod stats;
+
+mod transaction;
+
+mod tree;
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
+
+mod util;
+
+mod verify;
+
 mod vtable;
 
 use std::path::PathBuf;
@@ -44,6 +83,59 @@ impl FsFsBackend {
     }
 }
 
+/// The default maximum number of files per directory to store in the
+/// rev and revprops directory.  The number below is somewhat arbitrary;
+/// the figure of 1000 is reasonable for VFAT filesystems, which are by far
+/// the worst performers in this area.
+const SVN_FS_FS_DEFAULT_MAX_FILES_PER_DIR: u32 = 1000;
+
+/// The minimum format number that supports svndiff version 1.
+const SVN_FS_FS__MIN_SVNDIFF1_FORMAT: u32 = 2;
+
+/// The minimum format number that supports transaction ID generation
+/// using a transaction sequence in the txn-current file.
+const SVN_FS_FS__MIN_TXN_CURRENT_FORMAT: u32 = 3;
+
+/// The minimum format number that supports the "layout" filesystem
+/// format option.
+const SVN_FS_FS__MIN_LAYOUT_FORMAT_OPTION_FORMAT: u32 = 3;
+
+/// The minimum format number that stores protorevs in a separate
+/// subdirectory.
+const SVN_FS_FS__MIN_PROTOREVS_DIR_FORMAT: u32 = 3;
+
+/// The minimum format number that doesn't keep node and copy ID counters.
+const SVN_FS_FS__MIN_NO_GLOBAL_IDS_FORMAT: u32 = 3;
+
+/// The minimum format number that supports packed shards.
+const SVN_FS_FS__MIN_PACKED_FORMAT: u32 = 4;
+
+/// The minimum format number that supports packed revprops.
+const SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT: u32 = 6;
+
+/// The minimum format number that supports representation sharing.
+const SVN_FS_FS__MIN_REP_SHARING_FORMAT: u32 = 4;
+
+/// The minimum format number that supports a configuration file (fsfs.conf)
+const SVN_FS_FS__MIN_CONFIG_FILE: u32 = 4;
+
+/// The minimum format number that supports logical addressing.
+const SVN_FS_FS__MIN_LOG_ADDRESSING_FORMAT: u32 = 7;
+
+/// The minimum format number that uses the pack-lock instead of the
+/// write-lock to serialize packing.
+const SVN_FS_FS__MIN_PACK_LOCK_FORMAT: u32 = 7;
+
+/// Minimum format number that supports per-instance filesystem IDs.
+const SVN_FS_FS__MIN_INSTANCE_ID_FORMAT: u32 = 7;
+
+/// The minimum format number that supports svndiff version 2.
+const SVN_FS_FS__MIN_SVNDIFF2_FORMAT: u32 = 8;
+
+/// The minimum format number that uses the second rep-cache.db schema,
+/// a table without row IDs.
+const SVN_FS_FS__MIN_REP_CACHE_SCHEMA_V2_FORMAT: u32 = 8;
+
 /// The format number of this filesystem.
 /// This is independent of the repository format number, and
 /// independent of any other FS back ends.
@@ -59,11 +151,11 @@ const FORMAT_NUMBER: u32 = 8;
 /// and is otherwise ignored.
 ///
 /// @since New in 1.9.
-const SVN_FS_CONFIG_FSFS_SHARD_SIZE: &str = "fsfs-shard-size";
+pub(crate) const SVN_FS_CONFIG_FSFS_SHARD_SIZE: &str = "fsfs-shard-size";
 
 /// Enable / disable the FSFS format 7 logical addressing feature for a
 /// newly created repository.
 ///
 /// This option will only be used during the creation of new repositories
 /// and is otherwise ignored.
-const SVN_FS_CONFIG_FSFS_LOG_ADDRESSING: &str = "fsfs-log-addressing";
+pub(crate) const SVN_FS_CONFIG_FSFS_LOG_ADDRESSING: &str = "fsfs-log-addressing";
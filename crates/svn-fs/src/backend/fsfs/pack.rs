@@ -0,0 +1,289 @@
+//! `subversion/libsvn_fs_fs/pack.c`
+//!
+//! Packing concatenates the rev files of a completed shard into a single
+//! pack file.  With physical addressing, a manifest lists the offset of
+//! every revision within the pack file; with logical addressing, the pack
+//! file gets its own l2p / p2l indexes.
+
+use std::path::Path;
+
+use svn_subr::io::FileLock;
+use svn_types::RevisionNumber;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::SVN_FS_FS__MIN_PACK_LOCK_FORMAT;
+use super::SVN_FS_FS__MIN_PACKED_FORMAT;
+use super::SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT;
+use super::index;
+use super::index::P2lEntry;
+use crate::PackNotifyAction;
+use crate::backend::PATH_MANIFEST;
+use crate::backend::PATH_PACKED;
+use crate::backend::check_cancel;
+
+/// Call @a notify, if given, for @a shard and @a action.
+fn notify_action(
+    notify: &mut Option<&mut dyn FnMut(i64, PackNotifyAction)>,
+    shard: i64,
+    action: PackNotifyAction,
+) {
+    if let Some(notify) = notify {
+        notify(shard, action);
+    }
+}
+
+impl FsFsBackend {
+    /// Pack the completed shards of the filesystem, calling @a notify
+    /// before and after each shard and polling @a cancel in between.
+    ///
+    /// `svn_fs_fs__pack`
+    pub(crate) fn pack(
+        &mut self,
+        mut notify: Option<&mut dyn FnMut(i64, PackNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let format = self._data().format;
+        if format < SVN_FS_FS__MIN_PACKED_FORMAT {
+            return Err(BackendError::UnsupportedFeature(format!(
+                "FSFS format ({format}) too old to pack; please upgrade the filesystem."
+            )));
+        }
+
+        // If we aren't using sharding, we can't do any packing, so quit.
+        if self._data().max_files_per_dir == 0 {
+            notify_action(&mut notify, -1, PackNotifyAction::Noop);
+            return Ok(());
+        }
+
+        // Packing is a long-running operation.  Newer formats have a
+        // separate lock for it, so that commits only get blocked while a
+        // packed shard is being published.
+        let _lock = if format >= SVN_FS_FS__MIN_PACK_LOCK_FORMAT {
+            FileLock::lock(&self.path_pack_lock(), true)?
+        } else {
+            FileLock::lock(&self.path_lock(), true)?
+        };
+
+        self.pack_body(notify, cancel)
+    }
+
+    /// Pack all shards that are complete but not packed yet.  The caller
+    /// must hold the pack lock.
+    ///
+    /// `pack_body`
+    fn pack_body(
+        &mut self,
+        mut notify: Option<&mut dyn FnMut(i64, PackNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let max_files_per_dir = self._data().max_files_per_dir as RevisionNumber;
+
+        // Get the current youngest revision and the oldest unpacked one.
+        let min_unpacked_rev = self.read_min_unpacked_rev()?;
+        self._data_mut().min_unpacked_rev = min_unpacked_rev;
+        let youngest = self.read_current()?;
+        let completed_shards = (youngest + 1) / max_files_per_dir;
+
+        // See if we've already completed all possible shards thus far.
+        if min_unpacked_rev == completed_shards * max_files_per_dir {
+            notify_action(&mut notify, completed_shards, PackNotifyAction::Noop);
+            return Ok(());
+        }
+
+        for shard in min_unpacked_rev / max_files_per_dir..completed_shards {
+            check_cancel(cancel)?;
+            self.pack_shard(shard, &mut notify, cancel)?;
+        }
+
+        Ok(())
+    }
+
+    /// Pack the revisions and revprops of shard @a shard and make the
+    /// packed shard visible to readers.
+    ///
+    /// `pack_shard`
+    fn pack_shard(
+        &mut self,
+        shard: i64,
+        notify: &mut Option<&mut dyn FnMut(i64, PackNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let format = self._data().format;
+        let max_files_per_dir = self._data().max_files_per_dir as RevisionNumber;
+        let start_rev = shard * max_files_per_dir;
+
+        // Notify caller we're starting to pack this shard.
+        notify_action(notify, shard, PackNotifyAction::Start);
+
+        // pack the revision content
+        self.pack_rev_shard(shard, cancel)?;
+
+        // if enabled, pack the revprops in an equivalent way
+        if format >= SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+            notify_action(notify, shard, PackNotifyAction::StartRevprop);
+            self.pack_revprops_shard(shard, cancel)?;
+            notify_action(notify, shard, PackNotifyAction::EndRevprop);
+        }
+
+        // Update the min-unpacked-rev file to reflect our newly packed
+        // shard.  Older formats hold the write lock already.
+        {
+            let _write_lock = if format >= SVN_FS_FS__MIN_PACK_LOCK_FORMAT {
+                Some(FileLock::lock(&self.path_lock(), true)?)
+            } else {
+                None
+            };
+
+            let min_unpacked_rev = start_rev + max_files_per_dir;
+            self.write_min_unpacked_rev(min_unpacked_rev)?;
+            self._data_mut().min_unpacked_rev = min_unpacked_rev;
+
+            // Readers of the revprops now use the packed ones.
+            if format >= SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+                self.delete_revprops_shard(shard)?;
+            }
+        }
+
+        // Finally, remove the existing shard directory.
+        fs_err::remove_dir_all(self.path_rev_shard(start_rev))?;
+
+        // Notify caller we're finished packing this shard.
+        notify_action(notify, shard, PackNotifyAction::End);
+
+        Ok(())
+    }
+
+    /// Pack the rev files of shard @a shard into its pack directory,
+    /// replacing any incomplete left-overs of an earlier attempt.
+    ///
+    /// `pack_rev_shard`
+    fn pack_rev_shard(
+        &self,
+        shard: i64,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let max_files_per_dir = self._data().max_files_per_dir as RevisionNumber;
+        let start_rev = shard * max_files_per_dir;
+        let pack_dir = self.path_rev_pack_shard(start_rev);
+
+        // Remove any existing pack file for this shard, since it is
+        // incomplete.
+        if pack_dir.exists() {
+            fs_err::remove_dir_all(&pack_dir)?;
+        }
+
+        // Create the new directory and pack file.
+        fs_err::create_dir_all(&pack_dir)?;
+
+        let revs = start_rev..start_rev + max_files_per_dir;
+        if self._data().use_log_addressing {
+            self.pack_log_addressed(&pack_dir, revs, cancel)
+        } else {
+            self.pack_phys_addressed(&pack_dir, revs, cancel)
+        }
+    }
+
+    /// Concatenate the rev files of @a revs into the pack file in
+    /// @a pack_dir and write the manifest with their offsets.
+    ///
+    /// `pack_phys_addressed`
+    fn pack_phys_addressed(
+        &self,
+        pack_dir: &Path,
+        revs: std::ops::Range<RevisionNumber>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let mut pack = Vec::new();
+        let mut manifest = String::new();
+        for rev in revs {
+            check_cancel(cancel)?;
+
+            // Update the manifest.
+            manifest.push_str(&format!("{}\n", pack.len()));
+
+            // Copy all the bits from the rev file to the end of the pack
+            // file.
+            pack.extend_from_slice(&fs_err::read(self.path_rev(rev))?);
+        }
+
+        self.write_pack_file(&pack_dir.join(PATH_PACKED), &pack)?;
+        self.write_pack_file(&pack_dir.join(PATH_MANIFEST), manifest.as_bytes())
+    }
+
+    /// Concatenate the revision data of @a revs into the pack file in
+    /// @a pack_dir and append l2p / p2l indexes covering all of them.
+    ///
+    /// `pack_log_addressed`
+    fn pack_log_addressed(
+        &self,
+        pack_dir: &Path,
+        revs: std::ops::Range<RevisionNumber>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let first_rev = revs.start;
+        let p2l_page_size = self._data().p2l_page_size as u64;
+        let mut pack = Vec::new();
+        let mut entries = Vec::new();
+        for rev in revs {
+            check_cancel(cancel)?;
+
+            let mut rev_file = self.open_pack_or_rev_file(rev)?;
+            let p2l = rev_file.p2l_index()?;
+            let max_offset = index::p2l_get_max_offset(&p2l)?;
+
+            // Items keep their numbers but move to the end of the pack
+            // file.  Walk the index block by block.
+            let base = pack.len() as u64;
+            let mut offset = 0;
+            while offset < max_offset {
+                let block_start = offset;
+                for entry in index::p2l_index_lookup(&p2l, offset, p2l_page_size)? {
+                    // Skip items that we copied already and the padding
+                    // behind the revision data.
+                    if entry.offset < offset || entry.offset >= max_offset {
+                        continue;
+                    }
+
+                    offset = entry.offset + entry.size;
+                    entries.push(P2lEntry {
+                        offset: entry.offset + base,
+                        ..entry
+                    });
+                }
+
+                if offset == block_start {
+                    return Err(BackendError::Corrupt(format!(
+                        "P2L index of r{rev} does not cover offset {offset}"
+                    )));
+                }
+            }
+            pack.extend_from_slice(&rev_file.read_at(0, max_offset as usize)?);
+        }
+
+        let data_size = pack.len() as u64;
+        index::add_index_data(
+            &mut pack,
+            data_size,
+            &entries,
+            first_rev,
+            self._data().l2p_page_size as u64,
+            self._data().p2l_page_size as u64,
+        )?;
+
+        self.write_pack_file(&pack_dir.join(PATH_PACKED), &pack)
+    }
+
+    /// Write @a data to the read-only file at @a path.
+    fn write_pack_file(&self, path: &Path, data: &[u8]) -> Result<(), BackendError> {
+        let mut file = fs_err::File::create(path)?;
+        std::io::Write::write_all(&mut file, data)?;
+        if self._data().flush_to_disk {
+            file.sync_all()?;
+        }
+        drop(file);
+
+        svn_subr::io::set_file_read_only(path, false)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,255 @@
+//! `subversion/libsvn_fs_fs/recovery.c`
+//!
+//! Recovery brings a filesystem whose `current` file got lost, garbled or
+//! out of sync with the rev files (e.g. after a power loss in the middle
+//! of a commit) back into a consistent state.  The rev files are the
+//! authority: the youngest one that exists becomes the youngest revision.
+
+use std::collections::HashSet;
+
+use svn_subr::io::FileLock;
+use svn_types::RevisionNumber;
+use uuid::Uuid;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::SVN_FS_FS__MIN_PACK_LOCK_FORMAT;
+use super::SVN_FS_FS__MIN_PROTOREVS_DIR_FORMAT;
+use super::SVN_FS_FS__MIN_TXN_CURRENT_FORMAT;
+use crate::RecoverReport;
+use crate::backend::PATH_EXT_REV;
+use crate::backend::PATH_EXT_REV_LOCK;
+use crate::backend::PATH_MANIFEST;
+use crate::backend::check_cancel;
+use crate::id::IdPart;
+use crate::id::base36_to_u64;
+use crate::id::u64_to_base36;
+
+impl FsFsBackend {
+    /// Make sure that a `current` file exists and holds a revision
+    /// number, so that the filesystem can be opened for recovery.  The
+    /// actual youngest revision is determined by [`FsFsBackend::recover`].
+    ///
+    /// `svn_fs_fs__open_for_recovery`
+    pub(super) fn prepare_current_for_recovery(&self) -> Result<(), BackendError> {
+        match self.read_current() {
+            Ok(_) => Ok(()),
+            Err(BackendError::Io(_) | BackendError::CorruptFile { .. }) => self.write_current(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Recover the filesystem: find the youngest revision in the rev
+    /// files and record it in `current`, repair `txn-current` and clean
+    /// up proto-revs left behind by vanished transactions.  @a uuid is
+    /// the filesystem's UUID, which is kept while the instance ID gets
+    /// renewed.  Poll @a cancel while scanning.
+    ///
+    /// The write lock must not be held by anybody else; locks held by
+    /// crashed processes are released by the operating system, and lock
+    /// files that went missing are re-created.
+    ///
+    /// `svn_fs_fs__recover`
+    pub(crate) fn recover(
+        &mut self,
+        uuid: Uuid,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<RecoverReport, BackendError> {
+        let format = self._data().format;
+
+        // Recovery needs exclusive access; refuse to wait for a live
+        // committer that may be in the middle of changing `current`.
+        let _write_lock =
+            FileLock::try_lock(&self.path_lock(), true)?.ok_or(BackendError::ExclusiveAccess)?;
+        let _pack_lock = if format >= SVN_FS_FS__MIN_PACK_LOCK_FORMAT {
+            Some(
+                FileLock::try_lock(&self.path_pack_lock(), true)?
+                    .ok_or(BackendError::ExclusiveAccess)?,
+            )
+        } else {
+            None
+        };
+        let _txn_current_lock = if format >= SVN_FS_FS__MIN_TXN_CURRENT_FORMAT {
+            Some(
+                FileLock::try_lock(&self.path_txn_current_lock(), true)?
+                    .ok_or(BackendError::ExclusiveAccess)?,
+            )
+        } else {
+            None
+        };
+
+        self.recover_body(uuid, cancel)
+    }
+
+    /// The body of [`FsFsBackend::recover`].  The caller must hold all
+    /// locks.
+    ///
+    /// `recover_body`
+    fn recover_body(
+        &mut self,
+        uuid: Uuid,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<RecoverReport, BackendError> {
+        // Lose potentially corrupted data in temp caches held by other
+        // instances of this filesystem.
+        self.set_uuid(Some(uuid), None)?;
+
+        // The first step is to find the largest revision present.
+        let max_rev = self.recover_get_largest_revision(cancel)?;
+
+        // We only check whether `current` is ahead of the rev files; a
+        // `current` lagging behind simply gets bumped.
+        let youngest = self.read_current()?;
+        if youngest > max_rev {
+            return Err(BackendError::Corrupt(format!(
+                "Expected current rev to be <= {max_rev} but found {youngest}"
+            )));
+        }
+
+        // Before setting current, verify that there is a revprops file
+        // for the youngest revision.  (Issue #2992)
+        let revprops_exist = if self.is_packed_revprop(max_rev) {
+            self.path_revprops_pack_shard(max_rev)
+                .join(PATH_MANIFEST)
+                .is_file()
+        } else {
+            self.path_revprops(max_rev).is_file()
+        };
+        if !revprops_exist {
+            return Err(BackendError::Corrupt(format!(
+                "Revision {max_rev} has a revs file but no revprops file"
+            )));
+        }
+
+        // Prune younger-than-(newfound-youngest) revisions from the rep
+        // cache, if it exists.
+        if self._data().rep_sharing_allowed && self.exists_rep_cache() {
+            self.del_rep_reference(max_rev)?;
+        }
+
+        // Now store the discovered youngest revision.
+        self.write_current(max_rev)?;
+        self._data_mut().youngest_rev_cache = max_rev;
+
+        let mut report = RecoverReport {
+            youngest: max_rev,
+            ..Default::default()
+        };
+        self.recover_transactions(&mut report, cancel)?;
+
+        Ok(report)
+    }
+
+    /// Return the largest revision that has a rev file, found by probing
+    /// exponentially growing revision numbers and bisecting the range
+    /// between the last hit and the first miss.
+    ///
+    /// `recover_get_largest_revision`
+    fn recover_get_largest_revision(
+        &self,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<RevisionNumber, BackendError> {
+        // Every repository has r0.
+        if !self.path_rev_absolute(0).is_file() {
+            return Err(BackendError::Corrupt(
+                "Revision 0 has no revs file".to_string(),
+            ));
+        }
+
+        // Keep doubling right, until we find a revision that doesn't
+        // exist.
+        let mut left: RevisionNumber = 0;
+        let mut right: RevisionNumber = 1;
+        while self.path_rev_absolute(right).is_file() {
+            check_cancel(cancel)?;
+            left = right;
+            right <<= 1;
+        }
+
+        // We know that left exists and right doesn't.  Do a normal
+        // bsearch to find the last revision.
+        while left + 1 < right {
+            check_cancel(cancel)?;
+            let probe = left + (right - left) / 2;
+            if self.path_rev_absolute(probe).is_file() {
+                left = probe;
+            } else {
+                right = probe;
+            }
+        }
+
+        Ok(left)
+    }
+
+    /// Repair `txn-current` so that new transactions cannot collide with
+    /// existing ones, report transactions based on revisions that no
+    /// longer exist and remove proto-revs without a transaction.
+    fn recover_transactions(
+        &self,
+        report: &mut RecoverReport,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let format = self._data().format;
+
+        let txns = self.list_transactions()?;
+        let mut next_number = 0;
+        for name in &txns {
+            if let Ok(txn_id) = IdPart::parse_txn(name) {
+                next_number = next_number.max(txn_id.number + 1);
+                if txn_id.revision > report.youngest {
+                    report.orphaned_txns.push(name.clone());
+                }
+            }
+        }
+
+        // Proto-revs live in their own directory since format 3 and
+        // survive their transaction if a purge got interrupted.
+        if format >= SVN_FS_FS__MIN_PROTOREVS_DIR_FORMAT && self.path_txn_proto_revs().is_dir() {
+            let txns: HashSet<&str> = txns.iter().map(String::as_str).collect();
+            let mut orphans = Vec::new();
+            for entry in fs_err::read_dir(self.path_txn_proto_revs())? {
+                check_cancel(cancel)?;
+                let entry = entry?;
+                let file_name = entry.file_name();
+                let Some(file_name) = file_name.to_str() else {
+                    continue;
+                };
+                let Some(name) = file_name
+                    .strip_suffix(PATH_EXT_REV_LOCK)
+                    .or_else(|| file_name.strip_suffix(PATH_EXT_REV))
+                else {
+                    continue;
+                };
+                if let Ok(txn_id) = IdPart::parse_txn(name) {
+                    next_number = next_number.max(txn_id.number + 1);
+                }
+                if !txns.contains(name) {
+                    fs_err::remove_file(entry.path())?;
+                    orphans.push(name.to_string());
+                }
+            }
+            orphans.sort();
+            orphans.dedup();
+            report.orphaned_proto_revs = orphans;
+        }
+
+        if format >= SVN_FS_FS__MIN_TXN_CURRENT_FORMAT {
+            let path = self.path_txn_current();
+            let current = fs_err::read_to_string(&path)
+                .ok()
+                .and_then(|content| base36_to_u64(content.trim()));
+            if current.is_none_or(|number| number < next_number) {
+                let number = current.unwrap_or(0).max(next_number);
+                svn_subr::io::write_atomic(
+                    &path,
+                    format!("{}\n", u64_to_base36(number)).as_bytes(),
+                    Some(&self.path_uuid()),
+                    self._data().flush_to_disk,
+                )?;
+                report.txn_current_repaired = true;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,410 @@
+//! `subversion/libsvn_fs_fs/rep-cache.c`
+//!
+//! The rep-cache.db: a SQLite database mapping the SHA1 checksums of
+//! fulltexts to the representations storing them, so that identical
+//! contents get stored only once in the repository.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rusqlite::Connection;
+use rusqlite::ErrorCode;
+use rusqlite::MAIN_DB;
+use rusqlite::OpenFlags;
+use rusqlite::OptionalExtension;
+use rusqlite::TransactionBehavior;
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::SVN_FS_FS__MIN_REP_CACHE_SCHEMA_V2_FORMAT;
+use super::index::ItemType;
+use crate::Representation;
+use crate::id::IdPart;
+
+/// The file name of the rep cache database.
+///
+/// `REP_CACHE_DB_NAME`
+const REP_CACHE_DB_NAME: &str = "rep-cache.db";
+
+/// The lowest schema version an initialized rep cache has.
+///
+/// `REP_CACHE_SCHEMA_FORMAT`
+const REP_CACHE_SCHEMA_FORMAT: i32 = 1;
+
+/// How long to wait for other processes to release the database.
+///
+/// `BUSY_TIMEOUT`
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `STMT_CREATE_SCHEMA_V1`
+const STMT_CREATE_SCHEMA_V1: &str = "
+PRAGMA auto_vacuum = 1;
+CREATE TABLE rep_cache (
+  hash TEXT NOT NULL PRIMARY KEY,
+  revision INTEGER NOT NULL,
+  offset INTEGER NOT NULL,
+  size INTEGER NOT NULL,
+  expanded_size INTEGER NOT NULL
+  );
+PRAGMA user_version = 1;
+";
+
+/// `STMT_CREATE_SCHEMA_V2`
+const STMT_CREATE_SCHEMA_V2: &str = "
+CREATE TABLE rep_cache (
+  hash TEXT NOT NULL PRIMARY KEY,
+  revision INTEGER NOT NULL,
+  offset INTEGER NOT NULL,
+  size INTEGER NOT NULL,
+  expanded_size INTEGER NOT NULL
+  ) WITHOUT ROWID;
+PRAGMA user_version = 2;
+";
+
+/// `STMT_GET_REP`
+const STMT_GET_REP: &str = "
+SELECT revision, offset, size, expanded_size
+FROM rep_cache
+WHERE hash = ?1";
+
+/// `STMT_SET_REP`
+const STMT_SET_REP: &str = "
+INSERT OR FAIL INTO rep_cache (hash, revision, offset, size, expanded_size)
+VALUES (?1, ?2, ?3, ?4, ?5)";
+
+/// `STMT_GET_REPS_FOR_RANGE`
+const STMT_GET_REPS_FOR_RANGE: &str = "
+SELECT hash, revision, offset, size, expanded_size
+FROM rep_cache
+WHERE revision >= ?1 AND revision <= ?2";
+
+/// `STMT_GET_MAX_REV`
+const STMT_GET_MAX_REV: &str = "
+SELECT MAX(revision)
+FROM rep_cache";
+
+/// `STMT_DEL_REPS_YOUNGER_THAN_REV`
+const STMT_DEL_REPS_YOUNGER_THAN_REV: &str = "
+DELETE FROM rep_cache
+WHERE revision > ?1";
+
+/// Return the representation described by a row of the rep cache whose
+/// columns start with the revision at @a first.
+fn rep_from_row(
+    row: &rusqlite::Row<'_>,
+    first: usize,
+    sha1_digest: Checksum,
+) -> rusqlite::Result<Representation> {
+    Ok(Representation {
+        md5_digest: None,
+        sha1_digest: Some(sha1_digest),
+        revision: row.get(first)?,
+        item_index: row.get(first + 1)?,
+        size: row.get(first + 2)?,
+        expanded_size: row.get(first + 3)?,
+        txn_id: IdPart::UNUSED_TXN,
+        uniquifier: None,
+    })
+}
+
+impl FsFsBackend {
+    /// Return the path of the rep cache database.
+    ///
+    /// `path_rep_cache_db`
+    pub(crate) fn path_rep_cache_db(&self) -> PathBuf {
+        self.path.join(REP_CACHE_DB_NAME)
+    }
+
+    /// Return TRUE if the rep cache database exists.
+    ///
+    /// `svn_fs_fs__exists_rep_cache`
+    pub(crate) fn exists_rep_cache(&self) -> bool {
+        self.path_rep_cache_db().is_file()
+    }
+
+    /// Open the rep cache database, creating it and its schema if
+    /// necessary.
+    ///
+    /// `svn_fs_fs__open_rep_cache` / `open_rep_cache`
+    fn open_rep_cache(&self) -> Result<Connection, BackendError> {
+        let conn = Connection::open(self.path_rep_cache_db())?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < REP_CACHE_SCHEMA_FORMAT {
+            // Must be 0 -- an uninitialized (no schema) database.  Create
+            // the schema.
+            if self._data().format >= SVN_FS_FS__MIN_REP_CACHE_SCHEMA_V2_FORMAT {
+                conn.execute_batch(STMT_CREATE_SCHEMA_V2)?;
+            } else {
+                conn.execute_batch(STMT_CREATE_SCHEMA_V1)?;
+            }
+        }
+
+        Ok(conn)
+    }
+
+    /// Call @a body while holding a write lock on the rep cache database,
+    /// so that no other connection can add entries meanwhile.
+    ///
+    /// `svn_fs_fs__with_rep_cache_lock`
+    pub(crate) fn with_rep_cache_lock(
+        &self,
+        body: &mut dyn FnMut() -> Result<(), BackendError>,
+    ) -> Result<(), BackendError> {
+        let mut conn = self.open_rep_cache()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        body()?;
+        tx.rollback()?;
+
+        Ok(())
+    }
+
+    /// Return the representation for the fulltext with the SHA1 checksum
+    /// @a checksum from the rep cache, if there is one.
+    ///
+    /// `svn_fs_fs__get_rep_reference`
+    pub(crate) fn get_rep_reference(
+        &self,
+        checksum: &Checksum,
+    ) -> Result<Option<Representation>, BackendError> {
+        let conn = self.open_rep_cache()?;
+        let rep = conn
+            .query_row(STMT_GET_REP, [checksum.to_hex()], |row| {
+                rep_from_row(row, 0, checksum.clone())
+            })
+            .optional()?;
+
+        // Make sure the representation has been committed for real.
+        if let Some(rep) = &rep {
+            self.ensure_revision_exists(rep.revision).map_err(|_| {
+                BackendError::Corrupt(format!(
+                    "SHA1 checksum '{}' refers to the non-existent revision r{}",
+                    checksum.to_hex(),
+                    rep.revision
+                ))
+            })?;
+        }
+
+        Ok(rep)
+    }
+
+    /// Record the committed representations @a reps in the rep cache.
+    /// Checksums that are already known are left alone.
+    ///
+    /// `write_reps_to_cache` / `svn_fs_fs__set_rep_reference`
+    pub(crate) fn set_rep_references(&self, reps: &[Representation]) -> Result<(), BackendError> {
+        let mut conn = self.open_rep_cache()?;
+
+        // Use one SQLite transaction for all of them to speed things up.
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(STMT_SET_REP)?;
+            for rep in reps {
+                let Some(sha1) = &rep.sha1_digest else {
+                    return Err(BackendError::Corrupt(
+                        "Only SHA1 checksums can be used as keys in the rep_cache table"
+                            .to_string(),
+                    ));
+                };
+
+                match stmt.execute(rusqlite::params![
+                    sha1.to_hex(),
+                    rep.revision,
+                    rep.item_index,
+                    rep.size,
+                    rep.expanded_size
+                ]) {
+                    Ok(_) => {}
+                    // Constraint failed so the mapping for the checksum
+                    // exists already.  That's cool -- just do nothing.
+                    Err(rusqlite::Error::SqliteFailure(e, _))
+                        if e.code == ErrorCode::ConstraintViolation => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Call @a walker for every representation in the rep cache that has
+    /// been committed in revisions @a start to @a end.
+    ///
+    /// `svn_fs_fs__walk_rep_reference`
+    pub(crate) fn walk_rep_reference(
+        &self,
+        start: RevisionNumber,
+        end: RevisionNumber,
+        mut walker: impl FnMut(&Representation) -> Result<(), BackendError>,
+    ) -> Result<(), BackendError> {
+        let conn = self.open_rep_cache()?;
+
+        // Check global invariants.
+        if start == 0 {
+            let max: Option<RevisionNumber> =
+                conn.query_row(STMT_GET_MAX_REV, [], |row| row.get(0))?;
+            let youngest = self.read_current()?;
+            if let Some(max) = max
+                && max > youngest
+            {
+                return Err(BackendError::Corrupt(format!(
+                    "Youngest revision is r{youngest}, but rep-cache contains r{max}"
+                )));
+            }
+        }
+
+        let mut stmt = conn.prepare(STMT_GET_REPS_FOR_RANGE)?;
+        let mut rows = stmt.query([start, end])?;
+        while let Some(row) = rows.next()? {
+            let hex: String = row.get(0)?;
+            let sha1 = Checksum::parse_hex(ChecksumKind::Sha1, &hex)?.ok_or_else(|| {
+                BackendError::Corrupt(format!("Invalid SHA1 checksum '{hex}' in rep-cache"))
+            })?;
+            walker(&rep_from_row(row, 1, sha1)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Check that the rep cache entries for revisions @a start to @a end
+    /// point to actual representations.
+    ///
+    /// `verify_rep_cache`
+    pub(crate) fn verify_rep_cache(
+        &self,
+        start: RevisionNumber,
+        end: RevisionNumber,
+    ) -> Result<(), BackendError> {
+        // Not all repositories have a rep cache.
+        if !self.exists_rep_cache() {
+            return Ok(());
+        }
+
+        self.walk_rep_reference(start, end, |rep| self.check_rep(rep))
+    }
+
+    /// Check that the committed representation @a rep exists.
+    ///
+    /// `svn_fs_fs__check_rep`
+    pub(crate) fn check_rep(&self, rep: &Representation) -> Result<(), BackendError> {
+        if self._data().use_log_addressing {
+            // The item must be a representation according to the p2l index.
+            let mut rev_file = self.open_pack_or_rev_file(rep.revision)?;
+            let offset = self.item_offset(&mut rev_file, rep.revision, rep.item_index)?;
+            let entry = self.p2l_entry_lookup(&mut rev_file, offset)?;
+            return match entry.map(|entry| entry.item_type) {
+                Some(
+                    ItemType::FileRep | ItemType::DirRep | ItemType::FileProps | ItemType::DirProps,
+                ) => Ok(()),
+                _ => Err(BackendError::Corrupt(format!(
+                    "No representation found at offset {offset} for item {} in revision {}",
+                    rep.item_index, rep.revision
+                ))),
+            };
+        }
+
+        self.read_rep_header(rep.revision, rep.item_index)
+            .map(|_| ())
+            .map_err(|e| {
+                BackendError::Corrupt(format!(
+                    "No representation found for item {} in revision {}: {e}",
+                    rep.item_index, rep.revision
+                ))
+            })
+    }
+
+    /// Delete from the rep cache all representations of revisions younger
+    /// than @a youngest.
+    ///
+    /// `svn_fs_fs__del_rep_reference`
+    pub(crate) fn del_rep_reference(&self, youngest: RevisionNumber) -> Result<(), BackendError> {
+        let conn = self.open_rep_cache()?;
+        conn.execute(STMT_DEL_REPS_YOUNGER_THAN_REV, [youngest])?;
+
+        Ok(())
+    }
+
+    /// Replace the rep cache with a copy of the one of @a src.  SQLite's
+    /// online backup gives a consistent snapshot even while commits to
+    /// @a src keep adding entries.
+    ///
+    /// `svn_sqlite__hotcopy`
+    pub(crate) fn hotcopy_rep_cache(&self, src: &FsFsBackend) -> Result<(), BackendError> {
+        let conn =
+            Connection::open_with_flags(src.path_rep_cache_db(), OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.backup(MAIN_DB, self.path_rep_cache_db(), None)?;
+
+        Ok(())
+    }
+
+    /// Add the representations committed in revisions @a start to @a end
+    /// to the rep cache, (re-)creating it from the rev files.  Either
+    /// revision may be `SVN_INVALID_REVNUM` for "from r0" and "up to the
+    /// youngest revision", respectively.
+    ///
+    /// `svn_fs_fs__build_rep_cache`
+    pub(crate) fn build_rep_cache(
+        &self,
+        start: RevisionNumber,
+        end: RevisionNumber,
+    ) -> Result<(), BackendError> {
+        if !self._data().rep_sharing_allowed {
+            return Err(BackendError::RepSharingNotAllowed);
+        }
+
+        let start = if start == SVN_INVALID_REVNUM {
+            0
+        } else {
+            start
+        };
+        let end = if end == SVN_INVALID_REVNUM {
+            self.read_current()?
+        } else {
+            self.ensure_revision_exists(end)?;
+            end
+        };
+        if start > end {
+            return Err(BackendError::Corrupt(format!(
+                "Invalid revision range [{start}, {end}]"
+            )));
+        }
+
+        for rev in start..=end {
+            // Every representation added in REV belongs to a node-revision
+            // that got changed in REV.
+            let mut reps = Vec::new();
+            for change in self.get_changes(rev)? {
+                let Some(id) = change.node_rev_id.filter(|id| id.rev() == rev) else {
+                    continue;
+                };
+                let noderev = self.get_node_revision(&id)?;
+
+                if let Some(rep) = noderev.data_rep
+                    && noderev.kind == NodeKind::File
+                    && rep.revision == rev
+                    && rep.sha1_digest.is_some()
+                {
+                    reps.push(rep);
+                }
+                if let Some(rep) = noderev.prop_rep
+                    && rep.revision == rev
+                    && rep.sha1_digest.is_some()
+                {
+                    reps.push(rep);
+                }
+            }
+
+            self.set_rep_references(&reps)?;
+        }
+
+        Ok(())
+    }
+}
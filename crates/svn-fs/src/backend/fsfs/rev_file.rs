@@ -0,0 +1,372 @@
+//! `subversion/libsvn_fs_fs/rev_file.c`
+
+use std::collections::VecDeque;
+
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::low_level;
+use super::low_level::Footer;
+use crate::backend::PATH_PACKED;
+use crate::id::IdPart;
+
+/// The number of bytes read at once when looking for the end of an item.
+const READ_CHUNK_SIZE: usize = 0x1000;
+
+/// Reads spanning more blocks than this bypass the blocks kept in
+/// [`RevisionFile`] and go to the file directly.
+const MAX_BLOCKS_PER_READ: u64 = 16;
+
+/// The number of blocks a [`RevisionFile`] keeps.  Enough for the largest
+/// read going through them, so walking a large pack file doesn't pile up
+/// its contents in memory.
+const MAX_KEPT_BLOCKS: usize = MAX_BLOCKS_PER_READ as usize;
+
+/// All files and associated properties for a revision or pack file.
+///
+/// `svn_fs_fs__revision_file_t`
+#[derive(Debug)]
+pub(crate) struct RevisionFile {
+    /// first (potentially only) revision in the rev / pack file.
+    /// SVN_INVALID_REVNUM for txn proto-rev files.
+    pub start_revision: RevisionNumber,
+
+    /// the revision was packed when the first file / stream got opened
+    pub is_packed: bool,
+
+    /// rev / pack file
+    file: fs_err::File,
+
+    /// size of the whole file
+    file_size: u64,
+
+    /// index locations, read on demand by [`RevisionFile::footer`]
+    footer: Option<Footer>,
+
+    /// if set, read the file in aligned blocks of this many bytes and keep
+    /// the most recent ones in @a blocks
+    block_size: Option<u64>,
+
+    /// the blocks read last with their block numbers, oldest first, at
+    /// most [`MAX_KEPT_BLOCKS`] of them
+    blocks: VecDeque<(u64, Vec<u8>)>,
+}
+
+impl RevisionFile {
+    /// The size of the rev / pack file in bytes.
+    pub(crate) fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Read up to @a len bytes starting at @a offset.  Fewer bytes are
+    /// returned only if the end of the file has been reached.
+    ///
+    /// In block read mode, all blocks covering the range are read, in a
+    /// single batch, and the most recent ones kept for later reads.
+    pub(crate) fn read_at(&mut self, offset: u64, len: usize) -> Result<Vec<u8>, BackendError> {
+        let len = len.min(self.file_size.saturating_sub(offset) as usize);
+        let block_size = match self.block_size {
+            Some(block_size) if len > 0 && len as u64 <= MAX_BLOCKS_PER_READ * block_size => {
+                block_size
+            }
+            _ => return Ok(read_blocks(self.file.file(), &[(offset, len)])?.remove(0)),
+        };
+
+        let end = offset + len as u64;
+        let block_numbers = offset / block_size..end.div_ceil(block_size);
+        let missing: Vec<(u64, usize)> = block_numbers
+            .clone()
+            .filter(|number| self.block(*number).is_none())
+            .map(|number| {
+                let start = number * block_size;
+                (start, block_size.min(self.file_size - start) as usize)
+            })
+            .collect();
+        let read = read_blocks(self.file.file(), &missing)?;
+        for (&(start, _), block) in missing.iter().zip(read) {
+            self.blocks.push_back((start / block_size, block));
+        }
+
+        let mut buf = Vec::with_capacity(len);
+        for number in block_numbers {
+            let start = number * block_size;
+            let block = self.block(number).expect("the block has been read");
+            let from = offset.max(start) - start;
+            let to = end.min(start + block.len() as u64) - start;
+            buf.extend_from_slice(&block[from as usize..to as usize]);
+        }
+
+        // Drop the oldest blocks.
+        let excess = self.blocks.len().saturating_sub(MAX_KEPT_BLOCKS);
+        self.blocks.drain(..excess);
+
+        Ok(buf)
+    }
+
+    /// Return block number @a number if it is kept.
+    fn block(&self, number: u64) -> Option<&[u8]> {
+        self.blocks
+            .iter()
+            .find(|(kept, _)| *kept == number)
+            .map(|(_, block)| block.as_slice())
+    }
+
+    /// Read the item starting at @a offset whose end is determined by
+    /// @a item_len: called with the data read so far, it returns the
+    /// length of the item once the data covers all of it.
+    pub(crate) fn read_item<F>(&mut self, offset: u64, item_len: F) -> Result<Vec<u8>, BackendError>
+    where
+        F: Fn(&[u8]) -> Option<usize>,
+    {
+        let mut len = READ_CHUNK_SIZE;
+        loop {
+            let mut buf = self.read_at(offset, len)?;
+            if let Some(item_len) = item_len(&buf) {
+                buf.truncate(item_len);
+                return Ok(buf);
+            }
+            if buf.len() < len {
+                return Err(BackendError::Corrupt(format!(
+                    "Unexpected end of file while reading item at offset {offset}"
+                )));
+            }
+            len *= 2;
+        }
+    }
+
+    /// Return the footer of this format 7+ rev / pack file.
+    ///
+    /// `svn_fs_fs__auto_read_footer`
+    pub(crate) fn footer(&mut self) -> Result<&Footer, BackendError> {
+        if self.footer.is_none() {
+            // Determine file size.
+            let file_size = self.file_size;
+
+            // Read last byte (containing the length of the footer).
+            let last = self.read_at(file_size.saturating_sub(1), 1)?;
+            let footer_length = *last.first().ok_or_else(|| {
+                BackendError::Corrupt(format!("Empty revision file for r{}", self.start_revision))
+            })? as u64;
+            if footer_length + 1 > file_size {
+                return Err(BackendError::Corrupt(format!(
+                    "Invalid revision footer length in r{}",
+                    self.start_revision
+                )));
+            }
+
+            // Read footer.
+            let footer = self.read_at(file_size - 1 - footer_length, footer_length as usize)?;
+
+            // Extract index locations.
+            self.footer = Some(low_level::parse_footer(&footer, self.start_revision)?);
+        }
+
+        Ok(self.footer.as_ref().expect("footer has just been read"))
+    }
+
+    /// Return the l2p index of this format 7+ rev / pack file.
+    pub(crate) fn l2p_index(&mut self) -> Result<Vec<u8>, BackendError> {
+        let footer = self.footer()?;
+        let (l2p_offset, p2l_offset) = (footer.l2p_offset, footer.p2l_offset);
+        self.read_at(l2p_offset, (p2l_offset - l2p_offset) as usize)
+    }
+
+    /// Return the p2l index of this format 7+ rev / pack file, followed by
+    /// the footer.
+    pub(crate) fn p2l_index(&mut self) -> Result<Vec<u8>, BackendError> {
+        let p2l_offset = self.footer()?.p2l_offset;
+        self.read_at(p2l_offset, (self.file_size - p2l_offset) as usize)
+    }
+}
+
+/// Read the @a len bytes at @a offset of @a file for each (offset, len)
+/// pair in @a blocks.  With io-uring, they are read in batches; otherwise,
+/// or if it is not available, one by one.
+fn read_blocks(file: &std::fs::File, blocks: &[(u64, usize)]) -> std::io::Result<Vec<Vec<u8>>> {
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    if let Some(result) = super::uring::read_blocks(file, blocks) {
+        return result;
+    }
+
+    blocks
+        .iter()
+        .map(|&(offset, len)| {
+            let mut buf = vec![0; len];
+            read_exact_at(file, &mut buf, offset)?;
+            Ok(buf)
+        })
+        .collect()
+}
+
+/// Fill @a buf with the data at @a offset of @a file.
+#[cfg(unix)]
+fn read_exact_at(file: &std::fs::File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+/// Fill @a buf with the data at @a offset of @a file.
+#[cfg(not(unix))]
+fn read_exact_at(mut file: &std::fs::File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    use std::io::Read;
+    use std::io::Seek;
+
+    file.seek(std::io::SeekFrom::Start(offset))?;
+    file.read_exact(buf)
+}
+
+impl FsFsBackend {
+    /// Open the correct revision file for REV.  If the filesystem FS has
+    /// been packed, the returned file will be the pack file containing REV.
+    ///
+    /// `svn_fs_fs__open_pack_or_rev_file`
+    pub(crate) fn open_pack_or_rev_file(
+        &self,
+        rev: RevisionNumber,
+    ) -> Result<RevisionFile, BackendError> {
+        self.ensure_revision_exists(rev)?;
+
+        let mut min_unpacked_rev = self._data().min_unpacked_rev;
+        let mut retry = true;
+        loop {
+            let is_packed = rev < min_unpacked_rev;
+            let path = if is_packed {
+                self.path_rev_pack_shard(rev).join(PATH_PACKED)
+            } else {
+                self.path_rev(rev)
+            };
+
+            match fs_err::File::open(&path) {
+                Ok(file) => {
+                    let file_size = file.metadata()?.len();
+                    let start_revision = if is_packed {
+                        rev - rev % self._data().max_files_per_dir as RevisionNumber
+                    } else {
+                        rev
+                    };
+
+                    return Ok(RevisionFile {
+                        start_revision,
+                        is_packed,
+                        file,
+                        file_size,
+                        footer: None,
+                        block_size: self
+                            ._data()
+                            .use_block_read
+                            .then_some(self._data().block_size as u64),
+                        blocks: VecDeque::new(),
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound && retry => {
+                    // We failed for the first time. Refresh cache & retry:
+                    // the revision might have been packed in the meantime.
+                    retry = false;
+                    if self._data().format < super::SVN_FS_FS__MIN_PACKED_FORMAT {
+                        return Err(BackendError::NoSuchRevision(rev));
+                    }
+                    min_unpacked_rev = self.read_min_unpacked_rev()?;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(BackendError::NoSuchRevision(rev));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Open the proto-rev file of transaction TXN_ID for reading the
+    /// representations written to it so far.
+    ///
+    /// `open_and_seek_transaction`
+    pub(crate) fn open_proto_rev_file(
+        &self,
+        txn_id: &IdPart,
+    ) -> Result<RevisionFile, BackendError> {
+        let file = fs_err::File::open(self.path_txn_proto_rev(txn_id))?;
+        let file_size = file.metadata()?.len();
+
+        Ok(RevisionFile {
+            start_revision: SVN_INVALID_REVNUM,
+            is_packed: false,
+            file,
+            file_size,
+            footer: None,
+            // The proto-rev file is still growing.
+            block_size: None,
+            blocks: VecDeque::new(),
+        })
+    }
+
+    /// Return an error if REV does not exist in FS.
+    ///
+    /// `svn_fs_fs__ensure_revision_exists`
+    pub(crate) fn ensure_revision_exists(&self, rev: RevisionNumber) -> Result<(), BackendError> {
+        if rev < 0 {
+            return Err(BackendError::InvalidRevision(rev));
+        }
+
+        // Did the revision exist the last time we checked the current
+        // file?
+        if rev <= self._data().youngest_rev_cache {
+            return Ok(());
+        }
+
+        // Check again.
+        if rev <= self.read_current()? {
+            return Ok(());
+        }
+
+        Err(BackendError::NoSuchRevision(rev))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rev");
+        let data: Vec<u8> = (0..10000u32).map(|i| (i % 251) as u8).collect();
+        fs_err::write(&path, &data).unwrap();
+
+        // Many blocks at once, including a short one at the end of the file.
+        let file = fs_err::File::open(&path).unwrap();
+        let blocks: Vec<(u64, usize)> = (0..100).map(|i| (i * 100, 100)).collect();
+        let read = read_blocks(file.file(), &blocks).unwrap();
+        assert!(read.iter().zip(data.chunks(100)).all(|(a, b)| a == b));
+        assert!(read_blocks(file.file(), &[(9990, 20)]).is_err());
+
+        let mut rev_file = RevisionFile {
+            start_revision: 0,
+            is_packed: false,
+            file,
+            file_size: data.len() as u64,
+            footer: None,
+            block_size: Some(0x1000),
+            blocks: VecDeque::new(),
+        };
+        assert_eq!(rev_file.read_at(4000, 200).unwrap(), &data[4000..4200]);
+        assert_eq!(rev_file.blocks.len(), 2);
+        assert_eq!(rev_file.read_at(9000, 2000).unwrap(), &data[9000..]);
+        assert_eq!(rev_file.blocks.len(), 3);
+        assert_eq!(rev_file.read_at(0, 10000).unwrap(), data);
+        assert_eq!(rev_file.read_at(10000, 1).unwrap(), b"");
+
+        // Walking the whole file keeps only the most recent blocks.
+        rev_file.block_size = Some(100);
+        rev_file.blocks.clear();
+        for offset in (0..10000).step_by(50) {
+            assert_eq!(
+                rev_file.read_at(offset, 100).unwrap(),
+                &data[offset as usize..(offset as usize + 100).min(10000)]
+            );
+            assert!(rev_file.blocks.len() <= MAX_KEPT_BLOCKS);
+        }
+        let kept: Vec<u64> = rev_file.blocks.iter().map(|(number, _)| *number).collect();
+        assert_eq!(kept, (84..100).collect::<Vec<_>>());
+    }
+}
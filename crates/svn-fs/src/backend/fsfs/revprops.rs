@@ -0,0 +1,688 @@
+//! `subversion/libsvn_fs_fs/revprops.c`
+//!
+//! Revprops of non-packed shards are stored one file per revision.  Packed
+//! shards combine the revprops of consecutive revisions in (optionally
+//! compressed) pack files, which are listed per revision in the shard's
+//! manifest.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use svn_subr::compress;
+use svn_subr::hash::HASH_TERMINATOR;
+use svn_subr::io::FileLock;
+use svn_types::RevisionNumber;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT;
+use super::data::PairCacheKey;
+use crate::UpgradeNotifyAction;
+use crate::backend::PATH_MANIFEST;
+use crate::backend::check_cancel;
+
+/// The maximum number of bytes a decimal 64 bit number takes up, including
+/// the terminator.  Used to estimate the size of a revprop pack file.
+///
+/// `SVN_INT64_BUFFER_SIZE`
+const SVN_INT64_BUFFER_SIZE: i64 = 21;
+
+/// The revprops of consecutive revisions as stored in a pack file.
+///
+/// `packed_revprops_t`
+#[derive(Debug)]
+struct PackedRevprops {
+    /// first revision in the pack
+    start_revision: RevisionNumber,
+
+    /// the serialized revprops of each revision in the pack
+    revprops: Vec<Vec<u8>>,
+}
+
+impl PackedRevprops {
+    /// Parse the revprop pack file at @a path.
+    ///
+    /// `parse_packed_revprops`
+    fn read(path: &Path) -> Result<Self, BackendError> {
+        let content = fs_err::read(path)?;
+        let corrupt = |message: &str| BackendError::CorruptFile {
+            path: path.to_string_lossy().to_string(),
+            message: message.to_string(),
+        };
+
+        let data = compress::decompress_zlib(&content, usize::MAX)?;
+
+        // The header lists the first revision, the number of revisions
+        // and their sizes and is terminated by an empty line.
+        let header_end = data
+            .windows(2)
+            .position(|w| w == b"\n\n")
+            .ok_or_else(|| corrupt("Header end not found"))?;
+        let header = std::str::from_utf8(&data[..header_end])
+            .map_err(|_| corrupt("Header is not valid UTF-8"))?;
+        let mut lines = header.lines();
+        let mut next_number = |what: &str| -> Result<i64, BackendError> {
+            lines
+                .next()
+                .and_then(|line| line.parse().ok())
+                .ok_or_else(|| corrupt(&format!("Invalid {what} in revprop pack header")))
+        };
+
+        let start_revision = next_number("first revision")?;
+        let count = next_number("revision count")?;
+        let mut revprops = Vec::new();
+        let mut offset = header_end + 2;
+        for _ in 0..count {
+            let size = next_number("revprop size")? as usize;
+            let props = data
+                .get(offset..offset + size)
+                .ok_or_else(|| corrupt("Packed revprop size exceeds pack file size"))?;
+            revprops.push(props.to_vec());
+            offset += size;
+        }
+
+        Ok(Self {
+            start_revision,
+            revprops,
+        })
+    }
+
+    /// Return the pack file contents, compressed at @a compression_level.
+    ///
+    /// `copy_revprops` / `repack_revprops`
+    fn serialize(&self, compression_level: i32) -> Vec<u8> {
+        let mut data = format!("{}\n{}\n", self.start_revision, self.revprops.len());
+        for props in &self.revprops {
+            data.push_str(&format!("{}\n", props.len()));
+        }
+        data.push('\n');
+
+        let mut data = data.into_bytes();
+        for props in &self.revprops {
+            data.extend_from_slice(props);
+        }
+
+        compress::compress_zlib(&data, compression_level)
+    }
+
+    /// The size of the uncompressed pack file as estimated when packing.
+    fn total_size(&self) -> i64 {
+        self.revprops
+            .iter()
+            .map(|props| SVN_INT64_BUFFER_SIZE + props.len() as i64)
+            .sum::<i64>()
+            + 2 * SVN_INT64_BUFFER_SIZE
+    }
+
+    /// Return the index of revision @a rev within the pack.
+    fn index_of(&self, rev: RevisionNumber) -> Result<usize, BackendError> {
+        usize::try_from(rev - self.start_revision)
+            .ok()
+            .filter(|&i| i < self.revprops.len())
+            .ok_or_else(|| {
+                BackendError::Corrupt(format!(
+                    "Revision {rev} not found in revprop pack file starting at r{}",
+                    self.start_revision
+                ))
+            })
+    }
+}
+
+impl FsFsBackend {
+    /// Return TRUE, if for REV in FS the revprops are stored in a pack
+    /// file.
+    ///
+    /// `svn_fs_fs__packed_revprop_available`
+    pub(crate) fn is_packed_revprop(&self, rev: RevisionNumber) -> bool {
+        let ffd = self._data();
+
+        // rev 0 will not be found in the packed revprops
+        rev < ffd.min_unpacked_rev && rev != 0 && ffd.format >= SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT
+    }
+
+    /// The zlib compression level to use for revprop pack files.
+    fn revprop_compression_level(&self) -> i32 {
+        if self._data().compress_packed_revprops {
+            compress::COMPRESSION_LEVEL_DEFAULT
+        } else {
+            compress::COMPRESSION_LEVEL_NONE
+        }
+    }
+
+    /// Read the revprops for revision REV in FS and return them.
+    ///
+    /// `svn_fs_fs__get_revision_proplist`
+    pub(crate) fn get_revision_proplist(
+        &self,
+        rev: RevisionNumber,
+    ) -> Result<HashMap<String, String>, BackendError> {
+        // should they be available at all?
+        self.ensure_revision_exists(rev)?;
+
+        // Try cache lookup first.  The generation is odd while a revprop
+        // change is in progress; don't trust the cache then.
+        let cache = &self._data().cache.revprop_cache;
+        let key = match cache {
+            Some(_) => {
+                let generation = self.read_revprop_generation()?;
+                (generation % 2 == 0).then_some(PairCacheKey {
+                    revision: rev,
+                    second: generation as u64,
+                })
+            }
+            None => None,
+        };
+        if let (Some(cache), Some(key)) = (cache, &key)
+            && let Some(proplist) = cache.get(key)?
+        {
+            return Ok(proplist);
+        }
+
+        let proplist = self.read_revision_proplist(rev)?;
+        if let (Some(cache), Some(key)) = (cache, key) {
+            cache.set(key, proplist.clone())?;
+        }
+
+        Ok(proplist)
+    }
+
+    /// Read the revprops for revision REV in FS from disk.
+    ///
+    /// `svn_fs_fs__get_revision_proplist`
+    fn read_revision_proplist(
+        &self,
+        rev: RevisionNumber,
+    ) -> Result<HashMap<String, String>, BackendError> {
+        // if REV had not been packed when we began, try reading it from
+        // the non-packed shard.  If that fails, we will fall through to
+        // packed shard reads.
+        if !self.is_packed_revprop(rev) {
+            match self.read_non_packed_revprop(rev) {
+                Ok(proplist) => return Ok(proplist),
+                Err(BackendError::Io(e))
+                    if e.kind() == std::io::ErrorKind::NotFound
+                        && rev != 0
+                        && self._data().format >= SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        // the revprop has been packed, possibly by someone else since we
+        // last looked.
+        self.read_packed_revprop(rev)
+    }
+
+    /// Read the revprops for revision REV in FS from its non-packed file.
+    ///
+    /// `read_non_packed_revprop`
+    pub(crate) fn read_non_packed_revprop(
+        &self,
+        rev: RevisionNumber,
+    ) -> Result<HashMap<String, String>, BackendError> {
+        let content = fs_err::read(self.path_revprops(rev))?;
+        let (proplist, _) = svn_subr::hash::read_hash(&content, HASH_TERMINATOR)?;
+
+        Ok(proplist)
+    }
+
+    /// Read the manifest of the packed revprop shard containing REV and
+    /// return the pack file names it lists together with the first
+    /// revision it covers.
+    ///
+    /// `read_manifest` / `get_revprop_packname`
+    fn read_revprop_manifest(
+        &self,
+        rev: RevisionNumber,
+    ) -> Result<(Vec<String>, RevisionNumber), BackendError> {
+        let max_files_per_dir = self._data().max_files_per_dir as RevisionNumber;
+
+        // revision 0 is never packed
+        let mut manifest_start = rev - rev % max_files_per_dir;
+        if manifest_start == 0 {
+            manifest_start += 1;
+        }
+
+        let path = self.path_revprops_pack_shard(rev).join(PATH_MANIFEST);
+        let manifest = fs_err::read_to_string(path)?
+            .lines()
+            .map(str::to_string)
+            .collect();
+
+        Ok((manifest, manifest_start))
+    }
+
+    /// Return the name of the pack file containing the revprops of REV
+    /// as listed in @a manifest, which starts at @a manifest_start.
+    fn revprop_packname(
+        manifest: &[String],
+        manifest_start: RevisionNumber,
+        rev: RevisionNumber,
+    ) -> Result<&str, BackendError> {
+        usize::try_from(rev - manifest_start)
+            .ok()
+            .and_then(|i| manifest.get(i))
+            .map(String::as_str)
+            .ok_or_else(|| {
+                BackendError::Corrupt(format!("Packed revprop manifest for r{rev} too small"))
+            })
+    }
+
+    /// Read the revprops for revision REV in FS from its pack file.
+    ///
+    /// `read_pack_revprop`
+    fn read_packed_revprop(
+        &self,
+        rev: RevisionNumber,
+    ) -> Result<HashMap<String, String>, BackendError> {
+        let (manifest, manifest_start) = self.read_revprop_manifest(rev)?;
+        let packname = Self::revprop_packname(&manifest, manifest_start, rev)?;
+
+        let pack = PackedRevprops::read(&self.path_revprops_pack_shard(rev).join(packname))?;
+        let props = &pack.revprops[pack.index_of(rev)?];
+        let (proplist, _) = svn_subr::hash::read_hash(props, HASH_TERMINATOR)?;
+
+        Ok(proplist)
+    }
+
+    /// Set the revision property list of revision REV in filesystem FS to
+    /// PROPLIST.  The caller must hold the write lock, except while
+    /// creating FS.
+    ///
+    /// `svn_fs_fs__set_revision_proplist`
+    pub(crate) fn set_revision_proplist(
+        &self,
+        rev: RevisionNumber,
+        proplist: &HashMap<String, String>,
+    ) -> Result<(), BackendError> {
+        // Holding the write lock, REV can't get packed meanwhile, but it
+        // may have been since we last read min-unpacked-rev.
+        let is_packed = self.is_packed_revprop(rev)
+            || (rev != 0
+                && self._data().format >= SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT
+                && rev < self.read_min_unpacked_rev()?);
+
+        let generation = self.begin_revprop_change()?;
+
+        if is_packed {
+            self.write_packed_revprop(rev, proplist)?;
+        } else {
+            self.write_non_packed_revprop(rev, proplist)?;
+        }
+
+        let generation = self.end_revprop_change(generation)?;
+
+        // Keep the cache in sync for all instances sharing it.
+        if let Some(cache) = &self._data().cache.revprop_cache {
+            let key = PairCacheKey {
+                revision: rev,
+                second: generation as u64,
+            };
+            cache.set(key, proplist.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Change, add, or delete the property @a name of revision @a rev to
+    /// @a value, or delete it if that is `None`.
+    ///
+    /// If @a old_value is given, the property must have that value, with
+    /// `None` meaning "not present", or nothing gets changed and
+    /// [`BackendError::PropBaseValueMismatch`] is returned.
+    ///
+    /// `svn_fs_fs__change_rev_prop`
+    pub(crate) fn change_rev_prop(
+        &self,
+        rev: RevisionNumber,
+        name: &str,
+        old_value: Option<Option<&str>>,
+        value: Option<&str>,
+    ) -> Result<(), BackendError> {
+        self.ensure_revision_exists(rev)?;
+        let _write_lock = FileLock::lock(&self.path_lock(), true)?;
+
+        // Other processes may have changed the revprops; read them from
+        // disk.
+        let mut proplist = self.read_revision_proplist(rev)?;
+        if let Some(old_value) = old_value
+            && proplist.get(name).map(String::as_str) != old_value
+        {
+            return Err(BackendError::PropBaseValueMismatch(name.to_string()));
+        }
+
+        match value {
+            Some(value) => proplist.insert(name.to_string(), value.to_string()),
+            None => proplist.remove(name),
+        };
+
+        self.set_revision_proplist(rev, &proplist)
+    }
+
+    /// Return the current revprop generation of FS, 0 if it has none.
+    ///
+    /// `read_revprop_generation_file`
+    fn read_revprop_generation(&self) -> Result<i64, BackendError> {
+        if self._data().format < SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+            return Ok(0);
+        }
+
+        let content = match fs_err::read_to_string(self.path_revprop_generation()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        content.trim_end().parse().map_err(|_| {
+            BackendError::Corrupt(format!(
+                "Invalid revprop generation '{}'",
+                content.trim_end()
+            ))
+        })
+    }
+
+    /// Write @a generation as the revprop generation of FS.
+    ///
+    /// `write_revprop_generation_file`
+    fn write_revprop_generation(&self, generation: i64) -> Result<(), BackendError> {
+        svn_subr::io::write_atomic(
+            &self.path_revprop_generation(),
+            format!("{generation}\n").as_bytes(),
+            None,
+            self._data().flush_to_disk,
+        )?;
+
+        Ok(())
+    }
+
+    /// Reset the revprop generation of FS to 0, e.g. for a new or
+    /// hotcopied filesystem.
+    ///
+    /// `svn_fs_fs__reset_revprop_generation_file`
+    pub(crate) fn reset_revprop_generation_file(&self) -> Result<(), BackendError> {
+        if self._data().format >= SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+            self.write_revprop_generation(0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Mark the start of a revprop change by bumping the revprop
+    /// generation to an odd number, which makes readers bypass the cache.
+    /// Return the new generation.
+    ///
+    /// `begin_revprop_change`
+    fn begin_revprop_change(&self) -> Result<i64, BackendError> {
+        if self._data().format < SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+            return Ok(0);
+        }
+
+        // A previous change may have been interrupted; skip its number.
+        let generation = self.read_revprop_generation()?;
+        let generation = generation + if generation % 2 == 0 { 1 } else { 2 };
+        self.write_revprop_generation(generation)?;
+
+        Ok(generation)
+    }
+
+    /// Mark the end of the revprop change started with @a generation by
+    /// bumping the generation to the next even number, which invalidates
+    /// all cached revprops.  Return the new generation.
+    ///
+    /// `end_revprop_change`
+    fn end_revprop_change(&self, generation: i64) -> Result<i64, BackendError> {
+        if self._data().format < SVN_FS_FS__MIN_PACKED_REVPROP_FORMAT {
+            return Ok(0);
+        }
+
+        self.write_revprop_generation(generation + 1)?;
+
+        Ok(generation + 1)
+    }
+
+    /// Write PROPLIST to the non-packed revprop file of revision REV,
+    /// replacing the old one atomically.
+    ///
+    /// `write_non_packed_revprop` / `switch_to_new_revprop`
+    fn write_non_packed_revprop(
+        &self,
+        rev: RevisionNumber,
+        proplist: &HashMap<String, String>,
+    ) -> Result<(), BackendError> {
+        let content = svn_subr::hash::write_hash(proplist, HASH_TERMINATOR);
+
+        // Revprop files get the same permissions as the rev file.
+        svn_subr::io::write_atomic(
+            &self.path_revprops(rev),
+            content.as_bytes(),
+            Some(&self.path_rev_absolute(rev)),
+            self._data().flush_to_disk,
+        )?;
+
+        Ok(())
+    }
+
+    /// Replace the revprops of REV within its pack file by PROPLIST.  The
+    /// pack gets written to a new file, split into up to three files if it
+    /// grew too large, and the manifest is switched over to them.
+    ///
+    /// `write_packed_revprop`
+    fn write_packed_revprop(
+        &self,
+        rev: RevisionNumber,
+        proplist: &HashMap<String, String>,
+    ) -> Result<(), BackendError> {
+        let pack_dir = self.path_revprops_pack_shard(rev);
+        let (mut manifest, manifest_start) = self.read_revprop_manifest(rev)?;
+        let old_packname = Self::revprop_packname(&manifest, manifest_start, rev)?.to_string();
+
+        let mut pack = PackedRevprops::read(&pack_dir.join(&old_packname))?;
+        let index = pack.index_of(rev)?;
+        pack.revprops[index] = svn_subr::hash::write_hash(proplist, HASH_TERMINATOR).into_bytes();
+
+        // Pack file names are "<first rev>.<tag>"; new files get a new tag
+        // so that readers never see partially written ones.
+        let tag: u64 = old_packname
+            .split_once('.')
+            .and_then(|(_, tag)| tag.parse().ok())
+            .ok_or_else(|| {
+                BackendError::Corrupt(format!("Invalid revprop pack file name '{old_packname}'"))
+            })?;
+
+        // If the pack grew too large, move the revprops of REV into a file
+        // of their own.
+        let packs = if pack.revprops.len() > 1 && pack.total_size() > self._data().revprop_pack_size
+        {
+            let mut changed = pack.revprops.split_off(index);
+            let right = changed.split_off(1);
+
+            let mut packs = Vec::new();
+            if !pack.revprops.is_empty() {
+                packs.push(pack);
+            }
+            packs.push(PackedRevprops {
+                start_revision: rev,
+                revprops: changed,
+            });
+            if !right.is_empty() {
+                packs.push(PackedRevprops {
+                    start_revision: rev + 1,
+                    revprops: right,
+                });
+            }
+            packs
+        } else {
+            vec![pack]
+        };
+
+        let compression_level = self.revprop_compression_level();
+        for pack in &packs {
+            let packname = format!("{}.{}", pack.start_revision, tag + 1);
+            fs_err::write(pack_dir.join(&packname), pack.serialize(compression_level))?;
+
+            let first = (pack.start_revision - manifest_start) as usize;
+            for entry in &mut manifest[first..first + pack.revprops.len()] {
+                entry.clone_from(&packname);
+            }
+        }
+
+        // Switch to the new pack files, then remove the old one.
+        let mut content = manifest.join("\n");
+        content.push('\n');
+        svn_subr::io::write_atomic(
+            &pack_dir.join(PATH_MANIFEST),
+            content.as_bytes(),
+            None,
+            self._data().flush_to_disk,
+        )?;
+        fs_err::remove_file(pack_dir.join(old_packname))?;
+
+        Ok(())
+    }
+
+    /// Pack the revprops of shard @a shard into pack files of up to
+    /// `revprop_pack_size` bytes and write the manifest for them.
+    ///
+    /// `svn_fs_fs__pack_revprops_shard`
+    pub(crate) fn pack_revprops_shard(
+        &self,
+        shard: i64,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let max_files_per_dir = self._data().max_files_per_dir as RevisionNumber;
+        let max_pack_size = self._data().revprop_pack_size;
+        let compression_level = self.revprop_compression_level();
+        let first_rev = shard * max_files_per_dir;
+        let pack_dir = self.path_revprops_pack_shard(first_rev);
+
+        // Remove any existing pack file for this shard, since it is
+        // incomplete.
+        if pack_dir.exists() {
+            fs_err::remove_dir_all(&pack_dir)?;
+        }
+
+        // Create the new directory and manifest file stream.
+        fs_err::create_dir_all(&pack_dir)?;
+
+        // revision 0 is never packed
+        let start_rev = first_rev.max(1);
+        let end_rev = first_rev + max_files_per_dir - 1;
+
+        let mut manifest = String::new();
+        let mut packname = String::new();
+        let mut pack = PackedRevprops {
+            start_revision: start_rev,
+            revprops: Vec::new(),
+        };
+        let mut total_size = 2 * SVN_INT64_BUFFER_SIZE;
+        for rev in start_rev..=end_rev {
+            check_cancel(cancel)?;
+
+            let props = fs_err::read(self.path_revprops(rev))?;
+
+            // If we already have started a pack file and this revprop
+            // cannot be appended to it, write the previous pack file.
+            if !pack.revprops.is_empty()
+                && total_size + SVN_INT64_BUFFER_SIZE + props.len() as i64 > max_pack_size
+            {
+                fs_err::write(pack_dir.join(&packname), pack.serialize(compression_level))?;
+                total_size = 2 * SVN_INT64_BUFFER_SIZE;
+                pack = PackedRevprops {
+                    start_revision: rev,
+                    revprops: Vec::new(),
+                };
+            }
+
+            // Update the manifest.  Allocate a file name for the current
+            // pack file if it is a new one.
+            if pack.revprops.is_empty() {
+                packname = format!("{rev}.0");
+            }
+            manifest.push_str(&packname);
+            manifest.push('\n');
+
+            // add to list of files to put into the current pack file
+            total_size += SVN_INT64_BUFFER_SIZE + props.len() as i64;
+            pack.revprops.push(props);
+        }
+
+        // write the last pack file
+        fs_err::write(pack_dir.join(&packname), pack.serialize(compression_level))?;
+
+        // flush the manifest file
+        fs_err::write(pack_dir.join(PATH_MANIFEST), manifest)?;
+
+        Ok(())
+    }
+
+    /// Pack the revprops of all shards whose revisions are packed
+    /// already, as needed when upgrading a filesystem from a format
+    /// without packed revprops.  Then delete the non-packed revprops of
+    /// those shards.  Report each step to @a notify and poll @a cancel.
+    ///
+    /// `svn_fs_fs__upgrade_pack_revprops`
+    pub(crate) fn upgrade_pack_revprops(
+        &self,
+        notify: &mut Option<&mut dyn FnMut(i64, UpgradeNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let ffd = self._data();
+        if ffd.max_files_per_dir == 0 {
+            return Ok(());
+        }
+        let max_files_per_dir = ffd.max_files_per_dir as RevisionNumber;
+        let first_unpacked_shard = ffd.min_unpacked_rev / max_files_per_dir;
+
+        // An interrupted upgrade may have cleaned up some shards already,
+        // leaving the packed revprops as the only copy.  r0 never gets
+        // packed.
+        let is_unpacked = |shard: i64| {
+            self.path_revprops((shard * max_files_per_dir).max(1))
+                .exists()
+        };
+
+        // first, pack all revprops shards to match the packed revision
+        // shards
+        for shard in 0..first_unpacked_shard {
+            if !is_unpacked(shard) {
+                continue;
+            }
+            self.pack_revprops_shard(shard, cancel)?;
+            if let Some(notify) = notify {
+                notify(shard, UpgradeNotifyAction::PackRevprops);
+            }
+        }
+
+        // delete the non-packed revprops shards afterwards; don't stop
+        // half-way, the packed revprops are all in place now
+        for shard in 0..first_unpacked_shard {
+            if !is_unpacked(shard) {
+                continue;
+            }
+            self.delete_revprops_shard(shard)?;
+            if let Some(notify) = notify {
+                notify(shard, UpgradeNotifyAction::CleanupRevprops);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Delete the non-packed revprop shard @a shard.  The revprops of r0
+    /// are never packed and stay in place.
+    ///
+    /// `svn_fs_fs__delete_revprops_shard`
+    pub(crate) fn delete_revprops_shard(&self, shard: i64) -> Result<(), BackendError> {
+        let max_files_per_dir = self._data().max_files_per_dir as RevisionNumber;
+        let shard_path = self.path_revprops_shard(shard * max_files_per_dir);
+
+        if shard == 0 {
+            // delete all files except the one for revision 0
+            for rev in 1..max_files_per_dir {
+                fs_err::remove_file(shard_path.join(rev.to_string()))?;
+            }
+        } else {
+            fs_err::remove_dir_all(shard_path)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,390 @@
+//! `subversion/libsvn_fs_fs/stats.c`
+//!
+//! Statistics walk all revisions in order.  For each of them, the
+//! node-revisions created in that revision are found starting from its
+//! root directory, which works for physical and logical addressing alike.
+//! Representations are accounted for when the first node-revision
+//! referring to them is found; later references only count as shared.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::path::Path;
+
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+
+use super::super::BackendError;
+use super::super::fsx;
+use super::FsFsBackend;
+use super::cached_data::fixup_expanded_size;
+use super::cached_data::header_block_len;
+use super::cached_data::line_len;
+use super::low_level;
+use super::low_level::RepType;
+use super::rev_file::RevisionFile;
+use super::transaction::REP_TRAILER;
+use crate::FsStats;
+use crate::LARGEST_FILES_COUNT;
+use crate::LargestFile;
+use crate::NodeRevision;
+use crate::RepStats;
+use crate::Representation;
+use crate::ShardStats;
+use crate::SvnFs;
+use crate::backend::check_cancel;
+
+/// The kind of node-revision data a representation holds.
+#[derive(Debug, Clone, Copy)]
+enum RepKind {
+    FileData,
+    DirData,
+    FileProps,
+    DirProps,
+}
+
+/// What we remember about a representation seen before.
+///
+/// `rep_stats_t`
+struct RepInfo {
+    /// the kind of data it holds
+    kind: RepKind,
+    /// size in the rev file, excluding header and trailer
+    packed_size: u64,
+    /// size of the fulltext
+    expanded_size: u64,
+    /// size of header and trailer
+    overhead_size: u64,
+    /// length of the delta chain, counting the rep itself
+    chain_length: usize,
+    /// number of node-revisions referring to it
+    references: u64,
+}
+
+/// The state of a statistics run.
+///
+/// `query_t`
+#[derive(Default)]
+struct Query {
+    stats: FsStats,
+    /// all representations seen so far, by revision and item index
+    reps: HashMap<(RevisionNumber, u64), RepInfo>,
+    /// the largest files seen so far, smallest first
+    largest_files: BinaryHeap<Reverse<LargestFile>>,
+}
+
+impl Query {
+    /// Return the statistics for the representations of @a kind.
+    fn rep_stats(&mut self, kind: RepKind) -> &mut RepStats {
+        match kind {
+            RepKind::FileData => &mut self.stats.file_reps,
+            RepKind::DirData => &mut self.stats.dir_reps,
+            RepKind::FileProps => &mut self.stats.file_prop_reps,
+            RepKind::DirProps => &mut self.stats.dir_prop_reps,
+        }
+    }
+
+    /// Return the statistics of the shard being read.
+    fn shard(&mut self) -> &mut ShardStats {
+        self.stats
+            .shards
+            .last_mut()
+            .expect("the shard has been started")
+    }
+
+    /// Keep @a file if it is among the largest ones.
+    ///
+    /// `add_change`
+    fn add_largest_file(&mut self, file: LargestFile) {
+        if self.largest_files.len() < LARGEST_FILES_COUNT {
+            self.largest_files.push(Reverse(file));
+        } else if let Some(mut smallest) = self.largest_files.peek_mut()
+            && smallest.0 < file
+        {
+            *smallest = Reverse(file);
+        }
+    }
+
+    /// Add up the representations shared between node-revisions and
+    /// return the final statistics.
+    fn finish(mut self) -> FsStats {
+        for info in std::mem::take(&mut self.reps).into_values() {
+            let rep_stats = self.rep_stats(info.kind);
+            rep_stats.references += info.references;
+            if info.references > 1 {
+                rep_stats.shared.count += 1;
+                rep_stats.shared.packed_size += info.packed_size;
+                rep_stats.shared.expanded_size += info.expanded_size;
+                rep_stats.shared.overhead_size += info.overhead_size;
+                rep_stats.saved_size += (info.references - 1) * info.packed_size;
+            }
+        }
+
+        self.stats.largest_files = self
+            .largest_files
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(file)| file)
+            .collect();
+        self.stats
+    }
+}
+
+impl FsFsBackend {
+    /// Open the FSFS filesystem at @a path and gather its statistics, see
+    /// [`FsFsBackend::get_stats`].
+    pub(crate) fn stats_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        progress: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<FsStats, BackendError> {
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(Self::initialize_fs_struct());
+
+        fs.uuid = backend._open(path)?;
+        let stats = backend.get_stats(progress, cancel)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(stats)
+    }
+
+    /// Scan all revisions and return the statistics of their data.  Call
+    /// @a progress before reading each revision and stop as soon as
+    /// possible if @a cancel returns `true`.
+    ///
+    /// `svn_fs_fs__get_stats`
+    pub(crate) fn get_stats(
+        &self,
+        mut progress: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<FsStats, BackendError> {
+        let youngest = self.read_current()?;
+
+        let mut query = Query::default();
+        for rev in 0..=youngest {
+            check_cancel(cancel)?;
+            if let Some(progress) = &mut progress {
+                progress(rev);
+            }
+
+            self.read_revision(rev, &mut query, cancel)?;
+        }
+
+        Ok(query.finish())
+    }
+
+    /// Add the data of revision @a rev to @a query.
+    ///
+    /// `read_revisions` / `read_phys_revision` / `read_log_revision`
+    fn read_revision(
+        &self,
+        rev: RevisionNumber,
+        query: &mut Query,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        self.add_shard_files(rev, query)?;
+        query.stats.revision_count += 1;
+
+        let changes = self.read_changes_item(rev)?;
+        let binary = self._data().binary_items;
+        query.stats.change_count += low_level::parse_changes_item(&changes, binary)?.len() as u64;
+        query.stats.change_len += changes.len() as u64;
+        query.shard().item_size += changes.len() as u64;
+
+        let mut rev_file = self.open_pack_or_rev_file(rev)?;
+        let root = self.get_node_revision(&self.rev_get_root(rev)?)?;
+        self.read_noderev(&mut rev_file, &root, rev, query, cancel)
+    }
+
+    /// Count the rev / pack files holding revision @a rev in the shard
+    /// statistics of @a query, starting a new shard if necessary.
+    fn add_shard_files(&self, rev: RevisionNumber, query: &mut Query) -> Result<(), BackendError> {
+        let shard_size = self._data().max_files_per_dir as RevisionNumber;
+        let start_revision = if shard_size > 0 {
+            rev - rev % shard_size
+        } else {
+            0
+        };
+
+        if query
+            .stats
+            .shards
+            .last()
+            .is_none_or(|shard| shard.start_revision != start_revision)
+        {
+            let mut shard = ShardStats {
+                start_revision,
+                packed: self.is_packed_rev(rev),
+                ..Default::default()
+            };
+
+            // The pack file, and its manifest for physical addressing,
+            // cover the whole shard.
+            if shard.packed {
+                for entry in fs_err::read_dir(self.path_rev_pack_shard(rev))? {
+                    shard.files += 1;
+                    shard.file_size += entry?.metadata()?.len();
+                }
+                query.stats.total_size += shard.file_size;
+            }
+            query.stats.shards.push(shard);
+        }
+
+        let packed = query.shard().packed;
+        query.shard().revisions += 1;
+        if !packed {
+            let file_size = fs_err::metadata(self.path_rev(rev))?.len();
+            query.shard().files += 1;
+            query.shard().file_size += file_size;
+            query.stats.total_size += file_size;
+        }
+
+        Ok(())
+    }
+
+    /// Add @a noderev, found in @a rev_file, its representations and, for
+    /// directories, the node-revisions below it created in revision
+    /// @a rev to @a query.
+    ///
+    /// `read_noderev`
+    fn read_noderev(
+        &self,
+        rev_file: &mut RevisionFile,
+        noderev: &NodeRevision,
+        rev: RevisionNumber,
+        query: &mut Query,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        check_cancel(cancel)?;
+
+        let offset = self.item_offset(rev_file, rev, noderev.id.item())?;
+        let item_len = if self._data().binary_items {
+            fsx::item_len
+        } else {
+            header_block_len
+        };
+        let size = rev_file.read_item(offset, item_len)?.len() as u64;
+        query.shard().item_size += size;
+
+        let is_dir = noderev.kind == NodeKind::Directory;
+        let (node_stats, data_kind, prop_kind) = if is_dir {
+            (
+                &mut query.stats.dir_nodes,
+                RepKind::DirData,
+                RepKind::DirProps,
+            )
+        } else {
+            (
+                &mut query.stats.file_nodes,
+                RepKind::FileData,
+                RepKind::FileProps,
+            )
+        };
+        node_stats.count += 1;
+        node_stats.size += size;
+
+        if let Some(rep) = &noderev.prop_rep {
+            self.add_rep(rev_file, rep, prop_kind, rev, query)?;
+        }
+        if let Some(rep) = &noderev.data_rep
+            && let Some(expanded_size) = self.add_rep(rev_file, rep, data_kind, rev, query)?
+            && !is_dir
+        {
+            query.add_largest_file(LargestFile {
+                size: expanded_size,
+                revision: rev,
+                path: noderev.created_path.clone(),
+            });
+        }
+
+        if !is_dir {
+            return Ok(());
+        }
+
+        for entry in self.rep_contents_dir(noderev)?.into_values() {
+            // Older nodes have been read with their own revision.
+            if entry.id.rev() == rev {
+                let child = self.get_node_revision(&entry.id)?;
+                self.read_noderev(rev_file, &child, rev, query, cancel)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add @a rep of @a kind, referred to by a node-revision of revision
+    /// @a rev in @a rev_file, to @a query.  If it has not been seen before,
+    /// return its expanded size.
+    ///
+    /// `parse_representation`
+    fn add_rep(
+        &self,
+        rev_file: &mut RevisionFile,
+        rep: &Representation,
+        kind: RepKind,
+        rev: RevisionNumber,
+        query: &mut Query,
+    ) -> Result<Option<u64>, BackendError> {
+        let key = (rep.revision, rep.item_index);
+        if let Some(info) = query.reps.get_mut(&key) {
+            info.references += 1;
+            return Ok(None);
+        }
+
+        // Representations get written along with the first node-revision
+        // referring to them, so this one should be in @a rev_file.
+        let in_rev_file = rep.revision == rev;
+        let header = if in_rev_file {
+            let offset = self.item_offset(rev_file, rev, rep.item_index)?;
+            low_level::parse_rep_header(&rev_file.read_item(offset, line_len)?)?
+        } else {
+            self.read_rep_header(rep.revision, rep.item_index)?
+        };
+
+        let chain_length = match header.rep_type {
+            RepType::Plain | RepType::SelfDelta => 1,
+            RepType::Delta {
+                base_revision,
+                base_item_index,
+                ..
+            } => match query.reps.get(&(base_revision, base_item_index)) {
+                Some(base) => base.chain_length + 1,
+                None => self.rep_chain_length(rep)?.0,
+            },
+        };
+
+        let mut rep = rep.clone();
+        fixup_expanded_size(&mut rep);
+        let overhead_size = (header.header_size + REP_TRAILER.len()) as u64;
+        if in_rev_file {
+            query.shard().item_size += rep.size + overhead_size;
+        }
+
+        let rep_stats = query.rep_stats(kind);
+        let pack_stats = match header.rep_type {
+            RepType::Plain => &mut rep_stats.plain,
+            RepType::SelfDelta | RepType::Delta { .. } => &mut rep_stats.delta,
+        };
+        pack_stats.count += 1;
+        pack_stats.packed_size += rep.size;
+        pack_stats.expanded_size += rep.expanded_size;
+        pack_stats.overhead_size += overhead_size;
+        *rep_stats.chain_lengths.entry(chain_length).or_default() += 1;
+        rep_stats.size_histogram.add(rep.size);
+
+        query.reps.insert(
+            key,
+            RepInfo {
+                kind,
+                packed_size: rep.size,
+                expanded_size: rep.expanded_size,
+                overhead_size,
+                chain_length,
+                references: 1,
+            },
+        );
+
+        Ok(Some(rep.expanded_size))
+    }
+}
@@ -0,0 +1,127 @@
+//! `subversion/libsvn_fs_fs/temp_serializer.c`
+//!
+//! Serialization of the items kept in the FSFS caches.  Most items use the
+//! same textual forms as in rev files.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use svn_delta::svndiff;
+use svn_subr::CacheError;
+use svn_subr::CacheKey;
+use svn_subr::hash::HASH_TERMINATOR;
+
+use super::cached_data::parse_dir_entries;
+use super::data::CachedWindow;
+use super::data::PairCacheKey;
+use super::data::WindowCacheKey;
+use super::low_level;
+use super::transaction::unparse_dir_entries;
+use crate::DirectoryEntry;
+use crate::NodeRevision;
+use crate::id::FsId;
+
+/// Return the error for cached @a data that could not be deserialized.
+fn corrupt(err: impl Display) -> CacheError {
+    CacheError::SerializationError(err.to_string())
+}
+
+/// Return cached @a data as text.
+fn as_str(data: &[u8]) -> Result<&str, CacheError> {
+    std::str::from_utf8(data).map_err(corrupt)
+}
+
+impl CacheKey for PairCacheKey {
+    fn key_bytes(&self) -> Vec<u8> {
+        let mut key = self.revision.key_bytes();
+        key.extend_from_slice(&self.second.key_bytes());
+        key
+    }
+}
+
+impl CacheKey for WindowCacheKey {
+    fn key_bytes(&self) -> Vec<u8> {
+        let mut key = self.rep.key_bytes();
+        key.extend_from_slice(&(self.chunk_index as u64).key_bytes());
+        key
+    }
+}
+
+/// `svn_fs_fs__serialize_id`
+pub(crate) fn serialize_id(id: &FsId) -> Vec<u8> {
+    id.to_string().into_bytes()
+}
+
+/// `svn_fs_fs__deserialize_id`
+pub(crate) fn deserialize_id(data: &[u8]) -> Result<FsId, CacheError> {
+    as_str(data)?.parse().map_err(corrupt)
+}
+
+/// `svn_fs_fs__serialize_dir_entries`
+pub(crate) fn serialize_dir_entries(entries: &BTreeMap<String, DirectoryEntry>) -> Vec<u8> {
+    unparse_dir_entries(entries).into_bytes()
+}
+
+/// `svn_fs_fs__deserialize_dir_entries`
+pub(crate) fn deserialize_dir_entries(
+    data: &[u8],
+) -> Result<BTreeMap<String, DirectoryEntry>, CacheError> {
+    let (hash, _) = svn_subr::hash::read_hash(data, HASH_TERMINATOR).map_err(corrupt)?;
+    parse_dir_entries(hash, &"cached directory").map_err(corrupt)
+}
+
+/// `svn_fs_fs__serialize_node_revision`
+pub(crate) fn serialize_node_revision(noderev: &NodeRevision) -> Vec<u8> {
+    low_level::unparse_noderev(noderev).into_bytes()
+}
+
+/// `svn_fs_fs__deserialize_node_revision`
+pub(crate) fn deserialize_node_revision(data: &[u8]) -> Result<NodeRevision, CacheError> {
+    low_level::parse_noderev(as_str(data)?).map_err(corrupt)
+}
+
+/// `svn_fs_fs__serialize_properties`
+pub(crate) fn serialize_properties(props: &HashMap<String, String>) -> Vec<u8> {
+    svn_subr::hash::write_hash(props, HASH_TERMINATOR).into_bytes()
+}
+
+/// `svn_fs_fs__deserialize_properties`
+pub(crate) fn deserialize_properties(data: &[u8]) -> Result<HashMap<String, String>, CacheError> {
+    let (props, _) = svn_subr::hash::read_hash(data, HASH_TERMINATOR).map_err(corrupt)?;
+    Ok(props)
+}
+
+/// Serialize @a cached as its end offset followed by the svndiff0 form of
+/// the window.
+///
+/// `svn_fs_fs__serialize_txdelta_window`
+pub(crate) fn serialize_txdelta_window(cached: &CachedWindow) -> Vec<u8> {
+    let mut data = (cached.end_offset as u64).to_le_bytes().to_vec();
+    svndiff::write_window(&mut data, &cached.window);
+    data
+}
+
+/// `svn_fs_fs__deserialize_txdelta_window`
+pub(crate) fn deserialize_txdelta_window(data: &[u8]) -> Result<CachedWindow, CacheError> {
+    let (end_offset, window) = data
+        .split_first_chunk::<8>()
+        .ok_or_else(|| corrupt("Truncated txdelta window"))?;
+    let mut pos = 0;
+    let window = svndiff::read_window(window, &mut pos, 0).map_err(corrupt)?;
+    Ok(CachedWindow {
+        window,
+        end_offset: u64::from_le_bytes(*end_offset) as usize,
+    })
+}
+
+/// `svn_fs_fs__serialize_raw`
+#[allow(clippy::ptr_arg)] // must match `SerializeFn<Vec<u8>>`
+pub(crate) fn serialize_raw(data: &Vec<u8>) -> Vec<u8> {
+    data.clone()
+}
+
+/// `svn_fs_fs__deserialize_raw`
+pub(crate) fn deserialize_raw(data: &[u8]) -> Result<Vec<u8>, CacheError> {
+    Ok(data.to_vec())
+}
@@ -0,0 +1,1398 @@
+//! `subversion/libsvn_fs_fs/transaction.c`
+//!
+//! Transactions: creating them, recording the changes made to their nodes
+//! and turning them into new revisions.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::SystemTime;
+
+use svn_delta::svndiff;
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_subr::hash::HASH_TERMINATOR;
+use svn_subr::io::FileLock;
+use svn_subr::properties::SVN_PROP_REVISION_DATE;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::index;
+use super::index::ItemType;
+use super::index::P2lEntry;
+use super::index::SVN_FS_FS__ITEM_INDEX_CHANGES;
+use super::index::SVN_FS_FS__ITEM_INDEX_FIRST_USER;
+use super::index::SVN_FS_FS__ITEM_INDEX_ROOT_NODE;
+use super::low_level;
+use super::low_level::RepType;
+use super::tree::FsFsRoot;
+use crate::CompressionType;
+use crate::DirectoryEntry;
+use crate::FsAccess;
+use crate::NodeRevision;
+use crate::PathChange;
+use crate::Representation;
+use crate::RootTrait;
+use crate::backend::PATH_EXT_TXN;
+use crate::change::process_changes;
+use crate::id::FsId;
+use crate::id::IdPart;
+use crate::id::base36_to_u64;
+use crate::id::u64_to_base36;
+use crate::node::RepUniquifier;
+use crate::txn::Txn;
+
+/// The trailer of every representation in a rev / proto-rev file.
+pub(crate) const REP_TRAILER: &[u8] = b"ENDREP\n";
+
+/// Return the directory listing @a entries as stored in rev files and
+/// transactions.
+///
+/// `unparse_dir_entries`
+pub(crate) fn unparse_dir_entries(entries: &BTreeMap<String, DirectoryEntry>) -> String {
+    let hash = entries
+        .iter()
+        .map(|(name, entry)| (name.clone(), unparse_dir_entry(entry)))
+        .collect();
+    svn_subr::hash::write_hash(&hash, HASH_TERMINATOR)
+}
+
+/// Return the value describing @a entry in a directory listing.
+///
+/// `unparse_dir_entry`
+fn unparse_dir_entry(entry: &DirectoryEntry) -> String {
+    let kind = if entry.kind == NodeKind::File {
+        "file"
+    } else {
+        "dir"
+    };
+    format!("{kind} {}", entry.id)
+}
+
+/// The state of the revision file being assembled while committing a
+/// transaction.
+///
+/// `commit_baton`
+struct FinalRev {
+    /// the transaction being committed
+    txn_id: IdPart,
+    /// the revision being created
+    rev: RevisionNumber,
+    /// the contents of the new rev file, starting with the proto-rev
+    data: Vec<u8>,
+    /// the items of the new revision, for logical addressing
+    entries: Vec<P2lEntry>,
+    /// the permanent IDs of the node-revisions written so far
+    new_ids: HashMap<FsId, FsId>,
+    /// the representations to add to the rep cache once committed
+    reps_to_cache: Vec<Representation>,
+    /// the property representations written to the new revision, by
+    /// SHA1 checksum
+    reps_hash: HashMap<Checksum, Representation>,
+}
+
+/// Transaction handling
+impl FsFsBackend {
+    /// Return the transaction ID of the transaction called @a name, which
+    /// must exist.
+    ///
+    /// `svn_fs_fs__open_txn`
+    pub(crate) fn open_txn_id(&self, name: &str) -> Result<IdPart, BackendError> {
+        let no_such_txn = || BackendError::NoSuchTransaction(name.to_string());
+        let txn_id = IdPart::parse_txn(name).map_err(|_| no_such_txn())?;
+
+        if !self.path_txn_dir(&txn_id).is_dir() {
+            return Err(no_such_txn());
+        }
+        Ok(txn_id)
+    }
+
+    /// Return the revision transaction @a txn_id is currently based on.
+    ///
+    /// `get_txn` / `svn_fs_fs__txn_base_rev`
+    pub(crate) fn txn_base_rev(&self, txn_id: &IdPart) -> Result<RevisionNumber, BackendError> {
+        let root = self.get_node_revision(&FsId::txn_root(*txn_id))?;
+        Ok(root
+            .predecessor_id
+            .map_or(SVN_INVALID_REVNUM, |id| id.rev()))
+    }
+
+    /// Create a unique directory for a transaction based on revision
+    /// @a rev and return its ID.  The counter in `txn-current` makes the
+    /// IDs unique.
+    ///
+    /// `create_txn_dir`
+    fn create_txn_dir(&self, rev: RevisionNumber) -> Result<IdPart, BackendError> {
+        // Get the current transaction sequence value, which is a base-36
+        // number, from the txn-current file, and write an incremented
+        // value back out to the file.  Place the revision number the
+        // transaction is based off into the transaction id.
+        let number = {
+            let _lock = FileLock::lock(&self.path_txn_current_lock(), true)?;
+
+            let path = self.path_txn_current();
+            let content = fs_err::read_to_string(&path)?;
+            let number =
+                base36_to_u64(content.trim()).ok_or_else(|| BackendError::CorruptFile {
+                    path: path.to_string_lossy().to_string(),
+                    message: "expected a base-36 number".to_string(),
+                })?;
+
+            svn_subr::io::write_atomic(
+                &path,
+                format!("{}\n", u64_to_base36(number + 1)).as_bytes(),
+                Some(&path),
+                self._data().flush_to_disk,
+            )?;
+            number
+        };
+
+        let txn_id = IdPart {
+            revision: rev,
+            number,
+        };
+        fs_err::create_dir(self.path_txn_dir(&txn_id))?;
+
+        Ok(txn_id)
+    }
+
+    /// Create a new transaction based on revision @a rev and return its
+    /// ID.
+    ///
+    /// `svn_fs_fs__create_txn` / `svn_fs_fs__begin_txn`
+    pub(crate) fn begin_txn_id(&self, rev: RevisionNumber) -> Result<IdPart, BackendError> {
+        self.ensure_revision_exists(rev)?;
+        let txn_id = self.create_txn_dir(rev)?;
+
+        // Create a new root node for this transaction.
+        let root_id = self.rev_get_root(rev)?;
+        self.create_new_txn_noderev_from_rev(&txn_id, &root_id)?;
+
+        // Create an empty rev file.
+        fs_err::File::create(self.path_txn_proto_rev(&txn_id))?;
+
+        // Create an empty rev-lock file.
+        fs_err::File::create(self.path_txn_proto_rev_lock(&txn_id))?;
+
+        // Create an empty changes file.
+        fs_err::File::create(self.path_txn_changes(&txn_id))?;
+
+        // Create the next-ids file.
+        fs_err::write(self.path_txn_next_ids(&txn_id), "0 0\n")?;
+
+        // Create the (empty) item index file.
+        if self._data().use_log_addressing {
+            fs_err::File::create(self.path_txn_item_index(&txn_id))?;
+        }
+
+        // Put a datestamp on the newly created txn, so we always know
+        // exactly how old it is.
+        let date = svn_subr::time::time_to_cstring(SystemTime::now());
+        let props = HashMap::from([(SVN_PROP_REVISION_DATE.to_string(), date)]);
+        self.set_txn_proplist(&txn_id, &props)?;
+
+        Ok(txn_id)
+    }
+
+    /// Copy the root node-revision @a src of the base revision into
+    /// transaction @a txn_id as the (fresh) root of the transaction.
+    ///
+    /// `create_new_txn_noderev_from_rev`
+    fn create_new_txn_noderev_from_rev(
+        &self,
+        txn_id: &IdPart,
+        src: &FsId,
+    ) -> Result<(), BackendError> {
+        let mut noderev = self.get_node_revision(src)?;
+        noderev.predecessor_id = Some(src.clone());
+        noderev.predecessor_count += 1;
+        noderev.copyfrom = None;
+        noderev.is_fresh_txn_root = true;
+        noderev.id = FsId::txn(noderev.id.node_id, noderev.id.copy_id, *txn_id);
+
+        self.put_node_revision(&noderev)
+    }
+
+    /// Return the names of all uncommitted transactions.
+    ///
+    /// `svn_fs_fs__list_transactions`
+    pub(crate) fn list_transactions(&self) -> Result<Vec<String>, BackendError> {
+        let mut names = Vec::new();
+        for entry in fs_err::read_dir(self.path_txns_dir())? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if let Some(name) = name.to_str().and_then(|n| n.strip_suffix(PATH_EXT_TXN)) {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+
+        Ok(names)
+    }
+
+    /// Remove all files of the transaction called @a name.
+    ///
+    /// `svn_fs_fs__purge_txn`
+    pub(crate) fn purge_txn(&self, name: &str) -> Result<(), BackendError> {
+        let txn_id = IdPart::parse_txn(name)?;
+        let ignore_missing = |result: std::io::Result<()>| match result {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        };
+
+        // Remove the directory associated with this transaction.
+        ignore_missing(fs_err::remove_dir_all(self.path_txn_dir(&txn_id)))?;
+
+        // Delete protorev and its lock, which aren't in the txn
+        // directory.  It's OK if they don't exist (for example, if this
+        // is post-commit and the proto-rev has been moved into place).
+        ignore_missing(fs_err::remove_file(self.path_txn_proto_rev(&txn_id)))?;
+        ignore_missing(fs_err::remove_file(self.path_txn_proto_rev_lock(&txn_id)))?;
+
+        Ok(())
+    }
+
+    /// Return the properties of transaction @a txn_id.
+    ///
+    /// `get_txn_proplist`
+    pub(crate) fn get_txn_proplist(
+        &self,
+        txn_id: &IdPart,
+    ) -> Result<HashMap<String, String>, BackendError> {
+        let content = fs_err::read(self.path_txn_props(txn_id))?;
+        let (props, _) = svn_subr::hash::read_hash(&content, HASH_TERMINATOR)?;
+        Ok(props)
+    }
+
+    /// Replace the properties of transaction @a txn_id with @a props.
+    ///
+    /// `set_txn_proplist`
+    fn set_txn_proplist(
+        &self,
+        txn_id: &IdPart,
+        props: &HashMap<String, String>,
+    ) -> Result<(), BackendError> {
+        let content = svn_subr::hash::write_hash(props, HASH_TERMINATOR);
+        let path = self.path_txn_props(txn_id);
+        svn_subr::io::write_atomic(&path, content.as_bytes(), None, self._data().flush_to_disk)?;
+        Ok(())
+    }
+
+    /// Set (or, if @a value is `None`, delete) the property @a name of
+    /// transaction @a txn_id.
+    ///
+    /// `svn_fs_fs__change_txn_prop`
+    pub(crate) fn change_txn_prop(
+        &self,
+        txn_id: &IdPart,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), BackendError> {
+        let mut props = self.get_txn_proplist(txn_id)?;
+        match value {
+            Some(value) => props.insert(name.to_string(), value.to_string()),
+            None => props.remove(name),
+        };
+        self.set_txn_proplist(txn_id, &props)
+    }
+
+    /// Read the next node and copy IDs of transaction @a txn_id.
+    ///
+    /// `read_next_ids`
+    fn read_next_ids(&self, txn_id: &IdPart) -> Result<(u64, u64), BackendError> {
+        let path = self.path_txn_next_ids(txn_id);
+        let content = fs_err::read_to_string(&path)?;
+        let corrupt = || BackendError::CorruptFile {
+            path: path.to_string_lossy().to_string(),
+            message: "next-id file corrupt".to_string(),
+        };
+
+        let (node_id, copy_id) = content.trim_end().split_once(' ').ok_or_else(corrupt)?;
+        Ok((
+            base36_to_u64(node_id).ok_or_else(corrupt)?,
+            base36_to_u64(copy_id).ok_or_else(corrupt)?,
+        ))
+    }
+
+    /// Write the next node and copy IDs of transaction @a txn_id.
+    ///
+    /// `write_next_ids`
+    fn write_next_ids(
+        &self,
+        txn_id: &IdPart,
+        node_id: u64,
+        copy_id: u64,
+    ) -> Result<(), BackendError> {
+        fs_err::write(
+            self.path_txn_next_ids(txn_id),
+            format!("{} {}\n", u64_to_base36(node_id), u64_to_base36(copy_id)),
+        )?;
+        Ok(())
+    }
+
+    /// Reserve a new node ID in transaction @a txn_id.
+    ///
+    /// `get_new_txn_node_id`
+    fn get_new_txn_node_id(&self, txn_id: &IdPart) -> Result<IdPart, BackendError> {
+        let (node_id, copy_id) = self.read_next_ids(txn_id)?;
+        self.write_next_ids(txn_id, node_id + 1, copy_id)?;
+
+        Ok(IdPart {
+            revision: SVN_INVALID_REVNUM,
+            number: node_id,
+        })
+    }
+
+    /// Reserve a new copy ID in transaction @a txn_id.
+    ///
+    /// `svn_fs_fs__reserve_copy_id`
+    pub(crate) fn reserve_copy_id(&self, txn_id: &IdPart) -> Result<IdPart, BackendError> {
+        let (node_id, copy_id) = self.read_next_ids(txn_id)?;
+        self.write_next_ids(txn_id, node_id, copy_id + 1)?;
+
+        Ok(IdPart {
+            revision: SVN_INVALID_REVNUM,
+            number: copy_id,
+        })
+    }
+
+    /// Return a new item index in transaction @a txn_id.  Only used with
+    /// logical addressing.
+    ///
+    /// `svn_fs_fs__allocate_item_index`
+    fn allocate_item_index(&self, txn_id: &IdPart) -> Result<u64, BackendError> {
+        let path = self.path_txn_item_index(txn_id);
+        let content = fs_err::read_to_string(&path)?;
+        let item_index = match content.trim() {
+            "" => SVN_FS_FS__ITEM_INDEX_FIRST_USER,
+            value => value.parse().map_err(|_| BackendError::CorruptFile {
+                path: path.to_string_lossy().to_string(),
+                message: "expected an item index".to_string(),
+            })?,
+        };
+        fs_err::write(&path, format!("{}\n", item_index + 1))?;
+
+        Ok(item_index)
+    }
+
+    /// Return the offset of item @a item_index in the proto-rev file of
+    /// transaction @a txn_id.
+    ///
+    /// `svn_fs_fs__item_offset`
+    pub(crate) fn txn_item_offset(
+        &self,
+        txn_id: &IdPart,
+        item_index: u64,
+    ) -> Result<u64, BackendError> {
+        if !self._data().use_log_addressing {
+            return Ok(item_index);
+        }
+
+        let data = fs_err::read(self.path_p2l_proto_index(txn_id))?;
+        index::p2l_proto_index_read(&data, SVN_INVALID_REVNUM)?
+            .iter()
+            .find(|entry| entry.number == item_index)
+            .map(|entry| entry.offset)
+            .ok_or_else(|| {
+                BackendError::Corrupt(format!(
+                    "Item index {item_index} not found in transaction '{}'",
+                    txn_id.unparse_txn()
+                ))
+            })
+    }
+
+    /// Write out the node-revision @a noderev, which must be part of a
+    /// transaction.
+    ///
+    /// `svn_fs_fs__put_node_revision`
+    pub(crate) fn put_node_revision(&self, noderev: &NodeRevision) -> Result<(), BackendError> {
+        if !noderev.id.is_txn() {
+            return Err(BackendError::Corrupt(format!(
+                "Attempted to write to non-transaction '{}'",
+                noderev.id
+            )));
+        }
+
+        fs_err::write(
+            self.path_txn_node_rev(&noderev.id),
+            low_level::unparse_noderev(noderev),
+        )?;
+        Ok(())
+    }
+
+    /// Create a new node with a fresh node ID, copy ID @a copy_id in
+    /// transaction @a txn_id from @a noderev, and return its ID.
+    ///
+    /// `svn_fs_fs__create_node`
+    pub(crate) fn create_node(
+        &self,
+        noderev: &mut NodeRevision,
+        copy_id: IdPart,
+        txn_id: &IdPart,
+    ) -> Result<FsId, BackendError> {
+        // Get a new node-id for this node.
+        let node_id = self.get_new_txn_node_id(txn_id)?;
+
+        noderev.id = FsId::txn(node_id, copy_id, *txn_id);
+        self.put_node_revision(noderev)?;
+
+        Ok(noderev.id.clone())
+    }
+
+    /// Store @a noderev as the successor of @a old_id in transaction
+    /// @a txn_id, using @a copy_id or, if that is `None`, the copy ID of
+    /// @a old_id.  Return the new ID.
+    ///
+    /// `svn_fs_fs__create_successor`
+    pub(crate) fn create_successor(
+        &self,
+        old_id: &FsId,
+        noderev: &mut NodeRevision,
+        copy_id: Option<IdPart>,
+        txn_id: &IdPart,
+    ) -> Result<FsId, BackendError> {
+        noderev.id = FsId::txn(old_id.node_id, copy_id.unwrap_or(old_id.copy_id), *txn_id);
+        self.put_node_revision(noderev)?;
+
+        Ok(noderev.id.clone())
+    }
+
+    /// Delete the node-revision @a id, which must be part of a
+    /// transaction, including its mutable property and directory data.
+    ///
+    /// `svn_fs_fs__delete_node_revision`
+    pub(crate) fn delete_node_revision(&self, noderev: &NodeRevision) -> Result<(), BackendError> {
+        // Delete any mutable property representation.
+        if noderev
+            .prop_rep
+            .as_ref()
+            .is_some_and(|rep| rep.txn_id.is_used_txn())
+        {
+            fs_err::remove_file(self.path_txn_node_props(&noderev.id))?;
+        }
+
+        // Delete any mutable data representation.
+        if noderev.kind == NodeKind::Directory
+            && noderev
+                .data_rep
+                .as_ref()
+                .is_some_and(|rep| rep.txn_id.is_used_txn())
+        {
+            fs_err::remove_file(self.path_txn_node_children(&noderev.id))?;
+        }
+
+        fs_err::remove_file(self.path_txn_node_rev(&noderev.id))?;
+        Ok(())
+    }
+
+    /// Set (or, if @a entry is `None`, remove) the entry @a name in the
+    /// directory @a parent_id of transaction @a txn_id.
+    ///
+    /// `svn_fs_fs__set_entry`
+    pub(crate) fn set_entry(
+        &self,
+        txn_id: &IdPart,
+        parent_id: &FsId,
+        name: &str,
+        entry: Option<(&FsId, NodeKind)>,
+    ) -> Result<(), BackendError> {
+        let mut parent = self.get_node_revision(parent_id)?;
+        let path = self.path_txn_node_children(parent_id);
+
+        if !parent
+            .data_rep
+            .as_ref()
+            .is_some_and(|rep| rep.txn_id.is_used_txn())
+        {
+            // Before we can modify the directory, we need to dump its
+            // old contents into a mutable representation file.
+            let entries = self.rep_contents_dir(&parent)?;
+            fs_err::write(&path, unparse_dir_entries(&entries))?;
+
+            // Mark the node-rev's data rep as mutable.
+            parent.data_rep = Some(Representation {
+                md5_digest: None,
+                sha1_digest: None,
+                revision: SVN_INVALID_REVNUM,
+                item_index: 0,
+                size: 0,
+                expanded_size: 0,
+                txn_id: *txn_id,
+                uniquifier: None,
+            });
+            self.put_node_revision(&parent)?;
+        }
+
+        // Append an incremental hash entry for the entry change.
+        let value = entry.map(|(id, kind)| {
+            unparse_dir_entry(&DirectoryEntry {
+                name: name.to_string(),
+                id: id.clone(),
+                kind,
+            })
+        });
+        let mut content = String::new();
+        svn_subr::hash::write_hash_incremental(&mut content, name, value.as_deref());
+
+        let mut file = fs_err::OpenOptions::new().append(true).open(&path)?;
+        file.write_all(content.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Replace the properties of the node-revision @a id, which must be
+    /// part of a transaction, with @a props.
+    ///
+    /// `svn_fs_fs__set_proplist`
+    pub(crate) fn set_proplist(
+        &self,
+        id: &FsId,
+        props: &HashMap<String, String>,
+    ) -> Result<(), BackendError> {
+        // Dump the property list to the mutable property file.
+        let content = svn_subr::hash::write_hash(props, HASH_TERMINATOR);
+        fs_err::write(self.path_txn_node_props(id), content)?;
+
+        // Mark the node-rev's prop rep as mutable, if not already done.
+        let mut noderev = self.get_node_revision(id)?;
+        if !noderev
+            .prop_rep
+            .as_ref()
+            .is_some_and(|rep| rep.txn_id.is_used_txn())
+        {
+            noderev.prop_rep = Some(Representation {
+                md5_digest: None,
+                sha1_digest: None,
+                revision: SVN_INVALID_REVNUM,
+                item_index: 0,
+                size: 0,
+                expanded_size: 0,
+                txn_id: id.txn_id,
+                uniquifier: None,
+            });
+            self.put_node_revision(&noderev)?;
+        }
+
+        Ok(())
+    }
+
+    /// Record @a change in the changes file of transaction @a txn_id.
+    ///
+    /// `svn_fs_fs__add_change`
+    pub(crate) fn add_change(
+        &self,
+        txn_id: &IdPart,
+        change: &PathChange,
+    ) -> Result<(), BackendError> {
+        let mut file = fs_err::OpenOptions::new()
+            .append(true)
+            .open(self.path_txn_changes(txn_id))?;
+        file.write_all(low_level::unparse_change(change).as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Return the changes made in transaction @a txn_id, one per path.
+    ///
+    /// `svn_fs_fs__txn_changes_fetch`
+    pub(crate) fn txn_changes_fetch(
+        &self,
+        txn_id: &IdPart,
+    ) -> Result<BTreeMap<String, PathChange>, BackendError> {
+        let content = fs_err::read_to_string(self.path_txn_changes(txn_id))?;
+        process_changes(low_level::parse_changes(&content)?)
+    }
+
+    /// Get the exclusive lock on the proto-rev file of transaction
+    /// @a txn_id.
+    ///
+    /// `get_writable_proto_rev`
+    fn lock_proto_rev(&self, txn_id: &IdPart) -> Result<FileLock, BackendError> {
+        FileLock::try_lock(&self.path_txn_proto_rev_lock(txn_id), true)?
+            .ok_or_else(|| BackendError::RepBeingWritten(txn_id.unparse_txn()))
+    }
+
+    /// Append @a contents as the new text of the file node-revision
+    /// @a noderev to the proto-rev file of its transaction and return the
+    /// representation describing it.
+    ///
+    /// `rep_write_contents_close`
+    pub(crate) fn write_text_rep(
+        &self,
+        noderev: &NodeRevision,
+        contents: &[u8],
+    ) -> Result<Representation, BackendError> {
+        let txn_id = noderev.id.txn_id;
+        let _lock = self.lock_proto_rev(&txn_id)?;
+
+        let base_rep = self.choose_delta_base(noderev, false)?;
+        let (mut data, header_size) = self.write_delta_rep(base_rep.as_ref(), contents)?;
+        let size = (data.len() - header_size) as u64;
+        data.extend_from_slice(REP_TRAILER);
+
+        let mut rep = Representation {
+            md5_digest: Some(Checksum::compute(ChecksumKind::Md5, contents)),
+            sha1_digest: Some(Checksum::compute(ChecksumKind::Sha1, contents)),
+            revision: SVN_INVALID_REVNUM,
+            item_index: 0,
+            size,
+            expanded_size: contents.len() as u64,
+            txn_id,
+            uniquifier: Some(RepUniquifier {
+                noderev_txn_id: txn_id,
+                number: self.get_new_txn_node_id(&txn_id)?.number,
+            }),
+        };
+
+        // Check and see if we already have a representation somewhere
+        // that's identical to the one we are about to write out.
+        if let Some(old_rep) = self.get_shared_rep(&rep, None)? {
+            return Ok(old_rep);
+        }
+
+        let path = self.path_txn_proto_rev(&txn_id);
+        let mut file = fs_err::OpenOptions::new().append(true).open(&path)?;
+        let offset = file.metadata()?.len();
+        file.write_all(&data)?;
+
+        rep.item_index = if self._data().use_log_addressing {
+            let item_index = self.allocate_item_index(&txn_id)?;
+            let entry = P2lEntry::new(
+                &data,
+                0,
+                data.len() as u64,
+                ItemType::FileRep,
+                0,
+                item_index,
+            );
+            let mut proto_index = fs_err::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path_p2l_proto_index(&txn_id))?;
+            index::p2l_proto_index_add_entry(&mut proto_index, &P2lEntry { offset, ..entry })?;
+            item_index
+        } else {
+            offset
+        };
+
+        // Remember the new text for sharing within this transaction.
+        self.store_sha1_rep_mapping(&rep)?;
+
+        Ok(rep)
+    }
+
+    /// Return the svndiff version and zlib compression level to use for
+    /// new deltas.
+    ///
+    /// `txdelta_to_svndiff`
+    fn svndiff_options(&self) -> (u8, i32) {
+        match self._data().delta_compression_type {
+            CompressionType::None => (0, 0),
+            CompressionType::Zlib(level) => (1, level),
+            CompressionType::Lz4 => (2, 0),
+        }
+    }
+
+    /// Return the representation the contents (or, if @a props is set,
+    /// the properties) of @a noderev shall be deltified against, or `None`
+    /// for a self-delta.  Following the skip-delta scheme, the base is the
+    /// predecessor whose predecessor count is that of @a noderev with the
+    /// rightmost bit cleared, except for the linear top of the history.
+    ///
+    /// `choose_delta_base`
+    fn choose_delta_base(
+        &self,
+        noderev: &NodeRevision,
+        props: bool,
+    ) -> Result<Option<Representation>, BackendError> {
+        let ffd = self._data();
+
+        // If we have no predecessors, or that one is empty, then use the
+        // empty stream as a base.
+        if noderev.predecessor_count == 0 {
+            return Ok(None);
+        }
+
+        // Flip the rightmost '1' bit of the predecessor count to determine
+        // which file rev (counting from 0) we want to use.  (To see why
+        // count & (count - 1) unsets the rightmost set bit, think about
+        // how you decrement a binary number.)
+        let mut count = noderev.predecessor_count & (noderev.predecessor_count - 1);
+
+        // Finding the delta base over a very long distance can become
+        // extremely expensive for very deep histories, possibly causing
+        // client timeouts etc.  OTOH, this is a rare operation and its
+        // gains are minimal.  Lets simply start deltification anew close
+        // every other 1000 changes or so.
+        let walk = noderev.predecessor_count - count;
+        if walk > ffd.max_deltification_walk {
+            return Ok(None);
+        }
+
+        // We use skip delta for limiting the number of delta operations
+        // along very long node histories.  Close to HEAD however, we
+        // create a linear history to minimize delta size.
+        if walk < ffd.max_linear_deltification {
+            count = noderev.predecessor_count - 1;
+        }
+
+        // Walk back a number of predecessors equal to the difference
+        // between count and the original predecessor count.  (For
+        // example, if noderev has ten predecessors and we want the eighth
+        // file rev, walk back two predecessors.)
+        let mut base = noderev.clone();
+        let mut maybe_shared_rep = false;
+        while count < noderev.predecessor_count {
+            let Some(pred_id) = &base.predecessor_id else {
+                return Err(BackendError::Corrupt(format!(
+                    "Predecessor count of '{}' exceeds its history",
+                    noderev.id
+                )));
+            };
+            base = self.get_node_revision(pred_id)?;
+            count += 1;
+
+            // If there is a shared rep along the way, we need to limit
+            // the length of the deltification chain.
+            //
+            // Please note that copied nodes - such as branch directories -
+            // will look the same (false positive) while reps shared within
+            // the same revision will not be caught (false negative).
+            let base_rep = if props {
+                &base.prop_rep
+            } else {
+                &base.data_rep
+            };
+            if base_rep
+                .as_ref()
+                .is_some_and(|rep| base.id.rev() > rep.revision)
+            {
+                maybe_shared_rep = true;
+            }
+        }
+
+        // Return a suitable base representation.
+        let rep = if props { base.prop_rep } else { base.data_rep };
+
+        // If we encountered a shared rep, its parent chain may be
+        // different from the node-rev parent chain.
+        if let Some(rep) = &rep
+            && maybe_shared_rep
+        {
+            // Check whether the length of the deltification chain is
+            // acceptable.  Otherwise, shared reps may form a non-skipping
+            // delta chain in extreme cases.
+            let (chain_length, shard_count) = self.rep_chain_length(rep)?;
+
+            // Some reasonable limit, depending on how acceptable longer
+            // linear chains are in this repo.  Also, allow for some
+            // minimal chain.
+            if chain_length as i64 >= 2 * ffd.max_linear_deltification + 2 {
+                return Ok(None);
+            }
+
+            // To make it worth opening additional shards / pack files, we
+            // require that the reps have a certain minimal size.  To
+            // deltify against a rep in different shard, the lower limit
+            // is 512 bytes and doubles with every extra shard to visit
+            // along the delta chain.
+            if shard_count > 1 && (128u64 << shard_count.min(56)) >= rep.size {
+                return Ok(None);
+            }
+        }
+
+        Ok(rep)
+    }
+
+    /// Return @a contents encoded as DELTA representation against the
+    /// fulltext of @a base_rep, or as self-delta if there is no base, and
+    /// the size of the representation header at its start.
+    ///
+    /// `rep_write_get_baton` / `write_container_delta_rep`
+    fn write_delta_rep(
+        &self,
+        base_rep: Option<&Representation>,
+        contents: &[u8],
+    ) -> Result<(Vec<u8>, usize), BackendError> {
+        let (header, source) = match base_rep {
+            Some(base) => (
+                RepType::Delta {
+                    base_revision: base.revision,
+                    base_item_index: base.item_index,
+                    base_length: base.size,
+                },
+                self.rep_contents(Some(base))?,
+            ),
+            None => (RepType::SelfDelta, Vec::new()),
+        };
+
+        let mut data = low_level::unparse_rep_header(&header).into_bytes();
+        let header_size = data.len();
+
+        let (version, compression_level) = self.svndiff_options();
+        data.extend_from_slice(&svndiff::header(version));
+        for window in svn_delta::txdelta(&source, contents) {
+            svndiff::encode_window(&mut data, &window, version, compression_level);
+        }
+
+        Ok((data, header_size))
+    }
+
+    /// Return a representation other than @a rep that stores the same
+    /// contents, if rep-sharing is enabled and there is one.  Look for it
+    /// in @a reps_hash, then in the rep cache and finally among the texts
+    /// written in the transaction of @a rep.
+    ///
+    /// `get_shared_rep`
+    fn get_shared_rep(
+        &self,
+        rep: &Representation,
+        reps_hash: Option<&HashMap<Checksum, Representation>>,
+    ) -> Result<Option<Representation>, BackendError> {
+        // Return NULL, if rep sharing has been disabled.
+        if !self._data().rep_sharing_allowed {
+            return Ok(None);
+        }
+        let Some(sha1) = &rep.sha1_digest else {
+            return Ok(None);
+        };
+
+        // Check and see if we already have a representation somewhere
+        // that's identical to the one we just wrote out.  Start with the
+        // hash lookup because it is cheapest.
+        let mut old_rep = reps_hash.and_then(|hash| hash.get(sha1).cloned());
+
+        // Then, look it up in the rep cache.
+        if old_rep.is_none() {
+            old_rep = self.get_rep_reference(sha1)?;
+        }
+
+        // Look for intra-revision matches (usually data reps but not
+        // limited to them in case props happen to look like some data
+        // rep).
+        if old_rep.is_none() && rep.txn_id.is_used_txn() {
+            match fs_err::read_to_string(self.path_txn_sha1(&rep.txn_id, sha1)) {
+                Ok(value) => {
+                    let id = FsId::txn_root(rep.txn_id);
+                    old_rep = Some(low_level::parse_representation(value.trim_end(), &id)?);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // We don't want 0-length PLAIN representations to replace
+        // non-0-length ones (see issue #4554).  Also, this doubles as a
+        // simple guard against general rep-cache induced corruption.
+        Ok(old_rep
+            .filter(|old| {
+                old.expanded_size == rep.expanded_size
+                    && (rep.expanded_size != 0 || old.size == rep.size)
+            })
+            .map(|old| {
+                // Add information that is missing in the cached data.
+                Representation {
+                    md5_digest: rep.md5_digest.clone(),
+                    uniquifier: rep.uniquifier.clone(),
+                    ..old
+                }
+            }))
+    }
+
+    /// Remember the text representation @a rep written in its
+    /// transaction, so that later texts with the same contents in the
+    /// same transaction can share it.
+    ///
+    /// `store_sha1_rep_mapping`
+    fn store_sha1_rep_mapping(&self, rep: &Representation) -> Result<(), BackendError> {
+        if let Some(sha1) = &rep.sha1_digest
+            && self._data().rep_sharing_allowed
+        {
+            fs_err::write(
+                self.path_txn_sha1(&rep.txn_id, sha1),
+                low_level::unparse_representation(rep, false),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Append @a contents as representation of type @a item_type of
+    /// @a noderev to the revision being built in @a final_rev.  If
+    /// @a is_props is set, @a contents are the properties of @a noderev
+    /// and get a SHA1 checksum and a uniquifier; otherwise they are its
+    /// directory listing.  Depending on the configuration, the contents
+    /// get deltified or stored as PLAIN representation.
+    ///
+    /// `write_container_rep` / `write_container_delta_rep`
+    fn write_container_rep(
+        &self,
+        final_rev: &mut FinalRev,
+        contents: &[u8],
+        noderev: &NodeRevision,
+        item_type: ItemType,
+        is_props: bool,
+    ) -> Result<Representation, BackendError> {
+        let (sha1_digest, uniquifier) = if is_props {
+            let uniquifier = RepUniquifier {
+                noderev_txn_id: final_rev.txn_id,
+                number: self.get_new_txn_node_id(&final_rev.txn_id)?.number,
+            };
+            (
+                Some(Checksum::compute(ChecksumKind::Sha1, contents)),
+                Some(uniquifier),
+            )
+        } else {
+            (None, None)
+        };
+        let mut rep = Representation {
+            md5_digest: Some(Checksum::compute(ChecksumKind::Md5, contents)),
+            sha1_digest,
+            revision: final_rev.rev,
+            item_index: 0,
+            size: contents.len() as u64,
+            expanded_size: contents.len() as u64,
+            txn_id: IdPart::UNUSED_TXN,
+            uniquifier,
+        };
+
+        // Check and see if we already have a representation somewhere
+        // that's identical to the one we are about to write out.
+        if is_props && let Some(old_rep) = self.get_shared_rep(&rep, Some(&final_rev.reps_hash))? {
+            return Ok(old_rep);
+        }
+
+        let deltify = if is_props {
+            self._data().deltify_properties
+        } else {
+            self._data().deltify_directories
+        };
+
+        let offset = final_rev.data.len() as u64;
+        if deltify {
+            let base_rep = self.choose_delta_base(noderev, is_props)?;
+            let (data, header_size) = self.write_delta_rep(base_rep.as_ref(), contents)?;
+            rep.size = (data.len() - header_size) as u64;
+            final_rev.data.extend_from_slice(&data);
+        } else {
+            final_rev
+                .data
+                .extend_from_slice(low_level::unparse_rep_header(&RepType::Plain).as_bytes());
+            final_rev.data.extend_from_slice(contents);
+        }
+        final_rev.data.extend_from_slice(REP_TRAILER);
+        rep.item_index = self.final_item_index(final_rev, offset, item_type, None)?;
+
+        Ok(rep)
+    }
+
+    /// Return the item index of the item of type @a item_type that has
+    /// just been appended at @a offset to @a final_rev.  Use
+    /// @a item_index if given, otherwise allocate a new one.
+    ///
+    /// `store_p2l_index_entry` / `allocate_item_index`
+    fn final_item_index(
+        &self,
+        final_rev: &mut FinalRev,
+        offset: u64,
+        item_type: ItemType,
+        item_index: Option<u64>,
+    ) -> Result<u64, BackendError> {
+        if !self._data().use_log_addressing {
+            return Ok(offset);
+        }
+
+        let item_index = match item_index {
+            Some(item_index) => item_index,
+            None => self.allocate_item_index(&final_rev.txn_id)?,
+        };
+        let size = final_rev.data.len() as u64 - offset;
+        final_rev.entries.push(P2lEntry::new(
+            &final_rev.data,
+            offset,
+            size,
+            item_type,
+            final_rev.rev,
+            item_index,
+        ));
+
+        Ok(item_index)
+    }
+
+    /// Copy the node-revision @a id and, recursively, all its mutable
+    /// children into the revision being built in @a final_rev.  Return
+    /// the permanent ID of the node.  @a at_root is set for the root
+    /// directory of the transaction.
+    ///
+    /// `write_final_rev`
+    fn write_final_rev(
+        &self,
+        final_rev: &mut FinalRev,
+        id: &FsId,
+        at_root: bool,
+    ) -> Result<FsId, BackendError> {
+        // Check to see if this is a transaction node.
+        if !id.is_txn() {
+            return Ok(id.clone());
+        }
+
+        let mut noderev = self.get_node_revision(id)?;
+
+        if noderev.kind == NodeKind::Directory {
+            // This is a directory.  Write out all the children first.
+            let mut entries = self.rep_contents_dir(&noderev)?;
+            for entry in entries.values_mut() {
+                entry.id = self.write_final_rev(final_rev, &entry.id, false)?;
+            }
+
+            if noderev
+                .data_rep
+                .as_ref()
+                .is_some_and(|rep| rep.txn_id.is_used_txn())
+            {
+                // Write out the contents of this directory as a text
+                // rep.
+                let contents = unparse_dir_entries(&entries);
+                noderev.data_rep = Some(self.write_container_rep(
+                    final_rev,
+                    contents.as_bytes(),
+                    &noderev,
+                    ItemType::DirRep,
+                    false,
+                )?);
+            }
+        } else if let Some(rep) = &mut noderev.data_rep
+            && rep.txn_id.is_used_txn()
+        {
+            // This is a file.  We should make sure the data rep, if it
+            // exists in a "this" transaction, gets rewritten to our
+            // final revision.  The proto-rev data stays where it is.
+            rep.txn_id = IdPart::UNUSED_TXN;
+            rep.revision = final_rev.rev;
+        }
+
+        // Fix up the property reps.
+        if noderev
+            .prop_rep
+            .as_ref()
+            .is_some_and(|rep| rep.txn_id.is_used_txn())
+        {
+            let props = self.get_proplist(&noderev)?;
+            let contents = svn_subr::hash::write_hash(&props, HASH_TERMINATOR);
+            let item_type = if noderev.kind == NodeKind::Directory {
+                ItemType::DirProps
+            } else {
+                ItemType::FileProps
+            };
+            noderev.prop_rep = Some(self.write_container_rep(
+                final_rev,
+                contents.as_bytes(),
+                &noderev,
+                item_type,
+                true,
+            )?);
+        }
+
+        // Convert our temporary ID into a permanent revision one.
+        let rev = final_rev.rev;
+        let permanent = |part: IdPart| {
+            if part.revision == SVN_INVALID_REVNUM {
+                IdPart {
+                    revision: rev,
+                    ..part
+                }
+            } else {
+                part
+            }
+        };
+        let node_id = permanent(id.node_id);
+        let copy_id = permanent(id.copy_id);
+
+        if noderev.copyroot_rev == SVN_INVALID_REVNUM {
+            noderev.copyroot_rev = rev;
+        }
+        noderev.is_fresh_txn_root = false;
+
+        // Write out our new node-revision.  The root node of each
+        // revision has a fixed item index with logical addressing.
+        let offset = final_rev.data.len() as u64;
+        let item_index = if !self._data().use_log_addressing {
+            offset
+        } else if at_root {
+            SVN_FS_FS__ITEM_INDEX_ROOT_NODE
+        } else {
+            self.allocate_item_index(&final_rev.txn_id)?
+        };
+        noderev.id = FsId {
+            node_id,
+            copy_id,
+            txn_id: IdPart::UNUSED_TXN,
+            rev_item: IdPart {
+                revision: final_rev.rev,
+                number: item_index,
+            },
+        };
+        final_rev
+            .data
+            .extend_from_slice(&low_level::unparse_noderev_item(
+                &noderev,
+                self._data().binary_items,
+            ));
+        self.final_item_index(final_rev, offset, ItemType::NodeRev, Some(item_index))?;
+
+        // Save the data representation's hash in the rep cache.
+        if self._data().rep_sharing_allowed {
+            if let Some(rep) = &noderev.data_rep
+                && noderev.kind == NodeKind::File
+                && rep.revision == final_rev.rev
+            {
+                final_rev.reps_to_cache.push(rep.clone());
+            }
+
+            // Add new property reps to hash and on-disk cache.
+            if let Some(rep) = &noderev.prop_rep
+                && rep.revision == final_rev.rev
+                && let Some(sha1) = &rep.sha1_digest
+            {
+                final_rev.reps_to_cache.push(rep.clone());
+                final_rev.reps_hash.insert(sha1.clone(), rep.clone());
+            }
+        }
+
+        final_rev.new_ids.insert(id.clone(), noderev.id.clone());
+        Ok(noderev.id)
+    }
+
+    /// Commit transaction @a txn_id, which is based on revision
+    /// @a base_rev, as a new revision and return its number.  Fail with
+    /// [`BackendError::TxnOutOfDate`] if @a base_rev is not the youngest
+    /// revision, or if @a access may not change a locked path.
+    ///
+    /// `svn_fs_fs__commit` / `commit_body`
+    pub(crate) fn commit(
+        &self,
+        txn_id: &IdPart,
+        base_rev: RevisionNumber,
+        access: Option<&FsAccess>,
+    ) -> Result<RevisionNumber, BackendError> {
+        // Get the write lock for this filesystem.
+        let write_lock = FileLock::lock(&self.path_lock(), true)?;
+
+        // Locks may have been added (or stolen) between the calling of
+        // previous svn_fs.h functions and svn_fs_commit_txn(), so we need
+        // to re-examine every changed-path in the txn and re-verify all
+        // discovered locks.
+        self.verify_locks(txn_id, access)?;
+
+        // Check that the transaction is based on the latest revision.
+        let old_rev = self.read_current()?;
+        if base_rev != old_rev {
+            return Err(BackendError::TxnOutOfDate {
+                txn: txn_id.unparse_txn(),
+                rev: old_rev,
+            });
+        }
+        let new_rev = old_rev + 1;
+
+        // Get the changes before the node-revisions get rewritten.
+        let changes = self.txn_changes_fetch(txn_id)?;
+
+        // Get a write handle on the proto revision file.
+        let proto_lock = self.lock_proto_rev(txn_id)?;
+        let data = fs_err::read(self.path_txn_proto_rev(txn_id))?;
+        let entries = if self._data().use_log_addressing {
+            match fs_err::read(self.path_p2l_proto_index(txn_id)) {
+                Ok(index) => index::p2l_proto_index_read(&index, new_rev)?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            Vec::new()
+        };
+        let mut final_rev = FinalRev {
+            txn_id: *txn_id,
+            rev: new_rev,
+            data,
+            entries,
+            new_ids: HashMap::new(),
+            reps_to_cache: Vec::new(),
+            reps_hash: HashMap::new(),
+        };
+
+        // Write out all the node-revisions and directory contents.
+        let root_id = FsId::txn_root(*txn_id);
+        let new_root_id = self.write_final_rev(&mut final_rev, &root_id, true)?;
+
+        // Write the changed-path information.  Node-revisions that got
+        // committed are referred to by their new IDs.
+        let changes_offset = final_rev.data.len() as u64;
+        let changes: Vec<PathChange> = changes
+            .into_values()
+            .map(|change| PathChange {
+                node_rev_id: change
+                    .node_rev_id
+                    .map(|id| final_rev.new_ids.get(&id).cloned().unwrap_or(id)),
+                ..change
+            })
+            .collect();
+        final_rev
+            .data
+            .extend_from_slice(&low_level::unparse_changes_item(
+                &changes,
+                self._data().binary_items,
+            )?);
+
+        let mut data = if self._data().use_log_addressing {
+            self.final_item_index(
+                &mut final_rev,
+                changes_offset,
+                ItemType::Changes,
+                Some(SVN_FS_FS__ITEM_INDEX_CHANGES),
+            )?;
+
+            // Append the index data to the rev file.
+            let mut data = final_rev.data;
+            let data_size = data.len() as u64;
+            index::add_index_data(
+                &mut data,
+                data_size,
+                &final_rev.entries,
+                new_rev,
+                self._data().l2p_page_size as u64,
+                self._data().p2l_page_size as u64,
+            )?;
+            data
+        } else {
+            // Write the final line.
+            let mut data = final_rev.data;
+            data.extend_from_slice(format!("{} {changes_offset}\n", new_root_id.item()).as_bytes());
+            data
+        };
+        data.shrink_to_fit();
+
+        // Move the finished rev file into place.
+        if self._data().max_files_per_dir > 0 {
+            fs_err::create_dir_all(self.path_rev_shard(new_rev))?;
+        }
+        let rev_path = self.path_rev(new_rev);
+        svn_subr::io::write_atomic(
+            &rev_path,
+            &data,
+            Some(&self.path_rev_absolute(old_rev)),
+            self._data().flush_to_disk,
+        )?;
+        svn_subr::io::set_file_read_only(&rev_path, false)?;
+        drop(proto_lock);
+
+        // Update the 'svn:date' property and move the revprops into
+        // place.
+        let mut props = self.get_txn_proplist(txn_id)?;
+        props.insert(
+            SVN_PROP_REVISION_DATE.to_string(),
+            svn_subr::time::time_to_cstring(SystemTime::now()),
+        );
+        if self._data().max_files_per_dir > 0 {
+            fs_err::create_dir_all(self.path_revprops_shard(new_rev))?;
+        }
+        self.set_revision_proplist(new_rev, &props)?;
+
+        // Update the 'current' file.
+        self.write_current(new_rev)?;
+        drop(write_lock);
+
+        // Remove this transaction directory.
+        self.purge_txn(&txn_id.unparse_txn())?;
+
+        // Write new entries to the rep-sharing database.
+        if self._data().rep_sharing_allowed {
+            self.set_rep_references(&final_rev.reps_to_cache)?;
+        }
+
+        Ok(new_rev)
+    }
+}
+
+/// An FSFS transaction.
+///
+/// `fs_txn_data_t`
+pub(crate) struct FsFsTxn<'a> {
+    /// the filesystem this transaction belongs to
+    fs: &'a FsFsBackend,
+    /// the ID of this transaction
+    txn_id: IdPart,
+    /// the name of this transaction
+    name: String,
+    /// the revision the transaction is currently based on
+    base_rev: RevisionNumber,
+}
+
+impl<'a> FsFsTxn<'a> {
+    /// Return the transaction @a txn_id of @a fs, based on @a base_rev.
+    pub(crate) fn new(fs: &'a FsFsBackend, txn_id: IdPart, base_rev: RevisionNumber) -> Self {
+        Self {
+            fs,
+            txn_id,
+            name: txn_id.unparse_txn(),
+            base_rev,
+        }
+    }
+}
+
+impl Txn for FsFsTxn<'_> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn base_revision(&self) -> RevisionNumber {
+        self.base_rev
+    }
+
+    /// `svn_fs_fs__commit_txn`
+    fn commit(&mut self, access: Option<&FsAccess>) -> Result<RevisionNumber, BackendError> {
+        loop {
+            // Get the youngest revision and merge our changes into it, if
+            // there is something to merge.
+            let youngish_rev = self.fs.read_current()?;
+            self.fs.merge_changes(&self.txn_id, youngish_rev)?;
+            self.base_rev = youngish_rev;
+
+            // Try to commit.  If someone committed in the meantime, do
+            // it all over again.
+            match self.fs.commit(&self.txn_id, self.base_rev, access) {
+                Err(BackendError::TxnOutOfDate { .. }) => continue,
+                result => return result,
+            }
+        }
+    }
+
+    /// `svn_fs_fs__abort_txn`
+    fn abort(&self) -> Result<(), BackendError> {
+        self.fs.purge_txn(&self.name)
+    }
+
+    /// `svn_fs_fs__txn_prop`
+    fn prop(&self, name: &str) -> Result<Option<String>, BackendError> {
+        Ok(self.fs.get_txn_proplist(&self.txn_id)?.remove(name))
+    }
+
+    /// `svn_fs_fs__txn_proplist`
+    fn proplist(&self) -> Result<HashMap<String, String>, BackendError> {
+        self.fs.get_txn_proplist(&self.txn_id)
+    }
+
+    /// `svn_fs_fs__change_txn_prop`
+    fn change_prop(&self, name: &str, value: Option<&str>) -> Result<(), BackendError> {
+        self.fs.change_txn_prop(&self.txn_id, name, value)
+    }
+
+    /// `svn_fs_fs__txn_root`
+    fn root(&self) -> Result<Box<dyn RootTrait + '_>, BackendError> {
+        Ok(Box::new(FsFsRoot::txn_root(
+            self.fs,
+            self.txn_id,
+            self.base_rev,
+        )))
+    }
+}
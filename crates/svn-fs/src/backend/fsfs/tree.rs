@@ -0,0 +1,1510 @@
+//! `subversion/libsvn_fs_fs/tree.c`
+//!
+//! The FSFS implementation of revision and transaction roots: walking
+//! paths from the root directory down to the node-revisions they name,
+//! making nodes mutable for modification and merging transactions with
+//! newer revisions.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io::Read;
+
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_subr::Tristate;
+use svn_subr::mergeinfo::mergeinfo_inheritable;
+use svn_subr::mergeinfo::parse_mergeinfo;
+use svn_subr::properties::SVN_PROP_MERGEINFO;
+use svn_types::Mergeinfo;
+use svn_types::MergeinfoCatalog;
+use svn_types::MergeinfoInheritance;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::cached_data::fixup_expanded_size;
+use crate::CopyFromInfo;
+use crate::DirectoryEntry;
+use crate::FsId;
+use crate::NodeRevision;
+use crate::PathChange;
+use crate::PathChangeKind;
+use crate::history::HistoryTrait;
+use crate::id::IdPart;
+use crate::root::RootTrait;
+use crate::util::append_to_merged_froms;
+use crate::util::canonicalize_abspath;
+use crate::util::fspath_join;
+use crate::util::fspath_skip_ancestor;
+use crate::util::prop_lists_equal;
+
+/// How a node in a transaction gets its copy ID once it is made mutable.
+///
+/// `copy_id_inherit_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CopyIdInherit {
+    /// the node keeps its own copy ID
+    Self_,
+    /// the node takes the copy ID of its parent
+    Parent,
+    /// the node gets a new copy ID
+    New,
+}
+
+/// One step of the chain of directories leading from the root of a
+/// transaction to a path.
+///
+/// `parent_path_t`
+struct ParentPath {
+    /// the node-revision at this step, or `None` if the last component
+    /// of the path does not exist
+    noderev: Option<NodeRevision>,
+
+    /// the name of this node in its parent directory, empty for the root
+    entry: String,
+
+    /// the canonical path of this node
+    path: String,
+
+    /// how this node inherits its copy ID when it gets made mutable
+    copy_inherit: CopyIdInherit,
+}
+
+/// The root directory of a revision in an FSFS filesystem.
+///
+/// `fs_root_data_t`
+#[derive(Clone)]
+pub(crate) struct FsFsRoot<'a> {
+    /// the filesystem this root belongs to
+    fs: &'a FsFsBackend,
+
+    /// the revision of this root, or the base revision for transaction
+    /// roots
+    rev: RevisionNumber,
+
+    /// the transaction of this root, `None` for revision roots
+    txn: Option<IdPart>,
+
+    /// the node-revision ID of the root directory
+    root_id: FsId,
+}
+
+impl<'a> FsFsRoot<'a> {
+    /// Create the root of revision @a rev in @a fs.
+    ///
+    /// `svn_fs_fs__revision_root`
+    pub(crate) fn revision_root(
+        fs: &'a FsFsBackend,
+        rev: RevisionNumber,
+    ) -> Result<Self, BackendError> {
+        let root_id = fs.rev_get_root(rev)?;
+        Ok(Self {
+            fs,
+            rev,
+            txn: None,
+            root_id,
+        })
+    }
+
+    /// Create the root of transaction @a txn_id in @a fs, which is based
+    /// on revision @a base_rev.
+    ///
+    /// `svn_fs_fs__txn_root`
+    pub(crate) fn txn_root(fs: &'a FsFsBackend, txn_id: IdPart, base_rev: RevisionNumber) -> Self {
+        Self {
+            fs,
+            rev: base_rev,
+            txn: Some(txn_id),
+            root_id: FsId::txn_root(txn_id),
+        }
+    }
+
+    /// Return the transaction of this root or fail if this is not a
+    /// transaction root.
+    ///
+    /// `root_txn_id`
+    fn txn_id(&self) -> Result<IdPart, BackendError> {
+        self.txn.ok_or(BackendError::NotTxnRoot)
+    }
+
+    /// Return the error for a @a path that does not exist in this root.
+    ///
+    /// `SVN_FS__NOT_FOUND`
+    fn not_found(&self, path: &str) -> BackendError {
+        match &self.txn {
+            Some(txn_id) => BackendError::FileNotFound(format!(
+                "transaction '{}', path '{path}'",
+                txn_id.unparse_txn()
+            )),
+            None => BackendError::FileNotFound(format!("revision {}, path '{path}'", self.rev)),
+        }
+    }
+
+    /// Return the error for a @a path that already exists in this root.
+    ///
+    /// `SVN_FS__ALREADY_EXISTS`
+    fn already_exists(&self, path: &str) -> BackendError {
+        match &self.txn {
+            Some(txn_id) => BackendError::FileExists(format!(
+                "transaction '{}', path '{path}'",
+                txn_id.unparse_txn()
+            )),
+            None => BackendError::FileExists(format!("revision {}, path '{path}'", self.rev)),
+        }
+    }
+
+    /// Return the node-revision of the node at @a path.
+    ///
+    /// `open_path` / `get_dag`
+    fn open_path(&self, path: &str) -> Result<NodeRevision, BackendError> {
+        let mut noderev = self.fs.get_node_revision(&self.root_id)?;
+        let mut walked = String::new();
+
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            // The parent must be a directory to contain anything.
+            if noderev.kind != NodeKind::Directory {
+                return Err(BackendError::NotDirectory(if walked.is_empty() {
+                    "/".to_string()
+                } else {
+                    walked
+                }));
+            }
+            walked.push('/');
+            walked.push_str(component);
+
+            let mut entries = self.fs.rep_contents_dir(&noderev)?;
+            let entry = entries
+                .remove(component)
+                .ok_or_else(|| self.not_found(path))?;
+            noderev = self.fs.get_node_revision(&entry.id)?;
+        }
+
+        Ok(noderev)
+    }
+
+    /// Return the node-revision of the file at @a path.
+    fn open_file(&self, path: &str) -> Result<NodeRevision, BackendError> {
+        let noderev = self.open_path(path)?;
+        if noderev.kind != NodeKind::File {
+            return Err(BackendError::NotFile(path.to_string()));
+        }
+        Ok(noderev)
+    }
+
+    /// Return the chain of directories from the root of this transaction
+    /// down to @a path, ending with @a path itself.  If @a last_optional
+    /// is set, the last component of @a path need not exist.
+    ///
+    /// `open_path`
+    fn open_parent_path(
+        &self,
+        path: &str,
+        last_optional: bool,
+    ) -> Result<Vec<ParentPath>, BackendError> {
+        let path = canonicalize_abspath(path);
+        let mut chain = vec![ParentPath {
+            noderev: Some(self.fs.get_node_revision(&self.root_id)?),
+            entry: String::new(),
+            path: "/".to_string(),
+            copy_inherit: CopyIdInherit::Self_,
+        }];
+
+        let components: Vec<_> = path.split('/').filter(|c| !c.is_empty()).collect();
+        for (i, component) in components.iter().enumerate() {
+            let parent = chain.last().and_then(|p| p.noderev.as_ref()).unwrap();
+            let parent_path = chain.last().unwrap().path.clone();
+
+            // The parent must be a directory to contain anything.
+            if parent.kind != NodeKind::Directory {
+                return Err(BackendError::NotDirectory(parent_path));
+            }
+
+            let child_path = fspath_join(&parent_path, component);
+            let Some(entry) = self.fs.rep_contents_dir(parent)?.remove(*component) else {
+                // The last component may be missing.
+                if last_optional && i + 1 == components.len() {
+                    chain.push(ParentPath {
+                        noderev: None,
+                        entry: component.to_string(),
+                        path: child_path,
+                        copy_inherit: CopyIdInherit::Self_,
+                    });
+                    break;
+                }
+                return Err(self.not_found(&path));
+            };
+
+            // Only transaction roots make nodes mutable, so only they need
+            // the copy inheritance.
+            let child = self.fs.get_node_revision(&entry.id)?;
+            let copy_inherit = if self.txn.is_some() {
+                self.get_copy_inheritance(&child, parent, &child_path)?
+            } else {
+                CopyIdInherit::Self_
+            };
+            chain.push(ParentPath {
+                noderev: Some(child),
+                entry: component.to_string(),
+                path: child_path,
+                copy_inherit,
+            });
+        }
+
+        Ok(chain)
+    }
+
+    /// Return how @a child, reached via @a child_path in the directory
+    /// @a parent, gets its copy ID once it is made mutable.
+    ///
+    /// `get_copy_inheritance`
+    fn get_copy_inheritance(
+        &self,
+        child: &NodeRevision,
+        parent: &NodeRevision,
+        child_path: &str,
+    ) -> Result<CopyIdInherit, BackendError> {
+        // If this node is already mutable, we have nothing to do.
+        if child.id.is_txn() {
+            return Ok(CopyIdInherit::Self_);
+        }
+
+        // Special case: if the child's copy ID is '0', use the parent's
+        // copy ID.  Likewise if the child is on the same branch as the
+        // parent already.
+        let child_copy_id = child.id.copy_id;
+        if (child_copy_id.revision == 0 && child_copy_id.number == 0)
+            || child_copy_id == parent.id.copy_id
+        {
+            return Ok(CopyIdInherit::Parent);
+        }
+
+        // If the child is on the same branch that the parent is on, the
+        // child should just use the same copy ID that the parent would
+        // use.  Else, the child needs to generate a new copy ID to use
+        // should it need to be made mutable.  We will claim that child is
+        // on the same branch as its parent if the child itself is not a
+        // branch point, or if it is a branch point that we are accessing
+        // via its original copy destination path.
+        let copyroot = FsFsRoot::revision_root(self.fs, child.copyroot_rev)?
+            .open_path(&child.copyroot_path)?;
+        if !copyroot.id.check_related(&child.id) {
+            return Ok(CopyIdInherit::Parent);
+        }
+
+        // Determine if we are looking at the child via its original path
+        // or as a subtree item of a copied tree.
+        if child.created_path == child_path {
+            return Ok(CopyIdInherit::Self_);
+        }
+
+        // We are pretty sure that the child node is an unedited nested
+        // branched node.  When it needs to be made mutable, it should
+        // claim a new copy ID.
+        Ok(CopyIdInherit::New)
+    }
+
+    /// Make the node at the end of @a chain and all its parents mutable,
+    /// cloning them into the transaction where necessary.  @a error_path
+    /// is the path to report in errors.
+    ///
+    /// `make_path_mutable`
+    fn make_path_mutable(
+        &self,
+        chain: &mut [ParentPath],
+        error_path: &str,
+    ) -> Result<(), BackendError> {
+        let txn_id = self.txn_id()?;
+        let (last, parents) = chain
+            .split_last_mut()
+            .expect("path chain always contains the root");
+        let noderev = last
+            .noderev
+            .as_ref()
+            .ok_or_else(|| self.not_found(error_path))?;
+
+        // Is the node mutable already?
+        if noderev.id.is_txn() {
+            return Ok(());
+        }
+
+        // Nope, we need to clone it.  The root of a transaction is always
+        // mutable, so there must be a parent.
+        self.make_path_mutable(parents, error_path)?;
+        let parent_step = parents.last().expect("the transaction root is mutable");
+        let parent = parent_step.noderev.as_ref().unwrap();
+
+        // Figure out what our new node's copy ID will be.
+        let copy_id = match last.copy_inherit {
+            CopyIdInherit::Parent => Some(parent.id.copy_id),
+            CopyIdInherit::New => Some(self.fs.reserve_copy_id(&txn_id)?),
+            CopyIdInherit::Self_ => None,
+        };
+
+        // Determine what copyroot our new child node should use.
+        let copyroot = FsFsRoot::revision_root(self.fs, noderev.copyroot_rev)?
+            .open_path(&noderev.copyroot_path)?;
+        let is_parent_copyroot = noderev.id.node_id != copyroot.id.node_id;
+
+        // Now make this node mutable.
+        let mut clone = noderev.clone();
+        if is_parent_copyroot {
+            clone.copyroot_rev = parent.copyroot_rev;
+            clone.copyroot_path = parent.copyroot_path.clone();
+        }
+        clone.copyfrom = None;
+        clone.predecessor_id = Some(noderev.id.clone());
+        if clone.predecessor_count != -1 {
+            clone.predecessor_count += 1;
+        }
+        clone.created_path = fspath_join(&parent_step.path, &last.entry);
+
+        let new_id = self
+            .fs
+            .create_successor(&noderev.id, &mut clone, copy_id, &txn_id)?;
+        self.fs.set_entry(
+            &txn_id,
+            &parent.id,
+            &last.entry,
+            Some((&new_id, clone.kind)),
+        )?;
+
+        last.noderev = Some(clone);
+        Ok(())
+    }
+
+    /// Create a new, empty node of kind @a kind at @a path.
+    ///
+    /// `fs_make_dir` / `fs_make_file`
+    fn make_entry(&self, path: &str, kind: NodeKind) -> Result<(), BackendError> {
+        let txn_id = self.txn_id()?;
+        let mut chain = self.open_parent_path(path, true)?;
+        let path = chain.last().unwrap().path.clone();
+
+        // If there's already a node by that name, complain.
+        let Some((last, parents)) = chain.split_last_mut().filter(|(l, _)| l.noderev.is_none())
+        else {
+            return Err(self.already_exists(&path));
+        };
+
+        // Create the node in the (mutable) parent directory.
+        self.make_path_mutable(parents, &path)?;
+        let parent = parents.last().and_then(|p| p.noderev.as_ref()).unwrap();
+        let mut noderev = NodeRevision {
+            kind,
+            id: FsId::txn_root(txn_id),
+            predecessor_id: None,
+            copyfrom: None,
+            copyroot_rev: parent.copyroot_rev,
+            copyroot_path: parent.copyroot_path.clone(),
+            predecessor_count: 0,
+            prop_rep: None,
+            data_rep: None,
+            created_path: path.clone(),
+            is_fresh_txn_root: false,
+            mergeinfo_count: 0,
+            has_mergeinfo: false,
+        };
+        let id = self
+            .fs
+            .create_node(&mut noderev, parent.id.copy_id, &txn_id)?;
+        self.fs
+            .set_entry(&txn_id, &parent.id, &last.entry, Some((&id, kind)))?;
+
+        self.fs.add_change(
+            &txn_id,
+            &PathChange {
+                path,
+                node_rev_id: Some(id),
+                change_kind: PathChangeKind::Add,
+                node_kind: kind,
+                text_mod: kind == NodeKind::File,
+                prop_mod: false,
+                mergeinfo_mod: Tristate::False,
+                copyfrom: None,
+            },
+        )
+    }
+
+    /// Delete the node @a id and, recursively, all its children if it is
+    /// mutable.
+    ///
+    /// `svn_fs_fs__dag_delete_if_mutable`
+    fn delete_if_mutable(&self, id: &FsId) -> Result<(), BackendError> {
+        // If the node is immutable, there's nothing to do.
+        if !id.is_txn() {
+            return Ok(());
+        }
+
+        // Recurse into directories.
+        let noderev = self.fs.get_node_revision(id)?;
+        if noderev.kind == NodeKind::Directory {
+            for entry in self.fs.rep_contents_dir(&noderev)?.values() {
+                self.delete_if_mutable(&entry.id)?;
+            }
+        }
+
+        // ... then delete the node itself.
+        self.fs.delete_node_revision(&noderev)
+    }
+
+    /// Add @a increment to the mergeinfo counts of all nodes in
+    /// @a chain, which must be mutable.
+    ///
+    /// `increment_mergeinfo_up_tree`
+    fn increment_mergeinfo_up_tree(
+        &self,
+        chain: &[ParentPath],
+        increment: i64,
+    ) -> Result<(), BackendError> {
+        for step in chain.iter().rev() {
+            let noderev = step.noderev.as_ref().unwrap();
+            self.fs.increment_mergeinfo_count(&noderev.id, increment)?;
+        }
+
+        Ok(())
+    }
+
+    /// Return the `svn:mergeinfo` property of @a noderev, which claims
+    /// to have one, parsed.  Unparsable mergeinfo is treated as none.
+    fn read_mergeinfo(&self, noderev: &NodeRevision) -> Result<Option<Mergeinfo>, BackendError> {
+        let proplist = self.fs.get_proplist(noderev)?;
+        let value = proplist.get(SVN_PROP_MERGEINFO).ok_or_else(|| {
+            BackendError::Corrupt(format!(
+                "Node-revision #'{}' claims to have mergeinfo but doesn't",
+                noderev.id
+            ))
+        })?;
+
+        Ok(parse_mergeinfo(value).ok())
+    }
+
+    /// Return the mergeinfo of @a path, explicit or inherited as selected
+    /// by @a inherit, or `None` if there is none.
+    ///
+    /// `get_mergeinfo_for_path`
+    fn get_mergeinfo_for_path(
+        &self,
+        path: &str,
+        inherit: MergeinfoInheritance,
+        adjust_inherited_mergeinfo: bool,
+    ) -> Result<Option<Mergeinfo>, BackendError> {
+        let chain = self.open_parent_path(path, false)?;
+        let last = chain.len() - 1;
+
+        let mut nearest_ancestor = last;
+        if inherit == MergeinfoInheritance::NearestAncestor {
+            if nearest_ancestor == 0 {
+                return Ok(None);
+            }
+            nearest_ancestor -= 1;
+        }
+
+        while !chain[nearest_ancestor]
+            .noderev
+            .as_ref()
+            .unwrap()
+            .has_mergeinfo
+        {
+            // No need to loop if we're looking for explicit mergeinfo.
+            // Run out?  There's no mergeinfo.
+            if inherit == MergeinfoInheritance::Explicit || nearest_ancestor == 0 {
+                return Ok(None);
+            }
+            nearest_ancestor -= 1;
+        }
+
+        let ancestor = &chain[nearest_ancestor];
+        let Some(mergeinfo) = self.read_mergeinfo(ancestor.noderev.as_ref().unwrap())? else {
+            return Ok(None);
+        };
+
+        // If our nearest ancestor is the very path we inquired about, we
+        // can return the mergeinfo results directly.  Otherwise, we're
+        // inheriting the mergeinfo, so we need to a) remove
+        // non-inheritable ranges and b) telescope the merged-from paths.
+        if adjust_inherited_mergeinfo && nearest_ancestor != last {
+            let rel_path = fspath_skip_ancestor(&ancestor.path, &chain[last].path).unwrap();
+            return Ok(Some(append_to_merged_froms(
+                &mergeinfo_inheritable(&mergeinfo),
+                rel_path,
+            )));
+        }
+
+        Ok(Some(mergeinfo))
+    }
+
+    /// Add the explicit mergeinfo of all nodes below the directory
+    /// @a noderev at @a path to @a catalog.
+    ///
+    /// `crawl_directory_dag_for_mergeinfo`
+    fn crawl_directory_for_mergeinfo(
+        &self,
+        path: &str,
+        noderev: &NodeRevision,
+        catalog: &mut MergeinfoCatalog,
+    ) -> Result<(), BackendError> {
+        for (name, entry) in self.fs.rep_contents_dir(noderev)? {
+            let kid_path = fspath_join(path, &name);
+            let kid = self.fs.get_node_revision(&entry.id)?;
+
+            if kid.has_mergeinfo
+                && let Some(mergeinfo) = self.read_mergeinfo(&kid)?
+            {
+                catalog.insert(kid_path.clone(), mergeinfo);
+            }
+
+            if has_descendants_with_mergeinfo(&kid) {
+                self.crawl_directory_for_mergeinfo(&kid_path, &kid, catalog)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Return TRUE if nodes below @a noderev have mergeinfo.
+///
+/// `svn_fs_fs__dag_has_descendants_with_mergeinfo`
+fn has_descendants_with_mergeinfo(noderev: &NodeRevision) -> bool {
+    noderev.kind == NodeKind::Directory
+        && (noderev.mergeinfo_count > 1 || (noderev.mergeinfo_count == 1 && !noderev.has_mergeinfo))
+}
+
+/// Return the youngest copyroot of the nodes in @a chain, which is the
+/// target of the innermost copy affecting its last node.  If a parent and
+/// child were copied to in the same revision, prefer the child copy
+/// target, since it is the copy relevant to the history of the child.
+///
+/// `find_youngest_copyroot`
+fn find_youngest_copyroot(chain: &[ParentPath]) -> (RevisionNumber, String) {
+    chain.iter().filter_map(|step| step.noderev.as_ref()).fold(
+        (SVN_INVALID_REVNUM, String::new()),
+        |parent, noderev| {
+            if noderev.copyroot_rev >= parent.0 {
+                (noderev.copyroot_rev, noderev.copyroot_path.clone())
+            } else {
+                parent
+            }
+        },
+    )
+}
+
+/// The history of a node in an FSFS filesystem.
+///
+/// `fs_history_data_t`
+struct FsFsHistory<'a> {
+    /// the filesystem
+    fs: &'a FsFsBackend,
+
+    /// path of the historical location
+    path: String,
+
+    /// revision of the historical location
+    revision: RevisionNumber,
+
+    /// internal-use hint about where to resume the history search
+    path_hint: Option<String>,
+
+    /// internal-use hint about where to resume the history search
+    rev_hint: RevisionNumber,
+
+    /// FALSE until the first call to `svn_fs_history_prev()`
+    is_interesting: bool,
+}
+
+impl<'a> FsFsHistory<'a> {
+    /// Return a history object for @a path in @a revision of the same
+    /// filesystem as this one.
+    ///
+    /// `assemble_history`
+    fn assemble(
+        &self,
+        path: String,
+        revision: RevisionNumber,
+        is_interesting: bool,
+        path_hint: Option<String>,
+        rev_hint: RevisionNumber,
+    ) -> Self {
+        Self {
+            fs: self.fs,
+            path,
+            revision,
+            path_hint,
+            rev_hint,
+            is_interesting,
+        }
+    }
+
+    /// Return the history location before this one, which need not be
+    /// an interesting one, or `None` if there is none.
+    ///
+    /// `history_prev`
+    fn history_prev(&self, cross_copies: bool) -> Result<Option<Self>, BackendError> {
+        let mut path = self.path.as_str();
+        let mut revision = self.revision;
+        let mut reported = self.is_interesting;
+
+        // If our last history report left us hints about where to pickup
+        // the chase, then our last report was on the destination of a
+        // copy.  If we are crossing copies, start from those locations,
+        // otherwise, we're all done here.
+        if let Some(path_hint) = &self.path_hint
+            && self.rev_hint != SVN_INVALID_REVNUM
+        {
+            reported = false;
+            if !cross_copies {
+                return Ok(None);
+            }
+            path = path_hint;
+            revision = self.rev_hint;
+        }
+
+        // Construct a ROOT for the current revision, and open PATH there
+        // to get its node and a bunch of other goodies.
+        let root = FsFsRoot::revision_root(self.fs, revision)?;
+        let chain = root.open_parent_path(path, false)?;
+        let node = chain.last().and_then(|p| p.noderev.as_ref()).unwrap();
+        let mut commit_path = node.created_path.clone();
+        let mut commit_rev = node.id.rev();
+
+        // The Subversion filesystem is written in such a way that a given
+        // line of history may have at most one interesting history point
+        // per filesystem revision.  Either that node was edited (and
+        // possibly copied), or it was copied but not edited.  And a copy
+        // source cannot be from the same revision as its destination.  So,
+        // if our history revision matches its node's commit revision, we
+        // know that ...
+        if revision == commit_rev {
+            if !reported {
+                // ... we either have not yet reported on this revision (and
+                // need now to do so) ...
+                return Ok(Some(self.assemble(
+                    commit_path,
+                    commit_rev,
+                    true,
+                    None,
+                    SVN_INVALID_REVNUM,
+                )));
+            }
+
+            // ... or we *have* reported on this revision, and must now
+            // progress toward this node's predecessor (unless there is
+            // no predecessor, in which case we're all done!).
+            let Some(pred_id) = &node.predecessor_id else {
+                return Ok(None);
+            };
+
+            // Replace NODE and friends with the information from its
+            // predecessor.
+            let pred = self.fs.get_node_revision(pred_id)?;
+            commit_path = pred.created_path;
+            commit_rev = pred.id.rev();
+        }
+
+        // Find the youngest copyroot in the path of this node, including
+        // itself.
+        let (copyroot_rev, copyroot_path) = find_youngest_copyroot(&chain);
+
+        let mut copy_source = None;
+        if copyroot_rev > commit_rev {
+            let copyroot =
+                FsFsRoot::revision_root(self.fs, copyroot_rev)?.open_path(&copyroot_path)?;
+
+            // If our current path was the very destination of the copy,
+            // then our new current path will be the copy source.  If our
+            // current path was instead the *child* of the destination of
+            // the copy, then figure out its previous location by taking its
+            // path relative to the copy destination and appending that to
+            // the copy source.
+            if let (Some(remainder), Some(copyfrom)) = (
+                fspath_skip_ancestor(&copyroot.created_path, path),
+                &copyroot.copyfrom,
+            ) {
+                copy_source = Some((fspath_join(&copyfrom.path, remainder), copyfrom.rev));
+            }
+        }
+
+        // If we calculated a copy source path and revision, we'll make a
+        // 'copy-style' history object.
+        Ok(Some(match copy_source {
+            Some((src_path, src_rev)) => {
+                // It's possible for us to find a copy location that is the
+                // same as the history point we've just reported.  If that
+                // happens, we simply need to take another trip through this
+                // history search.
+                let retry = copyroot_rev == revision && reported;
+                self.assemble(
+                    path.to_string(),
+                    copyroot_rev,
+                    !retry,
+                    Some(src_path),
+                    src_rev,
+                )
+            }
+            None => self.assemble(commit_path, commit_rev, true, None, SVN_INVALID_REVNUM),
+        }))
+    }
+}
+
+impl<'a> HistoryTrait<'a> for FsFsHistory<'a> {
+    /// `fs_history_prev`
+    fn prev(
+        &self,
+        cross_copies: bool,
+    ) -> Result<Option<Box<dyn HistoryTrait<'a> + 'a>>, BackendError> {
+        // Special case: the root directory changes in every single
+        // revision, no exceptions.  And, the root can't be the target (or
+        // child of a target -- duh) of a copy.  So, if that's our path,
+        // then we need only decrement our revision by 1, and there you go.
+        let prev = if self.path == "/" {
+            if !self.is_interesting {
+                Some(self.assemble(
+                    "/".to_string(),
+                    self.revision,
+                    true,
+                    None,
+                    SVN_INVALID_REVNUM,
+                ))
+            } else if self.revision > 0 {
+                Some(self.assemble(
+                    "/".to_string(),
+                    self.revision - 1,
+                    true,
+                    None,
+                    SVN_INVALID_REVNUM,
+                ))
+            } else {
+                None
+            }
+        } else {
+            // Skip the locations that are not interesting.
+            let mut prev = self.history_prev(cross_copies)?;
+            while let Some(history) = &prev
+                && !history.is_interesting
+            {
+                prev = history.history_prev(cross_copies)?;
+            }
+            prev
+        };
+
+        Ok(prev.map(|history| Box::new(history) as Box<dyn HistoryTrait<'a> + 'a>))
+    }
+
+    /// `fs_history_location`
+    fn location(&self) -> (String, RevisionNumber) {
+        (self.path.clone(), self.revision)
+    }
+}
+
+impl FsFsBackend {
+    /// Merge the changes between the base revision of transaction
+    /// @a txn_id and revision @a source_rev into the transaction, so
+    /// that it becomes based on @a source_rev.  Fail with
+    /// [`BackendError::Conflict`] if the changes cannot be merged.
+    ///
+    /// `merge_changes`
+    pub(crate) fn merge_changes(
+        &self,
+        txn_id: &IdPart,
+        source_rev: RevisionNumber,
+    ) -> Result<(), BackendError> {
+        let target = self.get_node_revision(&FsId::txn_root(*txn_id))?;
+        let ancestor_id = target.predecessor_id.clone().ok_or_else(|| {
+            BackendError::Corrupt(format!(
+                "Transaction '{}' has no base revision",
+                txn_id.unparse_txn()
+            ))
+        })?;
+        let ancestor = self.get_node_revision(&ancestor_id)?;
+        let source = self.get_node_revision(&self.rev_get_root(source_rev)?)?;
+
+        self.merge("/", &target, &source, &ancestor, txn_id)?;
+        Ok(())
+    }
+
+    /// Merge the changes between @a ancestor and @a source into the
+    /// mutable directory @a target at @a target_path.  Return by how much
+    /// the mergeinfo count of @a target changed.
+    ///
+    /// `merge`
+    fn merge(
+        &self,
+        target_path: &str,
+        target: &NodeRevision,
+        source: &NodeRevision,
+        ancestor: &NodeRevision,
+        txn_id: &IdPart,
+    ) -> Result<i64, BackendError> {
+        // Base cases: Either no change made in source, or same change as
+        // made in target.  Both mean nothing to merge here.
+        if ancestor.id == source.id || source.id == target.id {
+            return Ok(0);
+        }
+
+        // Else proceed, knowing all three are distinct node revisions.
+        // Any of them not being a directory is a conflict.
+        let conflict = |path: &str| BackendError::Conflict(path.to_string());
+        if source.kind != NodeKind::Directory
+            || target.kind != NodeKind::Directory
+            || ancestor.kind != NodeKind::Directory
+        {
+            return Err(conflict(target_path));
+        }
+
+        // Possible real merge.  Property changes in the repository
+        // conflict with ours.
+        if !self.prop_rep_equal(source, ancestor)? {
+            return Err(conflict(target_path));
+        }
+
+        // There is an incoming prop change for this directory.  We will
+        // accept it only if the directory changes were mere updates to its
+        // entries, i.e. there were no additions or removals.  Those could
+        // cause update problems to the working copy.
+        if !self.prop_rep_equal(target, ancestor)?
+            && self.compare_dir_structure(source, ancestor)?
+        {
+            return Err(conflict(target_path));
+        }
+
+        let s_entries = self.rep_contents_dir(source)?;
+        let t_entries = self.rep_contents_dir(target)?;
+        let a_entries = self.rep_contents_dir(ancestor)?;
+        let mut mergeinfo_increment = 0;
+
+        // For each entry E in the ancestor ...
+        for (name, a_entry) in &a_entries {
+            let s_entry = s_entries.get(name);
+            let t_entry = t_entries.get(name);
+
+            if s_entry.is_some_and(|s| s.id == a_entry.id) {
+                // No changes were made to this entry while the transaction
+                // was in progress, so do nothing to the target.
+            } else if t_entry.is_some_and(|t| t.id == a_entry.id) {
+                // A change was made to this entry while the transaction
+                // was in process, but the transaction did not touch this
+                // entry.
+                let mergeinfo_start = self.get_node_revision(&a_entry.id)?.mergeinfo_count;
+                let mergeinfo_end = match s_entry {
+                    Some(s_entry) => self.get_node_revision(&s_entry.id)?.mergeinfo_count,
+                    None => 0,
+                };
+                mergeinfo_increment += mergeinfo_end - mergeinfo_start;
+
+                self.set_entry(txn_id, &target.id, name, s_entry.map(|s| (&s.id, s.kind)))?;
+            } else {
+                // Changes were made to this entry both within the
+                // transaction and to the repository while the transaction
+                // was in progress.  They must be merged or declared to be
+                // in conflict.
+                let entry_path = fspath_join(target_path, name);
+
+                // A double delete, a delete versus a modification or any
+                // file involved is a conflict.
+                let (Some(s_entry), Some(t_entry)) = (s_entry, t_entry) else {
+                    return Err(conflict(&entry_path));
+                };
+                if s_entry.kind == NodeKind::File
+                    || t_entry.kind == NodeKind::File
+                    || a_entry.kind == NodeKind::File
+                {
+                    return Err(conflict(&entry_path));
+                }
+
+                // If either SOURCE-ENTRY or TARGET-ENTRY is not a direct
+                // modification of ANCESTOR-ENTRY, declare a conflict.
+                let same_node = |id: &FsId| {
+                    id.node_id == a_entry.id.node_id && id.copy_id == a_entry.id.copy_id
+                };
+                if !same_node(&s_entry.id) || !same_node(&t_entry.id) {
+                    return Err(conflict(&entry_path));
+                }
+
+                // Direct modifications were made to the directory
+                // ANCESTOR-ENTRY in both SOURCE and TARGET.  Recursively
+                // merge these modifications.
+                mergeinfo_increment += self.merge(
+                    &entry_path,
+                    &self.get_node_revision(&t_entry.id)?,
+                    &self.get_node_revision(&s_entry.id)?,
+                    &self.get_node_revision(&a_entry.id)?,
+                    txn_id,
+                )?;
+            }
+        }
+
+        // For each entry E in source but not in ancestor ...
+        for (name, s_entry) in &s_entries {
+            if a_entries.contains_key(name) {
+                continue;
+            }
+
+            // If NAME exists in TARGET, declare a conflict.
+            if t_entries.contains_key(name) {
+                return Err(conflict(&fspath_join(target_path, name)));
+            }
+
+            mergeinfo_increment += self.get_node_revision(&s_entry.id)?.mergeinfo_count;
+            self.set_entry(txn_id, &target.id, name, Some((&s_entry.id, s_entry.kind)))?;
+        }
+
+        self.update_ancestry(&target.id, source)?;
+        self.increment_mergeinfo_count(&target.id, mergeinfo_increment)?;
+
+        Ok(mergeinfo_increment)
+    }
+
+    /// Return TRUE if @a a and @a b have the same properties.
+    ///
+    /// `svn_fs_fs__prop_rep_equal`
+    fn prop_rep_equal(&self, a: &NodeRevision, b: &NodeRevision) -> Result<bool, BackendError> {
+        match (&a.prop_rep, &b.prop_rep) {
+            // Committed property lists can be compared quickly.
+            (Some(rep_a), Some(rep_b))
+                if !rep_a.txn_id.is_used_txn() && !rep_b.txn_id.is_used_txn() =>
+            {
+                Ok(rep_a.md5_digest == rep_b.md5_digest)
+            }
+            (None, None) => Ok(true),
+
+            // At least one of the reps has been modified in a txn.  Fetch
+            // and compare them.
+            _ => Ok(prop_lists_equal(
+                &self.get_proplist(a)?,
+                &self.get_proplist(b)?,
+            )),
+        }
+    }
+
+    /// Return TRUE if entries have been added to or removed from the
+    /// directory @a lhs compared to @a rhs, or if any of their entries
+    /// got replaced by unrelated nodes.
+    ///
+    /// `compare_dir_structure`
+    fn compare_dir_structure(
+        &self,
+        lhs: &NodeRevision,
+        rhs: &NodeRevision,
+    ) -> Result<bool, BackendError> {
+        let lhs_entries = self.rep_contents_dir(lhs)?;
+        let rhs_entries = self.rep_contents_dir(rhs)?;
+
+        // different number of entries -> some addition / removal
+        if lhs_entries.len() != rhs_entries.len() {
+            return Ok(true);
+        }
+
+        // Since the entries are sorted by name, we can simply compare
+        // them one by one.
+        for ((lhs_name, lhs_entry), (rhs_name, rhs_entry)) in lhs_entries.iter().zip(&rhs_entries) {
+            if lhs_name != rhs_name || !lhs_entry.id.check_related(&rhs_entry.id) {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Make the node-revision @a source the predecessor of the mutable
+    /// node-revision @a target_id.
+    ///
+    /// `svn_fs_fs__dag_update_ancestry`
+    fn update_ancestry(&self, target_id: &FsId, source: &NodeRevision) -> Result<(), BackendError> {
+        if !target_id.is_txn() {
+            return Err(BackendError::Corrupt(format!(
+                "Attempted to update ancestry of non-mutable node '{target_id}'"
+            )));
+        }
+
+        let mut target = self.get_node_revision(target_id)?;
+        target.predecessor_id = Some(source.id.clone());
+        target.predecessor_count = source.predecessor_count;
+        if target.predecessor_count != -1 {
+            target.predecessor_count += 1;
+        }
+        self.put_node_revision(&target)
+    }
+
+    /// Add @a increment to the mergeinfo count of the mutable
+    /// node-revision @a id.
+    ///
+    /// `svn_fs_fs__dag_increment_mergeinfo_count`
+    fn increment_mergeinfo_count(&self, id: &FsId, increment: i64) -> Result<(), BackendError> {
+        if !id.is_txn() {
+            return Err(BackendError::Corrupt(format!(
+                "Can't increment mergeinfo count on *immutable* node-revision {id}"
+            )));
+        }
+        if increment == 0 {
+            return Ok(());
+        }
+
+        let mut noderev = self.get_node_revision(id)?;
+        noderev.mergeinfo_count += increment;
+        if noderev.mergeinfo_count < 0 {
+            return Err(BackendError::Corrupt(format!(
+                "Can't increment mergeinfo count on node-revision {id} to negative value {}",
+                noderev.mergeinfo_count
+            )));
+        }
+        if noderev.mergeinfo_count > 1 && noderev.kind == NodeKind::File {
+            return Err(BackendError::Corrupt(format!(
+                "Can't increment mergeinfo count on *file* node-revision {id} to {} (> 1)",
+                noderev.mergeinfo_count
+            )));
+        }
+        self.put_node_revision(&noderev)
+    }
+}
+
+impl RootTrait for FsFsRoot<'_> {
+    /// `fs_paths_changed`
+    fn paths_changed(&self) -> Result<Vec<PathChange>, BackendError> {
+        match &self.txn {
+            Some(txn_id) => Ok(self.fs.txn_changes_fetch(txn_id)?.into_values().collect()),
+            None => self.fs.get_changes(self.rev),
+        }
+    }
+
+    /// `fs_check_path`
+    fn check_path(&self, path: &str) -> Result<NodeKind, BackendError> {
+        match self.open_path(path) {
+            Ok(noderev) => Ok(noderev.kind),
+            Err(BackendError::FileNotFound(_) | BackendError::NotDirectory(_)) => {
+                Ok(NodeKind::None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// `fs_node_id`
+    fn node_id(&self, path: &str) -> Result<FsId, BackendError> {
+        Ok(self.open_path(path)?.id)
+    }
+
+    /// `fs_node_created_rev`
+    fn node_created_rev(&self, path: &str) -> Result<RevisionNumber, BackendError> {
+        Ok(self.open_path(path)?.id.rev())
+    }
+
+    /// `fs_copied_from`
+    fn copied_from(&self, path: &str) -> Result<Option<CopyFromInfo>, BackendError> {
+        Ok(self.open_path(path)?.copyfrom)
+    }
+
+    /// `fs_node_history`
+    fn node_history(&self, path: &str) -> Result<Box<dyn HistoryTrait<'_> + '_>, BackendError> {
+        // We require a revision root.
+        if self.txn.is_some() {
+            return Err(BackendError::NotRevisionRoot);
+        }
+
+        // And we require that the path exist in the root.
+        if self.check_path(path)? == NodeKind::None {
+            return Err(self.not_found(path));
+        }
+
+        Ok(Box::new(FsFsHistory {
+            fs: self.fs,
+            path: canonicalize_abspath(path),
+            revision: self.rev,
+            path_hint: None,
+            rev_hint: SVN_INVALID_REVNUM,
+            is_interesting: false,
+        }))
+    }
+
+    /// `fs_closest_copy`
+    fn closest_copy(&self, path: &str) -> Result<Option<(RevisionNumber, String)>, BackendError> {
+        let path = canonicalize_abspath(path);
+        let chain = self.open_parent_path(&path, false)?;
+        let node = chain.last().and_then(|p| p.noderev.as_ref()).unwrap();
+
+        // Find the youngest copyroot in the path of this node-rev, which
+        // will indicate the target of the innermost copy affecting the
+        // node-rev.
+        let (copy_dst_rev, copy_dst_path) = find_youngest_copyroot(&chain);
+        if copy_dst_rev <= 0 {
+            // There are no copies affecting this node-rev.
+            return Ok(None);
+        }
+
+        // It is possible that this node was created from scratch at some
+        // revision between COPY_DST_REV and REV.  Make sure that PATH
+        // exists as of COPY_DST_REV and is related to this node-rev.
+        let copy_dst_root = FsFsRoot::revision_root(self.fs, copy_dst_rev)?;
+        let copy_dst_node = match copy_dst_root.open_path(&path) {
+            Ok(noderev) => noderev,
+            Err(BackendError::FileNotFound(_) | BackendError::NotDirectory(_)) => {
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        if !copy_dst_node.id.check_related(&node.id) {
+            return Ok(None);
+        }
+
+        // One final check must be done here.  If you copy a directory and
+        // create a new entity somewhere beneath that directory in the same
+        // txn, then we can't claim that the copy affected the new entity.
+        // We detect this situation by asking if PATH@COPY_DST_REV's
+        // created-rev is COPY_DST_REV, and that node-revision has no
+        // predecessors, then there is no relevant closest copy.
+        if copy_dst_node.id.rev() == copy_dst_rev && copy_dst_node.predecessor_id.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some((copy_dst_rev, copy_dst_path)))
+    }
+
+    /// `fs_node_origin_rev`
+    fn node_origin_rev(&self, path: &str) -> Result<RevisionNumber, BackendError> {
+        let path = canonicalize_abspath(path);
+        let node_id = self.open_path(&path)?.id.node_id;
+
+        // Is it a brand new uncommitted node or a new-style node ID?
+        // (committed old-style nodes will have a zero revision value;
+        // rev 0, number 0 is rev 0 root node).  Note that != 0 includes
+        // SVN_INVALID_REVNUM for uncommitted nodes.
+        if node_id.revision != 0 || node_id.number == 0 {
+            return Ok(node_id.revision);
+        }
+
+        // OK, it's an old-style ID?  Maybe it's cached.
+        if let Some(origin_id) = self.fs.get_node_origin(&node_id)? {
+            return Ok(origin_id.rev());
+        }
+
+        // Ah well, the answer isn't in the ID itself or in the cache.
+        // Let's actually calculate it, then.  Walk the closest-copy chain
+        // back to the first copy in our history.
+        let mut root = self.clone();
+        let mut path = path;
+        while let Some((copy_rev, copy_path)) = root.closest_copy(&path)? {
+            // Ultimately, it's not the path of the closest copy's source
+            // that we care about -- it's our own path's location in the
+            // copy source revision.
+            let copyfrom = FsFsRoot::revision_root(self.fs, copy_rev)?
+                .open_path(&copy_path)?
+                .copyfrom
+                .ok_or_else(|| {
+                    BackendError::Corrupt(format!(
+                        "Copy root '{copy_path}' in revision {copy_rev} has no copy source"
+                    ))
+                })?;
+            let remainder = fspath_skip_ancestor(&copy_path, &path).unwrap_or_default();
+            path = fspath_join(&copyfrom.path, remainder);
+            root = FsFsRoot::revision_root(self.fs, copyfrom.rev)?;
+        }
+
+        // Walk the predecessor links back to origin.
+        let mut node = root.open_path(&path)?;
+        while let Some(pred_id) = &node.predecessor_id {
+            node = self.fs.get_node_revision(pred_id)?;
+        }
+
+        // Wow, I don't want to have to do all that again.  Let's cache
+        // the result.
+        if node_id.revision != SVN_INVALID_REVNUM {
+            self.fs.set_node_origin(&node_id, &node.id)?;
+        }
+
+        Ok(node.id.rev())
+    }
+
+    /// `fs_node_proplist`
+    fn node_proplist(&self, path: &str) -> Result<HashMap<String, String>, BackendError> {
+        let noderev = self.open_path(path)?;
+        self.fs.get_proplist(&noderev)
+    }
+
+    /// `fs_get_mergeinfo`
+    fn get_mergeinfo(
+        &self,
+        paths: &[&str],
+        inherit: MergeinfoInheritance,
+        include_descendants: bool,
+        adjust_inherited_mergeinfo: bool,
+    ) -> Result<MergeinfoCatalog, BackendError> {
+        // We require a revision root.
+        if self.txn.is_some() {
+            return Err(BackendError::NotRevisionRoot);
+        }
+
+        let mut catalog = MergeinfoCatalog::new();
+        for path in paths {
+            let path = canonicalize_abspath(path);
+            if let Some(mergeinfo) =
+                self.get_mergeinfo_for_path(&path, inherit, adjust_inherited_mergeinfo)?
+            {
+                catalog.insert(path.clone(), mergeinfo);
+            }
+
+            if include_descendants {
+                let noderev = self.open_path(&path)?;
+                if has_descendants_with_mergeinfo(&noderev) {
+                    self.crawl_directory_for_mergeinfo(&path, &noderev, &mut catalog)?;
+                }
+            }
+        }
+
+        Ok(catalog)
+    }
+
+    /// `fs_dir_entries`
+    fn dir_entries(&self, path: &str) -> Result<BTreeMap<String, DirectoryEntry>, BackendError> {
+        let noderev = self.open_path(path)?;
+        if noderev.kind != NodeKind::Directory {
+            return Err(BackendError::NotDirectory(path.to_string()));
+        }
+        self.fs.rep_contents_dir(&noderev)
+    }
+
+    /// `fs_file_length`
+    fn file_length(&self, path: &str) -> Result<u64, BackendError> {
+        let noderev = self.open_file(path)?;
+        Ok(match noderev.data_rep {
+            Some(mut rep) => {
+                fixup_expanded_size(&mut rep);
+                rep.expanded_size
+            }
+            None => 0,
+        })
+    }
+
+    /// `fs_file_checksum`
+    fn file_checksum(
+        &self,
+        kind: ChecksumKind,
+        path: &str,
+    ) -> Result<Option<Checksum>, BackendError> {
+        let noderev = self.open_file(path)?;
+        let Some(rep) = noderev.data_rep else {
+            // Files without a data rep are empty.
+            return Ok(Some(Checksum::compute(kind, b"")));
+        };
+
+        Ok(match kind {
+            ChecksumKind::Md5 => rep.md5_digest,
+            ChecksumKind::Sha1 => rep.sha1_digest,
+            _ => None,
+        })
+    }
+
+    /// `fs_file_contents`
+    fn file_contents(&self, path: &str) -> Result<Box<dyn Read + 'static>, BackendError> {
+        let noderev = self.open_file(path)?;
+        let reader = self.fs.read_representation(noderev.data_rep.as_ref())?;
+        Ok(Box::new(reader))
+    }
+
+    /// `fs_make_dir`
+    fn make_dir(&self, path: &str) -> Result<(), BackendError> {
+        self.make_entry(path, NodeKind::Directory)
+    }
+
+    /// `fs_make_file`
+    fn make_file(&self, path: &str) -> Result<(), BackendError> {
+        self.make_entry(path, NodeKind::File)
+    }
+
+    /// `fs_delete_node`
+    fn delete_node(&self, path: &str) -> Result<(), BackendError> {
+        let txn_id = self.txn_id()?;
+        let mut chain = self.open_parent_path(path, false)?;
+        let path = chain.last().unwrap().path.clone();
+
+        // We can't remove the root of the filesystem.
+        let (last, parents) = chain.split_last_mut().unwrap();
+        if parents.is_empty() {
+            return Err(BackendError::RootDir);
+        }
+        let noderev = last.noderev.as_ref().unwrap();
+
+        // Make the parent directory mutable, and do the deletion.
+        self.make_path_mutable(parents, &path)?;
+        let parent = parents.last().and_then(|p| p.noderev.as_ref()).unwrap();
+        self.fs.set_entry(&txn_id, &parent.id, &last.entry, None)?;
+        self.delete_if_mutable(&noderev.id)?;
+        self.increment_mergeinfo_up_tree(parents, -noderev.mergeinfo_count)?;
+
+        self.fs.add_change(
+            &txn_id,
+            &PathChange {
+                path,
+                node_rev_id: Some(noderev.id.clone()),
+                change_kind: PathChangeKind::Delete,
+                node_kind: noderev.kind,
+                text_mod: false,
+                prop_mod: false,
+                mergeinfo_mod: Tristate::Unknown,
+                copyfrom: None,
+            },
+        )
+    }
+
+    /// `fs_copy` / `copy_helper`
+    fn copy(
+        &self,
+        from_rev: RevisionNumber,
+        from_path: &str,
+        to_path: &str,
+    ) -> Result<(), BackendError> {
+        let txn_id = self.txn_id()?;
+        let from_path = canonicalize_abspath(from_path);
+        let from = FsFsRoot::revision_root(self.fs, from_rev)?.open_path(&from_path)?;
+        let mut chain = self.open_parent_path(to_path, true)?;
+        let to_path = chain.last().unwrap().path.clone();
+        let (last, parents) = chain.split_last_mut().unwrap();
+        if parents.is_empty() {
+            return Err(BackendError::RootDir);
+        }
+
+        // If the destination node already exists as the same node as the
+        // source (in other words, this operation would result in nothing
+        // happening at all), just do nothing an return successfully, proud
+        // that you saved yourself from a tiresome task.
+        let change_kind = match &last.noderev {
+            Some(noderev) if noderev.id == from.id => return Ok(()),
+            // If TO_PATH already existed prior to the copy, note that this
+            // operation is a replacement, not an addition.
+            Some(_) => PathChangeKind::Replace,
+            None => PathChangeKind::Add,
+        };
+        let mergeinfo_start = last.noderev.as_ref().map_or(0, |n| n.mergeinfo_count);
+
+        self.make_path_mutable(parents, &to_path)?;
+        let parent = parents.last().and_then(|p| p.noderev.as_ref()).unwrap();
+
+        // Create a successor of the source node that remembers where it
+        // has been copied from and is its own copy root.
+        let mut noderev = from.clone();
+        noderev.predecessor_id = Some(from.id.clone());
+        if noderev.predecessor_count != -1 {
+            noderev.predecessor_count += 1;
+        }
+        noderev.created_path = fspath_join(&parent.created_path, &last.entry);
+        noderev.copyfrom = Some(CopyFromInfo {
+            rev: from_rev,
+            path: from_path.clone(),
+        });
+        noderev.copyroot_rev = SVN_INVALID_REVNUM;
+        noderev.copyroot_path = noderev.created_path.clone();
+        noderev.is_fresh_txn_root = false;
+
+        let copy_id = self.fs.reserve_copy_id(&txn_id)?;
+        let id = self
+            .fs
+            .create_successor(&from.id, &mut noderev, Some(copy_id), &txn_id)?;
+        self.fs
+            .set_entry(&txn_id, &parent.id, &last.entry, Some((&id, from.kind)))?;
+        self.increment_mergeinfo_up_tree(parents, from.mergeinfo_count - mergeinfo_start)?;
+
+        self.fs.add_change(
+            &txn_id,
+            &PathChange {
+                path: to_path,
+                node_rev_id: Some(id),
+                change_kind,
+                node_kind: from.kind,
+                text_mod: false,
+                prop_mod: false,
+                mergeinfo_mod: Tristate::Unknown,
+                copyfrom: Some(CopyFromInfo {
+                    rev: from_rev,
+                    path: from_path,
+                }),
+            },
+        )
+    }
+
+    /// `fs_change_node_prop`
+    fn change_node_prop(
+        &self,
+        path: &str,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), BackendError> {
+        let txn_id = self.txn_id()?;
+        let mut chain = self.open_parent_path(path, false)?;
+        let path = chain.last().unwrap().path.clone();
+        self.make_path_mutable(&mut chain, &path)?;
+        let noderev = chain.last().and_then(|p| p.noderev.as_ref()).unwrap();
+
+        // Keep track of the nodes having mergeinfo.
+        if name == SVN_PROP_MERGEINFO && value.is_some() != noderev.has_mergeinfo {
+            self.increment_mergeinfo_up_tree(&chain, if value.is_some() { 1 } else { -1 })?;
+            let mut updated = self.fs.get_node_revision(&noderev.id)?;
+            updated.has_mergeinfo = value.is_some();
+            self.fs.put_node_revision(&updated)?;
+        }
+
+        let mut proplist = self.fs.get_proplist(noderev)?;
+        match value {
+            Some(value) => proplist.insert(name.to_string(), value.to_string()),
+            None => proplist.remove(name),
+        };
+        self.fs.set_proplist(&noderev.id, &proplist)?;
+
+        self.fs.add_change(
+            &txn_id,
+            &PathChange {
+                path,
+                node_rev_id: Some(noderev.id.clone()),
+                change_kind: PathChangeKind::Modify,
+                node_kind: noderev.kind,
+                text_mod: false,
+                prop_mod: true,
+                mergeinfo_mod: if name == SVN_PROP_MERGEINFO {
+                    Tristate::True
+                } else {
+                    Tristate::False
+                },
+                copyfrom: None,
+            },
+        )
+    }
+
+    /// `fs_apply_text`
+    fn apply_text(
+        &self,
+        path: &str,
+        contents: &[u8],
+        result_checksum: Option<&Checksum>,
+    ) -> Result<(), BackendError> {
+        let txn_id = self.txn_id()?;
+        let mut chain = self.open_parent_path(path, false)?;
+        let path = chain.last().unwrap().path.clone();
+
+        // Now, make sure this path is mutable.
+        self.make_path_mutable(&mut chain, &path)?;
+        let mut noderev = chain.pop().and_then(|p| p.noderev).unwrap();
+        if noderev.kind != NodeKind::File {
+            return Err(BackendError::NotFile(path));
+        }
+
+        // Write the new text and make sure it is what the caller expects.
+        let rep = self.fs.write_text_rep(&noderev, contents)?;
+        if let (Some(expected), Some(actual)) = (result_checksum, &rep.md5_digest) {
+            expected.verify(actual)?;
+        }
+        noderev.data_rep = Some(rep);
+        self.fs.put_node_revision(&noderev)?;
+
+        self.fs.add_change(
+            &txn_id,
+            &PathChange {
+                path,
+                node_rev_id: Some(noderev.id),
+                change_kind: PathChangeKind::Modify,
+                node_kind: NodeKind::File,
+                text_mod: true,
+                prop_mod: false,
+                mergeinfo_mod: Tristate::Unknown,
+                copyfrom: None,
+            },
+        )
+    }
+}
@@ -0,0 +1,120 @@
+//! Batched reads of rev / pack files through io-uring.
+//!
+//! This has no C counterpart.  Each thread sets up its own ring on first
+//! use; where that is not possible, e.g. on older kernels or when io-uring
+//! is blocked by a seccomp filter, callers fall back to plain `pread`s.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io;
+use std::os::fd::AsRawFd;
+
+use io_uring::IoUring;
+use io_uring::opcode;
+use io_uring::types;
+
+/// The number of reads submitted to the kernel at once.
+const QUEUE_DEPTH: u32 = 64;
+
+thread_local! {
+    /// The ring of this thread, `None` if io-uring is not available.
+    static RING: RefCell<Option<IoUring>> = RefCell::new(IoUring::new(QUEUE_DEPTH).ok());
+}
+
+/// Read the @a len bytes at @a offset of @a file for each (offset, len)
+/// pair in @a blocks, submitting up to @a QUEUE_DEPTH reads at once.
+///
+/// Return `None` if io-uring is not available, so that the caller has to
+/// read the blocks by other means.
+pub(crate) fn read_blocks(
+    file: &File,
+    blocks: &[(u64, usize)],
+) -> Option<io::Result<Vec<Vec<u8>>>> {
+    RING.with_borrow_mut(|slot| {
+        let ring = slot.as_mut()?;
+        let mut bufs: Vec<Vec<u8>> = blocks.iter().map(|&(_, len)| vec![0; len]).collect();
+        match read_into(ring, file, blocks, &mut bufs) {
+            Ok(result) => Some(result.map(|()| bufs)),
+            Err(_) => {
+                // The kernel may still write to buffers it has been handed.
+                // Leak them and stop using this ring.
+                std::mem::forget(bufs);
+                std::mem::forget(slot.take());
+                None
+            }
+        }
+    })
+}
+
+/// Fill @a bufs with the data of @a blocks in @a file using @a ring.
+///
+/// The outer error means that the ring itself failed and may still own
+/// some of the buffers; the inner one is an I/O error of the reads.
+fn read_into(
+    ring: &mut IoUring,
+    file: &File,
+    blocks: &[(u64, usize)],
+    bufs: &mut [Vec<u8>],
+) -> io::Result<io::Result<()>> {
+    let fd = types::Fd(file.as_raw_fd());
+    let mut done = vec![0usize; blocks.len()];
+    let mut pending: Vec<usize> = (0..blocks.len()).filter(|&i| blocks[i].1 > 0).collect();
+    let mut result = Ok(());
+
+    while !pending.is_empty() {
+        for batch in pending.chunks(QUEUE_DEPTH as usize) {
+            for &i in batch {
+                let rest = &mut bufs[i][done[i]..];
+                let len = rest.len().min(u32::MAX as usize) as u32;
+                let entry = opcode::Read::new(fd, rest.as_mut_ptr(), len)
+                    .offset(blocks[i].0 + done[i] as u64)
+                    .build()
+                    .user_data(i as u64);
+
+                // SAFETY: the buffer outlives the read; we either wait for
+                // its completion below or leak it.
+                unsafe { ring.submission().push(&entry) }
+                    .map_err(|_| io::Error::other("io-uring submission queue is full"))?;
+            }
+
+            let mut completed = 0;
+            while completed < batch.len() {
+                match ring.submit_and_wait(batch.len() - completed) {
+                    Ok(_) => {}
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+
+                for entry in ring.completion() {
+                    completed += 1;
+                    let i = entry.user_data() as usize;
+                    match entry.result() {
+                        res if res < 0 => {
+                            let err = io::Error::from_raw_os_error(-res);
+                            if !matches!(
+                                err.kind(),
+                                io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock
+                            ) {
+                                result = result.and(Err(err));
+                                done[i] = blocks[i].1;
+                            }
+                        }
+                        0 => {
+                            result = result.and(Err(io::ErrorKind::UnexpectedEof.into()));
+                            done[i] = blocks[i].1;
+                        }
+                        res => done[i] += res as usize,
+                    }
+                }
+            }
+        }
+
+        // Resubmit whatever has been read only partially.
+        pending.retain(|&i| done[i] < blocks[i].1);
+        if result.is_err() {
+            break;
+        }
+    }
+
+    Ok(result)
+}
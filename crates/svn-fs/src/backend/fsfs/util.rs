@@ -0,0 +1,414 @@
+//! `subversion/libsvn_fs_fs/util.c`
+
+use std::path::PathBuf;
+
+use svn_subr::Checksum;
+use svn_types::RevisionNumber;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::SVN_FS_FS__MIN_PROTOREVS_DIR_FORMAT;
+use crate::backend::PATH_CHANGES;
+use crate::backend::PATH_CURRENT;
+use crate::backend::PATH_EXT_CHILDREN;
+use crate::backend::PATH_EXT_P2L_INDEX;
+use crate::backend::PATH_EXT_PACKED_SHARD;
+use crate::backend::PATH_EXT_PROPS;
+use crate::backend::PATH_EXT_REV;
+use crate::backend::PATH_EXT_REV_LOCK;
+use crate::backend::PATH_EXT_TXN;
+use crate::backend::PATH_FORMAT;
+use crate::backend::PATH_INDEX;
+use crate::backend::PATH_LOCK_FILE;
+use crate::backend::PATH_MIN_UNPACKED_REV;
+use crate::backend::PATH_NEXT_IDS;
+use crate::backend::PATH_NODE_ORIGINS_DIR;
+use crate::backend::PATH_PACK_LOCK_FILE;
+use crate::backend::PATH_PACKED;
+use crate::backend::PATH_PREFIX_NODE;
+use crate::backend::PATH_REV;
+use crate::backend::PATH_REV_LOCK;
+use crate::backend::PATH_REVPROP_GENERATION;
+use crate::backend::PATH_REVPROPS_DIR;
+use crate::backend::PATH_REVS_DIR;
+use crate::backend::PATH_TXN_CURRENT;
+use crate::backend::PATH_TXN_CURRENT_LOCK;
+use crate::backend::PATH_TXN_ITEM_INDEX;
+use crate::backend::PATH_TXN_PROPS;
+use crate::backend::PATH_TXN_PROTOS_DIR;
+use crate::backend::PATH_TXNS_DIR;
+use crate::backend::PATH_UUID;
+use crate::id::FsId;
+use crate::id::IdPart;
+use crate::id::u64_to_base36;
+
+/// paths
+impl FsFsBackend {
+    /// `svn_fs_fs__path_format`
+    pub(crate) fn path_format(&self) -> PathBuf {
+        self.path.join(PATH_FORMAT)
+    }
+
+    /// `svn_fs_fs__path_uuid`
+    pub(crate) fn path_uuid(&self) -> PathBuf {
+        self.path.join(PATH_UUID)
+    }
+
+    /// `svn_fs_fs__path_current`
+    pub(crate) fn path_current(&self) -> PathBuf {
+        self.path.join(PATH_CURRENT)
+    }
+
+    /// `svn_fs_fs__path_txn_current`
+    pub(crate) fn path_txn_current(&self) -> PathBuf {
+        self.path.join(PATH_TXN_CURRENT)
+    }
+
+    /// `svn_fs_fs__path_txn_current_lock`
+    pub(crate) fn path_txn_current_lock(&self) -> PathBuf {
+        self.path.join(PATH_TXN_CURRENT_LOCK)
+    }
+
+    /// `svn_fs_fs__path_lock`
+    pub(crate) fn path_lock(&self) -> PathBuf {
+        self.path.join(PATH_LOCK_FILE)
+    }
+
+    /// `svn_fs_fs__path_pack_lock`
+    pub(crate) fn path_pack_lock(&self) -> PathBuf {
+        self.path.join(PATH_PACK_LOCK_FILE)
+    }
+
+    /// `svn_fs_fs__path_min_unpacked_rev`
+    pub(crate) fn path_min_unpacked_rev(&self) -> PathBuf {
+        self.path.join(PATH_MIN_UNPACKED_REV)
+    }
+
+    /// `svn_fs_fs__path_revprop_generation`
+    pub(crate) fn path_revprop_generation(&self) -> PathBuf {
+        self.path.join(PATH_REVPROP_GENERATION)
+    }
+
+    /// `svn_fs_fs__path_txns_dir`
+    pub(crate) fn path_txns_dir(&self) -> PathBuf {
+        self.path.join(PATH_TXNS_DIR)
+    }
+
+    /// `svn_fs_fs__path_txn_proto_revs`
+    pub(crate) fn path_txn_proto_revs(&self) -> PathBuf {
+        self.path.join(PATH_TXN_PROTOS_DIR)
+    }
+
+    /// Return the full path of the rev shard directory that will contain
+    /// revision REV in FS.
+    ///
+    /// `svn_fs_fs__path_rev_shard`
+    pub(crate) fn path_rev_shard(&self, rev: RevisionNumber) -> PathBuf {
+        let ffd = self._data();
+
+        assert!(ffd.max_files_per_dir > 0, "max_files_per_dir must be set");
+        self.path
+            .join(PATH_REVS_DIR)
+            .join((rev / ffd.max_files_per_dir as i64).to_string())
+    }
+
+    /// Return the full path of the non-packed rev file containing revision
+    /// REV in FS.
+    ///
+    /// `svn_fs_fs__path_rev`
+    pub(crate) fn path_rev(&self, rev: RevisionNumber) -> PathBuf {
+        assert!(!self.is_packed_rev(rev), "r{rev} is packed");
+
+        if self._data().max_files_per_dir > 0 {
+            self.path_rev_shard(rev).join(rev.to_string())
+        } else {
+            self.path.join(PATH_REVS_DIR).join(rev.to_string())
+        }
+    }
+
+    /// Return the path of the file storing the KIND data for the pack file
+    /// containing revision REV in FS.
+    ///
+    /// `svn_fs_fs__path_rev_packed`
+    pub(crate) fn path_rev_packed(&self, rev: RevisionNumber, kind: &str) -> PathBuf {
+        let ffd = self._data();
+
+        assert!(ffd.max_files_per_dir > 0, "max_files_per_dir must be set");
+        assert!(self.is_packed_rev(rev), "r{rev} is not packed");
+        self.path_rev_pack_shard(rev).join(kind)
+    }
+
+    /// Return the path of the pack directory of the shard containing
+    /// revision REV in FS, whether or not that shard has been packed
+    /// already.
+    pub(crate) fn path_rev_pack_shard(&self, rev: RevisionNumber) -> PathBuf {
+        self.path.join(PATH_REVS_DIR).join(format!(
+            "{}{PATH_EXT_PACKED_SHARD}",
+            rev / self._data().max_files_per_dir as i64
+        ))
+    }
+
+    /// Return the path of the file containing revision REV in FS, i.e.
+    /// either its pack file or its non-packed rev file.
+    ///
+    /// `svn_fs_fs__path_rev_absolute`
+    pub(crate) fn path_rev_absolute(&self, rev: RevisionNumber) -> PathBuf {
+        if self.is_packed_rev(rev) {
+            self.path_rev_packed(rev, PATH_PACKED)
+        } else {
+            self.path_rev(rev)
+        }
+    }
+
+    /// Return the path of the transaction directory for TXN_ID in FS.
+    ///
+    /// `svn_fs_fs__path_txn_dir`
+    pub(crate) fn path_txn_dir(&self, txn_id: &IdPart) -> PathBuf {
+        self.path_txns_dir()
+            .join(format!("{}{PATH_EXT_TXN}", txn_id.unparse_txn()))
+    }
+
+    /// Return the path of the file storing the node-revision ID in its
+    /// transaction.
+    ///
+    /// `svn_fs_fs__path_txn_node_rev`
+    pub(crate) fn path_txn_node_rev(&self, id: &FsId) -> PathBuf {
+        self.path_txn_dir(&id.txn_id).join(format!(
+            "{PATH_PREFIX_NODE}{}.{}",
+            id.node_id.unparse(),
+            id.copy_id.unparse()
+        ))
+    }
+
+    /// Return the path of the proto-revision file of transaction TXN_ID.
+    ///
+    /// `svn_fs_fs__path_txn_proto_rev`
+    pub(crate) fn path_txn_proto_rev(&self, txn_id: &IdPart) -> PathBuf {
+        if self._data().format >= SVN_FS_FS__MIN_PROTOREVS_DIR_FORMAT {
+            self.path_txn_proto_revs()
+                .join(format!("{}{PATH_EXT_REV}", txn_id.unparse_txn()))
+        } else {
+            self.path_txn_dir(txn_id).join(PATH_REV)
+        }
+    }
+
+    /// Return the path of the lock file of the proto-revision file of
+    /// transaction TXN_ID.
+    ///
+    /// `svn_fs_fs__path_txn_proto_rev_lock`
+    pub(crate) fn path_txn_proto_rev_lock(&self, txn_id: &IdPart) -> PathBuf {
+        if self._data().format >= SVN_FS_FS__MIN_PROTOREVS_DIR_FORMAT {
+            self.path_txn_proto_revs()
+                .join(format!("{}{PATH_EXT_REV_LOCK}", txn_id.unparse_txn()))
+        } else {
+            self.path_txn_dir(txn_id).join(PATH_REV_LOCK)
+        }
+    }
+
+    /// Return the path of the changes file of transaction TXN_ID.
+    ///
+    /// `path_txn_changes`
+    pub(crate) fn path_txn_changes(&self, txn_id: &IdPart) -> PathBuf {
+        self.path_txn_dir(txn_id).join(PATH_CHANGES)
+    }
+
+    /// Return the path of the transaction properties file of TXN_ID.
+    ///
+    /// `path_txn_props`
+    pub(crate) fn path_txn_props(&self, txn_id: &IdPart) -> PathBuf {
+        self.path_txn_dir(txn_id).join(PATH_TXN_PROPS)
+    }
+
+    /// Return the path of the file holding the next node and copy IDs of
+    /// transaction TXN_ID.
+    ///
+    /// `path_txn_next_ids`
+    pub(crate) fn path_txn_next_ids(&self, txn_id: &IdPart) -> PathBuf {
+        self.path_txn_dir(txn_id).join(PATH_NEXT_IDS)
+    }
+
+    /// Return the path of the file holding the next item index of
+    /// transaction TXN_ID.
+    ///
+    /// `svn_fs_fs__path_txn_item_index`
+    pub(crate) fn path_txn_item_index(&self, txn_id: &IdPart) -> PathBuf {
+        self.path_txn_dir(txn_id).join(PATH_TXN_ITEM_INDEX)
+    }
+
+    /// Return the path of the proto phys-to-log index of transaction
+    /// TXN_ID.
+    ///
+    /// `svn_fs_fs__path_p2l_proto_index`
+    pub(crate) fn path_p2l_proto_index(&self, txn_id: &IdPart) -> PathBuf {
+        self.path_txn_dir(txn_id)
+            .join(format!("{PATH_INDEX}{PATH_EXT_P2L_INDEX}"))
+    }
+
+    /// Return the path of the file mapping the SHA1 checksum SHA1 to a
+    /// representation written in transaction TXN_ID.
+    ///
+    /// `path_txn_sha1`
+    pub(crate) fn path_txn_sha1(&self, txn_id: &IdPart, sha1: &Checksum) -> PathBuf {
+        self.path_txn_dir(txn_id).join(sha1.to_hex())
+    }
+
+    /// Return the path of the file storing the properties of the
+    /// node-revision ID in its transaction.
+    ///
+    /// `svn_fs_fs__path_txn_node_props`
+    pub(crate) fn path_txn_node_props(&self, id: &FsId) -> PathBuf {
+        let mut path = self.path_txn_node_rev(id).into_os_string();
+        path.push(PATH_EXT_PROPS);
+        path.into()
+    }
+
+    /// Return the path of the file storing the entries of the directory
+    /// node-revision ID in its transaction.
+    ///
+    /// `svn_fs_fs__path_txn_node_children`
+    pub(crate) fn path_txn_node_children(&self, id: &FsId) -> PathBuf {
+        let mut path = self.path_txn_node_rev(id).into_os_string();
+        path.push(PATH_EXT_CHILDREN);
+        path.into()
+    }
+
+    /// Return the full path of the revprop shard directory that will
+    /// contain revision REV in FS.
+    ///
+    /// `svn_fs_fs__path_revprops_shard`
+    pub(crate) fn path_revprops_shard(&self, rev: RevisionNumber) -> PathBuf {
+        let ffd = self._data();
+
+        assert!(ffd.max_files_per_dir > 0, "max_files_per_dir must be set");
+        self.path
+            .join(PATH_REVPROPS_DIR)
+            .join((rev / ffd.max_files_per_dir as i64).to_string())
+    }
+
+    /// Return the full path of the packed revprop shard directory that
+    /// will contain revision REV in FS.
+    ///
+    /// `svn_fs_fs__path_revprops_pack_shard`
+    pub(crate) fn path_revprops_pack_shard(&self, rev: RevisionNumber) -> PathBuf {
+        let ffd = self._data();
+
+        assert!(ffd.max_files_per_dir > 0, "max_files_per_dir must be set");
+        self.path.join(PATH_REVPROPS_DIR).join(format!(
+            "{}{PATH_EXT_PACKED_SHARD}",
+            rev / ffd.max_files_per_dir as i64
+        ))
+    }
+
+    /// Return the full path of the non-packed revision properties file
+    /// that contains the props for revision REV in FS.
+    ///
+    /// `svn_fs_fs__path_revprops`
+    pub(crate) fn path_revprops(&self, rev: RevisionNumber) -> PathBuf {
+        if self._data().max_files_per_dir > 0 {
+            self.path_revprops_shard(rev).join(rev.to_string())
+        } else {
+            self.path.join(PATH_REVPROPS_DIR).join(rev.to_string())
+        }
+    }
+
+    /// Return the path of the node-origins cache file that holds the
+    /// origin of @a node_id: the file is named after the node ID without
+    /// its last base36 digit.
+    ///
+    /// `path_node_origin`
+    pub(crate) fn path_node_origin(&self, node_id: &IdPart) -> PathBuf {
+        let mut name = u64_to_base36(node_id.number);
+        if name.len() > 1 {
+            name.pop();
+        } else {
+            name = "0".to_string();
+        }
+        self.path.join(PATH_NODE_ORIGINS_DIR).join(name)
+    }
+
+    /// Return TRUE is REV is packed in FS, FALSE otherwise.
+    ///
+    /// `svn_fs_fs__is_packed_rev`
+    pub(crate) fn is_packed_rev(&self, rev: RevisionNumber) -> bool {
+        let ffd = self._data();
+        rev < ffd.min_unpacked_rev
+    }
+}
+
+/// `min-unpacked-rev` file access
+impl FsFsBackend {
+    /// Return the integer value read from the file returned by
+    /// [`FsFsBackend::path_min_unpacked_rev`] for FS.
+    ///
+    /// `svn_fs_fs__read_min_unpacked_rev`
+    pub(crate) fn read_min_unpacked_rev(&self) -> Result<RevisionNumber, BackendError> {
+        let path = self.path_min_unpacked_rev();
+        let content = fs_err::read_to_string(&path)?;
+
+        content
+            .trim()
+            .parse()
+            .map_err(|_| BackendError::CorruptFile {
+                path: path.to_string_lossy().to_string(),
+                message: "expected a revision number".to_string(),
+            })
+    }
+}
+
+/// `min-unpacked-rev` file update
+impl FsFsBackend {
+    /// Atomically update the `min-unpacked-rev` file to hold REVNUM.
+    ///
+    /// `svn_fs_fs__write_min_unpacked_rev`
+    pub(crate) fn write_min_unpacked_rev(
+        &self,
+        revnum: RevisionNumber,
+    ) -> Result<(), BackendError> {
+        svn_subr::io::write_atomic(
+            &self.path_min_unpacked_rev(),
+            format!("{revnum}\n").as_bytes(),
+            Some(&self.path_uuid()),
+            self._data().flush_to_disk,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// `current` file access
+impl FsFsBackend {
+    /// Read the youngest revision from the `current` file.
+    ///
+    /// `svn_fs_fs__read_current`
+    pub(crate) fn read_current(&self) -> Result<RevisionNumber, BackendError> {
+        let path = self.path_current();
+        let content = fs_err::read_to_string(&path)?;
+        let corrupt = || BackendError::CorruptFile {
+            path: path.to_string_lossy().to_string(),
+            message: "expected a revision number".to_string(),
+        };
+
+        // Formats before 3 append the node and copy ID counters.
+        let rev = content.split_whitespace().next().ok_or_else(corrupt)?;
+        rev.parse().map_err(|_| corrupt())
+    }
+
+    /// Atomically update the `current` file to hold the specified REV.
+    ///
+    /// `svn_fs_fs__write_current`
+    pub(crate) fn write_current(&self, rev: RevisionNumber) -> Result<(), BackendError> {
+        let ffd = self._data();
+        let path = self.path_current();
+
+        // The uuid file carries the permissions all repository files
+        // should have.
+        svn_subr::io::write_atomic(
+            &path,
+            format!("{rev}\n").as_bytes(),
+            Some(&self.path_uuid()),
+            ffd.flush_to_disk,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,571 @@
+//! `subversion/libsvn_fs_fs/verify.c`
+//!
+//! Verification walks the requested revisions one by one.  For each of
+//! them it checks the l2p / p2l indexes against each other and against the
+//! rev / pack file, the node-revisions and representations created in that
+//! revision, and the rep-cache entries pointing into it.
+
+use std::collections::HashSet;
+use std::io::Read;
+
+use svn_subr::Checksum;
+use svn_subr::ChecksumContext;
+use svn_subr::ChecksumKind;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+use super::super::BackendError;
+use super::FsFsBackend;
+use super::index;
+use super::index::ItemType;
+use super::rev_file::RevisionFile;
+use crate::FsId;
+use crate::NodeRevision;
+use crate::Representation;
+use crate::VerifyError;
+use crate::VerifyErrorKind;
+use crate::backend::check_cancel;
+use crate::util::fspath_join;
+
+/// Collects the problems found in the revision being verified.
+struct RevisionReport {
+    revision: RevisionNumber,
+    errors: Vec<VerifyError>,
+}
+
+impl RevisionReport {
+    /// Record a problem of @a kind at @a path and @a offset.
+    fn add(&mut self, path: Option<&str>, offset: Option<u64>, kind: VerifyErrorKind) {
+        self.errors.push(VerifyError {
+            revision: self.revision,
+            path: path.map(str::to_string),
+            offset,
+            kind,
+        });
+    }
+
+    /// Record the failure @a err to read the data at @a path and
+    /// @a offset.  Cancellation is no problem of the filesystem and is
+    /// passed on.
+    fn add_error(
+        &mut self,
+        path: Option<&str>,
+        offset: Option<u64>,
+        err: BackendError,
+    ) -> Result<(), BackendError> {
+        match err {
+            BackendError::Cancelled => Err(err),
+            err => {
+                self.add(path, offset, VerifyErrorKind::Corrupt(err.to_string()));
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FsFsBackend {
+    /// Verify revisions @a start to @a end and return all problems found.
+    /// Either revision may be `SVN_INVALID_REVNUM` for "from r0" and "up
+    /// to the youngest revision", respectively.  Unless @a keep_going is
+    /// set, stop after the first revision with problems.
+    ///
+    /// `svn_fs_fs__verify`
+    pub(crate) fn verify(
+        &self,
+        start: RevisionNumber,
+        end: RevisionNumber,
+        keep_going: bool,
+        mut notify: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<Vec<VerifyError>, BackendError> {
+        let youngest = self.read_current()?;
+        let start = if start == SVN_INVALID_REVNUM {
+            0
+        } else {
+            start
+        };
+        let end = if end == SVN_INVALID_REVNUM {
+            youngest
+        } else {
+            end
+        };
+        if start > end || end > youngest {
+            return Err(BackendError::Corrupt(format!(
+                "Invalid revision range [{start}, {end}]"
+            )));
+        }
+
+        // Rev / pack files whose indexes have been checked as a whole.
+        let mut checked_files = HashSet::new();
+        let mut errors = Vec::new();
+        for rev in start..=end {
+            check_cancel(cancel)?;
+            if let Some(notify) = &mut notify {
+                notify(rev);
+            }
+
+            let mut report = RevisionReport {
+                revision: rev,
+                errors: Vec::new(),
+            };
+            if self._data().use_log_addressing {
+                self.verify_index(rev, &mut checked_files, &mut report)?;
+            }
+            self.verify_root(rev, &mut report, cancel)?;
+            self.verify_rep_cache_revision(rev, &mut report)?;
+
+            let failed = !report.errors.is_empty();
+            errors.append(&mut report.errors);
+            if failed && !keep_going {
+                break;
+            }
+        }
+
+        Ok(errors)
+    }
+
+    /// Check the l2p / p2l indexes of revision @a rev against each other
+    /// and against the rev / pack file.  Checks covering the whole file
+    /// are skipped if its start revision is in @a checked_files already.
+    ///
+    /// `verify_f7_metadata`
+    fn verify_index(
+        &self,
+        rev: RevisionNumber,
+        checked_files: &mut HashSet<RevisionNumber>,
+        report: &mut RevisionReport,
+    ) -> Result<(), BackendError> {
+        let result = self.open_pack_or_rev_file(rev).and_then(|mut rev_file| {
+            if checked_files.insert(rev_file.start_revision) {
+                verify_index_checksums(&mut rev_file, report)?;
+            }
+            compare_l2p_to_p2l_index(&mut rev_file, rev, report)?;
+            compare_p2l_to_l2p_index(&mut rev_file, rev, report)
+        });
+
+        match result {
+            Err(err) => report.add_error(None, None, err),
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// Check the node-revisions and representations created in revision
+    /// @a rev, starting from its root directory.
+    ///
+    /// `svn_fs_fs__verify_root`
+    fn verify_root(
+        &self,
+        rev: RevisionNumber,
+        report: &mut RevisionReport,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let root = match self
+            .rev_get_root(rev)
+            .and_then(|id| self.get_node_revision(&id))
+        {
+            Ok(root) => root,
+            Err(err) => return report.add_error(Some("/"), None, err),
+        };
+
+        // Only r0 should have no predecessor and every other root is the
+        // successor of the previous revision's root.
+        match &root.predecessor_id {
+            None if rev != 0 => report.add(
+                Some("/"),
+                self.node_offset(&root.id),
+                VerifyErrorKind::DirectoryEntry(format!("r{rev}'s root node has no predecessor")),
+            ),
+            Some(pred_id) if pred_id.rev() != rev - 1 => report.add(
+                Some("/"),
+                self.node_offset(&root.id),
+                VerifyErrorKind::DirectoryEntry(format!(
+                    "r{rev}'s root node's predecessor is r{} but should be r{}",
+                    pred_id.rev(),
+                    rev - 1
+                )),
+            ),
+            _ => {}
+        }
+
+        let mut visited = HashSet::new();
+        self.verify_node(&root, "/", rev, &mut visited, report, cancel)
+    }
+
+    /// Check @a noderev at @a path and, if it is a directory, the nodes
+    /// below it that got changed in revision @a rev.  @a visited holds the
+    /// nodes checked so far, to detect cycles.
+    ///
+    /// `verify_node`
+    fn verify_node(
+        &self,
+        noderev: &NodeRevision,
+        path: &str,
+        rev: RevisionNumber,
+        visited: &mut HashSet<FsId>,
+        report: &mut RevisionReport,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        check_cancel(cancel)?;
+
+        // Detect (non-)DAG cycles.
+        if !visited.insert(noderev.id.clone()) {
+            report.add(
+                Some(path),
+                self.node_offset(&noderev.id),
+                VerifyErrorKind::DirectoryEntry(format!(
+                    "Node '{}' is referenced more than once",
+                    noderev.id
+                )),
+            );
+            return Ok(());
+        }
+
+        // The predecessor count is one more than the predecessor's.
+        let expected = match &noderev.predecessor_id {
+            None => Ok(0),
+            Some(pred_id) => self
+                .get_node_revision(pred_id)
+                .map(|pred| pred.predecessor_count + 1),
+        };
+        match expected {
+            Ok(expected) if expected != noderev.predecessor_count => report.add(
+                Some(path),
+                self.node_offset(&noderev.id),
+                VerifyErrorKind::PredecessorCount {
+                    expected,
+                    actual: noderev.predecessor_count,
+                },
+            ),
+            Ok(_) => {}
+            Err(err) => report.add_error(Some(path), self.node_offset(&noderev.id), err)?,
+        }
+
+        // Representations shared with or inherited from older revisions
+        // get verified with those.
+        for rep in [&noderev.prop_rep, &noderev.data_rep].into_iter().flatten() {
+            if rep.revision == rev {
+                self.verify_rep(rep, path, report)?;
+            }
+        }
+
+        if noderev.kind != NodeKind::Directory {
+            return Ok(());
+        }
+
+        let entries = match self.rep_contents_dir(noderev) {
+            Ok(entries) => entries,
+            Err(err) => {
+                let offset = self.rep_offset(noderev.data_rep.as_ref());
+                return report.add_error(Some(path), offset, err);
+            }
+        };
+        for (name, entry) in entries {
+            let child_path = fspath_join(path, &name);
+            let child = match self.get_node_revision(&entry.id) {
+                Ok(child) => child,
+                Err(BackendError::Cancelled) => return Err(BackendError::Cancelled),
+                Err(err) => {
+                    report.add(
+                        Some(&child_path),
+                        None,
+                        VerifyErrorKind::DirectoryEntry(format!(
+                            "Directory entry refers to unreadable node '{}': {err}",
+                            entry.id
+                        )),
+                    );
+                    continue;
+                }
+            };
+
+            if child.kind != entry.kind {
+                report.add(
+                    Some(&child_path),
+                    self.node_offset(&child.id),
+                    VerifyErrorKind::DirectoryEntry(format!(
+                        "Directory entry has kind {:?} but node '{}' is a {:?}",
+                        entry.kind, child.id, child.kind
+                    )),
+                );
+                continue;
+            }
+
+            // Older nodes have been verified with their own revision.
+            if entry.id.rev() == rev {
+                self.verify_node(&child, &child_path, rev, visited, report, cancel)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the MD5 and SHA1 checksums of the contents of @a rep, which
+    /// belongs to the node at @a path.
+    fn verify_rep(
+        &self,
+        rep: &Representation,
+        path: &str,
+        report: &mut RevisionReport,
+    ) -> Result<(), BackendError> {
+        // Read without the reader's own MD5 check to compare both
+        // checksums here.
+        let unchecked = Representation {
+            md5_digest: None,
+            ..rep.clone()
+        };
+        let mut md5 = ChecksumContext::new(ChecksumKind::Md5);
+        let mut sha1 = ChecksumContext::new(ChecksumKind::Sha1);
+        let result = self
+            .read_representation(Some(&unchecked))
+            .and_then(|mut reader| {
+                let mut buf = vec![0; 0x4000];
+                loop {
+                    let len = reader.read(&mut buf)?;
+                    if len == 0 {
+                        return Ok(());
+                    }
+                    md5.update(&buf[..len]);
+                    sha1.update(&buf[..len]);
+                }
+            });
+
+        let offset = self.rep_offset(Some(rep));
+        if let Err(err) = result {
+            return report.add_error(Some(path), offset, err);
+        }
+
+        let actual = [md5.finish(), sha1.finish()];
+        for (expected, actual) in [&rep.md5_digest, &rep.sha1_digest].into_iter().zip(actual) {
+            if let Some(expected) = expected
+                && *expected != actual
+            {
+                report.add(
+                    Some(path),
+                    offset,
+                    VerifyErrorKind::ChecksumMismatch {
+                        kind: *expected.kind(),
+                        expected: expected.to_hex(),
+                        actual: actual.to_hex(),
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the rep-cache entries of revision @a rev point to
+    /// representations.
+    ///
+    /// `verify_rep_cache`
+    fn verify_rep_cache_revision(
+        &self,
+        rev: RevisionNumber,
+        report: &mut RevisionReport,
+    ) -> Result<(), BackendError> {
+        // Not all repositories have a rep cache.
+        if !self.exists_rep_cache() {
+            return Ok(());
+        }
+
+        let result = self.walk_rep_reference(rev, rev, |rep| {
+            if let Err(err) = self.check_rep(rep) {
+                report.add(
+                    None,
+                    self.rep_offset(Some(rep)),
+                    VerifyErrorKind::RepCache(err.to_string()),
+                );
+            }
+            Ok(())
+        });
+
+        if let Err(err) = result {
+            report.add(None, None, VerifyErrorKind::RepCache(err.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Return the offset of the committed node-revision @a id within its
+    /// rev / pack file, if it can be determined.
+    fn node_offset(&self, id: &FsId) -> Option<u64> {
+        self.try_item_offset(id.rev(), id.item())
+    }
+
+    /// Return the offset of the committed representation @a rep within its
+    /// rev / pack file, if it can be determined.
+    fn rep_offset(&self, rep: Option<&Representation>) -> Option<u64> {
+        rep.and_then(|rep| self.try_item_offset(rep.revision, rep.item_index))
+    }
+
+    /// Return the offset of item @a item_index of revision @a rev, or
+    /// `None` if the indexes cannot tell.
+    fn try_item_offset(&self, rev: RevisionNumber, item_index: u64) -> Option<u64> {
+        let mut rev_file = self.open_pack_or_rev_file(rev).ok()?;
+        self.item_offset(&mut rev_file, rev, item_index).ok()
+    }
+}
+
+/// Check the MD5 checksums of the l2p and p2l indexes of @a rev_file
+/// against the ones in the footer and that the p2l index covers the
+/// revision data without gaps.
+///
+/// `verify_index_checksums` / `compare_p2l_to_rev`
+fn verify_index_checksums(
+    rev_file: &mut RevisionFile,
+    report: &mut RevisionReport,
+) -> Result<(), BackendError> {
+    let footer = rev_file.footer()?.clone();
+    let file_size = rev_file.file_size();
+
+    // The p2l index is followed by the footer and its length byte.
+    let footer_len = rev_file.read_at(file_size - 1, 1)?[0] as u64;
+    let l2p = rev_file.l2p_index()?;
+    let p2l_len = file_size - 1 - footer_len - footer.p2l_offset;
+    let p2l = rev_file.read_at(footer.p2l_offset, p2l_len as usize)?;
+
+    for (name, data, offset, expected) in [
+        ("L2P", l2p, footer.l2p_offset, &footer.l2p_checksum),
+        ("P2L", p2l, footer.p2l_offset, &footer.p2l_checksum),
+    ] {
+        let actual = Checksum::compute(ChecksumKind::Md5, &data);
+        if actual != *expected {
+            report.add(
+                None,
+                Some(offset),
+                VerifyErrorKind::Index(format!(
+                    "{name} index checksum mismatch: expected {}, actual {}",
+                    expected.to_hex(),
+                    actual.to_hex()
+                )),
+            );
+        }
+    }
+
+    // All items must be back to back, ending where the indexes start.
+    let p2l = rev_file.p2l_index()?;
+    let max_offset = index::p2l_get_max_offset(&p2l)?;
+    let mut offset = 0;
+    for entry in index::p2l_index_lookup(&p2l, 0, max_offset)? {
+        if entry.offset != offset {
+            report.add(
+                None,
+                Some(offset),
+                VerifyErrorKind::Index(format!(
+                    "P2L index entries are non-contiguous between offsets {offset} and {}",
+                    entry.offset
+                )),
+            );
+        }
+        offset = entry.offset + entry.size;
+    }
+    if offset != max_offset || max_offset > footer.l2p_offset {
+        report.add(
+            None,
+            Some(offset),
+            VerifyErrorKind::Index(format!(
+                "P2L index covers {offset} of {max_offset} bytes of revision data ending at {}",
+                footer.l2p_offset
+            )),
+        );
+    }
+
+    Ok(())
+}
+
+/// Check that every item of revision @a rev in the l2p index of
+/// @a rev_file has a matching p2l entry.
+///
+/// `compare_l2p_to_p2l_index`
+fn compare_l2p_to_p2l_index(
+    rev_file: &mut RevisionFile,
+    rev: RevisionNumber,
+    report: &mut RevisionReport,
+) -> Result<(), BackendError> {
+    let l2p = rev_file.l2p_index()?;
+    let p2l = rev_file.p2l_index()?;
+
+    for (number, offset) in index::l2p_revision_offsets(&l2p, rev)?
+        .into_iter()
+        .enumerate()
+    {
+        let Some(offset) = offset else {
+            continue;
+        };
+
+        match index::p2l_entry_lookup(&p2l, offset)? {
+            None => report.add(
+                None,
+                Some(offset),
+                VerifyErrorKind::Index(format!(
+                    "L2P index entry for item {number} not found in P2L index"
+                )),
+            ),
+            Some(entry) if entry.revision != rev || entry.number != number as u64 => report.add(
+                None,
+                Some(offset),
+                VerifyErrorKind::Index(format!(
+                    "P2L index info r{} item {} does not match L2P index item {number}",
+                    entry.revision, entry.number
+                )),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that every p2l entry of revision @a rev in @a rev_file is found
+/// at the same offset in the l2p index and matches the data in the file.
+///
+/// `compare_p2l_to_l2p_index` / `compare_p2l_to_rev`
+fn compare_p2l_to_l2p_index(
+    rev_file: &mut RevisionFile,
+    rev: RevisionNumber,
+    report: &mut RevisionReport,
+) -> Result<(), BackendError> {
+    let l2p = rev_file.l2p_index()?;
+    let p2l = rev_file.p2l_index()?;
+    let max_offset = index::p2l_get_max_offset(&p2l)?;
+
+    for entry in index::p2l_index_lookup(&p2l, 0, max_offset)? {
+        if entry.revision != rev || entry.item_type == ItemType::Unused {
+            continue;
+        }
+
+        match index::l2p_index_lookup(&l2p, rev, entry.number) {
+            Ok(offset) if offset == entry.offset => {}
+            Ok(offset) => report.add(
+                None,
+                Some(entry.offset),
+                VerifyErrorKind::Index(format!(
+                    "L2P offset {offset} of item {} does not match P2L index",
+                    entry.number
+                )),
+            ),
+            Err(err) => report.add(
+                None,
+                Some(entry.offset),
+                VerifyErrorKind::Index(format!(
+                    "P2L index entry for item {} not found in L2P index: {err}",
+                    entry.number
+                )),
+            ),
+        }
+
+        let data = rev_file.read_at(entry.offset, entry.size as usize)?;
+        let checksum = svn_subr::fnv1a_32x4(&data);
+        if data.len() as u64 != entry.size || checksum != entry.fnv1_checksum {
+            report.add(
+                None,
+                Some(entry.offset),
+                VerifyErrorKind::Index(format!(
+                    "Checksum mismatch of item {}: expected {:08x}, actual {checksum:08x}",
+                    entry.number, entry.fnv1_checksum
+                )),
+            );
+        }
+    }
+
+    Ok(())
+}
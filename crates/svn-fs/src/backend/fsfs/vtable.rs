@@ -1,22 +1,134 @@
+use svn_types::Depth;
+use svn_types::SvnLock;
+
 use super::super::BackendError;
 use super::super::FsInstance;
 use super::FsFsBackend;
+use super::transaction::FsFsTxn;
+use super::tree::FsFsRoot;
+use crate::RootTrait;
+use crate::txn::Txn;
 
 impl FsInstance for FsFsBackend {
-    fn youngest_rev(&self) -> svn_types::RevisionNumber {
-        todo!()
+    /// `svn_fs_fs__youngest_rev`
+    fn youngest_rev(&self) -> Result<svn_types::RevisionNumber, BackendError> {
+        self.read_current()
     }
 
     fn refresh_revision_prop(&self) -> Result<(), BackendError> {
         todo!()
     }
 
-    fn revision_prop(&self) -> Result<(), BackendError> {
-        todo!()
+    /// `svn_fs_fs__revision_proplist`
+    fn revision_proplist(
+        &self,
+        rev: svn_types::RevisionNumber,
+    ) -> Result<std::collections::HashMap<String, String>, BackendError> {
+        self.get_revision_proplist(rev)
     }
 
-    fn data_mut(&mut self) -> &mut Box<dyn std::any::Any> {
-        // self.data.as_mut().expect("Data should be initialized")
-        todo!()
+    /// `svn_fs_fs__change_rev_prop`
+    fn change_rev_prop(
+        &self,
+        rev: svn_types::RevisionNumber,
+        name: &str,
+        old_value: Option<Option<&str>>,
+        value: Option<&str>,
+    ) -> Result<(), BackendError> {
+        FsFsBackend::change_rev_prop(self, rev, name, old_value, value)
+    }
+
+    /// `svn_fs_fs__freeze`
+    fn freeze(&self, freeze_func: &mut dyn FnMut()) -> Result<(), BackendError> {
+        FsFsBackend::freeze(self, freeze_func)
+    }
+
+    fn data_mut(&mut self) -> &mut dyn std::any::Any {
+        self._data_mut()
+    }
+
+    /// `fs_revision_root`
+    fn revision_root(
+        &self,
+        rev: svn_types::RevisionNumber,
+    ) -> Result<Box<dyn RootTrait + '_>, BackendError> {
+        Ok(Box::new(FsFsRoot::revision_root(self, rev)?))
+    }
+
+    /// `svn_fs_fs__begin_txn`
+    fn begin_txn(&self, rev: svn_types::RevisionNumber) -> Result<Box<dyn Txn + '_>, BackendError> {
+        let txn_id = self.begin_txn_id(rev)?;
+        Ok(Box::new(FsFsTxn::new(self, txn_id, rev)))
+    }
+
+    /// `svn_fs_fs__open_txn`
+    fn open_txn(&self, name: &str) -> Result<Box<dyn Txn + '_>, BackendError> {
+        let txn_id = self.open_txn_id(name)?;
+        let base_rev = self.txn_base_rev(&txn_id)?;
+        Ok(Box::new(FsFsTxn::new(self, txn_id, base_rev)))
+    }
+
+    /// `svn_fs_fs__list_transactions`
+    fn list_transactions(&self) -> Result<Vec<String>, BackendError> {
+        FsFsBackend::list_transactions(self)
+    }
+
+    /// `svn_fs_fs__purge_txn`
+    fn purge_txn(&self, name: &str) -> Result<(), BackendError> {
+        FsFsBackend::purge_txn(self, name)
+    }
+
+    /// `svn_fs_fs__build_rep_cache`
+    fn build_rep_cache(
+        &self,
+        start: svn_types::RevisionNumber,
+        end: svn_types::RevisionNumber,
+    ) -> Result<(), BackendError> {
+        FsFsBackend::build_rep_cache(self, start, end)
+    }
+
+    /// `verify_rep_cache`
+    fn verify_rep_cache(
+        &self,
+        start: svn_types::RevisionNumber,
+        end: svn_types::RevisionNumber,
+    ) -> Result<(), BackendError> {
+        FsFsBackend::verify_rep_cache(self, start, end)
+    }
+
+    /// `svn_fs_fs__lock`
+    fn lock(
+        &self,
+        lock: &SvnLock,
+        current_rev: svn_types::RevisionNumber,
+        steal_lock: bool,
+    ) -> Result<(), BackendError> {
+        FsFsBackend::lock(self, lock, current_rev, steal_lock)
+    }
+
+    /// `svn_fs_fs__unlock`
+    fn unlock(
+        &self,
+        path: &str,
+        token: Option<&str>,
+        username: Option<&str>,
+        break_lock: bool,
+    ) -> Result<(), BackendError> {
+        FsFsBackend::unlock(self, path, token, username, break_lock)
+    }
+
+    /// `svn_fs_fs__get_lock`
+    fn get_lock(&self, path: &str) -> Result<Option<SvnLock>, BackendError> {
+        FsFsBackend::get_lock(self, path)
+    }
+
+    /// `svn_fs_fs__get_locks`
+    fn get_locks(&self, path: &str, depth: Depth) -> Result<Vec<SvnLock>, BackendError> {
+        FsFsBackend::get_locks(self, path, depth)
+    }
+
+    /// `svn_fs_fs__generate_lock_token`
+    fn generate_lock_token(&self) -> String {
+        crate::lock::generate_lock_token()
     }
 }
@@ -1,9 +1,334 @@
+//! `subversion/libsvn_fs_x/changes.c`
+//!
+//! The binary changed-paths list of FSX.  All paths of a list go into a
+//! string table first, sorted and prefix-compressed against their
+//! predecessor, since the paths of a revision tend to share long
+//! prefixes.  Every change then refers to its path and copy source by
+//! their index in that table.
+
+use std::collections::BTreeSet;
+
+use svn_subr::Tristate;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+
+use super::super::BackendError;
+use super::Decoder;
+use super::Encoder;
+use crate::CopyFromInfo;
+use crate::PathChange;
+use crate::PathChangeKind;
+use crate::id::FsId;
+
+// Bit fields of `BinaryChange::flags`.
+const CHANGE_TEXT_MOD: u64 = 0x00001;
+const CHANGE_PROP_MOD: u64 = 0x00002;
+const CHANGE_MERGEINFO_MOD: u64 = 0x00004;
+const CHANGE_MERGEINFO_UNKNOWN: u64 = 0x00008;
+const CHANGE_HAS_ID: u64 = 0x00010;
+const CHANGE_HAS_COPYFROM: u64 = 0x00020;
+
+const CHANGE_NODE_SHIFT: u64 = 6;
+const CHANGE_NODE_MASK: u64 = 0x000c0;
+
+const CHANGE_KIND_SHIFT: u64 = 8;
+const CHANGE_KIND_MASK: u64 = 0x00300;
+
+// Node kinds.
+const CHANGE_NODE_NONE: u64 = 0;
+const CHANGE_NODE_FILE: u64 = 1;
+const CHANGE_NODE_DIR: u64 = 2;
+const CHANGE_NODE_UNKNOWN: u64 = 3;
+
+// Change kinds.  Resets never make it into a revision.
+const CHANGE_KIND_MODIFY: u64 = 0;
+const CHANGE_KIND_ADD: u64 = 1;
+const CHANGE_KIND_DELETE: u64 = 2;
+const CHANGE_KIND_REPLACE: u64 = 3;
+
 /// Our internal representation of a change
 ///
 /// `binary_change_t`
 struct BinaryChange {
     /// define the kind of change and what specific information is present
-    flags: i32,
+    flags: u64,
     /// Path of the change.
     path: usize,
+    /// copy-from revision and path, if `CHANGE_HAS_COPYFROM` is set
+    copyfrom: Option<(RevisionNumber, usize)>,
+    /// the node-revision ID, if `CHANGE_HAS_ID` is set
+    noderev_id: Option<FsId>,
+}
+
+/// A sorted table of strings, referred to by their index.
+///
+/// `string_table_t`
+struct StringTable<'a> {
+    strings: Vec<&'a str>,
+}
+
+impl<'a> StringTable<'a> {
+    fn new(strings: BTreeSet<&'a str>) -> Self {
+        Self {
+            strings: strings.into_iter().collect(),
+        }
+    }
+
+    fn index(&self, s: &str) -> usize {
+        self.strings
+            .binary_search(&s)
+            .expect("all strings are in the table")
+    }
+
+    /// Write each string as the length of the prefix it shares with the
+    /// previous one plus the remainder.
+    fn write(&self, out: &mut Encoder) {
+        out.uint(self.strings.len() as u64);
+        let mut previous = "";
+        for s in &self.strings {
+            let shared = s
+                .bytes()
+                .zip(previous.bytes())
+                .take_while(|(a, b)| a == b)
+                .count();
+            out.uint(shared as u64);
+            out.string(&s[shared..]);
+            previous = s;
+        }
+    }
+}
+
+/// Read a string table written by [`StringTable::write`].
+fn read_string_table(input: &mut Decoder<'_>) -> Result<Vec<String>, BackendError> {
+    let count = input.uint()? as usize;
+    let mut strings: Vec<String> = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        let shared = input.uint()? as usize;
+        let previous = strings.last().map(String::as_bytes).unwrap_or_default();
+        let mut s = previous
+            .get(..shared)
+            .ok_or_else(|| BackendError::Corrupt("Invalid FSX string table".to_string()))?
+            .to_vec();
+        s.extend_from_slice(input.string()?.as_bytes());
+        strings.push(
+            String::from_utf8(s)
+                .map_err(|_| BackendError::Corrupt("Invalid UTF-8 in FSX item".to_string()))?,
+        );
+    }
+
+    Ok(strings)
+}
+
+/// Return the binary encoding of the changed-paths list @a changes.
+///
+/// `svn_fs_x__write_changes_container`
+pub(crate) fn write_changes(changes: &[PathChange]) -> Result<Vec<u8>, BackendError> {
+    let table = StringTable::new(
+        changes
+            .iter()
+            .flat_map(|change| {
+                std::iter::once(change.path.as_str())
+                    .chain(change.copyfrom.as_ref().map(|c| c.path.as_str()))
+            })
+            .collect(),
+    );
+
+    let mut out = Encoder::new();
+    table.write(&mut out);
+    out.uint(changes.len() as u64);
+    for change in changes {
+        let binary = to_binary_change(change, &table)?;
+        out.uint(binary.flags);
+        out.uint(binary.path as u64);
+        if let Some(id) = &binary.noderev_id {
+            out.id(id);
+        }
+        if let Some((rev, path)) = binary.copyfrom {
+            out.revnum(rev);
+            out.uint(path as u64);
+        }
+    }
+
+    Ok(out.data)
+}
+
+/// Parse the binary changed-paths list @a data.
+///
+/// `svn_fs_x__read_changes_container`
+pub(crate) fn read_changes(data: &[u8]) -> Result<Vec<PathChange>, BackendError> {
+    let mut input = Decoder::new(data);
+    let strings = read_string_table(&mut input)?;
+    let string = |index: u64| {
+        strings
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| BackendError::Corrupt(format!("Invalid FSX string index {index}")))
+    };
+
+    let count = input.uint()? as usize;
+    let mut changes = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        let flags = input.uint()?;
+        let path = string(input.uint()?)?;
+        let node_rev_id = if flags & CHANGE_HAS_ID != 0 {
+            Some(input.id()?)
+        } else {
+            None
+        };
+        let copyfrom = if flags & CHANGE_HAS_COPYFROM != 0 {
+            Some(CopyFromInfo {
+                rev: input.revnum()?,
+                path: string(input.uint()?)?,
+            })
+        } else {
+            None
+        };
+
+        changes.push(PathChange {
+            path,
+            node_rev_id,
+            change_kind: match (flags & CHANGE_KIND_MASK) >> CHANGE_KIND_SHIFT {
+                CHANGE_KIND_MODIFY => PathChangeKind::Modify,
+                CHANGE_KIND_ADD => PathChangeKind::Add,
+                CHANGE_KIND_DELETE => PathChangeKind::Delete,
+                _ => PathChangeKind::Replace,
+            },
+            node_kind: match (flags & CHANGE_NODE_MASK) >> CHANGE_NODE_SHIFT {
+                CHANGE_NODE_NONE => NodeKind::None,
+                CHANGE_NODE_FILE => NodeKind::File,
+                CHANGE_NODE_DIR => NodeKind::Directory,
+                _ => NodeKind::Unknown,
+            },
+            text_mod: flags & CHANGE_TEXT_MOD != 0,
+            prop_mod: flags & CHANGE_PROP_MOD != 0,
+            mergeinfo_mod: if flags & CHANGE_MERGEINFO_UNKNOWN != 0 {
+                Tristate::Unknown
+            } else if flags & CHANGE_MERGEINFO_MOD != 0 {
+                Tristate::True
+            } else {
+                Tristate::False
+            },
+            copyfrom,
+        });
+    }
+
+    if !input.is_empty() {
+        return Err(BackendError::Corrupt(
+            "Trailing data in FSX changed-paths list".to_string(),
+        ));
+    }
+
+    Ok(changes)
+}
+
+/// Return the binary form of @a change, whose paths are in @a table.
+fn to_binary_change(
+    change: &PathChange,
+    table: &StringTable<'_>,
+) -> Result<BinaryChange, BackendError> {
+    let kind = match change.change_kind {
+        PathChangeKind::Modify => CHANGE_KIND_MODIFY,
+        PathChangeKind::Add => CHANGE_KIND_ADD,
+        PathChangeKind::Delete => CHANGE_KIND_DELETE,
+        PathChangeKind::Replace => CHANGE_KIND_REPLACE,
+        PathChangeKind::Reset => {
+            return Err(BackendError::Corrupt(format!(
+                "Cannot store a reset of '{}' in a revision",
+                change.path
+            )));
+        }
+    };
+    let node = match change.node_kind {
+        NodeKind::None => CHANGE_NODE_NONE,
+        NodeKind::File => CHANGE_NODE_FILE,
+        NodeKind::Directory => CHANGE_NODE_DIR,
+        _ => CHANGE_NODE_UNKNOWN,
+    };
+
+    let mut flags = (kind << CHANGE_KIND_SHIFT) | (node << CHANGE_NODE_SHIFT);
+    if change.text_mod {
+        flags |= CHANGE_TEXT_MOD;
+    }
+    if change.prop_mod {
+        flags |= CHANGE_PROP_MOD;
+    }
+    match change.mergeinfo_mod {
+        Tristate::True => flags |= CHANGE_MERGEINFO_MOD,
+        Tristate::False => {}
+        Tristate::Unknown => flags |= CHANGE_MERGEINFO_UNKNOWN,
+    }
+    if change.node_rev_id.is_some() {
+        flags |= CHANGE_HAS_ID;
+    }
+    if change.copyfrom.is_some() {
+        flags |= CHANGE_HAS_COPYFROM;
+    }
+
+    Ok(BinaryChange {
+        flags,
+        path: table.index(&change.path),
+        copyfrom: change
+            .copyfrom
+            .as_ref()
+            .map(|copyfrom| (copyfrom.rev, table.index(&copyfrom.path))),
+        noderev_id: change.node_rev_id.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_roundtrip() {
+        let id: FsId = "2-1.0-1.r3/5".parse().unwrap();
+        let changes = vec![
+            PathChange {
+                path: "/trunk/dir/file".to_string(),
+                node_rev_id: Some(id),
+                change_kind: PathChangeKind::Modify,
+                node_kind: NodeKind::File,
+                text_mod: true,
+                prop_mod: false,
+                mergeinfo_mod: Tristate::Unknown,
+                copyfrom: None,
+            },
+            PathChange {
+                path: "/branches/b".to_string(),
+                node_rev_id: Some("0.1-3.r3/2".parse().unwrap()),
+                change_kind: PathChangeKind::Add,
+                node_kind: NodeKind::Directory,
+                text_mod: false,
+                prop_mod: true,
+                mergeinfo_mod: Tristate::True,
+                copyfrom: Some(CopyFromInfo {
+                    rev: 2,
+                    path: "/trunk".to_string(),
+                }),
+            },
+            PathChange {
+                path: "/trunk/dir".to_string(),
+                node_rev_id: None,
+                change_kind: PathChangeKind::Delete,
+                node_kind: NodeKind::None,
+                text_mod: false,
+                prop_mod: false,
+                mergeinfo_mod: Tristate::False,
+                copyfrom: None,
+            },
+        ];
+
+        let data = write_changes(&changes).unwrap();
+        assert_eq!(read_changes(&data).unwrap(), changes);
+        assert!(read_changes(&data[..data.len() - 1]).is_err());
+        assert!(
+            read_changes(&write_changes(&[]).unwrap())
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut reset = changes[0].clone();
+        reset.change_kind = PathChangeKind::Reset;
+        assert!(write_changes(&[reset]).is_err());
+    }
 }
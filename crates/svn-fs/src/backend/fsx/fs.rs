@@ -0,0 +1,186 @@
+// copy from `subversion/libsvn_fs_x/fs.c`
+
+use std::path::Path;
+
+use svn_types::RevisionNumber;
+
+use super::super::BackendError;
+use super::super::FsLibrary;
+use super::FsxBackend;
+use crate::PackNotifyAction;
+use crate::RecoverReport;
+use crate::SvnFs;
+use crate::UpgradeNotifyAction;
+use crate::VerifyError;
+use crate::backend::fsfs::FsFsBackend;
+
+impl FsxBackend {
+    /// Return an FSFS back-end in FSX mode for the filesystem at PATH.
+    ///
+    /// `initialize_fs_struct`
+    fn backend(path: &Path) -> FsFsBackend {
+        let mut data = FsFsBackend::initialize_fs_struct();
+        data.binary_items = true;
+
+        let mut backend = FsFsBackend::new(path.to_path_buf());
+        backend.set_data(data);
+        backend
+    }
+}
+
+impl FsLibrary for FsxBackend {
+    fn get_version(&self, _path: &Path) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    /// Create a new FSX filesystem at PATH.
+    ///
+    /// `x_create`
+    fn create(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
+        let mut backend = Self::backend(path);
+
+        fs.uuid = backend._create(fs.config(), path)?;
+        backend.initialize_caches(fs.config(), &fs.uuid, path)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
+    }
+
+    /// Open the FSX filesystem at PATH.
+    ///
+    /// `x_open`
+    fn open_fs(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
+        let mut backend = Self::backend(path);
+
+        fs.uuid = backend._open(path)?;
+        backend.initialize_caches(fs.config(), &fs.uuid, path)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
+    }
+
+    /// Open the FSX filesystem at PATH even if its `current` file is
+    /// missing or garbled, so that it can be recovered.
+    ///
+    /// `x_open_for_recovery`
+    fn open_fs_for_recovery(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
+        let mut backend = Self::backend(path);
+
+        fs.uuid = backend._open_for_recovery(path)?;
+        backend.initialize_caches(fs.config(), &fs.uuid, path)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
+    }
+
+    /// There is only one FSX format, so this merely checks that the
+    /// filesystem at PATH has it.
+    ///
+    /// `x_upgrade`
+    fn upgrade_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        notify: Option<&mut dyn FnMut(i64, UpgradeNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        Self::backend(path).upgrade(path, notify, cancel)?;
+        Self::open_fs(fs, path)
+    }
+
+    /// Verify the FSX filesystem at PATH, see [`FsFsBackend::verify`].
+    ///
+    /// `x_verify`
+    fn verify_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        start: RevisionNumber,
+        end: RevisionNumber,
+        keep_going: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<Vec<VerifyError>, BackendError> {
+        let mut backend = Self::backend(path);
+
+        fs.uuid = backend._open(path)?;
+        let errors = backend.verify(start, end, keep_going, notify, cancel)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(errors)
+    }
+
+    /// Delete the FSX filesystem at PATH, with everything in it.
+    ///
+    /// `x_delete_fs`
+    fn delete_fs(&self, path: &str) -> Result<(), BackendError> {
+        fs_err::remove_dir_all(path)?;
+        Ok(())
+    }
+
+    /// Copy the FSX filesystem at SRC_PATH to DST_PATH, see
+    /// [`FsFsBackend::hotcopy`].
+    ///
+    /// `x_hotcopy`
+    fn hotcopy(
+        src_fs: &mut SvnFs,
+        dst_fs: &mut SvnFs,
+        src_path: &Path,
+        dst_path: &Path,
+        incremental: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber, RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let mut src = Self::backend(src_path);
+        src_fs.uuid = src._open(src_path)?;
+        src.hotcopy(
+            src_fs.uuid,
+            &mut Self::backend(dst_path),
+            incremental,
+            notify,
+            cancel,
+        )?;
+
+        // Re-open the destination to pick up everything copied.
+        let mut dst = Self::backend(dst_path);
+        dst_fs.uuid = dst._open(dst_path)?;
+
+        src_fs.set_inner(Box::new(src));
+        dst_fs.set_inner(Box::new(dst));
+
+        Ok(())
+    }
+
+    /// Recover the FSX filesystem at PATH, see [`FsFsBackend::recover`].
+    ///
+    /// `x_recover`
+    fn recover_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<RecoverReport, BackendError> {
+        let mut backend = Self::backend(path);
+
+        fs.uuid = backend._open_for_recovery(path)?;
+        let report = backend.recover(fs.uuid, cancel)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(report)
+    }
+
+    /// Pack the FSX filesystem at PATH, see [`FsFsBackend::pack`].
+    ///
+    /// `x_pack`
+    fn pack_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        notify: Option<&mut dyn FnMut(i64, PackNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let mut backend = Self::backend(path);
+
+        fs.uuid = backend._open(path)?;
+        backend.pack(notify, cancel)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
+    }
+}
@@ -1,4 +1,240 @@
 //! interface to the FSX layer
+//!
+//! FSX shares the transaction, tree and administration logic of FSFS; an
+//! FSX filesystem is an FSFS filesystem with its own format file that
+//! always uses a sharded layout and logical addressing, and that stores
+//! node-revisions and changed-path lists in the binary encodings of
+//! [`noderevs`] and [`changes`] instead of the FSFS text format.
+//!
+//! The codecs are always built, since the FSFS code dispatches to them;
+//! the back-end itself is only available with the `fsx` feature.
 
-mod changes;
-mod tree;
+pub(crate) mod changes;
+pub(crate) mod noderevs;
+
+#[cfg(feature = "fsx")]
+mod fs;
+
+use std::path::Path;
+
+use svn_subr::compress::decode_uint;
+use svn_subr::compress::encode_uint;
+use svn_types::RevisionNumber;
+
+use super::BackendError;
+use crate::id::FsId;
+use crate::id::IdPart;
+
+/// The format number of this filesystem.
+/// This is independent of the repository format number, and
+/// independent of any other FS back ends.
+///
+/// `SVN_FS_X__FORMAT_NUMBER`
+pub(crate) const FORMAT_NUMBER: u32 = 2;
+
+/// The FSX back-end.  Its filesystems are served by an FSFS back-end in
+/// FSX mode, see the module documentation.
+#[cfg(feature = "fsx")]
+#[derive(Debug, Default)]
+pub struct FsxBackend;
+
+/// Return the contents of the 'format' file of an FSX filesystem with
+/// @a max_files_per_dir revisions per shard.
+///
+/// `svn_fs_x__write_format`
+pub(crate) fn unparse_format(max_files_per_dir: u32) -> String {
+    format!("{FORMAT_NUMBER}\nlayout sharded {max_files_per_dir}\n")
+}
+
+/// Parse the contents @a content of the 'format' file at @a format_path
+/// of an FSX filesystem and return the number of revisions per shard.
+///
+/// `read_format`
+pub(crate) fn parse_format(format_path: &Path, content: &str) -> Result<u32, BackendError> {
+    let corrupt = |message: String| BackendError::CorruptFormat {
+        path: format_path.to_string_lossy().to_string(),
+        message,
+    };
+
+    let mut lines = content.lines();
+    let format: u32 = lines
+        .next()
+        .and_then(|line| line.trim().parse().ok())
+        .ok_or_else(|| corrupt("expected a format number".to_string()))?;
+    if format != FORMAT_NUMBER {
+        return Err(BackendError::UnsupportedFormat {
+            expected: FORMAT_NUMBER,
+            found: format,
+        });
+    }
+
+    let layout = lines
+        .next()
+        .ok_or_else(|| corrupt("missing layout option".to_string()))?;
+    let max_files_per_dir = layout
+        .strip_prefix("layout sharded ")
+        .and_then(|shard_size| shard_size.parse().ok())
+        .filter(|&shard_size: &u32| shard_size > 0)
+        .ok_or_else(|| corrupt(format!("invalid layout option '{layout}'")))?;
+    if let Some(line) = lines.next() {
+        return Err(corrupt(format!("unrecognized option '{line}'")));
+    }
+
+    Ok(max_files_per_dir)
+}
+
+/// Return @a payload framed as an item of a revision file: its length
+/// followed by the data itself.
+pub(crate) fn frame_item(payload: &[u8]) -> Vec<u8> {
+    let mut item = Vec::with_capacity(payload.len() + 4);
+    encode_uint(&mut item, payload.len());
+    item.extend_from_slice(payload);
+    item
+}
+
+/// Return the length of the framed item at the start of @a data, if
+/// @a data covers it.
+pub(crate) fn item_len(data: &[u8]) -> Option<usize> {
+    let mut pos = 0;
+    let len = decode_uint(data, &mut pos)?;
+    (data.len() >= pos + len).then_some(pos + len)
+}
+
+/// Return the payload of the framed item @a data.
+pub(crate) fn unframe_item(data: &[u8]) -> Result<&[u8], BackendError> {
+    let mut pos = 0;
+    let len = decode_uint(data, &mut pos).ok_or_else(truncated)?;
+    data.get(pos..pos + len).ok_or_else(truncated)
+}
+
+fn truncated() -> BackendError {
+    BackendError::Corrupt("Truncated FSX item".to_string())
+}
+
+/// Appends the primitives the binary encodings are made of.
+#[derive(Default)]
+pub(crate) struct Encoder {
+    pub(crate) data: Vec<u8>,
+}
+
+impl Encoder {
+    pub(crate) fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    pub(crate) fn uint(&mut self, value: u64) {
+        encode_uint(&mut self.data, value as usize);
+    }
+
+    /// Revisions may be `SVN_INVALID_REVNUM`, so they are stored shifted
+    /// by one.
+    pub(crate) fn revnum(&mut self, rev: RevisionNumber) {
+        self.uint((rev + 1) as u64);
+    }
+
+    pub(crate) fn bytes(&mut self, data: &[u8]) {
+        self.data.extend_from_slice(data);
+    }
+
+    pub(crate) fn string(&mut self, s: &str) {
+        self.uint(s.len() as u64);
+        self.bytes(s.as_bytes());
+    }
+
+    pub(crate) fn id_part(&mut self, part: &IdPart) {
+        self.revnum(part.revision);
+        self.uint(part.number);
+    }
+
+    pub(crate) fn id(&mut self, id: &FsId) {
+        self.id_part(&id.node_id);
+        self.id_part(&id.copy_id);
+        self.id_part(&id.txn_id);
+        self.id_part(&id.rev_item);
+    }
+}
+
+/// Reads what an [`Encoder`] wrote, failing on malformed data.
+pub(crate) struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    pub(crate) fn uint(&mut self) -> Result<u64, BackendError> {
+        decode_uint(self.data, &mut self.pos)
+            .map(|value| value as u64)
+            .ok_or_else(truncated)
+    }
+
+    pub(crate) fn revnum(&mut self) -> Result<RevisionNumber, BackendError> {
+        let value = self.uint()?;
+        RevisionNumber::try_from(value)
+            .map(|rev| rev - 1)
+            .map_err(|_| BackendError::Corrupt(format!("Invalid revision number {value}")))
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], BackendError> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(truncated)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String, BackendError> {
+        let len = self.uint()? as usize;
+        let bytes = self.bytes(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| BackendError::Corrupt("Invalid UTF-8 in FSX item".to_string()))
+    }
+
+    pub(crate) fn id_part(&mut self) -> Result<IdPart, BackendError> {
+        Ok(IdPart {
+            revision: self.revnum()?,
+            number: self.uint()?,
+        })
+    }
+
+    pub(crate) fn id(&mut self) -> Result<FsId, BackendError> {
+        Ok(FsId {
+            node_id: self.id_part()?,
+            copy_id: self.id_part()?,
+            txn_id: self.id_part()?,
+            rev_item: self.id_part()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let path = Path::new("format");
+        assert_eq!(unparse_format(1000), "2\nlayout sharded 1000\n");
+        assert_eq!(parse_format(path, &unparse_format(4)).unwrap(), 4);
+        assert!(parse_format(path, "8\nlayout sharded 4\n").is_err());
+        assert!(parse_format(path, "2\nlayout linear\n").is_err());
+        assert!(parse_format(path, "2\nlayout sharded 4\naddressing logical\n").is_err());
+    }
+
+    #[test]
+    fn test_item_framing() {
+        let item = frame_item(b"payload");
+        assert_eq!(item_len(&item), Some(item.len()));
+        assert_eq!(item_len(&item[..4]), None);
+        assert_eq!(unframe_item(&item).unwrap(), b"payload");
+        assert!(unframe_item(&item[..4]).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! `subversion/libsvn_fs_x/noderevs.c`
+//!
+//! The binary node-revision encoding of FSX.  A flags word says which of
+//! the optional parts follow; numbers are variable-length and checksums
+//! are stored as raw digests.
+
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_types::NodeKind;
+
+use super::super::BackendError;
+use super::Decoder;
+use super::Encoder;
+use crate::CopyFromInfo;
+use crate::NodeRevision;
+use crate::Representation;
+use crate::node::RepUniquifier;
+
+// Bit fields of the node-revision flags.
+const NODEREV_KIND_MASK: u64 = 0x00003;
+const NODEREV_HAS_PRED: u64 = 0x00004;
+const NODEREV_HAS_COPYFROM: u64 = 0x00008;
+const NODEREV_HAS_PROPS: u64 = 0x00010;
+const NODEREV_HAS_DATA: u64 = 0x00020;
+const NODEREV_HAS_MINFO: u64 = 0x00040;
+const NODEREV_FRESH_TXN_ROOT: u64 = 0x00080;
+const NODEREV_COPYROOT_IS_CPATH: u64 = 0x00100;
+
+// Node kinds.
+const NODEREV_KIND_FILE: u64 = 1;
+const NODEREV_KIND_DIR: u64 = 2;
+
+// Bit fields of the representation flags.
+const REP_HAS_MD5: u64 = 0x1;
+const REP_HAS_SHA1: u64 = 0x2;
+const REP_HAS_UNIQUIFIER: u64 = 0x4;
+
+/// Return the binary encoding of @a noderev.
+///
+/// `svn_fs_x__write_noderevs_container`
+pub(crate) fn write_noderev(noderev: &NodeRevision) -> Vec<u8> {
+    let mut flags = match noderev.kind {
+        NodeKind::File => NODEREV_KIND_FILE,
+        _ => NODEREV_KIND_DIR,
+    };
+    if noderev.predecessor_id.is_some() {
+        flags |= NODEREV_HAS_PRED;
+    }
+    if noderev.copyfrom.is_some() {
+        flags |= NODEREV_HAS_COPYFROM;
+    }
+    if noderev.prop_rep.is_some() {
+        flags |= NODEREV_HAS_PROPS;
+    }
+    if noderev.data_rep.is_some() {
+        flags |= NODEREV_HAS_DATA;
+    }
+    if noderev.has_mergeinfo {
+        flags |= NODEREV_HAS_MINFO;
+    }
+    if noderev.is_fresh_txn_root {
+        flags |= NODEREV_FRESH_TXN_ROOT;
+    }
+    if noderev.copyroot_path == noderev.created_path {
+        flags |= NODEREV_COPYROOT_IS_CPATH;
+    }
+
+    let mut out = Encoder::new();
+    out.uint(flags);
+    out.id(&noderev.id);
+    if let Some(id) = &noderev.predecessor_id {
+        out.id(id);
+    }
+    out.uint(noderev.predecessor_count as u64);
+    out.string(&noderev.created_path);
+    if let Some(copyfrom) = &noderev.copyfrom {
+        out.revnum(copyfrom.rev);
+        out.string(&copyfrom.path);
+    }
+    out.revnum(noderev.copyroot_rev);
+    if flags & NODEREV_COPYROOT_IS_CPATH == 0 {
+        out.string(&noderev.copyroot_path);
+    }
+    for rep in [&noderev.prop_rep, &noderev.data_rep].into_iter().flatten() {
+        write_rep(&mut out, rep);
+    }
+    out.uint(noderev.mergeinfo_count as u64);
+
+    out.data
+}
+
+/// Parse the binary node-revision @a data.
+///
+/// `svn_fs_x__read_noderevs_container`
+pub(crate) fn read_noderev(data: &[u8]) -> Result<NodeRevision, BackendError> {
+    let mut input = Decoder::new(data);
+    let flags = input.uint()?;
+    let kind = match flags & NODEREV_KIND_MASK {
+        NODEREV_KIND_FILE => NodeKind::File,
+        NODEREV_KIND_DIR => NodeKind::Directory,
+        other => {
+            return Err(BackendError::Corrupt(format!(
+                "Invalid node kind {other} in FSX node-revision"
+            )));
+        }
+    };
+
+    let id = input.id()?;
+    let predecessor_id = if flags & NODEREV_HAS_PRED != 0 {
+        Some(input.id()?)
+    } else {
+        None
+    };
+    let predecessor_count = input.uint()? as i64;
+    let created_path = input.string()?;
+    let copyfrom = if flags & NODEREV_HAS_COPYFROM != 0 {
+        Some(CopyFromInfo {
+            rev: input.revnum()?,
+            path: input.string()?,
+        })
+    } else {
+        None
+    };
+    let copyroot_rev = input.revnum()?;
+    let copyroot_path = if flags & NODEREV_COPYROOT_IS_CPATH != 0 {
+        created_path.clone()
+    } else {
+        input.string()?
+    };
+    let prop_rep = if flags & NODEREV_HAS_PROPS != 0 {
+        Some(read_rep(&mut input)?)
+    } else {
+        None
+    };
+    let data_rep = if flags & NODEREV_HAS_DATA != 0 {
+        Some(read_rep(&mut input)?)
+    } else {
+        None
+    };
+    let mergeinfo_count = input.uint()? as i64;
+
+    if !input.is_empty() {
+        return Err(BackendError::Corrupt(format!(
+            "Trailing data in FSX node-revision '{id}'"
+        )));
+    }
+
+    Ok(NodeRevision {
+        kind,
+        id,
+        predecessor_id,
+        copyfrom,
+        copyroot_rev,
+        copyroot_path,
+        predecessor_count,
+        prop_rep,
+        data_rep,
+        created_path,
+        is_fresh_txn_root: flags & NODEREV_FRESH_TXN_ROOT != 0,
+        mergeinfo_count,
+        has_mergeinfo: flags & NODEREV_HAS_MINFO != 0,
+    })
+}
+
+/// Append the binary encoding of @a rep to @a out.
+///
+/// `write_rep`
+fn write_rep(out: &mut Encoder, rep: &Representation) {
+    let mut flags = 0;
+    if rep.md5_digest.is_some() {
+        flags |= REP_HAS_MD5;
+    }
+    if rep.sha1_digest.is_some() {
+        flags |= REP_HAS_SHA1;
+    }
+    if rep.uniquifier.is_some() {
+        flags |= REP_HAS_UNIQUIFIER;
+    }
+
+    out.uint(flags);
+    out.revnum(rep.revision);
+    out.uint(rep.item_index);
+    out.uint(rep.size);
+    out.uint(rep.expanded_size);
+    out.id_part(&rep.txn_id);
+    for checksum in [&rep.md5_digest, &rep.sha1_digest].into_iter().flatten() {
+        out.bytes(checksum.digest());
+    }
+    if let Some(uniquifier) = &rep.uniquifier {
+        out.id_part(&uniquifier.noderev_txn_id);
+        out.uint(uniquifier.number);
+    }
+}
+
+/// Read a representation written by [`write_rep`].
+///
+/// `read_rep`
+fn read_rep(input: &mut Decoder<'_>) -> Result<Representation, BackendError> {
+    let flags = input.uint()?;
+    let revision = input.revnum()?;
+    let item_index = input.uint()?;
+    let size = input.uint()?;
+    let expanded_size = input.uint()?;
+    let txn_id = input.id_part()?;
+    let mut checksum = |present: bool, kind: ChecksumKind| -> Result<_, BackendError> {
+        Ok(present.then(|| {
+            input
+                .bytes(kind.digest_size())
+                .map(|digest| Checksum::new(kind, digest.to_vec()))
+        }))
+    };
+    let md5_digest = checksum(flags & REP_HAS_MD5 != 0, ChecksumKind::Md5)?.transpose()?;
+    let sha1_digest = checksum(flags & REP_HAS_SHA1 != 0, ChecksumKind::Sha1)?.transpose()?;
+    let uniquifier = if flags & REP_HAS_UNIQUIFIER != 0 {
+        Some(RepUniquifier {
+            noderev_txn_id: input.id_part()?,
+            number: input.uint()?,
+        })
+    } else {
+        None
+    };
+
+    Ok(Representation {
+        md5_digest,
+        sha1_digest,
+        revision,
+        item_index,
+        size,
+        expanded_size,
+        txn_id,
+        uniquifier,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::fsfs::low_level::parse_noderev;
+
+    #[test]
+    fn test_noderev_roundtrip() {
+        for text in [
+            "id: 0.0.r0/2\ntype: dir\ncount: 0\ntext: 0 3 4 4 2d2977d1c96f487abe4a1e202dd03b4e\ncpath: /\n\n",
+            "id: 2-1.0-1.r3/1234\n\
+             type: file\n\
+             pred: 2-1.0-1.r2/88\n\
+             count: 2\n\
+             text: 3 1100 20 16 8c3b5f6b4b3a87d0bd4dcd0d2d1bc8a5 \
+             0beec7b5ea3f0fdbc95d0dd47f3c5bc275da8a33 3-2/_5\n\
+             props: 1 500 40 0 d41d8cd98f00b204e9800998ecf8427e\n\
+             cpath: /trunk/iota\n\
+             copyfrom: 1 /iota\n\
+             copyroot: 3 /trunk\n\
+             minfo-cnt: 1\n\
+             minfo-here: y\n\
+             \n",
+        ] {
+            let noderev = parse_noderev(text).unwrap();
+            let data = write_noderev(&noderev);
+            assert!(data.len() < text.len());
+            assert_eq!(read_noderev(&data).unwrap(), noderev);
+            assert!(read_noderev(&data[..data.len() - 1]).is_err());
+        }
+    }
+}
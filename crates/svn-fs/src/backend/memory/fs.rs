@@ -0,0 +1,185 @@
+use std::path::Path;
+
+use svn_types::RevisionNumber;
+
+use super::super::BackendError;
+use super::super::FsLibrary;
+use super::MemRepo;
+use super::MemoryBackend;
+use crate::PackNotifyAction;
+use crate::RecoverReport;
+use crate::SvnFs;
+use crate::UpgradeNotifyAction;
+use crate::VerifyError;
+use crate::backend::check_cancel;
+
+impl FsLibrary for MemoryBackend {
+    fn get_version(&self, _path: &Path) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    /// Create a new in-memory filesystem, registered under PATH.
+    fn create(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
+        let backend = MemoryBackend::register(path, MemRepo::new())?;
+
+        fs.uuid = backend.read().uuid;
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
+    }
+
+    /// Open the in-memory filesystem registered under PATH.
+    fn open_fs(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
+        let backend = MemoryBackend::registered(path)?;
+
+        fs.uuid = backend.read().uuid;
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
+    }
+
+    /// Nothing of an in-memory filesystem can get lost, so this is the
+    /// same as opening it.
+    fn open_fs_for_recovery(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError> {
+        Self::open_fs(fs, path)
+    }
+
+    /// In-memory filesystems have no format to upgrade.
+    fn upgrade_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        _notify: Option<&mut dyn FnMut(i64, UpgradeNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        check_cancel(cancel)?;
+        Self::open_fs(fs, path)
+    }
+
+    /// Verify the in-memory filesystem registered under PATH, see
+    /// [`MemoryBackend::verify`].
+    fn verify_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        start: RevisionNumber,
+        end: RevisionNumber,
+        keep_going: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<Vec<VerifyError>, BackendError> {
+        let backend = MemoryBackend::registered(path)?;
+
+        fs.uuid = backend.read().uuid;
+        let errors = backend.verify(start, end, keep_going, notify, cancel)?;
+        fs.set_inner(Box::new(backend));
+
+        Ok(errors)
+    }
+
+    /// Forget the in-memory filesystem registered under PATH.
+    fn delete_fs(&self, path: &str) -> Result<(), BackendError> {
+        MemoryBackend::delete(Path::new(path))
+    }
+
+    /// Copy the revisions and locks of the in-memory filesystem at
+    /// SRC_PATH to a new one at DST_PATH, or with INCREMENTAL, into the
+    /// existing one there.  Transactions are not copied.
+    fn hotcopy(
+        src_fs: &mut SvnFs,
+        dst_fs: &mut SvnFs,
+        src_path: &Path,
+        dst_path: &Path,
+        incremental: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber, RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        check_cancel(cancel)?;
+        let src = MemoryBackend::registered(src_path)?;
+        let mut copy = src.read().clone();
+        copy.txns.clear();
+        copy.next_txn_number = 0;
+        let src_youngest = copy.youngest();
+
+        let dst = if incremental && MemoryBackend::exists(dst_path) {
+            let dst = MemoryBackend::registered(dst_path)?;
+            {
+                let mut repo = dst.write();
+                if repo.uuid != copy.uuid {
+                    return Err(BackendError::Corrupt(format!(
+                        "The UUID of the hotcopy source ('{}') does not match the UUID of the hotcopy destination ('{}')",
+                        copy.uuid, repo.uuid
+                    )));
+                }
+                let dst_youngest = repo.youngest();
+                if dst_youngest > src_youngest {
+                    return Err(BackendError::Corrupt(format!(
+                        "The hotcopy destination already contains more revisions ({dst_youngest}) than the hotcopy source contains ({src_youngest}); are source and destination swapped?"
+                    )));
+                }
+
+                // Keep the transactions of the destination.
+                copy.txns = std::mem::take(&mut repo.txns);
+                copy.next_txn_number = repo.next_txn_number;
+                *repo = copy;
+
+                if dst_youngest < src_youngest
+                    && let Some(notify) = notify
+                {
+                    notify(dst_youngest + 1, src_youngest);
+                }
+            }
+            dst
+        } else {
+            let dst = MemoryBackend::register(dst_path, copy)?;
+            if let Some(notify) = notify {
+                notify(0, src_youngest);
+            }
+            dst
+        };
+
+        src_fs.uuid = src.read().uuid;
+        dst_fs.uuid = dst.read().uuid;
+        src_fs.set_inner(Box::new(src));
+        dst_fs.set_inner(Box::new(dst));
+
+        Ok(())
+    }
+
+    /// Nothing of an in-memory filesystem can get lost, so recovery just
+    /// reports the youngest revision.
+    fn recover_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<RecoverReport, BackendError> {
+        check_cancel(cancel)?;
+        let backend = MemoryBackend::registered(path)?;
+
+        fs.uuid = backend.read().uuid;
+        let report = RecoverReport {
+            youngest: backend.read().youngest(),
+            ..Default::default()
+        };
+        fs.set_inner(Box::new(backend));
+
+        Ok(report)
+    }
+
+    /// In-memory filesystems cannot be packed; report that with a
+    /// [`PackNotifyAction::Noop`] for shard -1.
+    fn pack_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        notify: Option<&mut dyn FnMut(i64, PackNotifyAction)>,
+        _cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError> {
+        let backend = MemoryBackend::registered(path)?;
+
+        fs.uuid = backend.read().uuid;
+        if let Some(notify) = notify {
+            notify(-1, PackNotifyAction::Noop);
+        }
+        fs.set_inner(Box::new(backend));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+//! Locks of the in-memory back-end, kept in a map sorted by path.
+
+use std::collections::BTreeMap;
+
+use svn_types::Depth;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+use svn_types::SvnLock;
+
+use super::super::BackendError;
+use super::MemoryBackend;
+use super::tree::revision_path;
+use crate::FsAccess;
+use crate::PathChange;
+use crate::PathChangeKind;
+use crate::lock::is_expired;
+use crate::lock::verify_lock;
+use crate::util::canonicalize_abspath;
+use crate::util::fspath_dirname;
+use crate::util::fspath_is_ancestor;
+
+/// Return the unexpired locks in @a locks on @a path and below, ordered
+/// by path.
+fn locks_below<'l>(
+    locks: &'l BTreeMap<String, SvnLock>,
+    path: &'l str,
+) -> impl Iterator<Item = &'l SvnLock> {
+    locks
+        .range(path.to_string()..)
+        .take_while(move |(lock_path, _)| lock_path.starts_with(path))
+        .filter(move |(lock_path, lock)| fspath_is_ancestor(path, lock_path) && !is_expired(lock))
+        .map(|(_, lock)| lock)
+}
+
+/// Fail unless @a access may change every path in @a changes, given the
+/// locks in @a locks.  Paths that get added, deleted or replaced require
+/// the right to change everything below them as well.
+///
+/// `verify_locks`
+pub(super) fn verify_locks(
+    locks: &BTreeMap<String, SvnLock>,
+    changes: &BTreeMap<String, PathChange>,
+    access: Option<&FsAccess>,
+) -> Result<(), BackendError> {
+    // The changes come sorted by path, so a recursively checked path is
+    // seen before its children.
+    let mut last_recursed: Option<&str> = None;
+    for (path, change) in changes {
+        if last_recursed.is_some_and(|parent| fspath_is_ancestor(parent, path)) {
+            continue;
+        }
+
+        if change.change_kind == PathChangeKind::Modify {
+            if let Some(lock) = locks.get(path).filter(|lock| !is_expired(lock)) {
+                verify_lock(access, lock)?;
+            }
+        } else {
+            for lock in locks_below(locks, path) {
+                verify_lock(access, lock)?;
+            }
+            last_recursed = Some(path);
+        }
+    }
+
+    Ok(())
+}
+
+impl MemoryBackend {
+    /// Lock `lock.path`, which must be a file in the youngest revision,
+    /// with @a lock.  If @a current_rev is valid, fail if the file has
+    /// been changed after that revision.  An existing lock on the path is
+    /// replaced only if @a steal_lock is set.
+    ///
+    /// `svn_fs_fs__lock` / `lock_body`
+    pub(crate) fn lock(
+        &self,
+        lock: &SvnLock,
+        current_rev: RevisionNumber,
+        steal_lock: bool,
+    ) -> Result<(), BackendError> {
+        let mut repo = self.write();
+        let path = lock.path.as_str();
+
+        // Until we implement directory locks someday, we only allow locks
+        // on files.
+        let node = match revision_path(&repo, repo.youngest(), path) {
+            Ok(node) => node,
+            Err(BackendError::FileNotFound(_) | BackendError::NotDirectory(_)) => {
+                return Err(BackendError::PathNotInHead(path.to_string()));
+            }
+            Err(e) => return Err(e),
+        };
+        if node.kind == NodeKind::Directory {
+            return Err(BackendError::NotFile(path.to_string()));
+        }
+
+        // Is the caller attempting to lock an out-of-date working file?
+        if current_rev >= 0 && current_rev < node.id.rev() {
+            return Err(BackendError::LockOutOfDate(path.to_string()));
+        }
+
+        // If the caller didn't ask to steal the lock, we need to be sure
+        // that no one else holds it.
+        if !steal_lock && let Some(existing) = repo.locks.get(path).filter(|lock| !is_expired(lock))
+        {
+            return Err(BackendError::PathAlreadyLocked {
+                path: path.to_string(),
+                owner: existing.owner.clone(),
+                fs: self.path.display().to_string(),
+            });
+        }
+
+        repo.locks.insert(path.to_string(), lock.clone());
+        Ok(())
+    }
+
+    /// Remove the lock on @a path.  Unless @a break_lock is set, the lock
+    /// must have the token @a token and be owned by @a username.
+    ///
+    /// `svn_fs_fs__unlock` / `unlock_body`
+    pub(crate) fn unlock(
+        &self,
+        path: &str,
+        token: Option<&str>,
+        username: Option<&str>,
+        break_lock: bool,
+    ) -> Result<(), BackendError> {
+        let path = canonicalize_abspath(path);
+        let mut repo = self.write();
+
+        let lock = repo
+            .locks
+            .get(&path)
+            .filter(|lock| !is_expired(lock))
+            .ok_or_else(|| BackendError::NoSuchLock(path.clone()))?;
+        if !break_lock {
+            // Sanity check:  the incoming token should match lock->token.
+            if token != Some(lock.token.as_str()) {
+                return Err(BackendError::NoSuchLock(path));
+            }
+
+            // There better be a username attached to the fs, and it better
+            // match the lock owner.
+            let user = username.unwrap_or_default();
+            if user != lock.owner {
+                return Err(BackendError::LockOwnerMismatch {
+                    user: user.to_string(),
+                    path,
+                    owner: lock.owner.clone(),
+                });
+            }
+        }
+
+        repo.locks.remove(&path);
+        Ok(())
+    }
+
+    /// Return the unexpired lock on @a path, if any.
+    ///
+    /// `svn_fs_fs__get_lock`
+    pub(crate) fn get_lock(&self, path: &str) -> Option<SvnLock> {
+        self.read()
+            .locks
+            .get(&canonicalize_abspath(path))
+            .filter(|lock| !is_expired(lock))
+            .cloned()
+    }
+
+    /// Return the unexpired locks on @a path and below, down to
+    /// @a depth, ordered by path.
+    ///
+    /// `svn_fs_fs__get_locks`
+    pub(crate) fn get_locks(&self, path: &str, depth: Depth) -> Vec<SvnLock> {
+        let path = canonicalize_abspath(path);
+        let repo = self.read();
+        locks_below(&repo.locks, &path)
+            .filter(|lock| match depth {
+                Depth::Empty => lock.path == path,
+                Depth::Files | Depth::Immediates => {
+                    lock.path == path || (lock.path != "/" && fspath_dirname(&lock.path) == path)
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,280 @@
+//! A back-end that keeps the whole filesystem in process memory.
+//!
+//! Revisions, node-revisions, transactions and locks never touch the
+//! disk, which makes throwaway repositories for tests cheap.  Nodes are
+//! shared between revisions just like in FSFS: a transaction clones the
+//! nodes it changes and commit gives them their final IDs.
+//!
+//! A filesystem is registered under the path it was created with, so it
+//! can be opened by path again while any [`crate::SvnFs`] still has it
+//! open, until [`crate::SvnFs::delete_fs`] removes it.  Its contents are
+//! dropped with the last instance, freeing the path for a new filesystem.
+//! Nothing is written at that path.
+
+mod fs;
+
+mod lock;
+
+mod transaction;
+
+mod tree;
+
+mod verify;
+
+mod vtable;
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
+use std::sync::Weak;
+use std::time::SystemTime;
+
+use svn_subr::properties::SVN_PROP_REVISION_DATE;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+use svn_types::SvnLock;
+use uuid::Uuid;
+
+use super::BackendError;
+use crate::CopyFromInfo;
+use crate::DirectoryEntry;
+use crate::FsId;
+use crate::PathChange;
+use crate::id::IdPart;
+
+/// The in-memory filesystems of this process, by the path they were
+/// created with.  Only the open instances keep a filesystem alive.
+static FILESYSTEMS: Mutex<BTreeMap<PathBuf, Weak<RwLock<MemRepo>>>> = Mutex::new(BTreeMap::new());
+
+/// A node-revision.  Unlike FSFS, the properties and contents are held
+/// directly instead of being referred to by representations.
+#[derive(Debug, Clone)]
+struct MemNode {
+    /// node kind
+    kind: NodeKind,
+    /// the ID of this node-revision
+    id: FsId,
+    /// the node-revision this one is a successor of
+    predecessor_id: Option<FsId>,
+    /// where this node-revision has been copied from, if it is a copy
+    copyfrom: Option<CopyFromInfo>,
+    /// path at which this node-revision came into existence
+    created_path: String,
+    /// revision of the root of the copy this node-revision is on,
+    /// `SVN_INVALID_REVNUM` for copies made in a transaction
+    copyroot_rev: RevisionNumber,
+    /// path of the root of the copy this node-revision is on
+    copyroot_path: String,
+    /// number of nodes with `svn:mergeinfo` at or below this one
+    mergeinfo_count: i64,
+    /// the node's properties
+    props: HashMap<String, String>,
+    /// the entries of a directory, keyed by name
+    entries: BTreeMap<String, DirectoryEntry>,
+    /// the contents of a file
+    contents: Arc<[u8]>,
+}
+
+/// A committed revision.
+#[derive(Debug, Clone)]
+struct MemRevision {
+    /// the ID of the root directory
+    root_id: FsId,
+    /// the revision properties
+    props: HashMap<String, String>,
+    /// the changed paths, folded to one entry per path and sorted
+    changes: Vec<PathChange>,
+}
+
+/// An uncommitted transaction.
+#[derive(Debug, Clone)]
+struct MemTxnData {
+    /// the ID of the transaction
+    txn_id: IdPart,
+    /// the revision the transaction is based on
+    base_rev: RevisionNumber,
+    /// the transaction properties
+    props: HashMap<String, String>,
+    /// the mutable node-revisions of the transaction
+    nodes: HashMap<FsId, MemNode>,
+    /// the changes in the order they were made
+    changes: Vec<PathChange>,
+    /// the next node ID to hand out within this transaction
+    next_node_id: u64,
+    /// the next copy ID to hand out within this transaction
+    next_copy_id: u64,
+}
+
+/// The contents of an in-memory filesystem.
+#[derive(Debug, Clone)]
+struct MemRepo {
+    /// the UUID of the filesystem
+    uuid: Uuid,
+    /// all revisions, indexed by revision number
+    revisions: Vec<MemRevision>,
+    /// the node-revisions of all revisions
+    nodes: HashMap<FsId, MemNode>,
+    /// the uncommitted transactions, by name
+    txns: BTreeMap<String, MemTxnData>,
+    /// the counter part of the next transaction ID
+    next_txn_number: u64,
+    /// the locks, by path
+    locks: BTreeMap<String, SvnLock>,
+}
+
+impl MemRepo {
+    /// Return a new filesystem holding just r0, an empty directory.
+    fn new() -> Self {
+        let root_id = FsId::root(0, 0);
+        let root = MemNode {
+            kind: NodeKind::Directory,
+            id: root_id.clone(),
+            predecessor_id: None,
+            copyfrom: None,
+            created_path: "/".to_string(),
+            copyroot_rev: 0,
+            copyroot_path: "/".to_string(),
+            mergeinfo_count: 0,
+            props: HashMap::new(),
+            entries: BTreeMap::new(),
+            contents: Arc::from([]),
+        };
+
+        Self {
+            uuid: Uuid::new_v4(),
+            revisions: vec![MemRevision {
+                root_id: root_id.clone(),
+                props: HashMap::from([(SVN_PROP_REVISION_DATE.to_string(), date_now())]),
+                changes: Vec::new(),
+            }],
+            nodes: HashMap::from([(root_id, root)]),
+            txns: BTreeMap::new(),
+            next_txn_number: 0,
+            locks: BTreeMap::new(),
+        }
+    }
+
+    /// Return the youngest revision.
+    fn youngest(&self) -> RevisionNumber {
+        self.revisions.len() as RevisionNumber - 1
+    }
+
+    /// Return revision @a rev, which must exist.
+    fn revision(&self, rev: RevisionNumber) -> Result<&MemRevision, BackendError> {
+        usize::try_from(rev)
+            .ok()
+            .and_then(|index| self.revisions.get(index))
+            .ok_or(BackendError::NoSuchRevision(rev))
+    }
+
+    /// Return revision @a rev, which must exist, for modification.
+    fn revision_mut(&mut self, rev: RevisionNumber) -> Result<&mut MemRevision, BackendError> {
+        usize::try_from(rev)
+            .ok()
+            .and_then(|index| self.revisions.get_mut(index))
+            .ok_or(BackendError::NoSuchRevision(rev))
+    }
+
+    /// Return the transaction called @a name, which must exist.
+    fn txn(&self, name: &str) -> Result<&MemTxnData, BackendError> {
+        self.txns
+            .get(name)
+            .ok_or_else(|| BackendError::NoSuchTransaction(name.to_string()))
+    }
+
+    /// Return the transaction called @a name for modification.
+    fn txn_mut(&mut self, name: &str) -> Result<&mut MemTxnData, BackendError> {
+        self.txns
+            .get_mut(name)
+            .ok_or_else(|| BackendError::NoSuchTransaction(name.to_string()))
+    }
+}
+
+/// Return the current time in the format of `svn:date`.
+fn date_now() -> String {
+    svn_subr::time::time_to_cstring(SystemTime::now())
+}
+
+/// An in-memory filesystem.
+#[derive(Debug)]
+pub struct MemoryBackend {
+    path: PathBuf,
+    repo: Arc<RwLock<MemRepo>>,
+}
+
+impl MemoryBackend {
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Lock the contents for reading.  A panic while holding the lock
+    /// leaves the contents consistent, since every change is made under
+    /// a single write lock, so poisoning is ignored.
+    fn read(&self) -> RwLockReadGuard<'_, MemRepo> {
+        self.repo.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the contents for writing.
+    fn write(&self) -> RwLockWriteGuard<'_, MemRepo> {
+        self.repo.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Return whether an in-memory filesystem has been created at
+    /// @a path.
+    pub(crate) fn exists(path: &Path) -> bool {
+        filesystems()
+            .get(path)
+            .is_some_and(|repo| repo.strong_count() > 0)
+    }
+
+    /// Register @a repo under @a path, which must not be taken by a
+    /// filesystem that is still open.
+    fn register(path: &Path, repo: MemRepo) -> Result<Self, BackendError> {
+        let mut filesystems = filesystems();
+        filesystems.retain(|_, repo| repo.strong_count() > 0);
+        if filesystems.contains_key(path) {
+            return Err(BackendError::FileExists(path.display().to_string()));
+        }
+
+        let repo = Arc::new(RwLock::new(repo));
+        filesystems.insert(path.to_path_buf(), Arc::downgrade(&repo));
+        Ok(Self {
+            path: path.to_path_buf(),
+            repo,
+        })
+    }
+
+    /// Return the filesystem registered under @a path.
+    fn registered(path: &Path) -> Result<Self, BackendError> {
+        let repo = filesystems()
+            .get(path)
+            .and_then(Weak::upgrade)
+            .ok_or_else(|| BackendError::FileNotFound(path.display().to_string()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            repo,
+        })
+    }
+
+    /// Remove the filesystem at @a path from the registry.  Instances that
+    /// are still open keep working on their copy.
+    pub(crate) fn delete(path: &Path) -> Result<(), BackendError> {
+        filesystems()
+            .remove(path)
+            .filter(|repo| repo.strong_count() > 0)
+            .map(|_| ())
+            .ok_or_else(|| BackendError::FileNotFound(path.display().to_string()))
+    }
+}
+
+/// Lock the registry of in-memory filesystems.
+fn filesystems() -> std::sync::MutexGuard<'static, BTreeMap<PathBuf, Weak<RwLock<MemRepo>>>> {
+    FILESYSTEMS.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,300 @@
+//! Transactions of the in-memory back-end.
+//!
+//! A transaction holds its mutable node-revisions until commit, which
+//! merges it with the youngest revision if necessary and then moves its
+//! nodes into the new revision under their final IDs.
+
+use std::collections::HashMap;
+
+use svn_subr::properties::SVN_PROP_REVISION_DATE;
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+use super::super::BackendError;
+use super::MemNode;
+use super::MemRevision;
+use super::MemTxnData;
+use super::MemoryBackend;
+use super::date_now;
+use super::lock::verify_locks;
+use super::tree::MemRoot;
+use super::tree::TxnTree;
+use crate::FsAccess;
+use crate::FsId;
+use crate::PathChange;
+use crate::RootTrait;
+use crate::change::process_changes;
+use crate::id::IdPart;
+use crate::txn::Txn;
+
+/// Return the permanent form of the node or copy ID @a part, which is
+/// committed in revision @a rev.
+fn final_id_part(part: IdPart, rev: RevisionNumber) -> IdPart {
+    if part.revision == SVN_INVALID_REVNUM {
+        IdPart {
+            revision: rev,
+            number: part.number,
+        }
+    } else {
+        part
+    }
+}
+
+/// Move the node-revision @a id and, recursively, its mutable children
+/// from @a txn_nodes to @a nodes, giving them their IDs in revision
+/// @a rev.  Record the new IDs in @a new_ids and return the new ID of
+/// @a id.
+///
+/// `write_final_rev`
+fn write_final_rev(
+    txn_nodes: &mut HashMap<FsId, MemNode>,
+    nodes: &mut HashMap<FsId, MemNode>,
+    id: &FsId,
+    rev: RevisionNumber,
+    new_ids: &mut HashMap<FsId, FsId>,
+) -> Result<FsId, BackendError> {
+    // Check to see if this is a transaction node.
+    if !id.is_txn() {
+        return Ok(id.clone());
+    }
+    let mut node = txn_nodes
+        .remove(id)
+        .ok_or_else(|| BackendError::Corrupt(format!("Reference to non-existent node '{id}'")))?;
+
+    // Write out all the children first.
+    for entry in node.entries.values_mut() {
+        entry.id = write_final_rev(txn_nodes, nodes, &entry.id, rev, new_ids)?;
+    }
+
+    // Copies made in the transaction are rooted in the new revision.
+    if node.copyroot_rev == SVN_INVALID_REVNUM {
+        node.copyroot_rev = rev;
+    }
+
+    let new_id = FsId {
+        node_id: final_id_part(node.id.node_id, rev),
+        copy_id: final_id_part(node.id.copy_id, rev),
+        txn_id: IdPart::UNUSED_TXN,
+        rev_item: IdPart {
+            revision: rev,
+            number: new_ids.len() as u64,
+        },
+    };
+    node.id = new_id.clone();
+    nodes.insert(new_id.clone(), node);
+    new_ids.insert(id.clone(), new_id.clone());
+
+    Ok(new_id)
+}
+
+impl MemoryBackend {
+    /// Create a new transaction based on revision @a rev and return its
+    /// ID.
+    ///
+    /// `svn_fs_fs__begin_txn`
+    pub(crate) fn begin_txn_id(&self, rev: RevisionNumber) -> Result<IdPart, BackendError> {
+        let mut repo = self.write();
+        let root_id = repo.revision(rev)?.root_id.clone();
+        let txn_id = IdPart {
+            revision: rev,
+            number: repo.next_txn_number,
+        };
+        repo.next_txn_number += 1;
+
+        // The root of the transaction is a successor of the root of its
+        // base revision.
+        let root = repo.nodes[&root_id].clone();
+        let txn_root_id = FsId::txn_root(txn_id);
+        let root = MemNode {
+            id: txn_root_id.clone(),
+            predecessor_id: Some(root_id),
+            copyfrom: None,
+            ..root
+        };
+
+        // Put a datestamp on the newly created txn, so we always know
+        // exactly how old it is.
+        repo.txns.insert(
+            txn_id.unparse_txn(),
+            MemTxnData {
+                txn_id,
+                base_rev: rev,
+                props: HashMap::from([(SVN_PROP_REVISION_DATE.to_string(), date_now())]),
+                nodes: HashMap::from([(txn_root_id, root)]),
+                changes: Vec::new(),
+                next_node_id: 0,
+                next_copy_id: 0,
+            },
+        );
+
+        Ok(txn_id)
+    }
+
+    /// Return the ID and base revision of the transaction called @a name.
+    ///
+    /// `svn_fs_fs__open_txn`
+    pub(crate) fn open_txn_id(&self, name: &str) -> Result<(IdPart, RevisionNumber), BackendError> {
+        let repo = self.read();
+        let txn = repo.txn(name)?;
+        Ok((txn.txn_id, txn.base_rev))
+    }
+
+    /// Return the names of all uncommitted transactions.
+    ///
+    /// `svn_fs_fs__list_transactions`
+    pub(crate) fn list_transactions(&self) -> Vec<String> {
+        self.read().txns.keys().cloned().collect()
+    }
+
+    /// Remove the transaction called @a name.  Like FSFS, it is no error
+    /// if it is gone already.
+    ///
+    /// `svn_fs_fs__purge_txn`
+    pub(crate) fn purge_txn(&self, name: &str) -> Result<(), BackendError> {
+        self.write().txns.remove(name);
+        Ok(())
+    }
+
+    /// Set the property @a name of the transaction called @a txn_name to
+    /// @a value, or delete it if @a value is `None`.
+    ///
+    /// `svn_fs_fs__change_txn_prop`
+    fn change_txn_prop(
+        &self,
+        txn_name: &str,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), BackendError> {
+        let mut repo = self.write();
+        let props = &mut repo.txn_mut(txn_name)?.props;
+        match value {
+            Some(value) => props.insert(name.to_string(), value.to_string()),
+            None => props.remove(name),
+        };
+
+        Ok(())
+    }
+
+    /// Commit the transaction called @a name as a new revision and return
+    /// its number.  The changes made since its base revision are merged
+    /// into the transaction first.  Fail if that merge conflicts, or if
+    /// @a access may not change a locked path; the transaction stays
+    /// intact then.
+    ///
+    /// `svn_fs_fs__commit` / `commit_body`
+    pub(crate) fn commit(
+        &self,
+        name: &str,
+        access: Option<&FsAccess>,
+    ) -> Result<RevisionNumber, BackendError> {
+        // The write lock makes merging and committing a single step.
+        let mut repo = self.write();
+
+        let changes = process_changes(repo.txn(name)?.changes.clone())?;
+        verify_locks(&repo.locks, &changes, access)?;
+
+        let youngest = repo.youngest();
+        TxnTree::new(&mut repo, name)?.merge_changes(youngest)?;
+
+        // From here on, nothing can fail unless the filesystem is
+        // corrupt.
+        let new_rev = youngest + 1;
+        let mut txn = repo.txns.remove(name).expect("transaction exists");
+        let mut new_ids = HashMap::new();
+        let root_id = write_final_rev(
+            &mut txn.nodes,
+            &mut repo.nodes,
+            &FsId::txn_root(txn.txn_id),
+            new_rev,
+            &mut new_ids,
+        )?;
+
+        // Node-revisions that got committed are referred to by their new
+        // IDs.
+        let changes = changes
+            .into_values()
+            .map(|change| PathChange {
+                node_rev_id: change
+                    .node_rev_id
+                    .map(|id| new_ids.get(&id).cloned().unwrap_or(id)),
+                ..change
+            })
+            .collect();
+
+        // Update the 'svn:date' property.
+        let mut props = txn.props;
+        props.insert(SVN_PROP_REVISION_DATE.to_string(), date_now());
+
+        repo.revisions.push(MemRevision {
+            root_id,
+            props,
+            changes,
+        });
+
+        Ok(new_rev)
+    }
+}
+
+/// A transaction of an in-memory filesystem.
+pub(crate) struct MemTxn<'a> {
+    /// the filesystem this transaction belongs to
+    fs: &'a MemoryBackend,
+    /// the ID of this transaction
+    txn_id: IdPart,
+    /// the name of this transaction
+    name: String,
+    /// the revision the transaction is currently based on
+    base_rev: RevisionNumber,
+}
+
+impl<'a> MemTxn<'a> {
+    /// Return the transaction @a txn_id of @a fs, based on @a base_rev.
+    pub(crate) fn new(fs: &'a MemoryBackend, txn_id: IdPart, base_rev: RevisionNumber) -> Self {
+        Self {
+            fs,
+            txn_id,
+            name: txn_id.unparse_txn(),
+            base_rev,
+        }
+    }
+}
+
+impl Txn for MemTxn<'_> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn base_revision(&self) -> RevisionNumber {
+        self.base_rev
+    }
+
+    fn commit(&mut self, access: Option<&FsAccess>) -> Result<RevisionNumber, BackendError> {
+        let new_rev = self.fs.commit(&self.name, access)?;
+        self.base_rev = new_rev - 1;
+        Ok(new_rev)
+    }
+
+    fn abort(&self) -> Result<(), BackendError> {
+        self.fs.purge_txn(&self.name)
+    }
+
+    fn prop(&self, name: &str) -> Result<Option<String>, BackendError> {
+        Ok(self.fs.read().txn(&self.name)?.props.get(name).cloned())
+    }
+
+    fn proplist(&self) -> Result<HashMap<String, String>, BackendError> {
+        Ok(self.fs.read().txn(&self.name)?.props.clone())
+    }
+
+    fn change_prop(&self, name: &str, value: Option<&str>) -> Result<(), BackendError> {
+        self.fs.change_txn_prop(&self.name, name, value)
+    }
+
+    fn root(&self) -> Result<Box<dyn RootTrait + '_>, BackendError> {
+        Ok(Box::new(MemRoot::txn_root(
+            self.fs,
+            self.txn_id,
+            self.base_rev,
+        )))
+    }
+}
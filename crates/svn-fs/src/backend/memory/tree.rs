@@ -0,0 +1,1327 @@
+//! Revision and transaction roots of the in-memory back-end.
+//!
+//! Paths are walked from the root directory through the directory
+//! entries.  Modifying a node clones it and its parents into the
+//! transaction first, so the revisions stay untouched; merging works on
+//! the directory entries exactly like it does for FSFS.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::io::Read;
+use std::sync::Arc;
+
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_subr::Tristate;
+use svn_subr::mergeinfo::mergeinfo_inheritable;
+use svn_subr::mergeinfo::parse_mergeinfo;
+use svn_subr::properties::SVN_PROP_MERGEINFO;
+use svn_types::Mergeinfo;
+use svn_types::MergeinfoCatalog;
+use svn_types::MergeinfoInheritance;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+use super::super::BackendError;
+use super::MemNode;
+use super::MemRepo;
+use super::MemRevision;
+use super::MemTxnData;
+use super::MemoryBackend;
+use crate::CopyFromInfo;
+use crate::DirectoryEntry;
+use crate::FsId;
+use crate::PathChange;
+use crate::PathChangeKind;
+use crate::change::process_changes;
+use crate::history::HistoryTrait;
+use crate::id::IdPart;
+use crate::root::RootTrait;
+use crate::util::append_to_merged_froms;
+use crate::util::canonicalize_abspath;
+use crate::util::fspath_dirname;
+use crate::util::fspath_join;
+use crate::util::fspath_skip_ancestor;
+
+/// Return the node-revision @a id, looking into @a txn for mutable ones.
+fn lookup<'r>(
+    nodes: &'r HashMap<FsId, MemNode>,
+    txn: Option<&'r MemTxnData>,
+    id: &FsId,
+) -> Result<&'r MemNode, BackendError> {
+    let node = if id.is_txn() {
+        txn.filter(|txn| txn.txn_id == id.txn_id)
+            .and_then(|txn| txn.nodes.get(id))
+    } else {
+        nodes.get(id)
+    };
+    node.ok_or_else(|| BackendError::Corrupt(format!("Reference to non-existent node '{id}'")))
+}
+
+/// Return the node-revision at @a path below the directory @a root_id.
+/// A missing path is reported as @a not_found.
+fn walk_path<'r>(
+    nodes: &'r HashMap<FsId, MemNode>,
+    txn: Option<&'r MemTxnData>,
+    root_id: &FsId,
+    path: &str,
+    not_found: &dyn Fn() -> BackendError,
+) -> Result<&'r MemNode, BackendError> {
+    let mut node = lookup(nodes, txn, root_id)?;
+    let mut walked = String::new();
+
+    for component in path.split('/').filter(|c| !c.is_empty()) {
+        // The parent must be a directory to contain anything.
+        if node.kind != NodeKind::Directory {
+            return Err(BackendError::NotDirectory(if walked.is_empty() {
+                "/".to_string()
+            } else {
+                walked
+            }));
+        }
+        walked.push('/');
+        walked.push_str(component);
+
+        let entry = node.entries.get(component).ok_or_else(not_found)?;
+        node = lookup(nodes, txn, &entry.id)?;
+    }
+
+    Ok(node)
+}
+
+/// Return the node-revision at @a path in revision @a rev of @a repo.
+pub(super) fn revision_path<'r>(
+    repo: &'r MemRepo,
+    rev: RevisionNumber,
+    path: &str,
+) -> Result<&'r MemNode, BackendError> {
+    let root_id = &repo.revision(rev)?.root_id;
+    walk_path(&repo.nodes, None, root_id, path, &|| {
+        BackendError::FileNotFound(format!("revision {rev}, path '{path}'"))
+    })
+}
+
+/// Return the youngest copyroot of the nodes on the way from the
+/// directory @a root_id down to the existing @a path.
+///
+/// `find_youngest_copyroot`
+fn find_youngest_copyroot(
+    nodes: &HashMap<FsId, MemNode>,
+    txn: Option<&MemTxnData>,
+    root_id: &FsId,
+    path: &str,
+) -> Result<(RevisionNumber, String), BackendError> {
+    let mut node = lookup(nodes, txn, root_id)?;
+    let mut youngest = (node.copyroot_rev, node.copyroot_path.clone());
+
+    for component in path.split('/').filter(|c| !c.is_empty()) {
+        let entry = node
+            .entries
+            .get(component)
+            .ok_or_else(|| BackendError::FileNotFound(path.to_string()))?;
+        node = lookup(nodes, txn, &entry.id)?;
+        if node.copyroot_rev >= youngest.0 {
+            youngest = (node.copyroot_rev, node.copyroot_path.clone());
+        }
+    }
+
+    Ok(youngest)
+}
+
+/// The history of a node in an in-memory filesystem.
+///
+/// `fs_history_data_t`
+struct MemHistory<'a> {
+    /// the filesystem
+    fs: &'a MemoryBackend,
+
+    /// path of the historical location
+    path: String,
+
+    /// revision of the historical location
+    revision: RevisionNumber,
+
+    /// where to resume the search after reporting a copy destination
+    hint: Option<(String, RevisionNumber)>,
+
+    /// FALSE until the first call to `svn_fs_history_prev()`
+    is_interesting: bool,
+}
+
+impl<'a> MemHistory<'a> {
+    /// Return a history object for @a path in @a revision of the same
+    /// filesystem as this one.
+    ///
+    /// `assemble_history`
+    fn assemble(
+        &self,
+        path: String,
+        revision: RevisionNumber,
+        is_interesting: bool,
+        hint: Option<(String, RevisionNumber)>,
+    ) -> Self {
+        Self {
+            fs: self.fs,
+            path,
+            revision,
+            hint,
+            is_interesting,
+        }
+    }
+
+    /// Return the history location before this one, which need not be
+    /// an interesting one, or `None` if there is none.  This follows
+    /// the FSFS algorithm step by step.
+    ///
+    /// `history_prev`
+    fn history_prev(&self, cross_copies: bool) -> Result<Option<Self>, BackendError> {
+        let mut path = self.path.as_str();
+        let mut revision = self.revision;
+        let mut reported = self.is_interesting;
+
+        // Continue at the copy source of the copy destination reported
+        // last, if we are crossing copies.
+        if let Some((hint_path, hint_rev)) = &self.hint {
+            if !cross_copies {
+                return Ok(None);
+            }
+            path = hint_path;
+            revision = *hint_rev;
+            reported = false;
+        }
+
+        let repo = self.fs.read();
+        let node = revision_path(&repo, revision, path)?;
+        let mut commit_path = node.created_path.clone();
+        let mut commit_rev = node.id.rev();
+
+        // A line of history has at most one interesting location per
+        // revision: report this one, or move on to the predecessor.
+        if revision == commit_rev {
+            if !reported {
+                return Ok(Some(self.assemble(commit_path, commit_rev, true, None)));
+            }
+            let Some(pred_id) = &node.predecessor_id else {
+                return Ok(None);
+            };
+            let pred = lookup(&repo.nodes, None, pred_id)?;
+            commit_path = pred.created_path.clone();
+            commit_rev = pred.id.rev();
+        }
+
+        // Has the node, or one of its parents, been copied since?
+        let root_id = &repo.revision(revision)?.root_id;
+        let (copyroot_rev, copyroot_path) =
+            find_youngest_copyroot(&repo.nodes, None, root_id, path)?;
+        if copyroot_rev > commit_rev {
+            let copyroot = revision_path(&repo, copyroot_rev, &copyroot_path)?;
+            if let (Some(remainder), Some(copyfrom)) = (
+                fspath_skip_ancestor(&copyroot.created_path, path),
+                &copyroot.copyfrom,
+            ) {
+                // Report the copy destination, unless we just did.
+                let retry = copyroot_rev == revision && reported;
+                let src_path = fspath_join(&copyfrom.path, remainder);
+                return Ok(Some(self.assemble(
+                    path.to_string(),
+                    copyroot_rev,
+                    !retry,
+                    Some((src_path, copyfrom.rev)),
+                )));
+            }
+        }
+
+        Ok(Some(self.assemble(commit_path, commit_rev, true, None)))
+    }
+}
+
+impl<'a> HistoryTrait<'a> for MemHistory<'a> {
+    /// `fs_history_prev`
+    fn prev(
+        &self,
+        cross_copies: bool,
+    ) -> Result<Option<Box<dyn HistoryTrait<'a> + 'a>>, BackendError> {
+        // The root directory changes in every revision and is never
+        // copied.
+        let prev = if self.path == "/" {
+            if !self.is_interesting {
+                Some(self.assemble("/".to_string(), self.revision, true, None))
+            } else if self.revision > 0 {
+                Some(self.assemble("/".to_string(), self.revision - 1, true, None))
+            } else {
+                None
+            }
+        } else {
+            // Skip the locations that are not interesting.
+            let mut prev = self.history_prev(cross_copies)?;
+            while let Some(history) = &prev
+                && !history.is_interesting
+            {
+                prev = history.history_prev(cross_copies)?;
+            }
+            prev
+        };
+
+        Ok(prev.map(|history| Box::new(history) as Box<dyn HistoryTrait<'a> + 'a>))
+    }
+
+    /// `fs_history_location`
+    fn location(&self) -> (String, RevisionNumber) {
+        (self.path.clone(), self.revision)
+    }
+}
+
+/// The root directory of a revision or transaction.
+pub(crate) struct MemRoot<'a> {
+    /// the filesystem this root belongs to
+    fs: &'a MemoryBackend,
+
+    /// the revision of this root, or the base revision for transaction
+    /// roots
+    rev: RevisionNumber,
+
+    /// the transaction of this root, `None` for revision roots
+    txn: Option<IdPart>,
+}
+
+impl<'a> MemRoot<'a> {
+    /// Create the root of revision @a rev in @a fs.
+    pub(crate) fn revision_root(
+        fs: &'a MemoryBackend,
+        rev: RevisionNumber,
+    ) -> Result<Self, BackendError> {
+        fs.read().revision(rev)?;
+        Ok(Self { fs, rev, txn: None })
+    }
+
+    /// Create the root of transaction @a txn_id in @a fs, which is based
+    /// on revision @a base_rev.
+    pub(crate) fn txn_root(
+        fs: &'a MemoryBackend,
+        txn_id: IdPart,
+        base_rev: RevisionNumber,
+    ) -> Self {
+        Self {
+            fs,
+            rev: base_rev,
+            txn: Some(txn_id),
+        }
+    }
+
+    /// Return the error for a @a path that does not exist in this root.
+    ///
+    /// `SVN_FS__NOT_FOUND`
+    fn not_found(&self, path: &str) -> BackendError {
+        match &self.txn {
+            Some(txn_id) => BackendError::FileNotFound(format!(
+                "transaction '{}', path '{path}'",
+                txn_id.unparse_txn()
+            )),
+            None => BackendError::FileNotFound(format!("revision {}, path '{path}'", self.rev)),
+        }
+    }
+
+    /// Return the transaction of this root in @a repo, if any, and the
+    /// ID of its root directory.
+    fn tree<'r>(&self, repo: &'r MemRepo) -> Result<(Option<&'r MemTxnData>, FsId), BackendError> {
+        match &self.txn {
+            Some(txn_id) => Ok((
+                Some(repo.txn(&txn_id.unparse_txn())?),
+                FsId::txn_root(*txn_id),
+            )),
+            None => Ok((None, repo.revision(self.rev)?.root_id.clone())),
+        }
+    }
+
+    /// Return the node-revision at @a path in @a repo.
+    fn open_path<'r>(&self, repo: &'r MemRepo, path: &str) -> Result<&'r MemNode, BackendError> {
+        match &self.txn {
+            Some(_) => {
+                let (txn, root_id) = self.tree(repo)?;
+                walk_path(&repo.nodes, txn, &root_id, path, &|| self.not_found(path))
+            }
+            None => revision_path(repo, self.rev, path),
+        }
+    }
+
+    /// Return the file at @a path in @a repo.
+    fn open_file<'r>(&self, repo: &'r MemRepo, path: &str) -> Result<&'r MemNode, BackendError> {
+        let node = self.open_path(repo, path)?;
+        if node.kind != NodeKind::File {
+            return Err(BackendError::NotFile(path.to_string()));
+        }
+        Ok(node)
+    }
+
+    /// Return the mergeinfo of @a path in @a repo, explicit or inherited
+    /// as selected by @a inherit, or `None` if there is none.
+    ///
+    /// `get_mergeinfo_for_path`
+    fn get_mergeinfo_for_path(
+        &self,
+        repo: &MemRepo,
+        path: &str,
+        inherit: MergeinfoInheritance,
+        adjust_inherited_mergeinfo: bool,
+    ) -> Result<Option<Mergeinfo>, BackendError> {
+        let mut node = self.open_path(repo, path)?;
+        let mut ancestor_path = path;
+        if inherit == MergeinfoInheritance::NearestAncestor {
+            if path == "/" {
+                return Ok(None);
+            }
+            ancestor_path = fspath_dirname(path);
+            node = self.open_path(repo, ancestor_path)?;
+        }
+
+        let value = loop {
+            if let Some(value) = node.props.get(SVN_PROP_MERGEINFO) {
+                break value;
+            }
+            if inherit == MergeinfoInheritance::Explicit || ancestor_path == "/" {
+                return Ok(None);
+            }
+            ancestor_path = fspath_dirname(ancestor_path);
+            node = self.open_path(repo, ancestor_path)?;
+        };
+
+        // Unparsable mergeinfo is treated as none.
+        let Ok(mergeinfo) = parse_mergeinfo(value) else {
+            return Ok(None);
+        };
+
+        // Inherited mergeinfo covers the inheritable ranges only, merged
+        // from the corresponding paths below the merge sources.
+        if adjust_inherited_mergeinfo && ancestor_path != path {
+            let rel_path = fspath_skip_ancestor(ancestor_path, path).unwrap();
+            return Ok(Some(append_to_merged_froms(
+                &mergeinfo_inheritable(&mergeinfo),
+                rel_path,
+            )));
+        }
+
+        Ok(Some(mergeinfo))
+    }
+
+    /// Add the explicit mergeinfo of all nodes below the directory
+    /// @a node at @a path in @a repo to @a catalog.
+    ///
+    /// `crawl_directory_dag_for_mergeinfo`
+    fn crawl_directory_for_mergeinfo(
+        &self,
+        repo: &MemRepo,
+        path: &str,
+        node: &MemNode,
+        catalog: &mut MergeinfoCatalog,
+    ) -> Result<(), BackendError> {
+        for (name, entry) in &node.entries {
+            let kid_path = fspath_join(path, name);
+            let kid = lookup(&repo.nodes, None, &entry.id)?;
+
+            if let Some(Ok(mergeinfo)) = kid
+                .props
+                .get(SVN_PROP_MERGEINFO)
+                .map(|v| parse_mergeinfo(v))
+            {
+                catalog.insert(kid_path.clone(), mergeinfo);
+            }
+
+            if has_descendants_with_mergeinfo(kid) {
+                self.crawl_directory_for_mergeinfo(repo, &kid_path, kid, catalog)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Call @a body with the transaction of this root, prepared for
+    /// modifications.
+    fn modify<T>(
+        &self,
+        body: impl FnOnce(&mut TxnTree<'_>) -> Result<T, BackendError>,
+    ) -> Result<T, BackendError> {
+        let txn_id = self.txn.ok_or(BackendError::NotTxnRoot)?;
+        let mut repo = self.fs.write();
+        body(&mut TxnTree::new(&mut repo, &txn_id.unparse_txn())?)
+    }
+}
+
+/// Return TRUE if nodes below @a node have mergeinfo.
+///
+/// `svn_fs_fs__dag_has_descendants_with_mergeinfo`
+fn has_descendants_with_mergeinfo(node: &MemNode) -> bool {
+    let has_mergeinfo = node.props.contains_key(SVN_PROP_MERGEINFO);
+    node.kind == NodeKind::Directory
+        && (node.mergeinfo_count > 1 || (node.mergeinfo_count == 1 && !has_mergeinfo))
+}
+
+/// One step of the chain of directories leading from the root of a
+/// transaction to a path.
+///
+/// `parent_path_t`
+struct Step {
+    /// the name of this node in its parent directory, empty for the root
+    entry: String,
+    /// the canonical path of this node
+    path: String,
+    /// the node-revision at this step, or `None` if the last component
+    /// of the path does not exist
+    id: Option<FsId>,
+}
+
+/// A transaction being modified, together with the revisions it may
+/// refer to.
+pub(super) struct TxnTree<'r> {
+    revisions: &'r [MemRevision],
+    nodes: &'r HashMap<FsId, MemNode>,
+    txn: &'r mut MemTxnData,
+}
+
+impl<'r> TxnTree<'r> {
+    /// Prepare the transaction called @a name in @a repo for
+    /// modifications.
+    pub(super) fn new(repo: &'r mut MemRepo, name: &str) -> Result<Self, BackendError> {
+        let MemRepo {
+            revisions,
+            nodes,
+            txns,
+            ..
+        } = repo;
+        let txn = txns
+            .get_mut(name)
+            .ok_or_else(|| BackendError::NoSuchTransaction(name.to_string()))?;
+        Ok(Self {
+            revisions,
+            nodes,
+            txn,
+        })
+    }
+
+    /// Return the node-revision @a id.
+    fn get(&self, id: &FsId) -> Result<&MemNode, BackendError> {
+        lookup(self.nodes, Some(self.txn), id)
+    }
+
+    /// Return the mutable node-revision @a id for modification.
+    fn get_mut(&mut self, id: &FsId) -> Result<&mut MemNode, BackendError> {
+        self.txn.nodes.get_mut(id).ok_or_else(|| {
+            BackendError::Corrupt(format!("Attempted to modify non-mutable node '{id}'"))
+        })
+    }
+
+    /// Return the error for a @a path that does not exist in this
+    /// transaction.
+    fn not_found(&self, path: &str) -> BackendError {
+        BackendError::FileNotFound(format!(
+            "transaction '{}', path '{path}'",
+            self.txn.txn_id.unparse_txn()
+        ))
+    }
+
+    /// Return the error for a @a path that already exists in this
+    /// transaction.
+    fn already_exists(&self, path: &str) -> BackendError {
+        BackendError::FileExists(format!(
+            "transaction '{}', path '{path}'",
+            self.txn.txn_id.unparse_txn()
+        ))
+    }
+
+    /// Return a new node ID, unique within the transaction.
+    fn new_node_id(&mut self) -> IdPart {
+        let number = self.txn.next_node_id;
+        self.txn.next_node_id += 1;
+        IdPart {
+            revision: SVN_INVALID_REVNUM,
+            number,
+        }
+    }
+
+    /// Return a new copy ID, unique within the transaction.
+    fn reserve_copy_id(&mut self) -> IdPart {
+        let number = self.txn.next_copy_id;
+        self.txn.next_copy_id += 1;
+        IdPart {
+            revision: SVN_INVALID_REVNUM,
+            number,
+        }
+    }
+
+    /// Return the chain of directories from the root of the transaction
+    /// down to @a path, ending with @a path itself.  If @a last_optional
+    /// is set, the last component of @a path need not exist.
+    ///
+    /// `open_path`
+    fn open_chain(&self, path: &str, last_optional: bool) -> Result<Vec<Step>, BackendError> {
+        let path = canonicalize_abspath(path);
+        let mut chain = vec![Step {
+            entry: String::new(),
+            path: "/".to_string(),
+            id: Some(FsId::txn_root(self.txn.txn_id)),
+        }];
+
+        let components: Vec<_> = path.split('/').filter(|c| !c.is_empty()).collect();
+        for (i, component) in components.iter().enumerate() {
+            let step = chain.last().unwrap();
+            let parent = self.get(step.id.as_ref().unwrap())?;
+
+            // The parent must be a directory to contain anything.
+            if parent.kind != NodeKind::Directory {
+                return Err(BackendError::NotDirectory(step.path.clone()));
+            }
+
+            let id = match parent.entries.get(*component) {
+                Some(entry) => Some(entry.id.clone()),
+                // The last component may be missing.
+                None if last_optional && i + 1 == components.len() => None,
+                None => return Err(self.not_found(&path)),
+            };
+            chain.push(Step {
+                entry: component.to_string(),
+                path: fspath_join(&step.path, component),
+                id,
+            });
+        }
+
+        Ok(chain)
+    }
+
+    /// Make the node at the end of @a chain and all its parents mutable,
+    /// cloning them into the transaction where necessary, and return
+    /// its mutable ID.  @a error_path is the path to report in errors.
+    ///
+    /// `make_path_mutable`
+    fn make_path_mutable(
+        &mut self,
+        chain: &mut [Step],
+        error_path: &str,
+    ) -> Result<FsId, BackendError> {
+        let (last, parents) = chain
+            .split_last_mut()
+            .expect("path chain always contains the root");
+        let id = last.id.clone().ok_or_else(|| self.not_found(error_path))?;
+
+        // Is the node mutable already?  The root of a transaction always
+        // is, so there is a parent otherwise.
+        if id.is_txn() {
+            return Ok(id);
+        }
+        let parent_id = self.make_path_mutable(parents, error_path)?;
+
+        // A node keeps its copy ID when accessed via the path it has been
+        // created at.  Nodes reached through a copied parent move to the
+        // parent's branch.
+        let node = self.get(&id)?.clone();
+        let copy_id = if node.created_path == last.path {
+            node.id.copy_id
+        } else {
+            parent_id.copy_id
+        };
+        let mut new_id = FsId::txn(node.id.node_id, copy_id, self.txn.txn_id);
+        if self.txn.nodes.contains_key(&new_id) {
+            // The same node is on that branch already under another name.
+            new_id.copy_id = self.reserve_copy_id();
+        }
+
+        // Nodes that are not the root of their copy themselves take the
+        // copy root of their new parent.
+        let copyroot_root_id = self.revision_root_id(node.copyroot_rev)?;
+        let copyroot = walk_path(
+            self.nodes,
+            None,
+            &copyroot_root_id,
+            &node.copyroot_path,
+            &|| self.not_found(&node.copyroot_path),
+        )?;
+        let (copyroot_rev, copyroot_path) = if copyroot.id.node_id != node.id.node_id {
+            let parent = self.get(&parent_id)?;
+            (parent.copyroot_rev, parent.copyroot_path.clone())
+        } else {
+            (node.copyroot_rev, node.copyroot_path.clone())
+        };
+
+        let kind = node.kind;
+        self.txn.nodes.insert(
+            new_id.clone(),
+            MemNode {
+                id: new_id.clone(),
+                predecessor_id: Some(node.id),
+                copyfrom: None,
+                created_path: last.path.clone(),
+                copyroot_rev,
+                copyroot_path,
+                ..node
+            },
+        );
+        self.set_entry(&parent_id, &last.entry, Some((&new_id, kind)))?;
+
+        last.id = Some(new_id.clone());
+        Ok(new_id)
+    }
+
+    /// Set the entry @a name of the mutable directory @a parent_id to
+    /// @a target, or remove it if @a target is `None`.
+    ///
+    /// `svn_fs_fs__set_entry`
+    fn set_entry(
+        &mut self,
+        parent_id: &FsId,
+        name: &str,
+        target: Option<(&FsId, NodeKind)>,
+    ) -> Result<(), BackendError> {
+        let parent = self.get_mut(parent_id)?;
+        match target {
+            Some((id, kind)) => {
+                parent.entries.insert(
+                    name.to_string(),
+                    DirectoryEntry {
+                        name: name.to_string(),
+                        id: id.clone(),
+                        kind,
+                    },
+                );
+            }
+            None => {
+                parent.entries.remove(name);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add @a increment to the mergeinfo counts of all nodes in
+    /// @a chain, which must be mutable.
+    ///
+    /// `increment_mergeinfo_up_tree`
+    fn increment_mergeinfo_up_tree(
+        &mut self,
+        chain: &[Step],
+        increment: i64,
+    ) -> Result<(), BackendError> {
+        for step in chain {
+            self.get_mut(step.id.as_ref().unwrap())?.mergeinfo_count += increment;
+        }
+
+        Ok(())
+    }
+
+    /// Record @a change in the transaction.
+    fn add_change(&mut self, change: PathChange) {
+        self.txn.changes.push(change);
+    }
+
+    /// Remove the node @a id and, recursively, all its children from the
+    /// transaction if it is mutable.
+    ///
+    /// `svn_fs_fs__dag_delete_if_mutable`
+    fn delete_if_mutable(&mut self, id: &FsId) {
+        // If the node is immutable, there's nothing to do.
+        let Some(node) = self.txn.nodes.remove(id) else {
+            return;
+        };
+        for entry in node.entries.values() {
+            self.delete_if_mutable(&entry.id);
+        }
+    }
+
+    /// Create a new, empty node of kind @a kind at @a path.
+    ///
+    /// `fs_make_dir` / `fs_make_file`
+    fn make_entry(&mut self, path: &str, kind: NodeKind) -> Result<(), BackendError> {
+        let mut chain = self.open_chain(path, true)?;
+        let path = chain.last().unwrap().path.clone();
+
+        // If there's already a node by that name, complain.
+        let Some((last, parents)) = chain.split_last_mut().filter(|(l, _)| l.id.is_none()) else {
+            return Err(self.already_exists(&path));
+        };
+
+        // Create the node in the (mutable) parent directory.
+        let parent_id = self.make_path_mutable(parents, &path)?;
+        let parent = self.get(&parent_id)?;
+        let (copyroot_rev, copyroot_path) = (parent.copyroot_rev, parent.copyroot_path.clone());
+        let id = FsId::txn(self.new_node_id(), parent_id.copy_id, self.txn.txn_id);
+        self.txn.nodes.insert(
+            id.clone(),
+            MemNode {
+                kind,
+                id: id.clone(),
+                predecessor_id: None,
+                copyfrom: None,
+                created_path: path.clone(),
+                copyroot_rev,
+                copyroot_path,
+                mergeinfo_count: 0,
+                props: HashMap::new(),
+                entries: BTreeMap::new(),
+                contents: Arc::from([]),
+            },
+        );
+        self.set_entry(&parent_id, &last.entry, Some((&id, kind)))?;
+
+        self.add_change(PathChange {
+            path,
+            node_rev_id: Some(id),
+            change_kind: PathChangeKind::Add,
+            node_kind: kind,
+            text_mod: kind == NodeKind::File,
+            prop_mod: false,
+            mergeinfo_mod: Tristate::False,
+            copyfrom: None,
+        });
+        Ok(())
+    }
+
+    /// Delete the node at @a path.
+    ///
+    /// `fs_delete_node`
+    fn delete_node(&mut self, path: &str) -> Result<(), BackendError> {
+        let mut chain = self.open_chain(path, false)?;
+        let path = chain.last().unwrap().path.clone();
+
+        // We can't remove the root of the filesystem.
+        let (last, parents) = chain.split_last_mut().unwrap();
+        if parents.is_empty() {
+            return Err(BackendError::RootDir);
+        }
+        let id = last.id.clone().unwrap();
+        let node = self.get(&id)?;
+        let (kind, mergeinfo_count) = (node.kind, node.mergeinfo_count);
+
+        // Make the parent directory mutable, and do the deletion.
+        let parent_id = self.make_path_mutable(parents, &path)?;
+        self.set_entry(&parent_id, &last.entry, None)?;
+        self.delete_if_mutable(&id);
+        self.increment_mergeinfo_up_tree(parents, -mergeinfo_count)?;
+
+        self.add_change(PathChange {
+            path,
+            node_rev_id: Some(id),
+            change_kind: PathChangeKind::Delete,
+            node_kind: kind,
+            text_mod: false,
+            prop_mod: false,
+            mergeinfo_mod: Tristate::Unknown,
+            copyfrom: None,
+        });
+        Ok(())
+    }
+
+    /// Copy @a from_path in revision @a from_rev to @a to_path.
+    ///
+    /// `fs_copy` / `copy_helper`
+    fn copy(
+        &mut self,
+        from_rev: RevisionNumber,
+        from_path: &str,
+        to_path: &str,
+    ) -> Result<(), BackendError> {
+        let from_path = canonicalize_abspath(from_path);
+        let root_id = &self.revision_root_id(from_rev)?;
+        let from = walk_path(self.nodes, None, root_id, &from_path, &|| {
+            BackendError::FileNotFound(format!("revision {from_rev}, path '{from_path}'"))
+        })?
+        .clone();
+
+        let mut chain = self.open_chain(to_path, true)?;
+        let to_path = chain.last().unwrap().path.clone();
+        let (last, parents) = chain.split_last_mut().unwrap();
+        if parents.is_empty() {
+            return Err(BackendError::RootDir);
+        }
+
+        // Copying a node onto itself changes nothing.  If TO_PATH already
+        // existed prior to the copy, note that this operation is a
+        // replacement, not an addition.
+        let change_kind = match &last.id {
+            Some(id) if *id == from.id => return Ok(()),
+            Some(_) => PathChangeKind::Replace,
+            None => PathChangeKind::Add,
+        };
+        let mergeinfo_start = match &last.id {
+            Some(id) => self.get(id)?.mergeinfo_count,
+            None => 0,
+        };
+
+        let parent_id = self.make_path_mutable(parents, &to_path)?;
+        if let Some(replaced) = &last.id {
+            self.delete_if_mutable(replaced);
+        }
+
+        // Create a successor of the source node that remembers where it
+        // has been copied from and starts a new branch.
+        let copyfrom = CopyFromInfo {
+            rev: from_rev,
+            path: from_path,
+        };
+        let id = FsId::txn(from.id.node_id, self.reserve_copy_id(), self.txn.txn_id);
+        let kind = from.kind;
+        let mergeinfo_increment = from.mergeinfo_count - mergeinfo_start;
+        self.txn.nodes.insert(
+            id.clone(),
+            MemNode {
+                id: id.clone(),
+                predecessor_id: Some(from.id.clone()),
+                copyfrom: Some(copyfrom.clone()),
+                created_path: to_path.clone(),
+                copyroot_rev: SVN_INVALID_REVNUM,
+                copyroot_path: to_path.clone(),
+                ..from
+            },
+        );
+        self.set_entry(&parent_id, &last.entry, Some((&id, kind)))?;
+        self.increment_mergeinfo_up_tree(parents, mergeinfo_increment)?;
+
+        self.add_change(PathChange {
+            path: to_path,
+            node_rev_id: Some(id),
+            change_kind,
+            node_kind: kind,
+            text_mod: false,
+            prop_mod: false,
+            mergeinfo_mod: Tristate::Unknown,
+            copyfrom: Some(copyfrom),
+        });
+        Ok(())
+    }
+
+    /// Return the ID of the root directory of revision @a rev.
+    fn revision_root_id(&self, rev: RevisionNumber) -> Result<FsId, BackendError> {
+        usize::try_from(rev)
+            .ok()
+            .and_then(|index| self.revisions.get(index))
+            .map(|revision| revision.root_id.clone())
+            .ok_or(BackendError::NoSuchRevision(rev))
+    }
+
+    /// Set the property @a name of the node at @a path to @a value.
+    ///
+    /// `fs_change_node_prop`
+    fn change_node_prop(
+        &mut self,
+        path: &str,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), BackendError> {
+        let mut chain = self.open_chain(path, false)?;
+        let path = chain.last().unwrap().path.clone();
+        let id = self.make_path_mutable(&mut chain, &path)?;
+
+        // Keep track of the nodes having mergeinfo.
+        let had_mergeinfo = self.get(&id)?.props.contains_key(SVN_PROP_MERGEINFO);
+        if name == SVN_PROP_MERGEINFO && value.is_some() != had_mergeinfo {
+            self.increment_mergeinfo_up_tree(&chain, if value.is_some() { 1 } else { -1 })?;
+        }
+
+        let node = self.get_mut(&id)?;
+        match value {
+            Some(value) => node.props.insert(name.to_string(), value.to_string()),
+            None => node.props.remove(name),
+        };
+        let kind = node.kind;
+
+        self.add_change(PathChange {
+            path,
+            node_rev_id: Some(id),
+            change_kind: PathChangeKind::Modify,
+            node_kind: kind,
+            text_mod: false,
+            prop_mod: true,
+            mergeinfo_mod: if name == SVN_PROP_MERGEINFO {
+                Tristate::True
+            } else {
+                Tristate::False
+            },
+            copyfrom: None,
+        });
+        Ok(())
+    }
+
+    /// Replace the contents of the file at @a path with @a contents.
+    ///
+    /// `fs_apply_text`
+    fn apply_text(
+        &mut self,
+        path: &str,
+        contents: &[u8],
+        result_checksum: Option<&Checksum>,
+    ) -> Result<(), BackendError> {
+        let mut chain = self.open_chain(path, false)?;
+        let path = chain.last().unwrap().path.clone();
+        if self.get(chain.last().unwrap().id.as_ref().unwrap())?.kind != NodeKind::File {
+            return Err(BackendError::NotFile(path));
+        }
+
+        // Make sure the new text is what the caller expects.
+        if let Some(expected) = result_checksum {
+            expected.verify(&Checksum::compute(ChecksumKind::Md5, contents))?;
+        }
+
+        let id = self.make_path_mutable(&mut chain, &path)?;
+        self.get_mut(&id)?.contents = Arc::from(contents);
+
+        self.add_change(PathChange {
+            path,
+            node_rev_id: Some(id),
+            change_kind: PathChangeKind::Modify,
+            node_kind: NodeKind::File,
+            text_mod: true,
+            prop_mod: false,
+            mergeinfo_mod: Tristate::Unknown,
+            copyfrom: None,
+        });
+        Ok(())
+    }
+
+    /// Merge the changes between the base revision of the transaction
+    /// and revision @a source_rev into the transaction, so that it
+    /// becomes based on @a source_rev.  Fail with
+    /// [`BackendError::Conflict`] if the changes cannot be merged.
+    ///
+    /// `merge_changes`
+    pub(super) fn merge_changes(&mut self, source_rev: RevisionNumber) -> Result<(), BackendError> {
+        let target_id = FsId::txn_root(self.txn.txn_id);
+        let ancestor_id = self
+            .get(&target_id)?
+            .predecessor_id
+            .clone()
+            .ok_or_else(|| {
+                BackendError::Corrupt(format!(
+                    "Transaction '{}' has no base revision",
+                    self.txn.txn_id.unparse_txn()
+                ))
+            })?;
+        let source_id = self.revision_root_id(source_rev)?;
+
+        self.merge("/", &target_id, &source_id, &ancestor_id)?;
+        self.txn.base_rev = source_rev;
+        Ok(())
+    }
+
+    /// Merge the changes between @a ancestor_id and @a source_id into the
+    /// mutable directory @a target_id at @a target_path.  Return by how
+    /// much the mergeinfo count of @a target_id changed.
+    ///
+    /// `merge`
+    fn merge(
+        &mut self,
+        target_path: &str,
+        target_id: &FsId,
+        source_id: &FsId,
+        ancestor_id: &FsId,
+    ) -> Result<i64, BackendError> {
+        // Base cases: Either no change made in source, or same change as
+        // made in target.  Both mean nothing to merge here.
+        if ancestor_id == source_id || source_id == target_id {
+            return Ok(0);
+        }
+
+        // Else proceed, knowing all three are distinct node revisions.
+        // Any of them not being a directory is a conflict, and so is
+        // merging into a node that is not ours to change.
+        let conflict = |path: &str| BackendError::Conflict(path.to_string());
+        let target = self.get(target_id)?.clone();
+        let source = self.get(source_id)?.clone();
+        let ancestor = self.get(ancestor_id)?.clone();
+        if source.kind != NodeKind::Directory
+            || target.kind != NodeKind::Directory
+            || ancestor.kind != NodeKind::Directory
+            || !target_id.is_txn()
+        {
+            return Err(conflict(target_path));
+        }
+
+        // Property changes in the repository conflict with ours.
+        if source.props != ancestor.props {
+            return Err(conflict(target_path));
+        }
+
+        // There is an incoming prop change for this directory.  We will
+        // accept it only if the directory changes were mere updates to its
+        // entries, i.e. there were no additions or removals.
+        if target.props != ancestor.props && dir_structure_changed(&source, &ancestor) {
+            return Err(conflict(target_path));
+        }
+
+        let mut mergeinfo_increment = 0;
+
+        // For each entry E in the ancestor ...
+        for (name, a_entry) in &ancestor.entries {
+            let s_entry = source.entries.get(name);
+            let t_entry = target.entries.get(name);
+
+            if s_entry.is_some_and(|s| s.id == a_entry.id) {
+                // No changes were made to this entry while the transaction
+                // was in progress, so do nothing to the target.
+            } else if t_entry.is_some_and(|t| t.id == a_entry.id) {
+                // A change was made to this entry while the transaction
+                // was in process, but the transaction did not touch this
+                // entry.
+                mergeinfo_increment -= self.get(&a_entry.id)?.mergeinfo_count;
+                if let Some(s_entry) = s_entry {
+                    mergeinfo_increment += self.get(&s_entry.id)?.mergeinfo_count;
+                }
+                self.set_entry(target_id, name, s_entry.map(|s| (&s.id, s.kind)))?;
+            } else {
+                // Changes were made to this entry both within the
+                // transaction and to the repository while the transaction
+                // was in progress.  They must be merged or declared to be
+                // in conflict.
+                let entry_path = fspath_join(target_path, name);
+
+                // A double delete, a delete versus a modification or any
+                // file involved is a conflict.
+                let (Some(s_entry), Some(t_entry)) = (s_entry, t_entry) else {
+                    return Err(conflict(&entry_path));
+                };
+                if s_entry.kind == NodeKind::File
+                    || t_entry.kind == NodeKind::File
+                    || a_entry.kind == NodeKind::File
+                {
+                    return Err(conflict(&entry_path));
+                }
+
+                // If either SOURCE-ENTRY or TARGET-ENTRY is not a direct
+                // modification of ANCESTOR-ENTRY, declare a conflict.
+                let same_node = |id: &FsId| {
+                    id.node_id == a_entry.id.node_id && id.copy_id == a_entry.id.copy_id
+                };
+                if !same_node(&s_entry.id) || !same_node(&t_entry.id) {
+                    return Err(conflict(&entry_path));
+                }
+
+                // Direct modifications were made to the directory
+                // ANCESTOR-ENTRY in both SOURCE and TARGET.  Recursively
+                // merge these modifications.
+                mergeinfo_increment +=
+                    self.merge(&entry_path, &t_entry.id, &s_entry.id, &a_entry.id)?;
+            }
+        }
+
+        // For each entry E in source but not in ancestor ...
+        for (name, s_entry) in &source.entries {
+            if ancestor.entries.contains_key(name) {
+                continue;
+            }
+
+            // If NAME exists in TARGET, declare a conflict.
+            if target.entries.contains_key(name) {
+                return Err(conflict(&fspath_join(target_path, name)));
+            }
+
+            mergeinfo_increment += self.get(&s_entry.id)?.mergeinfo_count;
+            self.set_entry(target_id, name, Some((&s_entry.id, s_entry.kind)))?;
+        }
+
+        // The target is a successor of the source now.
+        let target = self.get_mut(target_id)?;
+        target.predecessor_id = Some(source.id);
+        target.mergeinfo_count += mergeinfo_increment;
+        Ok(mergeinfo_increment)
+    }
+}
+
+/// Return TRUE if entries have been added to or removed from the
+/// directory @a lhs compared to @a rhs, or if any of their entries got
+/// replaced by unrelated nodes.
+///
+/// `compare_dir_structure`
+fn dir_structure_changed(lhs: &MemNode, rhs: &MemNode) -> bool {
+    lhs.entries.len() != rhs.entries.len()
+        || lhs.entries.iter().zip(&rhs.entries).any(
+            |((lhs_name, lhs_entry), (rhs_name, rhs_entry))| {
+                lhs_name != rhs_name || !lhs_entry.id.check_related(&rhs_entry.id)
+            },
+        )
+}
+
+impl RootTrait for MemRoot<'_> {
+    fn paths_changed(&self) -> Result<Vec<PathChange>, BackendError> {
+        let repo = self.fs.read();
+        match &self.txn {
+            Some(txn_id) => {
+                let txn = repo.txn(&txn_id.unparse_txn())?;
+                Ok(process_changes(txn.changes.clone())?
+                    .into_values()
+                    .collect())
+            }
+            None => Ok(repo.revision(self.rev)?.changes.clone()),
+        }
+    }
+
+    fn check_path(&self, path: &str) -> Result<NodeKind, BackendError> {
+        match self.open_path(&self.fs.read(), path) {
+            Ok(node) => Ok(node.kind),
+            Err(BackendError::FileNotFound(_) | BackendError::NotDirectory(_)) => {
+                Ok(NodeKind::None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn node_id(&self, path: &str) -> Result<FsId, BackendError> {
+        Ok(self.open_path(&self.fs.read(), path)?.id.clone())
+    }
+
+    fn node_created_rev(&self, path: &str) -> Result<RevisionNumber, BackendError> {
+        Ok(self.open_path(&self.fs.read(), path)?.id.rev())
+    }
+
+    fn copied_from(&self, path: &str) -> Result<Option<CopyFromInfo>, BackendError> {
+        Ok(self.open_path(&self.fs.read(), path)?.copyfrom.clone())
+    }
+
+    fn node_history(&self, path: &str) -> Result<Box<dyn HistoryTrait<'_> + '_>, BackendError> {
+        if self.txn.is_some() {
+            return Err(BackendError::NotRevisionRoot);
+        }
+        self.open_path(&self.fs.read(), path)?;
+
+        Ok(Box::new(MemHistory {
+            fs: self.fs,
+            path: canonicalize_abspath(path),
+            revision: self.rev,
+            hint: None,
+            is_interesting: false,
+        }))
+    }
+
+    fn closest_copy(&self, path: &str) -> Result<Option<(RevisionNumber, String)>, BackendError> {
+        let path = canonicalize_abspath(path);
+        let repo = self.fs.read();
+        let node = self.open_path(&repo, &path)?;
+        let (txn, root_id) = self.tree(&repo)?;
+        let (copy_dst_rev, copy_dst_path) =
+            find_youngest_copyroot(&repo.nodes, txn, &root_id, &path)?;
+        if copy_dst_rev <= 0 {
+            return Ok(None);
+        }
+
+        // The node must have existed, and been affected by the copy, as
+        // of the copy revision.
+        let copy_dst_node = match revision_path(&repo, copy_dst_rev, &path) {
+            Ok(node) => node,
+            Err(BackendError::FileNotFound(_) | BackendError::NotDirectory(_)) => {
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        if !copy_dst_node.id.check_related(&node.id)
+            || (copy_dst_node.id.rev() == copy_dst_rev && copy_dst_node.predecessor_id.is_none())
+        {
+            return Ok(None);
+        }
+
+        Ok(Some((copy_dst_rev, copy_dst_path)))
+    }
+
+    fn node_origin_rev(&self, path: &str) -> Result<RevisionNumber, BackendError> {
+        // Node IDs always carry the revision they were created in.
+        Ok(self.open_path(&self.fs.read(), path)?.id.node_id.revision)
+    }
+
+    fn node_proplist(&self, path: &str) -> Result<HashMap<String, String>, BackendError> {
+        Ok(self.open_path(&self.fs.read(), path)?.props.clone())
+    }
+
+    fn get_mergeinfo(
+        &self,
+        paths: &[&str],
+        inherit: MergeinfoInheritance,
+        include_descendants: bool,
+        adjust_inherited_mergeinfo: bool,
+    ) -> Result<MergeinfoCatalog, BackendError> {
+        if self.txn.is_some() {
+            return Err(BackendError::NotRevisionRoot);
+        }
+
+        let repo = self.fs.read();
+        let mut catalog = MergeinfoCatalog::new();
+        for path in paths {
+            let path = canonicalize_abspath(path);
+            if let Some(mergeinfo) =
+                self.get_mergeinfo_for_path(&repo, &path, inherit, adjust_inherited_mergeinfo)?
+            {
+                catalog.insert(path.clone(), mergeinfo);
+            }
+
+            if include_descendants {
+                let node = self.open_path(&repo, &path)?;
+                if has_descendants_with_mergeinfo(node) {
+                    self.crawl_directory_for_mergeinfo(&repo, &path, node, &mut catalog)?;
+                }
+            }
+        }
+
+        Ok(catalog)
+    }
+
+    fn dir_entries(&self, path: &str) -> Result<BTreeMap<String, DirectoryEntry>, BackendError> {
+        let repo = self.fs.read();
+        let node = self.open_path(&repo, path)?;
+        if node.kind != NodeKind::Directory {
+            return Err(BackendError::NotDirectory(path.to_string()));
+        }
+        Ok(node.entries.clone())
+    }
+
+    fn file_length(&self, path: &str) -> Result<u64, BackendError> {
+        Ok(self.open_file(&self.fs.read(), path)?.contents.len() as u64)
+    }
+
+    fn file_checksum(
+        &self,
+        kind: ChecksumKind,
+        path: &str,
+    ) -> Result<Option<Checksum>, BackendError> {
+        let repo = self.fs.read();
+        let node = self.open_file(&repo, path)?;
+        Ok(Some(Checksum::compute(kind, &node.contents)))
+    }
+
+    fn file_contents(&self, path: &str) -> Result<Box<dyn Read + 'static>, BackendError> {
+        let contents = self.open_file(&self.fs.read(), path)?.contents.clone();
+        Ok(Box::new(Cursor::new(contents)))
+    }
+
+    fn make_dir(&self, path: &str) -> Result<(), BackendError> {
+        self.modify(|tree| tree.make_entry(path, NodeKind::Directory))
+    }
+
+    fn make_file(&self, path: &str) -> Result<(), BackendError> {
+        self.modify(|tree| tree.make_entry(path, NodeKind::File))
+    }
+
+    fn delete_node(&self, path: &str) -> Result<(), BackendError> {
+        self.modify(|tree| tree.delete_node(path))
+    }
+
+    fn copy(
+        &self,
+        from_rev: RevisionNumber,
+        from_path: &str,
+        to_path: &str,
+    ) -> Result<(), BackendError> {
+        self.modify(|tree| tree.copy(from_rev, from_path, to_path))
+    }
+
+    fn change_node_prop(
+        &self,
+        path: &str,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), BackendError> {
+        self.modify(|tree| tree.change_node_prop(path, name, value))
+    }
+
+    fn apply_text(
+        &self,
+        path: &str,
+        contents: &[u8],
+        result_checksum: Option<&Checksum>,
+    ) -> Result<(), BackendError> {
+        self.modify(|tree| tree.apply_text(path, contents, result_checksum))
+    }
+}
@@ -0,0 +1,113 @@
+//! Verification of the in-memory back-end: every revision's tree must
+//! consist of node-revisions that exist, match their directory entries
+//! and have not been committed after the revision itself.
+
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+use super::super::BackendError;
+use super::MemRepo;
+use super::MemoryBackend;
+use crate::FsId;
+use crate::VerifyError;
+use crate::VerifyErrorKind;
+use crate::backend::check_cancel;
+use crate::util::fspath_join;
+
+/// Check the node-revision @a id at @a path in revision @a rev and,
+/// recursively, its children.  Add the problems found to @a errors.
+fn verify_node(
+    repo: &MemRepo,
+    rev: RevisionNumber,
+    path: &str,
+    id: &FsId,
+    errors: &mut Vec<VerifyError>,
+) {
+    let mut error = |kind| {
+        errors.push(VerifyError {
+            revision: rev,
+            path: Some(path.to_string()),
+            offset: None,
+            kind,
+        })
+    };
+
+    let Some(node) = repo.nodes.get(id) else {
+        error(VerifyErrorKind::Corrupt(format!(
+            "Reference to non-existent node '{id}'"
+        )));
+        return;
+    };
+    if id.is_txn() || id.rev() > rev {
+        error(VerifyErrorKind::Corrupt(format!(
+            "Node '{id}' does not belong to revision {rev} or older"
+        )));
+        return;
+    }
+
+    for (name, entry) in &node.entries {
+        let child_path = fspath_join(path, name);
+        match repo.nodes.get(&entry.id) {
+            Some(child) if child.kind != entry.kind => errors.push(VerifyError {
+                revision: rev,
+                path: Some(child_path),
+                offset: None,
+                kind: VerifyErrorKind::DirectoryEntry(format!(
+                    "Directory entry '{name}' claims kind {:?} but node '{}' is {:?}",
+                    entry.kind, entry.id, child.kind
+                )),
+            }),
+            _ => verify_node(repo, rev, &child_path, &entry.id, errors),
+        }
+    }
+}
+
+impl MemoryBackend {
+    /// Verify revisions @a start to @a end, calling @a notify before each
+    /// one.  Unless @a keep_going is set, stop after the first revision
+    /// with problems.
+    ///
+    /// `svn_fs_fs__verify`
+    pub(crate) fn verify(
+        &self,
+        start: RevisionNumber,
+        end: RevisionNumber,
+        keep_going: bool,
+        mut notify: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<Vec<VerifyError>, BackendError> {
+        let repo = self.read();
+        let youngest = repo.youngest();
+        let start = if start == SVN_INVALID_REVNUM {
+            0
+        } else {
+            start
+        };
+        let end = if end == SVN_INVALID_REVNUM {
+            youngest
+        } else {
+            end
+        };
+        if start > end || end > youngest {
+            return Err(BackendError::Corrupt(format!(
+                "Invalid revision range [{start}, {end}]"
+            )));
+        }
+
+        let mut errors = Vec::new();
+        for rev in start..=end {
+            check_cancel(cancel)?;
+            if let Some(notify) = &mut notify {
+                notify(rev);
+            }
+
+            let failed_before = errors.len();
+            verify_node(&repo, rev, "/", &repo.revision(rev)?.root_id, &mut errors);
+            if errors.len() > failed_before && !keep_going {
+                break;
+            }
+        }
+
+        Ok(errors)
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use svn_types::Depth;
+use svn_types::RevisionNumber;
+use svn_types::SvnLock;
+
+use super::super::BackendError;
+use super::super::FsInstance;
+use super::MemoryBackend;
+use super::transaction::MemTxn;
+use super::tree::MemRoot;
+use crate::RootTrait;
+use crate::txn::Txn;
+
+impl FsInstance for MemoryBackend {
+    fn youngest_rev(&self) -> Result<RevisionNumber, BackendError> {
+        Ok(self.read().youngest())
+    }
+
+    /// Revision properties are never cached, so there is nothing to
+    /// refresh.
+    fn refresh_revision_prop(&self) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    fn revision_proplist(
+        &self,
+        rev: RevisionNumber,
+    ) -> Result<HashMap<String, String>, BackendError> {
+        Ok(self.read().revision(rev)?.props.clone())
+    }
+
+    /// The check and the change happen under one write lock.
+    fn change_rev_prop(
+        &self,
+        rev: RevisionNumber,
+        name: &str,
+        old_value: Option<Option<&str>>,
+        value: Option<&str>,
+    ) -> Result<(), BackendError> {
+        let mut repo = self.write();
+        let props = &mut repo.revision_mut(rev)?.props;
+        if let Some(old_value) = old_value
+            && props.get(name).map(String::as_str) != old_value
+        {
+            return Err(BackendError::PropBaseValueMismatch(name.to_string()));
+        }
+
+        match value {
+            Some(value) => props.insert(name.to_string(), value.to_string()),
+            None => props.remove(name),
+        };
+
+        Ok(())
+    }
+
+    /// There are no files to keep consistent, so just call @a freeze_func.
+    /// Holding the contents lock instead would block its reads.
+    fn freeze(&self, freeze_func: &mut dyn FnMut()) -> Result<(), BackendError> {
+        freeze_func();
+        Ok(())
+    }
+
+    fn data_mut(&mut self) -> &mut dyn std::any::Any {
+        &mut self.repo
+    }
+
+    fn revision_root(&self, rev: RevisionNumber) -> Result<Box<dyn RootTrait + '_>, BackendError> {
+        Ok(Box::new(MemRoot::revision_root(self, rev)?))
+    }
+
+    fn begin_txn(&self, rev: RevisionNumber) -> Result<Box<dyn Txn + '_>, BackendError> {
+        let txn_id = self.begin_txn_id(rev)?;
+        Ok(Box::new(MemTxn::new(self, txn_id, rev)))
+    }
+
+    fn open_txn(&self, name: &str) -> Result<Box<dyn Txn + '_>, BackendError> {
+        let (txn_id, base_rev) = self.open_txn_id(name)?;
+        Ok(Box::new(MemTxn::new(self, txn_id, base_rev)))
+    }
+
+    fn list_transactions(&self) -> Result<Vec<String>, BackendError> {
+        Ok(MemoryBackend::list_transactions(self))
+    }
+
+    fn purge_txn(&self, name: &str) -> Result<(), BackendError> {
+        MemoryBackend::purge_txn(self, name)
+    }
+
+    /// Representations are never shared.
+    fn build_rep_cache(
+        &self,
+        _start: RevisionNumber,
+        _end: RevisionNumber,
+    ) -> Result<(), BackendError> {
+        Err(BackendError::RepSharingNotAllowed)
+    }
+
+    /// Without a rep-sharing cache, there is nothing to verify.
+    fn verify_rep_cache(
+        &self,
+        _start: RevisionNumber,
+        _end: RevisionNumber,
+    ) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    fn lock(
+        &self,
+        lock: &SvnLock,
+        current_rev: RevisionNumber,
+        steal_lock: bool,
+    ) -> Result<(), BackendError> {
+        MemoryBackend::lock(self, lock, current_rev, steal_lock)
+    }
+
+    fn unlock(
+        &self,
+        path: &str,
+        token: Option<&str>,
+        username: Option<&str>,
+        break_lock: bool,
+    ) -> Result<(), BackendError> {
+        MemoryBackend::unlock(self, path, token, username, break_lock)
+    }
+
+    fn get_lock(&self, path: &str) -> Result<Option<SvnLock>, BackendError> {
+        Ok(MemoryBackend::get_lock(self, path))
+    }
+
+    fn get_locks(&self, path: &str, depth: Depth) -> Result<Vec<SvnLock>, BackendError> {
+        Ok(MemoryBackend::get_locks(self, path, depth))
+    }
+
+    fn generate_lock_token(&self) -> String {
+        crate::lock::generate_lock_token()
+    }
+}
@@ -8,6 +8,8 @@ pub mod fsfs;
 
 pub mod fsx;
 
+pub mod memory;
+
 // Names of special files in the fs_fs filesystem
 // from `fs.h`
 
@@ -117,11 +119,29 @@ pub const CONFIG_OPTION_PACK_AFTER_COMMIT: &str = "pack-after-commit";
 pub const CONFIG_OPTION_VERIFY_BEFORE_COMMIT: &str = "verify-before-commit";
 pub const CONFIG_OPTION_COMPRESSION: &str = "compression";
 
+use std::collections::HashMap;
 use std::{any::Any, path::Path};
 
+use svn_types::Depth;
 use svn_types::RevisionNumber;
+use svn_types::SvnLock;
 
+use crate::PackNotifyAction;
+use crate::RecoverReport;
+use crate::RootTrait;
 use crate::SvnFs;
+use crate::UpgradeNotifyAction;
+use crate::VerifyError;
+use crate::txn::Txn;
+
+/// Return [`BackendError::Cancelled`] if @a cancel, if given, asks us to
+/// stop.
+pub(crate) fn check_cancel(cancel: Option<&dyn Fn() -> bool>) -> Result<(), BackendError> {
+    match cancel {
+        Some(cancel) if cancel() => Err(BackendError::Cancelled),
+        _ => Ok(()),
+    }
+}
 
 pub trait FsDataTrait {}
 
@@ -131,12 +151,92 @@ pub trait FsDataTrait {}
 pub trait FsInstance: FsLibrary {
     // type Data: Send + Sync + FsDataTrait;
 
-    fn youngest_rev(&self) -> RevisionNumber;
+    fn youngest_rev(&self) -> Result<RevisionNumber, BackendError>;
     fn refresh_revision_prop(&self) -> Result<(), BackendError>;
 
-    fn revision_prop(&self) -> Result<(), BackendError>;
+    /// Return the properties of revision @a rev.
+    fn revision_proplist(
+        &self,
+        rev: RevisionNumber,
+    ) -> Result<HashMap<String, String>, BackendError>;
+
+    /// Set the property @a name of revision @a rev to @a value, or
+    /// delete it if that is `None`.  If @a old_value is given, fail with
+    /// [`BackendError::PropBaseValueMismatch`] unless the property has
+    /// that value, `None` meaning "not present".
+    fn change_rev_prop(
+        &self,
+        rev: RevisionNumber,
+        name: &str,
+        old_value: Option<Option<&str>>,
+        value: Option<&str>,
+    ) -> Result<(), BackendError>;
+
+    /// Call @a freeze_func while no commit, revprop change or pack can
+    /// proceed, e.g. to take a consistent backup.
+    fn freeze(&self, freeze_func: &mut dyn FnMut()) -> Result<(), BackendError>;
+
+    fn data_mut(&mut self) -> &mut dyn Any;
+
+    /// Return the back-end implementation of the root of revision @a rev.
+    fn revision_root(&self, rev: RevisionNumber) -> Result<Box<dyn RootTrait + '_>, BackendError>;
+
+    /// Begin a new transaction based on revision @a rev.
+    fn begin_txn(&self, rev: RevisionNumber) -> Result<Box<dyn Txn + '_>, BackendError>;
+
+    /// Open the existing transaction called @a name.
+    fn open_txn(&self, name: &str) -> Result<Box<dyn Txn + '_>, BackendError>;
+
+    /// Return the names of all uncommitted transactions.
+    fn list_transactions(&self) -> Result<Vec<String>, BackendError>;
+
+    /// Remove all data of the transaction called @a name.
+    fn purge_txn(&self, name: &str) -> Result<(), BackendError>;
+
+    /// Add the representations committed in revisions @a start to
+    /// @a end to the rep-sharing cache.
+    fn build_rep_cache(
+        &self,
+        start: RevisionNumber,
+        end: RevisionNumber,
+    ) -> Result<(), BackendError>;
+
+    /// Check the rep-sharing cache entries of revisions @a start to
+    /// @a end.
+    fn verify_rep_cache(
+        &self,
+        start: RevisionNumber,
+        end: RevisionNumber,
+    ) -> Result<(), BackendError>;
+
+    /// Store @a lock on `lock.path`, replacing an existing lock only if
+    /// @a steal_lock is set.  If @a current_rev is valid, fail if the
+    /// path has been changed after that revision.
+    fn lock(
+        &self,
+        lock: &SvnLock,
+        current_rev: RevisionNumber,
+        steal_lock: bool,
+    ) -> Result<(), BackendError>;
+
+    /// Remove the lock on @a path, which must have the token @a token and
+    /// be owned by @a username unless @a break_lock is set.
+    fn unlock(
+        &self,
+        path: &str,
+        token: Option<&str>,
+        username: Option<&str>,
+        break_lock: bool,
+    ) -> Result<(), BackendError>;
+
+    /// Return the lock on @a path, if any.
+    fn get_lock(&self, path: &str) -> Result<Option<SvnLock>, BackendError>;
 
-    fn data_mut(&mut self) -> &mut Box<dyn Any>;
+    /// Return the locks on @a path and below, down to @a depth.
+    fn get_locks(&self, path: &str, depth: Depth) -> Result<Vec<SvnLock>, BackendError>;
+
+    /// Return a new, unique lock token.
+    fn generate_lock_token(&self) -> String;
 }
 
 /// Top-level library vtable type
@@ -155,11 +255,68 @@ pub trait FsLibrary: Send + Sync {
     fn create(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError>
     where
         Self: Sized;
-    fn open_fs(&self, path: &Path) -> Result<(), BackendError>;
-    fn open_fs_for_recovery(&self, path: &str) -> Result<(), BackendError>;
-    fn upgrade_fs(&self, path: &str) -> Result<(), BackendError>;
-    fn verify_fs(&self, path: &str) -> Result<(), BackendError>;
+    fn open_fs(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError>
+    where
+        Self: Sized;
+    fn open_fs_for_recovery(fs: &mut SvnFs, path: &Path) -> Result<(), BackendError>
+    where
+        Self: Sized;
+    /// Upgrade the filesystem at @a path to the latest format supported
+    /// by this back-end, reporting progress to @a notify and polling
+    /// @a cancel.
+    fn upgrade_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        notify: Option<&mut dyn FnMut(i64, UpgradeNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError>
+    where
+        Self: Sized;
+    /// Open the filesystem at @a path and verify revisions @a start to
+    /// @a end, reporting progress to @a notify and polling @a cancel.
+    fn verify_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        start: RevisionNumber,
+        end: RevisionNumber,
+        keep_going: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<Vec<VerifyError>, BackendError>
+    where
+        Self: Sized;
     fn delete_fs(&self, path: &str) -> Result<(), BackendError>;
-    fn hotcopy(&self, src_path: &str, dst_path: &str) -> Result<(), BackendError>;
-    fn pack_fs(&self, path: &str) -> Result<(), BackendError>;
+    /// Open the filesystem at @a src_path and copy it to @a dst_path,
+    /// reporting the copied revision ranges to @a notify and polling
+    /// @a cancel.
+    fn hotcopy(
+        src_fs: &mut SvnFs,
+        dst_fs: &mut SvnFs,
+        src_path: &Path,
+        dst_path: &Path,
+        incremental: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber, RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError>
+    where
+        Self: Sized;
+    /// Open the filesystem at @a path for recovery and recover it, polling
+    /// @a cancel.
+    fn recover_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<RecoverReport, BackendError>
+    where
+        Self: Sized;
+    /// Open the filesystem at @a path and pack its completed shards,
+    /// reporting progress to @a notify and polling @a cancel.
+    fn pack_fs(
+        fs: &mut SvnFs,
+        path: &Path,
+        notify: Option<&mut dyn FnMut(i64, PackNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), BackendError>
+    where
+        Self: Sized;
 }
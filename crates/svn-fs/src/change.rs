@@ -0,0 +1,281 @@
+//! Changed paths of a revision or transaction.
+
+use std::collections::BTreeMap;
+
+use svn_subr::Tristate;
+use svn_types::NodeKind;
+
+use crate::backend::BackendError;
+use crate::id::FsId;
+use crate::node::CopyFromInfo;
+
+/// The kind of change that occurred on the path.
+///
+/// `svn_fs_path_change_kind_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathChangeKind {
+    /// path modified in txn
+    Modify,
+    /// path added in txn
+    Add,
+    /// path removed in txn
+    Delete,
+    /// path removed and re-added in txn
+    Replace,
+    /// ignore all previous change items for path (internal-use only)
+    Reset,
+}
+
+impl PathChangeKind {
+    /// The word used for this kind in changed-paths lists.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PathChangeKind::Modify => "modify",
+            PathChangeKind::Add => "add",
+            PathChangeKind::Delete => "delete",
+            PathChangeKind::Replace => "replace",
+            PathChangeKind::Reset => "reset",
+        }
+    }
+
+    /// Parse the word used for this kind in changed-paths lists.
+    pub fn from_word(word: &str) -> Option<Self> {
+        match word {
+            "modify" => Some(PathChangeKind::Modify),
+            "add" => Some(PathChangeKind::Add),
+            "delete" => Some(PathChangeKind::Delete),
+            "replace" => Some(PathChangeKind::Replace),
+            "reset" => Some(PathChangeKind::Reset),
+            _ => None,
+        }
+    }
+}
+
+/// Change descriptor.
+///
+/// `svn_fs_path_change3_t`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathChange {
+    /// Path of the node that got changed.
+    pub path: String,
+
+    /// node revision id of changed path
+    pub node_rev_id: Option<FsId>,
+
+    /// kind of change
+    pub change_kind: PathChangeKind,
+
+    /// what node kind is the path?
+    /// (Note: it is legal for this to be #svn_node_unknown.)
+    pub node_kind: NodeKind,
+
+    /// was the text touched?
+    /// For node_kind=dir: always false. For node_kind=file:
+    ///   modify:      true iff text touched.
+    ///   add (copy):  true iff text touched.
+    ///   add (plain): always true.
+    ///   delete:      always false.
+    ///   replace:     as for the add/copy part of the replacement.
+    pub text_mod: bool,
+
+    /// were the properties touched?
+    ///   modify:      true iff props touched.
+    ///   add (copy):  true iff props touched.
+    ///   add (plain): true iff props touched.
+    ///   delete:      always false.
+    ///   replace:     as for the add/copy part of the replacement.
+    pub prop_mod: bool,
+
+    /// was the mergeinfo property touched?
+    ///   modify:      } true iff svn:mergeinfo property add/del/mod
+    ///   add (copy):  }          and fs format supports this flag.
+    ///   add (plain): }
+    ///   delete:      always false.
+    ///   replace:     as for the add/copy part of the replacement.
+    pub mergeinfo_mod: Tristate,
+
+    /// Copyfrom revision and path, if the node was copied.
+    pub copyfrom: Option<CopyFromInfo>,
+}
+
+/// Merge the change @a change into the changes folded so far in
+/// @a changes, keyed by path.
+///
+/// `fold_change`
+pub(crate) fn fold_change(
+    changes: &mut BTreeMap<String, PathChange>,
+    change: PathChange,
+) -> Result<(), BackendError> {
+    let invalid = |message: &str| BackendError::Corrupt(format!("{message} for '{}'", change.path));
+
+    let Some(old) = changes.get_mut(&change.path) else {
+        // This path is not yet in the hash, so add it.
+        if change.change_kind != PathChangeKind::Reset {
+            changes.insert(change.path.clone(), change);
+        }
+        return Ok(());
+    };
+
+    // Sanity check: only allow unknown node revision IDs in the `reset'
+    // case.
+    if change.node_rev_id.is_none() && change.change_kind != PathChangeKind::Reset {
+        return Err(invalid("Missing required node revision ID"));
+    }
+
+    // Sanity check: an add, replacement, or reset must be the first thing
+    // to follow a deletion.
+    if old.change_kind == PathChangeKind::Delete
+        && !matches!(
+            change.change_kind,
+            PathChangeKind::Replace | PathChangeKind::Reset | PathChangeKind::Add
+        )
+    {
+        return Err(invalid(
+            "Invalid change ordering: new node revision ID without delete",
+        ));
+    }
+
+    // Sanity check: an add can't follow anything except a delete or
+    // reset.
+    if change.change_kind == PathChangeKind::Add
+        && !matches!(
+            old.change_kind,
+            PathChangeKind::Delete | PathChangeKind::Reset
+        )
+    {
+        return Err(invalid(
+            "Invalid change ordering: add change on preexisting path",
+        ));
+    }
+
+    match change.change_kind {
+        PathChangeKind::Reset => {
+            // A reset here will simply remove the path change from the
+            // hash.
+            changes.remove(&change.path);
+        }
+        PathChangeKind::Delete => {
+            if old.change_kind == PathChangeKind::Add {
+                // If the path was introduced in this transaction via an
+                // add, and we are deleting it, just remove the path
+                // altogether.
+                changes.remove(&change.path);
+            } else {
+                // A deletion overrules a previous change (modify).
+                old.change_kind = PathChangeKind::Delete;
+                old.text_mod = change.text_mod;
+                old.prop_mod = change.prop_mod;
+                old.mergeinfo_mod = change.mergeinfo_mod;
+                old.copyfrom = None;
+            }
+        }
+        PathChangeKind::Add | PathChangeKind::Replace => {
+            // An add at this point must be following a previous delete,
+            // so treat it just like a replace.
+            old.change_kind = PathChangeKind::Replace;
+            old.node_rev_id = change.node_rev_id;
+            old.node_kind = change.node_kind;
+            old.text_mod = change.text_mod;
+            old.prop_mod = change.prop_mod;
+            old.mergeinfo_mod = change.mergeinfo_mod;
+            old.copyfrom = change.copyfrom;
+        }
+        PathChangeKind::Modify => {
+            old.node_rev_id = change.node_rev_id;
+            old.text_mod |= change.text_mod;
+            old.prop_mod |= change.prop_mod;
+            if change.mergeinfo_mod == Tristate::True {
+                old.mergeinfo_mod = Tristate::True;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fold the raw list of @a changes as recorded in a transaction into one
+/// change per path.
+///
+/// `process_changes`
+pub(crate) fn process_changes(
+    changes: Vec<PathChange>,
+) -> Result<BTreeMap<String, PathChange>, BackendError> {
+    let mut folded = BTreeMap::new();
+
+    for change in changes {
+        let path = change.path.clone();
+        let is_deletion = matches!(
+            change.change_kind,
+            PathChangeKind::Delete | PathChangeKind::Replace
+        );
+        fold_change(&mut folded, change)?;
+
+        // Now, if our change was a deletion or replacement, we have to
+        // blow away any changes thus far on paths that are (or, were)
+        // children of this path.
+        if is_deletion {
+            let prefix = if path == "/" {
+                path.clone()
+            } else {
+                format!("{path}/")
+            };
+            folded.retain(|p: &String, _| p == &path || !p.starts_with(&prefix));
+        }
+    }
+
+    Ok(folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(path: &str, change_kind: PathChangeKind) -> PathChange {
+        PathChange {
+            path: path.to_string(),
+            node_rev_id: Some("0.0.r0/2".parse().unwrap()),
+            change_kind,
+            node_kind: NodeKind::Directory,
+            text_mod: false,
+            prop_mod: change_kind == PathChangeKind::Modify,
+            mergeinfo_mod: Tristate::Unknown,
+            copyfrom: None,
+        }
+    }
+
+    #[test]
+    fn test_process_changes() {
+        let changes = process_changes(vec![
+            change("/a", PathChangeKind::Add),
+            change("/a/b", PathChangeKind::Add),
+            change("/a", PathChangeKind::Delete),
+            change("/c", PathChangeKind::Modify),
+            change("/c", PathChangeKind::Delete),
+            change("/c", PathChangeKind::Add),
+            change("/d", PathChangeKind::Modify),
+            change("/d", PathChangeKind::Modify),
+        ])
+        .unwrap();
+
+        let kinds: Vec<_> = changes
+            .values()
+            .map(|c| (c.path.as_str(), c.change_kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("/c", PathChangeKind::Replace),
+                ("/d", PathChangeKind::Modify)
+            ]
+        );
+        assert!(!changes["/c"].prop_mod);
+
+        assert!(
+            process_changes(vec![
+                change("/a", PathChangeKind::Modify),
+                change("/a", PathChangeKind::Add),
+            ])
+            .is_err()
+        );
+    }
+}
@@ -1,9 +1,16 @@
 //! port `Filesystem configuration options`
 
+use std::collections::HashMap;
+use std::path::Path;
+
 use uuid::Uuid;
 
 use crate::Error;
-use crate::SvnFs;
+use crate::backend::fsfs::SVN_FS_CONFIG_FSFS_CACHE_DELTAS;
+use crate::backend::fsfs::SVN_FS_CONFIG_FSFS_CACHE_FULLTEXTS;
+use crate::backend::fsfs::SVN_FS_CONFIG_FSFS_CACHE_NS;
+use crate::backend::fsfs::SVN_FS_CONFIG_FSFS_LOG_ADDRESSING;
+use crate::backend::fsfs::SVN_FS_CONFIG_FSFS_SHARD_SIZE;
 
 /// `svn_fs_type`
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -19,6 +26,9 @@ pub enum FsType {
     /// It is not ready for general production use.  Please consult the
     /// respective release notes on suggested usage scenarios.
     Fsx,
+    /// Keeps everything in process memory, for throwaway filesystems in
+    /// tests.  Nothing is ever written to disk.
+    Memory,
 }
 
 impl ToString for FsType {
@@ -28,6 +38,7 @@ impl ToString for FsType {
             FsType::Bdb => "bdb".to_string(),
             FsType::Fsfs => "fsfs".to_string(),
             FsType::Fsx => "fsx".to_string(),
+            FsType::Memory => "memory".to_string(),
         }
     }
 }
@@ -35,6 +46,13 @@ impl ToString for FsType {
 #[derive(Debug, Clone)]
 pub enum FsConfig {
     Fsfs(FsFsConfig),
+    /// An FSX filesystem, see [`FsType::Fsx`].  It takes the FSFS options,
+    /// but is always sharded and always uses logical addressing.
+    #[cfg(feature = "fsx")]
+    Fsx(FsFsConfig),
+    /// An in-memory filesystem, see [`FsType::Memory`].  It has no
+    /// options.
+    Memory,
 }
 
 impl Default for FsConfig {
@@ -47,12 +65,73 @@ impl FsConfig {
     pub fn fs_type(&self) -> FsType {
         match self {
             FsConfig::Fsfs(_) => FsType::Fsfs,
+            #[cfg(feature = "fsx")]
+            FsConfig::Fsx(_) => FsType::Fsx,
+            FsConfig::Memory => FsType::Memory,
+        }
+    }
+
+    /// Flatten the options into the `fs_config` hash the back-ends read
+    /// their settings from.
+    pub(crate) fn to_hash(&self) -> HashMap<String, String> {
+        let mut hash = HashMap::new();
+        hash.insert(
+            SVN_FS_CONFIG_FS_TYPE.to_string(),
+            self.fs_type().to_string(),
+        );
+
+        let fsfs = match self {
+            FsConfig::Fsfs(c) => Some(c),
+            #[cfg(feature = "fsx")]
+            FsConfig::Fsx(c) => Some(c),
+            FsConfig::Memory => None,
+        };
+        if let Some(c) = fsfs {
+            let mut set = |key: &str, value: String| {
+                hash.insert(key.to_string(), value);
+            };
+            set(SVN_FS_CONFIG_FSFS_CACHE_DELTAS, c.cache_deltas.to_string());
+            set(
+                SVN_FS_CONFIG_FSFS_CACHE_FULLTEXTS,
+                c.cache_fulltexts.to_string(),
+            );
+            set(
+                SVN_FS_CONFIG_FSFS_CACHE_REVPROPS,
+                c.cache_revprops.to_string(),
+            );
+            if !c.cache_namespace.is_nil() {
+                set(SVN_FS_CONFIG_FSFS_CACHE_NS, c.cache_namespace.to_string());
+            }
+            set(SVN_FS_CONFIG_FSFS_BLOCK_READ, c.block_read.to_string());
+            set(SVN_FS_CONFIG_FSFS_SHARD_SIZE, c.shard_size.clone());
+            set(
+                SVN_FS_CONFIG_FSFS_LOG_ADDRESSING,
+                c.log_addressing.to_string(),
+            );
+            set(
+                SVN_FS_CONFIG_NO_FLUSH_TO_DISK,
+                c.no_flush_to_disk.to_string(),
+            );
         }
+
+        hash
     }
 }
 
+/// The filesystem type, see [`FsType`].
+pub(crate) const SVN_FS_CONFIG_FS_TYPE: &str = "fs-type";
+
+/// Enable / disable revprop caching for a FSFS repository.
+pub(crate) const SVN_FS_CONFIG_FSFS_CACHE_REVPROPS: &str = "fsfs-cache-revprops";
+
+/// Enable / disable the FSFS format 7 "block read" feature.
+pub(crate) const SVN_FS_CONFIG_FSFS_BLOCK_READ: &str = "fsfs-block-read";
+
+/// Don't force the data to disk on every commit.
+pub(crate) const SVN_FS_CONFIG_NO_FLUSH_TO_DISK: &str = "no-flush-to-disk";
+
 /// Filesystem configuration options for a `FSFS` repository.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct FsFsConfig {
     /// Enable / disable text delta caching for a FSFS repository.
     pub cache_deltas: bool,
@@ -101,17 +180,34 @@ pub struct FsFsConfig {
     pub no_flush_to_disk: bool,
 }
 
+impl Default for FsFsConfig {
+    /// The defaults of `svn_fs_create2` with an empty `fs_config`: a
+    /// sharded, logically addressed repository with all caches enabled.
+    fn default() -> Self {
+        Self {
+            cache_deltas: true,
+            cache_fulltexts: true,
+            cache_revprops: false,
+            cache_namespace: Uuid::nil(),
+            block_read: false,
+            shard_size: "1000".to_string(),
+            log_addressing: true,
+            no_flush_to_disk: false,
+        }
+    }
+}
+
 impl FsFsConfig {
     pub const PATH_CONFIG: &'static str = "fsfs.conf";
 
-    /// Write FS' initial configuration file.
+    /// Write the initial configuration file of the filesystem at @a path.
     ///
     /// `write_config`
-    pub fn write_config(fs: &SvnFs) -> Result<(), Error> {
+    pub fn write_config(path: &Path) -> Result<(), Error> {
         let content = include_str!("../config/fsfs.conf");
-        let path = fs.path().join(Self::PATH_CONFIG);
+        let path = path.join(Self::PATH_CONFIG);
 
-        std::fs::write(&path, content)?;
+        fs_err::write(&path, content)?;
 
         Ok(())
     }
@@ -7,9 +7,31 @@ pub enum Error {
     #[error("UnsupportedFsType: {0:?}")]
     UnsupportedFsType(crate::FsType),
 
+    #[error("Unknown FS type '{0}'")]
+    UnknownFsType(String),
+
+    #[error(
+        "The filesystem type of the hotcopy source ('{}') does not match the filesystem type of the hotcopy destination ('{}')",
+        .src.to_string(),
+        .dst.to_string()
+    )]
+    FsTypeMismatch {
+        src: crate::FsType,
+        dst: crate::FsType,
+    },
+
     #[error(transparent)]
     Backend(#[from] crate::backend::BackendError),
 
+    #[error("Cannot copy between two different filesystems")]
+    CrossFsCopy,
+
+    #[error("Copy from mutable tree not currently supported")]
+    NotRevisionRoot,
+
+    #[error("Cannot compare {0} between two different filesystems")]
+    CrossFsCompare(&'static str),
+
     #[error(transparent)]
     Config(#[from] svn_subr::config::ConfigError),
 }
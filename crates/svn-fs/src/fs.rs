@@ -1,13 +1,30 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
 
+use svn_types::Depth;
+use svn_types::RevisionNumber;
+use svn_types::SvnLock;
 use uuid::Uuid;
 
 use crate::Error;
+use crate::FsRoot;
+use crate::FsStats;
 use crate::FsType;
+use crate::LockTarget;
+use crate::PackNotifyAction;
+use crate::RecoverReport;
+use crate::UpgradeNotifyAction;
+use crate::VerifyError;
+use crate::backend::BackendError;
 use crate::backend::fsfs::FsFsBackend;
+#[cfg(feature = "fsx")]
+use crate::backend::fsx::FsxBackend;
+use crate::backend::memory::MemoryBackend;
 use crate::backend::{FsInstance, FsLibrary};
+use crate::lock::lock_token_uuid;
+use crate::lock::time_now;
+use crate::txn::FsTxn;
+use crate::util::canonicalize_abspath;
 use crate::{FsAccess, FsConfig};
 
 const FS_TYPE_FILENAME: &str = "fs-type";
@@ -22,7 +39,7 @@ pub struct SvnFsCommon {
     pub config: HashMap<String, String>,
 
     /// An access context indicating who's using the fs
-    pub access_ctx: FsAccess,
+    pub access_ctx: Option<FsAccess>,
 }
 
 /// An object representing a Subversion filesystem.
@@ -58,6 +75,265 @@ impl SvnFs {
     pub fn config(&self) -> &HashMap<String, String> {
         &self.common.config
     }
+
+    /// Link the back-end instance that implements this filesystem.
+    pub(crate) fn set_inner(&mut self, inner: Box<dyn FsInstance>) {
+        self.inner = Some(inner);
+    }
+
+    /// Return the youngest revision in the filesystem.
+    ///
+    /// `svn_fs_youngest_rev`
+    pub fn youngest_rev(&self) -> Result<RevisionNumber, Error> {
+        Ok(self.inner().youngest_rev()?)
+    }
+
+    /// Return the root of revision @a rev.
+    ///
+    /// `svn_fs_revision_root`
+    pub fn revision_root(&self, rev: RevisionNumber) -> Result<FsRoot<'_>, Error> {
+        let vtable = self.inner().revision_root(rev)?;
+        Ok(FsRoot::new_revision_root(self, rev, vtable))
+    }
+
+    /// Begin a new transaction based on revision @a rev.
+    ///
+    /// `svn_fs_begin_txn2`
+    pub fn begin_txn(&self, rev: RevisionNumber) -> Result<FsTxn<'_>, Error> {
+        let vtable = self.inner().begin_txn(rev)?;
+        Ok(FsTxn::new(self, vtable))
+    }
+
+    /// Open the uncommitted transaction called @a name.
+    ///
+    /// `svn_fs_open_txn`
+    pub fn open_txn(&self, name: &str) -> Result<FsTxn<'_>, Error> {
+        let vtable = self.inner().open_txn(name)?;
+        Ok(FsTxn::new(self, vtable))
+    }
+
+    /// Return the names of all uncommitted transactions.
+    ///
+    /// `svn_fs_list_transactions`
+    pub fn list_transactions(&self) -> Result<Vec<String>, Error> {
+        Ok(self.inner().list_transactions()?)
+    }
+
+    /// Remove the transaction called @a name and all of its data.
+    ///
+    /// `svn_fs_purge_txn`
+    pub fn purge_txn(&self, name: &str) -> Result<(), Error> {
+        Ok(self.inner().purge_txn(name)?)
+    }
+
+    /// Associate the access context @a access with this filesystem, or
+    /// remove the current one.  It identifies the user for locking and
+    /// supplies the lock tokens for commits.
+    ///
+    /// `svn_fs_set_access`
+    pub fn set_access(&mut self, access: Option<FsAccess>) {
+        self.common.access_ctx = access;
+    }
+
+    /// Return the access context associated with this filesystem, if any.
+    ///
+    /// `svn_fs_get_access`
+    pub fn access(&self) -> Option<&FsAccess> {
+        self.common.access_ctx.as_ref()
+    }
+
+    /// Lock the file @a path for the user of the access context and
+    /// return the new lock.  @a target supplies the lock token (a new
+    /// one is generated if it has none) and optionally the revision the
+    /// caller's copy of the file is based on; locking fails if the file
+    /// has been changed since.  @a expiration_date is in microseconds
+    /// since the epoch, 0 meaning never.
+    ///
+    /// If the path is locked already, fail unless @a steal_lock is set.
+    ///
+    /// `svn_fs_lock_many` / `svn_fs_lock`
+    pub fn lock(
+        &self,
+        path: &str,
+        target: &LockTarget,
+        comment: Option<&str>,
+        is_dav_comment: bool,
+        expiration_date: i64,
+        steal_lock: bool,
+    ) -> Result<SvnLock, Error> {
+        let path = canonicalize_abspath(path);
+
+        // We need to have a username attached to the fs.
+        let Some(access) = self.access().filter(|access| !access.username.is_empty()) else {
+            return Err(BackendError::NoUser {
+                action: "lock",
+                path,
+            }
+            .into());
+        };
+
+        let token = match &target.token {
+            Some(token) => token.clone(),
+            None => self.generate_lock_token(),
+        };
+        if lock_token_uuid(&token).is_none() {
+            return Err(BackendError::MalformedLockToken(token).into());
+        }
+
+        let lock = SvnLock {
+            path,
+            token,
+            owner: access.username.clone(),
+            comment: comment.map(str::to_string),
+            is_dav_comment,
+            creation_date: time_now(),
+            expiration_date,
+        };
+        self.inner().lock(&lock, target.current_rev, steal_lock)?;
+
+        Ok(lock)
+    }
+
+    /// Remove the lock on @a path.  Unless @a break_lock is set, the
+    /// lock must have the token @a token and be owned by the user of the
+    /// access context.
+    ///
+    /// `svn_fs_unlock_many` / `svn_fs_unlock`
+    pub fn unlock(&self, path: &str, token: Option<&str>, break_lock: bool) -> Result<(), Error> {
+        let username = self
+            .access()
+            .map(|access| access.username.as_str())
+            .filter(|username| !username.is_empty());
+        if !break_lock && username.is_none() {
+            return Err(BackendError::NoUser {
+                action: "unlock",
+                path: canonicalize_abspath(path),
+            }
+            .into());
+        }
+
+        Ok(self.inner().unlock(path, token, username, break_lock)?)
+    }
+
+    /// Return the lock on @a path, or `None` if it is not locked.  Expired
+    /// locks are not returned.
+    ///
+    /// `svn_fs_get_lock`
+    pub fn get_lock(&self, path: &str) -> Result<Option<SvnLock>, Error> {
+        Ok(self.inner().get_lock(path)?)
+    }
+
+    /// Return the locks on @a path and below, ordered by path.  With
+    /// @a depth [`Depth::Empty`], only the lock on @a path itself is
+    /// returned; with [`Depth::Files`] and [`Depth::Immediates`] those
+    /// on its direct children as well.
+    ///
+    /// `svn_fs_get_locks2`
+    pub fn get_locks(&self, path: &str, depth: Depth) -> Result<Vec<SvnLock>, Error> {
+        Ok(self.inner().get_locks(path, depth)?)
+    }
+
+    /// Return a new, unique lock token for use with [`LockTarget`].
+    ///
+    /// `svn_fs_generate_lock_token`
+    pub fn generate_lock_token(&self) -> String {
+        self.inner().generate_lock_token()
+    }
+
+    /// Add the representations committed in revisions @a start to @a end
+    /// to the rep-sharing cache, e.g. after it got lost or rep-sharing
+    /// got enabled.  @a start and @a end may be `SVN_INVALID_REVNUM` for
+    /// r0 and the youngest revision, respectively.
+    ///
+    /// `svn_fs_build_rep_cache`
+    pub fn build_rep_cache(&self, start: RevisionNumber, end: RevisionNumber) -> Result<(), Error> {
+        Ok(self.inner().build_rep_cache(start, end)?)
+    }
+
+    /// Check that the rep-sharing cache entries of revisions @a start to
+    /// @a end refer to existing representations.
+    ///
+    /// `verify_rep_cache`
+    pub fn verify_rep_cache(
+        &self,
+        start: RevisionNumber,
+        end: RevisionNumber,
+    ) -> Result<(), Error> {
+        Ok(self.inner().verify_rep_cache(start, end)?)
+    }
+
+    /// Return the properties of revision @a rev.
+    ///
+    /// `svn_fs_revision_proplist2`
+    pub fn revision_proplist(&self, rev: RevisionNumber) -> Result<HashMap<String, String>, Error> {
+        Ok(self.inner().revision_proplist(rev)?)
+    }
+
+    /// Return the value of the property @a propname of revision @a rev,
+    /// or `None` if it is not set.
+    ///
+    /// `svn_fs_revision_prop2`
+    pub fn revision_prop(
+        &self,
+        rev: RevisionNumber,
+        propname: &str,
+    ) -> Result<Option<String>, Error> {
+        Ok(self.revision_proplist(rev)?.remove(propname))
+    }
+
+    /// Change, add, or delete the property @a name of revision @a rev to
+    /// @a value, which deletes it if `None`.
+    ///
+    /// If @a old_value is given, it is the value the property must have
+    /// for the change to happen, `None` meaning that it must not be set.
+    /// Otherwise, fail with [`BackendError::PropBaseValueMismatch`] and
+    /// leave the property alone.  Clients use this for atomic revprop
+    /// changes, see `SVN_RA_CAPABILITY_ATOMIC_REVPROPS`.
+    ///
+    /// `svn_fs_change_rev_prop2`
+    pub fn change_rev_prop(
+        &self,
+        rev: RevisionNumber,
+        name: &str,
+        old_value: Option<Option<&str>>,
+        value: Option<&str>,
+    ) -> Result<(), Error> {
+        Ok(self.inner().change_rev_prop(rev, name, old_value, value)?)
+    }
+
+    /// Take an exclusive lock on the filesystem to prevent commits,
+    /// revprop changes and packing, and call @a freeze_func while holding
+    /// it.  Readers are not blocked.  This allows taking a consistent
+    /// copy, e.g. a volume snapshot, of a live filesystem.
+    ///
+    /// `svn_fs_freeze`
+    pub fn freeze<T, E>(&self, freeze_func: impl FnOnce() -> Result<T, E>) -> Result<T, E>
+    where
+        E: From<Error>,
+    {
+        let mut freeze_func = Some(freeze_func);
+        let mut result = None;
+        self.inner()
+            .freeze(&mut || result = freeze_func.take().map(|f| f()))
+            .map_err(Error::from)?;
+
+        result.expect("the freeze function has been called")
+    }
+
+    /// The fs object with no back-end linked in yet.
+    ///
+    /// `fs_new`
+    fn fs_new(path: &Path, fs_config: HashMap<String, String>) -> Self {
+        Self {
+            common: SvnFsCommon {
+                path: path.to_path_buf(),
+                config: fs_config,
+                access_ctx: None,
+            },
+            inner: None,
+            uuid: Uuid::nil(),
+        }
+    }
 }
 
 impl SvnFs {
@@ -90,50 +366,42 @@ impl SvnFs {
     /// wish to ensure that a filesystem of a specific type is created.
     ///
     /// `svn_fs`/`svn_fs_create2`
-    pub fn create(
-        fs: &mut Option<Rc<dyn FsInstance>>,
-        db_path: &Path,
-        config: &FsConfig,
-    ) -> Result<Self, Error> {
+    pub fn create(db_path: &Path, config: &FsConfig) -> Result<Self, Error> {
         let fs_type = config.fs_type();
 
-        let new_fs: Box<dyn FsInstance> = match fs_type {
+        match fs_type {
             #[allow(deprecated)]
             FsType::Bdb => {
                 return Err(Error::UnsupportedFsType(FsType::Bdb));
             }
-            FsType::Fsfs => {
-                // Create a new FSFS filesystem
-                Box::new(FsFsBackend::new(db_path.to_path_buf()))
-            }
+            FsType::Fsfs => {}
+            #[cfg(feature = "fsx")]
+            FsType::Fsx => {}
+            #[cfg(not(feature = "fsx"))]
             FsType::Fsx => {
                 return Err(Error::UnsupportedFsType(FsType::Fsx));
             }
-        };
+            FsType::Memory => {
+                // Nothing goes to disk, not even the fs-type file.
+                let mut fs = Self::fs_new(db_path, config.to_hash());
+                MemoryBackend::create(&mut fs, db_path)?;
+                return Ok(fs);
+            }
+        }
 
         // Create the FS directory and write out the fsap-name file.
-        if !db_path.exists() {
-            fs_err::create_dir_all(db_path)?;
+        fs_err::create_dir(db_path)?;
+        write_fs_type(db_path, fs_type)?;
+
+        // Perform the actual creation.
+        let mut fs = Self::fs_new(db_path, config.to_hash());
+        match fs_type {
+            #[cfg(feature = "fsx")]
+            FsType::Fsx => FsxBackend::create(&mut fs, db_path)?,
+            _ => FsFsBackend::create(&mut fs, db_path)?,
         }
-        // write fs-type file
-        let fs_type_file = db_path.join(FS_TYPE_FILENAME);
-        fs_err::write(fs_type_file, format!("{}\n", fs_type.to_string()))?;
-
-        // let ret = Self {
-        //     inner: Some(new_fs),
-        //     common: SvnFsCommon {
-        //         path: db_path.to_path_buf(),
-        //         config: config.clone(),
-        //         access_ctx: FsAccess::default(),
-        //     },
-        //     uuid: Uuid::new_v4(), // Generate a new UUID for the filesystem
-        // };
-
-        // Perform the actual creation
-        // let mut ret = new_fs.create(db_path)?;
-        // new_fs.open(db_path, config, &mut ret)?;
-        // Ok(ret)
-        todo!()
+
+        Ok(fs)
     }
 
     /// Open a Subversion filesystem located in the directory @a path, and
@@ -156,18 +424,320 @@ impl SvnFs {
     /// Take a look at svn_repos_open3() `Repos::open` instead.
     ///
     /// `svn_fs_open2`
-    fn fs_open(&mut self, path: &Path, config: &FsConfig) -> Result<(), Error> {
-        let fs_lib = self.fs_library();
+    pub fn open(path: &Path, config: &FsConfig) -> Result<Self, Error> {
+        let fs_type = get_fs_type(path)?;
 
-        fs_lib.open_fs(path)?;
+        let mut fs = Self::fs_new(path, config.to_hash());
+        match fs_type {
+            FsType::Fsfs => FsFsBackend::open_fs(&mut fs, path)?,
+            #[cfg(feature = "fsx")]
+            FsType::Fsx => FsxBackend::open_fs(&mut fs, path)?,
+            FsType::Memory => MemoryBackend::open_fs(&mut fs, path)?,
+            other => return Err(Error::UnsupportedFsType(other)),
+        }
+
+        Ok(fs)
+    }
+
+    /// Possibly update the filesystem located in the directory @a path
+    /// to use disk space more efficiently, by packing the revisions of
+    /// completed shards.
+    ///
+    /// If @a notify is given, call it before and after packing each shard
+    /// (and its revprops).  If @a cancel returns `true`, stop as soon as
+    /// possible; shards packed so far stay packed.
+    ///
+    /// `svn_fs_pack`
+    pub fn pack(
+        path: &Path,
+        notify: Option<&mut dyn FnMut(i64, PackNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), Error> {
+        let fs_type = get_fs_type(path)?;
+
+        let mut fs = Self::fs_new(path, HashMap::new());
+        match fs_type {
+            FsType::Fsfs => FsFsBackend::pack_fs(&mut fs, path, notify, cancel)?,
+            #[cfg(feature = "fsx")]
+            FsType::Fsx => FsxBackend::pack_fs(&mut fs, path, notify, cancel)?,
+            FsType::Memory => MemoryBackend::pack_fs(&mut fs, path, notify, cancel)?,
+            other => return Err(Error::UnsupportedFsType(other)),
+        }
 
         Ok(())
     }
 
-    /// `fs_library_vtable`
-    fn fs_library(&self) -> &dyn FsLibrary {
-        let ins = self.inner.as_ref().unwrap();
-        let fs_lib: &dyn FsLibrary = ins.as_ref();
-        fs_lib
+    /// Verify the consistency of revisions @a start to @a end of the
+    /// filesystem located in the directory @a path.  Either revision may
+    /// be `SVN_INVALID_REVNUM` for "from r0" and "up to the youngest
+    /// revision", respectively.
+    ///
+    /// Every problem found is returned as a [`VerifyError`]; an empty list
+    /// means that the revisions are fine.  Unless @a keep_going is set,
+    /// verification stops after the first revision with problems.
+    ///
+    /// If @a notify is given, call it before verifying each revision.  If
+    /// @a cancel returns `true`, stop as soon as possible.
+    ///
+    /// `svn_fs_verify`
+    pub fn verify(
+        path: &Path,
+        start: RevisionNumber,
+        end: RevisionNumber,
+        keep_going: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<Vec<VerifyError>, Error> {
+        let fs_type = get_fs_type(path)?;
+
+        let mut fs = Self::fs_new(path, HashMap::new());
+        let errors = match fs_type {
+            FsType::Fsfs => {
+                FsFsBackend::verify_fs(&mut fs, path, start, end, keep_going, notify, cancel)?
+            }
+            #[cfg(feature = "fsx")]
+            FsType::Fsx => {
+                FsxBackend::verify_fs(&mut fs, path, start, end, keep_going, notify, cancel)?
+            }
+            FsType::Memory => {
+                MemoryBackend::verify_fs(&mut fs, path, start, end, keep_going, notify, cancel)?
+            }
+            other => return Err(Error::UnsupportedFsType(other)),
+        };
+
+        Ok(errors)
+    }
+
+    /// Scan all revisions of the filesystem located in the directory
+    /// @a path and return statistics about how their data is stored:
+    /// representation sizes and delta chains, rep-sharing, the largest
+    /// files and the efficiency of each shard.
+    ///
+    /// Only FSFS filesystems are supported.  If @a progress is given, call
+    /// it before reading each revision.  If @a cancel returns `true`, stop
+    /// as soon as possible.
+    ///
+    /// `svn_fs_fs__get_stats`
+    pub fn stats(
+        path: &Path,
+        progress: Option<&mut dyn FnMut(RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<FsStats, Error> {
+        let fs_type = get_fs_type(path)?;
+
+        let mut fs = Self::fs_new(path, HashMap::new());
+        let stats = match fs_type {
+            FsType::Fsfs => FsFsBackend::stats_fs(&mut fs, path, progress, cancel)?,
+            other => return Err(Error::UnsupportedFsType(other)),
+        };
+
+        Ok(stats)
+    }
+
+    /// Copy a possibly live Subversion filesystem from @a src_path to
+    /// @a dst_path.  Commits to the source may continue meanwhile; the
+    /// copy contains the revisions that were complete when it started.
+    ///
+    /// If @a incremental is set, make an effort to avoid re-copying
+    /// information already present in the destination where possible.  If
+    /// incremental hotcopy is not implemented, raise an error.  Otherwise
+    /// @a dst_path must not contain a filesystem yet.
+    ///
+    /// An in-memory copy only lives as long as it is open, so it is gone
+    /// again once this returns, unless @a incremental updated a
+    /// destination that is open elsewhere.
+    ///
+    /// If @a notify is given, call it with the range of revisions copied
+    /// at every step.  If @a cancel returns `true`, stop as soon as
+    /// possible.
+    ///
+    /// `svn_fs_hotcopy3`
+    pub fn hotcopy(
+        src_path: &Path,
+        dst_path: &Path,
+        incremental: bool,
+        notify: Option<&mut dyn FnMut(RevisionNumber, RevisionNumber)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), Error> {
+        let src_fs_type = get_fs_type(src_path)?;
+
+        if dst_path.is_file() {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("'{}' already exists and is a file", dst_path.display()),
+            )));
+        }
+        if dst_path.join(FS_TYPE_FILENAME).exists() || MemoryBackend::exists(dst_path) {
+            let dst_fs_type = get_fs_type(dst_path)?;
+            if src_fs_type != dst_fs_type {
+                return Err(Error::FsTypeMismatch {
+                    src: src_fs_type,
+                    dst: dst_fs_type,
+                });
+            }
+        }
+        // A full hotcopy must not overwrite an existing filesystem, or
+        // anything else.
+        if !incremental && dst_path.is_dir() && fs_err::read_dir(dst_path)?.next().is_some() {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::DirectoryNotEmpty,
+                format!(
+                    "Destination '{}' is not an empty directory",
+                    dst_path.display()
+                ),
+            )));
+        }
+
+        let mut src_fs = Self::fs_new(src_path, HashMap::new());
+        let mut dst_fs = Self::fs_new(dst_path, HashMap::new());
+        match src_fs_type {
+            FsType::Fsfs => <FsFsBackend as FsLibrary>::hotcopy(
+                &mut src_fs,
+                &mut dst_fs,
+                src_path,
+                dst_path,
+                incremental,
+                notify,
+                cancel,
+            )?,
+            #[cfg(feature = "fsx")]
+            FsType::Fsx => <FsxBackend as FsLibrary>::hotcopy(
+                &mut src_fs,
+                &mut dst_fs,
+                src_path,
+                dst_path,
+                incremental,
+                notify,
+                cancel,
+            )?,
+            FsType::Memory => <MemoryBackend as FsLibrary>::hotcopy(
+                &mut src_fs,
+                &mut dst_fs,
+                src_path,
+                dst_path,
+                incremental,
+                notify,
+                cancel,
+            )?,
+            other => return Err(Error::UnsupportedFsType(other)),
+        }
+
+        if src_fs_type != FsType::Memory {
+            write_fs_type(dst_path, src_fs_type)?;
+        }
+
+        Ok(())
+    }
+
+    /// Upgrade the filesystem located in the directory @a path to the
+    /// latest version supported by this library.  Return an error if the
+    /// filesystem's format cannot be upgraded in place.  Upgrading an
+    /// up-to-date filesystem is a no-op.
+    ///
+    /// If @a notify is given, call it for each step taken, with the shard
+    /// or the new format number.  If @a cancel returns `true`, stop as
+    /// soon as possible; the filesystem keeps its old format then and the
+    /// upgrade may be re-run.
+    ///
+    /// `svn_fs_upgrade2`
+    pub fn upgrade(
+        path: &Path,
+        notify: Option<&mut dyn FnMut(i64, UpgradeNotifyAction)>,
+        cancel: Option<&dyn Fn() -> bool>,
+    ) -> Result<(), Error> {
+        let fs_type = get_fs_type(path)?;
+
+        let mut fs = Self::fs_new(path, HashMap::new());
+        match fs_type {
+            FsType::Fsfs => FsFsBackend::upgrade_fs(&mut fs, path, notify, cancel)?,
+            #[cfg(feature = "fsx")]
+            FsType::Fsx => FsxBackend::upgrade_fs(&mut fs, path, notify, cancel)?,
+            FsType::Memory => MemoryBackend::upgrade_fs(&mut fs, path, notify, cancel)?,
+            other => return Err(Error::UnsupportedFsType(other)),
+        }
+
+        Ok(())
+    }
+
+    /// Recover the filesystem located in the directory @a path after a
+    /// crash or power loss: determine the youngest revision from the rev
+    /// files and rewrite `current` and `txn-current` accordingly.
+    /// Transactions that can no longer be committed and proto-revs
+    /// without a transaction are reported in the returned
+    /// [`RecoverReport`]; the latter are removed.
+    ///
+    /// Fails if some other process has the filesystem open for writing.
+    /// If @a cancel returns `true`, stop as soon as possible.
+    ///
+    /// `svn_fs_recover`
+    pub fn recover(path: &Path, cancel: Option<&dyn Fn() -> bool>) -> Result<RecoverReport, Error> {
+        let fs_type = get_fs_type(path)?;
+
+        let mut fs = Self::fs_new(path, HashMap::new());
+        let report = match fs_type {
+            FsType::Fsfs => FsFsBackend::recover_fs(&mut fs, path, cancel)?,
+            #[cfg(feature = "fsx")]
+            FsType::Fsx => FsxBackend::recover_fs(&mut fs, path, cancel)?,
+            FsType::Memory => MemoryBackend::recover_fs(&mut fs, path, cancel)?,
+            other => return Err(Error::UnsupportedFsType(other)),
+        };
+
+        Ok(report)
+    }
+
+    /// Delete the filesystem at @a path, with everything in it.
+    ///
+    /// `svn_fs_delete_fs`
+    pub fn delete_fs(path: &Path) -> Result<(), Error> {
+        let fs_type = get_fs_type(path)?;
+
+        let path_str = path.to_string_lossy();
+        match fs_type {
+            FsType::Fsfs => FsFsBackend::new(path.to_path_buf()).delete_fs(&path_str)?,
+            #[cfg(feature = "fsx")]
+            FsType::Fsx => FsxBackend.delete_fs(&path_str)?,
+            FsType::Memory => MemoryBackend::delete(path)?,
+            other => return Err(Error::UnsupportedFsType(other)),
+        }
+
+        Ok(())
+    }
+}
+
+/// Write the name of the back-end into the `fs-type` file under @a path.
+///
+/// `write_fs_type`
+fn write_fs_type(path: &Path, fs_type: FsType) -> Result<(), Error> {
+    let fs_type_file = path.join(FS_TYPE_FILENAME);
+    fs_err::write(fs_type_file, format!("{}\n", fs_type.to_string()))?;
+
+    Ok(())
+}
+
+/// Read the back-end name from the `fs-type` file under @a path.
+///
+/// Filesystems created before the file existed are BDB ones.  In-memory
+/// filesystems have no such file; they are found by @a path instead.
+///
+/// `svn_fs_type`
+fn get_fs_type(path: &Path) -> Result<FsType, Error> {
+    if MemoryBackend::exists(path) {
+        return Ok(FsType::Memory);
+    }
+
+    let fs_type_file = path.join(FS_TYPE_FILENAME);
+    let content = match fs_err::read_to_string(&fs_type_file) {
+        Ok(content) => content,
+        #[allow(deprecated)]
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FsType::Bdb),
+        Err(e) => return Err(e.into()),
+    };
+
+    match content.trim() {
+        "fsfs" => Ok(FsType::Fsfs),
+        "fsx" => Ok(FsType::Fsx),
+        #[allow(deprecated)]
+        "bdb" => Ok(FsType::Bdb),
+        other => Err(Error::UnknownFsType(other.to_string())),
     }
 }
@@ -0,0 +1,58 @@
+//! Node histories.
+//  *
+//  * An #svn_fs_history_t object represents the history of a node: the
+//  * path and revision of each interesting change made to it, youngest
+//  * first.
+
+use svn_types::RevisionNumber;
+
+use crate::Error;
+use crate::backend::BackendError;
+
+/// `history_vtable_t`
+pub trait HistoryTrait<'a> {
+    /// `svn_fs_history_prev2`
+    fn prev(
+        &self,
+        cross_copies: bool,
+    ) -> Result<Option<Box<dyn HistoryTrait<'a> + 'a>>, BackendError>;
+
+    /// `svn_fs_history_location`
+    fn location(&self) -> (String, RevisionNumber);
+}
+
+/// The history of a node, positioned at one of its locations.
+///
+/// `svn_fs_history_t`
+pub struct FsHistory<'a> {
+    /// The back-end specific implementation of this history
+    vtable: Box<dyn HistoryTrait<'a> + 'a>,
+}
+
+impl<'a> FsHistory<'a> {
+    pub(crate) fn new(vtable: Box<dyn HistoryTrait<'a> + 'a>) -> Self {
+        Self { vtable }
+    }
+
+    /// Return the history location before this one, or `None` if there
+    /// are no more interesting locations.  The first call on a history
+    /// just created by [`crate::FsRoot::node_history`] returns the
+    /// youngest location, the revision of the root being the upper
+    /// bound.
+    ///
+    /// If @a cross_copies is `false`, stop at the copy the node came
+    /// from; otherwise continue with the copy source, reporting the
+    /// copy itself as a location of its own.
+    ///
+    /// `svn_fs_history_prev2`
+    pub fn prev(&self, cross_copies: bool) -> Result<Option<FsHistory<'a>>, Error> {
+        Ok(self.vtable.prev(cross_copies)?.map(FsHistory::new))
+    }
+
+    /// Return the path and revision of this location.
+    ///
+    /// `svn_fs_history_location`
+    pub fn location(&self) -> (String, RevisionNumber) {
+        self.vtable.location()
+    }
+}
@@ -0,0 +1,317 @@
+//! `subversion/libsvn_fs_fs/id.c`
+//!
+//! Node-revision IDs have the form `<node-id>.<copy-id>.r<rev>/<item>` for
+//! committed nodes and `<node-id>.<copy-id>.t<txn-id>` for nodes in a
+//! transaction.
+
+use std::fmt;
+use std::str::FromStr;
+
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+pub enum IdError {
+    #[error("Malformed node revision ID string '{0}'")]
+    MalformedNodeRevId(String),
+
+    #[error("malformed txn id '{0}'")]
+    MalformedTxnId(String),
+}
+
+/// A rev / number pair identifying an item: a node, copy or transaction
+/// counter relative to its revision, or an item within a rev file.
+///
+/// `svn_fs_fs__id_part_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IdPart {
+    /// SVN_INVALID_REVNUM for txns -> not a txn, COUNTER must be 0.
+    /// SVN_INVALID_REVNUM for others -> not assigned to a revision, yet.
+    /// 0 for others -> old-style ID or the root in rev 0.
+    pub revision: RevisionNumber,
+
+    /// sub-id value relative to REVISION.  Its interpretation depends on
+    /// the part itself.  In rev_item, it is the index_index value, in others
+    /// it represents a unique counter value.
+    pub number: u64,
+}
+
+impl IdPart {
+    /// The "not set" value of txn IDs.
+    ///
+    /// `svn_fs_fs__id_txn_reset`
+    pub const UNUSED_TXN: IdPart = IdPart {
+        revision: SVN_INVALID_REVNUM,
+        number: 0,
+    };
+
+    /// Return TRUE if this is a txn ID that is in use.
+    ///
+    /// `svn_fs_fs__id_txn_used`
+    pub fn is_used_txn(&self) -> bool {
+        *self != Self::UNUSED_TXN
+    }
+
+    /// Parse a node or copy ID part: `0`, `<base36>-<rev>` or, within a
+    /// transaction, `_<base36>`.
+    ///
+    /// `part_parse`
+    fn parse(data: &str) -> Option<Self> {
+        // special case: ID inside some transaction
+        if let Some(number) = data.strip_prefix('_') {
+            return Some(Self {
+                revision: SVN_INVALID_REVNUM,
+                number: base36_to_u64(number)?,
+            });
+        }
+
+        // special case: 0 / default ID
+        if data == "0" {
+            return Some(Self {
+                revision: 0,
+                number: 0,
+            });
+        }
+
+        // read old style / new style ID
+        let (number, revision) = data.split_once('-')?;
+        Some(Self {
+            revision: revision.parse().ok()?,
+            number: base36_to_u64(number)?,
+        })
+    }
+
+    /// `part_unparse`
+    pub(crate) fn unparse(&self) -> String {
+        if self.revision == SVN_INVALID_REVNUM {
+            format!("_{}", u64_to_base36(self.number))
+        } else if self.revision == 0 && self.number == 0 {
+            "0".to_string()
+        } else {
+            format!("{}-{}", u64_to_base36(self.number), self.revision)
+        }
+    }
+
+    /// Parse a transaction ID as written by [`IdPart::unparse_txn`].
+    ///
+    /// `svn_fs_fs__id_txn_parse`
+    pub fn parse_txn(data: &str) -> Result<Self, IdError> {
+        let malformed = || IdError::MalformedTxnId(data.to_string());
+
+        let (revision, number) = data.split_once('-').ok_or_else(malformed)?;
+        Ok(Self {
+            revision: revision.parse().map_err(|_| malformed())?,
+            number: base36_to_u64(number).ok_or_else(malformed)?,
+        })
+    }
+
+    /// Return the string representation of a transaction ID:
+    /// `<base-rev>-<base36 counter>`.
+    ///
+    /// `svn_fs_fs__id_txn_unparse`
+    pub fn unparse_txn(&self) -> String {
+        format!("{}-{}", self.revision, u64_to_base36(self.number))
+    }
+}
+
+/// A node-revision ID.
+///
+/// `svn_fs_id_t` / `fs_fs__id_t`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FsId {
+    /// node ID, unique within the repository
+    pub node_id: IdPart,
+    /// copy ID, unique within the repository
+    pub copy_id: IdPart,
+    /// the transaction this node revision lives in, or unused
+    pub txn_id: IdPart,
+    /// the location of the node revision within a revision, or unused
+    pub rev_item: IdPart,
+}
+
+impl FsId {
+    /// Return the ID of the root node of revision @a revision.
+    ///
+    /// `svn_fs_fs__id_create_root`
+    pub fn root(revision: RevisionNumber, item_index: u64) -> Self {
+        Self {
+            node_id: IdPart {
+                revision: 0,
+                number: 0,
+            },
+            copy_id: IdPart {
+                revision: 0,
+                number: 0,
+            },
+            txn_id: IdPart::UNUSED_TXN,
+            rev_item: IdPart {
+                revision,
+                number: item_index,
+            },
+        }
+    }
+
+    /// Return the ID of the root node of transaction @a txn_id.
+    ///
+    /// `svn_fs_fs__id_txn_create_root`
+    pub fn txn_root(txn_id: IdPart) -> Self {
+        let root = IdPart {
+            revision: 0,
+            number: 0,
+        };
+        Self::txn(root, root, txn_id)
+    }
+
+    /// Return the ID of a node in transaction @a txn_id.
+    ///
+    /// `svn_fs_fs__id_txn_create`
+    pub fn txn(node_id: IdPart, copy_id: IdPart, txn_id: IdPart) -> Self {
+        Self {
+            node_id,
+            copy_id,
+            txn_id,
+            rev_item: IdPart::UNUSED_TXN,
+        }
+    }
+
+    /// The revision this node-revision was committed in, or
+    /// `SVN_INVALID_REVNUM` for nodes in a transaction.
+    ///
+    /// `svn_fs_fs__id_rev`
+    pub fn rev(&self) -> RevisionNumber {
+        self.rev_item.revision
+    }
+
+    /// The item index (or offset, for physical addressing) of the
+    /// node-revision within its revision.
+    ///
+    /// `svn_fs_fs__id_item`
+    pub fn item(&self) -> u64 {
+        self.rev_item.number
+    }
+
+    /// Return TRUE if this node-revision lives in a transaction.
+    ///
+    /// `svn_fs_fs__id_is_txn`
+    pub fn is_txn(&self) -> bool {
+        self.txn_id.is_used_txn()
+    }
+
+    /// Return TRUE if @a self and @a other belong to the same node, i.e.
+    /// are related by history.
+    ///
+    /// `svn_fs_fs__id_check_related`
+    pub fn check_related(&self, other: &FsId) -> bool {
+        // Items from different txns are unrelated.
+        if self.node_id.revision == SVN_INVALID_REVNUM && self.txn_id != other.txn_id {
+            return false;
+        }
+
+        self.node_id == other.node_id
+    }
+}
+
+impl FromStr for FsId {
+    type Err = IdError;
+
+    /// `svn_fs_fs__id_parse`
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let malformed = || IdError::MalformedNodeRevId(data.to_string());
+
+        let mut parts = data.splitn(3, '.');
+        let node_id = parts.next().and_then(IdPart::parse).ok_or_else(malformed)?;
+        let copy_id = parts.next().and_then(IdPart::parse).ok_or_else(malformed)?;
+        let location = parts.next().ok_or_else(malformed)?;
+
+        if let Some(rev_item) = location.strip_prefix('r') {
+            // This is a revision type ID
+            let (revision, number) = rev_item.split_once('/').ok_or_else(malformed)?;
+            Ok(Self {
+                node_id,
+                copy_id,
+                txn_id: IdPart::UNUSED_TXN,
+                rev_item: IdPart {
+                    revision: revision.parse().map_err(|_| malformed())?,
+                    number: number.parse().map_err(|_| malformed())?,
+                },
+            })
+        } else if let Some(txn_id) = location.strip_prefix('t') {
+            // This is a transaction type ID
+            Ok(Self::txn(
+                node_id,
+                copy_id,
+                IdPart::parse_txn(txn_id).map_err(|_| malformed())?,
+            ))
+        } else {
+            Err(malformed())
+        }
+    }
+}
+
+impl fmt::Display for FsId {
+    /// `svn_fs_fs__id_unparse`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_txn() {
+            write!(
+                f,
+                "{}.{}.t{}",
+                self.node_id.unparse(),
+                self.copy_id.unparse(),
+                self.txn_id.unparse_txn()
+            )
+        } else {
+            write!(
+                f,
+                "{}.{}.r{}/{}",
+                self.node_id.unparse(),
+                self.copy_id.unparse(),
+                self.rev_item.revision,
+                self.rev_item.number
+            )
+        }
+    }
+}
+
+/// `svn__base36toui64`
+pub(crate) fn base36_to_u64(data: &str) -> Option<u64> {
+    if data.is_empty() {
+        return None;
+    }
+    u64::from_str_radix(data, 36).ok()
+}
+
+/// `svn__ui64tobase36`
+pub(crate) fn u64_to_base36(mut value: u64) -> String {
+    if value == 0 {
+        return "0".to_string();
+    }
+
+    let mut digits = Vec::new();
+    while value > 0 {
+        digits.push(std::char::from_digit((value % 36) as u32, 36).expect("digit < 36"));
+        value /= 36;
+    }
+    digits.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_id_roundtrip() {
+        for s in ["0.0.r0/2", "1-3.0.r3/1234", "_a._1.t5-z", "zz-12.3-4.r12/0"] {
+            let id: FsId = s.parse().unwrap();
+            assert_eq!(id.to_string(), s);
+        }
+
+        let id: FsId = "_a._1.t5-z".parse().unwrap();
+        assert!(id.is_txn());
+        assert_eq!(id.node_id.number, 10);
+        assert_eq!(id.txn_id.number, 35);
+
+        assert!("0.0".parse::<FsId>().is_err());
+        assert!("0.0.x1".parse::<FsId>().is_err());
+    }
+}
@@ -13,13 +13,49 @@ pub use fs::SvnFs;
 pub mod backend;
 mod node;
 
+mod history;
+pub use history::FsHistory;
+pub use history::HistoryTrait;
+
 mod root;
+pub use root::DirectoryEntry;
 pub use root::FsRoot;
+pub use root::RootTrait;
 
+pub use node::CopyFromInfo;
 pub use node::NodeRevision;
+pub use node::Representation;
+
+pub mod id;
+pub use id::FsId;
+
+mod change;
+pub use change::PathChange;
+pub use change::PathChangeKind;
 
 mod util;
 
+mod lock;
+pub use lock::LockTarget;
+
+mod recover;
+pub use recover::RecoverReport;
+
+mod verify;
+pub use verify::VerifyError;
+pub use verify::VerifyErrorKind;
+
+mod stats;
+pub use stats::FsStats;
+pub use stats::Histogram;
+pub use stats::HistogramLine;
+pub use stats::LARGEST_FILES_COUNT;
+pub use stats::LargestFile;
+pub use stats::NodeStats;
+pub use stats::RepPackStats;
+pub use stats::RepStats;
+pub use stats::ShardStats;
+
 use std::collections::HashMap;
 use std::fmt::Debug;
 
@@ -39,6 +75,36 @@ pub struct FsAccess {
     pub lock_tokens: HashMap<Uuid, String>,
 }
 
+impl FsAccess {
+    /// Create an access context for the authenticated @a username,
+    /// without any lock tokens.
+    ///
+    /// `svn_fs_create_access`
+    pub fn new(username: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            lock_tokens: HashMap::new(),
+        }
+    }
+
+    /// Add the lock token @a token for @a path to this context, so that
+    /// commits may change the path locked with it.
+    ///
+    /// `svn_fs_access_add_lock_token2`
+    pub fn add_lock_token(&mut self, token: &str, path: &str) -> Result<(), Error> {
+        let uuid = lock::lock_token_uuid(token)
+            .ok_or_else(|| backend::BackendError::MalformedLockToken(token.to_string()))?;
+        self.lock_tokens.insert(uuid, path.to_string());
+
+        Ok(())
+    }
+
+    /// Return whether this context holds the lock token @a token.
+    pub(crate) fn has_lock_token(&self, token: &str) -> bool {
+        lock::lock_token_uuid(token).is_some_and(|uuid| self.lock_tokens.contains_key(&uuid))
+    }
+}
+
 /// `compression_type_t`
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum CompressionType {
@@ -47,3 +113,36 @@ pub enum CompressionType {
     Zlib(i32), // level
     Lz4,
 }
+
+/// The kind of action being taken by 'pack'.
+///
+/// `svn_fs_pack_notify_action_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackNotifyAction {
+    /// packing of the shard has commenced
+    Start,
+    /// packing of the shard is completed
+    End,
+    /// packing of the shard revprops has commenced
+    StartRevprop,
+    /// packing of the shard revprops has completed
+    EndRevprop,
+    /// pack has been a no-op for this repository.  The next / future
+    /// packable shard will be given.  If the shard is -1, then the
+    /// repository does not support packing at all.
+    Noop,
+}
+
+/// The kind of action being taken by 'upgrade'.
+///
+/// `svn_fs_upgrade_notify_action_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeNotifyAction {
+    /// Packing of the revprops of the given shard has completed.
+    PackRevprops,
+    /// Removal of the non-packed revprops of the given shard has
+    /// completed.
+    CleanupRevprops,
+    /// The filesystem has been bumped to the given format number.
+    FormatBumped,
+}
@@ -0,0 +1,97 @@
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use svn_types::RevisionNumber;
+use svn_types::SVN_INVALID_REVNUM;
+use svn_types::SvnLock;
+use uuid::Uuid;
+
+use crate::FsAccess;
+use crate::backend::BackendError;
+
+/// The URI scheme all lock tokens start with.
+pub(crate) const LOCK_TOKEN_SCHEME: &str = "opaquelocktoken:";
+
+/// A path to lock with [`crate::SvnFs::lock`].
+///
+/// `svn_fs_lock_target_t`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockTarget {
+    /// the token for the new lock, or `None` to generate one
+    pub token: Option<String>,
+    /// if valid, fail if the path has been changed after this revision
+    pub current_rev: RevisionNumber,
+}
+
+impl LockTarget {
+    /// Create a lock target with the token @a token and the out-of-date
+    /// check against @a current_rev.
+    ///
+    /// `svn_fs_lock_target_create`
+    pub fn new(token: Option<&str>, current_rev: RevisionNumber) -> Self {
+        Self {
+            token: token.map(str::to_string),
+            current_rev,
+        }
+    }
+}
+
+impl Default for LockTarget {
+    fn default() -> Self {
+        Self::new(None, SVN_INVALID_REVNUM)
+    }
+}
+
+/// Return the current time as microseconds since the epoch, the unit of
+/// the dates in [`svn_types::SvnLock`].
+///
+/// `apr_time_now`
+pub(crate) fn time_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_micros() as i64)
+}
+
+/// Return whether @a lock has expired.
+pub(crate) fn is_expired(lock: &SvnLock) -> bool {
+    lock.expiration_date != 0 && time_now() > lock.expiration_date
+}
+
+/// Fail if @a access may not change the path of @a lock.
+///
+/// `verify_lock`
+pub(crate) fn verify_lock(access: Option<&FsAccess>, lock: &SvnLock) -> Result<(), BackendError> {
+    let Some(access) = access.filter(|access| !access.username.is_empty()) else {
+        return Err(BackendError::NoUser {
+            action: "verify lock on",
+            path: lock.path.clone(),
+        });
+    };
+    if access.username != lock.owner {
+        return Err(BackendError::LockOwnerMismatch {
+            user: access.username.clone(),
+            path: lock.path.clone(),
+            owner: lock.owner.clone(),
+        });
+    }
+    if !access.has_lock_token(&lock.token) {
+        return Err(BackendError::BadLockToken(lock.path.clone()));
+    }
+
+    Ok(())
+}
+
+/// Return the UUID that makes up the lock token @a token, or `None` if
+/// @a token is not a valid lock token.
+pub(crate) fn lock_token_uuid(token: &str) -> Option<Uuid> {
+    token
+        .strip_prefix(LOCK_TOKEN_SCHEME)
+        .and_then(|uuid| Uuid::parse_str(uuid).ok())
+}
+
+/// Return a new, unique lock token.
+///
+/// `svn_fs_fs__generate_lock_token`
+pub(crate) fn generate_lock_token() -> String {
+    format!("{LOCK_TOKEN_SCHEME}{}", Uuid::new_v4())
+}
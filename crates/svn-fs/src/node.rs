@@ -1,20 +1,115 @@
+use svn_subr::Checksum;
 use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+
+use crate::id::FsId;
+use crate::id::IdPart;
+
+/// The location a node was copied from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyFromInfo {
+    /// the copy source revision
+    pub rev: RevisionNumber,
+    /// the copy source path
+    pub path: String,
+}
+
+/// Uniquifier of representations in a transaction: reps with the same
+/// content but different uniquifiers are not shared.
+///
+/// `svn_fs_fs__rep_uniquifier_t`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepUniquifier {
+    /// unique context, i.e. txn ID, in which the noderev (!) got created
+    pub noderev_txn_id: IdPart,
+    /// unique value within that txn
+    pub number: u64,
+}
+
+/// Representation is where the actual data (text or properties) of a
+/// node revision is stored.
+///
+/// `representation_t`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Representation {
+    /// Checksums digests for the contents produced by this representation.
+    /// If checksum is NULL, the checksum is unknown.
+    pub md5_digest: Option<Checksum>,
+    /// SHA1 digest, only written by formats supporting rep-sharing.
+    pub sha1_digest: Option<Checksum>,
+
+    /// Revision where this representation is located.
+    pub revision: RevisionNumber,
+    /// Item index (or offset, for physical addressing) with the revision.
+    pub item_index: u64,
+    /// The size of the representation in bytes as seen in the revision
+    /// file.
+    pub size: u64,
+    /// The size of the fulltext of the representation.  If this is 0,
+    /// for a plain rep, the real fulltext size is equal to the SIZE field.
+    /// For a delta rep, this field is always the real fulltext size.
+    ///
+    /// Note that svn_fs_fs__fixup_expanded_size() checks for these special
+    /// cases and ensures that this field contains the actual value.  We call
+    /// it early after reading a representation struct, so most code does not
+    /// have to worry about it.
+    pub expanded_size: u64,
+
+    /// Is this a representation (still) within a transaction?
+    pub txn_id: IdPart,
+
+    /// For rep-sharing, we need a way of uniquifying node-revs which share
+    /// the same representation (see svn_fs_fs__noderev_same_rep_key() ).
+    /// So, we store the original txn of the node rev (not the rep!), along
+    /// with some intra-node uniqification content.
+    pub uniquifier: Option<RepUniquifier>,
+}
 
 /// Node-Revision
 ///
 /// `node_revision_t`
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NodeRevision {
     /// node kind
     pub kind: NodeKind,
-    // /// The node-id for this node-rev.
-    // pub id: TODO
+
+    /// The node-id for this node-rev.
+    pub id: FsId,
+
     /// predecessor node revision id, or NULL if there is no predecessor
     /// for this node revision
-    pub predecessor_id: Option<String>,
+    pub predecessor_id: Option<FsId>,
 
     /// If this node-rev is a copy, where was it copied from?
-    ///
-    /// FIXME: make it `CopyFromInfo` struct together with `copyfrom_path` and `copyfrom_rev`
-    copyfrom_path: Option<String>,
-    copyfrom_rev: Option<i64>,
+    pub copyfrom: Option<CopyFromInfo>,
+
+    /// Helper for history tracing, root of the parent tree from whence
+    /// this node-rev was copied.
+    pub copyroot_rev: RevisionNumber,
+    pub copyroot_path: String,
+
+    /// number of predecessors this node revision has (recursively).
+    /// A difference from the BDB backend is that it cannot be -1.
+    pub predecessor_count: i64,
+
+    /// representation key for this node's properties.  May be NULL if
+    /// there are no properties.
+    pub prop_rep: Option<Representation>,
+
+    /// representation for this node's data.  May be NULL if there is
+    /// no data.
+    pub data_rep: Option<Representation>,
+
+    /// path at which this node first came into existence.
+    pub created_path: String,
+
+    /// is this the unmodified root of a transaction?
+    pub is_fresh_txn_root: bool,
+
+    /// Number of nodes with svn:mergeinfo properties that are
+    /// descendants of this node (including it itself)
+    pub mergeinfo_count: i64,
+
+    /// Does this node itself have svn:mergeinfo?
+    pub has_mergeinfo: bool,
 }
@@ -0,0 +1,18 @@
+use svn_types::RevisionNumber;
+
+/// What [`crate::SvnFs::recover`] found and repaired.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoverReport {
+    /// the youngest revision found in the rev files, now recorded in
+    /// `current`
+    pub youngest: RevisionNumber,
+    /// whether `txn-current` was missing, unreadable or behind the
+    /// existing transactions and had to be rewritten
+    pub txn_current_repaired: bool,
+    /// transactions based on revisions younger than `youngest`; they can
+    /// never be committed and should be purged
+    pub orphaned_txns: Vec<String>,
+    /// proto-rev files (and their locks) in `txn-protorevs` without a
+    /// transaction, which have been removed
+    pub orphaned_proto_revs: Vec<String>,
+}
@@ -5,22 +5,61 @@
 //  * node or node revision, you provide a root, and a directory path
 //  * relative to that root.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::io::Read;
 
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_types::MergeinfoCatalog;
+use svn_types::MergeinfoInheritance;
+use svn_types::NodeKind;
+use svn_types::RevisionNumber;
+
+use crate::CopyFromInfo;
+use crate::Error;
+use crate::FsId;
+use crate::PathChange;
+use crate::backend::BackendError;
 use crate::fs::SvnFs;
+use crate::history::FsHistory;
+use crate::history::HistoryTrait;
+use crate::util::prop_lists_equal;
+
+/// The type of a Subversion directory entry.
+///
+/// `svn_fs_dirent_t`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    /// The name of this directory entry.
+    pub name: String,
+
+    /// The node revision ID it names.
+    pub id: FsId,
+
+    /// The node kind.
+    pub kind: NodeKind,
+}
 
 /// The Filesystem Root object.
 ///
 /// `svn_fs_root_t`
-pub struct FsRoot {
+pub struct FsRoot<'fs> {
     /// The filesystem to which this root belongs
-    fs: Box<SvnFs>,
+    fs: &'fs SvnFs,
 
     /// The kind of root this is
     is_txn_root: bool,
 
     ///  For transaction roots, the name of the transaction
     txn: Option<String>,
+
+    /// For revision roots, the number of that revision.  For transaction
+    /// roots, the number of the revision that the transaction is based on.
+    rev: RevisionNumber,
+
+    /// The back-end specific implementation of this root
+    vtable: Box<dyn RootTrait + 'fs>,
 }
 
 /// `root_vtable_t`
@@ -35,5 +74,423 @@ pub struct FsRoot {
 ///
 /// @Note: delete_node() corresponds to svn_fs_delete().
 pub trait RootTrait {
-    fn paths_changed(&self, changed_paths: &HashMap<(), ()>) -> Result<(), ()>;
+    /// `svn_fs_paths_changed3`
+    fn paths_changed(&self) -> Result<Vec<PathChange>, BackendError>;
+
+    /// `svn_fs_check_path`
+    fn check_path(&self, path: &str) -> Result<NodeKind, BackendError>;
+
+    /// `svn_fs_node_id`
+    fn node_id(&self, path: &str) -> Result<FsId, BackendError>;
+
+    /// `svn_fs_node_created_rev`
+    fn node_created_rev(&self, path: &str) -> Result<RevisionNumber, BackendError>;
+
+    /// `svn_fs_copied_from`
+    fn copied_from(&self, path: &str) -> Result<Option<CopyFromInfo>, BackendError>;
+
+    /// `svn_fs_node_history2`
+    fn node_history(&self, path: &str) -> Result<Box<dyn HistoryTrait<'_> + '_>, BackendError>;
+
+    /// `svn_fs_closest_copy`
+    fn closest_copy(&self, path: &str) -> Result<Option<(RevisionNumber, String)>, BackendError>;
+
+    /// `svn_fs_node_origin_rev`
+    fn node_origin_rev(&self, path: &str) -> Result<RevisionNumber, BackendError>;
+
+    /// `svn_fs_node_proplist`
+    fn node_proplist(&self, path: &str) -> Result<HashMap<String, String>, BackendError>;
+
+    /// `svn_fs_get_mergeinfo2`
+    fn get_mergeinfo(
+        &self,
+        paths: &[&str],
+        inherit: MergeinfoInheritance,
+        include_descendants: bool,
+        adjust_inherited_mergeinfo: bool,
+    ) -> Result<MergeinfoCatalog, BackendError>;
+
+    /// `svn_fs_dir_entries`
+    fn dir_entries(&self, path: &str) -> Result<BTreeMap<String, DirectoryEntry>, BackendError>;
+
+    /// `svn_fs_file_length`
+    fn file_length(&self, path: &str) -> Result<u64, BackendError>;
+
+    /// `svn_fs_file_checksum`
+    fn file_checksum(
+        &self,
+        kind: ChecksumKind,
+        path: &str,
+    ) -> Result<Option<Checksum>, BackendError>;
+
+    /// `svn_fs_file_contents`
+    fn file_contents(&self, path: &str) -> Result<Box<dyn Read + 'static>, BackendError>;
+
+    /// `svn_fs_make_dir`
+    fn make_dir(&self, path: &str) -> Result<(), BackendError>;
+
+    /// `svn_fs_make_file`
+    fn make_file(&self, path: &str) -> Result<(), BackendError>;
+
+    /// `svn_fs_delete`
+    fn delete_node(&self, path: &str) -> Result<(), BackendError>;
+
+    /// `svn_fs_copy`
+    fn copy(
+        &self,
+        from_rev: RevisionNumber,
+        from_path: &str,
+        to_path: &str,
+    ) -> Result<(), BackendError>;
+
+    /// `svn_fs_change_node_prop`
+    fn change_node_prop(
+        &self,
+        path: &str,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), BackendError>;
+
+    /// `svn_fs_apply_text`
+    fn apply_text(
+        &self,
+        path: &str,
+        contents: &[u8],
+        result_checksum: Option<&Checksum>,
+    ) -> Result<(), BackendError>;
+}
+
+impl<'fs> FsRoot<'fs> {
+    /// Create a root object for revision @a rev of @a fs, implemented by
+    /// @a vtable.
+    pub(crate) fn new_revision_root(
+        fs: &'fs SvnFs,
+        rev: RevisionNumber,
+        vtable: Box<dyn RootTrait + 'fs>,
+    ) -> Self {
+        Self {
+            fs,
+            is_txn_root: false,
+            txn: None,
+            rev,
+            vtable,
+        }
+    }
+
+    /// Create a root object for the transaction @a txn of @a fs, based
+    /// on revision @a base_rev and implemented by @a vtable.
+    pub(crate) fn new_txn_root(
+        fs: &'fs SvnFs,
+        txn: String,
+        base_rev: RevisionNumber,
+        vtable: Box<dyn RootTrait + 'fs>,
+    ) -> Self {
+        Self {
+            fs,
+            is_txn_root: true,
+            txn: Some(txn),
+            rev: base_rev,
+            vtable,
+        }
+    }
+
+    /// Return the filesystem to which this root belongs.
+    ///
+    /// `svn_fs_root_fs`
+    pub fn fs(&self) -> &'fs SvnFs {
+        self.fs
+    }
+
+    /// Return `true` iff this root is a transaction root.
+    ///
+    /// `svn_fs_is_txn_root`
+    pub fn is_txn_root(&self) -> bool {
+        self.is_txn_root
+    }
+
+    /// Return `true` iff this root is a revision root.
+    ///
+    /// `svn_fs_is_revision_root`
+    pub fn is_revision_root(&self) -> bool {
+        !self.is_txn_root
+    }
+
+    /// If this is the root of a transaction, return its name.
+    ///
+    /// `svn_fs_txn_root_name`
+    pub fn txn_name(&self) -> Option<&str> {
+        self.txn.as_deref()
+    }
+
+    /// If this is the root of a revision, return its number.  For
+    /// transaction roots, return the base revision of the transaction.
+    ///
+    /// `svn_fs_revision_root_revision`
+    pub fn revision(&self) -> RevisionNumber {
+        self.rev
+    }
+
+    /// Return the changes made in this root, one entry per changed path.
+    ///
+    /// `svn_fs_paths_changed3`
+    pub fn paths_changed(&self) -> Result<Vec<PathChange>, Error> {
+        Ok(self.vtable.paths_changed()?)
+    }
+
+    /// Return the kind of node at @a path, or [`NodeKind::None`] if it
+    /// does not exist.
+    ///
+    /// `svn_fs_check_path`
+    pub fn check_path(&self, path: &str) -> Result<NodeKind, Error> {
+        Ok(self.vtable.check_path(path)?)
+    }
+
+    /// Return the node revision ID of @a path.
+    ///
+    /// `svn_fs_node_id`
+    pub fn node_id(&self, path: &str) -> Result<FsId, Error> {
+        Ok(self.vtable.node_id(path)?)
+    }
+
+    /// Return the revision in which @a path was last modified.
+    ///
+    /// `svn_fs_node_created_rev`
+    pub fn node_created_rev(&self, path: &str) -> Result<RevisionNumber, Error> {
+        Ok(self.vtable.node_created_rev(path)?)
+    }
+
+    /// Return the copy source of @a path if the node at @a path was
+    /// created by a copy in this root.
+    ///
+    /// `svn_fs_copied_from`
+    pub fn copied_from(&self, path: &str) -> Result<Option<CopyFromInfo>, Error> {
+        Ok(self.vtable.copied_from(path)?)
+    }
+
+    /// Return the history of the node at @a path, starting at the
+    /// revision of this root, which must be a revision root.
+    ///
+    /// `svn_fs_node_history2`
+    pub fn node_history(&self, path: &str) -> Result<FsHistory<'_>, Error> {
+        Ok(FsHistory::new(self.vtable.node_history(path)?))
+    }
+
+    /// Return the root and path of the destination of the most recent
+    /// copy that affected @a path in this root, or `None` if it has not
+    /// been affected by a copy.  For a path copied along with a
+    /// directory, that is the path of the copied directory.
+    ///
+    /// `svn_fs_closest_copy`
+    pub fn closest_copy(&self, path: &str) -> Result<Option<(FsRoot<'fs>, String)>, Error> {
+        match self.vtable.closest_copy(path)? {
+            Some((rev, copy_path)) => Ok(Some((self.fs.revision_root(rev)?, copy_path))),
+            None => Ok(None),
+        }
+    }
+
+    /// Return the revision in which the line of history of the node at
+    /// @a path was created, following it across copies.
+    ///
+    /// `svn_fs_node_origin_rev`
+    pub fn node_origin_rev(&self, path: &str) -> Result<RevisionNumber, Error> {
+        Ok(self.vtable.node_origin_rev(path)?)
+    }
+
+    /// Return `true` if the contents of the file @a path1 in this root
+    /// differ from those of the file @a path2 in @a root2.  Both roots
+    /// must belong to the same filesystem.
+    ///
+    /// `svn_fs_contents_changed`
+    pub fn contents_changed(
+        &self,
+        path1: &str,
+        root2: &FsRoot<'_>,
+        path2: &str,
+    ) -> Result<bool, Error> {
+        if !std::ptr::eq(self.fs, root2.fs) {
+            return Err(Error::CrossFsCompare("file contents"));
+        }
+        for (root, path) in [(self, path1), (root2, path2)] {
+            if root.check_path(path)? != NodeKind::File {
+                return Err(BackendError::NotFile(path.to_string()).into());
+            }
+        }
+
+        // The checksums tell unless a back-end does not know them.
+        if let (Some(md5_1), Some(md5_2)) = (
+            self.file_checksum(ChecksumKind::Md5, path1)?,
+            root2.file_checksum(ChecksumKind::Md5, path2)?,
+        ) {
+            return Ok(md5_1 != md5_2);
+        }
+
+        let mut contents1 = Vec::new();
+        let mut contents2 = Vec::new();
+        self.file_contents(path1)?.read_to_end(&mut contents1)?;
+        root2.file_contents(path2)?.read_to_end(&mut contents2)?;
+        Ok(contents1 != contents2)
+    }
+
+    /// Return `true` if the properties of @a path1 in this root differ
+    /// from those of @a path2 in @a root2.  Both roots must belong to the
+    /// same filesystem.
+    ///
+    /// `svn_fs_props_changed`
+    pub fn props_changed(
+        &self,
+        path1: &str,
+        root2: &FsRoot<'_>,
+        path2: &str,
+    ) -> Result<bool, Error> {
+        if !std::ptr::eq(self.fs, root2.fs) {
+            return Err(Error::CrossFsCompare("property value"));
+        }
+
+        Ok(!prop_lists_equal(
+            &self.node_proplist(path1)?,
+            &root2.node_proplist(path2)?,
+        ))
+    }
+
+    /// Return the properties of the node at @a path.
+    ///
+    /// `svn_fs_node_proplist`
+    pub fn node_proplist(&self, path: &str) -> Result<HashMap<String, String>, Error> {
+        Ok(self.vtable.node_proplist(path)?)
+    }
+
+    /// Return the mergeinfo of each of @a paths in this root, which must
+    /// be a revision root, keyed by path.  Paths without mergeinfo are
+    /// left out.
+    ///
+    /// @a inherit selects whether only the explicit mergeinfo of a path
+    /// counts, or whether the mergeinfo of the nearest ancestor having
+    /// any applies.  If @a adjust_inherited_mergeinfo is set, inherited
+    /// mergeinfo is reduced to its inheritable ranges and its merge
+    /// source paths are extended by the path below the ancestor.  If
+    /// @a include_descendants is set, the explicit mergeinfo of all
+    /// nodes below @a paths is returned as well.
+    ///
+    /// The back-ends find the subtrees holding mergeinfo without reading
+    /// the properties of every node.
+    ///
+    /// `svn_fs_get_mergeinfo2`
+    pub fn get_mergeinfo(
+        &self,
+        paths: &[&str],
+        inherit: MergeinfoInheritance,
+        include_descendants: bool,
+        adjust_inherited_mergeinfo: bool,
+    ) -> Result<MergeinfoCatalog, Error> {
+        Ok(self.vtable.get_mergeinfo(
+            paths,
+            inherit,
+            include_descendants,
+            adjust_inherited_mergeinfo,
+        )?)
+    }
+
+    /// Return the entries of the directory at @a path, keyed by name.
+    ///
+    /// `svn_fs_dir_entries`
+    pub fn dir_entries(&self, path: &str) -> Result<BTreeMap<String, DirectoryEntry>, Error> {
+        Ok(self.vtable.dir_entries(path)?)
+    }
+
+    /// Return the length of the file at @a path.
+    ///
+    /// `svn_fs_file_length`
+    pub fn file_length(&self, path: &str) -> Result<u64, Error> {
+        Ok(self.vtable.file_length(path)?)
+    }
+
+    /// Return the checksum of @a kind of the file at @a path, or `None` if
+    /// the filesystem does not have that checksum at hand.
+    ///
+    /// `svn_fs_file_checksum`
+    pub fn file_checksum(&self, kind: ChecksumKind, path: &str) -> Result<Option<Checksum>, Error> {
+        Ok(self.vtable.file_checksum(kind, path)?)
+    }
+
+    /// Return a stream over the contents of the file at @a path.  The
+    /// checksum of the contents is verified when the end of the stream is
+    /// reached.
+    ///
+    /// `svn_fs_file_contents`
+    pub fn file_contents(&self, path: &str) -> Result<Box<dyn Read + 'static>, Error> {
+        Ok(self.vtable.file_contents(path)?)
+    }
+
+    /// Create a new directory named @a path in this transaction root.
+    /// The parent of @a path must exist.
+    ///
+    /// `svn_fs_make_dir`
+    pub fn make_dir(&self, path: &str) -> Result<(), Error> {
+        Ok(self.vtable.make_dir(path)?)
+    }
+
+    /// Create a new, empty file named @a path in this transaction root.
+    /// The parent of @a path must exist.
+    ///
+    /// `svn_fs_make_file`
+    pub fn make_file(&self, path: &str) -> Result<(), Error> {
+        Ok(self.vtable.make_file(path)?)
+    }
+
+    /// Delete the node named @a path in this transaction root.  If the
+    /// node is a directory, its contents are deleted as well.
+    ///
+    /// `svn_fs_delete`
+    pub fn delete(&self, path: &str) -> Result<(), Error> {
+        Ok(self.vtable.delete_node(path)?)
+    }
+
+    /// Create a copy of @a from_path in @a from_root named @a to_path in
+    /// this transaction root.  The copy remembers its history, i.e. it is
+    /// a successor of the copied node.
+    ///
+    /// @a from_root must be a revision root of the same filesystem.
+    ///
+    /// `svn_fs_copy`
+    pub fn copy(
+        &self,
+        from_root: &FsRoot<'_>,
+        from_path: &str,
+        to_path: &str,
+    ) -> Result<(), Error> {
+        if !std::ptr::eq(self.fs, from_root.fs) {
+            return Err(Error::CrossFsCopy);
+        }
+        if from_root.is_txn_root {
+            return Err(Error::NotRevisionRoot);
+        }
+        Ok(self.vtable.copy(from_root.rev, from_path, to_path)?)
+    }
+
+    /// Set the property @a name of the node at @a path in this transaction
+    /// root to @a value, or delete it if @a value is `None`.
+    ///
+    /// `svn_fs_change_node_prop`
+    pub fn change_node_prop(
+        &self,
+        path: &str,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), Error> {
+        Ok(self.vtable.change_node_prop(path, name, value)?)
+    }
+
+    /// Replace the contents of the file at @a path in this transaction
+    /// root with @a contents.  If @a result_checksum is given, the MD5
+    /// checksum of @a contents must match it.
+    ///
+    /// `svn_fs_apply_text`
+    pub fn apply_text(
+        &self,
+        path: &str,
+        contents: &[u8],
+        result_checksum: Option<&Checksum>,
+    ) -> Result<(), Error> {
+        Ok(self.vtable.apply_text(path, contents, result_checksum)?)
+    }
 }
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use svn_types::RevisionNumber;
+
+/// The number of files kept in [`FsStats::largest_files`].
+pub const LARGEST_FILES_COUNT: usize = 64;
+
+/// A line of a [`Histogram`].
+///
+/// `svn_fs_fs__histogram_line_t`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistogramLine {
+    /// number of entries
+    pub count: u64,
+    /// sum over all entries
+    pub sum: u64,
+}
+
+/// A histogram of sizes in buckets of powers of two.
+///
+/// `svn_fs_fs__histogram_t`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// all entries
+    pub total: HistogramLine,
+    /// line 0 counts the entries of size 0, line @a i > 0 the ones in
+    /// [2^(i-1), 2^i)
+    pub lines: Vec<HistogramLine>,
+}
+
+impl Histogram {
+    /// Add an entry of @a size.
+    ///
+    /// `add_to_histogram`
+    pub(crate) fn add(&mut self, size: u64) {
+        let line = (u64::BITS - size.leading_zeros()) as usize;
+        if self.lines.len() <= line {
+            self.lines.resize(line + 1, HistogramLine::default());
+        }
+
+        for line in [&mut self.total, &mut self.lines[line]] {
+            line.count += 1;
+            line.sum += size;
+        }
+    }
+}
+
+/// Sizes of a set of representations.
+///
+/// `svn_fs_fs__rep_pack_stats_t`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepPackStats {
+    /// number of representations
+    pub count: u64,
+    /// total size of the representations as stored in the rev files,
+    /// excluding headers and trailers
+    pub packed_size: u64,
+    /// total size of the representations' fulltexts
+    pub expanded_size: u64,
+    /// total size of the representation headers and trailers
+    pub overhead_size: u64,
+}
+
+impl RepPackStats {
+    /// Return the sum of this and @a other.
+    pub fn add(&self, other: &RepPackStats) -> RepPackStats {
+        RepPackStats {
+            count: self.count + other.count,
+            packed_size: self.packed_size + other.packed_size,
+            expanded_size: self.expanded_size + other.expanded_size,
+            overhead_size: self.overhead_size + other.overhead_size,
+        }
+    }
+}
+
+/// Statistics of the representations of one kind of node-revision data.
+///
+/// `svn_fs_fs__representation_stats_t`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepStats {
+    /// PLAIN representations, i.e. stored as fulltext
+    pub plain: RepPackStats,
+    /// DELTA representations, against some base or the empty stream
+    pub delta: RepPackStats,
+    /// representations referred to by more than one node-revision, out of
+    /// @a plain and @a delta
+    pub shared: RepPackStats,
+    /// number of node-revisions referring to any of the representations
+    pub references: u64,
+    /// number of bytes of representation data that storing a separate copy
+    /// for every reference would take in addition
+    pub saved_size: u64,
+    /// number of representations by the length of their delta chain,
+    /// counting the representation itself
+    pub chain_lengths: BTreeMap<usize, u64>,
+    /// representation sizes as stored in the rev files
+    pub size_histogram: Histogram,
+}
+
+impl RepStats {
+    /// Return the sizes of all representations, PLAIN and DELTA.
+    pub fn total(&self) -> RepPackStats {
+        self.plain.add(&self.delta)
+    }
+}
+
+/// Statistics of a kind of node-revision.
+///
+/// `svn_fs_fs__node_stats_t`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeStats {
+    /// number of node-revisions
+    pub count: u64,
+    /// total size of the node-revision records
+    pub size: u64,
+}
+
+/// A file representation among the [`FsStats::largest_files`].
+///
+/// `svn_fs_fs__large_change_info_t`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LargestFile {
+    /// the size of the file's contents
+    pub size: u64,
+    /// the revision that added the representation
+    pub revision: RevisionNumber,
+    /// the path of the file in that revision
+    pub path: String,
+}
+
+/// How well the revisions of a shard are stored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShardStats {
+    /// the first revision of the shard
+    pub start_revision: RevisionNumber,
+    /// number of revisions found in the shard
+    pub revisions: u64,
+    /// whether the shard has been packed
+    pub packed: bool,
+    /// number of rev / pack files and manifests
+    pub files: u64,
+    /// total size of these files
+    pub file_size: u64,
+    /// total size of the node-revisions, representations and
+    /// changed-paths lists in them
+    pub item_size: u64,
+}
+
+impl ShardStats {
+    /// Return the number of bytes taken by anything but the items, i.e.
+    /// indexes, trailers and manifests.
+    pub fn overhead(&self) -> u64 {
+        self.file_size.saturating_sub(self.item_size)
+    }
+}
+
+/// Statistics about the data stored in an FSFS filesystem, as gathered
+/// by [`crate::SvnFs::stats`].
+///
+/// `svn_fs_fs__stats_t`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FsStats {
+    /// total size of all rev / pack files and manifests
+    pub total_size: u64,
+    /// number of revisions in the filesystem
+    pub revision_count: u64,
+    /// total number of changed paths
+    pub change_count: u64,
+    /// total size of the changed-paths lists
+    pub change_len: u64,
+
+    /// file contents
+    pub file_reps: RepStats,
+    /// directory listings
+    pub dir_reps: RepStats,
+    /// file properties
+    pub file_prop_reps: RepStats,
+    /// directory properties
+    pub dir_prop_reps: RepStats,
+
+    /// file node-revisions
+    pub file_nodes: NodeStats,
+    /// directory node-revisions
+    pub dir_nodes: NodeStats,
+
+    /// the largest file representations, up to [`LARGEST_FILES_COUNT`],
+    /// largest first
+    pub largest_files: Vec<LargestFile>,
+
+    /// the shards in revision order; a linear layout has a single one
+    pub shards: Vec<ShardStats>,
+}
+
+impl FsStats {
+    /// Return the sizes of all representations.
+    pub fn total_reps(&self) -> RepPackStats {
+        [
+            &self.file_reps,
+            &self.dir_reps,
+            &self.file_prop_reps,
+            &self.dir_prop_reps,
+        ]
+        .iter()
+        .fold(RepPackStats::default(), |total, reps| {
+            total.add(&reps.total())
+        })
+    }
+
+    /// Return the statistics of all node-revisions.
+    pub fn total_nodes(&self) -> NodeStats {
+        NodeStats {
+            count: self.file_nodes.count + self.dir_nodes.count,
+            size: self.file_nodes.size + self.dir_nodes.size,
+        }
+    }
+}
@@ -1,24 +1,147 @@
+use std::collections::HashMap;
+
 use svn_types::RevisionNumber;
 
+use crate::Error;
+use crate::FsAccess;
+use crate::FsRoot;
+use crate::RootTrait;
 use crate::SvnFs;
+use crate::backend::BackendError;
 
 /// `svn_fs_txn_t`
-pub struct FsTxn {
+pub struct FsTxn<'fs> {
     // The filesystem to which this transaction belongs
-    fs: SvnFs,
+    fs: &'fs SvnFs,
     // The revision on which this transaction is based, or
     // if the transaction is not based on a
     // revision at all
     base_rev: RevisionNumber,
     /// The ID of this transaction
     id: String,
+
+    /// The back-end specific implementation of this transaction
+    vtable: Box<dyn Txn + 'fs>,
 }
 
 /// `txn_vtable_t`
-trait Txn {
-    type FsapData;
+///
+/// These callbacks correspond to svn_fs_* functions in include/svn_fs.h,
+/// see there for details.
+pub trait Txn {
+    /// The name of the transaction.
+    fn name(&self) -> &str;
+
+    /// The revision the transaction is currently based on.
+    fn base_revision(&self) -> RevisionNumber;
+
+    /// `svn_fs_commit_txn`
+    ///
+    /// The locks on the changed paths must be owned by the user of
+    /// @a access, which must hold their tokens.
+    fn commit(&mut self, access: Option<&FsAccess>) -> Result<RevisionNumber, BackendError>;
+
+    /// `svn_fs_abort_txn`
+    fn abort(&self) -> Result<(), BackendError>;
+
+    /// `svn_fs_txn_prop`
+    fn prop(&self, name: &str) -> Result<Option<String>, BackendError>;
+
+    /// `svn_fs_txn_proplist`
+    fn proplist(&self) -> Result<HashMap<String, String>, BackendError>;
+
+    /// `svn_fs_change_txn_prop`
+    fn change_prop(&self, name: &str, value: Option<&str>) -> Result<(), BackendError>;
+
+    /// `svn_fs_txn_root`
+    fn root(&self) -> Result<Box<dyn RootTrait + '_>, BackendError>;
 }
 
-impl Txn for FsTxn {
-    type FsapData = ();
+impl<'fs> FsTxn<'fs> {
+    /// Wrap the back-end transaction @a vtable of @a fs.
+    pub(crate) fn new(fs: &'fs SvnFs, vtable: Box<dyn Txn + 'fs>) -> Self {
+        Self {
+            fs,
+            base_rev: vtable.base_revision(),
+            id: vtable.name().to_string(),
+            vtable,
+        }
+    }
+
+    /// Return the filesystem to which this transaction belongs.
+    pub fn fs(&self) -> &'fs SvnFs {
+        self.fs
+    }
+
+    /// Return the name of this transaction.
+    ///
+    /// `svn_fs_txn_name`
+    pub fn name(&self) -> &str {
+        &self.id
+    }
+
+    /// Return the revision this transaction is based on.
+    ///
+    /// `svn_fs_txn_base_revision`
+    pub fn base_revision(&self) -> RevisionNumber {
+        self.base_rev
+    }
+
+    /// Return the root directory of this transaction.
+    ///
+    /// `svn_fs_txn_root`
+    pub fn root(&self) -> Result<FsRoot<'_>, Error> {
+        let vtable = self.vtable.root()?;
+        Ok(FsRoot::new_txn_root(
+            self.fs,
+            self.id.clone(),
+            self.base_rev,
+            vtable,
+        ))
+    }
+
+    /// Commit this transaction and return the number of the new
+    /// revision.
+    ///
+    /// If the transaction is out of date with respect to the youngest
+    /// revision, the changes made since are merged into the transaction
+    /// first.  If that merge conflicts, the conflicting path is reported
+    /// as [`BackendError::Conflict`] and the transaction stays intact.
+    ///
+    /// Locked paths may only be changed if the filesystem's access
+    /// context (see [`SvnFs::set_access`]) owns the lock and holds its
+    /// token.
+    /// `svn_fs_commit_txn`
+    pub fn commit(mut self) -> Result<RevisionNumber, Error> {
+        Ok(self.vtable.commit(self.fs.access())?)
+    }
+
+    /// Abort this transaction, removing all of its data.
+    ///
+    /// `svn_fs_abort_txn`
+    pub fn abort(self) -> Result<(), Error> {
+        Ok(self.vtable.abort()?)
+    }
+
+    /// Return the value of the property @a name of this transaction.
+    ///
+    /// `svn_fs_txn_prop`
+    pub fn prop(&self, name: &str) -> Result<Option<String>, Error> {
+        Ok(self.vtable.prop(name)?)
+    }
+
+    /// Return all properties of this transaction.
+    ///
+    /// `svn_fs_txn_proplist`
+    pub fn proplist(&self) -> Result<HashMap<String, String>, Error> {
+        Ok(self.vtable.proplist()?)
+    }
+
+    /// Set the property @a name of this transaction to @a value, or delete
+    /// it if @a value is `None`.
+    ///
+    /// `svn_fs_change_txn_prop`
+    pub fn change_prop(&self, name: &str, value: Option<&str>) -> Result<(), Error> {
+        Ok(self.vtable.change_prop(name, value)?)
+    }
 }
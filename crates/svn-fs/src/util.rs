@@ -3,6 +3,8 @@
 
 use std::collections::HashMap;
 
+use svn_types::Mergeinfo;
+
 /// `svn_fs__prop_lists_equal`
 pub fn prop_lists_equal(a: &HashMap<String, String>, b: &HashMap<String, String>) -> bool {
     if a == b {
@@ -34,3 +36,98 @@ pub fn prop_lists_equal(a: &HashMap<String, String>, b: &HashMap<String, String>
 
     true
 }
+
+/// Return a canonicalized version of the filesystem @a path: it starts
+/// with a slash, has no trailing slash and no empty components.
+///
+/// `svn_fs__canonicalize_abspath`
+pub fn canonicalize_abspath(path: &str) -> String {
+    let mut result = String::new();
+    for component in path.split('/').filter(|c| !c.is_empty()) {
+        result.push('/');
+        result.push_str(component);
+    }
+    if result.is_empty() {
+        result.push('/');
+    }
+    result
+}
+
+/// Join the canonical absolute @a base and the relative path
+/// @a component, which may be empty.
+///
+/// `svn_fspath__join`
+pub fn fspath_join(base: &str, component: &str) -> String {
+    if component.is_empty() {
+        base.to_string()
+    } else if base == "/" {
+        format!("/{component}")
+    } else {
+        format!("{base}/{component}")
+    }
+}
+
+/// Return the parent of the canonical, non-root @a path.
+///
+/// `svn_fspath__dirname`
+pub fn fspath_dirname(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(i) => &path[..i],
+    }
+}
+
+/// Return whether the canonical @a path is @a ancestor or below it.
+pub fn fspath_is_ancestor(ancestor: &str, path: &str) -> bool {
+    ancestor == "/"
+        || path == ancestor
+        || path
+            .strip_prefix(ancestor)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Return the path of @a path relative to @a ancestor, empty if they are
+/// the same, or `None` if @a path is not @a ancestor or below it.
+///
+/// `svn_fspath__skip_ancestor`
+pub fn fspath_skip_ancestor<'a>(ancestor: &str, path: &'a str) -> Option<&'a str> {
+    if ancestor == "/" {
+        return Some(path.trim_start_matches('/'));
+    }
+    match path.strip_prefix(ancestor)? {
+        "" => Some(""),
+        rest => rest.strip_prefix('/'),
+    }
+}
+
+/// Return @a mergeinfo with @a rel_path appended to each of its merge
+/// source paths, as used for mergeinfo inherited by a descendant at
+/// @a rel_path below the node holding it.
+///
+/// `svn_fs__append_to_merged_froms`
+pub fn append_to_merged_froms(mergeinfo: &Mergeinfo, rel_path: &str) -> Mergeinfo {
+    mergeinfo
+        .iter()
+        .map(|(path, rangelist)| (fspath_join(path, rel_path), rangelist.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fspath_helpers() {
+        assert_eq!(fspath_dirname("/a"), "/");
+        assert_eq!(fspath_dirname("/a/b"), "/a");
+        assert!(fspath_is_ancestor("/", "/a"));
+        assert!(fspath_is_ancestor("/a", "/a"));
+        assert!(fspath_is_ancestor("/a", "/a/b"));
+        assert!(!fspath_is_ancestor("/a", "/ab"));
+        assert_eq!(fspath_skip_ancestor("/", "/a/b"), Some("a/b"));
+        assert_eq!(fspath_skip_ancestor("/a", "/a"), Some(""));
+        assert_eq!(fspath_skip_ancestor("/a", "/a/b"), Some("b"));
+        assert_eq!(fspath_skip_ancestor("/a", "/ab"), None);
+        assert_eq!(fspath_join("/a", ""), "/a");
+    }
+}
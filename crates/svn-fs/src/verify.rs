@@ -0,0 +1,78 @@
+use std::fmt;
+
+use svn_subr::ChecksumKind;
+use svn_types::RevisionNumber;
+
+/// The kind of problem found by [`crate::SvnFs::verify`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum VerifyErrorKind {
+    /// The contents of a representation do not match the checksum recorded
+    /// for them.
+    #[error("{kind:?} checksum mismatch: expected {expected}, actual {actual}")]
+    ChecksumMismatch {
+        /// the kind of checksum that did not match
+        kind: ChecksumKind,
+        /// the recorded checksum, in hex
+        expected: String,
+        /// the checksum of the actual contents, in hex
+        actual: String,
+    },
+
+    /// The predecessor count of a node-revision is not one more than the
+    /// one of its predecessor.
+    #[error("Predecessor count is {actual} but should be {expected}")]
+    PredecessorCount {
+        /// the count implied by the predecessor
+        expected: i64,
+        /// the count stored in the node-revision
+        actual: i64,
+    },
+
+    /// A directory entry does not match the node-revision it refers to.
+    #[error("{0}")]
+    DirectoryEntry(String),
+
+    /// The l2p / p2l indexes disagree with each other or with the contents
+    /// of the rev / pack file.
+    #[error("{0}")]
+    Index(String),
+
+    /// An entry of the rep-sharing cache does not refer to a valid
+    /// representation.
+    #[error("{0}")]
+    RepCache(String),
+
+    /// Any other failure to read the revision's data.
+    #[error("{0}")]
+    Corrupt(String),
+}
+
+/// A single problem found by [`crate::SvnFs::verify`], located as closely
+/// as possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    /// the revision being verified when the problem was found
+    pub revision: RevisionNumber,
+    /// the path within that revision, if the problem is about a node
+    pub path: Option<String>,
+    /// the offset of the offending item within the rev / pack file, if
+    /// known
+    pub offset: Option<u64>,
+    /// what is wrong
+    pub kind: VerifyErrorKind,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "r{}", self.revision)?;
+        if let Some(path) = &self.path {
+            write!(f, " '{path}'")?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {offset}")?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl std::error::Error for VerifyError {}
@@ -0,0 +1,700 @@
+//! Behaviour every back-end has to share, run against each of them.
+
+use std::io::Read;
+
+use rstest::rstest;
+use svn_fs::FsAccess;
+use svn_fs::FsConfig;
+use svn_fs::FsFsConfig;
+use svn_fs::FsRoot;
+use svn_fs::LockTarget;
+use svn_fs::PathChangeKind;
+use svn_fs::SvnFs;
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_subr::mergeinfo::mergeinfo_to_string;
+use svn_subr::properties::SVN_PROP_MERGEINFO;
+use svn_types::Depth;
+use svn_types::MergeinfoInheritance;
+use svn_types::NodeKind;
+use svn_types::SVN_INVALID_REVNUM;
+
+/// Return the full contents of the file @a path in @a root.
+fn read_file(root: &FsRoot<'_>, path: &str) -> Vec<u8> {
+    let mut contents = Vec::new();
+    root.file_contents(path)
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    contents
+}
+
+/// Commit a transaction based on the youngest revision of @a fs, with
+/// the changes made by @a change.
+fn edit(fs: &SvnFs, change: &dyn Fn(&FsRoot<'_>)) -> Result<i64, svn_fs::Error> {
+    let txn = fs.begin_txn(fs.youngest_rev().unwrap()).unwrap();
+    change(&txn.root().unwrap());
+    txn.commit()
+}
+
+#[rstest]
+#[case::fsfs(FsConfig::default())]
+#[case::memory(FsConfig::Memory)]
+#[cfg_attr(feature = "fsx", case::fsx(FsConfig::Fsx(FsFsConfig::default())))]
+fn test_create_open(#[case] config: FsConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+
+    let fs = SvnFs::create(&db, &config).unwrap();
+    assert!(!fs.uuid.is_nil());
+    assert_eq!(fs.youngest_rev().unwrap(), 0);
+    assert!(fs.revision_proplist(0).unwrap().contains_key("svn:date"));
+    assert!(SvnFs::create(&db, &config).is_err());
+
+    let reopened = SvnFs::open(&db, &config).unwrap();
+    assert_eq!(reopened.uuid, fs.uuid);
+    let root = reopened.revision_root(0).unwrap();
+    assert_eq!(root.check_path("/").unwrap(), NodeKind::Directory);
+    assert!(root.dir_entries("/").unwrap().is_empty());
+    assert!(root.paths_changed().unwrap().is_empty());
+    assert!(reopened.revision_root(1).is_err());
+}
+
+// `revision_props` / `test_set_rev_prop2`
+#[rstest]
+#[case::fsfs(FsConfig::default())]
+#[case::memory(FsConfig::Memory)]
+#[cfg_attr(feature = "fsx", case::fsx(FsConfig::Fsx(FsFsConfig::default())))]
+fn test_change_rev_prop(#[case] config: FsConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let fs = SvnFs::create(&dir.path().join("db"), &config).unwrap();
+
+    fs.change_rev_prop(0, "color", None, Some("red")).unwrap();
+    fs.change_rev_prop(0, "size", None, Some("small")).unwrap();
+    assert_eq!(
+        fs.revision_prop(0, "color").unwrap().as_deref(),
+        Some("red")
+    );
+
+    // The change happens only if the old value matches.
+    assert!(
+        fs.change_rev_prop(0, "color", Some(None), Some("blue"))
+            .is_err()
+    );
+    assert!(
+        fs.change_rev_prop(0, "color", Some(Some("green")), Some("blue"))
+            .is_err()
+    );
+    assert_eq!(
+        fs.revision_prop(0, "color").unwrap().as_deref(),
+        Some("red")
+    );
+    fs.change_rev_prop(0, "color", Some(Some("red")), Some("blue"))
+        .unwrap();
+    fs.change_rev_prop(0, "flavor", Some(None), Some("sweet"))
+        .unwrap();
+
+    // Deleting works the same way.
+    assert!(
+        fs.change_rev_prop(0, "size", Some(Some("large")), None)
+            .is_err()
+    );
+    fs.change_rev_prop(0, "size", Some(Some("small")), None)
+        .unwrap();
+
+    let props = fs.revision_proplist(0).unwrap();
+    assert_eq!(props["color"], "blue");
+    assert_eq!(props["flavor"], "sweet");
+    assert!(!props.contains_key("size"));
+    assert!(props.contains_key("svn:date"));
+    assert!(fs.change_rev_prop(1, "color", None, Some("red")).is_err());
+}
+
+// `test_basic_commit` / `copy_test` / `delete`
+#[rstest]
+#[case::fsfs(FsConfig::default())]
+#[case::memory(FsConfig::Memory)]
+#[case::fsfs_block_read(FsConfig::Fsfs(FsFsConfig { block_read: true, ..Default::default() }))]
+#[cfg_attr(feature = "fsx", case::fsx(FsConfig::Fsx(FsFsConfig::default())))]
+fn test_commit(#[case] config: FsConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let fs = SvnFs::create(&dir.path().join("db"), &config).unwrap();
+
+    let txn = fs.begin_txn(0).unwrap();
+    txn.change_prop("svn:log", Some("r1")).unwrap();
+    {
+        let root = txn.root().unwrap();
+        root.make_dir("/A").unwrap();
+        root.make_dir("/A/B").unwrap();
+        root.make_file("/A/iota").unwrap();
+        root.apply_text(
+            "/A/iota",
+            b"This is iota.\n",
+            Some(&Checksum::compute(ChecksumKind::Md5, b"This is iota.\n")),
+        )
+        .unwrap();
+        root.change_node_prop("/A", "color", Some("red")).unwrap();
+        assert!(root.make_dir("/A").is_err());
+        assert!(root.apply_text("/A", b"", None).is_err());
+        assert!(
+            root.apply_text(
+                "/A/iota",
+                b"other\n",
+                Some(&Checksum::compute(ChecksumKind::Md5, b"wrong\n"))
+            )
+            .is_err()
+        );
+        assert_eq!(read_file(&root, "/A/iota"), b"This is iota.\n");
+        assert_eq!(root.paths_changed().unwrap().len(), 3);
+    }
+    assert_eq!(txn.commit().unwrap(), 1);
+    assert_eq!(fs.youngest_rev().unwrap(), 1);
+    assert!(fs.list_transactions().unwrap().is_empty());
+    let props = fs.revision_proplist(1).unwrap();
+    assert_eq!(props.get("svn:log").map(String::as_str), Some("r1"));
+    assert!(props.contains_key("svn:date"));
+
+    let root = fs.revision_root(1).unwrap();
+    assert_eq!(root.check_path("/A/B").unwrap(), NodeKind::Directory);
+    assert_eq!(read_file(&root, "/A/iota"), b"This is iota.\n");
+    assert_eq!(root.file_length("/A/iota").unwrap(), 14);
+    assert_eq!(
+        root.file_checksum(ChecksumKind::Md5, "/A/iota").unwrap(),
+        Some(Checksum::compute(ChecksumKind::Md5, b"This is iota.\n"))
+    );
+    assert_eq!(root.node_created_rev("/A/iota").unwrap(), 1);
+    assert_eq!(
+        root.node_proplist("/A")
+            .unwrap()
+            .get("color")
+            .map(String::as_str),
+        Some("red")
+    );
+    let changes = root.paths_changed().unwrap();
+    let paths: Vec<_> = changes
+        .iter()
+        .map(|c| (c.path.as_str(), c.change_kind))
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            ("/A", PathChangeKind::Add),
+            ("/A/B", PathChangeKind::Add),
+            ("/A/iota", PathChangeKind::Add),
+        ]
+    );
+
+    // r2: copy, modify below the copy and delete.
+    edit(&fs, &|root| {
+        root.copy(&fs.revision_root(1).unwrap(), "/A", "/C")
+            .unwrap();
+        root.apply_text("/C/iota", b"changed\n", None).unwrap();
+        root.delete("/A/B").unwrap();
+        assert!(root.delete("/").is_err());
+        assert!(root.delete("/A/missing").is_err());
+    })
+    .unwrap();
+
+    let root = fs.revision_root(2).unwrap();
+    assert_eq!(root.check_path("/A/B").unwrap(), NodeKind::None);
+    assert_eq!(root.check_path("/C/B").unwrap(), NodeKind::Directory);
+    assert_eq!(read_file(&root, "/A/iota"), b"This is iota.\n");
+    assert_eq!(read_file(&root, "/C/iota"), b"changed\n");
+    assert_eq!(root.node_created_rev("/C/iota").unwrap(), 2);
+    assert_eq!(root.node_created_rev("/C/B").unwrap(), 1);
+    let copyfrom = root.copied_from("/C").unwrap().unwrap();
+    assert_eq!((copyfrom.rev, copyfrom.path.as_str()), (1, "/A"));
+    assert_eq!(root.copied_from("/C/iota").unwrap(), None);
+    let changes = root.paths_changed().unwrap();
+    let paths: Vec<_> = changes
+        .iter()
+        .map(|c| (c.path.as_str(), c.change_kind))
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            ("/A/B", PathChangeKind::Delete),
+            ("/C", PathChangeKind::Add),
+            ("/C/iota", PathChangeKind::Modify),
+        ]
+    );
+
+    // The old revisions are untouched.
+    let root = fs.revision_root(1).unwrap();
+    assert_eq!(root.check_path("/A/B").unwrap(), NodeKind::Directory);
+    assert_eq!(root.check_path("/C").unwrap(), NodeKind::None);
+}
+
+// `abort_txn` / `list_transactions`
+#[rstest]
+#[case::fsfs(FsConfig::default())]
+#[case::memory(FsConfig::Memory)]
+#[cfg_attr(feature = "fsx", case::fsx(FsConfig::Fsx(FsFsConfig::default())))]
+fn test_txn_abort(#[case] config: FsConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let fs = SvnFs::create(&dir.path().join("db"), &config).unwrap();
+
+    let txn1 = fs.begin_txn(0).unwrap();
+    let txn2 = fs.begin_txn(0).unwrap();
+    txn1.root().unwrap().make_file("/f").unwrap();
+    let name1 = txn1.name().to_string();
+    let name2 = txn2.name().to_string();
+    assert_ne!(name1, name2);
+    let mut names = fs.list_transactions().unwrap();
+    names.sort();
+    assert_eq!(names, [name1.clone(), name2.clone()]);
+
+    let reopened = fs.open_txn(&name1).unwrap();
+    assert_eq!(reopened.base_revision(), 0);
+    assert_eq!(
+        reopened.root().unwrap().check_path("/f").unwrap(),
+        NodeKind::File
+    );
+
+    txn1.abort().unwrap();
+    fs.purge_txn(&name2).unwrap();
+    assert!(fs.list_transactions().unwrap().is_empty());
+    assert!(fs.open_txn(&name1).is_err());
+    fs.purge_txn(&name2).unwrap();
+    assert_eq!(fs.youngest_rev().unwrap(), 0);
+
+    // Revision roots cannot be modified.
+    assert!(fs.revision_root(0).unwrap().make_dir("/x").is_err());
+}
+
+// `merging_commit` / `test_conflict`
+#[rstest]
+#[case::fsfs(FsConfig::default())]
+#[case::memory(FsConfig::Memory)]
+#[cfg_attr(feature = "fsx", case::fsx(FsConfig::Fsx(FsFsConfig::default())))]
+fn test_txn_merge(#[case] config: FsConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let fs = SvnFs::create(&dir.path().join("db"), &config).unwrap();
+    edit(&fs, &|root| {
+        root.make_dir("/A").unwrap();
+        root.make_file("/A/mu").unwrap();
+        root.make_file("/A/nu").unwrap();
+    })
+    .unwrap();
+
+    let txn1 = fs.begin_txn(1).unwrap();
+    let txn2 = fs.begin_txn(1).unwrap();
+    let txn3 = fs.begin_txn(1).unwrap();
+    txn1.root()
+        .unwrap()
+        .apply_text("/A/mu", b"mu\n", None)
+        .unwrap();
+    {
+        let root = txn2.root().unwrap();
+        root.apply_text("/A/nu", b"nu\n", None).unwrap();
+        root.make_file("/new").unwrap();
+    }
+    txn3.root()
+        .unwrap()
+        .apply_text("/A/mu", b"other\n", None)
+        .unwrap();
+
+    // Non-conflicting changes get merged.
+    assert_eq!(txn1.commit().unwrap(), 2);
+    assert_eq!(txn2.commit().unwrap(), 3);
+    let root = fs.revision_root(3).unwrap();
+    assert_eq!(read_file(&root, "/A/mu"), b"mu\n");
+    assert_eq!(read_file(&root, "/A/nu"), b"nu\n");
+    assert_eq!(root.check_path("/new").unwrap(), NodeKind::File);
+
+    // Conflicting ones do not.
+    let name3 = txn3.name().to_string();
+    let err = txn3.commit().unwrap_err();
+    assert!(
+        matches!(
+            err,
+            svn_fs::Error::Backend(svn_fs::backend::BackendError::Conflict(ref path))
+                if path == "/A/mu"
+        ),
+        "{err}"
+    );
+    assert_eq!(fs.youngest_rev().unwrap(), 3);
+    assert_eq!(fs.list_transactions().unwrap(), [name3]);
+}
+
+// `lock_only` / `lock_dir_propchange` / `lock_expiration`
+#[rstest]
+#[case::fsfs(FsConfig::default())]
+#[case::memory(FsConfig::Memory)]
+#[cfg_attr(feature = "fsx", case::fsx(FsConfig::Fsx(FsFsConfig::default())))]
+fn test_lock(#[case] config: FsConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let mut fs = SvnFs::create(&dir.path().join("db"), &config).unwrap();
+    edit(&fs, &|root| {
+        root.make_file("/a").unwrap();
+        root.make_dir("/dir").unwrap();
+        root.make_file("/dir/b").unwrap();
+    })
+    .unwrap();
+    let target = LockTarget::default();
+
+    fs.set_access(Some(FsAccess::new("alice")));
+    let lock = fs.lock("/a", &target, None, false, 0, false).unwrap();
+    let b_lock = fs.lock("/dir/b", &target, None, false, 0, false).unwrap();
+    assert_eq!(fs.get_lock("/a").unwrap(), Some(lock.clone()));
+    assert!(fs.lock("/a", &target, None, false, 0, false).is_err());
+    assert!(fs.lock("/dir", &target, None, false, 0, false).is_err());
+    assert!(fs.lock("/nope", &target, None, false, 0, false).is_err());
+    let paths: Vec<_> = fs
+        .get_locks("/", Depth::Infinity)
+        .unwrap()
+        .into_iter()
+        .map(|lock| lock.path)
+        .collect();
+    assert_eq!(paths, ["/a", "/dir/b"]);
+
+    // Commits need the lock tokens.
+    let modify_a = |root: &FsRoot<'_>| root.apply_text("/a", b"new\n", None).unwrap();
+    let delete_dir = |root: &FsRoot<'_>| root.delete("/dir").unwrap();
+    assert!(edit(&fs, &modify_a).is_err());
+    let mut alice = FsAccess::new("alice");
+    alice.add_lock_token(&lock.token, "/a").unwrap();
+    fs.set_access(Some(alice));
+    assert_eq!(edit(&fs, &modify_a).unwrap(), 2);
+    assert!(edit(&fs, &delete_dir).is_err());
+
+    // Unlocking needs the owner, unless the lock is broken.
+    fs.set_access(Some(FsAccess::new("bob")));
+    assert!(fs.unlock("/a", Some(&lock.token), false).is_err());
+    fs.unlock("/dir/b", None, true).unwrap();
+    assert_eq!(edit(&fs, &delete_dir).unwrap(), 3);
+    fs.set_access(Some(FsAccess::new("alice")));
+    assert!(fs.unlock("/dir/b", Some(&b_lock.token), false).is_err());
+    fs.unlock("/a", Some(&lock.token), false).unwrap();
+    assert_eq!(fs.get_lock("/a").unwrap(), None);
+}
+
+// `svn_fs_verify` / `svn_fs_pack` / `svn_fs_recover` / `svn_fs_hotcopy3`
+#[rstest]
+#[case::fsfs(FsConfig::default())]
+#[case::memory(FsConfig::Memory)]
+#[cfg_attr(feature = "fsx", case::fsx(FsConfig::Fsx(FsFsConfig::default())))]
+fn test_admin(#[case] config: FsConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    let dst = dir.path().join("dst");
+    let fs = SvnFs::create(&src, &config).unwrap();
+    for rev in 1..=3 {
+        edit(&fs, &|root| {
+            let path = format!("/f{rev}");
+            root.make_file(&path).unwrap();
+            root.apply_text(&path, path.as_bytes(), None).unwrap();
+        })
+        .unwrap();
+    }
+
+    let mut verified = Vec::new();
+    let mut notify = |rev| verified.push(rev);
+    let errors = SvnFs::verify(
+        &src,
+        SVN_INVALID_REVNUM,
+        SVN_INVALID_REVNUM,
+        false,
+        Some(&mut notify),
+        None,
+    )
+    .unwrap();
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(verified, [0, 1, 2, 3]);
+    assert!(SvnFs::verify(&src, 2, 4, false, None, None).is_err());
+
+    SvnFs::pack(&src, None, None).unwrap();
+    SvnFs::upgrade(&src, None, None).unwrap();
+    assert_eq!(SvnFs::recover(&src, None).unwrap().youngest, 3);
+
+    // A hotcopy has the same revisions and UUID, and can catch up
+    // incrementally.
+    SvnFs::hotcopy(&src, &dst, false, None, None).unwrap();
+    if matches!(config, FsConfig::Memory) {
+        // Nothing holds the in-memory copy open, so it is gone already.
+        assert!(SvnFs::open(&dst, &config).is_err());
+        return;
+    }
+    assert!(SvnFs::hotcopy(&src, &dst, false, None, None).is_err());
+    edit(&fs, &|root| root.delete("/f1").unwrap()).unwrap();
+    SvnFs::hotcopy(&src, &dst, true, None, None).unwrap();
+    let copy = SvnFs::open(&dst, &config).unwrap();
+    assert_eq!(copy.uuid, fs.uuid);
+    assert_eq!(copy.youngest_rev().unwrap(), 4);
+    let root = copy.revision_root(3).unwrap();
+    assert_eq!(read_file(&root, "/f3"), b"/f3");
+    assert_eq!(
+        copy.revision_root(4).unwrap().check_path("/f1").unwrap(),
+        NodeKind::None
+    );
+
+    SvnFs::delete_fs(&dst).unwrap();
+    assert!(SvnFs::open(&dst, &config).is_err());
+}
+
+/// Return the locations of @a path in @a root, youngest first.
+fn history(root: &FsRoot<'_>, path: &str, cross_copies: bool) -> Vec<(String, i64)> {
+    let mut locations = Vec::new();
+    let mut history = root.node_history(path).unwrap().prev(cross_copies).unwrap();
+    while let Some(location) = history {
+        locations.push(location.location());
+        history = location.prev(cross_copies).unwrap();
+    }
+    locations
+}
+
+// `revision_test` / `closest_copy_test` / `node_origin_rev`
+#[rstest]
+#[case::fsfs(FsConfig::default())]
+#[case::memory(FsConfig::Memory)]
+#[cfg_attr(feature = "fsx", case::fsx(FsConfig::Fsx(FsFsConfig::default())))]
+fn test_history(#[case] config: FsConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let fs = SvnFs::create(&dir.path().join("db"), &config).unwrap();
+    edit(&fs, &|root| {
+        root.make_dir("/trunk").unwrap();
+        root.make_file("/trunk/f").unwrap();
+        root.apply_text("/trunk/f", b"one", None).unwrap();
+    })
+    .unwrap();
+    edit(&fs, &|root| {
+        root.apply_text("/trunk/f", b"two", None).unwrap()
+    })
+    .unwrap();
+    edit(&fs, &|root| {
+        root.copy(&fs.revision_root(2).unwrap(), "/trunk", "/branch")
+            .unwrap();
+    })
+    .unwrap();
+    edit(&fs, &|root| {
+        root.apply_text("/branch/f", b"three", None).unwrap();
+        root.change_node_prop("/branch/f", "p", Some("v")).unwrap();
+    })
+    .unwrap();
+
+    let root = fs.revision_root(4).unwrap();
+    let location = |path: &str, rev| (path.to_string(), rev);
+    assert_eq!(
+        history(&root, "/branch/f", true),
+        [
+            location("/branch/f", 4),
+            location("/branch/f", 3),
+            location("/trunk/f", 2),
+            location("/trunk/f", 1),
+        ]
+    );
+    assert_eq!(
+        history(&root, "/branch/f", false),
+        [location("/branch/f", 4), location("/branch/f", 3)]
+    );
+    assert_eq!(
+        history(&fs.revision_root(2).unwrap(), "/", true),
+        [location("/", 2), location("/", 1), location("/", 0)]
+    );
+    assert!(root.node_history("/missing").is_err());
+    let txn = fs.begin_txn(4).unwrap();
+    assert!(txn.root().unwrap().node_history("/trunk").is_err());
+
+    let (copy_root, copy_path) = root.closest_copy("/branch/f").unwrap().unwrap();
+    assert_eq!((copy_root.revision(), copy_path.as_str()), (3, "/branch"));
+    assert!(root.closest_copy("/trunk/f").unwrap().is_none());
+    assert_eq!(root.node_origin_rev("/branch/f").unwrap(), 1);
+    assert_eq!(root.node_origin_rev("/branch").unwrap(), 1);
+
+    let r2 = fs.revision_root(2).unwrap();
+    let r3 = fs.revision_root(3).unwrap();
+    assert!(!r3.contents_changed("/branch/f", &r2, "/trunk/f").unwrap());
+    assert!(root.contents_changed("/branch/f", &r2, "/trunk/f").unwrap());
+    assert!(!r3.props_changed("/branch/f", &r2, "/trunk/f").unwrap());
+    assert!(root.props_changed("/branch/f", &r3, "/branch/f").unwrap());
+    assert!(root.contents_changed("/branch", &r3, "/branch").is_err());
+}
+
+/// Return the mergeinfo of @a paths in @a root in textual form.
+fn mergeinfo(
+    root: &FsRoot<'_>,
+    paths: &[&str],
+    inherit: MergeinfoInheritance,
+    include_descendants: bool,
+    adjust_inherited_mergeinfo: bool,
+) -> Vec<(String, String)> {
+    root.get_mergeinfo(
+        paths,
+        inherit,
+        include_descendants,
+        adjust_inherited_mergeinfo,
+    )
+    .unwrap()
+    .iter()
+    .map(|(path, mergeinfo)| (path.clone(), mergeinfo_to_string(mergeinfo)))
+    .collect()
+}
+
+// `get_mergeinfo`
+#[rstest]
+#[case::fsfs(FsConfig::default())]
+#[case::memory(FsConfig::Memory)]
+#[cfg_attr(feature = "fsx", case::fsx(FsConfig::Fsx(FsFsConfig::default())))]
+fn test_mergeinfo(#[case] config: FsConfig) {
+    use MergeinfoInheritance::*;
+
+    let dir = tempfile::tempdir().unwrap();
+    let fs = SvnFs::create(&dir.path().join("db"), &config).unwrap();
+    edit(&fs, &|root| {
+        root.make_dir("/trunk").unwrap();
+        root.make_dir("/trunk/a").unwrap();
+        root.make_file("/trunk/a/f").unwrap();
+    })
+    .unwrap();
+    edit(&fs, &|root| {
+        root.copy(&fs.revision_root(1).unwrap(), "/trunk", "/branch")
+            .unwrap();
+        root.change_node_prop("/branch", SVN_PROP_MERGEINFO, Some("/trunk:1\n/other:1*"))
+            .unwrap();
+    })
+    .unwrap();
+    edit(&fs, &|root| {
+        root.change_node_prop("/branch/a/f", SVN_PROP_MERGEINFO, Some("/trunk/a/f:1-2"))
+            .unwrap();
+    })
+    .unwrap();
+
+    let root = fs.revision_root(3).unwrap();
+    let entry = |path: &str, mergeinfo: &str| (path.to_string(), mergeinfo.to_string());
+    assert_eq!(
+        mergeinfo(&root, &["/branch", "/branch/a"], Explicit, false, true),
+        [entry("/branch", "/other:1*\n/trunk:1")]
+    );
+    assert_eq!(
+        mergeinfo(&root, &["/branch/a"], Inherited, false, true),
+        [entry("/branch/a", "/trunk/a:1")]
+    );
+    assert_eq!(
+        mergeinfo(&root, &["/branch/a"], Inherited, false, false),
+        [entry("/branch/a", "/other:1*\n/trunk:1")]
+    );
+    assert_eq!(
+        mergeinfo(&root, &["/branch/a/f"], NearestAncestor, false, true),
+        [entry("/branch/a/f", "/trunk/a/f:1")]
+    );
+    assert_eq!(
+        mergeinfo(&root, &["/"], Explicit, true, true),
+        [
+            entry("/branch", "/other:1*\n/trunk:1"),
+            entry("/branch/a/f", "/trunk/a/f:1-2"),
+        ]
+    );
+    let txn = fs.begin_txn(3).unwrap();
+    assert!(
+        txn.root()
+            .unwrap()
+            .get_mergeinfo(&["/"], Explicit, false, true)
+            .is_err()
+    );
+
+    // The subtrees holding mergeinfo are still found after merging a
+    // transaction with concurrent commits, and after deletions.
+    txn.root()
+        .unwrap()
+        .change_node_prop("/branch/a/f", SVN_PROP_MERGEINFO, None)
+        .unwrap();
+    edit(&fs, &|root| {
+        root.change_node_prop("/trunk", SVN_PROP_MERGEINFO, Some("/branch:2-3"))
+            .unwrap();
+    })
+    .unwrap();
+    assert_eq!(txn.commit().unwrap(), 5);
+    let root = fs.revision_root(5).unwrap();
+    assert_eq!(
+        mergeinfo(&root, &["/"], Explicit, true, true),
+        [
+            entry("/branch", "/other:1*\n/trunk:1"),
+            entry("/trunk", "/branch:2-3"),
+        ]
+    );
+    edit(&fs, &|root| root.delete("/branch").unwrap()).unwrap();
+    let root = fs.revision_root(6).unwrap();
+    assert_eq!(
+        mergeinfo(&root, &["/"], Explicit, true, true),
+        [entry("/trunk", "/branch:2-3")]
+    );
+}
+
+#[test]
+fn test_memory_stays_off_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+
+    let fs = SvnFs::create(&db, &FsConfig::Memory).unwrap();
+    edit(&fs, &|root| root.make_file("/f").unwrap()).unwrap();
+    assert!(!db.exists());
+
+    // Every handle sees the same filesystem until it gets deleted.
+    let other = SvnFs::open(&db, &FsConfig::Memory).unwrap();
+    assert_eq!(other.youngest_rev().unwrap(), 1);
+    SvnFs::delete_fs(&db).unwrap();
+    assert!(SvnFs::open(&db, &FsConfig::Memory).is_err());
+    assert!(SvnFs::create(&db, &FsConfig::Memory).is_ok());
+}
+
+#[test]
+fn test_memory_dropped_with_last_handle() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+
+    let fs = SvnFs::create(&db, &FsConfig::Memory).unwrap();
+    edit(&fs, &|root| root.make_file("/f").unwrap()).unwrap();
+    let other = SvnFs::open(&db, &FsConfig::Memory).unwrap();
+    drop(fs);
+    assert_eq!(
+        SvnFs::open(&db, &FsConfig::Memory)
+            .unwrap()
+            .youngest_rev()
+            .unwrap(),
+        1
+    );
+
+    // Once nothing has it open, the path is free for a new filesystem.
+    drop(other);
+    assert!(SvnFs::open(&db, &FsConfig::Memory).is_err());
+    let fs = SvnFs::create(&db, &FsConfig::Memory).unwrap();
+    assert_eq!(fs.youngest_rev().unwrap(), 0);
+}
+
+#[cfg(feature = "fsx")]
+#[test]
+fn test_fsx_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+
+    let config = FsConfig::Fsx(FsFsConfig::default());
+    let fs = SvnFs::create(&db, &config).unwrap();
+    edit(&fs, &|root| {
+        root.make_dir("/trunk").unwrap();
+        root.make_file("/trunk/f").unwrap();
+        root.apply_text("/trunk/f", b"contents", None).unwrap();
+    })
+    .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(db.join("format")).unwrap(),
+        "2\nlayout sharded 1000\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(db.join("fs-type")).unwrap(),
+        "fsx\n"
+    );
+    let rev = std::fs::read(db.join("revs/0/1")).unwrap();
+    assert!(!rev.windows(6).any(|w| w == b"cpath:"));
+
+    // The fs-type file picks the back-end, whatever the config says.
+    let fs = SvnFs::open(&db, &FsConfig::default()).unwrap();
+    let root = fs.revision_root(1).unwrap();
+    assert_eq!(read_file(&root, "/trunk/f"), b"contents");
+    assert_eq!(root.paths_changed().unwrap().len(), 2);
+
+    let linear = FsFsConfig {
+        shard_size: "0".to_string(),
+        ..FsFsConfig::default()
+    };
+    assert!(SvnFs::create(&dir.path().join("linear"), &FsConfig::Fsx(linear)).is_err());
+}
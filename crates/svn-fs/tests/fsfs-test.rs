@@ -0,0 +1,1810 @@
+use std::io::Read;
+use std::path::Path;
+
+use rstest::rstest;
+use svn_delta::Action;
+use svn_delta::TxdeltaOp;
+use svn_delta::TxdeltaWindow;
+use svn_fs::FsAccess;
+use svn_fs::FsConfig;
+use svn_fs::FsFsConfig;
+use svn_fs::LockTarget;
+use svn_fs::PackNotifyAction;
+use svn_fs::PathChangeKind;
+use svn_fs::SvnFs;
+use svn_fs::UpgradeNotifyAction;
+use svn_fs::VerifyErrorKind;
+use svn_subr::Checksum;
+use svn_subr::ChecksumKind;
+use svn_types::Depth;
+use svn_types::NodeKind;
+use svn_types::SVN_INVALID_REVNUM;
+
+fn fs_config(shard_size: &str, log_addressing: bool) -> FsConfig {
+    FsConfig::Fsfs(FsFsConfig {
+        shard_size: shard_size.to_string(),
+        log_addressing,
+        ..Default::default()
+    })
+}
+
+/// Check the footer of the format 7+ rev file at @a path and return the
+/// size of the revision data in front of the indexes.
+fn check_footer(path: &Path) -> usize {
+    let content = fs_err::read(path).unwrap();
+    let footer_len = *content.last().unwrap() as usize;
+    let footer_start = content.len() - 1 - footer_len;
+    let footer = std::str::from_utf8(&content[footer_start..content.len() - 1]).unwrap();
+
+    let parts: Vec<&str> = footer.split(' ').collect();
+    assert_eq!(parts.len(), 4, "{footer}");
+    let l2p_offset: usize = parts[0].parse().unwrap();
+    let p2l_offset: usize = parts[2].parse().unwrap();
+
+    let l2p = &content[l2p_offset..p2l_offset];
+    let p2l = &content[p2l_offset..footer_start];
+    assert!(l2p.starts_with(b"L2P-INDEX\n"));
+    assert!(p2l.starts_with(b"P2L-INDEX\n"));
+    assert_eq!(Checksum::compute(ChecksumKind::Md5, l2p).to_hex(), parts[1]);
+    assert_eq!(Checksum::compute(ChecksumKind::Md5, p2l).to_hex(), parts[3]);
+
+    l2p_offset
+}
+
+// `test_create_fs` / `create_packed_filesystem`
+#[rstest]
+#[case(
+    "1000",
+    true,
+    "8\nlayout sharded 1000\naddressing logical\n",
+    "revs/0/0"
+)]
+#[case("4", false, "8\nlayout sharded 4\naddressing physical\n", "revs/0/0")]
+#[case("0", true, "8\nlayout linear\naddressing logical\n", "revs/0")]
+#[case("0", false, "8\nlayout linear\naddressing physical\n", "revs/0")]
+fn test_create_fs(
+    #[case] shard_size: &str,
+    #[case] log_addressing: bool,
+    #[case] expected_format: &str,
+    #[case] rev_zero: &str,
+) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let config = fs_config(shard_size, log_addressing);
+
+    let fs = SvnFs::create(&db, &config).unwrap();
+    assert_eq!(fs.youngest_rev().unwrap(), 0);
+    assert!(!fs.uuid.is_nil());
+
+    assert_eq!(
+        fs_err::read_to_string(db.join("fs-type")).unwrap(),
+        "fsfs\n"
+    );
+    assert_eq!(
+        fs_err::read_to_string(db.join("format")).unwrap(),
+        expected_format
+    );
+    assert_eq!(fs_err::read_to_string(db.join("current")).unwrap(), "0\n");
+    assert_eq!(
+        fs_err::read_to_string(db.join("txn-current")).unwrap(),
+        "0\n"
+    );
+    assert_eq!(
+        fs_err::read_to_string(db.join("min-unpacked-rev")).unwrap(),
+        "0\n"
+    );
+    for file in ["write-lock", "txn-current-lock", "fsfs.conf"] {
+        assert!(db.join(file).is_file(), "{file}");
+    }
+    for dir in ["transactions", "txn-protorevs"] {
+        assert!(db.join(dir).is_dir(), "{dir}");
+    }
+
+    // format 7+ uuid files carry an instance ID as well.
+    let uuid = fs_err::read_to_string(db.join("uuid")).unwrap();
+    let lines: Vec<&str> = uuid.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], fs.uuid.to_string());
+    assert_ne!(lines[0], lines[1]);
+
+    // r0 always has a date.
+    let revprops = fs_err::read_to_string(db.join(rev_zero.replace("revs", "revprops"))).unwrap();
+    assert!(revprops.starts_with("K 8\nsvn:date\nV 27\n"), "{revprops}");
+    assert!(revprops.ends_with("Z\nEND\n"), "{revprops}");
+
+    let rev_file = db.join(rev_zero);
+    if log_addressing {
+        assert_eq!(check_footer(&rev_file), 107);
+    } else {
+        assert_eq!(
+            fs_err::read_to_string(&rev_file).unwrap(),
+            "PLAIN\nEND\nENDREP\nid: 0.0.r0/17\ntype: dir\ncount: 0\n\
+             text: 0 0 4 4 2d2977d1c96f487abe4a1e202dd03b4e\ncpath: /\n\n\n17 107\n"
+        );
+    }
+
+    let fs2 = SvnFs::open(&db, &config).unwrap();
+    assert_eq!(fs2.uuid, fs.uuid);
+    assert_eq!(fs2.youngest_rev().unwrap(), 0);
+}
+
+#[test]
+fn test_create_fs_existing_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    SvnFs::create(&db, &FsConfig::default()).unwrap();
+
+    // Never clobber an existing repository.
+    assert!(SvnFs::create(&db, &FsConfig::default()).is_err());
+}
+
+/// Run the stock Subversion tool @a program with @a args and return its
+/// output.
+fn run_svn_tool(program: &str, args: &[&std::ffi::OsStr]) -> String {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .unwrap_or_else(|e| panic!("{program}: {e}"));
+    assert!(
+        output.status.success(),
+        "{program} {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// Cross-check our repositories with stock `svnadmin` and `svnlook`.  Run
+// with `cargo test -p svn-fs --test fsfs-test -- --ignored svnadmin`.
+#[rstest]
+#[case::sharded_logical("4", true)]
+#[case::sharded_physical("4", false)]
+#[case::linear_logical("0", true)]
+#[case::linear_physical("0", false)]
+#[ignore = "needs svnadmin and svnlook from Subversion on PATH"]
+fn test_svnadmin_interop(#[case] shard_size: &str, #[case] log_addressing: bool) {
+    // The tools want a repository around the filesystem.
+    let dir = tempfile::tempdir().unwrap();
+    let repos = dir.path().join("repos");
+    fs_err::create_dir_all(repos.join("hooks")).unwrap();
+    fs_err::create_dir_all(repos.join("locks")).unwrap();
+    fs_err::write(repos.join("format"), "5\n").unwrap();
+    let db = repos.join("db");
+    let fs = SvnFs::create(&db, &fs_config(shard_size, log_addressing)).unwrap();
+
+    // r1 - r6: directories, properties, copies and deltified texts.
+    for rev in 1..=6 {
+        let txn = fs.begin_txn(rev - 1).unwrap();
+        txn.change_prop("svn:log", Some(&format!("log of r{rev}")))
+            .unwrap();
+        {
+            let root = txn.root().unwrap();
+            if rev == 1 {
+                root.make_dir("/A").unwrap();
+                root.make_file("/A/f").unwrap();
+                root.change_node_prop("/A", "color", Some("red")).unwrap();
+            }
+            if rev == 4 {
+                root.copy(&fs.revision_root(3).unwrap(), "/A", "/B")
+                    .unwrap();
+            }
+            let text = format!("{}line of r{rev}\n", "common text\n".repeat(100));
+            root.apply_text("/A/f", text.as_bytes(), None).unwrap();
+        }
+        assert_eq!(txn.commit().unwrap(), rev);
+    }
+    if shard_size != "0" {
+        SvnFs::pack(&db, None, None).unwrap();
+    }
+
+    let repos = repos.as_os_str();
+    run_svn_tool("svnadmin", &["verify".as_ref(), "--quiet".as_ref(), repos]);
+    let youngest = run_svn_tool("svnlook", &["youngest".as_ref(), repos]);
+    assert_eq!(youngest, "6\n");
+
+    for rev in ["1", "4", "6"] {
+        let root = fs.revision_root(rev.parse().unwrap()).unwrap();
+        let args = [
+            "cat".as_ref(),
+            "-r".as_ref(),
+            rev.as_ref(),
+            repos,
+            "A/f".as_ref(),
+        ];
+        let text = run_svn_tool("svnlook", &args);
+        assert_eq!(text.as_bytes(), read_file(&root, "/A/f"));
+
+        let args = ["log".as_ref(), "-r".as_ref(), rev.as_ref(), repos];
+        let log = run_svn_tool("svnlook", &args);
+        assert_eq!(log, format!("log of r{rev}\n"));
+    }
+    let args = ["propget".as_ref(), "-r".as_ref(), "5".as_ref(), repos];
+    let color = run_svn_tool(
+        "svnlook",
+        &[&args[..], &["color".as_ref(), "B".as_ref()]].concat(),
+    );
+    assert_eq!(color.trim_end(), "red");
+}
+
+#[rstest]
+#[case::logical(true)]
+#[case::physical(false)]
+fn test_revision_root_zero(#[case] log_addressing: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let fs = SvnFs::create(&db, &fs_config("1000", log_addressing)).unwrap();
+
+    let root = fs.revision_root(0).unwrap();
+    assert!(root.is_revision_root());
+    assert_eq!(root.revision(), 0);
+    assert_eq!(root.check_path("/").unwrap(), NodeKind::Directory);
+    assert_eq!(root.check_path("").unwrap(), NodeKind::Directory);
+    assert_eq!(root.check_path("/missing").unwrap(), NodeKind::None);
+    assert!(root.dir_entries("/").unwrap().is_empty());
+    assert!(root.node_proplist("/").unwrap().is_empty());
+    assert_eq!(root.node_created_rev("/").unwrap(), 0);
+    assert_eq!(root.copied_from("/").unwrap(), None);
+    assert!(root.paths_changed().unwrap().is_empty());
+    assert!(root.file_contents("/").is_err());
+    assert!(root.file_length("/missing").is_err());
+
+    assert!(fs.revision_root(1).is_err());
+}
+
+/// Append @a item to @a rev and return its offset.
+fn push_item(rev: &mut Vec<u8>, item: &[u8]) -> usize {
+    let offset = rev.len();
+    rev.extend_from_slice(item);
+    offset
+}
+
+/// Hand-craft a physically addressed r1 that adds /a as a delta against
+/// /b and check that the revision root reads it back.
+#[test]
+fn test_revision_root_delta() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    SvnFs::create(&db, &fs_config("1000", false)).unwrap();
+
+    let md5 = |data: &[u8]| Checksum::compute(ChecksumKind::Md5, data).to_hex();
+    let mut rev = Vec::new();
+
+    // /b: a PLAIN rep.
+    let base = b"hello world\n";
+    let b_rep = push_item(&mut rev, b"PLAIN\n");
+    rev.extend_from_slice(base);
+    rev.extend_from_slice(b"ENDREP\n");
+
+    // /a: "hello there\n" as a delta against /b.
+    let text = b"hello there\n";
+    let mut delta = svn_delta::svndiff::header(0).to_vec();
+    svn_delta::svndiff::write_window(
+        &mut delta,
+        &TxdeltaWindow {
+            sview_offset: 0,
+            sview_length: 6,
+            tview_length: 12,
+            num_ops: 2,
+            src_ops: 1,
+            ops: vec![
+                TxdeltaOp {
+                    action_code: Action::Source,
+                    offset: 0,
+                    length: 6,
+                },
+                TxdeltaOp {
+                    action_code: Action::New,
+                    offset: 0,
+                    length: 6,
+                },
+            ],
+            new_data: b"there\n".to_vec(),
+        },
+    );
+    let a_rep = push_item(
+        &mut rev,
+        format!("DELTA 1 {b_rep} {}\n", base.len()).as_bytes(),
+    );
+    rev.extend_from_slice(&delta);
+    rev.extend_from_slice(b"ENDREP\n");
+
+    // The props of /a.
+    let props = b"K 1\nx\nV 1\ny\nEND\n";
+    let props_rep = push_item(&mut rev, b"PLAIN\n");
+    rev.extend_from_slice(props);
+    rev.extend_from_slice(b"ENDREP\n");
+
+    let b_node = rev.len();
+    push_item(
+        &mut rev,
+        format!(
+            "id: 2-1.0.r1/{b_node}\ntype: file\ncount: 0\ntext: 1 {b_rep} {} {} {}\n\
+             cpath: /b\n\n",
+            base.len(),
+            base.len(),
+            md5(base)
+        )
+        .as_bytes(),
+    );
+    let a_node = rev.len();
+    push_item(
+        &mut rev,
+        format!(
+            "id: 1-1.0.r1/{a_node}\ntype: file\ncount: 0\ntext: 1 {a_rep} {} {} {}\n\
+             props: 1 {props_rep} {} {} {}\ncpath: /a\n\n",
+            delta.len(),
+            text.len(),
+            md5(text),
+            props.len(),
+            props.len(),
+            md5(props)
+        )
+        .as_bytes(),
+    );
+
+    // The root directory.
+    let a_entry = format!("file 1-1.0.r1/{a_node}");
+    let b_entry = format!("file 2-1.0.r1/{b_node}");
+    let dir_listing = format!(
+        "K 1\na\nV {}\n{a_entry}\nK 1\nb\nV {}\n{b_entry}\nEND\n",
+        a_entry.len(),
+        b_entry.len()
+    );
+    let dir_rep = push_item(&mut rev, b"PLAIN\n");
+    rev.extend_from_slice(dir_listing.as_bytes());
+    rev.extend_from_slice(b"ENDREP\n");
+
+    let root_node = rev.len();
+    push_item(
+        &mut rev,
+        format!(
+            "id: 0.0.r1/{root_node}\ntype: dir\npred: 0.0.r0/17\ncount: 1\n\
+             text: 1 {dir_rep} {} {} {}\ncpath: /\n\n",
+            dir_listing.len(),
+            dir_listing.len(),
+            md5(dir_listing.as_bytes())
+        )
+        .as_bytes(),
+    );
+
+    let changes = push_item(
+        &mut rev,
+        format!(
+            "1-1.0.r1/{a_node} add-file true true false /a\n\n\
+             2-1.0.r1/{b_node} add-file true false false /b\n1 /c\n\n"
+        )
+        .as_bytes(),
+    );
+    rev.extend_from_slice(format!("{root_node} {changes}\n").as_bytes());
+
+    fs_err::write(db.join("revs/0/1"), &rev).unwrap();
+    fs_err::write(db.join("current"), "1\n").unwrap();
+
+    let fs = SvnFs::open(&db, &fs_config("1000", false)).unwrap();
+    let root = fs.revision_root(1).unwrap();
+
+    let entries = root.dir_entries("/").unwrap();
+    assert_eq!(entries.keys().collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(entries["a"].kind, NodeKind::File);
+    assert_eq!(root.check_path("/a").unwrap(), NodeKind::File);
+    assert_eq!(root.check_path("/a/x").unwrap(), NodeKind::None);
+
+    let mut contents = Vec::new();
+    root.file_contents("/a")
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    assert_eq!(contents, text);
+    assert_eq!(root.file_length("/a").unwrap(), text.len() as u64);
+    assert_eq!(
+        root.file_checksum(ChecksumKind::Md5, "/a")
+            .unwrap()
+            .unwrap()
+            .to_hex(),
+        md5(text)
+    );
+
+    let mut contents = Vec::new();
+    root.file_contents("b")
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    assert_eq!(contents, base);
+
+    let props = root.node_proplist("/a").unwrap();
+    assert_eq!(props.get("x").map(String::as_str), Some("y"));
+    assert_eq!(root.node_created_rev("/a").unwrap(), 1);
+
+    let changes = root.paths_changed().unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].path, "/a");
+    assert_eq!(changes[0].change_kind, PathChangeKind::Add);
+    assert!(changes[0].text_mod && changes[0].prop_mod);
+    assert_eq!(changes[1].copyfrom.as_ref().unwrap().path, "/c");
+}
+
+/// Return the full contents of the file @a path in @a root.
+fn read_file(root: &svn_fs::FsRoot<'_>, path: &str) -> Vec<u8> {
+    let mut contents = Vec::new();
+    root.file_contents(path)
+        .unwrap()
+        .read_to_end(&mut contents)
+        .unwrap();
+    contents
+}
+
+// `test_basic_commit` / `copy_test` / `delete`
+#[rstest]
+#[case::logical(true)]
+#[case::physical(false)]
+fn test_txn_commit(#[case] log_addressing: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let fs = SvnFs::create(&db, &fs_config("1000", log_addressing)).unwrap();
+
+    // r1: a small tree with a file, a property and a directory.
+    let txn = fs.begin_txn(0).unwrap();
+    assert_eq!(fs.list_transactions().unwrap(), [txn.name()]);
+    assert!(txn.prop("svn:date").unwrap().is_some());
+    txn.change_prop("svn:log", Some("r1")).unwrap();
+    {
+        let root = txn.root().unwrap();
+        assert!(root.is_txn_root());
+        root.make_dir("/A").unwrap();
+        root.make_dir("/A/B").unwrap();
+        root.make_file("/A/iota").unwrap();
+        root.apply_text(
+            "/A/iota",
+            b"This is iota.\n",
+            Some(&Checksum::compute(ChecksumKind::Md5, b"This is iota.\n")),
+        )
+        .unwrap();
+        root.change_node_prop("/A", "color", Some("red")).unwrap();
+        assert!(root.make_dir("/A").is_err());
+        assert!(root.apply_text("/A", b"", None).is_err());
+
+        // The changes are visible in the transaction already.
+        assert_eq!(read_file(&root, "/A/iota"), b"This is iota.\n");
+        assert_eq!(root.dir_entries("/A").unwrap().len(), 2);
+        assert_eq!(root.paths_changed().unwrap().len(), 3);
+    }
+    assert_eq!(txn.commit().unwrap(), 1);
+    assert_eq!(fs.youngest_rev().unwrap(), 1);
+    assert!(fs.list_transactions().unwrap().is_empty());
+    if log_addressing {
+        check_footer(&db.join("revs/0/1"));
+    }
+
+    let root = fs.revision_root(1).unwrap();
+    assert_eq!(root.check_path("/A/B").unwrap(), NodeKind::Directory);
+    assert_eq!(read_file(&root, "/A/iota"), b"This is iota.\n");
+    assert_eq!(root.file_length("/A/iota").unwrap(), 14);
+    assert_eq!(root.node_created_rev("/A/iota").unwrap(), 1);
+    assert_eq!(
+        root.node_proplist("/A")
+            .unwrap()
+            .get("color")
+            .map(String::as_str),
+        Some("red")
+    );
+    let changes = root.paths_changed().unwrap();
+    let paths: Vec<_> = changes
+        .iter()
+        .map(|c| (c.path.as_str(), c.change_kind))
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            ("/A", PathChangeKind::Add),
+            ("/A/B", PathChangeKind::Add),
+            ("/A/iota", PathChangeKind::Add),
+        ]
+    );
+    assert!(changes[0].prop_mod);
+
+    // r2: copy, modify below the copy and delete.
+    let txn = fs.begin_txn(1).unwrap();
+    {
+        let root = txn.root().unwrap();
+        root.copy(&fs.revision_root(1).unwrap(), "/A", "/C")
+            .unwrap();
+        root.apply_text("/C/iota", b"changed\n", None).unwrap();
+        root.delete("/A/B").unwrap();
+        assert!(root.delete("/").is_err());
+        assert!(root.delete("/A/missing").is_err());
+    }
+    assert_eq!(txn.commit().unwrap(), 2);
+
+    let root = fs.revision_root(2).unwrap();
+    assert_eq!(root.check_path("/A/B").unwrap(), NodeKind::None);
+    assert_eq!(root.check_path("/C/B").unwrap(), NodeKind::Directory);
+    assert_eq!(read_file(&root, "/A/iota"), b"This is iota.\n");
+    assert_eq!(read_file(&root, "/C/iota"), b"changed\n");
+    let copyfrom = root.copied_from("/C").unwrap().unwrap();
+    assert_eq!((copyfrom.rev, copyfrom.path.as_str()), (1, "/A"));
+    assert_eq!(root.copied_from("/C/iota").unwrap(), None);
+    assert_eq!(
+        root.node_proplist("/C")
+            .unwrap()
+            .get("color")
+            .map(String::as_str),
+        Some("red")
+    );
+
+    // The old revisions are untouched.
+    let root = fs.revision_root(1).unwrap();
+    assert_eq!(root.check_path("/A/B").unwrap(), NodeKind::Directory);
+    assert_eq!(root.check_path("/C").unwrap(), NodeKind::None);
+}
+
+// `abort_txn` / `list_transactions`
+#[test]
+fn test_txn_abort() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let fs = SvnFs::create(&db, &FsConfig::default()).unwrap();
+
+    let txn1 = fs.begin_txn(0).unwrap();
+    let txn2 = fs.begin_txn(0).unwrap();
+    txn1.root().unwrap().make_file("/f").unwrap();
+    let name1 = txn1.name().to_string();
+    let name2 = txn2.name().to_string();
+    assert_ne!(name1, name2);
+
+    let mut names = fs.list_transactions().unwrap();
+    names.sort();
+    assert_eq!(names, [name1.clone(), name2.clone()]);
+
+    // Reopen a transaction and see its changes.
+    let reopened = fs.open_txn(&name1).unwrap();
+    assert_eq!(reopened.base_revision(), 0);
+    assert_eq!(
+        reopened.root().unwrap().check_path("/f").unwrap(),
+        NodeKind::File
+    );
+
+    txn1.abort().unwrap();
+    fs.purge_txn(&name2).unwrap();
+    assert!(fs.list_transactions().unwrap().is_empty());
+    assert!(fs.open_txn(&name1).is_err());
+    assert!(
+        reopened
+            .root()
+            .is_ok_and(|root| root.check_path("/f").is_err())
+    );
+    assert_eq!(fs.youngest_rev().unwrap(), 0);
+
+    // Revision roots cannot be modified.
+    assert!(fs.revision_root(0).unwrap().make_dir("/x").is_err());
+}
+
+// `merging_commit` / `test_conflict`
+#[rstest]
+#[case::logical(true)]
+#[case::physical(false)]
+fn test_txn_merge(#[case] log_addressing: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let fs = SvnFs::create(&db, &fs_config("1000", log_addressing)).unwrap();
+
+    let txn = fs.begin_txn(0).unwrap();
+    {
+        let root = txn.root().unwrap();
+        root.make_dir("/A").unwrap();
+        root.make_file("/A/mu").unwrap();
+        root.make_file("/A/nu").unwrap();
+    }
+    assert_eq!(txn.commit().unwrap(), 1);
+
+    // Three transactions based on r1.
+    let txn1 = fs.begin_txn(1).unwrap();
+    let txn2 = fs.begin_txn(1).unwrap();
+    let txn3 = fs.begin_txn(1).unwrap();
+    txn1.root()
+        .unwrap()
+        .apply_text("/A/mu", b"mu\n", None)
+        .unwrap();
+    {
+        let root = txn2.root().unwrap();
+        root.apply_text("/A/nu", b"nu\n", None).unwrap();
+        root.make_file("/new").unwrap();
+    }
+    txn3.root()
+        .unwrap()
+        .apply_text("/A/mu", b"other\n", None)
+        .unwrap();
+
+    // Non-conflicting changes get merged.
+    assert_eq!(txn1.commit().unwrap(), 2);
+    assert_eq!(txn2.commit().unwrap(), 3);
+    let root = fs.revision_root(3).unwrap();
+    assert_eq!(read_file(&root, "/A/mu"), b"mu\n");
+    assert_eq!(read_file(&root, "/A/nu"), b"nu\n");
+    assert_eq!(root.check_path("/new").unwrap(), NodeKind::File);
+
+    // Conflicting ones do not.
+    let name3 = txn3.name().to_string();
+    let err = txn3.commit().unwrap_err();
+    assert!(
+        matches!(
+            err,
+            svn_fs::Error::Backend(svn_fs::backend::BackendError::Conflict(ref path))
+                if path == "/A/mu"
+        ),
+        "{err}"
+    );
+    assert_eq!(fs.youngest_rev().unwrap(), 3);
+    assert_eq!(fs.list_transactions().unwrap(), [name3]);
+}
+
+/// Return how often @a needle occurs in the rev file of @a rev.
+fn count_in_rev_file(db: &Path, rev: &str, needle: &[u8]) -> usize {
+    let content = fs_err::read(db.join("revs/0").join(rev)).unwrap();
+    content
+        .windows(needle.len())
+        .filter(|window| *window == needle)
+        .count()
+}
+
+// `rep_sharing_effectiveness` / `test_rep_sharing_strict_content_check`
+#[rstest]
+#[case::logical(true)]
+#[case::physical(false)]
+fn test_rep_sharing(#[case] log_addressing: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let fs = SvnFs::create(&db, &fs_config("1000", log_addressing)).unwrap();
+    let vendored = b"The same vendored file on every branch.\n";
+    let fresh = b"Added twice in one revision.\n";
+
+    let txn = fs.begin_txn(0).unwrap();
+    {
+        let root = txn.root().unwrap();
+        root.make_file("/a").unwrap();
+        root.apply_text("/a", vendored, None).unwrap();
+        root.change_node_prop("/a", "p", Some("v")).unwrap();
+    }
+    assert_eq!(txn.commit().unwrap(), 1);
+    assert!(db.join("rep-cache.db").is_file());
+
+    // Known texts get shared with earlier revisions and within the
+    // transaction.
+    let txn = fs.begin_txn(1).unwrap();
+    {
+        let root = txn.root().unwrap();
+        for path in ["/b", "/c", "/x", "/y"] {
+            root.make_file(path).unwrap();
+        }
+        root.apply_text("/b", vendored, None).unwrap();
+        root.apply_text("/c", vendored, None).unwrap();
+        root.apply_text("/x", fresh, None).unwrap();
+        root.apply_text("/y", fresh, None).unwrap();
+        root.change_node_prop("/b", "p", Some("v")).unwrap();
+    }
+    assert_eq!(txn.commit().unwrap(), 2);
+    assert_eq!(count_in_rev_file(&db, "1", vendored), 1);
+    assert_eq!(count_in_rev_file(&db, "2", vendored), 0);
+    assert_eq!(count_in_rev_file(&db, "2", fresh), 1);
+
+    let root = fs.revision_root(2).unwrap();
+    for path in ["/a", "/b", "/c"] {
+        assert_eq!(read_file(&root, path), vendored);
+        assert_eq!(
+            root.file_checksum(ChecksumKind::Md5, path).unwrap(),
+            Some(Checksum::compute(ChecksumKind::Md5, vendored))
+        );
+    }
+    assert_eq!(read_file(&root, "/y"), fresh);
+    assert_eq!(
+        root.node_proplist("/b")
+            .unwrap()
+            .get("p")
+            .map(String::as_str),
+        Some("v")
+    );
+    fs.verify_rep_cache(0, 2).unwrap();
+
+    // Rebuild the cache from the rev files.
+    fs_err::remove_file(db.join("rep-cache.db")).unwrap();
+    fs.build_rep_cache(SVN_INVALID_REVNUM, SVN_INVALID_REVNUM)
+        .unwrap();
+    let conn = rusqlite::Connection::open(db.join("rep-cache.db")).unwrap();
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM rep_cache", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 3);
+    fs.verify_rep_cache(0, 2).unwrap();
+
+    // Entries pointing nowhere are caught.
+    conn.execute("UPDATE rep_cache SET offset = 99999 WHERE revision = 2", [])
+        .unwrap();
+    assert!(fs.verify_rep_cache(0, 2).is_err());
+    assert!(fs.build_rep_cache(3, 4).is_err());
+}
+
+// `delta_chain_with_plain` / `compare_contents`
+#[rstest]
+#[case::lz4(true, "lz4")]
+#[case::zlib(false, "zlib")]
+#[case::none(true, "none")]
+fn test_deltification(#[case] log_addressing: bool, #[case] compression: &str) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    SvnFs::create(&db, &fs_config("1000", log_addressing)).unwrap();
+
+    // Use pure skip-deltas for file contents, but no deltas at all for
+    // directories.
+    fs_err::write(
+        db.join("fsfs.conf"),
+        format!(
+            "[deltification]\n\
+             enable-dir-deltification = false\n\
+             enable-props-deltification = true\n\
+             max-linear-deltification = 1\n\
+             compression = {compression}\n"
+        ),
+    )
+    .unwrap();
+    let fs = SvnFs::open(&db, &fs_config("1000", log_addressing)).unwrap();
+
+    // Every revision changes a line of a text spanning several delta
+    // windows and the property of the file.
+    let mut lines: Vec<String> = (0..30_000).map(|i| format!("line {i}\n")).collect();
+    let mut texts = Vec::new();
+    for rev in 1..=9 {
+        if rev > 1 {
+            lines[rev * 2_500] = format!("changed in r{rev}\n");
+        }
+        let text = lines.concat();
+
+        let txn = fs.begin_txn(rev as i64 - 1).unwrap();
+        {
+            let root = txn.root().unwrap();
+            if rev == 1 {
+                root.make_dir("/d").unwrap();
+                root.make_file("/d/f").unwrap();
+            }
+            root.apply_text("/d/f", text.as_bytes(), None).unwrap();
+            root.change_node_prop("/d/f", "rev", Some(&rev.to_string()))
+                .unwrap();
+        }
+        assert_eq!(txn.commit().unwrap(), rev as i64);
+        texts.push(text);
+    }
+
+    // Skip-deltas: predecessor count N uses N with its rightmost bit
+    // cleared as base, i.e. the file in r(N & (N - 1)) + 1.
+    for (rev, base) in [("2", 1), ("4", 3), ("5", 1), ("8", 7), ("9", 1)] {
+        let header = format!("DELTA {base} ");
+        assert!(
+            count_in_rev_file(&db, rev, header.as_bytes()) >= 2,
+            "r{rev}"
+        );
+    }
+    assert_eq!(count_in_rev_file(&db, "1", b"DELTA\n"), 2);
+    // The text only shows up verbatim if compression is disabled.
+    assert_eq!(
+        count_in_rev_file(&db, "1", &texts[0].as_bytes()[..100]),
+        usize::from(compression == "none")
+    );
+
+    for (i, text) in texts.iter().enumerate() {
+        let root = fs.revision_root(i as i64 + 1).unwrap();
+        assert_eq!(read_file(&root, "/d/f"), text.as_bytes());
+        assert_eq!(
+            root.node_proplist("/d/f")
+                .unwrap()
+                .get("rev")
+                .map(String::as_str),
+            Some((i + 1).to_string().as_str())
+        );
+    }
+
+    // Without deltification walks, everything is a self-delta.
+    fs_err::write(
+        db.join("fsfs.conf"),
+        "[deltification]\nmax-deltification-walk = 0\n",
+    )
+    .unwrap();
+    let fs = SvnFs::open(&db, &fs_config("1000", log_addressing)).unwrap();
+    let txn = fs.begin_txn(9).unwrap();
+    txn.root()
+        .unwrap()
+        .apply_text("/d/f", b"short\n", None)
+        .unwrap();
+    assert_eq!(txn.commit().unwrap(), 10);
+    assert_eq!(count_in_rev_file(&db, "10", b"DELTA "), 0);
+    assert_eq!(
+        read_file(&fs.revision_root(10).unwrap(), "/d/f"),
+        b"short\n"
+    );
+}
+
+// `pack_filesystem` / `pack_even_filesystem` / `get_set_revprop_packed_fs`
+#[rstest]
+#[case::logical(true, "compress-packed-revprops = true\nrevprop-pack-size = 0\n")]
+#[case::physical(false, "")]
+fn test_pack(#[case] log_addressing: bool, #[case] packed_revprops: &str) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    SvnFs::create(&db, &fs_config("4", log_addressing)).unwrap();
+    fs_err::write(
+        db.join("fsfs.conf"),
+        format!("[packed-revprops]\n{packed_revprops}"),
+    )
+    .unwrap();
+    let fs = SvnFs::open(&db, &fs_config("4", log_addressing)).unwrap();
+
+    // r1 - r9: two completed shards plus r8 and r9.
+    for rev in 1..=9 {
+        let txn = fs.begin_txn(rev - 1).unwrap();
+        txn.change_prop("svn:log", Some(&format!("r{rev}")))
+            .unwrap();
+        {
+            let root = txn.root().unwrap();
+            if rev == 1 {
+                root.make_file("/f").unwrap();
+            }
+            root.apply_text("/f", format!("text of r{rev}\n").as_bytes(), None)
+                .unwrap();
+        }
+        assert_eq!(txn.commit().unwrap(), rev);
+    }
+
+    // Stop after the first shard.
+    let mut notifications = Vec::new();
+    let cancelled = std::cell::Cell::new(false);
+    let result = SvnFs::pack(
+        &db,
+        Some(&mut |shard, action| {
+            notifications.push((shard, action));
+            cancelled.set(action == PackNotifyAction::End);
+        }),
+        Some(&|| cancelled.get()),
+    );
+    assert!(result.is_err());
+    assert_eq!(
+        notifications,
+        [
+            (0, PackNotifyAction::Start),
+            (0, PackNotifyAction::StartRevprop),
+            (0, PackNotifyAction::EndRevprop),
+            (0, PackNotifyAction::End),
+        ]
+    );
+    assert_eq!(
+        fs_err::read_to_string(db.join("min-unpacked-rev")).unwrap(),
+        "4\n"
+    );
+
+    let mut notifications = Vec::new();
+    SvnFs::pack(
+        &db,
+        Some(&mut |shard, action| notifications.push((shard, action))),
+        None,
+    )
+    .unwrap();
+    assert_eq!(notifications.len(), 4);
+    assert_eq!(notifications[0], (1, PackNotifyAction::Start));
+    assert_eq!(
+        fs_err::read_to_string(db.join("min-unpacked-rev")).unwrap(),
+        "8\n"
+    );
+    for shard in ["0", "1"] {
+        assert!(!db.join("revs").join(shard).exists());
+        assert!(db.join("revs").join(format!("{shard}.pack/pack")).exists());
+        assert_eq!(
+            db.join("revs")
+                .join(format!("{shard}.pack/manifest"))
+                .exists(),
+            !log_addressing
+        );
+    }
+    assert!(db.join("revs/2/8").exists());
+    assert!(db.join("revprops/0/0").exists());
+    assert!(!db.join("revprops/0/1").exists());
+    assert!(!db.join("revprops/1").exists());
+    let manifest = fs_err::read_to_string(db.join("revprops/1.pack/manifest")).unwrap();
+    if log_addressing {
+        assert_eq!(manifest, "4.0\n5.0\n6.0\n7.0\n");
+        check_footer(&db.join("revs/0.pack/pack"));
+    } else {
+        assert_eq!(manifest, "4.0\n4.0\n4.0\n4.0\n");
+    }
+
+    // Nothing left to do.
+    let mut notifications = Vec::new();
+    SvnFs::pack(
+        &db,
+        Some(&mut |shard, action| notifications.push((shard, action))),
+        None,
+    )
+    .unwrap();
+    assert_eq!(notifications, [(2, PackNotifyAction::Noop)]);
+
+    // Packed and non-packed revisions read the same, and commits go on.
+    let fs = SvnFs::open(&db, &fs_config("4", log_addressing)).unwrap();
+    let txn = fs.begin_txn(9).unwrap();
+    txn.root()
+        .unwrap()
+        .apply_text("/f", b"text of r10\n", None)
+        .unwrap();
+    assert_eq!(txn.commit().unwrap(), 10);
+    for rev in 1..=10 {
+        let root = fs.revision_root(rev).unwrap();
+        assert_eq!(
+            read_file(&root, "/f"),
+            format!("text of r{rev}\n").as_bytes()
+        );
+        assert_eq!(root.node_created_rev("/f").unwrap(), rev);
+        assert!(fs.revision_prop(rev, "svn:date").unwrap().is_some());
+    }
+    for rev in 1..=9 {
+        assert_eq!(
+            fs.revision_prop(rev, "svn:log").unwrap(),
+            Some(format!("r{rev}"))
+        );
+    }
+    assert_eq!(fs.revision_prop(0, "svn:log").unwrap(), None);
+    assert!(fs.revision_prop(0, "svn:date").unwrap().is_some());
+
+    // Revprops of packed revisions change in place.
+    fs.change_rev_prop(5, "svn:log", Some(Some("r5")), Some("new log of r5"))
+        .unwrap();
+    assert!(
+        fs.change_rev_prop(5, "svn:log", Some(Some("r5")), None)
+            .is_err()
+    );
+    assert_eq!(
+        fs.revision_prop(5, "svn:log").unwrap().as_deref(),
+        Some("new log of r5")
+    );
+    assert_eq!(
+        fs.revision_prop(6, "svn:log").unwrap().as_deref(),
+        Some("r6")
+    );
+}
+
+// `set_huge_revprop_packed_fs`
+#[test]
+fn test_pack_revprop_split() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let config = fs_config("4", false);
+    let fs = SvnFs::create(&db, &config).unwrap();
+    for rev in 1..=7 {
+        let txn = fs.begin_txn(rev - 1).unwrap();
+        txn.change_prop("svn:log", Some(&format!("r{rev}")))
+            .unwrap();
+        txn.root().unwrap().make_file(&format!("/f{rev}")).unwrap();
+        assert_eq!(txn.commit().unwrap(), rev);
+    }
+    SvnFs::pack(&db, None, None).unwrap();
+    let pack_dir = db.join("revprops/1.pack");
+    assert_eq!(
+        fs_err::read_to_string(pack_dir.join("manifest")).unwrap(),
+        "4.0\n4.0\n4.0\n4.0\n"
+    );
+
+    // Growing the pack past 'revprop-pack-size' moves the revprops of r6
+    // into a file of their own, and r7 into another.  FS was opened before
+    // packing and has to notice it.
+    let huge = "x".repeat(20 * 1024);
+    fs.change_rev_prop(6, "svn:log", Some(Some("r6")), Some(&huge))
+        .unwrap();
+    assert_eq!(
+        fs_err::read_to_string(pack_dir.join("manifest")).unwrap(),
+        "4.1\n4.1\n6.1\n7.1\n"
+    );
+    assert!(!pack_dir.join("4.0").exists());
+    for packname in ["4.1", "6.1", "7.1"] {
+        assert!(pack_dir.join(packname).is_file());
+    }
+
+    let fs = SvnFs::open(&db, &config).unwrap();
+    for rev in [4, 5, 7] {
+        assert_eq!(
+            fs.revision_prop(rev, "svn:log").unwrap(),
+            Some(format!("r{rev}"))
+        );
+        assert!(fs.revision_prop(rev, "svn:date").unwrap().is_some());
+    }
+    assert_eq!(fs.revision_prop(6, "svn:log").unwrap(), Some(huge));
+}
+
+#[test]
+fn test_revprop_generation() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    SvnFs::create(&db, &fs_config("1000", true)).unwrap();
+    assert_eq!(
+        fs_err::read_to_string(db.join("revprop-generation")).unwrap(),
+        "0\n"
+    );
+
+    // Two instances that cache revprops, but don't share the caches.
+    let config = || {
+        FsConfig::Fsfs(FsFsConfig {
+            cache_revprops: true,
+            cache_namespace: uuid::Uuid::new_v4(),
+            ..Default::default()
+        })
+    };
+    let fs1 = SvnFs::open(&db, &config()).unwrap();
+    let fs2 = SvnFs::open(&db, &config()).unwrap();
+    assert_eq!(fs1.revision_prop(0, "svn:log").unwrap(), None);
+
+    // A change through one instance bumps the generation, so the other
+    // one does not use its cached revprops anymore.
+    fs2.change_rev_prop(0, "svn:log", Some(None), Some("log"))
+        .unwrap();
+    assert_eq!(
+        fs_err::read_to_string(db.join("revprop-generation")).unwrap(),
+        "2\n"
+    );
+    assert_eq!(
+        fs1.revision_prop(0, "svn:log").unwrap().as_deref(),
+        Some("log")
+    );
+}
+
+// `freeze_and_commit`
+#[test]
+fn test_freeze() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let config = fs_config("1000", true);
+    let fs = SvnFs::create(&db, &config).unwrap();
+    let txn = fs.begin_txn(0).unwrap();
+    {
+        let root = txn.root().unwrap();
+        root.make_file("/f").unwrap();
+        root.apply_text("/f", b"text\n", None).unwrap();
+    }
+    txn.commit().unwrap();
+    assert!(db.join("rep-cache.db").is_file());
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::scope(|scope| {
+        let youngest = fs
+            .freeze(|| {
+                scope.spawn(|| {
+                    let fs = SvnFs::open(&db, &config).unwrap();
+                    fs.change_rev_prop(1, "svn:log", None, Some("log")).unwrap();
+                    sender.send(()).unwrap();
+                });
+
+                // Changes wait for the freeze to end, reading goes on.
+                let timeout = std::time::Duration::from_millis(200);
+                assert!(receiver.recv_timeout(timeout).is_err());
+                assert_eq!(read_file(&fs.revision_root(1).unwrap(), "/f"), b"text\n");
+                fs.youngest_rev()
+            })
+            .unwrap();
+        assert_eq!(youngest, 1);
+        receiver.recv().unwrap();
+    });
+    assert_eq!(
+        fs.revision_prop(1, "svn:log").unwrap().as_deref(),
+        Some("log")
+    );
+
+    // Errors of the freeze function get passed through.
+    let result: Result<(), svn_fs::Error> = fs.freeze(|| Err(svn_fs::Error::CrossFsCopy));
+    assert!(matches!(result, Err(svn_fs::Error::CrossFsCopy)));
+}
+
+#[test]
+fn test_pack_linear() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let fs = SvnFs::create(&db, &fs_config("0", false)).unwrap();
+
+    // Revisions go straight into 'revs' and 'revprops'.
+    let txn = fs.begin_txn(0).unwrap();
+    txn.root().unwrap().make_file("/f").unwrap();
+    assert_eq!(txn.commit().unwrap(), 1);
+    assert!(db.join("revs/1").is_file());
+    assert!(db.join("revprops/1").is_file());
+
+    let mut notifications = Vec::new();
+    SvnFs::pack(
+        &db,
+        Some(&mut |shard, action| notifications.push((shard, action))),
+        None,
+    )
+    .unwrap();
+    assert_eq!(notifications, [(-1, PackNotifyAction::Noop)]);
+}
+
+// `test_reps` / `test_l2p_p2l_page_sizes`
+#[test]
+fn test_small_index_pages() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    SvnFs::create(&db, &fs_config("2", true)).unwrap();
+
+    // Spread items over many index pages: two items per l2p page and
+    // 1 kB per p2l page, with file contents spanning several of them.
+    fs_err::write(
+        db.join("fsfs.conf"),
+        "[io]\nl2p-page-size = 2\np2l-page-size = 1\n\
+         [rep-sharing]\nenable-rep-sharing = true\n",
+    )
+    .unwrap();
+    let fs = SvnFs::open(&db, &fs_config("2", true)).unwrap();
+
+    let text = |rev: i64, i: usize| format!("file {i} of r{rev}\n").repeat(200 * i + 1);
+    for rev in 1..=4 {
+        let txn = fs.begin_txn(rev - 1).unwrap();
+        {
+            let root = txn.root().unwrap();
+            for i in 0..5 {
+                let path = format!("/f{i}");
+                if rev == 1 {
+                    root.make_file(&path).unwrap();
+                }
+                root.apply_text(&path, text(rev, i).as_bytes(), None)
+                    .unwrap();
+            }
+        }
+        assert_eq!(txn.commit().unwrap(), rev);
+        check_footer(&db.join(format!("revs/{}/{rev}", rev / 2)));
+    }
+
+    let check = |fs: &SvnFs| {
+        for rev in 1..=4 {
+            let root = fs.revision_root(rev).unwrap();
+            for i in 0..5 {
+                assert_eq!(read_file(&root, &format!("/f{i}")), text(rev, i).as_bytes());
+            }
+        }
+        fs.verify_rep_cache(0, 4).unwrap();
+        let errors = SvnFs::verify(fs.path(), 0, 4, true, None, None).unwrap();
+        assert!(errors.is_empty(), "{errors:?}");
+    };
+    check(&fs);
+
+    SvnFs::pack(&db, None, None).unwrap();
+    check_footer(&db.join("revs/0.pack/pack"));
+    check_footer(&db.join("revs/1.pack/pack"));
+    check(&SvnFs::open(&db, &fs_config("2", true)).unwrap());
+}
+
+/// Overwrite the first occurrence of @a needle in the rev file of @a rev
+/// with @a replacement and return its offset.
+// `svnfsfs stats`
+#[rstest]
+#[case::logical(true)]
+#[case::physical(false)]
+fn test_stats(#[case] log_addressing: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let fs = SvnFs::create(&db, &fs_config("4", log_addressing)).unwrap();
+    let vendored = b"The same vendored file on every branch.\n";
+    let big = |len: usize| -> Vec<u8> { (0..len).map(|i| (i % 251) as u8).collect() };
+
+    let txn = fs.begin_txn(0).unwrap();
+    {
+        let root = txn.root().unwrap();
+        root.make_dir("/d").unwrap();
+        root.change_node_prop("/d", "p", Some("v")).unwrap();
+        root.make_file("/d/a").unwrap();
+        root.apply_text("/d/a", vendored, None).unwrap();
+        root.make_file("/big").unwrap();
+        root.apply_text("/big", &big(10000), None).unwrap();
+    }
+    txn.commit().unwrap();
+
+    // The vendored text gets shared, the big file deltified.
+    let txn = fs.begin_txn(1).unwrap();
+    {
+        let root = txn.root().unwrap();
+        root.make_file("/b").unwrap();
+        root.apply_text("/b", vendored, None).unwrap();
+        root.apply_text("/big", &big(11000), None).unwrap();
+    }
+    txn.commit().unwrap();
+    let txn = fs.begin_txn(2).unwrap();
+    txn.root()
+        .unwrap()
+        .apply_text("/big", &big(12000), None)
+        .unwrap();
+    txn.commit().unwrap();
+    for rev in 4..=5 {
+        let txn = fs.begin_txn(rev - 1).unwrap();
+        {
+            let root = txn.root().unwrap();
+            let path = format!("/r{rev}");
+            root.make_file(&path).unwrap();
+            root.apply_text(&path, path.as_bytes(), None).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+    SvnFs::pack(&db, None, None).unwrap();
+
+    let mut revisions = Vec::new();
+    let stats = SvnFs::stats(&db, Some(&mut |rev| revisions.push(rev)), None).unwrap();
+    assert_eq!(revisions, [0, 1, 2, 3, 4, 5]);
+    assert_eq!(stats.revision_count, 6);
+    assert_eq!(stats.change_count, 8);
+
+    // Shards and files as found on disk.
+    let file_size = |path: &str| fs_err::metadata(db.join(path)).unwrap().len();
+    let pack_files: &[&str] = if log_addressing {
+        &["pack"]
+    } else {
+        &["pack", "manifest"]
+    };
+    let packed_size = pack_files
+        .iter()
+        .map(|name| file_size(&format!("revs/0.pack/{name}")))
+        .sum::<u64>();
+    let unpacked_size = file_size("revs/1/4") + file_size("revs/1/5");
+    assert_eq!(stats.total_size, packed_size + unpacked_size);
+    let shards: Vec<_> = stats
+        .shards
+        .iter()
+        .map(|shard| {
+            assert!(shard.item_size > 0 && shard.overhead() > 0, "{shard:?}");
+            (
+                shard.start_revision,
+                shard.revisions,
+                shard.packed,
+                shard.files,
+                shard.file_size,
+            )
+        })
+        .collect();
+    assert_eq!(
+        shards,
+        [
+            (0, 4, true, pack_files.len() as u64, packed_size),
+            (4, 2, false, 2, unpacked_size)
+        ]
+    );
+
+    // One node-revision per new node plus a root directory per revision.
+    assert_eq!(stats.dir_nodes.count, 7);
+    assert_eq!(stats.file_nodes.count, 7);
+    assert_eq!(stats.total_nodes().count, 14);
+
+    // The vendored text is stored once for two files.
+    let files = &stats.file_reps;
+    assert_eq!(files.total().count, 6);
+    assert_eq!(files.references, 7);
+    assert_eq!(files.shared.count, 1);
+    assert_eq!(files.shared.expanded_size, vendored.len() as u64);
+    assert_eq!(files.saved_size, files.shared.packed_size);
+    assert!(files.delta.count >= 2);
+    assert!(files.chain_lengths.keys().any(|&len| len > 1));
+    assert_eq!(files.chain_lengths.values().sum::<u64>(), 6);
+    assert_eq!(files.size_histogram.total.count, 6);
+    assert_eq!(files.size_histogram.total.sum, files.total().packed_size);
+    assert_eq!(stats.dir_prop_reps.total().count, 1);
+    assert_eq!(stats.file_prop_reps.total().count, 0);
+
+    let largest: Vec<_> = stats
+        .largest_files
+        .iter()
+        .map(|file| (file.size, file.revision, file.path.as_str()))
+        .collect();
+    assert_eq!(
+        largest,
+        [
+            (12000, 3, "/big"),
+            (11000, 2, "/big"),
+            (10000, 1, "/big"),
+            (vendored.len() as u64, 1, "/d/a"),
+            (3, 5, "/r5"),
+            (3, 4, "/r4"),
+        ]
+    );
+}
+
+fn corrupt_rev_file(db: &Path, rev: &str, needle: &[u8], replacement: &[u8]) -> u64 {
+    let path = db.join("revs/0").join(rev);
+    let mut content = fs_err::read(&path).unwrap();
+    let offset = content
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .unwrap();
+    content[offset..offset + replacement.len()].copy_from_slice(replacement);
+
+    let mut perms = fs_err::metadata(&path).unwrap().permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    perms.set_readonly(false);
+    fs_err::set_permissions(&path, perms).unwrap();
+    fs_err::write(&path, content).unwrap();
+    offset as u64
+}
+
+#[rstest]
+#[case::logical(true)]
+#[case::physical(false)]
+fn test_verify(#[case] log_addressing: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let fs = SvnFs::create(&db, &fs_config("1000", log_addressing)).unwrap();
+
+    for (rev, path, text) in [
+        (1, "/a", "alpha file contents\n"),
+        (2, "/b", "beta file contents\n"),
+        (3, "/c", "gamma file contents\n"),
+    ] {
+        let txn = fs.begin_txn(rev - 1).unwrap();
+        {
+            let root = txn.root().unwrap();
+            root.make_file(path).unwrap();
+            root.apply_text(path, text.as_bytes(), None).unwrap();
+        }
+        assert_eq!(txn.commit().unwrap(), rev);
+    }
+
+    let mut notified = Vec::new();
+    let errors = SvnFs::verify(
+        &db,
+        SVN_INVALID_REVNUM,
+        SVN_INVALID_REVNUM,
+        false,
+        Some(&mut |rev| notified.push(rev)),
+        None,
+    )
+    .unwrap();
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(notified, [0, 1, 2, 3]);
+
+    // Damage the file contents of r1 and r3.
+    let offset1 = corrupt_rev_file(&db, "1", b"alpha", b"ALPHA");
+    corrupt_rev_file(&db, "3", b"gamma", b"GAMMA");
+
+    // Without keep-going, verification stops after r1.
+    let errors = SvnFs::verify(&db, 0, 3, false, None, None).unwrap();
+    assert!(!errors.is_empty());
+    assert!(errors.iter().all(|e| e.revision == 1), "{errors:?}");
+    let mismatches: Vec<_> = errors
+        .iter()
+        .filter_map(|e| match &e.kind {
+            VerifyErrorKind::ChecksumMismatch { kind, .. } => Some((*kind, e)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(mismatches.len(), 2, "{errors:?}");
+    assert_eq!(mismatches[0].0, ChecksumKind::Md5);
+    assert_eq!(mismatches[1].0, ChecksumKind::Sha1);
+    for (_, error) in &mismatches {
+        assert_eq!(error.path.as_deref(), Some("/a"));
+        assert!(error.offset.unwrap() < offset1);
+    }
+
+    // The item checksums in the p2l index catch the damage as well.
+    if log_addressing {
+        assert!(
+            errors
+                .iter()
+                .any(|e| matches!(e.kind, VerifyErrorKind::Index(_)) && e.path.is_none()),
+            "{errors:?}"
+        );
+    }
+
+    // With keep-going, all damaged revisions get reported.
+    let errors = SvnFs::verify(&db, 0, 3, true, None, None).unwrap();
+    let mut revisions: Vec<_> = errors.iter().map(|e| e.revision).collect();
+    revisions.dedup();
+    assert_eq!(revisions, [1, 3]);
+    assert!(errors.iter().any(
+        |e| e.path.as_deref() == Some("/c") && e.to_string().starts_with("r3 '/c' at offset ")
+    ));
+
+    // Cancellation is an error, not a report.
+    assert!(SvnFs::verify(&db, 0, 3, true, None, Some(&|| true)).is_err());
+}
+
+#[rstest]
+#[case::logical(true)]
+#[case::physical(false)]
+fn test_hotcopy(#[case] log_addressing: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    let dst = dir.path().join("dst");
+    let config = fs_config("2", log_addressing);
+    let fs = SvnFs::create(&src, &config).unwrap();
+
+    let text = |rev: i64| format!("contents of r{rev}\n");
+    let commit = |fs: &SvnFs, rev: i64| {
+        let txn = fs.begin_txn(rev - 1).unwrap();
+        {
+            let root = txn.root().unwrap();
+            let path = format!("/f{rev}");
+            root.make_file(&path).unwrap();
+            root.apply_text(&path, text(rev).as_bytes(), None).unwrap();
+        }
+        assert_eq!(txn.commit().unwrap(), rev);
+    };
+    let check = |youngest: i64| {
+        let copy = SvnFs::open(&dst, &config).unwrap();
+        assert_eq!(copy.youngest_rev().unwrap(), youngest);
+        assert_eq!(copy.uuid, fs.uuid);
+        let root = copy.revision_root(youngest).unwrap();
+        for rev in 1..=youngest {
+            assert_eq!(read_file(&root, &format!("/f{rev}")), text(rev).as_bytes());
+        }
+        copy.verify_rep_cache(0, youngest).unwrap();
+        let errors = SvnFs::verify(&dst, 0, youngest, true, None, None).unwrap();
+        assert!(errors.is_empty(), "{errors:?}");
+    };
+    let instance_id = |db: &Path| {
+        let uuid = fs_err::read_to_string(db.join("uuid")).unwrap();
+        uuid.lines().nth(1).unwrap().to_string()
+    };
+
+    for rev in 1..=3 {
+        commit(&fs, rev);
+    }
+
+    let mut notified = Vec::new();
+    SvnFs::hotcopy(
+        &src,
+        &dst,
+        false,
+        Some(&mut |start, end| notified.push((start, end))),
+        None,
+    )
+    .unwrap();
+    assert_eq!(notified, [(0, 0), (1, 1), (2, 2), (3, 3)]);
+    assert_ne!(instance_id(&src), instance_id(&dst));
+    check(3);
+
+    // A full hotcopy needs a fresh destination, and leaves an existing
+    // one alone.
+    let dst_instance_id = instance_id(&dst);
+    assert!(SvnFs::hotcopy(&src, &dst, false, None, None).is_err());
+    assert_eq!(instance_id(&dst), dst_instance_id);
+    check(3);
+    let other = dir.path().join("other");
+    fs_err::create_dir(&other).unwrap();
+    fs_err::write(other.join("README"), "keep me\n").unwrap();
+    assert!(SvnFs::hotcopy(&src, &other, false, None, None).is_err());
+    assert_eq!(fs_err::read_dir(&other).unwrap().count(), 1);
+
+    // An incremental hotcopy brings over new packs and revisions and
+    // drops the rev files that got packed.
+    commit(&fs, 4);
+    SvnFs::pack(&src, None, None).unwrap();
+    let mut notified = Vec::new();
+    SvnFs::hotcopy(
+        &src,
+        &dst,
+        true,
+        Some(&mut |start, end| notified.push((start, end))),
+        None,
+    )
+    .unwrap();
+    assert_eq!(notified, [(0, 1), (2, 3), (4, 4)]);
+    assert_eq!(instance_id(&dst), dst_instance_id);
+    assert_eq!(
+        fs_err::read_to_string(dst.join("min-unpacked-rev")).unwrap(),
+        "4\n"
+    );
+    assert!(dst.join("revs/1.pack/pack").is_file());
+    assert!(!dst.join("revs/0").exists());
+    assert!(!dst.join("revs/1").exists());
+    assert!(dst.join("revs/2/4").is_file());
+    assert!(dst.join("rep-cache.db").is_file());
+    check(4);
+
+    // Nothing to do the second time.
+    let mut notified = Vec::new();
+    SvnFs::hotcopy(
+        &src,
+        &dst,
+        true,
+        Some(&mut |start, end| notified.push((start, end))),
+        None,
+    )
+    .unwrap();
+    assert!(notified.is_empty(), "{notified:?}");
+    check(4);
+
+    // The destination must not be ahead of the source.
+    commit(&SvnFs::open(&dst, &config).unwrap(), 5);
+    assert!(SvnFs::hotcopy(&src, &dst, true, None, None).is_err());
+
+    let cancelled = dir.path().join("cancelled");
+    assert!(SvnFs::hotcopy(&src, &cancelled, false, None, Some(&|| true)).is_err());
+}
+
+#[rstest]
+#[case::logical(true)]
+#[case::physical(false)]
+fn test_recover(#[case] log_addressing: bool) {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let config = fs_config("2", log_addressing);
+    let fs = SvnFs::create(&db, &config).unwrap();
+
+    for rev in 1..=5 {
+        let txn = fs.begin_txn(rev - 1).unwrap();
+        {
+            let root = txn.root().unwrap();
+            let path = format!("/f{rev}");
+            root.make_file(&path).unwrap();
+            root.apply_text(&path, format!("r{rev}\n").as_bytes(), None)
+                .unwrap();
+        }
+        assert_eq!(txn.commit().unwrap(), rev);
+    }
+    SvnFs::pack(&db, None, None).unwrap();
+    let open_txn = fs.begin_txn(5).unwrap().name().to_string();
+    drop(fs);
+
+    // Simulate a crash: 'current' lags behind the rev files,
+    // 'txn-current' is gone and a purge left a proto-rev behind.
+    fs_err::write(db.join("current"), "3\n").unwrap();
+    fs_err::remove_file(db.join("txn-current")).unwrap();
+    fs_err::write(db.join("txn-protorevs/9-z.rev"), "").unwrap();
+    fs_err::write(db.join("txn-protorevs/9-z.rev-lock"), "").unwrap();
+    fs_err::create_dir(db.join("transactions/7-5.txn")).unwrap();
+    let instance_id = fs_err::read_to_string(db.join("uuid")).unwrap();
+
+    let report = SvnFs::recover(&db, None).unwrap();
+    assert_eq!(report.youngest, 5);
+    assert!(report.txn_current_repaired);
+    assert_eq!(report.orphaned_txns, ["7-5"]);
+    assert_eq!(report.orphaned_proto_revs, ["9-z"]);
+    assert!(!db.join("txn-protorevs/9-z.rev").exists());
+    assert!(!db.join("txn-protorevs/9-z.rev-lock").exists());
+    assert_eq!(fs_err::read_to_string(db.join("current")).unwrap(), "5\n");
+    assert_eq!(
+        fs_err::read_to_string(db.join("txn-current")).unwrap(),
+        "10\n"
+    );
+    assert_ne!(
+        fs_err::read_to_string(db.join("uuid")).unwrap(),
+        instance_id
+    );
+
+    let fs = SvnFs::open(&db, &config).unwrap();
+    assert_eq!(fs.youngest_rev().unwrap(), 5);
+    assert_eq!(read_file(&fs.revision_root(5).unwrap(), "/f5"), b"r5\n");
+    assert!(fs.list_transactions().unwrap().contains(&open_txn));
+    assert_eq!(fs.begin_txn(5).unwrap().name(), "5-10");
+    drop(fs);
+
+    // Nothing left to repair, but a garbled 'current' gets rewritten.
+    fs_err::write(db.join("current"), "garbage").unwrap();
+    let report = SvnFs::recover(&db, None).unwrap();
+    assert_eq!(report.youngest, 5);
+    assert!(!report.txn_current_repaired);
+    assert!(report.orphaned_proto_revs.is_empty());
+    assert_eq!(fs_err::read_to_string(db.join("current")).unwrap(), "5\n");
+
+    // 'current' pointing beyond the rev files is not ours to fix.
+    fs_err::write(db.join("current"), "9\n").unwrap();
+    assert!(SvnFs::recover(&db, None).is_err());
+    fs_err::write(db.join("current"), "5\n").unwrap();
+
+    // Live writers keep recovery out.
+    let lock = svn_subr::io::FileLock::lock(&db.join("write-lock"), true).unwrap();
+    assert!(SvnFs::recover(&db, None).is_err());
+    drop(lock);
+    SvnFs::recover(&db, None).unwrap();
+
+    assert!(SvnFs::recover(&db, Some(&|| true)).is_err());
+}
+
+#[test]
+fn test_upgrade() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let config = fs_config("2", false);
+    let fs = SvnFs::create(&db, &config).unwrap();
+    for rev in 1..=4 {
+        let txn = fs.begin_txn(rev - 1).unwrap();
+        txn.root().unwrap().make_dir(&format!("/d{rev}")).unwrap();
+        assert_eq!(txn.commit().unwrap(), rev);
+    }
+    drop(fs);
+
+    // Turn the filesystem into a packed format 5 one, which has neither
+    // packed revprops nor instance IDs.
+    let uuid = fs_err::read_to_string(db.join("uuid")).unwrap();
+    let uuid = uuid.lines().next().unwrap().to_string();
+    fs_err::write(db.join("uuid"), format!("{uuid}\n")).unwrap();
+    fs_err::write(db.join("format"), "5\nlayout sharded 2\n").unwrap();
+    fs_err::remove_file(db.join("fsfs.conf")).unwrap();
+    SvnFs::pack(&db, None, None).unwrap();
+    assert!(db.join("revs/1.pack/pack").is_file());
+    assert!(db.join("revprops/1/3").is_file());
+    assert!(db.join("revprops/2/4").is_file());
+    let dates: Vec<_> = (0..=4)
+        .map(|rev| {
+            let fs = SvnFs::open(&db, &config).unwrap();
+            fs.revision_prop(rev, "svn:date").unwrap().unwrap()
+        })
+        .collect();
+
+    let mut notified = Vec::new();
+    SvnFs::upgrade(
+        &db,
+        Some(&mut |number, action| notified.push((number, action))),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        notified,
+        [
+            (0, UpgradeNotifyAction::PackRevprops),
+            (1, UpgradeNotifyAction::PackRevprops),
+            (0, UpgradeNotifyAction::CleanupRevprops),
+            (1, UpgradeNotifyAction::CleanupRevprops),
+            (8, UpgradeNotifyAction::FormatBumped),
+        ]
+    );
+    assert_eq!(
+        fs_err::read_to_string(db.join("format")).unwrap(),
+        "8\nlayout sharded 2\naddressing physical\n"
+    );
+    assert!(db.join("fsfs.conf").is_file());
+    assert!(db.join("revprops/1.pack/manifest").is_file());
+    assert!(!db.join("revprops/1").exists());
+    assert!(db.join("revprops/0/0").is_file());
+    assert!(!db.join("revprops/0/1").exists());
+    assert!(db.join("revprops/2/4").is_file());
+    let new_uuid = fs_err::read_to_string(db.join("uuid")).unwrap();
+    assert_eq!(new_uuid.lines().next(), Some(uuid.as_str()));
+    assert_eq!(new_uuid.lines().count(), 2);
+
+    let fs = SvnFs::open(&db, &config).unwrap();
+    for (rev, date) in dates.iter().enumerate() {
+        assert_eq!(
+            fs.revision_prop(rev as i64, "svn:date").unwrap().as_ref(),
+            Some(date)
+        );
+    }
+    let txn = fs.begin_txn(4).unwrap();
+    txn.root().unwrap().make_dir("/d5").unwrap();
+    assert_eq!(txn.commit().unwrap(), 5);
+    drop(fs);
+    assert!(
+        SvnFs::verify(&db, 0, 5, false, None, None)
+            .unwrap()
+            .is_empty()
+    );
+
+    // Nothing left to do.
+    let mut notified = Vec::new();
+    SvnFs::upgrade(
+        &db,
+        Some(&mut |number, action| notified.push((number, action))),
+        None,
+    )
+    .unwrap();
+    assert!(notified.is_empty(), "{notified:?}");
+
+    // Formats before packing need a dump and load.
+    fs_err::write(db.join("format"), "3\nlayout sharded 2\n").unwrap();
+    let err = SvnFs::upgrade(&db, None, None).unwrap_err();
+    assert!(err.to_string().contains("dump and load"), "{err}");
+}
+
+#[test]
+fn test_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = dir.path().join("db");
+    let mut fs = SvnFs::create(&db, &fs_config("1000", true)).unwrap();
+    let edit = |fs: &SvnFs, change: &dyn Fn(&svn_fs::FsRoot<'_>)| {
+        let txn = fs.begin_txn(fs.youngest_rev().unwrap()).unwrap();
+        change(&txn.root().unwrap());
+        txn.commit()
+    };
+    edit(&fs, &|root| {
+        for path in ["/a", "/dir/b", "/dir/sub/c"] {
+            if path == "/dir/b" {
+                root.make_dir("/dir").unwrap();
+                root.make_dir("/dir/sub").unwrap();
+            }
+            root.make_file(path).unwrap();
+            root.apply_text(path, b"text\n", None).unwrap();
+        }
+    })
+    .unwrap();
+    let target = LockTarget::default();
+
+    // Locking needs a user.
+    assert!(fs.lock("/a", &target, None, false, 0, false).is_err());
+
+    fs.set_access(Some(FsAccess::new("alice")));
+    let lock = fs
+        .lock("a", &target, Some("mine"), false, 0, false)
+        .unwrap();
+    assert_eq!(lock.path, "/a");
+    assert_eq!(lock.owner, "alice");
+    assert!(lock.token.starts_with("opaquelocktoken:"));
+    assert_eq!(fs.get_lock("/a").unwrap(), Some(lock.clone()));
+    let digest = Checksum::compute(ChecksumKind::Md5, b"/a").to_hex();
+    assert!(db.join("locks").join(&digest[..3]).join(&digest).is_file());
+
+    // Only unlocked files in HEAD that didn't change since can be locked.
+    assert!(fs.lock("/a", &target, None, false, 0, false).is_err());
+    assert!(fs.lock("/dir", &target, None, false, 0, false).is_err());
+    assert!(fs.lock("/nope", &target, None, false, 0, false).is_err());
+    let old = LockTarget::new(None, 0);
+    assert!(fs.lock("/dir/b", &old, None, false, 0, false).is_err());
+    let malformed = LockTarget::new(Some("token"), SVN_INVALID_REVNUM);
+    assert!(
+        fs.lock("/dir/b", &malformed, None, false, 0, false)
+            .is_err()
+    );
+
+    let token = fs.generate_lock_token();
+    let b_target = LockTarget::new(Some(&token), 1);
+    let b_lock = fs.lock("/dir/b", &b_target, None, false, 0, false).unwrap();
+    assert_eq!(b_lock.token, token);
+    let c_lock = fs
+        .lock("/dir/sub/c", &target, None, false, 0, false)
+        .unwrap();
+
+    let paths = |locks: Vec<svn_types::SvnLock>| -> Vec<String> {
+        locks.into_iter().map(|lock| lock.path).collect()
+    };
+    assert_eq!(
+        paths(fs.get_locks("/", Depth::Infinity).unwrap()),
+        ["/a", "/dir/b", "/dir/sub/c"]
+    );
+    assert_eq!(
+        paths(fs.get_locks("/dir", Depth::Immediates).unwrap()),
+        ["/dir/b"]
+    );
+    assert!(fs.get_locks("/dir", Depth::Empty).unwrap().is_empty());
+    assert_eq!(
+        paths(fs.get_locks("/dir/b", Depth::Empty).unwrap()),
+        ["/dir/b"]
+    );
+
+    // Commits need the lock token and the lock owner.
+    let modify_a = |root: &svn_fs::FsRoot<'_>| root.apply_text("/a", b"new\n", None).unwrap();
+    assert!(edit(&fs, &modify_a).is_err());
+    let mut bob = FsAccess::new("bob");
+    bob.add_lock_token(&lock.token, "/a").unwrap();
+    fs.set_access(Some(bob));
+    assert!(edit(&fs, &modify_a).is_err());
+    let mut alice = FsAccess::new("alice");
+    alice.add_lock_token(&lock.token, "/a").unwrap();
+    alice.add_lock_token(&b_lock.token, "/dir/b").unwrap();
+    fs.set_access(Some(alice));
+    assert_eq!(edit(&fs, &modify_a).unwrap(), 2);
+
+    // Deleting a directory needs the tokens of all locks below it.
+    let delete_dir = |root: &svn_fs::FsRoot<'_>| root.delete("/dir").unwrap();
+    assert!(edit(&fs, &delete_dir).is_err());
+    let mut alice = FsAccess::new("alice");
+    alice.add_lock_token(&b_lock.token, "/dir/b").unwrap();
+    alice.add_lock_token(&c_lock.token, "/dir/sub/c").unwrap();
+    fs.set_access(Some(alice));
+    assert_eq!(edit(&fs, &delete_dir).unwrap(), 3);
+
+    // Unlocking needs the token and the owner, unless the lock is broken.
+    assert!(fs.unlock("/a", Some(&b_lock.token), false).is_err());
+    fs.set_access(Some(FsAccess::new("bob")));
+    assert!(fs.unlock("/a", Some(&lock.token), false).is_err());
+    fs.unlock("/dir/b", None, true).unwrap();
+    fs.set_access(Some(FsAccess::new("alice")));
+    fs.unlock("/a", Some(&lock.token), false).unwrap();
+    fs.unlock("/dir/sub/c", Some(&c_lock.token), false).unwrap();
+    assert_eq!(fs.get_lock("/a").unwrap(), None);
+    assert!(fs.unlock("/a", Some(&lock.token), false).is_err());
+    assert!(!db.join("locks").join(&digest[..3]).join(&digest).exists());
+
+    // Locks can be stolen, and expired ones are gone.
+    fs.lock("/a", &target, None, false, 0, false).unwrap();
+    fs.set_access(Some(FsAccess::new("bob")));
+    assert!(fs.lock("/a", &target, None, false, 0, false).is_err());
+    let stolen = fs.lock("/a", &target, None, false, 0, true).unwrap();
+    assert_eq!(fs.get_lock("/a").unwrap().unwrap().owner, "bob");
+    fs.set_access(Some(FsAccess::new("alice")));
+    fs.lock("/a", &target, None, false, 1, true).unwrap();
+    assert_eq!(fs.get_lock("/a").unwrap(), None);
+    assert_eq!(edit(&fs, &modify_a).unwrap(), 4);
+    fs.lock("/a", &target, None, false, 0, false).unwrap();
+    assert!(fs.unlock("/a", Some(&stolen.token), false).is_err());
+}
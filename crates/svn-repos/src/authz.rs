@@ -82,9 +82,11 @@ impl Node {
     /// Make sure a Node for segment exists in array and return it.
     /// Auto-create either if they don't exist. Entries in array are
     /// sorted by their segment strings.
+    /// The tree is only shared once it has been built, so the nodes are
+    /// still exclusively owned here.
     fn ensure_node_in_array<'a>(
-        array: &mut Option<Vec<SortedPattern>>,
-        segment: &'a AuthzRuleSegment,
+        array: &'a mut Option<Vec<SortedPattern>>,
+        segment: &AuthzRuleSegment,
     ) -> &'a mut Node {
         // Auto-create the array if it doesn't exist
         if array.is_none() {
@@ -100,22 +102,23 @@ impl Node {
         {
             Ok(index) => {
                 // Found existing node - return it
-                return &mut array[index].node;
+                return Arc::get_mut(&mut array[index].node)
+                    .expect("tree nodes are not shared during construction");
             }
             Err(index) => index, // This is where we would insert the new node
         };
 
         // There is no such node yet.
         // Create one and insert it into the sorted array.
-        let mut entry = SortedPattern {
+        let entry = SortedPattern {
             node: Arc::new(Self::create_node(segment)),
         };
 
         // Insert at the calculated position to maintain sorted order
-        array.push(entry);
+        array.insert(idx, entry);
 
         // Return a reference to the newly created node
-        &mut array[idx].node
+        Arc::get_mut(&mut array[idx].node).expect("the node has just been created")
     }
 }
 
@@ -663,3 +666,42 @@ pub struct SvnAuthz {
     /// May be NULL.
     filtered: Option<Arc<AuthzUserRules>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_node_in_array() {
+        let segment = |pattern: &str| AuthzRuleSegment {
+            kind: AuthzRuleSegmentKind::Prefix,
+            pattern: pattern.to_string(),
+        };
+        let mut array = None;
+        for pattern in ["b", "c", "a", "b"] {
+            let node = Node::ensure_node_in_array(&mut array, &segment(pattern));
+            assert_eq!(node.segment, pattern);
+            node.rights.access.sequence_number += 1;
+        }
+
+        // The array stays sorted, and existing nodes are reused.
+        let nodes: Vec<_> = array
+            .unwrap()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.node.segment.clone(),
+                    entry.node.rights.access.sequence_number,
+                )
+            })
+            .collect();
+        assert_eq!(
+            nodes,
+            [
+                ("a".to_string(), NO_SEQUENCE_NUMBER + 1),
+                ("b".to_string(), NO_SEQUENCE_NUMBER + 2),
+                ("c".to_string(), NO_SEQUENCE_NUMBER + 1),
+            ]
+        );
+    }
+}
@@ -6,7 +6,7 @@ use svn_types::RevisionNumber;
 
 use crate::Repos;
 
-pub struct EditBaton {
+pub struct EditBaton<'a> {
     // Revision properties to set for this commit.
     revprop_table: HashMap<String, String>,
 
@@ -27,7 +27,7 @@ pub struct EditBaton {
     txn_owner: bool,
     // svn transaction associated with this edit (created in
     // open_root, or supplied by the public API caller).
-    txn: Option<FsTxn>,
+    txn: Option<FsTxn<'a>>,
 
     /** Filled in when the edit is closed: **/
     // The new revision created by this commit.
@@ -21,8 +21,8 @@ pub struct ConfigAccess {
 /// IOW, the stream object is cheap as long as it is not accessed.
 ///
 /// `repr_steam_baton_t`
-struct ReprStreamBaton {
-    root: FsRoot,
+struct ReprStreamBaton<'a> {
+    root: FsRoot<'a>,
     fs_path: PathBuf,
 }
 
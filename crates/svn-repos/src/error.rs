@@ -8,6 +8,9 @@ pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
+    #[error(transparent)]
+    Fs(#[from] svn_fs::Error),
+
     #[error("Invalid format file: path={path}, error={error}")]
     InvalidFormatFile { path: String, error: String },
 
@@ -128,10 +128,13 @@ impl Repos {
     fn check_repos_format(&mut self, repository_path: &str) -> Result<(), Error> {
         let format_path = Path::new(repository_path).join(PATH_FORMAT);
         let format_str = fs_err::read_to_string(&format_path)?;
-        let format: i32 = format_str.parse().map_err(|_| Error::InvalidFormatFile {
-            path: format_path.to_string_lossy().to_string(),
-            error: "Failed to parse format".to_string(),
-        })?;
+        let format: i32 = format_str
+            .trim()
+            .parse()
+            .map_err(|_| Error::InvalidFormatFile {
+                path: format_path.to_string_lossy().to_string(),
+                error: "Failed to parse format".to_string(),
+            })?;
         if format != FORMAT_NUMBER && format != FORMAT_NUMBER_LEGACY {
             return Err(Error::InvalidFormat {
                 found: format,
@@ -372,6 +375,34 @@ impl Repos {
         Self::check_path(path)
     }
 
+    /// Take an exclusive lock on each of the repositories in @a paths to
+    /// prevent commits and then while holding all the locks invoke
+    /// @a freeze_func.  The repositories may be readable by Subversion
+    /// while frozen, or may be unreadable, depending on which FS backend
+    /// the repository uses.  Repositories are locked in the order in
+    /// which they are specified in the array.
+    ///
+    /// `svn_repos_freeze`
+    pub fn freeze(
+        paths: &[PathBuf],
+        freeze_func: &mut dyn FnMut() -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        // Each repository's freeze function takes the next one, so that
+        // @a freeze_func runs with all of them frozen.
+        //
+        // `multi_freeze`
+        let Some((path, rest)) = paths.split_first() else {
+            return freeze_func();
+        };
+
+        // Only validate the repository, there is no need to open it fully.
+        Self::default().check_repos_format(&path.to_string_lossy())?;
+
+        // Don't use the FS cache, it will be left blank anyway.
+        let fs = SvnFs::open(&path.join(DB_DIR), &FsConfig::default())?;
+        fs.freeze(|| Self::freeze(rest, freeze_func))
+    }
+
     /// There is, at present, nothing within the direct responsibility
     /// of libsvn_repos which requires locking.  For historical compatibility
     /// reasons, the BDB libsvn_fs backend does not do its own locking, expecting
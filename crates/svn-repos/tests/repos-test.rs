@@ -0,0 +1,68 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use svn_fs::FsConfig;
+use svn_fs::SvnFs;
+use svn_repos::Repos;
+
+/// Lay out a repository at @a path holding a FSFS filesystem, and
+/// return the filesystem.
+fn create_repos(path: &Path) -> SvnFs {
+    fs_err::create_dir_all(path).unwrap();
+    fs_err::write(path.join("format"), "5\n").unwrap();
+    SvnFs::create(&path.join("db"), &FsConfig::default()).unwrap()
+}
+
+/// Commit a new file at @a path to @a fs and return the new revision.
+fn commit_file(fs: &SvnFs, path: &str) -> i64 {
+    let txn = fs.begin_txn(fs.youngest_rev().unwrap()).unwrap();
+    txn.root().unwrap().make_file(path).unwrap();
+    txn.commit().unwrap()
+}
+
+// `freeze_and_commit` / `svn_repos_freeze`
+#[test]
+fn test_freeze() {
+    let dir = tempfile::tempdir().unwrap();
+    let paths: Vec<PathBuf> = ["a", "b"]
+        .iter()
+        .map(|name| dir.path().join(name))
+        .collect();
+    let fs = create_repos(&paths[0]);
+    create_repos(&paths[1]);
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        Repos::freeze(&paths, &mut || {
+            // Commits to any of the repositories wait for the freeze to end.
+            for path in &paths {
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    let fs = SvnFs::open(&path.join("db"), &FsConfig::default()).unwrap();
+                    sender.send(commit_file(&fs, "/f")).unwrap();
+                });
+            }
+            assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+            assert_eq!(fs.youngest_rev().unwrap(), 0);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(receiver.recv().unwrap(), 1);
+        assert_eq!(receiver.recv().unwrap(), 1);
+    });
+    assert_eq!(fs.youngest_rev().unwrap(), 1);
+
+    // Errors of the freeze function get passed through, and the
+    // repositories are thawed again.
+    let result = Repos::freeze(&paths, &mut || Err(svn_fs::Error::CrossFsCopy.into()));
+    assert!(matches!(
+        result,
+        Err(svn_repos::Error::Fs(svn_fs::Error::CrossFsCopy))
+    ));
+    assert_eq!(commit_file(&fs, "/g"), 2);
+
+    // Only repositories can be frozen.
+    assert!(Repos::freeze(&[dir.path().join("missing")], &mut || Ok(())).is_err());
+}
@@ -2,6 +2,9 @@
 //!
 //! configuration of internal caches
 
+use std::sync::LazyLock;
+use std::sync::RwLock;
+
 /// Cache resource settings. It controls what caches, in what size and
 /// how they will be created. The settings apply for the whole process.
 ///
@@ -9,18 +12,19 @@
 /// compatibility.
 ///
 /// `svn_cache_config_t`
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CacheConfig {
     /// total cache size in bytes. Please note that this is only soft limit
     /// to the total application memory usage and will be exceeded due to
     /// temporary objects and other program state.
     /// May be 0, resulting in default caching code being used.
-    cache_size: u64,
+    pub cache_size: u64,
 
     /// maximum number of files kept open
-    file_handle_count: usize,
+    pub file_handle_count: usize,
 
     ///  is this application guaranteed to be single-threaded?
-    single_threaded: bool,
+    pub single_threaded: bool,
 }
 
 impl Default for CacheConfig {
@@ -45,3 +49,23 @@ impl Default for CacheConfig {
         }
     }
 }
+
+/// Cache configuration. Initialize with default values.
+static CACHE_SETTINGS: LazyLock<RwLock<CacheConfig>> =
+    LazyLock::new(|| RwLock::new(CacheConfig::default()));
+
+/// Get the current cache configuration.
+///
+/// `svn_cache_config_get`
+pub fn cache_config_get() -> CacheConfig {
+    CACHE_SETTINGS.read().unwrap().clone()
+}
+
+/// Set the cache configuration.  Please note that it may not change the
+/// actual configuration *in use*.  Therefore, call it before reading data
+/// from any repo and call it only once.
+///
+/// `svn_cache_config_set`
+pub fn cache_config_set(settings: &CacheConfig) {
+    *CACHE_SETTINGS.write().unwrap() = settings.clone();
+}
@@ -0,0 +1,164 @@
+//! `subversion/libsvn_subr/inprocess-cache.c`
+//!
+//! A cache storage that keeps the values in process memory, within a
+//! fixed budget of bytes.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use super::CacheError;
+use super::CacheInfo;
+use super::CacheStorage;
+
+/// An in-process cache storage holding up to @a max_size bytes of values,
+/// as reported by its sizer function.  When full, the entries inserted
+/// first get evicted.
+///
+/// `inprocess_cache_t`
+pub struct InProcessCache<K, V> {
+    /// the entries, behind a lock serializing all accesses
+    entries: Mutex<Entries<K, V>>,
+
+    /// the maximum of the entries' total size
+    max_size: usize,
+
+    /// return the number of bytes a value occupies
+    sizer: fn(&V) -> usize,
+}
+
+/// The contents of an [`InProcessCache`].
+struct Entries<K, V> {
+    /// the cached values and their sizes
+    values: HashMap<K, (V, usize)>,
+
+    /// the keys in @a values, oldest first
+    order: VecDeque<K>,
+
+    /// the sum of the sizes in @a values
+    total_size: usize,
+}
+
+impl<K, V> Entries<K, V>
+where
+    K: Clone + Eq + Hash,
+{
+    /// Evict the oldest entries until @a size more bytes fit within
+    /// @a max_size.
+    fn make_room(&mut self, size: usize, max_size: usize) {
+        while self.total_size + size > max_size {
+            let Some(key) = self.order.pop_front() else {
+                break;
+            };
+            if let Some((_, old_size)) = self.values.remove(&key) {
+                self.total_size -= old_size;
+            }
+        }
+    }
+}
+
+impl<K, V> InProcessCache<K, V>
+where
+    K: Clone + Eq + Hash,
+{
+    /// Create an empty cache of @a max_size bytes, measuring the values
+    /// with @a sizer.
+    ///
+    /// `svn_cache__create_inprocess`
+    pub fn new(max_size: usize, sizer: fn(&V) -> usize) -> Self {
+        Self {
+            entries: Mutex::new(Entries {
+                values: HashMap::new(),
+                order: VecDeque::new(),
+                total_size: 0,
+            }),
+            max_size,
+            sizer,
+        }
+    }
+}
+
+impl<K, V> CacheStorage<K, V> for InProcessCache<K, V>
+where
+    K: Clone + Eq + Hash + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    fn get(&self, key: &K) -> Result<Option<V>, CacheError> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries.values.get(key).map(|(value, _)| value.clone()))
+    }
+
+    fn has_key(&self, key: &K) -> Result<bool, CacheError> {
+        Ok(self.entries.lock().unwrap().values.contains_key(key))
+    }
+
+    fn set(&self, key: K, value: V) -> Result<(), CacheError> {
+        let size = (self.sizer)(&value);
+        if !self.is_cachable(size) {
+            // Silently drop what does not fit; it is only a cache.
+            return Ok(());
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if let Some((_, old_size)) = entries.values.remove(&key) {
+            entries.total_size -= old_size;
+            entries.order.retain(|k| k != &key);
+        }
+        entries.make_room(size, self.max_size);
+
+        entries.order.push_back(key.clone());
+        entries.values.insert(key, (value, size));
+        entries.total_size += size;
+
+        Ok(())
+    }
+
+    fn is_cachable(&self, size: usize) -> bool {
+        size <= self.max_size
+    }
+
+    fn get_info(&self) -> CacheInfo {
+        let entries = self.entries.lock().unwrap();
+        CacheInfo {
+            item_count: entries.values.len() as u64,
+            total_size: entries.total_size,
+            capacity: self.max_size,
+        }
+    }
+
+    fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.values.clear();
+        entries.order.clear();
+        entries.total_size = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SvnCache;
+
+    #[test]
+    fn test_inprocess_cache() {
+        let cache = SvnCache::new(InProcessCache::new(10, |v: &String| v.len()));
+        cache.set(1, "abcd".to_string()).unwrap();
+        cache.set(2, "efgh".to_string()).unwrap();
+        assert_eq!(cache.get(&1).unwrap().as_deref(), Some("abcd"));
+
+        // The oldest entry makes room for the new one.
+        cache.set(3, "ijkl".to_string()).unwrap();
+        assert_eq!(cache.get(&1).unwrap(), None);
+        assert_eq!(cache.get(&3).unwrap().as_deref(), Some("ijkl"));
+
+        // Values larger than the whole cache are not kept.
+        assert!(!cache.is_cachable(11));
+        cache.set(4, "x".repeat(11)).unwrap();
+        assert_eq!(cache.get(&4).unwrap(), None);
+
+        let info = cache.get_info();
+        assert_eq!((info.item_count, info.total_size), (2, 8));
+        assert_eq!(cache.stats(), (4, 4, 2, 0));
+    }
+}
@@ -0,0 +1,487 @@
+//! `subversion/libsvn_subr/cache-membuffer.c`
+//!
+//! A single block of memory shared by all caches of the process.  Entries
+//! are stored in serialized form and charged by their size, so the total
+//! memory use stays within the budget given by [`CacheConfig`].  The
+//! buffer is split into segments with locks of their own, so threads
+//! working on different entries rarely contend.  Within a segment, entries
+//! get evicted following the CLOCK algorithm, with higher priority entries
+//! getting more chances to stay.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use super::CacheError;
+use super::CacheInfo;
+use super::CacheStorage;
+use super::config::CacheConfig;
+use crate::Checksum;
+use crate::ChecksumKind;
+use crate::fnv1a_32;
+
+/// The bookkeeping memory charged for every entry in addition to its key
+/// and data.
+const ENTRY_OVERHEAD: usize = 64;
+
+/// Don't create segments smaller than this.
+const MIN_SEGMENT_SIZE: usize = 0x10000;
+
+/// The maximum number of segments of a membuffer.
+const MAX_SEGMENT_COUNT: usize = 0x100;
+
+/// The share of a segment a single entry may take at most.
+const MAX_ENTRY_SHARE: usize = 4;
+
+/// How likely an entry shall survive in the cache.
+///
+/// `SVN_CACHE__MEMBUFFER_*_PRIORITY`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CachePriority {
+    /// Data that is unlikely to be needed again, e.g. txdelta windows
+    /// read while reconstructing a fulltext.  Never evicts entries of
+    /// higher priority.
+    Low,
+    /// The default priority.
+    Default,
+    /// Data that is expensive to rebuild or frequently used, e.g.
+    /// directories and node-revisions.
+    High,
+}
+
+impl CachePriority {
+    /// The number of times the clock hand passes an unused entry before
+    /// evicting it.
+    fn credits(self) -> u8 {
+        match self {
+            CachePriority::Low => 0,
+            CachePriority::Default => 1,
+            CachePriority::High => 3,
+        }
+    }
+}
+
+/// A single entry of a segment.
+///
+/// `entry_t`
+struct Entry {
+    /// the serialized value
+    data: Vec<u8>,
+    /// the memory charged for this entry
+    size: usize,
+    /// the priority the entry has been stored with
+    priority: CachePriority,
+    /// whether the entry has been read since the clock hand passed it
+    referenced: bool,
+    /// the remaining passes of the clock hand before eviction
+    credits: u8,
+}
+
+/// A part of the membuffer with its own lock.
+///
+/// `svn_membuffer_t`
+struct Segment {
+    /// the entries, keyed by their full key
+    entries: HashMap<Vec<u8>, Entry>,
+    /// the keys of @a entries in clock order, the hand being at the front
+    clock: VecDeque<Vec<u8>>,
+    /// the memory charged for all @a entries
+    used: usize,
+    /// the maximum of @a used
+    capacity: usize,
+}
+
+impl Segment {
+    /// Return a copy of the data stored for @a key and mark it as used.
+    fn get(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        let entry = self.entries.get_mut(key)?;
+        entry.referenced = true;
+        Some(entry.data.clone())
+    }
+
+    /// Evict entries until @a size more bytes fit for a new entry of
+    /// @a priority.  Entries of [`CachePriority::Low`] never evict entries
+    /// of higher priority; return `false` if only those are left.
+    ///
+    /// `ensure_data_insertable_l1`
+    fn make_room(&mut self, size: usize, priority: CachePriority) -> bool {
+        let mut skipped = 0;
+        while self.used + size > self.capacity {
+            if skipped >= self.clock.len() {
+                return false;
+            }
+            let Some(key) = self.clock.pop_front() else {
+                return false;
+            };
+            let Some(entry) = self.entries.get_mut(&key) else {
+                continue;
+            };
+
+            if priority == CachePriority::Low && entry.priority > priority {
+                skipped += 1;
+                self.clock.push_back(key);
+                continue;
+            }
+            if entry.referenced {
+                // Used recently: give it another round.
+                entry.referenced = false;
+                entry.credits = entry.priority.credits();
+                self.clock.push_back(key);
+                continue;
+            }
+            if entry.credits > 0 {
+                entry.credits -= 1;
+                self.clock.push_back(key);
+                continue;
+            }
+
+            let entry = self.entries.remove(&key).unwrap();
+            self.used -= entry.size;
+        }
+
+        true
+    }
+
+    /// Store @a data for @a key with @a priority, if it fits.
+    ///
+    /// `membuffer_cache_set_internal`
+    fn set(&mut self, key: Vec<u8>, data: Vec<u8>, priority: CachePriority) {
+        let size = key.len() + data.len() + ENTRY_OVERHEAD;
+        if size > self.capacity / MAX_ENTRY_SHARE {
+            return;
+        }
+
+        // Drop any old version of the entry.
+        if let Some(old) = self.entries.remove(&key) {
+            self.used -= old.size;
+            self.clock.retain(|k| k != &key);
+        }
+
+        if !self.make_room(size, priority) {
+            return;
+        }
+
+        self.clock.push_back(key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                data,
+                size,
+                priority,
+                referenced: false,
+                credits: priority.credits(),
+            },
+        );
+        self.used += size;
+    }
+
+    /// Drop all entries whose key starts with @a prefix.
+    fn remove_prefixed(&mut self, prefix: &[u8]) {
+        self.entries.retain(|key, entry| {
+            let keep = !key.starts_with(prefix);
+            if !keep {
+                self.used -= entry.size;
+            }
+            keep
+        });
+        self.clock.retain(|key| !key.starts_with(prefix));
+    }
+}
+
+/// A fixed amount of memory holding the serialized entries of any number
+/// of [`MembufferCache`]s.
+///
+/// `svn_membuffer_t`
+pub struct Membuffer {
+    segments: Vec<Mutex<Segment>>,
+}
+
+impl std::fmt::Debug for Membuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Membuffer")
+            .field("segments", &self.segments.len())
+            .finish()
+    }
+}
+
+impl Membuffer {
+    /// Create a membuffer of @a total_size bytes.  Unless @a thread_safe
+    /// is set, all data goes into a single segment.
+    ///
+    /// `svn_cache__membuffer_cache_create`
+    pub fn new(total_size: usize, thread_safe: bool) -> Self {
+        let segment_count = if thread_safe {
+            // A power of two, keeping segments at MIN_SEGMENT_SIZE or above.
+            let count = (total_size / MIN_SEGMENT_SIZE).clamp(1, MAX_SEGMENT_COUNT);
+            1 << count.ilog2()
+        } else {
+            1
+        };
+        let capacity = total_size / segment_count;
+
+        let segments = (0..segment_count)
+            .map(|_| {
+                Mutex::new(Segment {
+                    entries: HashMap::new(),
+                    clock: VecDeque::new(),
+                    used: 0,
+                    capacity,
+                })
+            })
+            .collect();
+
+        Self { segments }
+    }
+
+    /// Return the segment responsible for @a key.
+    ///
+    /// `get_group_index`
+    fn segment(&self, key: &[u8]) -> &Mutex<Segment> {
+        let index = fnv1a_32(key) as usize % self.segments.len();
+        &self.segments[index]
+    }
+
+    /// Return the largest entry, key and data together, this membuffer
+    /// can hold.
+    pub fn max_entry_size(&self) -> usize {
+        let capacity = self.segments[0].lock().unwrap().capacity;
+        (capacity / MAX_ENTRY_SHARE).saturating_sub(ENTRY_OVERHEAD)
+    }
+
+    /// Return the number of entries and the memory charged for them.
+    ///
+    /// `svn_cache__membuffer_get_global_segment_info`
+    pub fn get_info(&self) -> CacheInfo {
+        let mut info = CacheInfo {
+            item_count: 0,
+            total_size: 0,
+            capacity: 0,
+        };
+        for segment in &self.segments {
+            let segment = segment.lock().unwrap();
+            info.item_count += segment.entries.len() as u64;
+            info.total_size += segment.used;
+            info.capacity += segment.capacity;
+        }
+        info
+    }
+}
+
+/// The membuffer shared by all caches of the process, or `None` if
+/// caching has been disabled by a zero [`CacheConfig::cache_size`].
+static GLOBAL_MEMBUFFER: LazyLock<Option<Arc<Membuffer>>> = LazyLock::new(|| {
+    let config: CacheConfig = super::config::cache_config_get();
+    (config.cache_size > 0).then(|| {
+        Arc::new(Membuffer::new(
+            config.cache_size as usize,
+            !config.single_threaded,
+        ))
+    })
+});
+
+/// Return the membuffer shared by all caches of the process, created on
+/// first use from the cache configuration then in effect.  Return `None`
+/// if caching has been disabled.
+///
+/// `svn_cache__get_global_membuffer_cache`
+pub fn get_global_membuffer_cache() -> Option<Arc<Membuffer>> {
+    GLOBAL_MEMBUFFER.clone()
+}
+
+/// Keys of serialized caches.
+pub trait CacheKey {
+    /// Return the key as bytes, unique among all keys of the cache.
+    fn key_bytes(&self) -> Vec<u8>;
+}
+
+impl CacheKey for i64 {
+    fn key_bytes(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+}
+
+impl CacheKey for u64 {
+    fn key_bytes(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+}
+
+impl CacheKey for String {
+    fn key_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+}
+
+/// Serialize a value for a [`MembufferCache`].
+///
+/// `svn_cache__serialize_func_t`
+pub type SerializeFn<V> = fn(&V) -> Vec<u8>;
+
+/// Restore a value serialized by the matching [`SerializeFn`].
+///
+/// `svn_cache__deserialize_func_t`
+pub type DeserializeFn<V> = fn(&[u8]) -> Result<V, CacheError>;
+
+/// A cache storing its values serialized in a [`Membuffer`].  Its keys
+/// are prefixed with a fingerprint of its name, so any number of caches
+/// can share the same membuffer.
+///
+/// `svn_membuffer_cache_t`
+pub struct MembufferCache<K, V> {
+    /// where the entries are stored
+    membuffer: Arc<Membuffer>,
+    /// the fingerprint of the cache name, prepended to all keys
+    prefix: Vec<u8>,
+    /// the priority of all entries of this cache
+    priority: CachePriority,
+    /// turns values into bytes
+    serialize: SerializeFn<V>,
+    /// turns bytes back into values
+    deserialize: DeserializeFn<V>,
+    _key: PhantomData<fn(&K)>,
+}
+
+impl<K, V> MembufferCache<K, V> {
+    /// Create a cache named @a prefix within @a membuffer, storing its
+    /// entries with @a priority.
+    ///
+    /// `svn_cache__create_membuffer_cache`
+    pub fn new(
+        membuffer: Arc<Membuffer>,
+        prefix: &str,
+        priority: CachePriority,
+        serialize: SerializeFn<V>,
+        deserialize: DeserializeFn<V>,
+    ) -> Self {
+        let prefix = Checksum::compute(ChecksumKind::Md5, prefix.as_bytes())
+            .digest()
+            .to_vec();
+        Self {
+            membuffer,
+            prefix,
+            priority,
+            serialize,
+            deserialize,
+            _key: PhantomData,
+        }
+    }
+
+    /// Return the key of @a key within the membuffer.
+    ///
+    /// `combine_key`
+    fn combine_key(&self, key: &K) -> Vec<u8>
+    where
+        K: CacheKey,
+    {
+        let mut full_key = self.prefix.clone();
+        full_key.extend_from_slice(&key.key_bytes());
+        full_key
+    }
+}
+
+impl<K, V> CacheStorage<K, V> for MembufferCache<K, V>
+where
+    K: CacheKey + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    fn get(&self, key: &K) -> Result<Option<V>, CacheError> {
+        let key = self.combine_key(key);
+        let data = self.membuffer.segment(&key).lock().unwrap().get(&key);
+        data.map(|data| (self.deserialize)(&data)).transpose()
+    }
+
+    fn has_key(&self, key: &K) -> Result<bool, CacheError> {
+        let key = self.combine_key(key);
+        let segment = self.membuffer.segment(&key).lock().unwrap();
+        Ok(segment.entries.contains_key(&key))
+    }
+
+    fn set(&self, key: K, value: V) -> Result<(), CacheError> {
+        let key = self.combine_key(&key);
+        let data = (self.serialize)(&value);
+        let mut segment = self.membuffer.segment(&key).lock().unwrap();
+        segment.set(key, data, self.priority);
+        Ok(())
+    }
+
+    fn is_cachable(&self, size: usize) -> bool {
+        size <= self.membuffer.max_entry_size()
+    }
+
+    fn get_info(&self) -> CacheInfo {
+        self.membuffer.get_info()
+    }
+
+    fn clear(&self) {
+        for segment in &self.membuffer.segments {
+            segment.lock().unwrap().remove_prefixed(&self.prefix);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SvnCache;
+
+    fn serialize(value: &String) -> Vec<u8> {
+        value.as_bytes().to_vec()
+    }
+
+    fn deserialize(data: &[u8]) -> Result<String, CacheError> {
+        String::from_utf8(data.to_vec()).map_err(|e| CacheError::SerializationError(e.to_string()))
+    }
+
+    fn cache(
+        membuffer: &Arc<Membuffer>,
+        name: &str,
+        priority: CachePriority,
+    ) -> SvnCache<u64, String> {
+        SvnCache::new(MembufferCache::new(
+            membuffer.clone(),
+            name,
+            priority,
+            serialize,
+            deserialize,
+        ))
+    }
+
+    #[test]
+    fn test_membuffer_cache() {
+        let membuffer = Arc::new(Membuffer::new(4 * MIN_SEGMENT_SIZE, true));
+        let texts = cache(&membuffer, "texts", CachePriority::Default);
+        let dirs = cache(&membuffer, "dirs", CachePriority::High);
+        let windows = cache(&membuffer, "windows", CachePriority::Low);
+
+        // Caches sharing the membuffer don't see each other's entries.
+        texts.set(1, "text".to_string()).unwrap();
+        dirs.set(1, "dir".to_string()).unwrap();
+        assert_eq!(texts.get(&1).unwrap().as_deref(), Some("text"));
+        assert_eq!(dirs.get(&1).unwrap().as_deref(), Some("dir"));
+        assert_eq!(windows.get(&1).unwrap(), None);
+
+        // Entries are charged by their serialized size.
+        let info = texts.get_info();
+        assert_eq!(info.item_count, 2);
+        assert_eq!(info.total_size, 2 * (16 + 8 + ENTRY_OVERHEAD) + 4 + 3);
+        assert_eq!(info.capacity, 4 * MIN_SEGMENT_SIZE);
+
+        // Flooding the cache keeps it within its budget, and low priority
+        // entries don't push out high priority ones.
+        for i in 0..100_000 {
+            windows.set(i, "x".repeat(100)).unwrap();
+        }
+        let info = windows.get_info();
+        assert!(info.total_size <= info.capacity);
+        assert!(info.item_count < 100_000);
+        assert_eq!(dirs.get(&1).unwrap().as_deref(), Some("dir"));
+
+        // Entries too large for a segment are not stored at all.
+        assert!(!texts.is_cachable(MIN_SEGMENT_SIZE));
+        texts.set(2, "x".repeat(MIN_SEGMENT_SIZE)).unwrap();
+        assert_eq!(texts.get(&2).unwrap(), None);
+    }
+}
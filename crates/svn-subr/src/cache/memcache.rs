@@ -1,5 +1,570 @@
-//! `svn_memcache_t`
+//! `subversion/libsvn_subr/cache-memcache.c`
+//!
+//! A cache storage on memcached servers, talking the memcached text
+//! protocol.  Keys are spread over the servers by consistent hashing, so
+//! adding or removing a server only moves a small share of them.
 
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use super::CacheError;
+use super::CacheInfo;
+use super::CacheKey;
+use super::CacheStorage;
+use super::DeserializeFn;
+use super::SerializeFn;
+use crate::Checksum;
+use crate::ChecksumKind;
+use crate::SvnConfig;
+use crate::fnv1a_32;
+
+/// The longest key memcached accepts.
+pub const MAX_MEMCACHED_KEY_LEN: usize = 250;
+
+/// Keys longer than @a MAX_MEMCACHED_KEY_LEN get cut to this length and
+/// the hex MD5 digest of the full key appended.
+const MEMCACHED_KEY_UNHASHED_LEN: usize = MAX_MEMCACHED_KEY_LEN - 32;
+
+/// The largest value memcached stores by default.
+const MAX_MEMCACHED_ITEM_SIZE: usize = 1000 * 1000;
+
+/// How many points each server gets on the hash ring.
+const POINTS_PER_SERVER: u32 = 100;
+
+/// How long to wait for a memcached server by default.
+pub const DEFAULT_MEMCACHED_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to leave a server alone after talking to it failed.
+const SERVER_RETRY_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The most idle connections kept open to each server.
+const MAX_IDLE_CONNECTIONS: usize = 8;
+
+/// The section of the configuration listing the memcached servers.
+pub const CONFIG_SECTION_MEMCACHED_SERVERS: &str = "memcached-servers";
+
+/// The section of the configuration holding the cache options.
+pub const CONFIG_SECTION_CACHES: &str = "caches";
+
+/// The option giving the memcached timeout in milliseconds.
+pub const CONFIG_OPTION_MEMCACHED_TIMEOUT: &str = "memcached-timeout";
+
+/// A memcached server and the idle connections to it.
+struct Server {
+    /// the server as `HOST:PORT`
+    address: String,
+    /// the open connections not used by any request.  The lock is only
+    /// held to take or return one, never while talking to the server.
+    idle: Mutex<Vec<BufReader<TcpStream>>>,
+    /// when to try the server again after it failed, if it did
+    down_until: Mutex<Option<Instant>>,
+}
+
+impl Server {
+    /// Open a connection to this server.
+    fn connect(&self, timeout: Duration) -> std::io::Result<BufReader<TcpStream>> {
+        let mut last_err = None;
+        for addr in self.address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(timeout))?;
+                    stream.set_write_timeout(Some(timeout))?;
+                    stream.set_nodelay(true)?;
+                    return Ok(BufReader::new(stream));
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Cannot resolve '{}'", self.address),
+            )
+        }))
+    }
+
+    /// Run @a request on a connection to this server, reusing an idle
+    /// one if possible.  Connections that fail get dropped, they may be
+    /// out of sync, and the server is skipped for a while after I/O
+    /// errors rather than making every request wait for it to time out.
+    fn request<T>(
+        &self,
+        timeout: Duration,
+        request: impl FnOnce(&mut BufReader<TcpStream>) -> Result<T, CacheError>,
+    ) -> Result<T, CacheError> {
+        if let Some(until) = *self.down_until.lock().unwrap()
+            && Instant::now() < until
+        {
+            return Err(CacheError::Memcache(format!(
+                "memcached server '{}' is down",
+                self.address
+            )));
+        }
+
+        let idle = self.idle.lock().unwrap().pop();
+        let result = match idle {
+            Some(stream) => Ok(stream),
+            None => self.connect(timeout).map_err(CacheError::from),
+        }
+        .and_then(|mut stream| request(&mut stream).map(|value| (stream, value)));
+
+        match result {
+            Ok((stream, value)) => {
+                let mut idle = self.idle.lock().unwrap();
+                if idle.len() < MAX_IDLE_CONNECTIONS {
+                    idle.push(stream);
+                }
+                *self.down_until.lock().unwrap() = None;
+                Ok(value)
+            }
+            Err(err) => {
+                if matches!(err, CacheError::Io(_)) {
+                    *self.down_until.lock().unwrap() = Some(Instant::now() + SERVER_RETRY_INTERVAL);
+                }
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Read a line of a memcached reply, without its CRLF.
+fn read_reply_line(stream: &mut BufReader<TcpStream>) -> Result<String, CacheError> {
+    let mut line = String::new();
+    stream.read_line(&mut line)?;
+    match line.strip_suffix("\r\n") {
+        Some(line) => Ok(line.to_string()),
+        None => Err(CacheError::Memcache(format!(
+            "Unexpected end of memcached reply '{line}'"
+        ))),
+    }
+}
+
+/// The configured memcached servers.  Clones share the servers and their
+/// connections.
+///
 /// `svn_memcache_t`
-#[derive(Debug)]
-pub struct MemCache {}
+#[derive(Clone)]
+pub struct MemCache {
+    /// the servers
+    servers: Arc<Vec<Server>>,
+    /// the points of the servers on the hash ring
+    ring: Arc<BTreeMap<u32, usize>>,
+    /// how long to wait for connections, requests and replies
+    timeout: Duration,
+}
+
+impl std::fmt::Debug for MemCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let servers: Vec<&str> = self.servers.iter().map(|s| s.address.as_str()).collect();
+        f.debug_struct("MemCache")
+            .field("servers", &servers)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl MemCache {
+    /// Use the memcached @a servers, each given as `HOST:PORT`, waiting at
+    /// most @a timeout for any of them.  No connection is made yet.
+    pub fn new(servers: &[&str], timeout: Duration) -> Result<Self, CacheError> {
+        if servers.is_empty() {
+            return Err(CacheError::BadServerSpecification(String::new()));
+        }
+
+        let mut ring = BTreeMap::new();
+        let mut list = Vec::new();
+        for (index, &address) in servers.iter().enumerate() {
+            // Check that this looks like HOST:PORT.
+            match address.rsplit_once(':') {
+                Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {}
+                _ => return Err(CacheError::BadServerSpecification(address.to_string())),
+            }
+
+            for point in 0..POINTS_PER_SERVER {
+                ring.insert(fnv1a_32(format!("{address}-{point}").as_bytes()), index);
+            }
+            list.push(Server {
+                address: address.to_string(),
+                idle: Mutex::default(),
+                down_until: Mutex::default(),
+            });
+        }
+
+        Ok(Self {
+            servers: Arc::new(list),
+            ring: Arc::new(ring),
+            timeout,
+        })
+    }
+
+    /// Return the server responsible for @a key: the first one at or
+    /// behind the key's hash on the ring.
+    fn server(&self, key: &str) -> &Server {
+        let hash = fnv1a_32(key.as_bytes());
+        let (_, &index) = self
+            .ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .unwrap();
+        &self.servers[index]
+    }
+
+    /// Return the value stored for @a key, if any.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        self.server(key).request(self.timeout, |stream| {
+            stream
+                .get_mut()
+                .write_all(format!("get {key}\r\n").as_bytes())?;
+
+            let line = read_reply_line(stream)?;
+            if line == "END" {
+                return Ok(None);
+            }
+
+            // VALUE <key> <flags> <bytes>
+            let len = match line.split(' ').collect::<Vec<_>>()[..] {
+                ["VALUE", k, _, len] if k == key => len.parse::<usize>().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| CacheError::Memcache(format!("Unexpected memcached reply '{line}'")))?;
+
+            let mut data = vec![0; len + 2];
+            stream.read_exact(&mut data)?;
+            if !data.ends_with(b"\r\n") || read_reply_line(stream)? != "END" {
+                return Err(CacheError::Memcache(
+                    "Malformed memcached value".to_string(),
+                ));
+            }
+            data.truncate(len);
+            Ok(Some(data))
+        })
+    }
+
+    /// Store @a data for @a key.
+    fn set(&self, key: &str, data: &[u8]) -> Result<(), CacheError> {
+        self.server(key).request(self.timeout, |stream| {
+            let mut request = format!("set {key} 0 0 {}\r\n", data.len()).into_bytes();
+            request.extend_from_slice(data);
+            request.extend_from_slice(b"\r\n");
+            stream.get_mut().write_all(&request)?;
+
+            match read_reply_line(stream)?.as_str() {
+                "STORED" => Ok(()),
+                reply => Err(CacheError::Memcache(format!(
+                    "Unexpected memcached reply '{reply}'"
+                ))),
+            }
+        })
+    }
+}
+
+/// Return the memcached servers listed in the `[memcached-servers]`
+/// section of @a config, or `None` if there are none.  Wait for them as
+/// long as `[caches] memcached-timeout` says.
+///
+/// `svn_cache__make_memcache_from_config`
+pub fn make_memcache_from_config(config: &SvnConfig) -> Result<Option<MemCache>, CacheError> {
+    let servers: Vec<&str> = config
+        .options(CONFIG_SECTION_MEMCACHED_SERVERS)
+        .into_iter()
+        .map(|(_, address)| address)
+        .collect();
+    if servers.is_empty() {
+        return Ok(None);
+    }
+
+    let default_timeout = DEFAULT_MEMCACHED_TIMEOUT.as_millis() as i64;
+    let timeout = config.get_i64(
+        CONFIG_SECTION_CACHES,
+        CONFIG_OPTION_MEMCACHED_TIMEOUT,
+        default_timeout,
+    )?;
+    if timeout <= 0 {
+        return Err(crate::config::ConfigError::InvalidInteger {
+            section: CONFIG_SECTION_CACHES.to_string(),
+            option: CONFIG_OPTION_MEMCACHED_TIMEOUT.to_string(),
+            value: timeout.to_string(),
+        }
+        .into());
+    }
+
+    MemCache::new(&servers, Duration::from_millis(timeout as u64)).map(Some)
+}
+
+/// A cache storing its values serialized on memcached servers.  Its keys
+/// are prefixed with its name.
+///
+/// `memcache_t`
+pub struct MemcacheCache<K, V> {
+    /// the servers
+    memcache: MemCache,
+    /// the escaped name of the cache, prepended to all keys
+    prefix: String,
+    /// turns values into bytes
+    serialize: SerializeFn<V>,
+    /// turns bytes back into values
+    deserialize: DeserializeFn<V>,
+    _key: PhantomData<fn(&K)>,
+}
+
+impl<K, V> MemcacheCache<K, V> {
+    /// Create a cache named @a prefix on the servers of @a memcache.
+    ///
+    /// `svn_cache__create_memcache`
+    pub fn new(
+        memcache: MemCache,
+        prefix: &str,
+        serialize: SerializeFn<V>,
+        deserialize: DeserializeFn<V>,
+    ) -> Self {
+        Self {
+            memcache,
+            prefix: escape_key(prefix.as_bytes()),
+            serialize,
+            deserialize,
+            _key: PhantomData,
+        }
+    }
+
+    /// Return the memcached key for @a key: the prefix and the escaped
+    /// key, hashed if that is too long for memcached.
+    ///
+    /// `build_key`
+    fn build_key(&self, key: &K) -> String
+    where
+        K: CacheKey,
+    {
+        let long_key = format!("{}:{}", self.prefix, escape_key(&key.key_bytes()));
+        if long_key.len() <= MAX_MEMCACHED_KEY_LEN {
+            return long_key;
+        }
+
+        // Keep the start of the key readable and make the rest unique.
+        let digest = Checksum::compute(ChecksumKind::Md5, long_key.as_bytes()).to_hex();
+        format!("{}{digest}", &long_key[..MEMCACHED_KEY_UNHASHED_LEN])
+    }
+}
+
+/// Return @a key with everything memcached doesn't allow in keys, i.e.
+/// whitespace and control characters, as well as `%`, URI-escaped.
+fn escape_key(key: &[u8]) -> String {
+    let mut escaped = String::with_capacity(key.len());
+    for &b in key {
+        if b.is_ascii_graphic() && b != b'%' {
+            escaped.push(b as char);
+        } else {
+            write!(escaped, "%{b:02X}").unwrap();
+        }
+    }
+    escaped
+}
+
+impl<K, V> CacheStorage<K, V> for MemcacheCache<K, V>
+where
+    K: CacheKey + Clone + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    fn get(&self, key: &K) -> Result<Option<V>, CacheError> {
+        let data = self.memcache.get(&self.build_key(key))?;
+        data.map(|data| (self.deserialize)(&data)).transpose()
+    }
+
+    fn has_key(&self, key: &K) -> Result<bool, CacheError> {
+        Ok(self.memcache.get(&self.build_key(key))?.is_some())
+    }
+
+    fn set(&self, key: K, value: V) -> Result<(), CacheError> {
+        let data = (self.serialize)(&value);
+        if !self.is_cachable(data.len()) {
+            return Ok(());
+        }
+        self.memcache.set(&self.build_key(&key), &data)
+    }
+
+    fn is_cachable(&self, size: usize) -> bool {
+        size <= MAX_MEMCACHED_ITEM_SIZE
+    }
+
+    fn get_info(&self) -> CacheInfo {
+        // The contents are only known to the servers.
+        CacheInfo {
+            item_count: 0,
+            total_size: 0,
+            capacity: 0,
+        }
+    }
+
+    fn clear(&self) {
+        // Other caches share the servers; "flush_all" would wipe them too.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::SvnCache;
+
+    /// The items stored on a fake memcached server.
+    type Items = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// Start a memcached server knowing just "get" and "set" on a thread
+    /// and return its address and its contents.
+    fn fake_memcached() -> (String, Items) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let items: Items = Arc::default();
+
+        let store = items.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let items = store.clone();
+                std::thread::spawn(move || {
+                    let mut stream = BufReader::new(stream.unwrap());
+                    let mut line = String::new();
+                    while stream.read_line(&mut line).unwrap() > 0 {
+                        let words: Vec<String> =
+                            line.split_whitespace().map(str::to_string).collect();
+                        line.clear();
+                        let reply = match &words.iter().map(String::as_str).collect::<Vec<_>>()[..]
+                        {
+                            ["get", key] => match items.lock().unwrap().get(*key) {
+                                Some(data) => {
+                                    let mut reply =
+                                        format!("VALUE {key} 0 {}\r\n", data.len()).into_bytes();
+                                    reply.extend_from_slice(data);
+                                    reply.extend_from_slice(b"\r\nEND\r\n");
+                                    reply
+                                }
+                                None => b"END\r\n".to_vec(),
+                            },
+                            ["set", key, _, _, len] => {
+                                assert!(key.len() <= MAX_MEMCACHED_KEY_LEN);
+                                let mut data = vec![0; len.parse::<usize>().unwrap() + 2];
+                                stream.read_exact(&mut data).unwrap();
+                                data.truncate(data.len() - 2);
+                                items.lock().unwrap().insert(key.to_string(), data);
+                                b"STORED\r\n".to_vec()
+                            }
+                            _ => b"ERROR\r\n".to_vec(),
+                        };
+                        stream.get_mut().write_all(&reply).unwrap();
+                    }
+                });
+            }
+        });
+
+        (address, items)
+    }
+
+    fn serialize(value: &String) -> Vec<u8> {
+        value.as_bytes().to_vec()
+    }
+
+    fn deserialize(data: &[u8]) -> Result<String, CacheError> {
+        String::from_utf8(data.to_vec()).map_err(|e| CacheError::SerializationError(e.to_string()))
+    }
+
+    #[test]
+    fn test_memcache() {
+        let (first, first_items) = fake_memcached();
+        let (second, second_items) = fake_memcached();
+        let memcache = MemCache::new(&[&first, &second], Duration::from_secs(1)).unwrap();
+
+        // Long names with odd characters still give valid keys.
+        let prefix = format!("fsfs: {}", "x".repeat(300));
+        let cache: SvnCache<u64, String> = SvnCache::new(MemcacheCache::new(
+            memcache,
+            &prefix,
+            serialize,
+            deserialize,
+        ));
+        for i in 0..50 {
+            cache.set(i, format!("value\r\n{i}")).unwrap();
+        }
+        for i in 0..50 {
+            assert_eq!(cache.get(&i).unwrap(), Some(format!("value\r\n{i}")));
+        }
+        assert_eq!(cache.get(&50).unwrap(), None);
+
+        // Both servers got a share of the keys.
+        let (first_count, second_count) = (
+            first_items.lock().unwrap().len(),
+            second_items.lock().unwrap().len(),
+        );
+        assert_eq!(first_count + second_count, 50);
+        assert!(first_count > 0 && second_count > 0);
+
+        // Unreachable servers fail the cache accesses, unless the errors
+        // get ignored.
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let memcache = MemCache::new(&[&format!("127.0.0.1:{port}")], Duration::from_secs(1));
+        let mut cache: SvnCache<u64, String> = SvnCache::new(MemcacheCache::new(
+            memcache.unwrap(),
+            "test",
+            serialize,
+            deserialize,
+        ));
+        assert!(cache.get(&1).is_err());
+        cache.set_error_handler(Arc::new(|_| Ok(())));
+        assert_eq!(cache.get(&1).unwrap(), None);
+        cache.set(1, "value".to_string()).unwrap();
+        assert_eq!(cache.stats().3, 3);
+
+        assert!(MemCache::new(&["no-port"], DEFAULT_MEMCACHED_TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn test_memcache_timeout() {
+        // A server that accepts connections but never replies.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            let connections: Vec<_> = listener.incoming().collect();
+            drop(connections);
+        });
+
+        let mut config = SvnConfig::default();
+        assert!(make_memcache_from_config(&config).unwrap().is_none());
+        config.set(CONFIG_SECTION_MEMCACHED_SERVERS, "hanging", &address);
+        config.set(
+            CONFIG_SECTION_CACHES,
+            CONFIG_OPTION_MEMCACHED_TIMEOUT,
+            "200",
+        );
+        let memcache = make_memcache_from_config(&config).unwrap().unwrap();
+        assert_eq!(memcache.timeout, Duration::from_millis(200));
+
+        // The first request waits for the timeout, later ones skip the
+        // server without waiting.
+        let cache: SvnCache<u64, String> =
+            SvnCache::new(MemcacheCache::new(memcache, "test", serialize, deserialize));
+        let start = Instant::now();
+        assert!(matches!(cache.get(&1), Err(CacheError::Io(_))));
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        let start = Instant::now();
+        assert!(matches!(cache.get(&1), Err(CacheError::Memcache(_))));
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        config.set(CONFIG_SECTION_CACHES, CONFIG_OPTION_MEMCACHED_TIMEOUT, "0");
+        assert!(make_memcache_from_config(&config).is_err());
+    }
+}
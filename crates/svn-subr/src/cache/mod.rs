@@ -1,14 +1,28 @@
 //! `svn_cache__t`
 
+mod inprocess;
+pub use inprocess::InProcessCache;
+
+mod membuffer;
+pub use membuffer::CacheKey;
+pub use membuffer::CachePriority;
+pub use membuffer::DeserializeFn;
+pub use membuffer::Membuffer;
+pub use membuffer::MembufferCache;
+pub use membuffer::SerializeFn;
+pub use membuffer::get_global_membuffer_cache;
+
 mod memcache;
 pub use memcache::MemCache;
+pub use memcache::MemcacheCache;
+pub use memcache::make_memcache_from_config;
 
 pub mod config;
 
 use std::fmt::Debug;
 
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, RwLock};
 
 #[allow(missing_docs)]
 #[derive(Debug, thiserror::Error)]
@@ -19,29 +33,59 @@ pub enum CacheError {
     KeyNotFound,
     #[error("serialization error: {0}")]
     SerializationError(String),
+    #[error("Invalid memcached server specification '{0}'")]
+    BadServerSpecification(String),
+    #[error("memcached error: {0}")]
+    Memcache(String),
+    #[error(transparent)]
+    Config(#[from] crate::config::ConfigError),
     #[error(transparent)]
     Io(#[from] std::io::Error),
 }
 
+/// Handle an error returned by a cache storage: return `Ok` to ignore
+/// it, treating the access as a cache miss, or pass it on.
+///
+/// `svn_cache__error_handler_t`
+pub type CacheErrorHandler = Arc<dyn Fn(CacheError) -> Result<(), CacheError> + Send + Sync>;
+
+/// A cache of values of type @a V, keyed by @a K.  Clones share the
+/// storage and the statistics.
+///
 /// `svn_cache__t`
 pub struct SvnCache<K, V>
 where
     K: Clone + Send + Sync + 'static,
     V: Clone + Send + Sync + 'static,
 {
-    /// storage
-    storage: Arc<RwLock<dyn CacheStorage<K, V> + Send + Sync>>,
+    /// storage, doing its own locking
+    storage: Arc<dyn CacheStorage<K, V> + Send + Sync>,
 
     /// error handler
-    error_handler: Option<Arc<dyn Fn(&CacheError) + Send + Sync>>,
+    error_handler: Option<CacheErrorHandler>,
 
     /// cache statistics
-    stats: CacheStats,
+    stats: Arc<CacheStats>,
 
     /// whether to pretend the cache is empty
     pretend_empty: bool,
 }
 
+impl<K, V> Clone for SvnCache<K, V>
+where
+    K: Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            error_handler: self.error_handler.clone(),
+            stats: self.stats.clone(),
+            pretend_empty: self.pretend_empty,
+        }
+    }
+}
+
 impl<K, V> Debug for SvnCache<K, V>
 where
     K: Clone + Send + Sync + 'static + Debug,
@@ -55,6 +99,9 @@ where
     }
 }
 
+/// The storage behind an [`SvnCache`].  Storages get shared between
+/// threads and do their own locking, so that accesses to unrelated
+/// entries can run in parallel.
 pub trait CacheStorage<K, V>
 where
     K: Clone + Send + Sync,
@@ -62,16 +109,23 @@ where
 {
     fn get(&self, key: &K) -> Result<Option<V>, CacheError>;
     fn has_key(&self, key: &K) -> Result<bool, CacheError>;
-    fn set(&mut self, key: K, value: V) -> Result<(), CacheError>;
+    fn set(&self, key: K, value: V) -> Result<(), CacheError>;
     fn is_cachable(&self, size: usize) -> bool;
     fn get_info(&self) -> CacheInfo;
-    fn clear(&mut self);
+    fn clear(&self);
 }
 
+/// Information about the contents of a cache storage.
+///
+/// `svn_cache__info_t`
 #[derive(Debug, Clone)]
 pub struct CacheInfo {
+    /// the number of entries
     pub item_count: u64,
+    /// the memory charged for the entries
     pub total_size: usize,
+    /// the memory available to the entries
+    pub capacity: usize,
 }
 
 #[derive(Default, Debug)]
@@ -87,18 +141,41 @@ where
     K: Clone + Send + Sync + 'static,
     V: Clone + Send + Sync + 'static,
 {
+    /// Create a cache backed by @a storage.
     pub fn new<T>(storage: T) -> Self
     where
         T: CacheStorage<K, V> + Send + Sync + 'static,
     {
         Self {
-            storage: Arc::new(RwLock::new(storage)),
+            storage: Arc::new(storage),
             error_handler: None,
-            stats: CacheStats::default(),
+            stats: Arc::default(),
             pretend_empty: false,
         }
     }
 
+    /// Install @a handler to deal with the errors of the storage.
+    ///
+    /// `svn_cache__set_error_handler`
+    pub fn set_error_handler(&mut self, handler: CacheErrorHandler) {
+        self.error_handler = Some(handler);
+    }
+
+    /// Count the failure @a err and pass it through the error handler.
+    ///
+    /// `handle_error`
+    fn handle_error(&self, err: CacheError) -> Result<(), CacheError> {
+        self.stats.failures.fetch_add(1, Ordering::Relaxed);
+        match &self.error_handler {
+            Some(handler) => handler(err),
+            None => Err(err),
+        }
+    }
+
+    /// Return the value cached for @a key, if any.  Ignored storage errors
+    /// count as misses.
+    ///
+    /// `svn_cache__get`
     pub fn get(&self, key: &K) -> Result<Option<V>, CacheError> {
         self.stats.reads.fetch_add(1, Ordering::Relaxed);
 
@@ -106,36 +183,43 @@ where
             return Ok(None);
         }
 
-        let storage = self.storage.read().unwrap();
-        match storage.get(key) {
+        let result = self.storage.get(key);
+        match result {
             Ok(Some(value)) => {
                 self.stats.hits.fetch_add(1, Ordering::Relaxed);
                 Ok(Some(value))
             }
             Ok(None) => Ok(None),
-            Err(e) => {
-                self.stats.failures.fetch_add(1, Ordering::Relaxed);
-                if let Some(handler) = &self.error_handler {
-                    handler(&e);
-                }
-                Err(e)
-            }
+            Err(e) => self.handle_error(e).map(|()| None),
         }
     }
 
+    /// Store @a value for @a key.
+    ///
+    /// `svn_cache__set`
     pub fn set(&self, key: K, value: V) -> Result<(), CacheError> {
         self.stats.writes.fetch_add(1, Ordering::Relaxed);
 
-        let mut storage = self.storage.write().unwrap();
-        storage.set(key, value).map_err(|e| {
-            self.stats.failures.fetch_add(1, Ordering::Relaxed);
-            if let Some(handler) = &self.error_handler {
-                handler(&e);
-            }
-            e
-        })
+        let result = self.storage.set(key, value);
+        result.or_else(|e| self.handle_error(e))
+    }
+
+    /// Return whether a value of @a size bytes could be cached at all.
+    ///
+    /// `svn_cache__is_cachable`
+    pub fn is_cachable(&self, size: usize) -> bool {
+        self.storage.is_cachable(size)
+    }
+
+    /// Return the number of entries and the bytes held by the storage.
+    ///
+    /// `svn_cache__get_info`
+    pub fn get_info(&self) -> CacheInfo {
+        self.storage.get_info()
     }
 
+    /// Return the number of reads, writes, hits and failures so far, in
+    /// that order.
     pub fn stats(&self) -> (u64, u64, u64, u64) {
         (
             self.stats.reads.load(Ordering::Relaxed),
@@ -1,6 +1,8 @@
 use md5::{Digest, Md5};
+use sha1::Sha1;
 
 /// Various types of checksums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ChecksumKind {
     /// The checksum is (or should be set to) an MD5 checksum.
     Md5,
@@ -37,6 +39,7 @@ impl ChecksumKind {
 }
 
 /// A generic checksum representation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Checksum {
     /// The bytes of the checksum.
     digest: Vec<u8>,
@@ -71,7 +74,84 @@ const FNV1A32_MODIFIED_EMPTY_STRING_DIGEST_ARRAY: &[u8; 4] = &[
     0xcd, 0x6d, 0x9a, 0x85
 ];
 
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+pub enum ChecksumError {
+    #[error("Invalid character in hex checksum")]
+    InvalidHex,
+
+    #[error("Checksum mismatch, expected: {expected}, actual: {actual}")]
+    Mismatch { expected: String, actual: String },
+}
+
+impl ChecksumKind {
+    /// The size of a digest of this kind, in bytes.
+    ///
+    /// `svn_checksum_size`
+    pub const fn digest_size(&self) -> usize {
+        match self {
+            ChecksumKind::Md5 => 16,
+            ChecksumKind::Sha1 => 20,
+            ChecksumKind::Fnv1a32 | ChecksumKind::Fnv1a32Modified => 4,
+        }
+    }
+}
+
 impl Checksum {
+    /// Create a checksum of @a kind from the raw @a digest bytes.
+    pub fn new(kind: ChecksumKind, digest: Vec<u8>) -> Self {
+        debug_assert_eq!(digest.len(), kind.digest_size());
+        Self { digest, kind }
+    }
+
+    /// Return the checksum of @a kind for @a data.
+    ///
+    /// `svn_checksum`
+    pub fn compute(kind: ChecksumKind, data: &[u8]) -> Self {
+        let digest = match kind {
+            ChecksumKind::Md5 => Md5::digest(data).to_vec(),
+            ChecksumKind::Sha1 => Sha1::digest(data).to_vec(),
+            ChecksumKind::Fnv1a32 => fnv1a_32(data).to_be_bytes().to_vec(),
+            ChecksumKind::Fnv1a32Modified => fnv1a_32x4(data).to_be_bytes().to_vec(),
+        };
+        Self { digest, kind }
+    }
+
+    /// Parse the hex representation @a hex of a checksum of @a kind.
+    /// An all-zero digest yields `None`, like Subversion does.
+    ///
+    /// `svn_checksum_parse_hex`
+    pub fn parse_hex(kind: ChecksumKind, hex: &str) -> Result<Option<Self>, ChecksumError> {
+        let digest = hex::decode(hex).map_err(|_| ChecksumError::InvalidHex)?;
+        if digest.len() != kind.digest_size() {
+            return Err(ChecksumError::InvalidHex);
+        }
+        if digest.iter().all(|&b| b == 0) {
+            return Ok(None);
+        }
+        Ok(Some(Self { digest, kind }))
+    }
+
+    /// Return the lowercase hex representation of the digest.
+    ///
+    /// `svn_checksum_to_cstring_display`
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.digest)
+    }
+
+    /// Return an error if @a actual doesn't match this checksum.
+    ///
+    /// `svn_checksum_mismatch_err`
+    pub fn verify(&self, actual: &Checksum) -> Result<(), ChecksumError> {
+        if self != actual {
+            return Err(ChecksumError::Mismatch {
+                expected: self.to_hex(),
+                actual: actual.to_hex(),
+            });
+        }
+        Ok(())
+    }
+
     /// Get the bytes of the checksum.
     pub fn digest(&self) -> &[u8] {
         &self.digest
@@ -83,6 +163,104 @@ impl Checksum {
     }
 }
 
+/// A context for computing a checksum incrementally.
+///
+/// `svn_checksum_ctx_t`
+#[derive(Debug, Clone)]
+pub struct ChecksumContext {
+    state: ChecksumState,
+}
+
+#[derive(Debug, Clone)]
+enum ChecksumState {
+    Md5(Md5),
+    Sha1(Sha1),
+    /// The FNV-1a variants are cheap enough to be computed in one go.
+    Buffered(ChecksumKind, Vec<u8>),
+}
+
+impl ChecksumContext {
+    /// Create a new checksum context of @a kind.
+    ///
+    /// `svn_checksum_ctx_create`
+    pub fn new(kind: ChecksumKind) -> Self {
+        let state = match kind {
+            ChecksumKind::Md5 => ChecksumState::Md5(Md5::new()),
+            ChecksumKind::Sha1 => ChecksumState::Sha1(Sha1::new()),
+            kind => ChecksumState::Buffered(kind, Vec::new()),
+        };
+        Self { state }
+    }
+
+    /// Add @a data to the checksum.
+    ///
+    /// `svn_checksum_update`
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            ChecksumState::Md5(ctx) => ctx.update(data),
+            ChecksumState::Sha1(ctx) => ctx.update(data),
+            ChecksumState::Buffered(_, buf) => buf.extend_from_slice(data),
+        }
+    }
+
+    /// Return the checksum of all data added so far.
+    ///
+    /// `svn_checksum_final`
+    pub fn finish(self) -> Checksum {
+        match self.state {
+            ChecksumState::Md5(ctx) => Checksum::new(ChecksumKind::Md5, ctx.finalize().to_vec()),
+            ChecksumState::Sha1(ctx) => Checksum::new(ChecksumKind::Sha1, ctx.finalize().to_vec()),
+            ChecksumState::Buffered(kind, buf) => Checksum::compute(kind, &buf),
+        }
+    }
+}
+
+const FNV1_PRIME_32: u32 = 0x01000193;
+const FNV1_BASE_32: u32 = 2166136261;
+
+/// Number of interleaved hashes in [`fnv1a_32x4`].
+const SCALING: usize = 4;
+
+/// Calculate the FNV-1a 32 bit checksum of @a input.
+///
+/// `svn__fnv1a_32`
+pub fn fnv1a_32(input: &[u8]) -> u32 {
+    fnv1a_32_update(FNV1_BASE_32, input)
+}
+
+fn fnv1a_32_update(mut hash: u32, input: &[u8]) -> u32 {
+    for &b in input {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(FNV1_PRIME_32);
+    }
+    hash
+}
+
+/// Calculate the modified FNV-1a 32 bit checksum of @a input: four
+/// interleaved FNV-1a hashes, combined by running FNV-1a over their big
+/// endian representation followed by the remaining tail bytes.
+///
+/// `svn__fnv1a_32x4`
+pub fn fnv1a_32x4(input: &[u8]) -> u32 {
+    let mut hashes = [FNV1_BASE_32; SCALING];
+
+    let processed = input.len() - input.len() % SCALING;
+    for chunk in input[..processed].chunks_exact(SCALING) {
+        for (hash, &b) in hashes.iter_mut().zip(chunk) {
+            *hash ^= b as u32;
+            *hash = hash.wrapping_mul(FNV1_PRIME_32);
+        }
+    }
+
+    let mut final_data = Vec::with_capacity(4 * SCALING + SCALING - 1);
+    for hash in hashes {
+        final_data.extend_from_slice(&hash.to_be_bytes());
+    }
+    final_data.extend_from_slice(&input[processed..]);
+
+    fnv1a_32(&final_data)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,4 +271,27 @@ mod tests {
         let result = hasher.finalize();
         assert_eq!(result.as_slice(), MD5_EMPTY_STRING_DIGEST_ARRAY);
     }
+
+    #[test]
+    fn test_empty_string_digests() {
+        for kind in [
+            ChecksumKind::Md5,
+            ChecksumKind::Sha1,
+            ChecksumKind::Fnv1a32,
+            ChecksumKind::Fnv1a32Modified,
+        ] {
+            let checksum = Checksum::compute(kind, b"");
+            assert_eq!(checksum.digest(), kind.empty_string_digest_array());
+        }
+    }
+
+    #[test]
+    fn test_checksum_context() {
+        for kind in [ChecksumKind::Md5, ChecksumKind::Sha1, ChecksumKind::Fnv1a32] {
+            let mut ctx = ChecksumContext::new(kind);
+            ctx.update(b"hello ");
+            ctx.update(b"world");
+            assert_eq!(ctx.finish(), Checksum::compute(kind, b"hello world"));
+        }
+    }
 }
@@ -0,0 +1,167 @@
+//! `compress_zlib.c` / `compress_lz4.c`
+//!
+//! The compressed data format used by svndiff1 / svndiff2 windows and
+//! packed revprops: the original length followed by either the zlib / lz4
+//! compressed data or, if compression did not help, the data as-is.
+
+use std::io::Read;
+use std::io::Write;
+
+/// Data shorter than this is never zlib compressed: the overhead would
+/// eat up any savings.
+///
+/// `MIN_COMPRESS_SIZE`
+const MIN_COMPRESS_SIZE: usize = 512;
+
+/// The zlib compression level that disables compression.
+///
+/// `SVN_DELTA_COMPRESSION_LEVEL_NONE`
+pub const COMPRESSION_LEVEL_NONE: i32 = 0;
+
+/// The default zlib compression level.
+///
+/// `SVN_DELTA_COMPRESSION_LEVEL_DEFAULT`
+pub const COMPRESSION_LEVEL_DEFAULT: i32 = 5;
+
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+pub enum CompressError {
+    #[error("Decompression of {0} compressed data failed")]
+    Decompression(&'static str),
+
+    #[error("Size of uncompressed data does not match stored original length")]
+    SizeMismatch,
+
+    #[error("Decompression of {0} compressed data failed: no size")]
+    NoSize(&'static str),
+}
+
+/// Decode the variable-length, big-endian (most significant 7-bit group
+/// first) number at @a pos in @a data and advance @a pos.  Return `None`
+/// if @a data ends within the number or it does not fit into a `usize`.
+///
+/// `svn__decode_uint`
+pub fn decode_uint(data: &[u8], pos: &mut usize) -> Option<usize> {
+    let mut value: usize = 0;
+    loop {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+
+        value = value
+            .checked_mul(0x80)?
+            .checked_add((byte & 0x7f) as usize)?;
+        if byte < 0x80 {
+            return Some(value);
+        }
+    }
+}
+
+/// Append the encoding of @a value as used by [`decode_uint`] to @a out.
+///
+/// `svn__encode_uint`
+pub fn encode_uint(out: &mut Vec<u8>, value: usize) {
+    let mut groups = vec![(value & 0x7f) as u8];
+    let mut rest = value >> 7;
+    while rest > 0 {
+        groups.push((rest & 0x7f) as u8 | 0x80);
+        rest >>= 7;
+    }
+    out.extend(groups.iter().rev());
+}
+
+/// Append @a data to @a out, using @a compressed instead if that is
+/// actually shorter.
+fn append_smaller(out: &mut Vec<u8>, data: &[u8], compressed: Option<Vec<u8>>) {
+    encode_uint(out, data.len());
+    match compressed {
+        Some(compressed) if compressed.len() < data.len() => out.extend_from_slice(&compressed),
+        // Compression didn't help :(, just append the original text.
+        _ => out.extend_from_slice(data),
+    }
+}
+
+/// Return @a data compressed with zlib at @a compression_level.  A level
+/// of [`COMPRESSION_LEVEL_NONE`] stores the data as-is.
+///
+/// `svn__compress_zlib`
+pub fn compress_zlib(data: &[u8], compression_level: i32) -> Vec<u8> {
+    let compressed =
+        if data.len() < MIN_COMPRESS_SIZE || compression_level == COMPRESSION_LEVEL_NONE {
+            None
+        } else {
+            let level = flate2::Compression::new(compression_level.clamp(0, 9) as u32);
+            let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+            encoder.write_all(data).and_then(|_| encoder.finish()).ok()
+        };
+
+    let mut out = Vec::new();
+    append_smaller(&mut out, data, compressed);
+    out
+}
+
+/// Return @a data compressed with lz4.
+///
+/// `svn__compress_lz4`
+pub fn compress_lz4(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    append_smaller(&mut out, data, Some(lz4_flex::block::compress(data)));
+    out
+}
+
+/// Split @a data into the original length and the (compressed) payload,
+/// checking the length against @a limit.
+fn split_compressed<'a>(
+    data: &'a [u8],
+    limit: usize,
+    name: &'static str,
+) -> Result<(usize, &'a [u8]), CompressError> {
+    let mut pos = 0;
+    let len = decode_uint(data, &mut pos).ok_or(CompressError::NoSize(name))?;
+    if len > limit {
+        return Err(CompressError::Decompression(name));
+    }
+    Ok((len, &data[pos..]))
+}
+
+/// Undo [`compress_zlib`].  Fail if the original data would be longer
+/// than @a limit.
+///
+/// `svn__decompress_zlib`
+pub fn decompress_zlib(data: &[u8], limit: usize) -> Result<Vec<u8>, CompressError> {
+    let (len, compressed) = split_compressed(data, limit, "zlib")?;
+
+    // We need to subtract the size of the encoded original length off the
+    // still remaining input length.  If that matches the original length,
+    // the data has been stored uncompressed.
+    if compressed.len() == len {
+        return Ok(compressed.to_vec());
+    }
+
+    let mut out = Vec::with_capacity(len);
+    flate2::read::ZlibDecoder::new(compressed)
+        .take(len as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|_| CompressError::Decompression("zlib"))?;
+    if out.len() != len {
+        return Err(CompressError::SizeMismatch);
+    }
+    Ok(out)
+}
+
+/// Undo [`compress_lz4`].  Fail if the original data would be longer than
+/// @a limit.
+///
+/// `svn__decompress_lz4`
+pub fn decompress_lz4(data: &[u8], limit: usize) -> Result<Vec<u8>, CompressError> {
+    let (len, compressed) = split_compressed(data, limit, "lz4")?;
+    if compressed.len() == len {
+        return Ok(compressed.to_vec());
+    }
+
+    let out = lz4_flex::block::decompress(compressed, len)
+        .map_err(|_| CompressError::Decompression("lz4"))?;
+    if out.len() != len {
+        return Err(CompressError::SizeMismatch);
+    }
+    Ok(out)
+}
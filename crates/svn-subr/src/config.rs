@@ -1,18 +1,142 @@
+use std::collections::HashMap;
 use std::path::Path;
 
 /// Opaque structure describing a set of configuration options.
 ///
+/// Section and option names are matched case-insensitively, like
+/// `svn_config_read3(..., FALSE, FALSE)` does.
+///
 /// `svn_config_t`
-#[derive(Debug)]
-pub struct SvnConfig {}
+#[derive(Debug, Default, Clone)]
+pub struct SvnConfig {
+    /// section name -> (option name -> value)
+    sections: HashMap<String, HashMap<String, String>>,
+}
 
 #[allow(missing_docs)]
 #[derive(Debug, thiserror::Error)]
-pub enum ConfigError {}
+pub enum ConfigError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("{path}:{line}: Option expected")]
+    OptionExpected { path: String, line: usize },
+
+    #[error("{path}:{line}: Section header must start in the first column")]
+    SectionHeaderIndented { path: String, line: usize },
+
+    #[error("{path}:{line}: Section header expected")]
+    SectionHeaderExpected { path: String, line: usize },
+
+    #[error("Config error: invalid boolean value '{value}' for '[{section}] {option}'")]
+    InvalidBool {
+        section: String,
+        option: String,
+        value: String,
+    },
+
+    #[error("Config error: invalid integer value '{value}' for '[{section}] {option}'")]
+    InvalidInteger {
+        section: String,
+        option: String,
+        value: String,
+    },
+}
 
 impl SvnConfig {
-    pub fn from_path(path: &Path) -> Self {
-        todo!()
+    /// Read configuration data from @a path. A missing file results in an
+    /// empty configuration.
+    ///
+    /// `svn_config_read3`
+    pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        match fs_err::read_to_string(path) {
+            Ok(content) => Self::parse(&content, &path.to_string_lossy()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Parse the INI-like text in @a content. @a path is only used for
+    /// error messages.
+    ///
+    /// `svn_config__parse_stream`
+    pub fn parse(content: &str, path: &str) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        let mut section: Option<String> = None;
+        let mut last_option: Option<String> = None;
+
+        for (idx, line) in content.lines().enumerate() {
+            let line_no = idx + 1;
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || line.starts_with('#') {
+                last_option = None;
+                continue;
+            }
+
+            if line.starts_with('[') {
+                let end = line.find(']').ok_or(ConfigError::SectionHeaderExpected {
+                    path: path.to_string(),
+                    line: line_no,
+                })?;
+                let name = line[1..end].trim().to_lowercase();
+                config.sections.entry(name.clone()).or_default();
+                section = Some(name);
+                last_option = None;
+                continue;
+            }
+
+            if line.starts_with(char::is_whitespace) {
+                // Continuation of the previous option's value.
+                if trimmed.starts_with('[') {
+                    return Err(ConfigError::SectionHeaderIndented {
+                        path: path.to_string(),
+                        line: line_no,
+                    });
+                }
+                match (&section, &last_option) {
+                    (Some(section), Some(option)) => {
+                        let value = config
+                            .sections
+                            .get_mut(section)
+                            .and_then(|s| s.get_mut(option))
+                            .expect("option was just inserted");
+                        value.push(' ');
+                        value.push_str(trimmed);
+                        continue;
+                    }
+                    _ => {
+                        return Err(ConfigError::OptionExpected {
+                            path: path.to_string(),
+                            line: line_no,
+                        });
+                    }
+                }
+            }
+
+            let Some(section) = &section else {
+                return Err(ConfigError::SectionHeaderExpected {
+                    path: path.to_string(),
+                    line: line_no,
+                });
+            };
+
+            let sep = line.find([':', '=']).ok_or(ConfigError::OptionExpected {
+                path: path.to_string(),
+                line: line_no,
+            })?;
+            let option = line[..sep].trim().to_lowercase();
+            let value = line[sep + 1..].trim().to_string();
+
+            config
+                .sections
+                .get_mut(section)
+                .expect("section was just inserted")
+                .insert(option.clone(), value);
+            last_option = Some(option);
+        }
+
+        Ok(config)
     }
 
     /// load the configuration from the system registry path
@@ -28,12 +152,45 @@ impl SvnConfig {
 
     /// load the configuration from the system config file
     pub fn load_from_sys_config_file(sys_config_file: &str) -> Result<SvnConfig, ConfigError> {
-        todo!()
+        Self::from_path(Path::new(sys_config_file))
     }
 
     /// load the configuration from the user config file
     pub fn load_from_user_config_file(user_config_file: &str) -> Result<SvnConfig, ConfigError> {
-        todo!()
+        Self::from_path(Path::new(user_config_file))
+    }
+
+    /// Return the value of @a option in @a section, if set.
+    ///
+    /// `svn_config_get`
+    pub fn get(&self, section: &str, option: &str) -> Option<&str> {
+        self.sections
+            .get(&section.to_lowercase())
+            .and_then(|s| s.get(&option.to_lowercase()))
+            .map(String::as_str)
+    }
+
+    /// Set @a option in @a section to @a value.
+    ///
+    /// `svn_config_set`
+    pub fn set(&mut self, section: &str, option: &str, value: &str) {
+        self.sections
+            .entry(section.to_lowercase())
+            .or_default()
+            .insert(option.to_lowercase(), value.to_string());
+    }
+
+    /// Return the names of all options in @a section.
+    ///
+    /// `svn_config_enumerate2`
+    pub fn options(&self, section: &str) -> Vec<(&str, &str)> {
+        let mut ret: Vec<(&str, &str)> = self
+            .sections
+            .get(&section.to_lowercase())
+            .map(|s| s.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect())
+            .unwrap_or_default();
+        ret.sort();
+        ret
     }
 
     /// `svn_config_get_bool`
@@ -43,7 +200,22 @@ impl SvnConfig {
         option: &str,
         default_value: bool,
     ) -> Result<bool, ConfigError> {
-        todo!()
+        let Some(value) = self.get(section, option) else {
+            return Ok(default_value);
+        };
+        if value.is_empty() {
+            return Ok(default_value);
+        }
+
+        match value.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Ok(true),
+            "false" | "no" | "off" | "0" => Ok(false),
+            _ => Err(ConfigError::InvalidBool {
+                section: section.to_string(),
+                option: option.to_string(),
+                value: value.to_string(),
+            }),
+        }
     }
 
     /// `svn_config_get_int64`
@@ -53,6 +225,17 @@ impl SvnConfig {
         option: &str,
         default_value: i64,
     ) -> Result<i64, ConfigError> {
-        todo!()
+        let Some(value) = self.get(section, option) else {
+            return Ok(default_value);
+        };
+        if value.is_empty() {
+            return Ok(default_value);
+        }
+
+        value.parse().map_err(|_| ConfigError::InvalidInteger {
+            section: section.to_string(),
+            option: option.to_string(),
+            value: value.to_string(),
+        })
     }
 }
@@ -1,5 +1,20 @@
 use std::collections::HashMap;
 
+/// The conventional terminator for hash dumps.
+///
+/// `SVN_HASH_TERMINATOR`
+pub const HASH_TERMINATOR: &str = "END";
+
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+pub enum HashError {
+    #[error("Serialized hash malformed")]
+    Malformed,
+
+    #[error("Serialized hash missing terminator")]
+    MissingTerminator,
+}
+
 /// `svn_hash__get_bool`
 pub fn get_bool(map: &HashMap<String, String>, key: &str, default_value: bool) -> bool {
     match map.get(key) {
@@ -7,3 +22,134 @@ pub fn get_bool(map: &HashMap<String, String>, key: &str, default_value: bool) -
         None => default_value,
     }
 }
+
+/// Dump @a hash in the "K len / V len" format, sorted by key and followed
+/// by @a terminator.
+///
+/// `svn_hash_write2`
+pub fn write_hash(hash: &HashMap<String, String>, terminator: &str) -> String {
+    let mut keys: Vec<&String> = hash.keys().collect();
+    keys.sort();
+
+    let mut out = String::new();
+    for key in keys {
+        let value = &hash[key];
+        out.push_str(&format!(
+            "K {}\n{}\nV {}\n{}\n",
+            key.len(),
+            key,
+            value.len(),
+            value
+        ));
+    }
+    out.push_str(terminator);
+    out.push('\n');
+    out
+}
+
+/// Read a hash dump written by [`write_hash`] (or
+/// [`write_hash_incremental`]) from the start of @a data.
+///
+/// Returns the hash and the number of bytes consumed including the
+/// terminator line. Deletion entries ("D len") remove keys read earlier.
+///
+/// `svn_hash_read2`
+pub fn read_hash(
+    data: &[u8],
+    terminator: &str,
+) -> Result<(HashMap<String, String>, usize), HashError> {
+    let mut hash = HashMap::new();
+    let pos = read_hash_incremental(&mut hash, data, Some(terminator))?;
+    Ok((hash, pos))
+}
+
+/// Apply the entries of the (possibly incremental) hash dump at the start
+/// of @a data to @a hash.  Stop at the @a terminator line or, if that is
+/// `None`, at the end of @a data.
+///
+/// Returns the number of bytes consumed.
+///
+/// `svn_hash_read_incremental`
+pub fn read_hash_incremental(
+    hash: &mut HashMap<String, String>,
+    data: &[u8],
+    terminator: Option<&str>,
+) -> Result<usize, HashError> {
+    let mut pos = 0;
+
+    loop {
+        if terminator.is_none() && pos == data.len() {
+            return Ok(pos);
+        }
+        let line = read_line(data, &mut pos).ok_or(HashError::MissingTerminator)?;
+        if terminator.is_some_and(|t| line == t.as_bytes()) {
+            return Ok(pos);
+        }
+
+        let (tag, len) = parse_len_line(line)?;
+        match tag {
+            b'K' => {
+                let key = read_counted(data, &mut pos, len)?;
+                let line = read_line(data, &mut pos).ok_or(HashError::Malformed)?;
+                let (tag, len) = parse_len_line(line)?;
+                if tag != b'V' {
+                    return Err(HashError::Malformed);
+                }
+                let value = read_counted(data, &mut pos, len)?;
+                hash.insert(key, value);
+            }
+            b'D' => {
+                let key = read_counted(data, &mut pos, len)?;
+                hash.remove(&key);
+            }
+            _ => return Err(HashError::Malformed),
+        }
+    }
+}
+
+/// Append an incremental entry for @a key to @a out: set it to @a value,
+/// or delete it if @a value is `None`.
+///
+/// `svn_hash_write_incremental`
+pub fn write_hash_incremental(out: &mut String, key: &str, value: Option<&str>) {
+    match value {
+        Some(value) => {
+            out.push_str(&format!(
+                "K {}\n{}\nV {}\n{}\n",
+                key.len(),
+                key,
+                value.len(),
+                value
+            ));
+        }
+        None => out.push_str(&format!("D {}\n{}\n", key.len(), key)),
+    }
+}
+
+fn read_line<'a>(data: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+    let rest = &data[*pos..];
+    let end = rest.iter().position(|&b| b == b'\n')?;
+    *pos += end + 1;
+    Some(&rest[..end])
+}
+
+fn parse_len_line(line: &[u8]) -> Result<(u8, usize), HashError> {
+    if line.len() < 3 || line[1] != b' ' {
+        return Err(HashError::Malformed);
+    }
+    let len = std::str::from_utf8(&line[2..])
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or(HashError::Malformed)?;
+    Ok((line[0], len))
+}
+
+fn read_counted(data: &[u8], pos: &mut usize, len: usize) -> Result<String, HashError> {
+    let end = *pos + len;
+    if end >= data.len() || data[end] != b'\n' {
+        return Err(HashError::Malformed);
+    }
+    let s = String::from_utf8_lossy(&data[*pos..end]).into_owned();
+    *pos = end + 1;
+    Ok(s)
+}
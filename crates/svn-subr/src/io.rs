@@ -161,6 +161,84 @@ pub fn write_atomic(
     Ok(())
 }
 
+/// Copy @a src to @a dst, including its permissions.  The contents are
+/// written to a temporary file next to @a dst first and then renamed, so
+/// that readers of @a dst never see a partial copy.
+///
+/// `svn_io_copy_file`
+pub fn copy_file(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
+    let temp_file_path = dst.with_file_name(format!(
+        "{}.tmp",
+        dst.file_name().unwrap().to_string_lossy()
+    ));
+
+    // Copying the contents carries the permissions over as well.
+    fs_err::copy(src, &temp_file_path)?;
+    fs_err::rename(temp_file_path, dst)?;
+
+    Ok(())
+}
+
+/// An advisory lock on a file, released when dropped.
+///
+/// `svn_io_file_lock2` / `svn_io_unlock_open_file`
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+}
+
+impl FileLock {
+    /// Lock the file at @a path, creating it if it does not exist.  If
+    /// @a exclusive is true, obtain an exclusive (write) lock, otherwise
+    /// a shared (read) lock.  Block until the lock is available.
+    ///
+    /// `svn_io_file_lock2`
+    pub fn lock(path: &Path, exclusive: bool) -> Result<Self, std::io::Error> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        if exclusive {
+            file.lock()?;
+        } else {
+            file.lock_shared()?;
+        }
+
+        Ok(Self { file })
+    }
+
+    /// Like [`FileLock::lock`], but return `None` instead of blocking if
+    /// the lock is held by someone else.
+    pub fn try_lock(path: &Path, exclusive: bool) -> Result<Option<Self>, std::io::Error> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let result = if exclusive {
+            file.try_lock()
+        } else {
+            file.try_lock_shared()
+        };
+        match result {
+            Ok(()) => Ok(Some(Self { file })),
+            Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+            Err(std::fs::TryLockError::Error(e)) => Err(e),
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
 /// Same as svn_io_dir_make(), but sets the hidden attribute on the
 ///     directory on systems that support it.
 /// `svn_io_dir_make_hidden`
@@ -279,6 +357,39 @@ pub fn detect_binary_mimetype(local_abspath: &Path) -> Result<Option<Mime>, std:
     Ok(None)
 }
 
+/// Examine up to @a len bytes of data in @a buf to determine if the
+/// content is likely to be of a binary nature.
+///
+/// If more than 85% of the bytes are outside of the ranges 0x07-0x0D and
+/// 0x20-0x7F, or any byte is 0x00, the data is considered binary. An empty
+/// UTF-8 file containing just the BOM is treated as plain text.
+///
+/// `svn_io_is_binary_data`
+pub fn is_binary_data(buf: &[u8]) -> bool {
+    if buf == [0xEF, 0xBB, 0xBF] {
+        return false;
+    }
+
+    if buf.is_empty() {
+        return false;
+    }
+
+    // If we see a 0x00 byte, we'll set our count to its max and stop
+    // reading the data.
+    let mut binary_count = 0;
+    for &b in buf {
+        if b == 0 {
+            binary_count = buf.len();
+            break;
+        }
+        if b < 0x07 || (b > 0x0D && b < 0x20) || b > 0x7F {
+            binary_count += 1;
+        }
+    }
+
+    (binary_count * 1000) / buf.len() > 850
+}
+
 pub fn check_special_path(path: &Path) -> Result<(NodeKind, bool), std::io::Error> {
     #[cfg(target_os = "windows")]
     {
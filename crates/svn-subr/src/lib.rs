@@ -1,5 +1,6 @@
 mod checksum;
 pub mod dirent_url;
+pub mod mergeinfo;
 pub mod opt;
 pub mod path;
 pub mod properties;
@@ -7,10 +8,15 @@ mod tristate;
 pub use tristate::Tristate;
 
 pub use checksum::Checksum;
+pub use checksum::ChecksumContext;
+pub use checksum::ChecksumError;
 pub use checksum::ChecksumKind;
+pub use checksum::{fnv1a_32, fnv1a_32x4};
 
 mod base64;
 
+pub mod compress;
+
 pub mod config;
 pub use config::SvnConfig;
 
@@ -19,8 +25,24 @@ pub mod user;
 
 mod cache;
 mod version;
+pub use cache::CacheError;
+pub use cache::CacheErrorHandler;
+pub use cache::CacheInfo;
+pub use cache::CacheKey;
+pub use cache::CachePriority;
+pub use cache::CacheStorage;
+pub use cache::DeserializeFn;
+pub use cache::InProcessCache;
 pub use cache::MemCache;
+pub use cache::Membuffer;
+pub use cache::MembufferCache;
+pub use cache::MemcacheCache;
+pub use cache::SerializeFn;
 pub use cache::SvnCache;
+pub use cache::config::CacheConfig;
+pub use cache::config::{cache_config_get, cache_config_set};
+pub use cache::get_global_membuffer_cache;
+pub use cache::make_memcache_from_config;
 
 pub use version::Version;
 
@@ -29,6 +51,8 @@ pub use uuid::{Uuid, uuid};
 
 pub mod io;
 
+pub mod time;
+
 pub mod hash;
 
 pub mod auth;
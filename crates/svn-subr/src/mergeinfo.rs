@@ -0,0 +1,258 @@
+//! `subversion/libsvn_subr/mergeinfo.c`
+//!
+//! Mergeinfo is stored in the `svn:mergeinfo` property as one line per
+//! merge source, e.g. `/trunk:3-5,7*`, where `*` marks ranges that are
+//! not inherited by the children of the node.
+
+use svn_types::MergeRange;
+use svn_types::Mergeinfo;
+use svn_types::Rangelist;
+use svn_types::RevisionNumber;
+
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+pub enum MergeinfoError {
+    #[error("Pathname not terminated by ':'")]
+    PathnameNotTerminated,
+
+    #[error("No pathname preceding ':'")]
+    NoPathname,
+
+    #[error("Mergeinfo for '{0}' maps to an empty revision range")]
+    EmptyRangelist(String),
+
+    #[error("Invalid revision number found parsing '{0}'")]
+    InvalidRevision(String),
+
+    #[error("Invalid revision number '0' found in range list")]
+    RevisionZero,
+
+    #[error("Unable to parse reversed revision range '{0}-{1}'")]
+    ReversedRange(RevisionNumber, RevisionNumber),
+
+    #[error("Unable to parse revision range '{0}-{1}' with same start and end revisions")]
+    SameStartEnd(RevisionNumber, RevisionNumber),
+
+    #[error(
+        "Unable to parse overlapping revision ranges '{0}' and '{1}' with different inheritance types"
+    )]
+    OverlappingRanges(String, String),
+}
+
+/// Parse the revision number @a token of a range list.
+fn parse_revision(token: &str) -> Result<RevisionNumber, MergeinfoError> {
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(MergeinfoError::InvalidRevision(token.to_string()));
+    }
+    match token.parse() {
+        Ok(0) => Err(MergeinfoError::RevisionZero),
+        Ok(rev) => Ok(rev),
+        Err(_) => Err(MergeinfoError::InvalidRevision(token.to_string())),
+    }
+}
+
+/// Parse one element of a range list, `N`, `N-M`, either optionally
+/// followed by `*`.
+///
+/// `parse_rangelist`
+fn parse_range(token: &str) -> Result<MergeRange, MergeinfoError> {
+    let (token, inheritable) = match token.strip_suffix('*') {
+        Some(token) => (token, false),
+        None => (token, true),
+    };
+
+    let (start, end) = match token.split_once('-') {
+        Some((first, last)) => {
+            let (first, last) = (parse_revision(first)?, parse_revision(last)?);
+            if first > last {
+                return Err(MergeinfoError::ReversedRange(first, last));
+            }
+            if first == last {
+                return Err(MergeinfoError::SameStartEnd(first, last));
+            }
+            (first - 1, last)
+        }
+        None => {
+            let rev = parse_revision(token)?;
+            (rev - 1, rev)
+        }
+    };
+
+    Ok(MergeRange {
+        start,
+        end,
+        inheritable,
+    })
+}
+
+/// Sort @a rangelist and combine adjacent or overlapping ranges with the
+/// same inheritability.
+///
+/// `svn_rangelist__canonicalize`
+fn canonicalize_rangelist(rangelist: &mut Rangelist) -> Result<(), MergeinfoError> {
+    rangelist.sort_by_key(|range| (range.start, range.end));
+
+    let mut combined: Rangelist = Vec::with_capacity(rangelist.len());
+    for range in rangelist.drain(..) {
+        if let Some(last) = combined.last_mut()
+            && last.start <= range.end
+            && range.start <= last.end
+        {
+            // The ranges are adjacent or intersect.
+            if range.start < last.end && range.inheritable != last.inheritable {
+                return Err(MergeinfoError::OverlappingRanges(
+                    range_to_string(last),
+                    range_to_string(&range),
+                ));
+            }
+
+            // Combine overlapping or adjacent ranges with the same
+            // inheritability.
+            if last.inheritable == range.inheritable {
+                last.end = last.end.max(range.end);
+                continue;
+            }
+        }
+        combined.push(range);
+    }
+    *rangelist = combined;
+
+    Ok(())
+}
+
+/// Parse the `svn:mergeinfo` property value @a input.  Paths are made
+/// absolute, and the ranges of each path are sorted and combined.
+///
+/// `svn_mergeinfo_parse`
+pub fn parse_mergeinfo(input: &str) -> Result<Mergeinfo, MergeinfoError> {
+    let mut mergeinfo = Mergeinfo::new();
+
+    for line in input.lines().filter(|line| !line.trim().is_empty()) {
+        // A pathname may contain colons, so find the last colon.
+        let (pathname, ranges) = line
+            .rsplit_once(':')
+            .ok_or(MergeinfoError::PathnameNotTerminated)?;
+        if pathname.is_empty() {
+            return Err(MergeinfoError::NoPathname);
+        }
+
+        // Tolerate relative repository paths, but convert them to
+        // absolute.
+        let mut path = String::new();
+        for component in pathname.split('/').filter(|c| !c.is_empty()) {
+            path.push('/');
+            path.push_str(component);
+        }
+        if path.is_empty() {
+            path.push('/');
+        }
+
+        let ranges = ranges.trim_end();
+        if ranges.is_empty() {
+            return Err(MergeinfoError::EmptyRangelist(path));
+        }
+        let rangelist = mergeinfo.entry(path).or_default();
+        for token in ranges.split(',') {
+            rangelist.push(parse_range(token)?);
+        }
+        canonicalize_rangelist(rangelist)?;
+    }
+
+    Ok(mergeinfo)
+}
+
+/// Return the textual form of @a range, as used in `svn:mergeinfo`.
+fn range_to_string(range: &MergeRange) -> String {
+    let suffix = if range.inheritable { "" } else { "*" };
+    if range.start + 1 == range.end {
+        format!("{}{suffix}", range.end)
+    } else {
+        format!("{}-{}{suffix}", range.start + 1, range.end)
+    }
+}
+
+/// Return the textual form of @a rangelist, e.g. `3-5,7*`.
+///
+/// `svn_rangelist_to_string`
+pub fn rangelist_to_string(rangelist: &Rangelist) -> String {
+    rangelist
+        .iter()
+        .map(range_to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Return the `svn:mergeinfo` property value for @a mergeinfo.
+///
+/// `svn_mergeinfo_to_string`
+pub fn mergeinfo_to_string(mergeinfo: &Mergeinfo) -> String {
+    mergeinfo
+        .iter()
+        .map(|(path, rangelist)| format!("{path}:{}", rangelist_to_string(rangelist)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Return @a mergeinfo without its non-inheritable ranges.  Paths left
+/// without ranges are dropped.
+///
+/// `svn_mergeinfo_inheritable2`
+pub fn mergeinfo_inheritable(mergeinfo: &Mergeinfo) -> Mergeinfo {
+    mergeinfo
+        .iter()
+        .filter_map(|(path, rangelist)| {
+            let inheritable: Rangelist = rangelist
+                .iter()
+                .filter(|range| range.inheritable)
+                .copied()
+                .collect();
+            (!inheritable.is_empty()).then(|| (path.clone(), inheritable))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mergeinfo() {
+        let mergeinfo = parse_mergeinfo("/trunk:7*,1-3,4\nbranch:2\n").unwrap();
+        assert_eq!(
+            mergeinfo["/trunk"],
+            [
+                MergeRange {
+                    start: 0,
+                    end: 4,
+                    inheritable: true
+                },
+                MergeRange {
+                    start: 6,
+                    end: 7,
+                    inheritable: false
+                },
+            ]
+        );
+        assert_eq!(mergeinfo_to_string(&mergeinfo), "/branch:2\n/trunk:1-4,7*");
+        assert_eq!(
+            mergeinfo_to_string(&mergeinfo_inheritable(
+                &parse_mergeinfo("/a:1*\n/b:2*,3").unwrap()
+            )),
+            "/b:3"
+        );
+        assert!(parse_mergeinfo("").unwrap().is_empty());
+
+        for bad in [
+            "/trunk",
+            ":1",
+            "/trunk:",
+            "/trunk:0",
+            "/trunk:3-1",
+            "/trunk:1-1",
+            "/trunk:x",
+        ] {
+            assert!(parse_mergeinfo(bad).is_err(), "{bad}");
+        }
+        assert!(parse_mergeinfo("/trunk:1-5,3*").is_err());
+    }
+}
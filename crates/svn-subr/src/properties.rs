@@ -1,5 +1,25 @@
 use std::collections::HashMap;
 
+/// The fs revision property that stores a commit's author.
+///
+/// `SVN_PROP_REVISION_AUTHOR`
+pub const SVN_PROP_REVISION_AUTHOR: &str = "svn:author";
+
+/// The fs revision property that stores a commit's log message.
+///
+/// `SVN_PROP_REVISION_LOG`
+pub const SVN_PROP_REVISION_LOG: &str = "svn:log";
+
+/// The fs revision property that stores a commit's date.
+///
+/// `SVN_PROP_REVISION_DATE`
+pub const SVN_PROP_REVISION_DATE: &str = "svn:date";
+
+/// The property recording the changes merged into a node.
+///
+/// `SVN_PROP_MERGEINFO`
+pub const SVN_PROP_MERGEINFO: &str = "svn:mergeinfo";
+
 pub fn has_svn_prop(props: &HashMap<String, String>) -> bool {
     if props.is_empty() {
         return false;
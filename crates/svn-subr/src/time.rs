@@ -0,0 +1,121 @@
+//! `subversion/libsvn_subr/time.c`
+//!
+//! Subversion stores timestamps as ISO-8601 strings in UTC with microsecond
+//! precision, e.g. `2002-05-03T01:45:49.508021Z`.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[allow(missing_docs)]
+#[derive(Debug, thiserror::Error)]
+pub enum TimeError {
+    #[error("Bad date: '{0}'")]
+    BadDate(String),
+}
+
+/// Convert @a when to a string in the format used by `svn:date`.
+///
+/// `svn_time_to_cstring`
+pub fn time_to_cstring(when: SystemTime) -> String {
+    let since_epoch = when.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs() as i64;
+    let micros = since_epoch.subsec_micros();
+
+    let days = secs.div_euclid(86400);
+    let secs_of_day = secs.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60,
+        micros
+    )
+}
+
+/// Convert @a data, a string as created by [`time_to_cstring`], back to a
+/// timestamp.
+///
+/// `svn_time_from_cstring`
+pub fn time_from_cstring(data: &str) -> Result<SystemTime, TimeError> {
+    let bad = || TimeError::BadDate(data.to_string());
+
+    let (date, time) = data.trim().split_once('T').ok_or_else(bad)?;
+    let time = time.strip_suffix('Z').ok_or_else(bad)?;
+
+    let mut date_parts = date.splitn(3, '-');
+    let mut next_date = || -> Result<i64, TimeError> {
+        date_parts
+            .next()
+            .ok_or_else(bad)?
+            .parse()
+            .map_err(|_| bad())
+    };
+    let (year, month, day) = (next_date()?, next_date()?, next_date()?);
+
+    let (hms, micros) = time.split_once('.').unwrap_or((time, "0"));
+    let mut time_parts = hms.splitn(3, ':');
+    let mut next_time = || -> Result<i64, TimeError> {
+        time_parts
+            .next()
+            .ok_or_else(bad)?
+            .parse()
+            .map_err(|_| bad())
+    };
+    let (hour, minute, second) = (next_time()?, next_time()?, next_time()?);
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return Err(bad());
+    }
+
+    // Pad or truncate the fraction to microseconds.
+    let micros: u32 = format!("{micros:0<6}")[..6].parse().map_err(|_| bad())?;
+
+    let secs = days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second;
+    if secs < 0 {
+        return Err(bad());
+    }
+
+    Ok(UNIX_EPOCH + Duration::new(secs as u64, micros * 1000))
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) triple.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Convert a (year, month, day) triple to days since 1970-01-01.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_roundtrip() {
+        let when = UNIX_EPOCH + Duration::new(1020390349, 508021000);
+        let s = time_to_cstring(when);
+        assert_eq!(s, "2002-05-03T01:45:49.508021Z");
+        assert_eq!(time_from_cstring(&s).unwrap(), when);
+    }
+}
@@ -21,6 +21,10 @@ pub use lock::SvnLock;
 
 mod merge;
 pub use merge::MergeRange;
+pub use merge::Mergeinfo;
+pub use merge::MergeinfoCatalog;
+pub use merge::MergeinfoInheritance;
+pub use merge::Rangelist;
 
 /// A revision number.
 ///
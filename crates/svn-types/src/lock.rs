@@ -15,21 +15,21 @@
 /// @note in the current implementation, only files are lockable.
 ///
 /// @from svn_types.h `svn_lock_t`
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SvnLock {
     /// the path this lock applies to
-    path: String,
+    pub path: String,
     /// unique URI representing lock
-    token: String,
+    pub token: String,
     /// the username which owns the lock
-    owner: String,
+    pub owner: String,
     /// (optional) description of lock
-    comment: Option<String>,
+    pub comment: Option<String>,
     /// was comment made by generic DAV client?
-    is_dav_comment: bool,
-    /// when lock was made
-    creation_date: i64,
+    pub is_dav_comment: bool,
+    /// when lock was made, in microseconds since the epoch
+    pub creation_date: i64,
     /// (optional) when lock will expire;
     /// If value is 0, lock will never expire.
-    expiration_date: i64,
+    pub expiration_date: i64,
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use crate::RevisionNumber;
 
 /// Mergeinfo representing a merge of a range of revisions.
@@ -5,16 +7,53 @@ use crate::RevisionNumber;
 /// @since New in 1.5
 ///
 /// `svn_merge_range_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MergeRange {
     /// If the 'start' field is less than the 'end' field then 'start' is
     /// exclusive and 'end' inclusive of the range described.  This is termed
     /// a forward merge range.  If 'start' is greater than 'end' then the
     /// opposite is true.  This is termed a reverse merge range.  If 'start'
     /// equals 'end' the meaning of the range is not defined.
-    start: RevisionNumber,
-    end: RevisionNumber,
+    pub start: RevisionNumber,
+    pub end: RevisionNumber,
 
     /// Whether this merge range should be inherited by treewise
     /// descendants of the path to which the range applies.
-    inheritable: bool,
+    pub inheritable: bool,
+}
+
+/// A list of merge ranges, sorted and without overlaps.
+///
+/// `svn_rangelist_t`
+pub type Rangelist = Vec<MergeRange>;
+
+/// A mapping of merge source paths to the ranges merged from them.
+///
+/// `svn_mergeinfo_t`
+pub type Mergeinfo = BTreeMap<String, Rangelist>;
+
+/// A mapping of repository paths to their mergeinfo.
+///
+/// `svn_mergeinfo_catalog_t`
+pub type MergeinfoCatalog = BTreeMap<String, Mergeinfo>;
+
+/// The three ways to request mergeinfo affecting a given path.
+///
+/// @since New in 1.5.
+///
+/// `svn_mergeinfo_inheritance_t`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeinfoInheritance {
+    /// Explicit mergeinfo only.
+    Explicit,
+
+    /// Explicit mergeinfo, or if that doesn't exist, the inherited
+    /// mergeinfo from a target's nearest (path-wise, not history-wise)
+    /// ancestor.
+    Inherited,
+
+    /// Mergeinfo inherited from a target's nearest (path-wise, not
+    /// history-wise) ancestor, regardless of whether target has explicit
+    /// mergeinfo.
+    NearestAncestor,
 }
@@ -26,5 +26,9 @@ pub fn is_binary(mime_type: &str) -> Result<bool, Error> {
     if mime.type_() == mime::TEXT {
         return Ok(false);
     }
+    // These image types are textual, even though they don't start with "text/".
+    if mime.type_() == mime::IMAGE && matches!(mime.subtype().as_str(), "x-xbitmap" | "x-xpixmap") {
+        return Ok(false);
+    }
     Ok(true)
 }